@@ -467,6 +467,11 @@ where
         }
     }
 
+    /// Get pool name.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
     /// Get pool limit.
     pub fn limit(&self) -> S {
         self.shared.limit.v
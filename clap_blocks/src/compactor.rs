@@ -194,6 +194,446 @@ pub struct CompactorConfig {
     )]
     pub max_num_files_per_plan: usize,
 
+    /// Multiple of the maximum compaction size that the L1 files of a
+    /// partition must exceed before they are compacted to L2 while L0
+    /// files are still backlogged.
+    ///
+    /// Compacting a large L1 backlog to L2 early simplifies the
+    /// remaining L0->L1 compactions, but can increase write
+    /// amplification on some workloads. Setting this very high
+    /// effectively disables the early L1->L2 compaction.
+    #[clap(
+        long = "compaction-early-l1-to-l2-size-multiplier",
+        env = "INFLUXDB_IOX_COMPACTION_EARLY_L1_TO_L2_SIZE_MULTIPLIER",
+        default_value = "3.0",
+        action
+    )]
+    pub early_l1_to_l2_size_multiplier: f64,
+
+    /// Allow L1 files to be compacted to L2 while L0 files are backlogged.
+    ///
+    /// Set to false on latency sensitive workloads, so compaction always
+    /// reduces the L0 files first, regardless of how many L1 bytes there
+    /// are.
+    #[clap(
+        long = "compaction-allow-early-l1-to-l2",
+        env = "INFLUXDB_IOX_COMPACTION_ALLOW_EARLY_L1_TO_L2",
+        default_value = "true",
+        action
+    )]
+    pub allow_early_l1_to_l2: bool,
+
+    /// Compact cold partitions straight to L2.
+    ///
+    /// When set, a partition whose files all fit in a single
+    /// compaction plan and that has not ingested any data for at
+    /// least this many seconds is compacted directly to L2 in one
+    /// round, rather than stepping through L0->L1->L2.
+    #[clap(
+        long = "compaction-cold-threshold-secs",
+        env = "INFLUXDB_IOX_COMPACTION_COLD_THRESHOLD_SECS",
+        action
+    )]
+    pub compaction_cold_threshold_secs: Option<u64>,
+
+    /// Align vertical split times to L2 file boundaries.
+    ///
+    /// When splitting highly overlapped L0 files, also use the time
+    /// boundaries of L2 files as split hints (L1 boundaries are always
+    /// used), so the subsequent L1->L2 compaction doesn't have to split
+    /// the resulting files again.
+    #[clap(
+        long = "compaction-vertical-split-l2-hints",
+        env = "INFLUXDB_IOX_COMPACTION_VERTICAL_SPLIT_L2_HINTS",
+        action
+    )]
+    pub vertical_split_l2_hints: bool,
+
+    /// Maximum number of split times used per vertical split round.
+    ///
+    /// Badly backlogged partitions can require hundreds of splits to
+    /// resolve their overlapping L0 files, which makes a single round
+    /// very expensive and starves other partitions. When set, each
+    /// round only performs the earliest splits, and the rest are done
+    /// in later rounds. Must be non-zero.
+    #[clap(
+        long = "compaction-max-split-times-per-round",
+        env = "INFLUXDB_IOX_COMPACTION_MAX_SPLIT_TIMES_PER_ROUND",
+        action
+    )]
+    pub max_split_times_per_round: Option<NonZeroUsize>,
+
+    /// Count overlaps per chain of L0 files when detecting many small files.
+    ///
+    /// By default the L1 files overlapping the whole time range of the L0
+    /// files are counted, which overestimates the overlaps when the L0s are
+    /// clustered into disjoint time ranges and causes needless compaction
+    /// rounds within L0. When set, the overlaps of each chain of overlapping
+    /// L0 files are counted separately.
+    #[clap(
+        long = "compaction-per-chain-overlaps",
+        env = "INFLUXDB_IOX_COMPACTION_PER_CHAIN_OVERLAPS",
+        action
+    )]
+    pub per_chain_overlaps: bool,
+
+    /// Do not compact files whose data is entirely outside the namespace
+    /// retention period.
+    ///
+    /// Such files are deleted by the garbage collector anyway, so compacting
+    /// them is wasted effort. Files straddling the retention cutoff are still
+    /// compacted.
+    #[clap(
+        long = "compaction-drop-expired-files",
+        env = "INFLUXDB_IOX_COMPACTION_DROP_EXPIRED_FILES",
+        action
+    )]
+    pub drop_expired_files: bool,
+
+    /// Memory budget for the uncompressed data of a single compaction plan.
+    ///
+    /// The compaction size limits only bound the (compressed) parquet bytes
+    /// of a plan, which for tables with many columns can expand to far more
+    /// memory than the executor has available. When set, the parquet size
+    /// limit of such partitions is reduced so the estimated uncompressed size
+    /// of each plan fits within this many bytes.
+    #[clap(
+        long = "compaction-max-estimated-arrow-bytes-per-plan",
+        env = "INFLUXDB_IOX_COMPACTION_MAX_ESTIMATED_ARROW_BYTES_PER_PLAN",
+        action
+    )]
+    pub max_estimated_arrow_bytes_per_plan: Option<usize>,
+
+    /// Estimated growth of uncompressed data over its parquet size, per
+    /// column of the table.
+    ///
+    /// The uncompressed size of a plan is estimated as its parquet size times
+    /// this value times the number of columns (but never less than the
+    /// parquet size). Only used when
+    /// `--compaction-max-estimated-arrow-bytes-per-plan` is set.
+    #[clap(
+        long = "compaction-arrow-expansion-per-column",
+        env = "INFLUXDB_IOX_COMPACTION_ARROW_EXPANSION_PER_COLUMN",
+        default_value = "0.05",
+        action
+    )]
+    pub arrow_expansion_per_column: f64,
+
+    /// Maximum number of branches compacted per round of a partition.
+    ///
+    /// A single round can otherwise produce dozens of branches, keeping the
+    /// compactor busy with one partition while others wait. When set, the
+    /// branches holding the oldest data are compacted first and the rest are
+    /// left for later rounds. Must be non-zero.
+    #[clap(
+        long = "compaction-max-branches-per-round",
+        env = "INFLUXDB_IOX_COMPACTION_MAX_BRANCHES_PER_ROUND",
+        action
+    )]
+    pub max_branches_per_round: Option<NonZeroUsize>,
+
+    /// Width, in seconds, of the L0 creation time windows used when compacting
+    /// many small files.
+    ///
+    /// When set, L0 files created in different windows are never compacted
+    /// together by a many small files round. Must be non-zero.
+    #[clap(
+        long = "compaction-many-small-files-window-secs",
+        env = "INFLUXDB_IOX_COMPACTION_MANY_SMALL_FILES_WINDOW_SECS",
+        action
+    )]
+    pub compaction_many_small_files_window_secs: Option<u64>,
+
+    /// Number of recent round decisions kept in memory.
+    ///
+    /// When set, the compactor records the type of round it chose for each
+    /// partition, along with the file counts per level and number of
+    /// branches, keeping only the most recent decisions. Useful to
+    /// reconstruct why a partition was compacted the way it was.
+    #[clap(
+        long = "compaction-round-decision-log-capacity",
+        env = "INFLUXDB_IOX_COMPACTION_ROUND_DECISION_LOG_CAPACITY",
+        action
+    )]
+    pub round_decision_log_capacity: Option<usize>,
+
+    /// Maximum number of times a partition may repeat the same round.
+    ///
+    /// When set, the compaction of a partition is aborted with an error once
+    /// the same type of round is chosen for the same set of files more than
+    /// this many times, rather than looping forever without making progress.
+    #[clap(
+        long = "compaction-max-repeated-rounds",
+        env = "INFLUXDB_IOX_COMPACTION_MAX_REPEATED_ROUNDS",
+        action
+    )]
+    pub max_repeated_rounds: Option<usize>,
+
+    /// Lower bound of the adaptive total size of files per plan.
+    ///
+    /// When both this and the ceiling are set, the total size of files
+    /// compacted in a plan is scaled by how much the data of the partition
+    /// shrank in its previous round, within these bounds. The first round of
+    /// a partition uses the static limit.
+    #[clap(
+        long = "compaction-adaptive-file-size-per-plan-floor-bytes",
+        env = "INFLUXDB_IOX_COMPACTION_ADAPTIVE_FILE_SIZE_PER_PLAN_FLOOR_BYTES",
+        action
+    )]
+    pub adaptive_file_size_per_plan_floor_bytes: Option<usize>,
+
+    /// Upper bound of the adaptive total size of files per plan.
+    ///
+    /// See `--compaction-adaptive-file-size-per-plan-floor-bytes`.
+    #[clap(
+        long = "compaction-adaptive-file-size-per-plan-ceiling-bytes",
+        env = "INFLUXDB_IOX_COMPACTION_ADAPTIVE_FILE_SIZE_PER_PLAN_CEILING_BYTES",
+        action
+    )]
+    pub adaptive_file_size_per_plan_ceiling_bytes: Option<usize>,
+
+    /// Maximum number of rows a plan should compact.
+    ///
+    /// Highly repetitive data compresses into small files with a lot of rows,
+    /// which use far more memory than their size suggests once decoded. When
+    /// set, L0 files averaging more rows than this limit allows per file are
+    /// not compacted together just to reduce their number.
+    #[clap(
+        long = "compaction-max-total-rows-per-plan",
+        env = "INFLUXDB_IOX_COMPACTION_MAX_TOTAL_ROWS_PER_PLAN",
+        action
+    )]
+    pub max_total_rows_per_plan: Option<usize>,
+
+    /// Width, in seconds, of the time buckets compacted separately.
+    ///
+    /// When set, files are compacted to the next level in one branch per
+    /// bucket of data time (e.g. 86400 for daily buckets), however many files
+    /// each bucket has, so output files line up with query time ranges. Must
+    /// be non-zero.
+    #[clap(
+        long = "compaction-time-bucket-secs",
+        env = "INFLUXDB_IOX_COMPACTION_TIME_BUCKET_SECS",
+        action
+    )]
+    pub compaction_time_bucket_secs: Option<u64>,
+
+    /// Maximum number of bytes staged in the scratchpad, across all partitions compacting at once.
+    ///
+    /// If not set, the scratchpad is unbounded. When set, files are no longer
+    /// prefetched while the previous branches of a partition compact, since
+    /// prefetched files would not count against the capacity.
+    #[clap(
+        long = "compaction-scratchpad-capacity-bytes",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_CAPACITY_BYTES",
+        action
+    )]
+    pub compaction_scratchpad_capacity_bytes: Option<usize>,
+
+    /// Wait for scratchpad space to be freed when files to stage exceed the remaining capacity.
+    ///
+    /// If false, the compaction of the partition fails with an out of memory error instead, and is
+    /// retried with fewer files. Only used if `--compaction-scratchpad-capacity-bytes` is set.
+    #[clap(
+        long = "compaction-scratchpad-block-when-full",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_BLOCK_WHEN_FULL",
+        default_value = "true",
+        action
+    )]
+    pub compaction_scratchpad_block_when_full: bool,
+
+    /// Maximum number of files copied into or out of the scratchpad at once, across all
+    /// partitions.
+    ///
+    /// This bounds the object store requests of large branches. If not set, only
+    /// `--compaction-partition-scratchpad-concurrency` limits the copies.
+    #[clap(
+        long = "compaction-scratchpad-max-concurrent-transfers",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_MAX_CONCURRENT_TRANSFERS",
+        action
+    )]
+    pub compaction_scratchpad_max_concurrent_transfers: Option<NonZeroUsize>,
+
+    /// Keep the files written by compaction in the scratchpad for the next round of the partition.
+    ///
+    /// This saves downloading them again for partitions needing several rounds, at the cost of
+    /// scratchpad memory.
+    #[clap(
+        long = "compaction-scratchpad-keep-written",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_KEEP_WRITTEN",
+        default_value = "false",
+        action
+    )]
+    pub compaction_scratchpad_keep_written: bool,
+
+    /// Read the smallest files of a branch directly from the object store, as long as they total
+    /// less than this many bytes, instead of copying them to the scratchpad.
+    ///
+    /// If not set, all files are copied to the scratchpad.
+    #[clap(
+        long = "compaction-scratchpad-read-through-bytes",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_READ_THROUGH_BYTES",
+        action
+    )]
+    pub compaction_scratchpad_read_through_bytes: Option<usize>,
+
+    /// Attempts to copy each file into or out of the scratchpad before failing the compaction of
+    /// the partition.
+    ///
+    /// If not set, copies are retried forever.
+    #[clap(
+        long = "compaction-scratchpad-transfer-max-attempts",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_TRANSFER_MAX_ATTEMPTS",
+        action
+    )]
+    pub compaction_scratchpad_transfer_max_attempts: Option<NonZeroUsize>,
+
+    /// Consecutive failed transfers into or out of the scratchpad after which the scratchpad is
+    /// bypassed for a while, reading the files to compact from the object store directly and
+    /// writing the compacted files to it.
+    ///
+    /// Only failures of the scratchpad store count. Requires
+    /// `--compaction-scratchpad-transfer-max-attempts`, since transfers retried forever never fail.
+    ///
+    /// If not set, the scratchpad is never bypassed.
+    #[clap(
+        long = "compaction-scratchpad-breaker-failures",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_BREAKER_FAILURES",
+        requires = "compaction_scratchpad_transfer_max_attempts",
+        action
+    )]
+    pub compaction_scratchpad_breaker_failures: Option<NonZeroUsize>,
+
+    /// Number of seconds the scratchpad is bypassed once
+    /// `--compaction-scratchpad-breaker-failures` is reached, before staging files is tried again.
+    #[clap(
+        long = "compaction-scratchpad-breaker-cooldown-secs",
+        env = "INFLUXDB_IOX_COMPACTION_SCRATCHPAD_BREAKER_COOLDOWN_SECS",
+        default_value = "60",
+        action
+    )]
+    pub compaction_scratchpad_breaker_cooldown_secs: u64,
+
+    /// Number of seconds the files of a partition are cached, instead of fetching them from the
+    /// catalog for every round.
+    ///
+    /// The files of a partition are always fetched again after the compactor changed them.
+    ///
+    /// If not set, files are not cached.
+    #[clap(
+        long = "compaction-partition-files-cache-ttl-secs",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_CACHE_TTL_SECS",
+        action
+    )]
+    pub compaction_partition_files_cache_ttl_secs: Option<u64>,
+
+    /// Maximum number of partitions whose files are cached, see
+    /// `--compaction-partition-files-cache-ttl-secs`.
+    #[clap(
+        long = "compaction-partition-files-cache-max-entries",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_CACHE_MAX_ENTRIES",
+        default_value = "10000",
+        action
+    )]
+    pub compaction_partition_files_cache_max_entries: NonZeroUsize,
+
+    /// Give up fetching the files of a partition from the catalog after this many seconds,
+    /// retries included. The partition is skipped and compacted again later.
+    ///
+    /// If not set, fetches are retried forever.
+    #[clap(
+        long = "compaction-partition-files-fetch-timeout-secs",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_FETCH_TIMEOUT_SECS",
+        action
+    )]
+    pub compaction_partition_files_fetch_timeout_secs: Option<u64>,
+
+    /// Maximum number of partitions whose files are fetched from the catalog in a single query,
+    /// when fetching the files of many partitions at once.
+    #[clap(
+        long = "compaction-max-partitions-per-fetch-query",
+        env = "INFLUXDB_IOX_COMPACTION_MAX_PARTITIONS_PER_FETCH_QUERY",
+        default_value = "100",
+        action
+    )]
+    pub max_partitions_per_fetch_query: NonZeroUsize,
+
+    /// Drop files at the final level larger than this many bytes, which overlap no other file of
+    /// their partition, right when the files of a partition are fetched.
+    ///
+    /// If not set, no files are dropped.
+    #[clap(
+        long = "compaction-partition-files-filter-max-file-size-bytes",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_FILTER_MAX_FILE_SIZE_BYTES",
+        action
+    )]
+    pub compaction_partition_files_filter_max_file_size_bytes: Option<u64>,
+
+    /// For this many seconds after a commit, the files of the partition committed to reflect the
+    /// commit, even if the catalog still returns the files as they were before it.
+    ///
+    /// If not set, the files are taken from the catalog as is.
+    #[clap(
+        long = "compaction-partition-files-commit-window-secs",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_COMMIT_WINDOW_SECS",
+        action
+    )]
+    pub compaction_partition_files_commit_window_secs: Option<u64>,
+
+    /// Minimum number of seconds between two fetch queries for the files of the same partition.
+    ///
+    /// Only used if `--max-partition-fetch-queries-per-second` is set.
+    #[clap(
+        long = "compaction-partition-fetch-min-interval-secs",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FETCH_MIN_INTERVAL_SECS",
+        action
+    )]
+    pub compaction_partition_fetch_min_interval_secs: Option<u64>,
+
+    /// Number of files of a partition fetched from the catalog per query.
+    ///
+    /// If not set, all files of a partition are fetched with a single query.
+    #[clap(
+        long = "compaction-partition-files-page-size",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_PAGE_SIZE",
+        action
+    )]
+    pub compaction_partition_files_page_size: Option<NonZeroUsize>,
+
+    /// Maximum number of L0 files compacted for a partition. Only the oldest L0 files (by
+    /// `max_l0_created_at`) of a partition with more L0 files are compacted, together with all its
+    /// L1 and L2 files.
+    ///
+    /// If not set, all files of a partition are compacted.
+    #[clap(
+        long = "compaction-partition-files-max-files",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_MAX_FILES",
+        action
+    )]
+    pub compaction_partition_files_max_files: Option<NonZeroUsize>,
+
+    /// Prefetch the files of the partitions of fetched compaction jobs in the background, and use
+    /// the prefetched files for this many seconds.
+    ///
+    /// If not set, files are fetched once the compaction of a partition starts.
+    #[clap(
+        long = "compaction-partition-files-prefetch-ttl-secs",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_PREFETCH_TTL_SECS",
+        action
+    )]
+    pub compaction_partition_files_prefetch_ttl_secs: Option<u64>,
+
+    /// Maximum number of batches of fetched compaction jobs whose files are prefetched at a time,
+    /// see `--compaction-partition-files-prefetch-ttl-secs`. The files of the partitions of a
+    /// batch are fetched together.
+    #[clap(
+        long = "compaction-partition-files-prefetch-concurrency",
+        env = "INFLUXDB_IOX_COMPACTION_PARTITION_FILES_PREFETCH_CONCURRENCY",
+        default_value = "10",
+        action
+    )]
+    pub compaction_partition_files_prefetch_concurrency: NonZeroUsize,
+
     /// Minimum number of L1 files to compact to L2.
     ///
     /// If there are more than this many L1 (by definition non
@@ -247,3 +687,91 @@ pub struct CompactorConfig {
     )]
     pub max_partition_fetch_queries_per_second: Option<usize>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use test_helpers::assert_contains;
+
+    #[test]
+    fn max_split_times_per_round() {
+        let config = CompactorConfig::try_parse_from(["my_binary"]).unwrap();
+        assert_eq!(config.max_split_times_per_round, None);
+
+        let config = CompactorConfig::try_parse_from([
+            "my_binary",
+            "--compaction-max-split-times-per-round",
+            "5",
+        ])
+        .unwrap();
+        assert_eq!(config.max_split_times_per_round, NonZeroUsize::new(5));
+
+        let error = CompactorConfig::try_parse_from([
+            "my_binary",
+            "--compaction-max-split-times-per-round",
+            "0",
+        ])
+        .unwrap_err()
+        .to_string();
+        assert_contains!(
+            &error,
+            "invalid value '0' for '--compaction-max-split-times-per-round"
+        );
+    }
+
+    #[test]
+    fn max_branches_per_round() {
+        let config = CompactorConfig::try_parse_from(["my_binary"]).unwrap();
+        assert_eq!(config.max_branches_per_round, None);
+
+        let config = CompactorConfig::try_parse_from([
+            "my_binary",
+            "--compaction-max-branches-per-round",
+            "3",
+        ])
+        .unwrap();
+        assert_eq!(config.max_branches_per_round, NonZeroUsize::new(3));
+
+        // 0 would leave every branch for later, so the partition never makes progress
+        let error = CompactorConfig::try_parse_from([
+            "my_binary",
+            "--compaction-max-branches-per-round",
+            "0",
+        ])
+        .unwrap_err()
+        .to_string();
+        assert_contains!(
+            &error,
+            "invalid value '0' for '--compaction-max-branches-per-round"
+        );
+    }
+
+    #[test]
+    fn scratchpad_breaker_requires_max_attempts() {
+        let config = CompactorConfig::try_parse_from([
+            "my_binary",
+            "--compaction-scratchpad-breaker-failures",
+            "3",
+            "--compaction-scratchpad-transfer-max-attempts",
+            "2",
+        ])
+        .unwrap();
+        assert_eq!(
+            config.compaction_scratchpad_breaker_failures,
+            NonZeroUsize::new(3)
+        );
+
+        let error = CompactorConfig::try_parse_from([
+            "my_binary",
+            "--compaction-scratchpad-breaker-failures",
+            "3",
+        ])
+        .unwrap_err()
+        .to_string();
+        assert_contains!(
+            &error,
+            "--compaction-scratchpad-transfer-max-attempts <COMPACTION_SCRATCHPAD_TRANSFER_MAX_ATTEMPTS>"
+        );
+    }
+}
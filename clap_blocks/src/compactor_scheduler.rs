@@ -1,5 +1,7 @@
 //! Compactor-Scheduler-related configs.
 
+use std::num::{NonZeroU64, NonZeroUsize};
+
 /// Compaction Scheduler type.
 #[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
 pub enum CompactorSchedulerType {
@@ -11,6 +13,17 @@ pub enum CompactorSchedulerType {
     Remote,
 }
 
+/// What to do with commits that exceed the commit limits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum CommitLimitPolicy {
+    /// Fail the commit.
+    #[default]
+    Reject,
+
+    /// Split the commit into several catalog transactions within the limits.
+    Split,
+}
+
 /// CLI config for compactor scheduler.
 #[derive(Debug, Clone, Default, clap::Parser)]
 pub struct ShardConfigForLocalScheduler {
@@ -110,6 +123,60 @@ pub struct CompactorSchedulerConfig {
     /// Shard config used by the local scheduler.
     #[clap(flatten)]
     pub shard_config: ShardConfigForLocalScheduler,
+
+    /// Maximum number of attempts to commit a change to the catalog.
+    ///
+    /// Transient catalog errors are retried with a backoff until the commit succeeds or this
+    /// many attempts were made. If not set, retries forever.
+    #[clap(
+        long = "compaction-commit-max-attempts",
+        env = "INFLUXDB_IOX_COMPACTION_COMMIT_MAX_ATTEMPTS",
+        action
+    )]
+    pub commit_max_attempts: Option<NonZeroUsize>,
+
+    /// Maximum number of files a single commit may delete.
+    ///
+    /// See `--compaction-commit-limit-policy` for what happens to commits exceeding it. If not
+    /// set, the number is not limited.
+    #[clap(
+        long = "compaction-commit-max-deletes",
+        env = "INFLUXDB_IOX_COMPACTION_COMMIT_MAX_DELETES",
+        action
+    )]
+    pub commit_max_deletes: Option<NonZeroUsize>,
+
+    /// Maximum number of files a single commit may create.
+    ///
+    /// See `--compaction-commit-limit-policy` for what happens to commits exceeding it. If not
+    /// set, the number is not limited.
+    #[clap(
+        long = "compaction-commit-max-creates",
+        env = "INFLUXDB_IOX_COMPACTION_COMMIT_MAX_CREATES",
+        action
+    )]
+    pub commit_max_creates: Option<NonZeroUsize>,
+
+    /// Maximum total size in bytes of the files a single commit may create.
+    ///
+    /// See `--compaction-commit-limit-policy` for what happens to commits exceeding it. If not
+    /// set, the size is not limited.
+    #[clap(
+        long = "compaction-commit-max-create-bytes",
+        env = "INFLUXDB_IOX_COMPACTION_COMMIT_MAX_CREATE_BYTES",
+        action
+    )]
+    pub commit_max_create_bytes: Option<NonZeroU64>,
+
+    /// What to do with commits exceeding the commit limits.
+    #[clap(
+        value_enum,
+        long = "compaction-commit-limit-policy",
+        env = "INFLUXDB_IOX_COMPACTION_COMMIT_LIMIT_POLICY",
+        default_value = "reject",
+        action
+    )]
+    pub commit_limit_policy: CommitLimitPolicy,
 }
 
 #[cfg(test)]
@@ -156,4 +223,22 @@ mod tests {
         );
         assert_contains!(&error, "[possible values: local, remote]");
     }
+
+    #[test]
+    fn commit_limits() {
+        let config = CompactorSchedulerConfig::try_parse_from(["my_binary"]).unwrap();
+        assert_eq!(config.commit_max_creates, None);
+        assert_eq!(config.commit_limit_policy, CommitLimitPolicy::Reject);
+
+        let config = CompactorSchedulerConfig::try_parse_from([
+            "my_binary",
+            "--compaction-commit-max-creates",
+            "1000",
+            "--compaction-commit-limit-policy",
+            "split",
+        ])
+        .unwrap();
+        assert_eq!(config.commit_max_creates, NonZeroUsize::new(1000));
+        assert_eq!(config.commit_limit_policy, CommitLimitPolicy::Split);
+    }
 }
@@ -5,7 +5,7 @@ use crate::{
     memory_size::MemorySize,
     single_tenant::{CONFIG_AUTHZ_ENV_NAME, CONFIG_AUTHZ_FLAG},
 };
-use std::{collections::HashMap, num::NonZeroUsize};
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
 
 /// CLI config for querier configuration
 #[derive(Debug, Clone, PartialEq, Eq, clap::Parser)]
@@ -79,6 +79,50 @@ pub struct QuerierConfig {
     )]
     pub ram_pool_data_bytes: MemorySize,
 
+    /// How long existing namespaces are kept in the namespace cache, e.g. "5m".
+    #[clap(
+        long = "namespace-cache-ttl",
+        env = "INFLUXDB_IOX_NAMESPACE_CACHE_TTL",
+        default_value = "5m",
+        value_parser = humantime::parse_duration,
+        action
+    )]
+    pub namespace_cache_ttl: Duration,
+
+    /// Initial interval after which cached namespaces are refreshed from the catalog, e.g. "30s".
+    ///
+    /// The interval grows exponentially for namespaces that are refreshed repeatedly.
+    #[clap(
+        long = "namespace-cache-refresh-interval",
+        env = "INFLUXDB_IOX_NAMESPACE_CACHE_REFRESH_INTERVAL",
+        default_value = "30s",
+        value_parser = humantime::parse_duration,
+        action
+    )]
+    pub namespace_cache_refresh_interval: Duration,
+
+    /// Namespaces that are loaded into the namespace cache at startup, e.g. "ns1,ns2".
+    ///
+    /// This avoids that the first query of each namespace pays for loading its schema.
+    #[clap(
+        long = "warm-namespaces",
+        env = "INFLUXDB_IOX_WARM_NAMESPACES",
+        required = false,
+        num_args = 0..,
+        value_delimiter = ','
+    )]
+    pub warm_namespaces: Vec<String>,
+
+    /// Load all namespaces into the namespace cache at startup.
+    ///
+    /// Takes precedence over `--warm-namespaces`.
+    #[clap(
+        long = "warm-all-namespaces",
+        env = "INFLUXDB_IOX_WARM_ALL_NAMESPACES",
+        action
+    )]
+    pub warm_all_namespaces: bool,
+
     /// Limit the number of concurrent queries.
     #[clap(
         long = "max-concurrent-queries",
@@ -88,6 +132,95 @@ pub struct QuerierConfig {
     )]
     pub max_concurrent_queries: usize,
 
+    /// Number of entries kept in the query log, shared by all namespaces.
+    ///
+    /// The log backs the `system.queries` table. Once it is full, the oldest entries are evicted.
+    #[clap(
+        long = "query-log-size",
+        env = "INFLUXDB_IOX_QUERY_LOG_SIZE",
+        default_value = "10000",
+        action
+    )]
+    pub query_log_size: usize,
+
+    /// Maximum age of the entries in the query log, e.g. "1h".
+    ///
+    /// Entries are dropped once their query was issued longer ago than this. Queries that are still
+    /// running stay in the log until they complete. If not specified, entries are only evicted
+    /// once the log is full.
+    #[clap(
+        long = "query-log-max-age",
+        env = "INFLUXDB_IOX_QUERY_LOG_MAX_AGE",
+        value_parser = humantime::parse_duration,
+        action
+    )]
+    pub query_log_max_age: Option<Duration>,
+
+    /// Maximum number of characters of the query text recorded in the query log.
+    ///
+    /// Longer query texts are truncated. If not specified, the full text is recorded.
+    #[clap(
+        long = "query-log-max-text-length",
+        env = "INFLUXDB_IOX_QUERY_LOG_MAX_TEXT_LENGTH",
+        action
+    )]
+    pub query_log_max_text_length: Option<usize>,
+
+    /// Replace query texts in the query log with a hash of the full text.
+    ///
+    /// The first `--query-log-max-text-length` characters are kept, if set.
+    #[clap(
+        long = "query-log-redact-text",
+        env = "INFLUXDB_IOX_QUERY_LOG_REDACT_TEXT",
+        action
+    )]
+    pub query_log_redact_text: bool,
+
+    /// System tables that are listed for every query, e.g. "queries,compactions".
+    ///
+    /// By default, the "queries", "parquet_files" and "compactions" system tables are only listed
+    /// when the query requests debug info, and all other system tables are never listed.
+    #[clap(
+        long = "public-system-tables",
+        env = "INFLUXDB_IOX_PUBLIC_SYSTEM_TABLES",
+        required = false,
+        num_args = 0..,
+        value_delimiter = ','
+    )]
+    pub public_system_tables: Vec<String>,
+
+    /// System tables that are listed when the query requests debug info, e.g. "caches,ingesters".
+    ///
+    /// Takes precedence over `--public-system-tables`.
+    #[clap(
+        long = "debug-system-tables",
+        env = "INFLUXDB_IOX_DEBUG_SYSTEM_TABLES",
+        required = false,
+        num_args = 0..,
+        value_delimiter = ','
+    )]
+    pub debug_system_tables: Vec<String>,
+
+    /// System tables that are never listed, not even when the query requests debug info.
+    ///
+    /// Takes precedence over `--public-system-tables` and `--debug-system-tables`.
+    #[clap(
+        long = "hidden-system-tables",
+        env = "INFLUXDB_IOX_HIDDEN_SYSTEM_TABLES",
+        required = false,
+        num_args = 0..,
+        value_delimiter = ','
+    )]
+    pub hidden_system_tables: Vec<String>,
+
+    /// Namespace whose `system.queries` table lists the queries of all namespaces, including their
+    /// namespace ID.
+    ///
+    /// Access to this namespace must be restricted to operators. If not specified, every
+    /// `system.queries` table only lists the queries of its own namespace.
+    #[clap(long = "admin-namespace", env = "INFLUXDB_IOX_ADMIN_NAMESPACE", action)]
+    pub admin_namespace: Option<String>,
+
     /// After how many ingester query errors should the querier enter circuit breaker mode?
     ///
     /// The querier normally contacts the ingester for any unpersisted data during query planning.
@@ -166,6 +299,106 @@ mod tests {
         assert_eq!(actual.num_query_threads, None);
         assert!(actual.ingester_addresses.is_empty());
         assert!(actual.datafusion_config.is_empty());
+        assert_eq!(actual.namespace_cache_ttl, Duration::from_secs(300));
+        assert_eq!(
+            actual.namespace_cache_refresh_interval,
+            Duration::from_secs(30)
+        );
+        assert!(actual.warm_namespaces.is_empty());
+        assert!(!actual.warm_all_namespaces);
+        assert_eq!(actual.query_log_size, 10_000);
+        assert_eq!(actual.query_log_max_age, None);
+        assert_eq!(actual.query_log_max_text_length, None);
+        assert!(!actual.query_log_redact_text);
+        assert!(actual.public_system_tables.is_empty());
+        assert!(actual.debug_system_tables.is_empty());
+        assert!(actual.hidden_system_tables.is_empty());
+        assert_eq!(actual.admin_namespace, None);
+    }
+
+    #[test]
+    fn test_query_log_size() {
+        let actual =
+            QuerierConfig::try_parse_from(["my_binary", "--query-log-size", "42"]).unwrap();
+
+        assert_eq!(actual.query_log_size, 42);
+    }
+
+    #[test]
+    fn test_namespace_cache() {
+        let actual = QuerierConfig::try_parse_from([
+            "my_binary",
+            "--namespace-cache-ttl",
+            "1m",
+            "--namespace-cache-refresh-interval",
+            "10s",
+        ])
+        .unwrap();
+
+        assert_eq!(actual.namespace_cache_ttl, Duration::from_secs(60));
+        assert_eq!(
+            actual.namespace_cache_refresh_interval,
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn test_warm_namespaces() {
+        let actual =
+            QuerierConfig::try_parse_from(["my_binary", "--warm-namespaces", "ns1,ns2"]).unwrap();
+
+        assert_eq!(actual.warm_namespaces, vec!["ns1", "ns2"]);
+        assert!(!actual.warm_all_namespaces);
+
+        let actual = QuerierConfig::try_parse_from(["my_binary", "--warm-all-namespaces"]).unwrap();
+
+        assert!(actual.warm_namespaces.is_empty());
+        assert!(actual.warm_all_namespaces);
+    }
+
+    #[test]
+    fn test_query_log_max_age() {
+        let actual =
+            QuerierConfig::try_parse_from(["my_binary", "--query-log-max-age", "1h"]).unwrap();
+
+        assert_eq!(actual.query_log_max_age, Some(Duration::from_secs(60 * 60)));
+    }
+
+    #[test]
+    fn test_query_log_text() {
+        let actual = QuerierConfig::try_parse_from([
+            "my_binary",
+            "--query-log-max-text-length",
+            "100",
+            "--query-log-redact-text",
+        ])
+        .unwrap();
+
+        assert_eq!(actual.query_log_max_text_length, Some(100));
+        assert!(actual.query_log_redact_text);
+    }
+
+    #[test]
+    fn test_system_tables() {
+        let actual = QuerierConfig::try_parse_from([
+            "my_binary",
+            "--public-system-tables",
+            "queries,compactions",
+            "--debug-system-tables",
+            "ingesters",
+            "--hidden-system-tables",
+            "caches",
+        ])
+        .unwrap();
+
+        assert_eq!(actual.public_system_tables, vec!["queries", "compactions"]);
+        assert_eq!(actual.debug_system_tables, vec!["ingesters"]);
+        assert_eq!(actual.hidden_system_tables, vec!["caches"]);
+
+        let actual =
+            QuerierConfig::try_parse_from(["my_binary", "--admin-namespace", "ops"]).unwrap();
+
+        assert_eq!(actual.admin_namespace.as_deref(), Some("ops"));
     }
 
     #[test]
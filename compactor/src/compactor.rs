@@ -13,7 +13,9 @@ use tracker::AsyncSemaphoreMetrics;
 use crate::{
     components::{
         hardcoded::hardcoded_components,
+        partition_files_source::rate_limit::RateLimitConfig,
         report::{log_components, log_config},
+        round_info_source::decision_log::RoundDecisionLog,
     },
     config::Config,
     driver::compact,
@@ -32,6 +34,8 @@ fn shared_handle(handle: JoinHandle<()>) -> SharedJoinHandle {
 pub struct Compactor {
     shutdown: CancellationToken,
     worker: SharedJoinHandle,
+    round_decision_log: Option<Arc<RoundDecisionLog>>,
+    partition_fetch_rate_limit: Option<Arc<RateLimitConfig>>,
 }
 
 impl Compactor {
@@ -42,6 +46,8 @@ impl Compactor {
 
         let shutdown = CancellationToken::new();
         let shutdown_captured = shutdown.clone();
+        let round_decision_log = config.round_decision_log.clone();
+        let partition_fetch_rate_limit = config.partition_fetch_rate_limit.clone();
 
         let components = hardcoded_components(&config);
         log_components(&components);
@@ -70,7 +76,24 @@ impl Compactor {
         });
         let worker = shared_handle(worker);
 
-        Self { shutdown, worker }
+        Self {
+            shutdown,
+            worker,
+            round_decision_log,
+            partition_fetch_rate_limit,
+        }
+    }
+
+    /// The most recent round decisions, if the compactor was configured to record them.
+    pub fn round_decision_log(&self) -> Option<&Arc<RoundDecisionLog>> {
+        self.round_decision_log.as_ref()
+    }
+
+    /// The rate limit of partition file fetches, if the compactor was configured with one.
+    ///
+    /// Changing it takes effect immediately, e.g. to go easy on the catalog during an incident.
+    pub fn partition_fetch_rate_limit(&self) -> Option<&Arc<RateLimitConfig>> {
+        self.partition_fetch_rate_limit.as_ref()
     }
 
     /// Trigger shutdown. You should [join](Self::join) afterwards.
@@ -4,16 +4,54 @@ use compactor_scheduler::{
     CommitUpdate, CompactionJob, CompactionJobStatus, CompactionJobStatusResponse,
     CompactionJobStatusVariant, Scheduler,
 };
-use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams};
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams};
+
+use super::partition_files_source::{
+    caching::PartitionFilesInvalidator, commit_aware::RecentCommits,
+    prefetching::PartitionFilesPrefetcher,
+};
 
 #[derive(Debug)]
 pub struct CommitToScheduler {
     scheduler: Arc<dyn Scheduler>,
+    partition_files_invalidator: Option<PartitionFilesInvalidator>,
+    recent_commits: Option<RecentCommits>,
+    partition_files_prefetcher: Option<PartitionFilesPrefetcher>,
 }
 
 impl CommitToScheduler {
     pub fn new(scheduler: Arc<dyn Scheduler>) -> Self {
-        Self { scheduler }
+        Self {
+            scheduler,
+            partition_files_invalidator: None,
+            recent_commits: None,
+            partition_files_prefetcher: None,
+        }
+    }
+
+    /// Record the successful commits, so that the files of the partitions committed to reflect
+    /// them even if the catalog lags behind.
+    pub fn with_recent_commits(mut self, recent_commits: RecentCommits) -> Self {
+        self.recent_commits = Some(recent_commits);
+        self
+    }
+
+    /// Forget the cached files of the partitions committed to.
+    pub fn with_partition_files_invalidator(
+        mut self,
+        partition_files_invalidator: PartitionFilesInvalidator,
+    ) -> Self {
+        self.partition_files_invalidator = Some(partition_files_invalidator);
+        self
+    }
+
+    /// Forget the prefetched files of the partitions committed to.
+    pub fn with_partition_files_prefetcher(
+        mut self,
+        partition_files_prefetcher: PartitionFilesPrefetcher,
+    ) -> Self {
+        self.partition_files_prefetcher = Some(partition_files_prefetcher);
+        self
     }
 
     pub async fn commit(
@@ -23,8 +61,9 @@ impl CommitToScheduler {
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, crate::DynError> {
-        match self
+    ) -> Result<Vec<ParquetFile>, crate::DynError> {
+        let partition_id = job.partition_id;
+        let res = self
             .scheduler
             .update_job_status(CompactionJobStatus {
                 job: job.clone(),
@@ -36,11 +75,34 @@ impl CommitToScheduler {
                     target_level,
                 )),
             })
-            .await?
-        {
-            CompactionJobStatusResponse::CreatedParquetFiles(ids) => Ok(ids),
+            .await;
+
+        // the files may have changed even if the commit failed
+        if let Some(partition_files_invalidator) = &self.partition_files_invalidator {
+            partition_files_invalidator.invalidate(partition_id);
+        }
+        if let Some(partition_files_prefetcher) = &self.partition_files_prefetcher {
+            partition_files_prefetcher.invalidate(partition_id);
+        }
+
+        let created = match res? {
+            CompactionJobStatusResponse::CreatedParquetFiles(created) => created,
             CompactionJobStatusResponse::Ack => unreachable!("scheduler should not ack"),
+        };
+
+        if let Some(recent_commits) = &self.recent_commits {
+            let upgraded = upgrade.iter().map(|file| ParquetFile {
+                compaction_level: target_level,
+                ..file.clone()
+            });
+            recent_commits.record(
+                partition_id,
+                delete.iter().map(|file| file.id),
+                created.iter().cloned().chain(upgraded),
+            );
         }
+
+        Ok(created)
     }
 }
 
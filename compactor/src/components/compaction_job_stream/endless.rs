@@ -54,7 +54,7 @@ where
                     while let Some(d) = self.limiter.can_proceed() {
                         // Throttling because either we don't need to go this fast, or we're at risk of hitting the catalog
                         // to hard, or both.
-                        tokio::time::sleep(d).await;
+                        self.limiter.wait(d).await;
                     }
 
                     if let Some(p_id) = buffer.pop_front() {
@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use compactor_scheduler::CompactionJob;
+
+use crate::components::partition_files_source::prefetching::PartitionFilesPrefetcher;
+
+use super::CompactionJobsSource;
+
+/// Hints the partitions of the fetched jobs as upcoming, so that their files are prefetched while
+/// the jobs before them are compacted.
+#[derive(Debug)]
+pub struct PrefetchCompactionJobsSourceWrapper<T>
+where
+    T: CompactionJobsSource,
+{
+    inner: T,
+    prefetcher: PartitionFilesPrefetcher,
+}
+
+impl<T> PrefetchCompactionJobsSourceWrapper<T>
+where
+    T: CompactionJobsSource,
+{
+    pub fn new(inner: T, prefetcher: PartitionFilesPrefetcher) -> Self {
+        Self { inner, prefetcher }
+    }
+}
+
+impl<T> Display for PrefetchCompactionJobsSourceWrapper<T>
+where
+    T: CompactionJobsSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "prefetch({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> CompactionJobsSource for PrefetchCompactionJobsSourceWrapper<T>
+where
+    T: CompactionJobsSource,
+{
+    async fn fetch(&self) -> Vec<CompactionJob> {
+        let compaction_jobs = self.inner.fetch().await;
+        let partitions: Vec<_> = compaction_jobs.iter().map(|job| job.partition_id).collect();
+        self.prefetcher.hint_upcoming(&partitions);
+        compaction_jobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+
+    use data_types::PartitionId;
+    use iox_tests::{partition_identifier, ParquetFileBuilder, PartitionBuilder};
+    use iox_time::SystemProvider;
+
+    use crate::components::partition_files_source::{
+        mock::MockPartitionFilesSource, prefetching::PrefetchingPartitionFilesSource,
+        PartitionFilesSource,
+    };
+
+    use super::{super::mock::MockCompactionJobsSource, *};
+
+    fn files_source() -> PrefetchingPartitionFilesSource<Arc<MockPartitionFilesSource>> {
+        let inner = MockPartitionFilesSource::new(
+            [(PartitionId::new(1), partition_identifier(1))].into(),
+            vec![ParquetFileBuilder::new(1)
+                .with_partition(partition_identifier(1))
+                .build()],
+        )
+        .with_partitions([PartitionBuilder::new(1).build()]);
+        PrefetchingPartitionFilesSource::new(
+            Arc::new(inner),
+            Duration::from_secs(60),
+            NonZeroUsize::new(2).unwrap(),
+            Arc::new(SystemProvider::new()),
+        )
+    }
+
+    #[test]
+    fn test_display() {
+        let source = PrefetchCompactionJobsSourceWrapper::new(
+            MockCompactionJobsSource::new(vec![]),
+            files_source().prefetcher(),
+        );
+        assert_eq!(source.to_string(), "prefetch(mock)");
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        let files_source = files_source();
+        let prefetcher = files_source.prefetcher();
+        let jobs = vec![CompactionJob::new(PartitionId::new(1))];
+        let source = PrefetchCompactionJobsSourceWrapper::new(
+            MockCompactionJobsSource::new(jobs.clone()),
+            prefetcher.clone(),
+        );
+
+        assert_eq!(source.fetch().await, jobs);
+
+        // the files of the partition are prefetched
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while prefetcher.ready() < 1 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("prefetched in time");
+        let partition_files = files_source
+            .fetch_with_partition_info(PartitionId::new(1))
+            .await
+            .unwrap();
+        assert_eq!(partition_files.files.len(), 1);
+    }
+}
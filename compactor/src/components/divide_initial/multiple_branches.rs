@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
 use data_types::{CompactionLevel, ParquetFile, Timestamp};
+use observability_deps::tracing::error;
 
 use crate::{
     components::split_or_compact::start_level_files_to_split::{
@@ -51,6 +52,8 @@ impl DivideInitial for MultipleBranchesDivideInitial {
                 start_level,
                 max_num_files_to_group,
                 max_total_file_size_to_group,
+                max_l0_created_at_window_ns,
+                ..
             } => {
                 // Files must be sorted by `max_l0_created_at` when there are overlaps to resolve.
                 // If the `start_level` is greater than 0, there cannot be overlaps within the level,
@@ -101,53 +104,35 @@ impl DivideInitial for MultipleBranchesDivideInitial {
                     chains = vec![start_level_files];
                 }
 
-                // Reason 2) above - ensure the grouping in branches doesn't undo the vertical splitting.
-                // Assume we start with 30 files (A,B,C,...), that were each split into 3 files (A1, A2, A3, B1, ..).  If we create branches
-                // from sorting all files by max_l0_created_at we'd undo the vertical splitting (A1-A3 would get compacted back into one file).
-                // Currently the contents of each chain is more like A1, B1, C1, so by grouping chains together we can preserve the previous
-                // vertical splitting.
-                for chain in chains {
-                    let start_level_files = order_files(chain, start_level);
-
-                    let capacity = start_level_files.len();
-
-                    // Split L0s into many small groups, each has max_num_files_to_group but not exceed max_total_file_size_to_group
-                    // Collect files until either limit is reached
-                    let mut current_branch = Vec::with_capacity(capacity);
-                    let mut current_branch_size = 0;
-                    for f in start_level_files {
-                        if current_branch.len() == max_num_files_to_group
-                            || current_branch_size + f.file_size_bytes as usize
-                                > max_total_file_size_to_group
-                        {
-                            // panic if current_branch is empty
-                            if current_branch.is_empty() {
-                                panic!("Size of a file {} is larger than the max size limit to compact. Please adjust the settings. See ticket https://github.com/influxdata/idpe/issues/17209" , f.file_size_bytes);
-                            }
-
-                            if current_branch.len() == 1 {
-                                // Compacting a branch of 1 won't help us reduce the L0 file count.  Put it on the ignore list.
-                                more_for_later.push(current_branch.pop().unwrap());
-                            } else {
-                                branches.push(current_branch);
-                            }
-                            current_branch = Vec::with_capacity(capacity);
-                            current_branch_size = 0;
-                        }
-                        current_branch_size += f.file_size_bytes as usize;
-                        current_branch.push(f);
-                    }
-
-                    // push the last branch
-                    if !current_branch.is_empty() {
-                        if current_branch.len() == 1 {
-                            // Compacting a branch of 1 won't help us reduce the L0 file count.  Put it on the ignore list.
-                            more_for_later.push(current_branch.pop().unwrap());
-                        } else {
-                            branches.push(current_branch);
-                        }
+                // When a max_l0_created_at window is set, L0s created in different windows are never grouped together, so
+                // output files don't end up with a huge spread of max_l0_created_at.  Since the L0s of a chain are ordered
+                // by max_l0_created_at, the files of each window are adjacent.  This doesn't apply to the large chains kept
+                // intact above for vertical splitting, nor to L1s, whose grouping must follow min_time.
+                let window_ns =
+                    max_l0_created_at_window_ns.filter(|_| start_level == CompactionLevel::Initial);
+                let unwindowed_chains = window_ns.is_some().then(|| chains.clone());
+                let (mut grouped, mut for_later) = group_chains(
+                    chains,
+                    start_level,
+                    max_num_files_to_group,
+                    max_total_file_size_to_group,
+                    |f| window_ns.map(|width| f.max_l0_created_at.get().div_euclid(width)),
+                );
+                if let Some(chains) = unwindowed_chains {
+                    if branches.is_empty() && grouped.is_empty() {
+                        // Every window holds a single file, so this round would compact nothing, and the next one would
+                        // find the same windows.  Group across windows instead of never making progress.
+                        (grouped, for_later) = group_chains(
+                            chains,
+                            start_level,
+                            max_num_files_to_group,
+                            max_total_file_size_to_group,
+                            |_| None,
+                        );
                     }
                 }
+                branches.extend(grouped);
+                more_for_later.extend(for_later);
 
                 (branches, more_for_later)
             }
@@ -155,17 +140,34 @@ impl DivideInitial for MultipleBranchesDivideInitial {
             RoundInfo::TargetLevel {
                 target_level,
                 max_total_file_size_to_group,
+                level_stats,
+                ..
             } => {
-                let total_bytes: usize = files.iter().map(|f| f.file_size_bytes as usize).sum();
+                // RoundSplit kept the start level and all the levels after it.
+                let start_level = target_level.prev();
+                let total_bytes = level_stats.total_bytes_from_level(start_level);
                 if total_bytes < max_total_file_size_to_group {
                     (vec![files], more_for_later)
                 } else {
                     let (mut for_now, rest): (Vec<ParquetFile>, Vec<ParquetFile>) = files
                         .into_iter()
-                        .partition(|f| f.compaction_level == target_level.prev());
-
-                    let min_time = for_now.iter().map(|f| f.min_time).min().unwrap();
-                    let max_time = for_now.iter().map(|f| f.max_time).max().unwrap();
+                        .partition(|f| f.compaction_level == start_level);
+
+                    let start_level_stats = level_stats.level(start_level);
+                    let (Some(min_time), Some(max_time)) =
+                        (start_level_stats.min_time, start_level_stats.max_time)
+                    else {
+                        // The stats the round was decided on don't match its files, so the overlaps
+                        // can't be trusted either.  Compact nothing: a round with no branches and no
+                        // files for later ends the partition, leaving its files untouched.
+                        error!(
+                            %target_level,
+                            %level_stats,
+                            start_level_file_count = for_now.len(),
+                            "round has no time range for its start level, skipping partition",
+                        );
+                        return (vec![], vec![]);
+                    };
 
                     let (overlaps, for_later): (Vec<ParquetFile>, Vec<ParquetFile>) = rest
                         .into_iter()
@@ -180,6 +182,7 @@ impl DivideInitial for MultipleBranchesDivideInitial {
             RoundInfo::SimulatedLeadingEdge {
                 max_num_files_to_group,
                 max_total_file_size_to_group,
+                ..
             } => {
                 // There may be a lot of L0s, but we're going to keep it simple and just look at the first (few).
                 let start_level = CompactionLevel::Initial;
@@ -265,10 +268,111 @@ impl DivideInitial for MultipleBranchesDivideInitial {
                 assert!(files.is_empty(), "all files should map to a range");
                 (branches, vec![])
             }
+
+            // The round was only chosen because everything fits in a single plan, so it's one branch.
+            RoundInfo::ColdCompaction { .. } => (vec![files], more_for_later),
+
+            // Nothing is compacted while dropping expired files.
+            RoundInfo::Expired { .. } => {
+                more_for_later.extend(files);
+                (vec![], more_for_later)
+            }
+
+            RoundInfo::TimeBucketed { bucket_nanos, .. } => {
+                // Each bucket of time is a branch, however many files it has.  Files go to the bucket of their
+                // min_time, so a file straddling a bucket boundary stays in the earlier bucket.  So does any file
+                // overlapping it: branches are compacted concurrently, and overlapping files compacted in separate
+                // branches would produce overlapping target level files.
+                let mut files = files;
+                files.sort_by_key(|f| f.min_time);
+
+                let mut branches: Vec<Vec<ParquetFile>> = vec![];
+                let mut branch_bucket = None;
+                let mut branch_max_time = Timestamp::new(i64::MIN);
+                for f in files {
+                    let bucket = f.min_time.get().div_euclid(bucket_nanos);
+                    if branch_bucket != Some(bucket) && f.min_time > branch_max_time {
+                        branches.push(vec![]);
+                        branch_bucket = Some(bucket);
+                    }
+                    branch_max_time = branch_max_time.max(f.max_time);
+                    branches.last_mut().expect("branch started").push(f);
+                }
+
+                (branches, more_for_later)
+            }
         }
     }
 }
 
+/// Group the files of each chain into branches of at most `max_num_files_to_group` files and
+/// `max_total_file_size_to_group` bytes, never grouping files of different `window`s.
+///
+/// Returns the branches, and the files left alone in their group: compacting a single file doesn't
+/// reduce the file count, so they are left for later.
+fn group_chains(
+    chains: Vec<Vec<ParquetFile>>,
+    start_level: CompactionLevel,
+    max_num_files_to_group: usize,
+    max_total_file_size_to_group: usize,
+    window: impl Fn(&ParquetFile) -> Option<i64>,
+) -> (Vec<Vec<ParquetFile>>, Vec<ParquetFile>) {
+    let mut branches = vec![];
+    let mut more_for_later = vec![];
+
+    // Reason 2) of `divide` - ensure the grouping in branches doesn't undo the vertical splitting.
+    // Assume we start with 30 files (A,B,C,...), that were each split into 3 files (A1, A2, A3, B1, ..).  If we create branches
+    // from sorting all files by max_l0_created_at we'd undo the vertical splitting (A1-A3 would get compacted back into one file).
+    // Currently the contents of each chain is more like A1, B1, C1, so by grouping chains together we can preserve the previous
+    // vertical splitting.
+    for chain in chains {
+        let start_level_files = order_files(chain, start_level);
+
+        let capacity = start_level_files.len();
+
+        // Split L0s into many small groups, each has max_num_files_to_group but not exceed max_total_file_size_to_group
+        // Collect files until either limit is reached, or the max_l0_created_at window changes
+        let mut current_branch = Vec::with_capacity(capacity);
+        let mut current_branch_size = 0;
+        for f in start_level_files {
+            if current_branch.len() == max_num_files_to_group
+                || current_branch_size + f.file_size_bytes as usize > max_total_file_size_to_group
+                || current_branch
+                    .first()
+                    .map_or(false, |first| window(first) != window(&f))
+            {
+                // panic if current_branch is empty
+                if current_branch.is_empty() {
+                    panic!("Size of a file {} is larger than the max size limit to compact. Please adjust the settings. See ticket https://github.com/influxdata/idpe/issues/17209" , f.file_size_bytes);
+                }
+
+                if current_branch.len() == 1 {
+                    // Compacting a branch of 1 won't help us reduce the L0 file count.  Put it on the ignore list.
+                    more_for_later.push(current_branch.pop().unwrap());
+                } else {
+                    branches.push(current_branch);
+                }
+                current_branch = Vec::with_capacity(capacity);
+                current_branch_size = 0;
+            }
+            current_branch_size += f.file_size_bytes as usize;
+            current_branch.push(f);
+        }
+
+        // push the last branch
+        if !current_branch.is_empty() {
+            if current_branch.len() == 1 {
+                // Compacting a branch of 1 won't help us reduce the L0 file count.  Put it on the ignore list.
+                more_for_later.push(current_branch.pop().unwrap());
+            } else {
+                branches.push(current_branch);
+            }
+        }
+    }
+
+    (branches, more_for_later)
+}
+
 /// Return a sorted files of the given ones.
 /// The order is used to split the files and form the right groups of files to compact
 /// and deduplicate correctly to fewer and larger but same level files
@@ -298,6 +402,7 @@ mod tests {
     use iox_tests::ParquetFileBuilder;
 
     use super::*;
+    use crate::round_info::LevelStats;
 
     #[test]
     fn test_display() {
@@ -313,6 +418,8 @@ mod tests {
             start_level: CompactionLevel::Initial,
             max_num_files_to_group: 2,
             max_total_file_size_to_group: 100,
+            max_l0_created_at_window_ns: None,
+            level_stats: Default::default(),
         };
         let divide = MultipleBranchesDivideInitial::new();
 
@@ -346,6 +453,89 @@ mod tests {
         assert_eq!(branches[0], vec![f1, f2]);
     }
 
+    #[test]
+    fn test_divide_max_l0_created_at_window() {
+        // three clusters of L0s by creation time, the last being a single file
+        let files = [1, 2, 3, 105, 106, 107, 250]
+            .into_iter()
+            .enumerate()
+            .map(|(i, created_at)| {
+                ParquetFileBuilder::new(i as i64 + 1)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_time_range(0, 100)
+                    .with_max_l0_created_at(created_at)
+                    .with_file_size_bytes(10)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let round_info = |max_l0_created_at_window_ns| RoundInfo::ManySmallFiles {
+            start_level: CompactionLevel::Initial,
+            max_num_files_to_group: 10,
+            max_total_file_size_to_group: 1000,
+            max_l0_created_at_window_ns,
+            level_stats: Default::default(),
+        };
+        let divide = MultipleBranchesDivideInitial::new();
+
+        // without windows, everything fits in one plan
+        let (branches, more_for_later) = divide.divide(files.clone(), round_info(None));
+        assert_eq!(branches, vec![files.clone()]);
+        assert!(more_for_later.is_empty());
+
+        // with windows, each window gets its own plan, and the lone file of the last window is left
+        // for later.  Input order doesn't matter.
+        let mut shuffled = files.clone();
+        shuffled.reverse();
+        let (branches, more_for_later) = divide.divide(shuffled, round_info(Some(100)));
+        assert_eq!(branches, vec![files[0..3].to_vec(), files[3..6].to_vec()]);
+        assert_eq!(more_for_later, vec![files[6].clone()]);
+
+        // the count limit still applies within a window
+        let round_info = RoundInfo::ManySmallFiles {
+            start_level: CompactionLevel::Initial,
+            max_num_files_to_group: 2,
+            max_total_file_size_to_group: 1000,
+            max_l0_created_at_window_ns: Some(100),
+            level_stats: Default::default(),
+        };
+        let (branches, more_for_later) = divide.divide(files.clone(), round_info);
+        assert_eq!(branches, vec![files[0..2].to_vec(), files[3..5].to_vec()]);
+        assert_eq!(
+            more_for_later,
+            vec![files[2].clone(), files[5].clone(), files[6].clone()]
+        );
+    }
+
+    #[test]
+    fn test_divide_max_l0_created_at_window_all_singletons() {
+        // every L0 is alone in its window
+        let files = [1, 150, 250]
+            .into_iter()
+            .enumerate()
+            .map(|(i, created_at)| {
+                ParquetFileBuilder::new(i as i64 + 1)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_time_range(0, 100)
+                    .with_max_l0_created_at(created_at)
+                    .with_file_size_bytes(10)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let round_info = RoundInfo::ManySmallFiles {
+            start_level: CompactionLevel::Initial,
+            max_num_files_to_group: 10,
+            max_total_file_size_to_group: 1000,
+            max_l0_created_at_window_ns: Some(100),
+            level_stats: Default::default(),
+        };
+        let divide = MultipleBranchesDivideInitial::new();
+
+        // windowing would leave every file for later, so the files are grouped across windows instead
+        let (branches, more_for_later) = divide.divide(files.clone(), round_info);
+        assert_eq!(branches, vec![files]);
+        assert!(more_for_later.is_empty());
+    }
+
     #[test]
     #[should_panic(
         expected = "Size of a file 50 is larger than the max size limit to compact. Please adjust the settings"
@@ -355,6 +545,8 @@ mod tests {
             start_level: CompactionLevel::Initial,
             max_num_files_to_group: 10,
             max_total_file_size_to_group: 40,
+            max_l0_created_at_window_ns: None,
+            level_stats: Default::default(),
         };
         let divide = MultipleBranchesDivideInitial::new();
 
@@ -382,6 +574,8 @@ mod tests {
             start_level: CompactionLevel::Initial,
             max_num_files_to_group: 10,
             max_total_file_size_to_group: 100,
+            max_l0_created_at_window_ns: None,
+            level_stats: Default::default(),
         };
         let divide = MultipleBranchesDivideInitial::new();
 
@@ -410,4 +604,91 @@ mod tests {
         assert_eq!(more_for_later.len(), 1);
         assert_eq!(branches[0], vec![f2, f3]);
     }
+
+    #[test]
+    fn test_divide_inconsistent_level_stats() {
+        let l0 = ParquetFileBuilder::new(1)
+            .with_compaction_level(CompactionLevel::Initial)
+            .with_time_range(0, 100)
+            .with_file_size_bytes(10)
+            .build();
+        let l1 = ParquetFileBuilder::new(2)
+            .with_compaction_level(CompactionLevel::FileNonOverlapped)
+            .with_time_range(50, 150)
+            .with_file_size_bytes(100)
+            .build();
+        // stats missing the L0
+        let round_info = RoundInfo::TargetLevel {
+            target_level: CompactionLevel::FileNonOverlapped,
+            max_total_file_size_to_group: 50,
+            estimated_branch_output_bytes: vec![],
+            level_stats: LevelStats::new([&l1]),
+        };
+        let divide = MultipleBranchesDivideInitial::new();
+
+        // nothing is compacted, rather than panicking
+        let (branches, more_for_later) = divide.divide(vec![l0, l1], round_info);
+        assert!(branches.is_empty());
+        assert!(more_for_later.is_empty());
+    }
+
+    #[test]
+    fn test_divide_time_bucketed() {
+        let round_info = RoundInfo::TimeBucketed {
+            bucket_nanos: 100,
+            target_level: CompactionLevel::FileNonOverlapped,
+            level_stats: Default::default(),
+        };
+        let divide = MultipleBranchesDivideInitial::new();
+
+        let file = |id: i64, level, min_time, max_time| {
+            ParquetFileBuilder::new(id)
+                .with_compaction_level(level)
+                .with_time_range(min_time, max_time)
+                .with_max_l0_created_at(id)
+                .build()
+        };
+        // bucket 0
+        let l0_1 = file(1, CompactionLevel::Initial, 10, 50);
+        let l0_2 = file(2, CompactionLevel::Initial, 40, 90);
+        let l1_3 = file(3, CompactionLevel::FileNonOverlapped, 0, 60);
+        // bucket 1, however many files it has
+        let l0_4 = file(4, CompactionLevel::Initial, 110, 120);
+        let l0_5 = file(5, CompactionLevel::Initial, 130, 140);
+        let l0_6 = file(6, CompactionLevel::Initial, 150, 160);
+        let l1_7 = file(7, CompactionLevel::FileNonOverlapped, 100, 199);
+        // bucket 3, bucket 2 has no files
+        let l0_8 = file(8, CompactionLevel::Initial, 310, 320);
+
+        let files = vec![
+            l0_8.clone(),
+            l0_6.clone(),
+            l1_7.clone(),
+            l0_1.clone(),
+            l0_5.clone(),
+            l1_3.clone(),
+            l0_4.clone(),
+            l0_2.clone(),
+        ];
+        let (branches, more_for_later) = divide.divide(files, round_info.clone());
+        assert_eq!(
+            branches,
+            vec![
+                vec![l1_3.clone(), l0_1.clone(), l0_2],
+                vec![l1_7, l0_4, l0_5, l0_6],
+                vec![l0_8],
+            ]
+        );
+        assert!(more_for_later.is_empty());
+
+        // A file straddling the boundary of buckets 0 and 1 goes to bucket 0, along with the file of
+        // bucket 1 it overlaps.  The files of bucket 1 after it still get their own branch.
+        let l0_9 = file(9, CompactionLevel::Initial, 80, 120);
+        let l0_10 = file(10, CompactionLevel::Initial, 110, 130);
+        let l0_11 = file(11, CompactionLevel::Initial, 150, 160);
+        let files = vec![l0_11.clone(), l0_10.clone(), l0_9.clone(), l0_1.clone()];
+        let (branches, more_for_later) = divide.divide(files, round_info);
+        assert_eq!(branches, vec![vec![l0_1, l0_9, l0_10], vec![l0_11]]);
+        assert!(more_for_later.is_empty());
+    }
 }
@@ -9,7 +9,7 @@ use crate::{
     },
     file_classification::{
         CompactReason, FileClassification, FileToSplit, FilesForProgress, FilesToSplitOrCompact,
-        SplitReason,
+        NoneReason, SplitReason,
     },
     partition_info::PartitionInfo,
     RoundInfo,
@@ -141,6 +141,7 @@ where
                 start_level,
                 max_num_files_to_group,
                 max_total_file_size_to_group,
+                ..
             } => file_classification_for_many_files(
                 *max_total_file_size_to_group,
                 *max_num_files_to_group,
@@ -148,7 +149,7 @@ where
                 *start_level,
             ),
 
-            RoundInfo::SimulatedLeadingEdge { .. } => {
+            RoundInfo::SimulatedLeadingEdge { .. } | RoundInfo::ColdCompaction { .. } => {
                 // file division already done in round_info_source
                 FileClassification {
                     target_level: round_info.target_level(),
@@ -163,11 +164,23 @@ where
                 }
             }
 
-            RoundInfo::VerticalSplit { split_times } => {
+            // Nothing is compacted while dropping expired files.
+            RoundInfo::Expired { .. } => FileClassification {
+                target_level: round_info.target_level(),
+                files_to_make_progress_on: FilesForProgress {
+                    upgrade: vec![],
+                    split_or_compact: FilesToSplitOrCompact::None(NoneReason::NoInputFiles),
+                },
+                files_to_keep: files_to_compact,
+            },
+
+            RoundInfo::VerticalSplit { split_times, .. } => {
                 file_classification_for_vertical_split(split_times, files_to_compact)
             }
 
-            RoundInfo::TargetLevel { target_level, .. } => {
+            // Each bucket of a TimeBucketed round is classified like a whole TargetLevel round.
+            RoundInfo::TargetLevel { target_level, .. }
+            | RoundInfo::TimeBucketed { target_level, .. } => {
                 // Split files into files_to_compact, files_to_upgrade, and files_to_keep
                 //
                 // Since output of one compaction is used as input of next compaction, all files that are not
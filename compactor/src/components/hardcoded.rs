@@ -7,8 +7,15 @@ use std::{sync::Arc, time::Duration};
 use compactor_scheduler::{create_scheduler, Scheduler};
 use data_types::CompactionLevel;
 use object_store::memory::InMemory;
+use observability_deps::tracing::warn;
 
-use crate::{config::Config, error::ErrorKind, object_store::ignore_writes::IgnoreWrites};
+use parquet_file::storage::ParquetStorage;
+
+use crate::{
+    config::Config,
+    error::ErrorKind,
+    object_store::{ignore_writes::IgnoreWrites, write_through::WriteThroughStore},
+};
 
 use super::{
     changed_files_filter::logging::LoggingChangedFiles,
@@ -24,6 +31,7 @@ use super::{
     compaction_jobs_source::{
         logging::LoggingCompactionJobsWrapper, metrics::MetricsCompactionJobsSourceWrapper,
         not_empty::NotEmptyCompactionJobsSourceWrapper,
+        prefetch::PrefetchCompactionJobsSourceWrapper,
         randomize_order::RandomizeOrderCompactionJobsSourcesWrapper,
         scheduled::ScheduledCompactionJobsSource, CompactionJobsSource,
     },
@@ -43,13 +51,18 @@ use super::{
     },
     ir_planner::{logging::LoggingIRPlannerWrapper, planner_v1::V1IRPlanner, IRPlanner},
     namespaces_source::catalog::CatalogNamespacesSource,
+    output_ratio::OutputRatioTracker,
     parquet_file_sink::{
         dedicated::DedicatedExecParquetFileSinkWrapper, logging::LoggingParquetFileSinkWrapper,
         object_store::ObjectStoreParquetFileSink,
     },
     parquet_files_sink::{dispatch::DispatchParquetFilesSink, ParquetFilesSink},
     partition_files_source::{
+        caching::{CachingPartitionFilesSource, PartitionFilesInvalidator},
         catalog::{CatalogPartitionFilesSource, QueryRateLimiter},
+        commit_aware::{CommitAwarePartitionFilesSource, RecentCommits},
+        filtering::FilteringPartitionFilesSource,
+        prefetching::{PartitionFilesPrefetcher, PrefetchingPartitionFilesSource},
         rate_limit::RateLimit,
         PartitionFilesSource,
     },
@@ -70,9 +83,16 @@ use super::{
         metrics::MetricsPostClassificationFilterWrapper, possible_progress::PossibleProgressFilter,
         PostClassificationPartitionFilter,
     },
-    round_info_source::{LevelBasedRoundInfo, LoggingRoundInfoWrapper, RoundInfoSource},
+    round_info_source::{
+        decision_log::DecisionLogRoundInfoWrapper, loop_detection::LoopDetectingRoundInfoWrapper,
+        metrics::MetricsRoundInfoWrapper, LevelBasedRoundInfo, LoggingRoundInfoWrapper,
+        RoundInfoSource,
+    },
     round_split::many_files::ManyFilesRoundSplit,
-    scratchpad::{noop::NoopScratchpadGen, prod::ProdScratchpadGen, ScratchpadGen},
+    scratchpad::{
+        breaker::ScratchpadBreaker, metrics::MetricsScratchpadGenWrapper, noop::NoopScratchpadGen,
+        prod::ProdScratchpadGen, ScratchpadGen, ScratchpadRetryPolicy,
+    },
     split_or_compact::{
         logging::LoggingSplitOrCompactWrapper, metrics::MetricsSplitOrCompactWrapper,
         split_compact::SplitCompact,
@@ -90,13 +110,17 @@ pub fn hardcoded_components(config: &Config) -> Arc<Components> {
         Arc::clone(&config.metric_registry),
         config.shadow_mode,
     );
+    let (partition_files_source, partition_files_state_source, partition_files_handles) =
+        make_partition_files_source(config);
     let (compaction_jobs_source, commit, compaction_job_done_sink) =
-        make_jobs_source_commit_jobs_sink(config, Arc::clone(&scheduler));
+        make_jobs_source_commit_jobs_sink(config, Arc::clone(&scheduler), partition_files_handles);
+    let scratchpad_breaker = make_scratchpad_breaker(config);
 
     Arc::new(Components {
         compaction_job_stream: make_compaction_job_stream(config, compaction_jobs_source),
         partition_info_source: make_partition_info_source(config),
-        partition_files_source: make_partition_files_source(config),
+        partition_files_source,
+        partition_files_state_source,
         round_info_source: make_round_info_source(config),
         partition_filter: make_partition_filter(config),
         compaction_job_done_sink,
@@ -104,19 +128,29 @@ pub fn hardcoded_components(config: &Config) -> Arc<Components> {
         ir_planner: make_ir_planner(config),
         df_planner: make_df_planner(config),
         df_plan_exec: make_df_plan_exec(config),
-        parquet_files_sink: make_parquet_files_sink(config),
+        parquet_files_sink: make_parquet_files_sink(config, scratchpad_breaker.clone()),
         round_split: Arc::new(ManyFilesRoundSplit::new()),
         divide_initial: Arc::new(MultipleBranchesDivideInitial::new()),
-        scratchpad_gen: make_scratchpad_gen(config),
+        scratchpad_gen: make_scratchpad_gen(config, scratchpad_breaker),
         file_classifier: make_file_classifier(config),
         post_classification_partition_filter: make_post_classification_partition_filter(config),
         changed_files_filter: Arc::new(LoggingChangedFiles::new()),
+        output_ratio_tracker: Arc::new(OutputRatioTracker::new()),
     })
 }
 
+/// Handles to keep the partition files source up to date with the commits and upcoming jobs.
+#[derive(Debug, Default)]
+struct PartitionFilesHandles {
+    invalidator: Option<PartitionFilesInvalidator>,
+    recent_commits: Option<RecentCommits>,
+    prefetcher: Option<PartitionFilesPrefetcher>,
+}
+
 fn make_jobs_source_commit_jobs_sink(
     config: &Config,
     scheduler: Arc<dyn Scheduler>,
+    partition_files_handles: PartitionFilesHandles,
 ) -> (
     Arc<dyn CompactionJobsSource>,
     Arc<CommitToScheduler>,
@@ -124,7 +158,21 @@ fn make_jobs_source_commit_jobs_sink(
 ) {
     let compaction_jobs_source = ScheduledCompactionJobsSource::new(Arc::clone(&scheduler));
 
-    let commit = CommitToScheduler::new(Arc::clone(&scheduler));
+    let PartitionFilesHandles {
+        invalidator,
+        recent_commits,
+        prefetcher,
+    } = partition_files_handles;
+    let mut commit = CommitToScheduler::new(Arc::clone(&scheduler));
+    if let Some(invalidator) = invalidator {
+        commit = commit.with_partition_files_invalidator(invalidator);
+    }
+    if let Some(recent_commits) = recent_commits {
+        commit = commit.with_recent_commits(recent_commits);
+    }
+    if let Some(prefetcher) = &prefetcher {
+        commit = commit.with_partition_files_prefetcher(prefetcher.clone());
+    }
 
     let compaction_job_done_sink = CompactionJobDoneSinkToScheduler::new(Arc::clone(&scheduler));
 
@@ -141,7 +189,7 @@ fn make_jobs_source_commit_jobs_sink(
                     // use explicit match statement so we never forget to add new variants
                     match kind {
                         ErrorKind::OutOfMemory | ErrorKind::Timeout | ErrorKind::Unknown => true,
-                        ErrorKind::ObjectStore => false,
+                        ErrorKind::ObjectStore | ErrorKind::Catalog => false,
                     }
                 })
                 .copied()
@@ -171,6 +219,13 @@ fn make_jobs_source_commit_jobs_sink(
             Arc::clone(&config.time_provider),
         ))
     };
+    let compaction_jobs_source: Arc<dyn CompactionJobsSource> = match prefetcher {
+        Some(prefetcher) => Arc::new(PrefetchCompactionJobsSourceWrapper::new(
+            compaction_jobs_source,
+            prefetcher,
+        )),
+        None => compaction_jobs_source,
+    };
 
     (
         compaction_jobs_source,
@@ -201,25 +256,181 @@ fn make_partition_info_source(config: &Config) -> Arc<dyn PartitionInfoSource> {
     ))
 }
 
-fn make_partition_files_source(config: &Config) -> Arc<dyn PartitionFilesSource> {
-    match config.max_partition_fetch_queries_per_second {
-        Some(rps) => Arc::new(CatalogPartitionFilesSource::new(
-            config.backoff_config.clone(),
-            QueryRateLimiter::new(Arc::clone(&config.catalog), RateLimit::new(rps, 25)),
-        )),
-        None => Arc::new(CatalogPartitionFilesSource::new(
-            config.backoff_config.clone(),
-            Arc::clone(&config.catalog),
+/// Returns the source of the files of a partition to compact, the source of its current files to
+/// check for concurrent changes before committing, and the handles to keep the former up to date.
+fn make_partition_files_source(
+    config: &Config,
+) -> (
+    Arc<dyn PartitionFilesSource>,
+    Arc<dyn PartitionFilesSource>,
+    PartitionFilesHandles,
+) {
+    let source: Arc<dyn PartitionFilesSource> = match &config.partition_fetch_rate_limit {
+        Some(rate_limit) => {
+            let mut rate_limit = RateLimit::with_config(Arc::clone(rate_limit))
+                .with_metrics(&config.metric_registry);
+            if let Some(interval) = config.partition_fetch_min_interval {
+                rate_limit = rate_limit.with_partition_interval(interval);
+            }
+            Arc::new(configure_catalog_partition_files_source(
+                config,
+                CatalogPartitionFilesSource::new(
+                    config.backoff_config.clone(),
+                    QueryRateLimiter::new(Arc::clone(&config.catalog), rate_limit),
+                ),
+            ))
+        }
+        None => Arc::new(configure_catalog_partition_files_source(
+            config,
+            CatalogPartitionFilesSource::new(
+                config.backoff_config.clone(),
+                Arc::clone(&config.catalog),
+            ),
         )),
+    };
+
+    // overlay the commits before caching, as the cache is invalidated on commit
+    let (source, recent_commits): (Arc<dyn PartitionFilesSource>, _) =
+        match config.partition_files_commit_window {
+            Some(window) => {
+                let source = CommitAwarePartitionFilesSource::new(
+                    source,
+                    window,
+                    Arc::clone(&config.time_provider),
+                );
+                let recent_commits = source.recent_commits();
+                (Arc::new(source), Some(recent_commits))
+            }
+            None => (source, None),
+        };
+
+    // the state right before a commit must not come from a cache or a prefetch, but recent commits
+    // of the compactor are no concurrent changes
+    let state_source = Arc::clone(&source);
+
+    let (source, invalidator): (Arc<dyn PartitionFilesSource>, _) =
+        match config.partition_files_cache_ttl {
+            Some(ttl) => {
+                let source = CachingPartitionFilesSource::new(
+                    source,
+                    ttl,
+                    config.partition_files_cache_max_entries,
+                    Arc::clone(&config.time_provider),
+                );
+                let invalidator = source.invalidator();
+                (Arc::new(source), Some(invalidator))
+            }
+            None => (source, None),
+        };
+
+    let source: Arc<dyn PartitionFilesSource> =
+        match config.partition_files_filter_max_file_size_bytes {
+            Some(max_file_size_bytes) => Arc::new(
+                FilteringPartitionFilesSource::new(source, &config.metric_registry)
+                    .with_max_file_size_bytes(max_file_size_bytes),
+            ),
+            None => source,
+        };
+
+    // prefetch what the compaction of a partition would fetch, filtered files included
+    let (source, prefetcher): (Arc<dyn PartitionFilesSource>, _) =
+        match config.partition_files_prefetch_ttl {
+            Some(ttl) => {
+                let source = PrefetchingPartitionFilesSource::new(
+                    source,
+                    ttl,
+                    config.partition_files_prefetch_concurrency,
+                    Arc::clone(&config.time_provider),
+                );
+                let prefetcher = source.prefetcher();
+                (Arc::new(source), Some(prefetcher))
+            }
+            None => (source, None),
+        };
+
+    (
+        source,
+        state_source,
+        PartitionFilesHandles {
+            invalidator,
+            recent_commits,
+            prefetcher,
+        },
+    )
+}
+
+fn configure_catalog_partition_files_source<T>(
+    config: &Config,
+    source: CatalogPartitionFilesSource<T>,
+) -> CatalogPartitionFilesSource<T> {
+    let mut source = source.with_max_partitions_per_query(config.max_partitions_per_fetch_query);
+    if let Some(deadline) = config.partition_files_fetch_timeout {
+        source = source.with_deadline(deadline);
+    }
+    if let Some(page_size) = config.partition_files_page_size {
+        source = source.with_page_size(page_size);
+    }
+    if let Some(max_files) = config.partition_files_max_files {
+        source = source.with_max_files(max_files);
     }
+    source
 }
 
 fn make_round_info_source(config: &Config) -> Arc<dyn RoundInfoSource> {
+    let mut round_info = LevelBasedRoundInfo::new(
+        config.max_num_files_per_plan,
+        config.max_compact_size_bytes(),
+        config.early_l1_to_l2_size_multiplier,
+        config.allow_early_l1_to_l2,
+    )
+    .with_l2_split_hints(config.vertical_split_l2_hints)
+    .with_per_chain_overlaps(config.per_chain_overlaps);
+    if let Some(max_split_times) = config.max_split_times_per_round {
+        round_info = round_info.with_max_split_times_per_round(max_split_times);
+    }
+    if let Some(max_arrow_bytes) = config.max_estimated_arrow_bytes_per_plan {
+        round_info =
+            round_info.with_memory_budget(max_arrow_bytes, config.arrow_expansion_per_column);
+    }
+    if let Some(max_branches) = config.max_branches_per_round {
+        round_info = round_info.with_max_branches_per_round(max_branches);
+    }
+    if let Some(window) = config.many_small_files_window {
+        round_info = round_info.with_many_small_files_window(window);
+    }
+    if let Some(time_bucket) = config.time_bucket {
+        round_info = round_info.with_time_bucket(time_bucket);
+    }
+    if let Some(max_rows) = config.max_total_rows_per_plan {
+        round_info = round_info.with_max_total_rows_per_plan(max_rows);
+    }
+    if let (Some(floor), Some(ceiling)) = (
+        config.adaptive_file_size_per_plan_floor,
+        config.adaptive_file_size_per_plan_ceiling,
+    ) {
+        round_info = round_info.with_adaptive_file_size_per_plan(floor, ceiling);
+    }
+    if config.drop_expired_files {
+        round_info = round_info.with_retention(Arc::clone(&config.time_provider));
+    }
+    if let Some(threshold) = config.cold_compaction_threshold {
+        round_info = round_info.with_cold_compaction(Arc::clone(&config.time_provider), threshold);
+    }
+
+    let mut round_info: Arc<dyn RoundInfoSource> = Arc::new(round_info);
+    if let Some(log) = config.round_decision_log.as_ref() {
+        round_info = Arc::new(DecisionLogRoundInfoWrapper::new(
+            round_info,
+            Arc::clone(log),
+            Arc::clone(&config.time_provider),
+        ));
+    }
+    if let Some(max_repeats) = config.max_repeated_rounds {
+        round_info = Arc::new(LoopDetectingRoundInfoWrapper::new(round_info, max_repeats));
+    }
+
     Arc::new(LoggingRoundInfoWrapper::new(Arc::new(
-        LevelBasedRoundInfo::new(
-            config.max_num_files_per_plan,
-            config.max_compact_size_bytes(),
-        ),
+        MetricsRoundInfoWrapper::new(round_info, &config.metric_registry),
     )))
 }
 
@@ -298,15 +509,31 @@ fn make_df_plan_exec(config: &Config) -> Arc<dyn DataFusionPlanExec> {
     }
 }
 
-fn make_parquet_files_sink(config: &Config) -> Arc<dyn ParquetFilesSink> {
+fn make_parquet_files_sink(
+    config: &Config,
+    scratchpad_breaker: Option<Arc<ScratchpadBreaker>>,
+) -> Arc<dyn ParquetFilesSink> {
     if let Some(sink) = config.parquet_files_sink_override.as_ref() {
         Arc::clone(sink)
     } else {
+        // while the scratchpad store is bypassed, write the files straight to the real store. In
+        // shadow mode, the real store must never be written to.
+        let parquet_store = match scratchpad_breaker {
+            Some(breaker) if !config.shadow_mode => ParquetStorage::new(
+                Arc::new(WriteThroughStore::new(
+                    Arc::clone(config.parquet_store_scratchpad.object_store()),
+                    Arc::clone(config.parquet_store_real.object_store()),
+                    breaker,
+                )),
+                config.parquet_store_scratchpad.id(),
+            ),
+            _ => config.parquet_store_scratchpad.clone(),
+        };
         let parquet_file_sink = Arc::new(LoggingParquetFileSinkWrapper::new(
             DedicatedExecParquetFileSinkWrapper::new(
                 ObjectStoreParquetFileSink::new(
                     config.exec.pool(),
-                    config.parquet_store_scratchpad.clone(),
+                    parquet_store,
                     Arc::clone(&config.time_provider),
                 ),
                 Arc::clone(&config.exec),
@@ -316,9 +543,30 @@ fn make_parquet_files_sink(config: &Config) -> Arc<dyn ParquetFilesSink> {
     }
 }
 
-fn make_scratchpad_gen(config: &Config) -> Arc<dyn ScratchpadGen> {
+/// The breaker shared by the scratchpads and the files they write, if the scratchpad store is used
+/// at all.
+fn make_scratchpad_breaker(config: &Config) -> Option<Arc<ScratchpadBreaker>> {
     if config.simulate_without_object_store || !config.enable_scratchpad {
-        Arc::new(NoopScratchpadGen::new())
+        return None;
+    }
+    let max_failures = config.scratchpad_breaker_failures?;
+    Some(Arc::new(ScratchpadBreaker::new(
+        max_failures,
+        config.scratchpad_breaker_cooldown,
+        Arc::clone(&config.time_provider),
+        &config.metric_registry,
+    )))
+}
+
+fn make_scratchpad_gen(
+    config: &Config,
+    scratchpad_breaker: Option<Arc<ScratchpadBreaker>>,
+) -> Arc<dyn ScratchpadGen> {
+    if config.simulate_without_object_store || !config.enable_scratchpad {
+        Arc::new(MetricsScratchpadGenWrapper::new(
+            NoopScratchpadGen::new(),
+            &config.metric_registry,
+        ))
     } else {
         let scratchpad_store_output = if config.shadow_mode {
             Arc::new(IgnoreWrites::new(Arc::new(InMemory::new())))
@@ -326,13 +574,45 @@ fn make_scratchpad_gen(config: &Config) -> Arc<dyn ScratchpadGen> {
             Arc::clone(config.parquet_store_real.object_store())
         };
 
-        Arc::new(ProdScratchpadGen::new(
+        let mut scratchpad_gen = ProdScratchpadGen::new(
             config.shadow_mode,
             config.partition_scratchpad_concurrency,
             config.backoff_config.clone(),
             Arc::clone(config.parquet_store_real.object_store()),
             Arc::clone(config.parquet_store_scratchpad.object_store()),
             scratchpad_store_output,
+        )
+        .with_keep_written(config.scratchpad_keep_written)
+        .with_retry_policy(ScratchpadRetryPolicy {
+            max_attempts: config.scratchpad_transfer_max_attempts,
+            backoff_config: config.backoff_config.clone(),
+        });
+        if let Some(capacity_bytes) = config.scratchpad_capacity_bytes {
+            warn!(
+                capacity_bytes,
+                "scratchpad capacity is limited, files of later branches won't be prefetched"
+            );
+            scratchpad_gen =
+                scratchpad_gen.with_capacity(capacity_bytes, config.scratchpad_capacity_policy);
+        }
+        if let Some(max_concurrent_transfers) = config.scratchpad_max_concurrent_transfers {
+            scratchpad_gen = scratchpad_gen.with_max_concurrent_transfers(max_concurrent_transfers);
+        }
+        if let Some(threshold_bytes) = config.scratchpad_read_through_bytes {
+            scratchpad_gen = scratchpad_gen.with_read_through(threshold_bytes);
+        }
+        if let Some(breaker) = scratchpad_breaker {
+            // transfers retried forever never fail, so the breaker would never open
+            assert!(
+                config.scratchpad_transfer_max_attempts.is_some(),
+                "the scratchpad breaker requires a maximum number of transfer attempts"
+            );
+            scratchpad_gen = scratchpad_gen.with_breaker(breaker);
+        }
+
+        Arc::new(MetricsScratchpadGenWrapper::new(
+            scratchpad_gen,
+            &config.metric_registry,
         ))
     }
 }
@@ -4,9 +4,9 @@ use self::{
     changed_files_filter::ChangedFilesFilter, commit::CommitToScheduler,
     compaction_job_done_sink::CompactionJobDoneSink, compaction_job_stream::CompactionJobStream,
     df_plan_exec::DataFusionPlanExec, df_planner::DataFusionPlanner, divide_initial::DivideInitial,
-    file_classifier::FileClassifier, ir_planner::IRPlanner, parquet_files_sink::ParquetFilesSink,
-    partition_files_source::PartitionFilesSource, partition_filter::PartitionFilter,
-    partition_info_source::PartitionInfoSource,
+    file_classifier::FileClassifier, ir_planner::IRPlanner, output_ratio::OutputRatioTracker,
+    parquet_files_sink::ParquetFilesSink, partition_files_source::PartitionFilesSource,
+    partition_filter::PartitionFilter, partition_info_source::PartitionInfoSource,
     post_classification_partition_filter::PostClassificationPartitionFilter,
     round_info_source::RoundInfoSource, round_split::RoundSplit, scratchpad::ScratchpadGen,
 };
@@ -25,6 +25,7 @@ pub mod files_split;
 pub mod hardcoded;
 pub mod ir_planner;
 pub mod namespaces_source;
+pub mod output_ratio;
 pub mod parquet_file_sink;
 pub mod parquet_files_sink;
 pub mod partition_files_source;
@@ -51,6 +52,9 @@ pub struct Components {
     pub partition_info_source: Arc<dyn PartitionInfoSource>,
     /// Source of files in a partition for compaction
     pub partition_files_source: Arc<dyn PartitionFilesSource>,
+    /// Source of the current files in a partition, bypassing caches and prefetches, to detect
+    /// concurrent changes right before committing
+    pub partition_files_state_source: Arc<dyn PartitionFilesSource>,
     /// Determines what type of compaction round the compactor will be doing
     pub round_info_source: Arc<dyn RoundInfoSource>,
     /// stop condition for completing a partition compaction
@@ -79,4 +83,6 @@ pub struct Components {
     pub file_classifier: Arc<dyn FileClassifier>,
     /// Check for other processes modifying files.
     pub changed_files_filter: Arc<dyn ChangedFilesFilter>,
+    /// Tracks how much the data of each partition shrinks when compacted.
+    pub output_ratio_tracker: Arc<OutputRatioTracker>,
 }
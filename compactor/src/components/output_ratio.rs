@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use data_types::PartitionId;
+use parking_lot::Mutex;
+
+/// Tracks how many bytes compaction writes per byte it reads, per partition.
+///
+/// The driver records the input and output bytes of the plans it runs, and the round info source
+/// takes the ratio observed since the previous round to size the next one.  Compression ratios vary
+/// a lot between namespaces, so this tells how much input a plan can handle much better than a
+/// fixed limit.
+#[derive(Debug, Default)]
+pub struct OutputRatioTracker {
+    /// (input bytes, output bytes) recorded per partition since the ratio was last taken.
+    totals: Mutex<HashMap<PartitionId, (usize, usize)>>,
+}
+
+impl OutputRatioTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that compacting `input_bytes` of the partition's files produced `output_bytes`.
+    pub fn record(&self, partition_id: PartitionId, input_bytes: usize, output_bytes: usize) {
+        let mut totals = self.totals.lock();
+        let (input, output) = totals.entry(partition_id).or_default();
+        *input += input_bytes;
+        *output += output_bytes;
+    }
+
+    /// Output-bytes-to-input-bytes ratio recorded for the partition since the last call, if any
+    /// input was recorded.
+    pub fn take_ratio(&self, partition_id: PartitionId) -> Option<f64> {
+        match self.totals.lock().remove(&partition_id) {
+            Some((input, output)) if input > 0 => Some(output as f64 / input as f64),
+            _ => None,
+        }
+    }
+
+    /// Forget the partition, e.g. once it's done compacting.
+    pub fn forget(&self, partition_id: PartitionId) {
+        self.totals.lock().remove(&partition_id);
+    }
+}
+
+impl std::fmt::Display for OutputRatioTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OutputRatioTracker")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_ratio() {
+        let tracker = OutputRatioTracker::new();
+        let p1 = PartitionId::new(1);
+        let p2 = PartitionId::new(2);
+
+        assert_eq!(tracker.take_ratio(p1), None);
+
+        tracker.record(p1, 100, 20);
+        tracker.record(p1, 300, 80);
+        tracker.record(p2, 0, 10);
+        assert_eq!(tracker.take_ratio(p1), Some(0.25));
+        assert_eq!(tracker.take_ratio(p2), None);
+
+        // taken ratios start over
+        assert_eq!(tracker.take_ratio(p1), None);
+        tracker.record(p1, 100, 50);
+        assert_eq!(tracker.take_ratio(p1), Some(0.5));
+
+        tracker.record(p1, 100, 50);
+        tracker.forget(p1);
+        assert_eq!(tracker.take_ratio(p1), None);
+    }
+}
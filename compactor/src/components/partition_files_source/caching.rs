@@ -0,0 +1,514 @@
+use std::{collections::HashMap, fmt::Display, num::NonZeroUsize, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use data_types::PartitionId;
+use iox_time::{Time, TimeProvider};
+use parking_lot::Mutex;
+
+use crate::error::DynError;
+
+use super::{FetchedFiles, PartitionFiles, PartitionFilesSource};
+
+/// Caches the files of each partition for a short time.
+///
+/// The files of a partition rarely change between consecutive fetches, unless the compactor
+/// commits changes to it in between, which must [invalidate](PartitionFilesInvalidator) its entry.
+///
+/// Files fetched with their partition, by
+/// [`fetch_with_partition_info`](PartitionFilesSource::fetch_with_partition_info) or
+/// [`fetch_many`](PartitionFilesSource::fetch_many), are cached together with it and serve all
+/// fetches. Files fetched by [`fetch`](PartitionFilesSource::fetch) only serve plain fetches.
+#[derive(Debug)]
+pub struct CachingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    inner: T,
+    ttl: Duration,
+    time_provider: Arc<dyn TimeProvider>,
+    cache: Arc<Mutex<Cache>>,
+}
+
+#[derive(Debug)]
+struct Cache {
+    max_entries: NonZeroUsize,
+    entries: HashMap<PartitionId, Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    cached: Cached,
+    expires: Time,
+}
+
+#[derive(Debug, Clone)]
+enum Cached {
+    /// Files fetched without their partition.
+    Files(FetchedFiles),
+
+    PartitionFiles(PartitionFiles),
+}
+
+impl From<Cached> for FetchedFiles {
+    fn from(cached: Cached) -> Self {
+        match cached {
+            Cached::Files(files) => files,
+            Cached::PartitionFiles(partition_files) => partition_files.into(),
+        }
+    }
+}
+
+impl Cache {
+    /// Make room for a new entry, evicting expired entries, or the entry expiring first.
+    fn make_room(&mut self, now: Time) {
+        if self.entries.len() < self.max_entries.get() {
+            return;
+        }
+
+        self.entries
+            .retain(|_partition_id, entry| entry.expires > now);
+        if self.entries.len() < self.max_entries.get() {
+            return;
+        }
+
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_partition_id, entry)| entry.expires)
+            .map(|(partition_id, _entry)| *partition_id);
+        if let Some(partition_id) = oldest {
+            self.entries.remove(&partition_id);
+        }
+    }
+}
+
+impl<T> CachingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    pub fn new(
+        inner: T,
+        ttl: Duration,
+        max_entries: NonZeroUsize,
+        time_provider: Arc<dyn TimeProvider>,
+    ) -> Self {
+        Self {
+            inner,
+            ttl,
+            time_provider,
+            cache: Arc::new(Mutex::new(Cache {
+                max_entries,
+                entries: HashMap::new(),
+            })),
+        }
+    }
+
+    /// The cached entry of a partition, unless it expired.
+    fn get(&self, partition_id: PartitionId) -> Option<Cached> {
+        let cache = self.cache.lock();
+        cache
+            .entries
+            .get(&partition_id)
+            .filter(|entry| entry.expires > self.time_provider.now())
+            .map(|entry| entry.cached.clone())
+    }
+
+    // Note: an invalidation while fetching may be overwritten with files fetched before it, but
+    // commits of a partition and its fetches are not concurrent.
+    fn insert(&self, partition_id: PartitionId, cached: Cached) {
+        let now = self.time_provider.now();
+        let mut cache = self.cache.lock();
+        cache.entries.remove(&partition_id);
+        cache.make_room(now);
+        cache.entries.insert(
+            partition_id,
+            Entry {
+                cached,
+                expires: now + self.ttl,
+            },
+        );
+    }
+
+    /// Handle to invalidate the entries of partitions whose files changed.
+    pub fn invalidator(&self) -> PartitionFilesInvalidator {
+        PartitionFilesInvalidator {
+            cache: Arc::clone(&self.cache),
+        }
+    }
+}
+
+impl<T> Display for CachingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "caching({}, ttl={:?})", self.inner, self.ttl)
+    }
+}
+
+#[async_trait]
+impl<T> PartitionFilesSource for CachingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    async fn fetch(&self, partition_id: PartitionId) -> Result<FetchedFiles, DynError> {
+        if let Some(cached) = self.get(partition_id) {
+            return Ok(cached.into());
+        }
+
+        // failures are not cached
+        let files = self.inner.fetch(partition_id).await?;
+        self.insert(partition_id, Cached::Files(files.clone()));
+
+        Ok(files)
+    }
+
+    async fn fetch_with_partition_info(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<PartitionFiles, DynError> {
+        if let Some(Cached::PartitionFiles(partition_files)) = self.get(partition_id) {
+            return Ok(partition_files);
+        }
+
+        let partition_files = self.inner.fetch_with_partition_info(partition_id).await?;
+        self.insert(
+            partition_id,
+            Cached::PartitionFiles(partition_files.clone()),
+        );
+
+        Ok(partition_files)
+    }
+
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        let mut files = HashMap::with_capacity(partitions.len());
+        let mut missing = vec![];
+        for partition_id in partitions {
+            match self.get(*partition_id) {
+                Some(Cached::PartitionFiles(partition_files)) => {
+                    files.insert(*partition_id, partition_files);
+                }
+                _ => missing.push(*partition_id),
+            }
+        }
+
+        if !missing.is_empty() {
+            for (partition_id, partition_files) in self.inner.fetch_many(&missing).await? {
+                self.insert(
+                    partition_id,
+                    Cached::PartitionFiles(partition_files.clone()),
+                );
+                files.insert(partition_id, partition_files);
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+/// Invalidates entries of a [`CachingPartitionFilesSource`].
+#[derive(Debug, Clone)]
+pub struct PartitionFilesInvalidator {
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl PartitionFilesInvalidator {
+    /// Forget the files of a partition, so that the next fetch gets them from the inner source.
+    pub fn invalidate(&self, partition_id: PartitionId) {
+        self.cache.lock().entries.remove(&partition_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_tests::{partition_identifier, ParquetFileBuilder, PartitionBuilder};
+    use iox_time::MockProvider;
+
+    use crate::{
+        components::partition_files_source::mock::{MockFetch, MockPartitionFilesSource},
+        error::ErrorKind,
+    };
+
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(5);
+
+    /// Caching source of up to 2 partitions, over a mock with 3 partitions of a file each.
+    fn source() -> (
+        CachingPartitionFilesSource<Arc<MockPartitionFilesSource>>,
+        Arc<MockPartitionFilesSource>,
+        Arc<MockProvider>,
+    ) {
+        let partition_lookup = (1..=3)
+            .map(|i| (PartitionId::new(i), partition_identifier(i)))
+            .collect();
+        let files = (1..=3)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_partition(partition_identifier(i))
+                    .build()
+            })
+            .collect();
+        let inner = Arc::new(
+            MockPartitionFilesSource::new(partition_lookup, files)
+                .with_partitions((1..=3).map(|i| PartitionBuilder::new(i).build())),
+        );
+        let time_provider = Arc::new(MockProvider::new(Time::MIN));
+        let source = CachingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(2).unwrap(),
+            Arc::clone(&time_provider) as _,
+        );
+        (source, inner, time_provider)
+    }
+
+    #[test]
+    fn test_display() {
+        let (source, _inner, _time_provider) = source();
+        assert_eq!(source.to_string(), "caching(mock, ttl=5s)");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_within_ttl() {
+        let (source, inner, time_provider) = source();
+        let p1 = PartitionId::new(1);
+
+        let files = source.fetch(p1).await.unwrap().files;
+        assert_eq!(files.len(), 1);
+        assert_eq!(inner.fetches(), 1);
+
+        time_provider.inc(TTL - Duration::from_millis(1));
+        assert_eq!(source.fetch(p1).await.unwrap().files, files);
+        assert_eq!(inner.fetches(), 1);
+
+        // expired
+        time_provider.inc(Duration::from_millis(1));
+        assert_eq!(source.fetch(p1).await.unwrap().files, files);
+        assert_eq!(inner.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate() {
+        let (source, inner, _time_provider) = source();
+        let invalidator = source.invalidator();
+        let (p1, p2) = (PartitionId::new(1), PartitionId::new(2));
+
+        source.fetch(p1).await.unwrap();
+        source.fetch(p2).await.unwrap();
+        assert_eq!(inner.fetches(), 2);
+
+        invalidator.invalidate(p1);
+        source.fetch(p1).await.unwrap();
+        source.fetch(p2).await.unwrap();
+        assert_eq!(inner.fetches(), 3);
+
+        // unknown partitions are fine
+        invalidator.invalidate(PartitionId::new(42));
+    }
+
+    #[tokio::test]
+    async fn test_max_entries() {
+        let (source, inner, time_provider) = source();
+        let (p1, p2, p3) = (
+            PartitionId::new(1),
+            PartitionId::new(2),
+            PartitionId::new(3),
+        );
+
+        source.fetch(p1).await.unwrap();
+        time_provider.inc(Duration::from_millis(1));
+        source.fetch(p2).await.unwrap();
+        time_provider.inc(Duration::from_millis(1));
+
+        // evicts the partition expiring first
+        source.fetch(p3).await.unwrap();
+        assert_eq!(inner.fetches(), 3);
+        source.fetch(p2).await.unwrap();
+        source.fetch(p3).await.unwrap();
+        assert_eq!(inner.fetches(), 3);
+        source.fetch(p1).await.unwrap();
+        assert_eq!(inner.fetches(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many() {
+        let (source, inner, _time_provider) = source();
+        let (p1, p2) = (PartitionId::new(1), PartitionId::new(2));
+        source.fetch_with_partition_info(p1).await.unwrap();
+        assert_eq!(inner.fetches(), 1);
+
+        // only the missing partitions are fetched...
+        let files = source.fetch_many(&[p1, p2]).await.unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[&p1].partition.id, p1);
+        assert_eq!(files[&p2].partition.id, p2);
+        assert_eq!(files[&p2].files.len(), 1);
+        assert_eq!(inner.fetches(), 2);
+        assert_eq!(inner.batches(), vec![vec![p2]]);
+
+        // ...and cached with their partition
+        source.fetch(p2).await.unwrap();
+        source.fetch_with_partition_info(p2).await.unwrap();
+        assert_eq!(inner.fetches(), 2);
+
+        // unknown partitions fail
+        source
+            .fetch_many(&[PartitionId::new(42)])
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_partition_info() {
+        let p1 = PartitionId::new(1);
+        let file = |id| {
+            ParquetFileBuilder::new(id)
+                .with_partition(partition_identifier(1))
+                .build()
+        };
+        let inner = Arc::new(
+            MockPartitionFilesSource::new(Default::default(), Default::default())
+                .with_partitions([PartitionBuilder::new(1).build()])
+                .with_script(
+                    p1,
+                    [
+                        MockFetch::files(vec![file(1)]),
+                        MockFetch::files(vec![file(2)]),
+                    ],
+                ),
+        );
+        let source = CachingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(2).unwrap(),
+            Arc::new(MockProvider::new(Time::MIN)),
+        );
+
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file(1)]);
+
+        // files cached without their partition are fetched again...
+        let partition_files = source.fetch_with_partition_info(p1).await.unwrap();
+        assert_eq!(partition_files.files, vec![file(2)]);
+        assert_eq!(partition_files.partition.id, p1);
+        assert_eq!(inner.fetches(), 2);
+
+        // ...and then cached with it
+        assert_eq!(
+            source.fetch_with_partition_info(p1).await.unwrap(),
+            partition_files
+        );
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file(2)]);
+        assert_eq!(inner.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failures_not_cached() {
+        let inner = Arc::new(
+            MockPartitionFilesSource::new(Default::default(), Default::default())
+                .with_error(ErrorKind::Catalog, "foo"),
+        );
+        let source = CachingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(2).unwrap(),
+            Arc::new(MockProvider::new(Time::MIN)),
+        );
+
+        source.fetch(PartitionId::new(1)).await.unwrap_err();
+        source.fetch(PartitionId::new(1)).await.unwrap_err();
+        assert_eq!(inner.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_failure() {
+        let p1 = PartitionId::new(1);
+        let file = ParquetFileBuilder::new(1)
+            .with_partition(partition_identifier(1))
+            .build();
+        let inner = Arc::new(
+            MockPartitionFilesSource::new(Default::default(), Default::default()).with_script(
+                p1,
+                [
+                    MockFetch::error(ErrorKind::Catalog, "foo"),
+                    MockFetch::files(vec![file.clone()]),
+                ],
+            ),
+        );
+        let source = CachingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(2).unwrap(),
+            Arc::new(MockProvider::new(Time::MIN)),
+        );
+
+        source.fetch(p1).await.unwrap_err();
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file.clone()]);
+
+        // the files fetched by the retry are cached
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file]);
+        assert_eq!(inner.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_sees_new_files() {
+        let p1 = PartitionId::new(1);
+        let file = |id| {
+            ParquetFileBuilder::new(id)
+                .with_partition(partition_identifier(1))
+                .build()
+        };
+        let inner = Arc::new(
+            MockPartitionFilesSource::new(Default::default(), Default::default()).with_script(
+                p1,
+                [
+                    MockFetch::files(vec![file(1)]),
+                    MockFetch::files(vec![file(2)]),
+                ],
+            ),
+        );
+        let source = CachingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(2).unwrap(),
+            Arc::new(MockProvider::new(Time::MIN)),
+        );
+
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file(1)]);
+
+        // a commit replaced file 1 with file 2
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file(1)]);
+        source.invalidator().invalidate(p1);
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file(2)]);
+        assert_eq!(inner.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_fetch_not_cached() {
+        let p1 = PartitionId::new(1);
+        let inner = Arc::new(
+            MockPartitionFilesSource::new(Default::default(), Default::default()).with_script(
+                p1,
+                [MockFetch::files(vec![]).with_delay(Duration::from_secs(60))],
+            ),
+        );
+        let source = CachingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(2).unwrap(),
+            Arc::new(MockProvider::new(Time::MIN)),
+        );
+
+        tokio::time::timeout(Duration::from_millis(10), source.fetch(p1))
+            .await
+            .unwrap_err();
+
+        // nothing was cached, so the partition is fetched again
+        source.fetch(p1).await.unwrap();
+        source.fetch(p1).await.unwrap();
+        assert_eq!(inner.fetches(), 2);
+    }
+}
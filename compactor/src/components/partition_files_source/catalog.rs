@@ -1,15 +1,23 @@
 use std::{
+    collections::{HashMap, HashSet},
     fmt::{Debug, Display},
+    future::Future,
+    num::NonZeroUsize,
     sync::Arc,
+    time::Duration,
 };
 
 use async_trait::async_trait;
 use backoff::{Backoff, BackoffConfig};
-use data_types::{ParquetFile, PartitionId, TransitionPartitionId};
-use iox_catalog::interface::Catalog;
+use data_types::{
+    CompactionLevel, ParquetFile, ParquetFileId, Partition, PartitionId, TransitionPartitionId,
+};
+use iox_catalog::{interface::Catalog, partition_lookup};
 use observability_deps::tracing::warn;
 
-use super::{rate_limit::RateLimit, PartitionFilesSource};
+use crate::error::{DynError, ErrorKind, SimpleError};
+
+use super::{rate_limit::RateLimit, FetchedFiles, PartitionFiles, PartitionFilesSource};
 
 #[async_trait]
 pub(crate) trait CatalogQuerier: Send + Sync + Debug {
@@ -17,6 +25,37 @@ pub(crate) trait CatalogQuerier: Send + Sync + Debug {
         &self,
         partition_id: PartitionId,
     ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error>;
+
+    /// Get the files of a partition together with the partition, with a single borrow of the
+    /// repositories.
+    ///
+    /// With a `limit`, only the first page of files is returned, see
+    /// [`get_partition_files_page`](Self::get_partition_files_page).
+    ///
+    /// Returns `None` if the partition does not exist.
+    async fn get_partition_with_files(
+        &self,
+        partition_id: PartitionId,
+        limit: Option<usize>,
+    ) -> Result<Option<(Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error>;
+
+    /// Get a page of at most `limit` files of a partition, ordered by ID, starting after the
+    /// file with ID `after`.
+    async fn get_partition_files_page(
+        &self,
+        partition_id: PartitionId,
+        after: Option<ParquetFileId>,
+        limit: usize,
+    ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error>;
+
+    /// Get the files of several partitions in a single query, grouped by partition, together
+    /// with the partitions.
+    ///
+    /// Every given partition that exists is in the result, with no files if it has none.
+    async fn get_partitions_batch(
+        &self,
+        partition_ids: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, (Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error>;
 }
 
 /// a QueryRateLimiter applies a RateLimit to a CatalogQuerier.
@@ -41,15 +80,60 @@ where
         &self,
         partition_id: PartitionId,
     ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error> {
-        while let Some(d) = self.rate_limit.can_proceed() {
-            warn!(%partition_id, "partition fetch rate limited");
-
-            // Don't busy loop - wait the fractions of a second before a retry
-            // is allowed.
-            tokio::time::sleep(d).await;
+        if let Some(waited) = self.rate_limit.acquire(&[partition_id]).await {
+            warn!(%partition_id, ?waited, "partition fetch rate limited");
         }
         self.inner.get_partitions(partition_id).await
     }
+
+    async fn get_partition_with_files(
+        &self,
+        partition_id: PartitionId,
+        limit: Option<usize>,
+    ) -> Result<Option<(Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error> {
+        if let Some(waited) = self.rate_limit.acquire(&[partition_id]).await {
+            warn!(%partition_id, ?waited, "partition fetch rate limited");
+        }
+        self.inner
+            .get_partition_with_files(partition_id, limit)
+            .await
+    }
+
+    async fn get_partition_files_page(
+        &self,
+        partition_id: PartitionId,
+        after: Option<ParquetFileId>,
+        limit: usize,
+    ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error> {
+        // The partition interval spaces out the fetches of a partition as a whole, so only the
+        // first page waits for it. All pages count against the rate.
+        let partitions = if after.is_none() {
+            vec![partition_id]
+        } else {
+            vec![]
+        };
+        if let Some(waited) = self.rate_limit.acquire(&partitions).await {
+            warn!(%partition_id, ?waited, "partition fetch rate limited");
+        }
+        self.inner
+            .get_partition_files_page(partition_id, after, limit)
+            .await
+    }
+
+    async fn get_partitions_batch(
+        &self,
+        partition_ids: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, (Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error>
+    {
+        if let Some(waited) = self.rate_limit.acquire(partition_ids).await {
+            warn!(
+                partitions = partition_ids.len(),
+                ?waited,
+                "partition batch fetch rate limited"
+            );
+        }
+        self.inner.get_partitions_batch(partition_ids).await
+    }
 }
 
 #[async_trait]
@@ -64,12 +148,122 @@ impl CatalogQuerier for Arc<dyn Catalog> {
             .list_by_partition_not_to_delete(&TransitionPartitionId::Deprecated(partition_id))
             .await
     }
+
+    async fn get_partition_with_files(
+        &self,
+        partition_id: PartitionId,
+        limit: Option<usize>,
+    ) -> Result<Option<(Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error> {
+        let mut repos = self.repositories().await;
+        let id = TransitionPartitionId::Deprecated(partition_id);
+
+        let Some(partition) = partition_lookup(repos.as_mut(), &id).await? else {
+            return Ok(None);
+        };
+        let files = match limit {
+            Some(limit) => {
+                repos
+                    .parquet_files()
+                    .list_by_partition_not_to_delete_page(&id, None, limit)
+                    .await?
+            }
+            None => {
+                repos
+                    .parquet_files()
+                    .list_by_partition_not_to_delete(&id)
+                    .await?
+            }
+        };
+
+        Ok(Some((files, partition)))
+    }
+
+    async fn get_partition_files_page(
+        &self,
+        partition_id: PartitionId,
+        after: Option<ParquetFileId>,
+        limit: usize,
+    ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error> {
+        self.repositories()
+            .await
+            .parquet_files()
+            .list_by_partition_not_to_delete_page(
+                &TransitionPartitionId::Deprecated(partition_id),
+                after,
+                limit,
+            )
+            .await
+    }
+
+    async fn get_partitions_batch(
+        &self,
+        partition_ids: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, (Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error>
+    {
+        let mut repos = self.repositories().await;
+
+        // files refer to their partition by hash ID if they have one, by catalog ID otherwise
+        let partitions = repos
+            .partitions()
+            .get_by_id_batch(partition_ids.to_vec())
+            .await?;
+        let mut ids = HashMap::with_capacity(partitions.len() * 2);
+        for partition in &partitions {
+            ids.insert(
+                TransitionPartitionId::Deprecated(partition.id),
+                partition.id,
+            );
+            if let Some(hash_id) = partition.hash_id() {
+                ids.insert(
+                    TransitionPartitionId::Deterministic(hash_id.clone()),
+                    partition.id,
+                );
+            }
+        }
+
+        let mut files: HashMap<_, _> = partitions
+            .into_iter()
+            .map(|partition| (partition.id, (vec![], partition)))
+            .collect();
+        for file in repos
+            .parquet_files()
+            .list_by_partitions_not_to_delete(partition_ids)
+            .await?
+        {
+            // partitions are never deleted, so the partitions of the files were listed above
+            let partition_id = ids
+                .get(&file.partition_id)
+                .expect("partition of file listed");
+            files
+                .get_mut(partition_id)
+                .expect("partition requested")
+                .0
+                .push(file);
+        }
+
+        Ok(files)
+    }
 }
 
+/// Default for [`CatalogPartitionFilesSource::with_max_partitions_per_query`].
+pub const DEFAULT_MAX_PARTITIONS_PER_QUERY: usize = 100;
+
 #[derive(Debug)]
 pub struct CatalogPartitionFilesSource<T = QueryRateLimiter<Arc<dyn Catalog>>> {
     backoff_config: BackoffConfig,
     catalog: T,
+
+    /// Overall time after which retrying a fetch gives up, or retry forever.
+    deadline: Option<Duration>,
+
+    /// Maximum number of partitions whose files are fetched in a single query.
+    max_partitions_per_query: NonZeroUsize,
+
+    /// Number of files of a partition fetched per query, or all of them at once.
+    page_size: Option<NonZeroUsize>,
+
+    /// Maximum number of files fetched for a partition, or no limit.
+    max_files: Option<NonZeroUsize>,
 }
 
 impl<T> CatalogPartitionFilesSource<T> {
@@ -77,10 +271,163 @@ impl<T> CatalogPartitionFilesSource<T> {
         Self {
             backoff_config,
             catalog,
+            deadline: None,
+            max_partitions_per_query: NonZeroUsize::new(DEFAULT_MAX_PARTITIONS_PER_QUERY)
+                .expect("not zero"),
+            page_size: None,
+            max_files: None,
+        }
+    }
+
+    /// Give up fetching the files of a partition after `deadline`, retries included.
+    pub fn with_deadline(self, deadline: Duration) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    /// Fetch the files of at most `max_partitions_per_query` partitions in a single query.
+    pub fn with_max_partitions_per_query(self, max_partitions_per_query: NonZeroUsize) -> Self {
+        Self {
+            max_partitions_per_query,
+            ..self
+        }
+    }
+
+    /// Page through the files of a partition, fetching at most `page_size` of them per query.
+    ///
+    /// Batches of partitions are then fetched one partition at a time.
+    pub fn with_page_size(self, page_size: NonZeroUsize) -> Self {
+        Self {
+            page_size: Some(page_size),
+            ..self
+        }
+    }
+
+    /// Keep at most `max_files` L0 files of a partition, the oldest ones (by `max_l0_created_at`).
+    /// Partitions with more L0 files are fetched [truncated](PartitionFiles::truncated).
+    ///
+    /// Files of other levels are never dropped: the kept L0 files may overlap any of them. So all
+    /// files are still fetched, only the result is capped.
+    pub fn with_max_files(self, max_files: NonZeroUsize) -> Self {
+        Self {
+            max_files: Some(max_files),
+            ..self
+        }
+    }
+
+    /// Maximum number of files to fetch per query, or `None` for all of them.
+    fn page_limit(&self) -> Option<usize> {
+        self.page_size.map(NonZeroUsize::get)
+    }
+
+    /// Only keep the oldest L0 files of a partition that has more than the maximum number of L0
+    /// files, and all files of other levels. Returns whether any were dropped.
+    fn cap(&self, partition_id: PartitionId, files: &mut Vec<ParquetFile>) -> bool {
+        let Some(max_files) = self.max_files else {
+            return false;
+        };
+        let mut l0_files: Vec<_> = files
+            .iter()
+            .filter(|f| f.compaction_level == CompactionLevel::Initial)
+            .map(|f| (f.max_l0_created_at, f.id))
+            .collect();
+        if l0_files.len() <= max_files.get() {
+            return false;
+        }
+
+        warn!(
+            %partition_id,
+            max_files = max_files.get(),
+            l0_files = l0_files.len(),
+            "partition has too many L0 files, only fetched the oldest ones",
+        );
+        l0_files.sort();
+        let dropped: HashSet<_> = l0_files[max_files.get()..]
+            .iter()
+            .map(|(_max_l0_created_at, id)| *id)
+            .collect();
+        files.retain(|f| !dropped.contains(&f.id));
+        files.sort_by_key(|f| f.id);
+        true
+    }
+
+    /// Wait for `fut`, unless it takes longer than the deadline.
+    async fn within_deadline<F>(&self, fut: F, what: impl Display) -> Result<F::Output, DynError>
+    where
+        F: Future + Send,
+    {
+        match self.deadline {
+            Some(deadline) => tokio::time::timeout(deadline, fut).await.map_err(|_| {
+                Box::new(SimpleError::new(
+                    ErrorKind::Catalog,
+                    format!("fetching the files of {what} timed out after {deadline:?}"),
+                )) as _
+            }),
+            None => Ok(fut.await),
         }
     }
 }
 
+impl<T> CatalogPartitionFilesSource<T>
+where
+    T: CatalogQuerier,
+{
+    /// Fetch the remaining pages of files of a partition, after its `files` fetched with `limit`,
+    /// and [cap](Self::cap) them. Returns whether the files are truncated.
+    async fn fetch_remaining_pages(
+        &self,
+        partition_id: PartitionId,
+        files: &mut Vec<ParquetFile>,
+        limit: Option<usize>,
+    ) -> bool {
+        let mut page_len = files.len();
+        if let Some(page_limit) = limit {
+            while page_len >= page_limit {
+                // pages are ordered by ID
+                let after = files.last().map(|f| f.id);
+                let page = Backoff::new(&self.backoff_config)
+                    .retry_all_errors("parquet_files_of_given_partition_page", || async {
+                        self.catalog
+                            .get_partition_files_page(partition_id, after, page_limit)
+                            .await
+                    })
+                    .await
+                    .expect("retry forever");
+                page_len = page.len();
+                files.extend(page);
+            }
+        }
+
+        self.cap(partition_id, files)
+    }
+
+    /// Fetch the files of a partition page by page, together with the partition.
+    ///
+    /// Returns `None` if the partition does not exist.
+    async fn fetch_paged(&self, partition_id: PartitionId) -> Option<PartitionFiles> {
+        let limit = self.page_limit();
+        let (mut files, partition) = Backoff::new(&self.backoff_config)
+            .retry_all_errors("partition_with_parquet_files", || async {
+                self.catalog
+                    .get_partition_with_files(partition_id, limit)
+                    .await
+            })
+            .await
+            .expect("retry forever")?;
+        let truncated = self
+            .fetch_remaining_pages(partition_id, &mut files, limit)
+            .await;
+
+        Some(PartitionFiles {
+            files,
+            partition,
+            truncated,
+        })
+    }
+}
+
 impl<T> Display for CatalogPartitionFilesSource<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "catalog")
@@ -92,19 +439,100 @@ impl<T> PartitionFilesSource for CatalogPartitionFilesSource<T>
 where
     T: CatalogQuerier,
 {
-    async fn fetch(&self, partition_id: PartitionId) -> Vec<ParquetFile> {
-        Backoff::new(&self.backoff_config)
-            .retry_all_errors("parquet_files_of_given_partition", || async {
-                self.catalog.get_partitions(partition_id).await
-            })
+    async fn fetch(&self, partition_id: PartitionId) -> Result<FetchedFiles, DynError> {
+        let fut = async {
+            let limit = self.page_limit();
+            let mut files = Backoff::new(&self.backoff_config)
+                .retry_all_errors("parquet_files_of_given_partition", || async {
+                    match limit {
+                        Some(limit) => {
+                            self.catalog
+                                .get_partition_files_page(partition_id, None, limit)
+                                .await
+                        }
+                        None => self.catalog.get_partitions(partition_id).await,
+                    }
+                })
+                .await
+                .expect("retry forever");
+            let truncated = self
+                .fetch_remaining_pages(partition_id, &mut files, limit)
+                .await;
+            FetchedFiles { files, truncated }
+        };
+
+        self.within_deadline(fut, format!("partition {partition_id}"))
             .await
-            .expect("retry forever")
+    }
+
+    async fn fetch_with_partition_info(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<PartitionFiles, DynError> {
+        self.within_deadline(
+            self.fetch_paged(partition_id),
+            format!("partition {partition_id}"),
+        )
+        .await?
+        .ok_or_else::<DynError, _>(|| String::from("Cannot find partition info").into())
+    }
+
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        let fut = async {
+            let mut files = HashMap::with_capacity(partitions.len());
+
+            // a batch query returns all files of its partitions, so page them one at a time
+            if self.page_size.is_some() {
+                for partition_id in partitions {
+                    if let Some(partition_files) = self.fetch_paged(*partition_id).await {
+                        files.insert(*partition_id, partition_files);
+                    }
+                }
+                return files;
+            }
+
+            for chunk in partitions.chunks(self.max_partitions_per_query.get()) {
+                let chunk_files = Backoff::new(&self.backoff_config)
+                    .retry_all_errors("parquet_files_of_given_partitions", || async {
+                        self.catalog.get_partitions_batch(chunk).await
+                    })
+                    .await
+                    .expect("retry forever");
+                for (partition_id, (mut partition_files, partition)) in chunk_files {
+                    let truncated = self.cap(partition_id, &mut partition_files);
+                    files.insert(
+                        partition_id,
+                        PartitionFiles {
+                            files: partition_files,
+                            partition,
+                            truncated,
+                        },
+                    );
+                }
+            }
+            files
+        };
+
+        let files = self
+            .within_deadline(fut, format!("{} partitions", partitions.len()))
+            .await?;
+        match partitions.iter().find(|id| !files.contains_key(id)) {
+            Some(partition_id) => {
+                Err(format!("Cannot find partition info of {partition_id}").into())
+            }
+            None => Ok(files),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorKindExt;
+    use iox_tests::{PartitionBuilder, TestCatalog, TestParquetFileBuilder};
     use std::{sync::Mutex, time::Duration};
     use tokio::time::Instant;
 
@@ -121,6 +549,497 @@ mod tests {
             *self.0.lock().unwrap() += 1;
             Ok(vec![])
         }
+
+        async fn get_partition_with_files(
+            &self,
+            partition_id: PartitionId,
+            _limit: Option<usize>,
+        ) -> Result<Option<(Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error> {
+            *self.0.lock().unwrap() += 1;
+            Ok(Some((
+                vec![],
+                PartitionBuilder::new(partition_id.get()).build(),
+            )))
+        }
+
+        async fn get_partition_files_page(
+            &self,
+            _partition_id: PartitionId,
+            _after: Option<ParquetFileId>,
+            _limit: usize,
+        ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error> {
+            *self.0.lock().unwrap() += 1;
+            Ok(vec![])
+        }
+
+        async fn get_partitions_batch(
+            &self,
+            partition_ids: &[PartitionId],
+        ) -> Result<
+            HashMap<PartitionId, (Vec<ParquetFile>, Partition)>,
+            iox_catalog::interface::Error,
+        > {
+            *self.0.lock().unwrap() += 1;
+            Ok(partition_ids
+                .iter()
+                .map(|id| (*id, (vec![], PartitionBuilder::new(id.get()).build())))
+                .collect())
+        }
+    }
+
+    /// A [`CatalogQuerier`] that always fails.
+    #[derive(Debug)]
+    struct FailingInner;
+    #[async_trait]
+    impl CatalogQuerier for FailingInner {
+        async fn get_partitions(
+            &self,
+            _partition_id: PartitionId,
+        ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error> {
+            Err(iox_catalog::interface::Error::InvalidName { name: "foo".into() })
+        }
+
+        async fn get_partition_with_files(
+            &self,
+            _partition_id: PartitionId,
+            _limit: Option<usize>,
+        ) -> Result<Option<(Vec<ParquetFile>, Partition)>, iox_catalog::interface::Error> {
+            Err(iox_catalog::interface::Error::InvalidName { name: "foo".into() })
+        }
+
+        async fn get_partition_files_page(
+            &self,
+            _partition_id: PartitionId,
+            _after: Option<ParquetFileId>,
+            _limit: usize,
+        ) -> Result<Vec<ParquetFile>, iox_catalog::interface::Error> {
+            Err(iox_catalog::interface::Error::InvalidName { name: "foo".into() })
+        }
+
+        async fn get_partitions_batch(
+            &self,
+            _partition_ids: &[PartitionId],
+        ) -> Result<
+            HashMap<PartitionId, (Vec<ParquetFile>, Partition)>,
+            iox_catalog::interface::Error,
+        > {
+            Err(iox_catalog::interface::Error::InvalidName { name: "foo".into() })
+        }
+    }
+
+    fn backoff_config() -> BackoffConfig {
+        BackoffConfig {
+            init_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        let inner = MockInner::default();
+        let source = CatalogPartitionFilesSource::new(backoff_config(), &inner)
+            .with_deadline(Duration::from_secs(10));
+
+        assert_eq!(
+            source.fetch(PartitionId::new(1)).await.unwrap().files,
+            vec![]
+        );
+        assert_eq!(*inner.0.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_deadline() {
+        let source = CatalogPartitionFilesSource::new(backoff_config(), FailingInner)
+            .with_deadline(Duration::from_millis(50));
+
+        let start = Instant::now();
+        let err = source.fetch(PartitionId::new(1)).await.unwrap_err();
+        assert!(Instant::now() - start >= Duration::from_millis(50));
+        assert_eq!(err.classify(), ErrorKind::Catalog);
+        assert_eq!(
+            err.to_string(),
+            "fetching the files of partition 1 timed out after 50ms"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_partition_info() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns.create_table("table").await;
+        let partition = table
+            .create_partition_with_sort_key("k1", &["tag1", "time"], &[1, 2])
+            .await;
+        let other = table.create_partition("other").await;
+        partition
+            .create_parquet_file_catalog_record(TestParquetFileBuilder::default())
+            .await;
+        partition
+            .create_parquet_file_catalog_record(TestParquetFileBuilder::default())
+            .await;
+        other
+            .create_parquet_file_catalog_record(TestParquetFileBuilder::default())
+            .await;
+
+        let source = CatalogPartitionFilesSource::new(backoff_config(), catalog.catalog());
+        let partition_id = partition.partition.id;
+        let PartitionFiles {
+            mut files,
+            partition: fetched,
+            truncated,
+        } = source
+            .fetch_with_partition_info(partition_id)
+            .await
+            .unwrap();
+        assert!(!truncated);
+
+        // the partition is the one of the files
+        assert_eq!(fetched.id, partition_id);
+        assert_eq!(fetched.hash_id(), partition.partition.hash_id());
+        assert_eq!(fetched.partition_key, partition.partition.partition_key);
+        assert_eq!(fetched.sort_key(), partition.partition.sort_key());
+        assert!(fetched.sort_key().is_some());
+        assert_eq!(files.len(), 2);
+        for file in &files {
+            assert_eq!(file.partition_id, fetched.transition_partition_id());
+            assert_eq!(file.table_id, fetched.table_id);
+        }
+
+        // same files as fetched alone
+        let mut single = source.fetch(partition_id).await.unwrap().files;
+        single.sort_by_key(|f| f.id);
+        files.sort_by_key(|f| f.id);
+        assert_eq!(files, single);
+
+        // unknown partition
+        let err = source
+            .fetch_with_partition_info(PartitionId::new(42))
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Cannot find partition info");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_partition_info_deadline() {
+        let source = CatalogPartitionFilesSource::new(backoff_config(), FailingInner)
+            .with_deadline(Duration::from_millis(50));
+
+        let err = source
+            .fetch_with_partition_info(PartitionId::new(1))
+            .await
+            .unwrap_err();
+        assert_eq!(err.classify(), ErrorKind::Catalog);
+    }
+
+    /// Partition with 5 files, one other deleted file, and another partition with a file.
+    async fn partition_with_files(catalog: &TestCatalog) -> (PartitionId, Vec<ParquetFile>) {
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns.create_table("table").await;
+        let partition = table.create_partition("k1").await;
+        let other = table.create_partition("other").await;
+
+        let mut files = vec![];
+        for i in 0..6 {
+            let file = partition
+                .create_parquet_file_catalog_record(
+                    TestParquetFileBuilder::default().with_to_delete(i == 2),
+                )
+                .await
+                .parquet_file;
+            if i != 2 {
+                files.push(file);
+            }
+            other
+                .create_parquet_file_catalog_record(TestParquetFileBuilder::default())
+                .await;
+        }
+        files.sort_by_key(|f| f.id);
+
+        (partition.partition.id, files)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_paged() {
+        let catalog = TestCatalog::new();
+        let (partition_id, expected) = partition_with_files(&catalog).await;
+
+        for page_size in [1, 2, 5, 6] {
+            let source = CatalogPartitionFilesSource::new(backoff_config(), catalog.catalog())
+                .with_page_size(NonZeroUsize::new(page_size).unwrap());
+
+            let mut files = source.fetch(partition_id).await.unwrap().files;
+            files.sort_by_key(|f| f.id);
+            assert_eq!(files, expected, "page size {page_size}");
+
+            let partition_files = source
+                .fetch_with_partition_info(partition_id)
+                .await
+                .unwrap();
+            assert_eq!(partition_files.partition.id, partition_id);
+            assert_eq!(partition_files.files, expected, "page size {page_size}");
+            assert!(!partition_files.truncated);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_max_files() {
+        let catalog = TestCatalog::new();
+        let (partition_id, expected) = partition_with_files(&catalog).await;
+
+        for page_size in [None, Some(1), Some(2), Some(10)] {
+            let source = |max_files| {
+                let source = CatalogPartitionFilesSource::new(backoff_config(), catalog.catalog())
+                    .with_max_files(NonZeroUsize::new(max_files).unwrap());
+                match page_size {
+                    Some(page_size) => source.with_page_size(NonZeroUsize::new(page_size).unwrap()),
+                    None => source,
+                }
+            };
+
+            // only the oldest files
+            let partition_files = source(3)
+                .fetch_with_partition_info(partition_id)
+                .await
+                .unwrap();
+            assert_eq!(
+                partition_files.files,
+                expected[..3],
+                "page size {page_size:?}"
+            );
+            assert!(partition_files.truncated);
+
+            let fetched = source(3).fetch(partition_id).await.unwrap();
+            assert_eq!(fetched.files, expected[..3], "page size {page_size:?}");
+            assert!(fetched.truncated);
+
+            let mut files = source(3).fetch_many(&[partition_id]).await.unwrap();
+            let partition_files = files.remove(&partition_id).unwrap();
+            assert_eq!(
+                partition_files.files,
+                expected[..3],
+                "page size {page_size:?}"
+            );
+            assert!(partition_files.truncated);
+
+            // exactly at the cap
+            let partition_files = source(5)
+                .fetch_with_partition_info(partition_id)
+                .await
+                .unwrap();
+            assert_eq!(partition_files.files, expected, "page size {page_size:?}");
+            assert!(!partition_files.truncated);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_max_files_only_caps_l0_files() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns.create_table("table").await;
+        let partition = table.create_partition("k1").await;
+
+        // L0 files created later have smaller IDs, but are capped first
+        let mut files = vec![];
+        for (level, max_l0_created_at) in [
+            (CompactionLevel::Initial, 3),
+            (CompactionLevel::FileNonOverlapped, 10),
+            (CompactionLevel::Initial, 1),
+            (CompactionLevel::Final, 10),
+            (CompactionLevel::Initial, 2),
+        ] {
+            let file = partition
+                .create_parquet_file_catalog_record(
+                    TestParquetFileBuilder::default()
+                        .with_compaction_level(level)
+                        .with_max_l0_created_at(iox_time::Time::from_timestamp_nanos(
+                            max_l0_created_at,
+                        )),
+                )
+                .await
+                .parquet_file;
+            files.push(file);
+        }
+        files.sort_by_key(|f| f.id);
+        let expected = vec![
+            files[1].clone(),
+            files[2].clone(),
+            files[3].clone(),
+            files[4].clone(),
+        ];
+        let partition_id = partition.partition.id;
+
+        for page_size in [None, Some(1), Some(2)] {
+            let source = CatalogPartitionFilesSource::new(backoff_config(), catalog.catalog())
+                .with_max_files(NonZeroUsize::new(2).unwrap());
+            let source = match page_size {
+                Some(page_size) => source.with_page_size(NonZeroUsize::new(page_size).unwrap()),
+                None => source,
+            };
+
+            // the L1 and L2 files are never dropped, whatever their position
+            let fetched = source.fetch(partition_id).await.unwrap();
+            assert_eq!(fetched.files, expected, "page size {page_size:?}");
+            assert!(fetched.truncated);
+
+            let partition_files = source
+                .fetch_with_partition_info(partition_id)
+                .await
+                .unwrap();
+            assert_eq!(partition_files.files, expected, "page size {page_size:?}");
+            assert!(partition_files.truncated);
+
+            let partition_files = source
+                .fetch_many(&[partition_id])
+                .await
+                .unwrap()
+                .remove(&partition_id)
+                .unwrap();
+            assert_eq!(partition_files.files, expected, "page size {page_size:?}");
+            assert!(partition_files.truncated);
+        }
+
+        // with as many L0 files as the cap, nothing is truncated
+        let source = CatalogPartitionFilesSource::new(backoff_config(), catalog.catalog())
+            .with_max_files(NonZeroUsize::new(3).unwrap());
+        let fetched = source.fetch(partition_id).await.unwrap();
+        assert_eq!(fetched.files.len(), 5);
+        assert!(!fetched.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_paged_deadline() {
+        let source = CatalogPartitionFilesSource::new(backoff_config(), FailingInner)
+            .with_page_size(NonZeroUsize::new(2).unwrap())
+            .with_deadline(Duration::from_millis(50));
+
+        let err = source.fetch(PartitionId::new(1)).await.unwrap_err();
+        assert_eq!(err.classify(), ErrorKind::Catalog);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns.create_table("table").await;
+        let p1 = table.create_partition("k1").await;
+        let p2 = table.create_partition("k2").await;
+        let p3 = table.create_partition("k3").await;
+        let other = table.create_partition("other").await;
+
+        let mut expected_p1 = vec![];
+        for _ in 0..2 {
+            let file = p1
+                .create_parquet_file_catalog_record(TestParquetFileBuilder::default())
+                .await;
+            expected_p1.push(file.parquet_file.id);
+        }
+        p1.create_parquet_file_catalog_record(
+            TestParquetFileBuilder::default().with_to_delete(true),
+        )
+        .await;
+        let f3 = p3
+            .create_parquet_file_catalog_record(TestParquetFileBuilder::default())
+            .await;
+        other
+            .create_parquet_file_catalog_record(TestParquetFileBuilder::default())
+            .await;
+
+        // the files of the 3 partitions take 2 queries
+        let source = CatalogPartitionFilesSource::new(backoff_config(), catalog.catalog())
+            .with_max_partitions_per_query(NonZeroUsize::new(2).unwrap());
+        let (p1, p2, p3) = (p1.partition.id, p2.partition.id, p3.partition.id);
+        let files = source.fetch_many(&[p1, p2, p3]).await.unwrap();
+
+        assert_eq!(files.len(), 3);
+        let mut ids: Vec<_> = files[&p1].files.iter().map(|f| f.id).collect();
+        ids.sort();
+        expected_p1.sort();
+        assert_eq!(ids, expected_p1);
+        assert_eq!(files[&p2].files, vec![]);
+        assert_eq!(files[&p3].files, vec![f3.parquet_file]);
+
+        // same as fetching one partition at a time
+        for (partition_id, mut partition_files) in files {
+            let mut single = source
+                .fetch_with_partition_info(partition_id)
+                .await
+                .unwrap();
+            single.files.sort_by_key(|f| f.id);
+            partition_files.files.sort_by_key(|f| f.id);
+            assert_eq!(single, partition_files);
+        }
+
+        // unknown partitions fail
+        source
+            .fetch_many(&[p1, PartitionId::new(42)])
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many_chunks() {
+        let inner = MockInner::default();
+        let source = CatalogPartitionFilesSource::new(backoff_config(), &inner)
+            .with_max_partitions_per_query(NonZeroUsize::new(2).unwrap());
+
+        let partitions: Vec<_> = (1..=5).map(PartitionId::new).collect();
+        let files = source.fetch_many(&partitions).await.unwrap();
+        assert_eq!(files.len(), 5);
+        assert!(files
+            .values()
+            .all(|partition_files| partition_files.files.is_empty()));
+        assert_eq!(*inner.0.lock().unwrap(), 3);
+
+        assert!(source.fetch_many(&[]).await.unwrap().is_empty());
+        assert_eq!(*inner.0.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many_paged() {
+        let catalog = TestCatalog::new();
+        let (partition_id, expected) = partition_with_files(&catalog).await;
+
+        for page_size in [1, 2, 4] {
+            let source = CatalogPartitionFilesSource::new(backoff_config(), catalog.catalog())
+                .with_page_size(NonZeroUsize::new(page_size).unwrap());
+
+            let mut files = source.fetch_many(&[partition_id]).await.unwrap();
+            let partition_files = files.remove(&partition_id).unwrap();
+            assert_eq!(partition_files.partition.id, partition_id);
+            assert_eq!(partition_files.files, expected, "page size {page_size}");
+            assert!(!partition_files.truncated);
+
+            // unknown partitions still fail
+            source
+                .fetch_many(&[partition_id, PartitionId::new(42)])
+                .await
+                .unwrap_err();
+        }
+
+        // each partition is paged on its own, not in a batch
+        let inner = MockInner::default();
+        let source = CatalogPartitionFilesSource::new(backoff_config(), &inner)
+            .with_page_size(NonZeroUsize::new(2).unwrap());
+        let partitions: Vec<_> = (1..=3).map(PartitionId::new).collect();
+        assert_eq!(source.fetch_many(&partitions).await.unwrap().len(), 3);
+        assert_eq!(*inner.0.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many_deadline() {
+        let source = CatalogPartitionFilesSource::new(backoff_config(), FailingInner)
+            .with_deadline(Duration::from_millis(50));
+
+        let err = source
+            .fetch_many(&[PartitionId::new(1), PartitionId::new(2)])
+            .await
+            .unwrap_err();
+        assert_eq!(err.classify(), ErrorKind::Catalog);
+        assert_eq!(
+            err.to_string(),
+            "fetching the files of 2 partitions timed out after 50ms"
+        );
     }
 
     #[tokio::test]
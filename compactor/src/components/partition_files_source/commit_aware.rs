@@ -0,0 +1,351 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use data_types::{ParquetFile, ParquetFileId, PartitionId};
+use iox_time::{Time, TimeProvider};
+use parking_lot::Mutex;
+
+use crate::error::DynError;
+
+use super::{FetchedFiles, PartitionFiles, PartitionFilesSource};
+
+/// Overlays the recent commits of the compactor onto the files fetched from the inner source.
+///
+/// Right after a commit, the catalog may still return the files of a partition as they were
+/// before it, e.g. when reading from a replica. Planning the next round against that stale list
+/// would compact deleted files again and miss the created ones, so for a short window the commits
+/// [recorded](RecentCommits::record) by the commit path win over what the inner source returns.
+#[derive(Debug)]
+pub struct CommitAwarePartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    inner: T,
+    recent_commits: RecentCommits,
+}
+
+impl<T> CommitAwarePartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    pub fn new(inner: T, window: Duration, time_provider: Arc<dyn TimeProvider>) -> Self {
+        Self {
+            inner,
+            recent_commits: RecentCommits {
+                window,
+                time_provider,
+                partitions: Default::default(),
+            },
+        }
+    }
+
+    /// Handle for the commit path to record its commits.
+    pub fn recent_commits(&self) -> RecentCommits {
+        self.recent_commits.clone()
+    }
+}
+
+impl<T> Display for CommitAwarePartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "commit_aware({}, window={:?})",
+            self.inner, self.recent_commits.window
+        )
+    }
+}
+
+#[async_trait]
+impl<T> PartitionFilesSource for CommitAwarePartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    async fn fetch(&self, partition_id: PartitionId) -> Result<FetchedFiles, DynError> {
+        let fetched = self.inner.fetch(partition_id).await?;
+        Ok(FetchedFiles {
+            files: self.recent_commits.merge(partition_id, fetched.files),
+            ..fetched
+        })
+    }
+
+    async fn fetch_with_partition_info(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<PartitionFiles, DynError> {
+        let partition_files = self.inner.fetch_with_partition_info(partition_id).await?;
+        Ok(PartitionFiles {
+            files: self
+                .recent_commits
+                .merge(partition_id, partition_files.files),
+            ..partition_files
+        })
+    }
+
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        let files = self.inner.fetch_many(partitions).await?;
+        Ok(files
+            .into_iter()
+            .map(|(partition_id, partition_files)| {
+                let partition_files = PartitionFiles {
+                    files: self
+                        .recent_commits
+                        .merge(partition_id, partition_files.files),
+                    ..partition_files
+                };
+                (partition_id, partition_files)
+            })
+            .collect())
+    }
+}
+
+/// Commits of the last window, shared by a [`CommitAwarePartitionFilesSource`] and the commit
+/// path.
+#[derive(Debug, Clone)]
+pub struct RecentCommits {
+    window: Duration,
+    time_provider: Arc<dyn TimeProvider>,
+    partitions: Arc<Mutex<HashMap<PartitionId, PartitionCommits>>>,
+}
+
+/// What recent commits changed in a partition.
+#[derive(Debug, Default)]
+struct PartitionCommits {
+    /// Created or upgraded files, as they are after the commit.
+    upserted: HashMap<ParquetFileId, (ParquetFile, Time)>,
+
+    /// Deleted files.
+    deleted: HashMap<ParquetFileId, Time>,
+}
+
+impl PartitionCommits {
+    /// Forget the changes committed before `cutoff`.
+    fn age_out(&mut self, cutoff: Time) {
+        self.upserted.retain(|_id, (_file, at)| *at > cutoff);
+        self.deleted.retain(|_id, at| *at > cutoff);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.upserted.is_empty() && self.deleted.is_empty()
+    }
+}
+
+impl RecentCommits {
+    /// Record a successful commit to a partition.
+    ///
+    /// `upserted` are the created and upgraded files as they are after the commit, i.e. with their
+    /// new IDs and levels.
+    pub fn record(
+        &self,
+        partition_id: PartitionId,
+        deleted: impl IntoIterator<Item = ParquetFileId>,
+        upserted: impl IntoIterator<Item = ParquetFile>,
+    ) {
+        let now = self.time_provider.now();
+        let mut partitions = self.partitions.lock();
+        self.age_out(&mut partitions, now);
+
+        let commits = partitions.entry(partition_id).or_default();
+        for id in deleted {
+            // a file created by an earlier commit may be deleted by a later one
+            commits.upserted.remove(&id);
+            commits.deleted.insert(id, now);
+        }
+        for file in upserted {
+            commits.upserted.insert(file.id, (file, now));
+        }
+    }
+
+    /// Overlay the recent commits to a partition onto its fetched `files`.
+    fn merge(&self, partition_id: PartitionId, mut files: Vec<ParquetFile>) -> Vec<ParquetFile> {
+        let mut partitions = self.partitions.lock();
+        self.age_out(&mut partitions, self.time_provider.now());
+        let Some(commits) = partitions.get(&partition_id) else {
+            return files;
+        };
+
+        files.retain(|file| !commits.deleted.contains_key(&file.id));
+
+        let mut seen = HashSet::with_capacity(files.len());
+        for file in &mut files {
+            if let Some((upserted, _at)) = commits.upserted.get(&file.id) {
+                file.clone_from(upserted);
+            }
+            seen.insert(file.id);
+        }
+        files.extend(
+            commits
+                .upserted
+                .values()
+                .filter(|(file, _at)| !seen.contains(&file.id))
+                .map(|(file, _at)| file.clone()),
+        );
+
+        files
+    }
+
+    fn age_out(&self, partitions: &mut HashMap<PartitionId, PartitionCommits>, now: Time) {
+        let Some(cutoff) = now.checked_sub(self.window) else {
+            return;
+        };
+        partitions.retain(|_partition_id, commits| {
+            commits.age_out(cutoff);
+            !commits.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::CompactionLevel;
+    use iox_tests::{partition_identifier, ParquetFileBuilder, PartitionBuilder};
+    use iox_time::MockProvider;
+
+    use crate::components::partition_files_source::mock::{MockFetch, MockPartitionFilesSource};
+
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(10);
+
+    fn file(id: i64) -> ParquetFile {
+        ParquetFileBuilder::new(id)
+            .with_partition(partition_identifier(1))
+            .build()
+    }
+
+    /// Commit-aware source over a catalog that keeps returning files 1 and 2.
+    fn source() -> (
+        CommitAwarePartitionFilesSource<MockPartitionFilesSource>,
+        Arc<MockProvider>,
+    ) {
+        let inner = MockPartitionFilesSource::new(
+            HashMap::from([(PartitionId::new(1), partition_identifier(1))]),
+            vec![file(1), file(2)],
+        )
+        .with_partitions([PartitionBuilder::new(1).build()]);
+        let time_provider = Arc::new(MockProvider::new(Time::MIN));
+        let source =
+            CommitAwarePartitionFilesSource::new(inner, WINDOW, Arc::clone(&time_provider) as _);
+        (source, time_provider)
+    }
+
+    fn ids(files: &[ParquetFile]) -> Vec<i64> {
+        let mut ids: Vec<_> = files.iter().map(|f| f.id.get()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_display() {
+        let (source, _time_provider) = source();
+        assert_eq!(source.to_string(), "commit_aware(mock, window=10s)");
+    }
+
+    #[tokio::test]
+    async fn test_stale_read() {
+        let (source, time_provider) = source();
+        let recent_commits = source.recent_commits();
+        let p1 = PartitionId::new(1);
+
+        // compact file 1 into file 3, and upgrade file 2
+        let upgraded = ParquetFile {
+            compaction_level: CompactionLevel::Final,
+            ..file(2)
+        };
+        recent_commits.record(p1, [ParquetFileId::new(1)], [file(3), upgraded.clone()]);
+
+        // the catalog didn't see the commit yet
+        let files = source.fetch(p1).await.unwrap().files;
+        assert_eq!(ids(&files), vec![2, 3]);
+        assert!(files.contains(&upgraded));
+
+        let files = source.fetch_many(&[p1]).await.unwrap();
+        assert_eq!(ids(&files[&p1].files), vec![2, 3]);
+
+        // other partitions are untouched
+        let files = source.fetch(PartitionId::new(2)).await.unwrap().files;
+        assert!(files.is_empty());
+
+        // then the commit ages out
+        time_provider.inc(WINDOW);
+        let files = source.fetch(p1).await.unwrap().files;
+        assert_eq!(ids(&files), vec![1, 2]);
+        assert!(!files.contains(&upgraded));
+    }
+
+    #[tokio::test]
+    async fn test_dedup() {
+        let (source, _time_provider) = source();
+        let recent_commits = source.recent_commits();
+        let p1 = PartitionId::new(1);
+
+        // the catalog already returns the committed files
+        recent_commits.record(p1, [], [file(1), file(2)]);
+        let files = source.fetch(p1).await.unwrap().files;
+        assert_eq!(ids(&files), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_successive_commits() {
+        let (source, time_provider) = source();
+        let recent_commits = source.recent_commits();
+        let p1 = PartitionId::new(1);
+
+        recent_commits.record(p1, [ParquetFileId::new(1)], [file(3)]);
+        time_provider.inc(WINDOW / 2);
+
+        // file 3, created by the previous commit, is compacted again
+        recent_commits.record(
+            p1,
+            [ParquetFileId::new(2), ParquetFileId::new(3)],
+            [file(4)],
+        );
+        let files = source.fetch(p1).await.unwrap().files;
+        assert_eq!(ids(&files), vec![4]);
+
+        // only the first commit aged out
+        time_provider.inc(WINDOW / 2);
+        let files = source.fetch(p1).await.unwrap().files;
+        assert_eq!(ids(&files), vec![1, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_catalog_catches_up() {
+        let p1 = PartitionId::new(1);
+        let inner = MockPartitionFilesSource::new(Default::default(), Default::default())
+            .with_script(
+                p1,
+                [
+                    // stale
+                    MockFetch::files(vec![file(1), file(2)]),
+                    // caught up
+                    MockFetch::files(vec![file(2), file(3)]),
+                    MockFetch::files(vec![file(2), file(3)]),
+                ],
+            );
+        let time_provider = Arc::new(MockProvider::new(Time::MIN));
+        let source =
+            CommitAwarePartitionFilesSource::new(inner, WINDOW, Arc::clone(&time_provider) as _);
+
+        // compact file 1 into file 3
+        source
+            .recent_commits()
+            .record(p1, [ParquetFileId::new(1)], [file(3)]);
+
+        // the files are the same, whether the catalog saw the commit or not
+        assert_eq!(ids(&source.fetch(p1).await.unwrap().files), vec![2, 3]);
+        assert_eq!(ids(&source.fetch(p1).await.unwrap().files), vec![2, 3]);
+        time_provider.inc(WINDOW);
+        assert_eq!(ids(&source.fetch(p1).await.unwrap().files), vec![2, 3]);
+    }
+}
@@ -0,0 +1,407 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use async_trait::async_trait;
+use data_types::{CompactionLevel, ParquetFile, PartitionId};
+use metric::{Registry, U64Counter};
+
+use crate::error::DynError;
+
+use super::{PartitionFiles, PartitionFilesSource};
+
+const METRIC_NAME_PARTITION_FILES_FILTERED: &str = "iox_compactor_partition_files_filtered";
+
+/// Drops files that can never take part in a compaction right when they are fetched, so the
+/// rounds don't spend time classifying them.
+///
+/// Only files at [`CompactionLevel::Final`] that overlap no other file of the partition are ever
+/// dropped: compacting the other files must still see every file they overlap. Such files are
+/// dropped if they are larger than the maximum file size, or if their level is excluded. Nothing
+/// is dropped from a [truncated](PartitionFiles::truncated) list of files.
+#[derive(Debug)]
+pub struct FilteringPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    inner: T,
+    max_file_size_bytes: Option<u64>,
+    excluded_levels: BTreeSet<CompactionLevel>,
+
+    /// Files dropped so far, also reported by [`Display`].
+    dropped: AtomicUsize,
+    too_large_counter: U64Counter,
+    excluded_level_counter: U64Counter,
+}
+
+impl<T> FilteringPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    /// Filter the files of `inner`, which drops nothing until predicates are added.
+    pub fn new(inner: T, registry: &Registry) -> Self {
+        let metric = registry.register_metric::<U64Counter>(
+            METRIC_NAME_PARTITION_FILES_FILTERED,
+            "Number of files that were dropped when fetching the files of a partition",
+        );
+        let too_large_counter = metric.recorder(&[("reason", "too_large")]);
+        let excluded_level_counter = metric.recorder(&[("reason", "excluded_level")]);
+
+        Self {
+            inner,
+            max_file_size_bytes: None,
+            excluded_levels: BTreeSet::new(),
+            dropped: AtomicUsize::new(0),
+            too_large_counter,
+            excluded_level_counter,
+        }
+    }
+
+    /// Drop non-overlapping files larger than `max_file_size_bytes`.
+    pub fn with_max_file_size_bytes(self, max_file_size_bytes: u64) -> Self {
+        Self {
+            max_file_size_bytes: Some(max_file_size_bytes),
+            ..self
+        }
+    }
+
+    /// Drop non-overlapping files of the given levels.
+    ///
+    /// Since only [`CompactionLevel::Final`] files are ever dropped, excluding other levels has no
+    /// effect.
+    pub fn with_excluded_levels(
+        self,
+        excluded_levels: impl IntoIterator<Item = CompactionLevel>,
+    ) -> Self {
+        Self {
+            excluded_levels: excluded_levels.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Filter the files of a partition, unless they are truncated: the files that were not fetched
+    /// may overlap any of the fetched ones.
+    fn filter(&self, files: Vec<ParquetFile>, truncated: bool) -> Vec<ParquetFile> {
+        if truncated {
+            return files;
+        }
+
+        let isolated = isolated_files(&files);
+
+        files
+            .into_iter()
+            .zip(isolated)
+            .filter(|(file, isolated)| {
+                if !isolated || file.compaction_level != CompactionLevel::Final {
+                    return true;
+                }
+
+                let counter = if self.excluded_levels.contains(&file.compaction_level) {
+                    &self.excluded_level_counter
+                } else if self
+                    .max_file_size_bytes
+                    .map(|max| file.file_size_bytes as u64 > max)
+                    .unwrap_or(false)
+                {
+                    &self.too_large_counter
+                } else {
+                    return true;
+                };
+
+                counter.inc(1);
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            })
+            .map(|(file, _isolated)| file)
+            .collect()
+    }
+}
+
+impl<T> Display for FilteringPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "filtering({}, max_file_size_bytes={:?}, excluded_levels={:?}, dropped={})",
+            self.inner,
+            self.max_file_size_bytes,
+            self.excluded_levels,
+            self.dropped.load(Ordering::Relaxed),
+        )
+    }
+}
+
+#[async_trait]
+impl<T> PartitionFilesSource for FilteringPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    async fn fetch(&self, partition_id: PartitionId) -> Result<FetchedFiles, DynError> {
+        let fetched = self.inner.fetch(partition_id).await?;
+        Ok(FetchedFiles {
+            files: self.filter(fetched.files, fetched.truncated),
+            ..fetched
+        })
+    }
+
+    async fn fetch_with_partition_info(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<PartitionFiles, DynError> {
+        let partition_files = self.inner.fetch_with_partition_info(partition_id).await?;
+        Ok(PartitionFiles {
+            files: self.filter(partition_files.files, partition_files.truncated),
+            ..partition_files
+        })
+    }
+
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        let files = self.inner.fetch_many(partitions).await?;
+        Ok(files
+            .into_iter()
+            .map(|(partition_id, partition_files)| {
+                let partition_files = PartitionFiles {
+                    files: self.filter(partition_files.files, partition_files.truncated),
+                    ..partition_files
+                };
+                (partition_id, partition_files)
+            })
+            .collect())
+    }
+}
+
+/// Whether each file overlaps no other file, in the same order as `files`.
+///
+/// Overlaps are inclusive, like [`ParquetFile::overlaps`].
+fn isolated_files(files: &[ParquetFile]) -> Vec<bool> {
+    let mut order: Vec<_> = (0..files.len()).collect();
+    order.sort_by_key(|&i| files[i].min_time);
+
+    // Sorted by min time, a file overlaps an earlier file iff one of them ends at or after its
+    // min time, and a later file iff the next one starts at or before its max time.
+    let mut isolated = vec![true; files.len()];
+    let mut max_time_so_far = None;
+    for (pos, &i) in order.iter().enumerate() {
+        let file = &files[i];
+        let overlaps_earlier = max_time_so_far
+            .map(|max_time| max_time >= file.min_time)
+            .unwrap_or(false);
+        let overlaps_later = order
+            .get(pos + 1)
+            .map(|&next| files[next].min_time <= file.max_time)
+            .unwrap_or(false);
+        isolated[i] = !overlaps_earlier && !overlaps_later;
+
+        max_time_so_far = max_time_so_far.max(Some(file.max_time));
+    }
+
+    isolated
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_tests::{partition_identifier, ParquetFileBuilder, PartitionBuilder};
+    use metric::{Attributes, Metric};
+
+    use crate::components::partition_files_source::mock::{MockFetch, MockPartitionFilesSource};
+
+    use super::*;
+
+    fn file(id: i64, level: CompactionLevel, min: i64, max: i64, size: i64) -> ParquetFile {
+        ParquetFileBuilder::new(id)
+            .with_partition(partition_identifier(1))
+            .with_compaction_level(level)
+            .with_time_range(min, max)
+            .with_file_size_bytes(size)
+            .build()
+    }
+
+    fn source(
+        files: Vec<ParquetFile>,
+        registry: &Registry,
+    ) -> FilteringPartitionFilesSource<MockPartitionFilesSource> {
+        let inner = MockPartitionFilesSource::new(
+            HashMap::from([(PartitionId::new(1), partition_identifier(1))]),
+            files,
+        )
+        .with_partitions([PartitionBuilder::new(1).build()]);
+        FilteringPartitionFilesSource::new(inner, registry).with_max_file_size_bytes(100)
+    }
+
+    fn filtered(registry: &Registry, reason: &'static str) -> u64 {
+        registry
+            .get_instrument::<Metric<U64Counter>>(METRIC_NAME_PARTITION_FILES_FILTERED)
+            .expect("metric registered")
+            .get_observer(&Attributes::from(&[("reason", reason)]))
+            .expect("observer exists")
+            .fetch()
+    }
+
+    fn ids(files: &[ParquetFile]) -> Vec<i64> {
+        let mut ids: Vec<_> = files.iter().map(|f| f.id.get()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_display() {
+        let registry = Registry::new();
+        let source = source(vec![], &registry).with_excluded_levels([CompactionLevel::Final]);
+        assert_eq!(
+            source.to_string(),
+            "filtering(mock, max_file_size_bytes=Some(100), excluded_levels={Final}, dropped=0)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        let registry = Registry::new();
+        let source = source(
+            vec![
+                // isolated and too large
+                file(1, CompactionLevel::Final, 0, 10, 1000),
+                // isolated and small
+                file(2, CompactionLevel::Final, 20, 30, 10),
+                // isolated and too large, but not final
+                file(3, CompactionLevel::FileNonOverlapped, 40, 50, 1000),
+                file(4, CompactionLevel::Initial, 60, 70, 1000),
+            ],
+            &registry,
+        );
+
+        let files = source.fetch(PartitionId::new(1)).await.unwrap().files;
+        assert_eq!(ids(&files), vec![2, 3, 4]);
+        assert_eq!(filtered(&registry, "too_large"), 1);
+        assert_eq!(filtered(&registry, "excluded_level"), 0);
+        assert!(source.to_string().ends_with("dropped=1)"));
+
+        let files = source.fetch_many(&[PartitionId::new(1)]).await.unwrap();
+        assert_eq!(ids(&files[&PartitionId::new(1)].files), vec![2, 3, 4]);
+        assert_eq!(filtered(&registry, "too_large"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_excluded_levels() {
+        let registry = Registry::new();
+        let source = source(
+            vec![
+                file(1, CompactionLevel::Final, 0, 10, 10),
+                file(2, CompactionLevel::FileNonOverlapped, 20, 30, 10),
+                file(3, CompactionLevel::Final, 30, 40, 10),
+            ],
+            &registry,
+        )
+        .with_excluded_levels([CompactionLevel::Final, CompactionLevel::FileNonOverlapped]);
+
+        // only the isolated final file is dropped
+        let files = source.fetch(PartitionId::new(1)).await.unwrap().files;
+        assert_eq!(ids(&files), vec![2, 3]);
+        assert_eq!(filtered(&registry, "excluded_level"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_files_are_kept() {
+        let registry = Registry::new();
+        let source = source(
+            vec![
+                // touches the L1 file at its max time
+                file(1, CompactionLevel::Final, 0, 10, 1000),
+                file(2, CompactionLevel::FileNonOverlapped, 10, 15, 10),
+                // overlaps another final file
+                file(3, CompactionLevel::Final, 20, 30, 1000),
+                file(4, CompactionLevel::Final, 25, 26, 1000),
+                // contained in a long file that starts earlier than the file in between
+                file(5, CompactionLevel::Initial, 40, 100, 10),
+                file(6, CompactionLevel::Final, 50, 55, 10),
+                file(7, CompactionLevel::Final, 60, 70, 1000),
+                // isolated
+                file(8, CompactionLevel::Final, 101, 200, 1000),
+            ],
+            &registry,
+        );
+
+        let files = source.fetch(PartitionId::new(1)).await.unwrap().files;
+        assert_eq!(ids(&files), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(filtered(&registry, "too_large"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_files_change_between_fetches() {
+        let registry = Registry::new();
+        let p1 = PartitionId::new(1);
+        let large = file(1, CompactionLevel::Final, 0, 10, 1000);
+        let overlapping = file(2, CompactionLevel::Initial, 5, 15, 10);
+        let inner = MockPartitionFilesSource::new(Default::default(), Default::default())
+            .with_script(
+                p1,
+                [
+                    MockFetch::files(vec![large.clone(), overlapping]),
+                    // the overlapping file was compacted away
+                    MockFetch::files(vec![large.clone()]),
+                ],
+            );
+        let source =
+            FilteringPartitionFilesSource::new(inner, &registry).with_max_file_size_bytes(100);
+
+        assert_eq!(ids(&source.fetch(p1).await.unwrap().files), vec![1, 2]);
+        assert_eq!(filtered(&registry, "too_large"), 0);
+
+        assert!(source.fetch(p1).await.unwrap().files.is_empty());
+        assert_eq!(filtered(&registry, "too_large"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_files_are_kept() {
+        let p1 = PartitionId::new(1);
+        let inner = || {
+            MockPartitionFilesSource::new(
+                HashMap::from([(p1, partition_identifier(1))]),
+                vec![file(1, CompactionLevel::Final, 0, 10, 1000)],
+            )
+            .with_partitions([PartitionBuilder::new(1).build()])
+        };
+
+        let registry = Registry::new();
+        let source =
+            FilteringPartitionFilesSource::new(inner(), &registry).with_max_file_size_bytes(100);
+        let partition_files = source.fetch_with_partition_info(p1).await.unwrap();
+        assert!(partition_files.files.is_empty());
+        assert_eq!(filtered(&registry, "too_large"), 1);
+
+        // the files that were not fetched may overlap the large file
+        let registry = Registry::new();
+        let source = FilteringPartitionFilesSource::new(inner().with_truncated(), &registry)
+            .with_max_file_size_bytes(100);
+        let partition_files = source.fetch_with_partition_info(p1).await.unwrap();
+        assert!(partition_files.truncated);
+        assert_eq!(ids(&partition_files.files), vec![1]);
+
+        let fetched = source.fetch(p1).await.unwrap();
+        assert!(fetched.truncated);
+        assert_eq!(ids(&fetched.files), vec![1]);
+        assert_eq!(filtered(&registry, "too_large"), 0);
+    }
+
+    #[test]
+    fn test_isolated_files() {
+        assert_eq!(isolated_files(&[]), Vec::<bool>::new());
+
+        let f = |id, min, max| file(id, CompactionLevel::Final, min, max, 1);
+        assert_eq!(isolated_files(&[f(1, 0, 10)]), vec![true]);
+        assert_eq!(
+            isolated_files(&[f(1, 20, 30), f(2, 0, 100), f(3, 40, 50), f(4, 101, 102)]),
+            vec![false, false, false, true],
+        );
+        assert_eq!(
+            isolated_files(&[f(1, 11, 20), f(2, 0, 10), f(3, 20, 20)]),
+            vec![false, true, false],
+        );
+    }
+}
@@ -1,8 +1,16 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    sync::Arc,
+    time::Duration,
+};
 
-use super::PartitionFilesSource;
+use super::{FetchedFiles, PartitionFiles, PartitionFilesSource};
+use crate::error::{DynError, ErrorKind, SimpleError};
 use async_trait::async_trait;
-use data_types::{ParquetFile, PartitionId, TransitionPartitionId};
+use data_types::{ParquetFile, Partition, PartitionId, TransitionPartitionId};
+use iox_time::{SystemProvider, Time, TimeProvider};
+use parking_lot::Mutex;
 
 #[derive(Debug)]
 pub struct MockPartitionFilesSource {
@@ -12,6 +20,61 @@ pub struct MockPartitionFilesSource {
     // it yet. This should become simpler when the transition is complete.
     partition_lookup: HashMap<PartitionId, TransitionPartitionId>,
     file_lookup: HashMap<TransitionPartitionId, Vec<ParquetFile>>,
+    partitions: HashMap<PartitionId, Partition>,
+    truncated: bool,
+    delay: Option<Duration>,
+    error: Option<(ErrorKind, String)>,
+
+    /// Outcomes of the next fetches of each partition, used before the files above.
+    scripts: Mutex<HashMap<PartitionId, VecDeque<MockFetch>>>,
+    calls: Mutex<Vec<MockFetchCall>>,
+    batches: Mutex<Vec<Vec<PartitionId>>>,
+    time_provider: Arc<dyn TimeProvider>,
+}
+
+/// Scripted outcome of a single fetch of a [`MockPartitionFilesSource`].
+#[derive(Debug, Clone)]
+pub struct MockFetch {
+    delay: Option<Duration>,
+    result: Result<Vec<ParquetFile>, (ErrorKind, String)>,
+}
+
+impl MockFetch {
+    /// The fetch returns `files`.
+    #[cfg(test)]
+    pub fn files(files: Vec<ParquetFile>) -> Self {
+        Self {
+            delay: None,
+            result: Ok(files),
+        }
+    }
+
+    /// The fetch fails with an error of the given kind.
+    #[cfg(test)]
+    pub fn error(kind: ErrorKind, msg: impl Into<String>) -> Self {
+        Self {
+            delay: None,
+            result: Err((kind, msg.into())),
+        }
+    }
+
+    /// The fetch takes `delay` before returning.
+    #[cfg(test)]
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self {
+            delay: Some(delay),
+            ..self
+        }
+    }
+}
+
+/// A fetch made from a [`MockPartitionFilesSource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockFetchCall {
+    pub partition_id: PartitionId,
+
+    /// When the fetch started.
+    pub at: Time,
 }
 
 impl MockPartitionFilesSource {
@@ -29,8 +92,92 @@ impl MockPartitionFilesSource {
         Self {
             partition_lookup,
             file_lookup,
+            partitions: HashMap::new(),
+            truncated: false,
+            delay: None,
+            error: None,
+            scripts: Default::default(),
+            calls: Default::default(),
+            batches: Default::default(),
+            time_provider: Arc::new(SystemProvider::new()),
         }
     }
+
+    /// Return these partitions with their files, see
+    /// [`fetch_with_partition_info`](PartitionFilesSource::fetch_with_partition_info).
+    #[cfg(test)]
+    pub fn with_partitions(mut self, partitions: impl IntoIterator<Item = Partition>) -> Self {
+        self.partitions.extend(
+            partitions
+                .into_iter()
+                .map(|partition| (partition.id, partition)),
+        );
+        self
+    }
+
+    /// Report the returned files as [truncated](PartitionFiles::truncated).
+    #[cfg(test)]
+    pub fn with_truncated(mut self) -> Self {
+        self.truncated = true;
+        self
+    }
+
+    /// Take `delay` for each fetch, e.g. to simulate a slow catalog.
+    #[cfg(test)]
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Fail all fetches with an error of the given kind.
+    #[cfg(test)]
+    pub fn with_error(mut self, kind: ErrorKind, msg: impl Into<String>) -> Self {
+        self.error = Some((kind, msg.into()));
+        self
+    }
+
+    /// Script the outcomes of the next fetches of a partition, in order.
+    ///
+    /// Once they are used up, fetches of the partition behave as if it was not scripted.
+    #[cfg(test)]
+    pub fn with_script(
+        self,
+        partition_id: PartitionId,
+        fetches: impl IntoIterator<Item = MockFetch>,
+    ) -> Self {
+        self.scripts
+            .lock()
+            .entry(partition_id)
+            .or_default()
+            .extend(fetches);
+        self
+    }
+
+    /// Time the [calls](Self::calls) with `time_provider`.
+    #[cfg(test)]
+    pub fn with_time_provider(mut self, time_provider: Arc<dyn TimeProvider>) -> Self {
+        self.time_provider = time_provider;
+        self
+    }
+
+    /// Number of fetches so far.
+    #[cfg(test)]
+    pub fn fetches(&self) -> usize {
+        self.calls.lock().len()
+    }
+
+    /// Fetches so far, in the order they started.
+    #[cfg(test)]
+    pub fn calls(&self) -> Vec<MockFetchCall> {
+        self.calls.lock().clone()
+    }
+
+    /// Partitions of each [`fetch_many`](PartitionFilesSource::fetch_many) so far, in order. Their
+    /// fetches are [calls](Self::calls) as well.
+    #[cfg(test)]
+    pub fn batches(&self) -> Vec<Vec<PartitionId>> {
+        self.batches.lock().clone()
+    }
 }
 
 impl Display for MockPartitionFilesSource {
@@ -41,18 +188,84 @@ impl Display for MockPartitionFilesSource {
 
 #[async_trait]
 impl PartitionFilesSource for MockPartitionFilesSource {
-    async fn fetch(&self, partition_id: PartitionId) -> Vec<ParquetFile> {
-        self.partition_lookup
+    async fn fetch(&self, partition_id: PartitionId) -> Result<FetchedFiles, DynError> {
+        self.calls.lock().push(MockFetchCall {
+            partition_id,
+            at: self.time_provider.now(),
+        });
+
+        let scripted = self
+            .scripts
+            .lock()
+            .get_mut(&partition_id)
+            .and_then(|script| script.pop_front());
+        let MockFetch { delay, result } = match scripted {
+            Some(fetch) => fetch,
+            None => MockFetch {
+                delay: self.delay,
+                result: match &self.error {
+                    Some(err) => Err(err.clone()),
+                    None => Ok(self
+                        .partition_lookup
+                        .get(&partition_id)
+                        .and_then(|partition_hash_id| {
+                            self.file_lookup.get(partition_hash_id).cloned()
+                        })
+                        .unwrap_or_default()),
+                },
+            },
+        };
+
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        result
+            .map(|files| FetchedFiles {
+                files,
+                truncated: self.truncated,
+            })
+            .map_err(|(kind, msg)| SimpleError::new(kind, msg).into())
+    }
+
+    async fn fetch_with_partition_info(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<PartitionFiles, DynError> {
+        let FetchedFiles { files, truncated } = self.fetch(partition_id).await?;
+        let partition = self
+            .partitions
             .get(&partition_id)
-            .and_then(|partition_hash_id| self.file_lookup.get(partition_hash_id).cloned())
-            .unwrap_or_default()
+            .cloned()
+            .ok_or_else::<DynError, _>(|| String::from("Cannot find partition info").into())?;
+        Ok(PartitionFiles {
+            files,
+            partition,
+            truncated,
+        })
+    }
+
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        self.batches.lock().push(partitions.to_vec());
+
+        let mut files = HashMap::with_capacity(partitions.len());
+        for partition_id in partitions {
+            files.insert(
+                *partition_id,
+                self.fetch_with_partition_info(*partition_id).await?,
+            );
+        }
+        Ok(files)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use iox_tests::{partition_identifier, ParquetFileBuilder};
+    use iox_tests::{partition_identifier, ParquetFileBuilder, PartitionBuilder};
+    use iox_time::MockProvider;
 
     #[test]
     fn test_display() {
@@ -88,15 +301,186 @@ mod tests {
 
         // different partitions
         assert_eq!(
-            source.fetch(partition_id_1).await,
+            source.fetch(partition_id_1).await.unwrap().files,
             vec![f_1_1.clone(), f_1_2.clone()],
         );
-        assert_eq!(source.fetch(partition_id_2).await, vec![f_2_1],);
+        assert_eq!(
+            source.fetch(partition_id_2).await.unwrap().files,
+            vec![f_2_1],
+        );
 
         // fetching does not drain
-        assert_eq!(source.fetch(partition_id_1).await, vec![f_1_1, f_1_2],);
+        assert_eq!(
+            source.fetch(partition_id_1).await.unwrap().files,
+            vec![f_1_1, f_1_2],
+        );
 
         // unknown partition => empty result
-        assert_eq!(source.fetch(PartitionId::new(3)).await, vec![],);
+        assert_eq!(
+            source.fetch(PartitionId::new(3)).await.unwrap().files,
+            vec![],
+        );
+
+        assert_eq!(source.fetches(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many() {
+        let partition_id_1 = PartitionId::new(1);
+        let partition_id_2 = PartitionId::new(2);
+        let partition_id_3 = PartitionId::new(3);
+        let partition_identifier_1 = partition_identifier(1);
+        let partition_identifier_2 = partition_identifier(2);
+        let f_1_1 = ParquetFileBuilder::new(1)
+            .with_partition(partition_identifier_1.clone())
+            .build();
+        let f_1_2 = ParquetFileBuilder::new(2)
+            .with_partition(partition_identifier_1.clone())
+            .build();
+        let f_2_1 = ParquetFileBuilder::new(3)
+            .with_partition(partition_identifier_2.clone())
+            .build();
+
+        let partition_lookup = HashMap::from([
+            (partition_id_1, partition_identifier_1.clone()),
+            (partition_id_2, partition_identifier_2.clone()),
+        ]);
+        let files = vec![f_1_1.clone(), f_1_2.clone(), f_2_1.clone()];
+        let source = MockPartitionFilesSource::new(partition_lookup, files)
+            .with_partitions((1..=3).map(|i| PartitionBuilder::new(i).build()));
+
+        let files = source
+            .fetch_many(&[partition_id_1, partition_id_2, partition_id_3])
+            .await
+            .unwrap();
+        let files: HashMap<_, _> = files
+            .into_iter()
+            .map(|(partition_id, partition_files)| {
+                assert_eq!(partition_files.partition.id, partition_id);
+                (partition_id, partition_files.files)
+            })
+            .collect();
+        assert_eq!(
+            files,
+            HashMap::from([
+                (partition_id_1, vec![f_1_1, f_1_2]),
+                (partition_id_2, vec![f_2_1]),
+                (partition_id_3, vec![]),
+            ])
+        );
+        assert_eq!(source.fetches(), 3);
+        assert_eq!(
+            source.batches(),
+            vec![vec![partition_id_1, partition_id_2, partition_id_3]],
+        );
+
+        // unknown partitions fail
+        source.fetch_many(&[PartitionId::new(4)]).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_slow_or_failing() {
+        let source = MockPartitionFilesSource::new(Default::default(), Default::default())
+            .with_delay(Duration::from_secs(60));
+        tokio::time::timeout(Duration::from_millis(10), source.fetch(PartitionId::new(1)))
+            .await
+            .unwrap_err();
+
+        let source = MockPartitionFilesSource::new(Default::default(), Default::default())
+            .with_error(ErrorKind::Catalog, "foo");
+        let err = source.fetch(PartitionId::new(1)).await.unwrap_err();
+        assert_eq!(err.to_string(), "foo");
+        assert_eq!(source.fetches(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_script() {
+        let partition_id_1 = PartitionId::new(1);
+        let partition_id_2 = PartitionId::new(2);
+        let partition_identifier_1 = partition_identifier(1);
+        let f_1 = ParquetFileBuilder::new(1)
+            .with_partition(partition_identifier_1.clone())
+            .build();
+        let f_2 = ParquetFileBuilder::new(2)
+            .with_partition(partition_identifier_1.clone())
+            .build();
+
+        let source = MockPartitionFilesSource::new(
+            HashMap::from([(partition_id_1, partition_identifier_1)]),
+            vec![f_1.clone()],
+        )
+        .with_script(
+            partition_id_1,
+            [
+                MockFetch::error(ErrorKind::Catalog, "foo"),
+                MockFetch::files(vec![f_1.clone(), f_2.clone()])
+                    .with_delay(Duration::from_millis(1)),
+            ],
+        )
+        .with_script(partition_id_1, [MockFetch::files(vec![])])
+        .with_script(partition_id_2, [MockFetch::files(vec![f_2.clone()])]);
+
+        let err = source.fetch(partition_id_1).await.unwrap_err();
+        assert_eq!(err.to_string(), "foo");
+        assert_eq!(
+            source.fetch(partition_id_1).await.unwrap().files,
+            vec![f_1.clone(), f_2.clone()],
+        );
+        assert_eq!(source.fetch(partition_id_1).await.unwrap().files, vec![]);
+
+        // scripts are used up, the static files are back
+        assert_eq!(source.fetch(partition_id_1).await.unwrap().files, vec![f_1]);
+
+        // scripts are per partition
+        assert_eq!(source.fetch(partition_id_2).await.unwrap().files, vec![f_2]);
+        assert_eq!(source.fetch(partition_id_2).await.unwrap().files, vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_script_slow_fetch() {
+        let source = MockPartitionFilesSource::new(Default::default(), Default::default())
+            .with_script(
+                PartitionId::new(1),
+                [MockFetch::files(vec![]).with_delay(Duration::from_secs(60))],
+            );
+        tokio::time::timeout(Duration::from_millis(10), source.fetch(PartitionId::new(1)))
+            .await
+            .unwrap_err();
+
+        // the timed out fetch used up its outcome
+        source.fetch(PartitionId::new(1)).await.unwrap();
+        assert_eq!(source.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_calls() {
+        let time_provider = Arc::new(MockProvider::new(Time::MIN));
+        let source = MockPartitionFilesSource::new(Default::default(), Default::default())
+            .with_partitions((1..=2).map(|i| PartitionBuilder::new(i).build()))
+            .with_time_provider(Arc::clone(&time_provider) as _);
+        let (p1, p2) = (PartitionId::new(1), PartitionId::new(2));
+
+        source.fetch(p2).await.unwrap();
+        time_provider.inc(Duration::from_secs(1));
+        source.fetch_many(&[p1, p2]).await.unwrap();
+
+        let t1 = Time::MIN + Duration::from_secs(1);
+        assert_eq!(
+            source.calls(),
+            vec![
+                MockFetchCall {
+                    partition_id: p2,
+                    at: Time::MIN,
+                },
+                MockFetchCall {
+                    partition_id: p1,
+                    at: t1,
+                },
+                MockFetchCall {
+                    partition_id: p2,
+                    at: t1,
+                },
+            ],
+        );
     }
 }
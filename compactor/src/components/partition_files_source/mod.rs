@@ -1,19 +1,117 @@
-use std::fmt::{Debug, Display};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
-use data_types::{ParquetFile, PartitionId};
+use data_types::{ParquetFile, Partition, PartitionId};
 
+use crate::error::DynError;
+
+pub mod caching;
 pub mod catalog;
+pub mod commit_aware;
+pub mod filtering;
 pub mod mock;
+pub mod prefetching;
 pub mod rate_limit;
 
+/// Files of a partition, see [`PartitionFilesSource::fetch`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FetchedFiles {
+    pub files: Vec<ParquetFile>,
+
+    /// Only the oldest L0 files of the partition were fetched, see
+    /// [`PartitionFiles::truncated`].
+    pub truncated: bool,
+}
+
+impl From<PartitionFiles> for FetchedFiles {
+    fn from(partition_files: PartitionFiles) -> Self {
+        Self {
+            files: partition_files.files,
+            truncated: partition_files.truncated,
+        }
+    }
+}
+
+/// Files of a partition, together with the partition, see
+/// [`PartitionFilesSource::fetch_with_partition_info`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionFiles {
+    pub files: Vec<ParquetFile>,
+    pub partition: Partition,
+
+    /// Only the oldest L0 files of the partition were fetched, because it has too many, see
+    /// [`CatalogPartitionFilesSource::with_max_files`](catalog::CatalogPartitionFilesSource::with_max_files).
+    pub truncated: bool,
+}
+
 /// Finds files in a partition for compaction
 #[async_trait]
 pub trait PartitionFilesSource: Debug + Display + Send + Sync {
     /// Get undeleted parquet files for given partition.
     ///
-    /// This MUST NOT perform any filtering (expect for the "not marked for deletion" flag).
+    /// This MUST NOT perform any filtering (expect for the "not marked for deletion" flag), unless
+    /// the dropped files can never take part in a compaction, see
+    /// [`FilteringPartitionFilesSource`](filtering::FilteringPartitionFilesSource).
     ///
-    /// This method performs retries.
-    async fn fetch(&self, partition: PartitionId) -> Vec<ParquetFile>;
+    /// This method performs retries, and only fails when it gives up, e.g. after a deadline.
+    async fn fetch(&self, partition: PartitionId) -> Result<FetchedFiles, DynError>;
+
+    /// Get undeleted parquet files for given partition, together with the partition itself, so
+    /// that the catalog is not queried a second time for the sort key and IDs of the partition.
+    ///
+    /// Fails if the partition does not exist. Otherwise the same rules as for
+    /// [`fetch`](Self::fetch) apply.
+    async fn fetch_with_partition_info(
+        &self,
+        partition: PartitionId,
+    ) -> Result<PartitionFiles, DynError>;
+
+    /// Get undeleted parquet files for several partitions, together with the partitions.
+    ///
+    /// Every given partition is in the result, with no files if it has none. Fails if any of the
+    /// partitions does not exist. Otherwise the same rules as for [`fetch`](Self::fetch) apply.
+    ///
+    /// The default implementation fetches one partition at a time, sources that can fetch many
+    /// partitions at once should override it.
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        let mut files = HashMap::with_capacity(partitions.len());
+        for partition in partitions {
+            files.insert(
+                *partition,
+                self.fetch_with_partition_info(*partition).await?,
+            );
+        }
+        Ok(files)
+    }
+}
+
+#[async_trait]
+impl<T> PartitionFilesSource for Arc<T>
+where
+    T: PartitionFilesSource + ?Sized,
+{
+    async fn fetch(&self, partition: PartitionId) -> Result<FetchedFiles, DynError> {
+        self.as_ref().fetch(partition).await
+    }
+
+    async fn fetch_with_partition_info(
+        &self,
+        partition: PartitionId,
+    ) -> Result<PartitionFiles, DynError> {
+        self.as_ref().fetch_with_partition_info(partition).await
+    }
+
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        self.as_ref().fetch_many(partitions).await
+    }
 }
@@ -0,0 +1,501 @@
+use std::{collections::HashMap, fmt::Display, num::NonZeroUsize, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use data_types::PartitionId;
+use iox_time::{Time, TimeProvider};
+use observability_deps::tracing::debug;
+use parking_lot::Mutex;
+use tokio::sync::Semaphore;
+
+use crate::error::DynError;
+
+use super::{FetchedFiles, PartitionFiles, PartitionFilesSource};
+
+/// Fetches the files of partitions in the background as soon as they are
+/// [hinted](PartitionFilesPrefetcher::hint_upcoming) to be compacted soon, so that fetching them
+/// once their compaction starts doesn't wait for the catalog.
+///
+/// The partitions of a hint are fetched together, with a single
+/// [`fetch_many`](PartitionFilesSource::fetch_many) of the inner source.
+///
+/// Prefetched files are used for `ttl`, unless the partition is
+/// [invalidated](PartitionFilesPrefetcher::invalidate) before, e.g. by a commit. A fetch of a
+/// partition whose prefetch is still running doesn't wait for it.
+#[derive(Debug)]
+pub struct PrefetchingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    inner: Arc<T>,
+    prefetcher: PartitionFilesPrefetcher,
+}
+
+impl<T> PrefetchingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource + 'static,
+{
+    /// Prefetch the files of at most `concurrency` hints at a time.
+    pub fn new(
+        inner: T,
+        ttl: Duration,
+        concurrency: NonZeroUsize,
+        time_provider: Arc<dyn TimeProvider>,
+    ) -> Self {
+        let inner = Arc::new(inner);
+        Self {
+            prefetcher: PartitionFilesPrefetcher {
+                inner: Arc::clone(&inner) as _,
+                ttl,
+                concurrency,
+                time_provider,
+                permits: Arc::new(Semaphore::new(concurrency.get())),
+                prefetched: Default::default(),
+            },
+            inner,
+        }
+    }
+}
+
+impl<T> PrefetchingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    /// Start fetching the files of partitions that are about to be compacted, see
+    /// [`PartitionFilesPrefetcher::hint_upcoming`].
+    pub fn hint_upcoming(&self, partitions: &[PartitionId]) {
+        self.prefetcher.hint_upcoming(partitions)
+    }
+
+    /// Handle to hint upcoming partitions, and to invalidate the prefetched files of partitions
+    /// whose files changed.
+    pub fn prefetcher(&self) -> PartitionFilesPrefetcher {
+        self.prefetcher.clone()
+    }
+}
+
+impl<T> Display for PrefetchingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "prefetching({}, ttl={:?}, concurrency={})",
+            self.inner, self.prefetcher.ttl, self.prefetcher.concurrency
+        )
+    }
+}
+
+#[async_trait]
+impl<T> PartitionFilesSource for PrefetchingPartitionFilesSource<T>
+where
+    T: PartitionFilesSource,
+{
+    async fn fetch(&self, partition_id: PartitionId) -> Result<FetchedFiles, DynError> {
+        match self.prefetcher.get(partition_id) {
+            Some(partition_files) => Ok(partition_files.into()),
+            None => self.inner.fetch(partition_id).await,
+        }
+    }
+
+    async fn fetch_with_partition_info(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<PartitionFiles, DynError> {
+        match self.prefetcher.get(partition_id) {
+            Some(partition_files) => Ok(partition_files),
+            None => self.inner.fetch_with_partition_info(partition_id).await,
+        }
+    }
+
+    async fn fetch_many(
+        &self,
+        partitions: &[PartitionId],
+    ) -> Result<HashMap<PartitionId, PartitionFiles>, DynError> {
+        let mut files = HashMap::with_capacity(partitions.len());
+        let mut missing = vec![];
+        for partition_id in partitions {
+            match self.prefetcher.get(*partition_id) {
+                Some(partition_files) => {
+                    files.insert(*partition_id, partition_files);
+                }
+                None => missing.push(*partition_id),
+            }
+        }
+
+        if !missing.is_empty() {
+            files.extend(self.inner.fetch_many(&missing).await?);
+        }
+
+        Ok(files)
+    }
+}
+
+/// Prefetches for a [`PrefetchingPartitionFilesSource`].
+#[derive(Debug, Clone)]
+pub struct PartitionFilesPrefetcher {
+    inner: Arc<dyn PartitionFilesSource>,
+    ttl: Duration,
+    concurrency: NonZeroUsize,
+    time_provider: Arc<dyn TimeProvider>,
+    permits: Arc<Semaphore>,
+    prefetched: Arc<Mutex<Prefetched>>,
+}
+
+#[derive(Debug, Default)]
+struct Prefetched {
+    next_token: u64,
+    entries: HashMap<PartitionId, Entry>,
+}
+
+#[derive(Debug)]
+enum Entry {
+    /// The prefetch identified by the token is running.
+    Pending(u64),
+
+    Ready {
+        partition_files: PartitionFiles,
+        expires: Time,
+    },
+}
+
+impl PartitionFilesPrefetcher {
+    /// Start fetching the files of partitions that are about to be compacted, unless they are
+    /// prefetched already.
+    ///
+    /// The partitions that are not prefetched yet are fetched together, in the background.
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn hint_upcoming(&self, partitions: &[PartitionId]) {
+        let now = self.time_provider.now();
+        let mut prefetched = self.prefetched.lock();
+        prefetched
+            .entries
+            .retain(|_partition_id, entry| match entry {
+                Entry::Pending(_token) => true,
+                Entry::Ready { expires, .. } => *expires > now,
+            });
+
+        let mut batch = vec![];
+        for partition_id in partitions {
+            if prefetched.entries.contains_key(partition_id) {
+                continue;
+            }
+
+            let token = prefetched.next_token;
+            prefetched.next_token += 1;
+            prefetched
+                .entries
+                .insert(*partition_id, Entry::Pending(token));
+            batch.push((*partition_id, token));
+        }
+
+        if !batch.is_empty() {
+            let this = self.clone();
+            tokio::spawn(async move { this.prefetch(batch).await });
+        }
+    }
+
+    /// Forget the prefetched files of a partition, so that the next fetch gets them from the
+    /// inner source. A running prefetch of the partition is discarded once done.
+    pub fn invalidate(&self, partition_id: PartitionId) {
+        self.prefetched.lock().entries.remove(&partition_id);
+    }
+
+    /// Fetch the files of the partitions of a hint, each with the token of its prefetch.
+    async fn prefetch(&self, batch: Vec<(PartitionId, u64)>) {
+        let partition_ids: Vec<_> = batch
+            .iter()
+            .map(|(partition_id, _token)| *partition_id)
+            .collect();
+        let res = {
+            let _permit = self.permits.acquire().await.expect("semaphore not closed");
+            self.inner.fetch_many(&partition_ids).await
+        };
+        let mut files = res.unwrap_or_else(|e| {
+            // the fetches at the start of the compactions will retry
+            debug!(
+                partitions = partition_ids.len(),
+                %e,
+                "prefetching partition files failed"
+            );
+            HashMap::new()
+        });
+
+        let mut prefetched = self.prefetched.lock();
+        let expires = self.time_provider.now() + self.ttl;
+        for (partition_id, token) in batch {
+            match prefetched.entries.get(&partition_id) {
+                Some(Entry::Pending(pending)) if *pending == token => {}
+                // invalidated while fetching
+                _ => continue,
+            }
+
+            match files.remove(&partition_id) {
+                Some(partition_files) => {
+                    prefetched.entries.insert(
+                        partition_id,
+                        Entry::Ready {
+                            partition_files,
+                            expires,
+                        },
+                    );
+                }
+                None => {
+                    prefetched.entries.remove(&partition_id);
+                }
+            }
+        }
+    }
+
+    /// The prefetched files of a partition, if prefetched within the TTL.
+    fn get(&self, partition_id: PartitionId) -> Option<PartitionFiles> {
+        match self.prefetched.lock().entries.get(&partition_id) {
+            Some(Entry::Ready {
+                partition_files,
+                expires,
+            }) if *expires > self.time_provider.now() => Some(partition_files.clone()),
+            _ => None,
+        }
+    }
+
+    /// Number of partitions whose files are prefetched, expired or not.
+    #[cfg(test)]
+    pub(crate) fn ready(&self) -> usize {
+        self.prefetched
+            .lock()
+            .entries
+            .values()
+            .filter(|entry| matches!(entry, Entry::Ready { .. }))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_tests::{partition_identifier, ParquetFileBuilder, PartitionBuilder};
+    use iox_time::{MockProvider, SystemProvider};
+
+    use crate::{
+        components::partition_files_source::mock::{MockFetch, MockPartitionFilesSource},
+        error::ErrorKind,
+    };
+
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(5);
+
+    /// Mock with 3 partitions of a file each.
+    fn inner() -> MockPartitionFilesSource {
+        let partition_lookup = (1..=3)
+            .map(|i| (PartitionId::new(i), partition_identifier(i)))
+            .collect();
+        let files = (1..=3)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_partition(partition_identifier(i))
+                    .build()
+            })
+            .collect();
+        MockPartitionFilesSource::new(partition_lookup, files)
+            .with_partitions((1..=3).map(|i| PartitionBuilder::new(i).build()))
+    }
+
+    fn source(
+        inner: MockPartitionFilesSource,
+        concurrency: usize,
+    ) -> (
+        PrefetchingPartitionFilesSource<Arc<MockPartitionFilesSource>>,
+        Arc<MockPartitionFilesSource>,
+        Arc<MockProvider>,
+    ) {
+        let inner = Arc::new(inner);
+        let time_provider = Arc::new(MockProvider::new(Time::MIN));
+        let source = PrefetchingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(concurrency).unwrap(),
+            Arc::clone(&time_provider) as _,
+        );
+        (source, inner, time_provider)
+    }
+
+    /// Wait for `n` partitions to be prefetched.
+    async fn prefetched(
+        source: &PrefetchingPartitionFilesSource<impl PartitionFilesSource>,
+        n: usize,
+    ) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while source.prefetcher.ready() < n {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("prefetched in time");
+    }
+
+    #[test]
+    fn test_display() {
+        let (source, _inner, _time_provider) = source(inner(), 2);
+        assert_eq!(
+            source.to_string(),
+            "prefetching(mock, ttl=5s, concurrency=2)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hinted_fetch() {
+        let (source, inner, _time_provider) = source(inner(), 2);
+        let (p1, p2, p3) = (
+            PartitionId::new(1),
+            PartitionId::new(2),
+            PartitionId::new(3),
+        );
+
+        source.hint_upcoming(&[p1, p2]);
+        prefetched(&source, 2).await;
+        assert_eq!(inner.fetches(), 2);
+
+        // the hinted partitions are fetched together
+        assert_eq!(inner.batches(), vec![vec![p1, p2]]);
+
+        // hinted partitions are not fetched again
+        let partition_files = source.fetch_with_partition_info(p1).await.unwrap();
+        assert_eq!(partition_files.partition.id, p1);
+        assert_eq!(partition_files.files.len(), 1);
+        assert_eq!(source.fetch(p2).await.unwrap().files.len(), 1);
+        assert_eq!(inner.fetches(), 2);
+
+        // nor are partitions hinted again
+        source.hint_upcoming(&[p1, p2]);
+        assert_eq!(inner.fetches(), 2);
+
+        // other partitions still work
+        let partition_files = source.fetch_with_partition_info(p3).await.unwrap();
+        assert_eq!(partition_files.partition.id, p3);
+        assert_eq!(inner.fetches(), 3);
+
+        let files = source.fetch_many(&[p1, p3]).await.unwrap();
+        assert_eq!(files[&p1].files.len(), 1);
+        assert_eq!(files[&p3].files.len(), 1);
+        assert_eq!(inner.fetches(), 4);
+        assert_eq!(inner.batches(), vec![vec![p1, p2], vec![p3]]);
+    }
+
+    #[tokio::test]
+    async fn test_ttl() {
+        let (source, inner, time_provider) = source(inner(), 2);
+        let p1 = PartitionId::new(1);
+
+        source.hint_upcoming(&[p1]);
+        prefetched(&source, 1).await;
+
+        time_provider.inc(TTL - Duration::from_millis(1));
+        source.fetch(p1).await.unwrap();
+        assert_eq!(inner.fetches(), 1);
+
+        // expired
+        time_provider.inc(Duration::from_millis(1));
+        source.fetch(p1).await.unwrap();
+        assert_eq!(inner.fetches(), 2);
+
+        // and prefetched again
+        source.hint_upcoming(&[p1]);
+        prefetched(&source, 1).await;
+        source.fetch(p1).await.unwrap();
+        assert_eq!(inner.fetches(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate() {
+        let (source, inner, _time_provider) = source(inner(), 2);
+        let prefetcher = source.prefetcher();
+        let p1 = PartitionId::new(1);
+
+        source.hint_upcoming(&[p1]);
+        prefetched(&source, 1).await;
+
+        prefetcher.invalidate(p1);
+        source.fetch(p1).await.unwrap();
+        assert_eq!(inner.fetches(), 2);
+
+        // unknown partitions are fine
+        prefetcher.invalidate(PartitionId::new(42));
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_while_prefetching() {
+        let p1 = PartitionId::new(1);
+        let file = |id| {
+            ParquetFileBuilder::new(id)
+                .with_partition(partition_identifier(1))
+                .build()
+        };
+        let inner = inner().with_script(
+            p1,
+            [
+                MockFetch::files(vec![file(1)]).with_delay(Duration::from_millis(50)),
+                MockFetch::files(vec![file(2)]),
+            ],
+        );
+        let (source, _inner, _time_provider) = source(inner, 2);
+
+        source.hint_upcoming(&[p1]);
+        source.prefetcher().invalidate(p1);
+
+        // the prefetch that started before the invalidation is discarded
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(source.prefetcher.ready(), 0);
+        assert_eq!(source.fetch(p1).await.unwrap().files, vec![file(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_prefetch() {
+        let p1 = PartitionId::new(1);
+        let inner = inner().with_script(p1, [MockFetch::error(ErrorKind::Catalog, "boom")]);
+        let (source, inner, _time_provider) = source(inner, 2);
+
+        source.hint_upcoming(&[p1]);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while source
+                .prefetcher
+                .prefetched
+                .lock()
+                .entries
+                .contains_key(&p1)
+            {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("prefetch done");
+
+        // failures are not used
+        assert_eq!(source.fetch(p1).await.unwrap().files.len(), 1);
+        assert_eq!(inner.fetches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency() {
+        let inner = inner().with_delay(Duration::from_millis(100));
+        let inner = Arc::new(inner);
+        let source = PrefetchingPartitionFilesSource::new(
+            Arc::clone(&inner),
+            TTL,
+            NonZeroUsize::new(1).unwrap(),
+            Arc::new(SystemProvider::new()),
+        );
+
+        source.hint_upcoming(&[PartitionId::new(1)]);
+        source.hint_upcoming(&[PartitionId::new(2)]);
+        source.hint_upcoming(&[PartitionId::new(3)]);
+
+        // one hint at a time
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(inner.fetches(), 1);
+
+        prefetched(&source, 3).await;
+        assert_eq!(inner.fetches(), 3);
+        assert_eq!(inner.batches().len(), 3);
+    }
+}
@@ -1,12 +1,96 @@
-use std::{sync::Mutex, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use tokio::time::Instant;
+use data_types::PartitionId;
+use metric::{DurationHistogram, Registry};
+use observability_deps::tracing::info;
+use tokio::{
+    sync::{Notify, OwnedMutexGuard},
+    time::Instant,
+};
+
+const METRIC_NAME_RATE_LIMIT_WAIT: &str = "iox_compactor_partition_fetch_rate_limit_wait";
+
+/// Rate of a [`RateLimit`], which can be changed while it is in use.
+#[derive(Debug)]
+pub struct RateLimitConfig {
+    rps: AtomicUsize,
+    max_burst: AtomicUsize,
+
+    /// Wakes up the queries waiting for their turn when the rate changes.
+    changed: Notify,
+}
+
+impl RateLimitConfig {
+    /// Maximum burst of queries after a period of inactivity, if not specified otherwise.
+    pub const DEFAULT_MAX_BURST: usize = 25;
+
+    /// Allow `rps` queries per second, with bursts of up to `max_burst` queries.
+    ///
+    /// # Panics
+    /// If `rps` is zero.
+    pub fn new(rps: usize, max_burst: usize) -> Self {
+        assert!(rps > 0, "rate limit must allow some queries");
+        Self {
+            rps: AtomicUsize::new(rps),
+            max_burst: AtomicUsize::new(max_burst),
+            changed: Notify::new(),
+        }
+    }
+
+    /// Queries allowed per second.
+    pub fn rps(&self) -> usize {
+        self.rps.load(Ordering::Relaxed)
+    }
+
+    /// Maximum burst of queries after a period of inactivity.
+    pub fn max_burst(&self) -> usize {
+        self.max_burst.load(Ordering::Relaxed)
+    }
+
+    /// Change the queries allowed per second.
+    ///
+    /// Queries already waiting for their turn are paced at the new rate.
+    ///
+    /// # Panics
+    /// If `rps` is zero.
+    pub fn set_rps(&self, rps: usize) {
+        assert!(rps > 0, "rate limit must allow some queries");
+        let old = self.rps.swap(rps, Ordering::Relaxed);
+        if old != rps {
+            info!(old, new = rps, "rate limit changed");
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Change the queries allowed per second and the maximum burst.
+    ///
+    /// # Panics
+    /// If `rps` is zero.
+    pub fn set(&self, rps: usize, max_burst: usize) {
+        assert!(rps > 0, "rate limit must allow some queries");
+        self.max_burst.store(max_burst, Ordering::Relaxed);
+        if self.rps.swap(rps, Ordering::Relaxed) != rps {
+            self.changed.notify_waiters();
+        }
+    }
+
+    fn min_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.rps() as u32
+    }
+}
 
 /// A [`RateLimit`] rate limiter that smooths `N` queries over a second.
 #[derive(Debug)]
 pub struct RateLimit {
     last_query: Mutex<Instant>,
-    min_interval: Mutex<Duration>,
+    config: Arc<RateLimitConfig>,
 
     // if we compute a simple interval and enforce at least that much time between each, a variable query
     // rate will be unpredictably slower than the specified rate.  So when the delay between queries is more
@@ -14,28 +98,84 @@ pub struct RateLimit {
     // inactivity.
     //
     // burst_balance is how many can proceed immediately, without delay, due to slowness of the previous.
+    //
+    // The maximum burst balance we allow is part of the config.  Without it, a large delay in queries
+    // could allow too big of a burst of queries.
     burst_balance: Mutex<usize>,
-    // max_burst is the maximum burst balance we allow.  Without this, a large delay in queries could
-    // allow too big of a burst of queries.
-    max_burst: Mutex<usize>,
+
+    /// Queries waiting for their turn in [`acquire`](Self::acquire), served in the order they
+    /// arrived, since the tokio mutex is fair.
+    queue: tokio::sync::Mutex<()>,
+
+    /// Minimum time between two queries for the same partition.
+    partition_interval: Option<Duration>,
+
+    /// When each partition was last queried. A query holds the lock of its partitions until it
+    /// proceeds, so that queries for the same partition are spaced out one after the other.
+    partitions: Mutex<HashMap<PartitionId, Arc<tokio::sync::Mutex<Option<Instant>>>>>,
+
+    queue_wait: Option<DurationHistogram>,
+    partition_interval_wait: Option<DurationHistogram>,
 }
 
 impl RateLimit {
     pub(crate) fn new(rps: usize, max_burst: usize) -> Self {
+        Self::with_config(Arc::new(RateLimitConfig::new(rps, max_burst)))
+    }
+
+    /// Rate limiter whose rate is controlled by a shared `config`.
+    pub(crate) fn with_config(config: Arc<RateLimitConfig>) -> Self {
         Self {
             last_query: Mutex::new(Instant::now()),
-            min_interval: Mutex::new(Duration::from_secs(1) / rps as u32),
-            max_burst: Mutex::new(max_burst),
+            config,
             burst_balance: Mutex::new(0),
+            queue: tokio::sync::Mutex::new(()),
+            partition_interval: None,
+            partitions: Mutex::new(HashMap::new()),
+            queue_wait: None,
+            partition_interval_wait: None,
+        }
+    }
+
+    /// In [`acquire`](Self::acquire), let at most one query for the same partition proceed every
+    /// `partition_interval`, whatever the rate.
+    pub fn with_partition_interval(self, partition_interval: Duration) -> Self {
+        Self {
+            partition_interval: Some(partition_interval),
+            ..self
+        }
+    }
+
+    /// Record the time queries wait in [`acquire`](Self::acquire).
+    pub fn with_metrics(self, registry: &Registry) -> Self {
+        let metric = registry.register_metric::<DurationHistogram>(
+            METRIC_NAME_RATE_LIMIT_WAIT,
+            "Time partition fetch queries waited for their turn",
+        );
+        Self {
+            queue_wait: Some(metric.recorder(&[("reason", "rate")])),
+            partition_interval_wait: Some(metric.recorder(&[("reason", "partition_interval")])),
+            ..self
         }
     }
 
+    /// The config controlling the rate of this limiter.
+    pub fn config(&self) -> &Arc<RateLimitConfig> {
+        &self.config
+    }
+
     pub fn can_proceed(&self) -> Option<Duration> {
         let mut last_query = self.last_query.lock().unwrap();
         let mut burst_balance = self.burst_balance.lock().unwrap();
-        let interval = *self.min_interval.lock().unwrap();
+        let interval = self.config.min_interval();
+        let max = self.config.max_burst();
         let now = Instant::now();
 
+        // The maximum burst may have been lowered since the balance was built up.
+        if *burst_balance > max {
+            *burst_balance = max;
+        }
+
         // Has enough time passed since the last query was allowed?
         let next_allowed = last_query.checked_add(interval).unwrap();
         if now < next_allowed {
@@ -52,7 +192,6 @@ impl RateLimit {
         // request was more than 2x the min interval, we can add to the burst balance.
         let credits = now.duration_since(next_allowed).as_nanos() / interval.as_nanos();
         if credits > 1 {
-            let max = *self.max_burst.lock().unwrap();
             *burst_balance += credits as usize - 1;
 
             if *burst_balance > max {
@@ -65,11 +204,302 @@ impl RateLimit {
     }
 
     pub fn update_rps(&self, rps: usize, max_burst: usize) {
-        *self.min_interval.lock().unwrap() = Duration::from_secs(1) / rps as u32;
-        *self.max_burst.lock().unwrap() = max_burst;
-        let mut burst_balance = self.burst_balance.lock().unwrap();
-        if *burst_balance > max_burst {
-            *burst_balance = max_burst;
+        self.config.set(rps, max_burst);
+    }
+
+    /// Sleep for `d`, the delay returned by [`can_proceed`](Self::can_proceed), but wake up early
+    /// if the rate changes meanwhile, so that the caller can check again at the new rate.
+    pub async fn wait(&self, d: Duration) {
+        tokio::select! {
+            _ = tokio::time::sleep(d) => {}
+            _ = self.config.changed.notified() => {}
+        }
+    }
+
+    /// Wait until a query for `partition_ids` may proceed, and return how long that took if it had
+    /// to wait at all.
+    ///
+    /// Unlike polling [`can_proceed`](Self::can_proceed), queries proceed in the order they
+    /// arrived, so a partition that is queried over and over cannot starve the others. With a
+    /// [partition interval](Self::with_partition_interval), a query first waits for the interval
+    /// to pass since the last query for any of its partitions, without holding up the queries for
+    /// other partitions meanwhile.
+    pub async fn acquire(&self, partition_ids: &[PartitionId]) -> Option<Duration> {
+        let start = Instant::now();
+
+        let (partitions, mut waited) = match self.partition_interval {
+            Some(interval) => self.wait_for_partitions(partition_ids, interval).await,
+            None => (vec![], false),
+        };
+        let queued = Instant::now();
+
+        {
+            let _turn = match self.queue.try_lock() {
+                Ok(turn) => turn,
+                Err(_) => {
+                    waited = true;
+                    self.queue.lock().await
+                }
+            };
+            while let Some(d) = self.can_proceed() {
+                waited = true;
+                self.wait(d).await;
+            }
+        }
+        let now = Instant::now();
+
+        for mut last_query in partitions {
+            *last_query = Some(now);
+        }
+
+        if let Some(partition_interval_wait) = &self.partition_interval_wait {
+            partition_interval_wait.record(queued - start);
         }
+        if let Some(queue_wait) = &self.queue_wait {
+            queue_wait.record(now - queued);
+        }
+        waited.then(|| now - start)
+    }
+
+    /// Lock the partitions, and wait for `interval` to pass since their last query.
+    ///
+    /// Also returns whether that took any waiting.
+    async fn wait_for_partitions(
+        &self,
+        partition_ids: &[PartitionId],
+        interval: Duration,
+    ) -> (Vec<OwnedMutexGuard<Option<Instant>>>, bool) {
+        // locking in order can't deadlock with a query for an overlapping set of partitions
+        let mut partition_ids = partition_ids.to_vec();
+        partition_ids.sort();
+        partition_ids.dedup();
+
+        let locks: Vec<_> = {
+            let mut partitions = self.partitions.lock().unwrap();
+
+            // forget the partitions that may be queried again right away, and that no query holds
+            let now = Instant::now();
+            partitions.retain(|_partition_id, last_query| {
+                Arc::strong_count(last_query) > 1
+                    || matches!(
+                        last_query.try_lock().map(|last_query| *last_query),
+                        Ok(Some(last_query)) if last_query + interval > now
+                    )
+            });
+
+            partition_ids
+                .iter()
+                .map(|partition_id| Arc::clone(partitions.entry(*partition_id).or_default()))
+                .collect()
+        };
+
+        let mut waited = false;
+        let mut guards = Vec::with_capacity(locks.len());
+        for lock in locks {
+            let guard = match Arc::clone(&lock).try_lock_owned() {
+                Ok(guard) => guard,
+                Err(_) => {
+                    waited = true;
+                    lock.lock_owned().await
+                }
+            };
+            guards.push(guard);
+        }
+
+        let next_allowed = guards
+            .iter()
+            .filter_map(|last_query| **last_query)
+            .max()
+            .map(|last_query| last_query + interval);
+        if let Some(next_allowed) = next_allowed {
+            if next_allowed > Instant::now() {
+                waited = true;
+                tokio::time::sleep_until(next_allowed).await;
+            }
+        }
+
+        (guards, waited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use metric::{assert_histogram, Attributes};
+
+    use super::*;
+
+    /// Time taken by `n` queries.
+    async fn take(limit: &RateLimit, n: usize) -> Duration {
+        let start = Instant::now();
+        for _ in 0..n {
+            while let Some(d) = limit.can_proceed() {
+                limit.wait(d).await;
+            }
+        }
+        Instant::now() - start
+    }
+
+    #[tokio::test]
+    async fn test_set_rps() {
+        let config = Arc::new(RateLimitConfig::new(100, 0));
+        let limit = RateLimit::with_config(Arc::clone(&config));
+
+        // 10ms per query
+        let duration = take(&limit, 5).await;
+        assert!(duration >= Duration::from_millis(50));
+        assert!(duration < Duration::from_millis(100));
+
+        // 50ms per query
+        config.set_rps(20);
+        assert_eq!(limit.config().rps(), 20);
+        let duration = take(&limit, 3).await;
+        assert!(duration >= Duration::from_millis(100));
+        assert!(duration < Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_set_rps_wakes_waiters() {
+        let config = Arc::new(RateLimitConfig::new(1, 0));
+        let limit = Arc::new(RateLimit::with_config(Arc::clone(&config)));
+
+        // the query would wait for a second at the initial rate
+        let waiter = tokio::spawn({
+            let limit = Arc::clone(&limit);
+            async move { take(&limit, 1).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        config.set_rps(100);
+
+        let duration = waiter.await.unwrap();
+        assert!(duration < Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_lower_max_burst() {
+        let config = Arc::new(RateLimitConfig::new(100, 10));
+        let limit = RateLimit::with_config(Arc::clone(&config));
+
+        // build up the burst balance
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(limit.can_proceed().is_none());
+
+        config.set(100, 1);
+        assert!(limit.can_proceed().is_none());
+        assert!(limit.can_proceed().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_without_waiting() {
+        let limit = RateLimit::new(1000, 0);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(limit.acquire(&[PartitionId::new(1)]).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_is_fair() {
+        let limit = Arc::new(RateLimit::new(100, 0));
+        let order = Arc::new(Mutex::new(vec![]));
+        let (p1, p2) = (PartitionId::new(1), PartitionId::new(2));
+
+        // a partition queried over and over, by several tasks at once
+        let hot: Vec<_> = (0..5)
+            .map(|_| {
+                let limit = Arc::clone(&limit);
+                let order = Arc::clone(&order);
+                tokio::spawn(async move {
+                    for _ in 0..8 {
+                        limit.acquire(&[p1]).await;
+                        order.lock().unwrap().push(p1);
+                    }
+                })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // only waits for the queries that arrived before it
+        let waited = limit.acquire(&[p2]).await.unwrap();
+        order.lock().unwrap().push(p2);
+        assert!(waited < Duration::from_millis(150), "{waited:?}");
+
+        for task in hot {
+            task.await.unwrap();
+        }
+        let order = order.lock().unwrap();
+        assert_eq!(order.len(), 41);
+        let pos = order.iter().position(|p| *p == p2).unwrap();
+        assert!(pos < 20, "{pos}");
+    }
+
+    #[tokio::test]
+    async fn test_partition_interval() {
+        const INTERVAL: Duration = Duration::from_millis(100);
+        let limit = Arc::new(RateLimit::new(1000, 100).with_partition_interval(INTERVAL));
+        let start = Instant::now();
+
+        // interleaved queries for two partitions
+        let tasks: Vec<_> = [PartitionId::new(1), PartitionId::new(2)]
+            .into_iter()
+            .map(|partition_id| {
+                let limit = Arc::clone(&limit);
+                tokio::spawn(async move {
+                    let mut times = vec![];
+                    for _ in 0..3 {
+                        limit.acquire(&[partition_id]).await;
+                        times.push(Instant::now());
+                    }
+                    times
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            let times = task.await.unwrap();
+
+            // neither partition waits for the other
+            assert!(times[0] - start < INTERVAL / 2);
+
+            // but each waits for its own interval
+            for pair in times.windows(2) {
+                assert!(pair[1] - pair[0] >= INTERVAL);
+            }
+        }
+
+        // a batch waits for the interval of all of its partitions
+        let waited = limit
+            .acquire(&[
+                PartitionId::new(2),
+                PartitionId::new(3),
+                PartitionId::new(1),
+            ])
+            .await
+            .unwrap();
+        assert!(waited > INTERVAL / 2);
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let registry = Registry::new();
+        let limit = RateLimit::new(1000, 100)
+            .with_partition_interval(Duration::from_millis(10))
+            .with_metrics(&registry);
+
+        limit.acquire(&[PartitionId::new(1)]).await;
+        limit.acquire(&[PartitionId::new(1)]).await;
+
+        for reason in ["rate", "partition_interval"] {
+            assert_histogram!(
+                registry,
+                DurationHistogram,
+                METRIC_NAME_RATE_LIMIT_WAIT,
+                labels = Attributes::from(&[("reason", reason)]),
+                samples = 2,
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "rate limit must allow some queries")]
+    fn test_zero_rps() {
+        RateLimitConfig::new(1, 0).set_rps(0);
     }
 }
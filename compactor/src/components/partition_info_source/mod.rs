@@ -4,7 +4,7 @@ use std::{
 };
 
 use async_trait::async_trait;
-use data_types::PartitionId;
+use data_types::{Partition, PartitionId};
 
 use crate::{error::DynError, partition_info::PartitionInfo};
 
@@ -14,4 +14,11 @@ pub mod sub_sources;
 #[async_trait]
 pub trait PartitionInfoSource: Debug + Display + Send + Sync {
     async fn fetch(&self, partition_id: PartitionId) -> Result<Arc<PartitionInfo>, DynError>;
+
+    /// Same as [`fetch`](Self::fetch), for a partition that was already fetched, e.g. together
+    /// with its files.
+    async fn fetch_for_partition(
+        &self,
+        partition: Partition,
+    ) -> Result<Arc<PartitionInfo>, DynError>;
 }
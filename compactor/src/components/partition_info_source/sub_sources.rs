@@ -1,7 +1,7 @@
 use std::{fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
-use data_types::PartitionId;
+use data_types::{Partition, PartitionId};
 
 use crate::{
     components::{
@@ -71,6 +71,13 @@ where
             .await
             .ok_or_else::<DynError, _>(|| String::from("Cannot find partition info").into())?;
 
+        self.fetch_for_partition(partition).await
+    }
+
+    async fn fetch_for_partition(
+        &self,
+        partition: Partition,
+    ) -> Result<Arc<PartitionInfo>, DynError> {
         let table = self
             .tables_source
             .fetch(partition.table_id)
@@ -97,14 +104,16 @@ where
             .ok_or_else::<DynError, _>(|| String::from("Cannot find table schema").into())?;
 
         Ok(Arc::new(PartitionInfo {
-            partition_id,
+            partition_id: partition.id,
             partition_hash_id: partition.hash_id().cloned(),
             namespace_id: table.namespace_id,
             namespace_name: namespace.name,
+            retention_period_ns: namespace.retention_period_ns,
             table: Arc::new(table),
             table_schema: Arc::new(table_schema.clone()),
             sort_key: partition.sort_key(),
             partition_key: partition.partition_key,
+            files_truncated: false,
         }))
     }
 }
@@ -37,7 +37,43 @@ pub fn log_config(config: &Config) {
         all_errors_are_fatal,
         max_num_columns_per_table,
         max_num_files_per_plan,
-        max_partition_fetch_queries_per_second,
+        early_l1_to_l2_size_multiplier,
+        allow_early_l1_to_l2,
+        cold_compaction_threshold,
+        vertical_split_l2_hints,
+        max_split_times_per_round,
+        per_chain_overlaps,
+        drop_expired_files,
+        max_estimated_arrow_bytes_per_plan,
+        arrow_expansion_per_column,
+        max_branches_per_round,
+        many_small_files_window,
+        round_decision_log,
+        max_repeated_rounds,
+        adaptive_file_size_per_plan_floor,
+        adaptive_file_size_per_plan_ceiling,
+        max_total_rows_per_plan,
+        time_bucket,
+        scratchpad_capacity_bytes,
+        scratchpad_capacity_policy,
+        scratchpad_max_concurrent_transfers,
+        scratchpad_keep_written,
+        scratchpad_read_through_bytes,
+        scratchpad_transfer_max_attempts,
+        scratchpad_breaker_failures,
+        scratchpad_breaker_cooldown,
+        partition_files_cache_ttl,
+        partition_files_cache_max_entries,
+        partition_files_fetch_timeout,
+        max_partitions_per_fetch_query,
+        partition_files_filter_max_file_size_bytes,
+        partition_files_commit_window,
+        partition_fetch_min_interval,
+        partition_files_page_size,
+        partition_files_max_files,
+        partition_files_prefetch_ttl,
+        partition_files_prefetch_concurrency,
+        partition_fetch_rate_limit,
     } = &config;
 
     let parquet_files_sink_override = parquet_files_sink_override
@@ -69,7 +105,43 @@ pub fn log_config(config: &Config) {
         all_errors_are_fatal,
         max_num_columns_per_table,
         max_num_files_per_plan,
-        max_partition_fetch_queries_per_second,
+        early_l1_to_l2_size_multiplier,
+        allow_early_l1_to_l2,
+        cold_compaction_threshold_secs=cold_compaction_threshold.map(|d| d.as_secs_f32()),
+        vertical_split_l2_hints,
+        max_split_times_per_round,
+        per_chain_overlaps,
+        drop_expired_files,
+        max_estimated_arrow_bytes_per_plan,
+        arrow_expansion_per_column,
+        max_branches_per_round,
+        many_small_files_window_secs=many_small_files_window.map(|d| d.as_secs_f32()),
+        round_decision_log_capacity=round_decision_log.as_ref().map(|log| log.capacity()),
+        max_repeated_rounds,
+        adaptive_file_size_per_plan_floor,
+        adaptive_file_size_per_plan_ceiling,
+        max_total_rows_per_plan,
+        time_bucket_secs=time_bucket.map(|d| d.as_secs_f32()),
+        scratchpad_capacity_bytes,
+        ?scratchpad_capacity_policy,
+        scratchpad_max_concurrent_transfers=scratchpad_max_concurrent_transfers.map(|n| n.get()),
+        scratchpad_keep_written,
+        scratchpad_read_through_bytes,
+        scratchpad_transfer_max_attempts=scratchpad_transfer_max_attempts.map(|n| n.get()),
+        scratchpad_breaker_failures=scratchpad_breaker_failures.map(|n| n.get()),
+        scratchpad_breaker_cooldown_secs=scratchpad_breaker_cooldown.as_secs_f32(),
+        partition_files_cache_ttl_secs=partition_files_cache_ttl.map(|d| d.as_secs_f32()),
+        partition_files_cache_max_entries=partition_files_cache_max_entries.get(),
+        partition_files_fetch_timeout_secs=partition_files_fetch_timeout.map(|d| d.as_secs_f32()),
+        max_partitions_per_fetch_query=max_partitions_per_fetch_query.get(),
+        partition_files_filter_max_file_size_bytes,
+        partition_files_commit_window_secs=partition_files_commit_window.map(|d| d.as_secs_f32()),
+        partition_fetch_min_interval_secs=partition_fetch_min_interval.map(|d| d.as_secs_f32()),
+        partition_files_page_size=partition_files_page_size.map(|n| n.get()),
+        partition_files_max_files=partition_files_max_files.map(|n| n.get()),
+        partition_files_prefetch_ttl_secs=partition_files_prefetch_ttl.map(|d| d.as_secs_f32()),
+        partition_files_prefetch_concurrency=partition_files_prefetch_concurrency.get(),
+        max_partition_fetch_queries_per_second=partition_fetch_rate_limit.as_ref().map(|limit| limit.rps()),
         "config",
     );
 }
@@ -81,6 +153,7 @@ pub fn log_components(components: &Components) {
         compaction_job_stream,
         partition_info_source,
         partition_files_source,
+        partition_files_state_source,
         round_info_source,
         partition_filter,
         post_classification_partition_filter: partition_too_large_to_compact_filter,
@@ -95,12 +168,14 @@ pub fn log_components(components: &Components) {
         scratchpad_gen,
         file_classifier,
         changed_files_filter,
+        output_ratio_tracker,
     } = components;
 
     info!(
         %compaction_job_stream,
         %partition_info_source,
         %partition_files_source,
+        %partition_files_state_source,
         %round_info_source,
         %partition_filter,
         %partition_too_large_to_compact_filter,
@@ -115,6 +190,7 @@ pub fn log_components(components: &Components) {
         %scratchpad_gen,
         %file_classifier,
         %changed_files_filter,
+        %output_ratio_tracker,
         "component setup",
     );
 }
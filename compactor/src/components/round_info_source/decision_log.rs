@@ -0,0 +1,289 @@
+use std::{collections::VecDeque, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use data_types::{CompactionLevel, ParquetFile, PartitionId};
+use iox_time::{Time, TimeProvider};
+use parking_lot::Mutex;
+
+use crate::{components::Components, error::DynError, PartitionInfo, RoundInfo};
+
+use super::RoundInfoSource;
+
+/// A round decision, as recorded in a [`RoundDecisionLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundDecision {
+    pub partition_id: PartitionId,
+    pub time: Time,
+    /// Name of the chosen [`RoundInfo`] variant, see [`RoundInfo::name`].
+    pub variant: &'static str,
+    pub num_l0_files: usize,
+    pub num_l1_files: usize,
+    pub num_l2_files: usize,
+    pub num_branches: usize,
+}
+
+/// Bounded history of the most recent round decisions, across all partitions.
+///
+/// Once `capacity` decisions are recorded, each new decision evicts the oldest one.
+#[derive(Debug)]
+pub struct RoundDecisionLog {
+    capacity: usize,
+    entries: Mutex<VecDeque<RoundDecision>>,
+}
+
+impl RoundDecisionLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Maximum number of decisions kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Record a decision, evicting the oldest one if the log is full.
+    pub fn record(&self, decision: RoundDecision) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(decision);
+    }
+
+    /// The decisions kept, oldest first.
+    pub fn entries(&self) -> Vec<RoundDecision> {
+        self.entries.lock().iter().cloned().collect()
+    }
+
+    /// The decisions kept for the given partition, oldest first.
+    pub fn entries_for_partition(&self, partition_id: PartitionId) -> Vec<RoundDecision> {
+        self.entries
+            .lock()
+            .iter()
+            .filter(|decision| decision.partition_id == partition_id)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Records the decisions of the inner [`RoundInfoSource`] in a [`RoundDecisionLog`].
+#[derive(Debug)]
+pub struct DecisionLogRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    log: Arc<RoundDecisionLog>,
+    time_provider: Arc<dyn TimeProvider>,
+    inner: T,
+}
+
+impl<T> DecisionLogRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    pub fn new(inner: T, log: Arc<RoundDecisionLog>, time_provider: Arc<dyn TimeProvider>) -> Self {
+        Self {
+            log,
+            time_provider,
+            inner,
+        }
+    }
+}
+
+impl<T> Display for DecisionLogRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "decision_log({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> RoundInfoSource for DecisionLogRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    async fn calculate(
+        &self,
+        components: Arc<Components>,
+        last_round_info: Option<RoundInfo>,
+        partition_info: &PartitionInfo,
+        files: Vec<ParquetFile>,
+    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        let num_files = |level| files.iter().filter(|f| f.compaction_level == level).count();
+        let num_l0_files = num_files(CompactionLevel::Initial);
+        let num_l1_files = num_files(CompactionLevel::FileNonOverlapped);
+        let num_l2_files = num_files(CompactionLevel::Final);
+
+        let res = self
+            .inner
+            .calculate(components, last_round_info, partition_info, files)
+            .await;
+
+        if let Ok((round_info, branches, _files_later)) = &res {
+            self.log.record(RoundDecision {
+                partition_id: partition_info.partition_id,
+                time: self.time_provider.now(),
+                variant: round_info.name(),
+                num_l0_files,
+                num_l1_files,
+                num_l2_files,
+                num_branches: branches.len(),
+            });
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_tests::ParquetFileBuilder;
+    use iox_time::MockProvider;
+
+    use crate::{
+        components::round_info_source::mock::MockRoundInfoSource,
+        test_utils::{test_components, PartitionInfoBuilder},
+    };
+
+    use super::*;
+
+    fn decision(partition_id: i64, time: Time) -> RoundDecision {
+        RoundDecision {
+            partition_id: PartitionId::new(partition_id),
+            time,
+            variant: "many_small_files",
+            num_l0_files: 0,
+            num_l1_files: 0,
+            num_l2_files: 0,
+            num_branches: 0,
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let source = DecisionLogRoundInfoWrapper::new(
+            MockRoundInfoSource::new(vec![]),
+            Arc::new(RoundDecisionLog::new(10)),
+            Arc::new(MockProvider::new(Time::from_timestamp_nanos(0))),
+        );
+        assert_eq!(source.to_string(), "decision_log(mock)");
+    }
+
+    #[test]
+    fn test_log_evicts_oldest() {
+        let log = RoundDecisionLog::new(2);
+        let t = Time::from_timestamp_nanos;
+
+        log.record(decision(1, t(1)));
+        log.record(decision(2, t(2)));
+        assert_eq!(log.entries(), vec![decision(1, t(1)), decision(2, t(2))]);
+
+        log.record(decision(1, t(3)));
+        assert_eq!(log.entries(), vec![decision(2, t(2)), decision(1, t(3))]);
+        assert_eq!(
+            log.entries_for_partition(PartitionId::new(1)),
+            vec![decision(1, t(3))]
+        );
+        assert_eq!(log.capacity(), 2);
+    }
+
+    #[test]
+    fn test_log_zero_capacity() {
+        let log = RoundDecisionLog::new(0);
+        log.record(decision(1, Time::from_timestamp_nanos(1)));
+        assert!(log.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_records_each_calculate() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(100)));
+        let log = Arc::new(RoundDecisionLog::new(10));
+        let partition_info = PartitionInfoBuilder::new().with_partition_id(7).build();
+
+        let l0 = |id| {
+            ParquetFileBuilder::new(id)
+                .with_compaction_level(CompactionLevel::Initial)
+                .build()
+        };
+        let l1 = ParquetFileBuilder::new(3)
+            .with_compaction_level(CompactionLevel::FileNonOverlapped)
+            .build();
+        let files = vec![l0(1), l0(2), l1.clone()];
+
+        let many_small_files = (
+            RoundInfo::ManySmallFiles {
+                start_level: CompactionLevel::Initial,
+                max_num_files_to_group: 2,
+                max_total_file_size_to_group: 100,
+                max_l0_created_at_window_ns: None,
+                level_stats: Default::default(),
+            },
+            vec![vec![l0(1), l0(2)]],
+            vec![l1.clone()],
+        );
+        let target_level = (
+            RoundInfo::TargetLevel {
+                target_level: CompactionLevel::Final,
+                max_total_file_size_to_group: 100,
+                estimated_branch_output_bytes: vec![],
+                level_stats: Default::default(),
+            },
+            vec![vec![l1.clone()], vec![l0(4)]],
+            vec![],
+        );
+        let source = DecisionLogRoundInfoWrapper::new(
+            MockRoundInfoSource::new(vec![many_small_files, target_level]).with_error("failed"),
+            Arc::clone(&log),
+            Arc::clone(&time_provider) as _,
+        );
+
+        source
+            .calculate(test_components(), None, &partition_info, files)
+            .await
+            .unwrap();
+        time_provider.inc(std::time::Duration::from_nanos(50));
+        source
+            .calculate(test_components(), None, &partition_info, vec![l1, l0(4)])
+            .await
+            .unwrap();
+
+        // errors are not recorded
+        source
+            .calculate(test_components(), None, &partition_info, vec![])
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            log.entries(),
+            vec![
+                RoundDecision {
+                    partition_id: PartitionId::new(7),
+                    time: Time::from_timestamp_nanos(100),
+                    variant: "many_small_files",
+                    num_l0_files: 2,
+                    num_l1_files: 1,
+                    num_l2_files: 0,
+                    num_branches: 1,
+                },
+                RoundDecision {
+                    partition_id: PartitionId::new(7),
+                    time: Time::from_timestamp_nanos(150),
+                    variant: "target_level",
+                    num_l0_files: 1,
+                    num_l1_files: 1,
+                    num_l2_files: 0,
+                    num_branches: 2,
+                },
+            ]
+        );
+    }
+}
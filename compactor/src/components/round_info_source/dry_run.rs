@@ -0,0 +1,204 @@
+use std::{fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use data_types::{CompactionLevel, ParquetFile, ParquetFileId, PartitionId};
+use observability_deps::tracing::debug;
+use parking_lot::Mutex;
+
+use crate::{components::Components, error::DynError, PartitionInfo, RoundInfo};
+
+use super::RoundInfoSource;
+
+/// A file as seen by a [`PlannedRound`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedFile {
+    pub id: ParquetFileId,
+    pub file_size_bytes: i64,
+    pub compaction_level: CompactionLevel,
+}
+
+impl From<&ParquetFile> for PlannedFile {
+    fn from(f: &ParquetFile) -> Self {
+        Self {
+            id: f.id,
+            file_size_bytes: f.file_size_bytes,
+            compaction_level: f.compaction_level,
+        }
+    }
+}
+
+/// What the compactor would have done in a round, as recorded by [`DryRunRoundInfoSource`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedRound {
+    pub partition_id: PartitionId,
+    pub round_info: RoundInfo,
+    pub branches: Vec<Vec<PlannedFile>>,
+    pub files_later: Vec<PlannedFile>,
+}
+
+/// Plans the round using the inner [`RoundInfoSource`] but never lets it execute.
+///
+/// The plan is recorded (see [`plans`](Self::plans)) and logged, and then an empty round (no
+/// branches, no files for later) is returned, so the driver ends the partition successfully
+/// without touching the object store or catalog.
+#[derive(Debug)]
+pub struct DryRunRoundInfoSource<T>
+where
+    T: RoundInfoSource,
+{
+    plans: Mutex<Vec<PlannedRound>>,
+    inner: T,
+}
+
+impl<T> DryRunRoundInfoSource<T>
+where
+    T: RoundInfoSource,
+{
+    pub fn new(inner: T) -> Self {
+        Self {
+            plans: Mutex::new(vec![]),
+            inner,
+        }
+    }
+
+    /// The rounds planned so far, in the order they were calculated.
+    pub fn plans(&self) -> Vec<PlannedRound> {
+        self.plans.lock().clone()
+    }
+}
+
+impl<T> Display for DryRunRoundInfoSource<T>
+where
+    T: RoundInfoSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dry_run({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> RoundInfoSource for DryRunRoundInfoSource<T>
+where
+    T: RoundInfoSource,
+{
+    async fn calculate(
+        &self,
+        components: Arc<Components>,
+        last_round_info: Option<RoundInfo>,
+        partition_info: &PartitionInfo,
+        files: Vec<ParquetFile>,
+    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        let (round_info, branches, files_later) = self
+            .inner
+            .calculate(components, last_round_info, partition_info, files)
+            .await?;
+
+        let plan = PlannedRound {
+            partition_id: partition_info.partition_id,
+            round_info,
+            branches: branches
+                .iter()
+                .map(|branch| branch.iter().map(PlannedFile::from).collect())
+                .collect(),
+            files_later: files_later.iter().map(PlannedFile::from).collect(),
+        };
+        debug!(
+            partition_id = partition_info.partition_id.get(),
+            round_info = %plan.round_info,
+            branches = ?plan.branches,
+            files_later = ?plan.files_later,
+            "dry run planned round",
+        );
+        let round_info = plan.round_info.clone();
+        self.plans.lock().push(plan);
+
+        Ok((round_info, vec![], vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_tests::ParquetFileBuilder;
+
+    use crate::{
+        components::round_info_source::{mock::MockRoundInfoSource, LevelBasedRoundInfo},
+        test_utils::{test_components, PartitionInfoBuilder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let source = DryRunRoundInfoSource::new(LevelBasedRoundInfo::new(10, 1000, 3.0, true));
+        assert_eq!(source.to_string(), "dry_run(LevelBasedRoundInfo 10)");
+    }
+
+    #[tokio::test]
+    async fn test_records_plan_without_executing() {
+        let inner = LevelBasedRoundInfo::new(10, 1000, 3.0, true);
+        let source = DryRunRoundInfoSource::new(LevelBasedRoundInfo::new(10, 1000, 3.0, true));
+        let partition_info = PartitionInfoBuilder::new().with_partition_id(7).build();
+
+        let files = vec![
+            ParquetFileBuilder::new(1)
+                .with_time_range(0, 100)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(10)
+                .build(),
+            ParquetFileBuilder::new(2)
+                .with_time_range(50, 150)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(20)
+                .build(),
+            ParquetFileBuilder::new(3)
+                .with_time_range(0, 200)
+                .with_compaction_level(CompactionLevel::Final)
+                .with_file_size_bytes(30)
+                .build(),
+        ];
+
+        // what would really happen
+        let (round_info, branches, files_later) = inner
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+
+        // the dry run leaves nothing to execute...
+        let (dry_round_info, dry_branches, dry_files_later) = source
+            .calculate(test_components(), None, &partition_info, files)
+            .await
+            .unwrap();
+        assert_eq!(dry_round_info, round_info);
+        assert!(dry_branches.is_empty());
+        assert!(dry_files_later.is_empty());
+
+        // ...but records it
+        let to_planned = |files: &[ParquetFile]| -> Vec<PlannedFile> {
+            files.iter().map(PlannedFile::from).collect()
+        };
+        assert_eq!(
+            source.plans(),
+            vec![PlannedRound {
+                partition_id: PartitionId::new(7),
+                round_info,
+                branches: branches.iter().map(|b| to_planned(b.as_slice())).collect(),
+                files_later: to_planned(&files_later),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inner_errors_are_not_recorded() {
+        let source = DryRunRoundInfoSource::new(
+            MockRoundInfoSource::new(vec![]).with_error("inner failure"),
+        );
+        let partition_info = PartitionInfoBuilder::new().build();
+
+        let err = source
+            .calculate(test_components(), None, &partition_info, vec![])
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "inner failure");
+        assert!(source.plans().is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    fmt::Display,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use data_types::{ParquetFile, PartitionId};
+use parking_lot::Mutex;
+
+use crate::{
+    components::Components,
+    error::{DynError, ErrorKind, SimpleError},
+    PartitionInfo, RoundInfo,
+};
+
+use super::RoundInfoSource;
+
+/// Number of rounds remembered per partition, for each repeat allowed.  This lets the detection
+/// catch loops alternating between up to this many different rounds.
+const HISTORY_LEN_PER_REPEAT: usize = 4;
+
+/// Default maximum number of partitions whose history is kept.
+const DEFAULT_MAX_TRACKED_PARTITIONS: usize = 10_000;
+
+/// Aborts the compaction of a partition when the inner [`RoundInfoSource`] keeps making the same
+/// decision over the same files.
+///
+/// For each partition, the (variant, input files) of its last rounds are remembered.  If the same
+/// variant is chosen for the same set of files more than `max_repeats` times, the compaction of the
+/// partition is not making progress (e.g. rounds alternate between undoing and redoing the same
+/// work), so an error is returned instead of the round.
+///
+/// The history of a partition starts over with each new compaction of the partition (i.e. when there
+/// is no previous round).  Since the wrapper doesn't know when a partition is done, the history of at
+/// most `max_tracked_partitions` partitions is kept, forgetting the least recently seen ones first.
+#[derive(Debug)]
+pub struct LoopDetectingRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    max_repeats: usize,
+    history_len: usize,
+    max_tracked_partitions: usize,
+    state: Mutex<State>,
+    inner: T,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Incremented on each round, to find the least recently seen partition.
+    seq: u64,
+    partitions: HashMap<PartitionId, PartitionHistory>,
+}
+
+#[derive(Debug, Default)]
+struct PartitionHistory {
+    last_seen: u64,
+    /// (variant, fingerprint of the input files) of the most recent rounds, oldest first.
+    rounds: VecDeque<(&'static str, u64)>,
+}
+
+impl<T> LoopDetectingRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    pub fn new(inner: T, max_repeats: usize) -> Self {
+        Self {
+            max_repeats,
+            history_len: (max_repeats + 1) * HISTORY_LEN_PER_REPEAT,
+            max_tracked_partitions: DEFAULT_MAX_TRACKED_PARTITIONS,
+            state: Default::default(),
+            inner,
+        }
+    }
+
+    /// Limit the number of partitions whose history is kept.
+    pub fn with_max_tracked_partitions(mut self, max_tracked_partitions: usize) -> Self {
+        self.max_tracked_partitions = max_tracked_partitions;
+        self
+    }
+
+    /// Record a round for the partition, returning how many times the same round occurred in its
+    /// recent history (including this one).
+    fn record(
+        &self,
+        partition_id: PartitionId,
+        new_compaction: bool,
+        round: (&'static str, u64),
+    ) -> usize {
+        let mut state = self.state.lock();
+        state.seq += 1;
+        let seq = state.seq;
+
+        let history = state.partitions.entry(partition_id).or_default();
+        if new_compaction {
+            history.rounds.clear();
+        }
+        history.last_seen = seq;
+        if history.rounds.len() == self.history_len {
+            history.rounds.pop_front();
+        }
+        history.rounds.push_back(round);
+        let repeats = history.rounds.iter().filter(|r| **r == round).count();
+
+        if repeats > self.max_repeats {
+            // the partition is aborted, its next compaction starts over
+            state.partitions.remove(&partition_id);
+        } else if state.partitions.len() > self.max_tracked_partitions {
+            let oldest = state
+                .partitions
+                .iter()
+                .min_by_key(|(_, history)| history.last_seen)
+                .map(|(partition_id, _)| *partition_id)
+                .expect("not empty");
+            state.partitions.remove(&oldest);
+        }
+
+        repeats
+    }
+}
+
+impl<T> Display for LoopDetectingRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "loop_detection({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> RoundInfoSource for LoopDetectingRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    async fn calculate(
+        &self,
+        components: Arc<Components>,
+        last_round_info: Option<RoundInfo>,
+        partition_info: &PartitionInfo,
+        files: Vec<ParquetFile>,
+    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        let fingerprint = fingerprint(&files);
+        let new_compaction = last_round_info.is_none();
+
+        let (round_info, branches, files_later) = self
+            .inner
+            .calculate(components, last_round_info, partition_info, files)
+            .await?;
+
+        let repeats = self.record(
+            partition_info.partition_id,
+            new_compaction,
+            (round_info.name(), fingerprint),
+        );
+        if repeats > self.max_repeats {
+            return Err(Box::new(SimpleError::new(
+                ErrorKind::Unknown,
+                format!(
+                    "suspected compaction loop in partition {}: {} round chosen {} times for the same files",
+                    partition_info.partition_id,
+                    round_info.name(),
+                    repeats,
+                ),
+            )));
+        }
+
+        Ok((round_info, branches, files_later))
+    }
+}
+
+/// Fingerprint of a set of files, independent of their order.
+fn fingerprint(files: &[ParquetFile]) -> u64 {
+    let mut ids = files.iter().map(|f| f.id).collect::<Vec<_>>();
+    ids.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    ids.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::CompactionLevel;
+    use iox_tests::ParquetFileBuilder;
+
+    use crate::{
+        components::round_info_source::mock::{MockRoundInfoSource, Round},
+        test_utils::{test_components, PartitionInfoBuilder},
+    };
+
+    use super::*;
+
+    fn l0(id: i64) -> ParquetFile {
+        ParquetFileBuilder::new(id)
+            .with_compaction_level(CompactionLevel::Initial)
+            .build()
+    }
+
+    fn vertical_split(files: Vec<ParquetFile>) -> Round {
+        (
+            RoundInfo::VerticalSplit {
+                split_times: vec![50],
+                split_time_provenance: Default::default(),
+                estimated_branch_output_bytes: vec![],
+                level_stats: Default::default(),
+            },
+            vec![files],
+            vec![],
+        )
+    }
+
+    fn many_small_files(files: Vec<ParquetFile>) -> Round {
+        (
+            RoundInfo::ManySmallFiles {
+                start_level: CompactionLevel::Initial,
+                max_num_files_to_group: 10,
+                max_total_file_size_to_group: 100,
+                max_l0_created_at_window_ns: None,
+                level_stats: Default::default(),
+            },
+            vec![files],
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_display() {
+        let source = LoopDetectingRoundInfoWrapper::new(MockRoundInfoSource::new(vec![]), 3);
+        assert_eq!(source.to_string(), "loop_detection(mock)");
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fingerprint(&[l0(1), l0(2)]), fingerprint(&[l0(2), l0(1)]));
+        assert_ne!(fingerprint(&[l0(1), l0(2)]), fingerprint(&[l0(1), l0(3)]));
+    }
+
+    #[tokio::test]
+    async fn test_ping_pong_aborts() {
+        let partition_info = PartitionInfoBuilder::new().with_partition_id(7).build();
+        let split = vec![l0(1), l0(2)];
+        let unsplit = vec![l0(3)];
+
+        // rounds alternate between splitting the files and compacting them back, without making
+        // any progress
+        let source = LoopDetectingRoundInfoWrapper::new(
+            MockRoundInfoSource::new((0..3).flat_map(|_| {
+                [
+                    vertical_split(unsplit.clone()),
+                    many_small_files(split.clone()),
+                ]
+            })),
+            2,
+        );
+
+        let mut last_round_info = None;
+        for round in 0..4 {
+            let files = if round % 2 == 0 {
+                unsplit.clone()
+            } else {
+                split.clone()
+            };
+            let (round_info, _, _) = source
+                .calculate(test_components(), last_round_info, &partition_info, files)
+                .await
+                .unwrap();
+            last_round_info = Some(round_info);
+        }
+
+        // the third time around, the vertical split is considered a loop
+        let err = source
+            .calculate(
+                test_components(),
+                last_round_info,
+                &partition_info,
+                unsplit.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "suspected compaction loop in partition 7: vertical_split round chosen 3 times for the same files"
+        );
+
+        // the next compaction of the partition starts over
+        source
+            .calculate(test_components(), None, &partition_info, split)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_progress_is_not_a_loop() {
+        let partition_info = PartitionInfoBuilder::new().build();
+
+        // the same variant over and over is fine, as long as the files change
+        let source = LoopDetectingRoundInfoWrapper::new(
+            MockRoundInfoSource::new((0..5).map(|id| many_small_files(vec![l0(id)]))),
+            1,
+        );
+
+        let mut last_round_info = None;
+        for id in 0..5 {
+            let (round_info, _, _) = source
+                .calculate(
+                    test_components(),
+                    last_round_info,
+                    &partition_info,
+                    vec![l0(id)],
+                )
+                .await
+                .unwrap();
+            last_round_info = Some(round_info);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_reset_per_compaction() {
+        let partition_info = PartitionInfoBuilder::new().build();
+        let files = vec![l0(1)];
+
+        let source = LoopDetectingRoundInfoWrapper::new(
+            MockRoundInfoSource::new((0..3).map(|_| many_small_files(files.clone()))),
+            1,
+        );
+
+        // each compaction of the partition only does one round over the files
+        for _ in 0..3 {
+            source
+                .calculate(test_components(), None, &partition_info, files.clone())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_tracked_partitions() {
+        let files = vec![l0(1)];
+        let round = many_small_files(files.clone());
+
+        let source =
+            LoopDetectingRoundInfoWrapper::new(MockRoundInfoSource::new(vec![round; 4]), 1)
+                .with_max_tracked_partitions(1);
+
+        // partition 1 repeats a round, but partition 2 makes it forget
+        let partition_1 = PartitionInfoBuilder::new().with_partition_id(1).build();
+        let partition_2 = PartitionInfoBuilder::new().with_partition_id(2).build();
+        let (round_info, _, _) = source
+            .calculate(test_components(), None, &partition_1, files.clone())
+            .await
+            .unwrap();
+        source
+            .calculate(test_components(), None, &partition_2, files.clone())
+            .await
+            .unwrap();
+        source
+            .calculate(
+                test_components(),
+                Some(round_info.clone()),
+                &partition_1,
+                files.clone(),
+            )
+            .await
+            .unwrap();
+
+        // ...while it remains tracked, the repeat is caught
+        source
+            .calculate(test_components(), Some(round_info), &partition_1, files)
+            .await
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,230 @@
+use std::{collections::HashMap, fmt::Display, sync::Arc};
+
+use async_trait::async_trait;
+use data_types::ParquetFile;
+use metric::{Registry, U64Counter, U64Histogram, U64HistogramOptions};
+
+use crate::{components::Components, error::DynError, PartitionInfo, RoundInfo};
+
+use super::RoundInfoSource;
+
+const METRIC_NAME_ROUND_INFO_DECISION_COUNT: &str = "iox_compactor_round_info_decision";
+const METRIC_NAME_ROUND_INFO_BRANCHES: &str = "iox_compactor_round_info_branches";
+const METRIC_NAME_ROUND_INFO_FILES_LATER: &str = "iox_compactor_round_info_files_later";
+
+/// Records the [`RoundInfo`] chosen by the inner [`RoundInfoSource`], and the shape of the
+/// resulting round.
+#[derive(Debug)]
+pub struct MetricsRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    decision_count: HashMap<&'static str, U64Counter>,
+    branches: U64Histogram,
+    files_later: U64Histogram,
+    inner: T,
+}
+
+impl<T> MetricsRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    pub fn new(inner: T, registry: &Registry) -> Self {
+        let metric = registry.register_metric::<U64Counter>(
+            METRIC_NAME_ROUND_INFO_DECISION_COUNT,
+            "Number of times the compactor chose each type of round",
+        );
+        let decision_count = RoundInfo::names()
+            .iter()
+            .map(|name| (*name, metric.recorder(&[("variant", *name)])))
+            .collect();
+
+        let branches = registry
+            .register_metric_with_options::<U64Histogram, _>(
+                METRIC_NAME_ROUND_INFO_BRANCHES,
+                "Number of branches created per compaction round",
+                || U64HistogramOptions::new([1, 2, 5, 10, 20, 50, 100, u64::MAX]),
+            )
+            .recorder(&[]);
+
+        let files_later = registry
+            .register_metric_with_options::<U64Histogram, _>(
+                METRIC_NAME_ROUND_INFO_FILES_LATER,
+                "Number of files deferred to a later compaction round",
+                || U64HistogramOptions::new([0, 1, 10, 100, 1_000, 10_000, u64::MAX]),
+            )
+            .recorder(&[]);
+
+        Self {
+            decision_count,
+            branches,
+            files_later,
+            inner,
+        }
+    }
+}
+
+impl<T> Display for MetricsRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "metrics({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> RoundInfoSource for MetricsRoundInfoWrapper<T>
+where
+    T: RoundInfoSource,
+{
+    async fn calculate(
+        &self,
+        components: Arc<Components>,
+        last_round_info: Option<RoundInfo>,
+        partition_info: &PartitionInfo,
+        files: Vec<ParquetFile>,
+    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        let res = self
+            .inner
+            .calculate(components, last_round_info, partition_info, files)
+            .await;
+
+        if let Ok((round_info, branches, files_later)) = &res {
+            self.decision_count
+                .get(round_info.name())
+                .expect("all variants constructed")
+                .inc(1);
+            self.branches.record(branches.len() as u64);
+            self.files_later.record(files_later.len() as u64);
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::CompactionLevel;
+    use iox_tests::ParquetFileBuilder;
+    use metric::{assert_counter, assert_histogram, Attributes};
+
+    use crate::{
+        components::round_info_source::mock::MockRoundInfoSource,
+        test_utils::{test_components, PartitionInfoBuilder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let registry = Registry::new();
+        let source = MetricsRoundInfoWrapper::new(MockRoundInfoSource::new(vec![]), &registry);
+        assert_eq!(source.to_string(), "metrics(mock)");
+    }
+
+    #[tokio::test]
+    async fn test_records_decisions() {
+        let registry = Registry::new();
+        let partition_info = PartitionInfoBuilder::new().build();
+
+        let f1 = ParquetFileBuilder::new(1)
+            .with_compaction_level(CompactionLevel::Initial)
+            .build();
+        let f2 = ParquetFileBuilder::new(2)
+            .with_compaction_level(CompactionLevel::Initial)
+            .build();
+        let f3 = ParquetFileBuilder::new(3)
+            .with_compaction_level(CompactionLevel::Final)
+            .build();
+
+        let round_info = RoundInfo::ManySmallFiles {
+            start_level: CompactionLevel::Initial,
+            max_num_files_to_group: 2,
+            max_total_file_size_to_group: 100,
+            max_l0_created_at_window_ns: None,
+            level_stats: Default::default(),
+        };
+        let res = (
+            round_info.clone(),
+            vec![vec![f1.clone()], vec![f2.clone()]],
+            vec![f3.clone()],
+        );
+        let source =
+            MetricsRoundInfoWrapper::new(MockRoundInfoSource::new(vec![res.clone(); 2]), &registry);
+
+        for _ in 0..2 {
+            let got = source
+                .calculate(
+                    test_components(),
+                    None,
+                    &partition_info,
+                    vec![f1.clone(), f2.clone(), f3.clone()],
+                )
+                .await
+                .unwrap();
+            assert_eq!(got, res);
+        }
+
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_ROUND_INFO_DECISION_COUNT,
+            labels = Attributes::from(&[("variant", "many_small_files")]),
+            value = 2,
+        );
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_ROUND_INFO_DECISION_COUNT,
+            labels = Attributes::from(&[("variant", "target_level")]),
+            value = 0,
+        );
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ROUND_INFO_BRANCHES,
+            samples = 2,
+            sum = 4,
+        );
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ROUND_INFO_FILES_LATER,
+            samples = 2,
+            sum = 2,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_errors_not_recorded() {
+        let registry = Registry::new();
+        let partition_info = PartitionInfoBuilder::new().build();
+        let source = MetricsRoundInfoWrapper::new(
+            MockRoundInfoSource::new(vec![]).with_error("no round"),
+            &registry,
+        );
+
+        let err = source
+            .calculate(test_components(), None, &partition_info, vec![])
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no round");
+
+        for variant in RoundInfo::names() {
+            assert_counter!(
+                registry,
+                U64Counter,
+                METRIC_NAME_ROUND_INFO_DECISION_COUNT,
+                labels = Attributes::from(&[("variant", *variant)]),
+                value = 0,
+            );
+        }
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ROUND_INFO_BRANCHES,
+            samples = 0,
+        );
+    }
+}
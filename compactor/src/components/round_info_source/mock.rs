@@ -0,0 +1,153 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use data_types::ParquetFile;
+
+use crate::{components::Components, error::DynError, PartitionInfo, RoundInfo};
+
+use super::RoundInfoSource;
+
+/// A round as returned by [`RoundInfoSource::calculate`].
+pub type Round = (RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>);
+
+/// Mock for [`RoundInfoSource`] that returns a scripted sequence of results, in order.
+///
+/// Once the script is exhausted, every further call returns an error.
+#[derive(Debug, Default)]
+pub struct MockRoundInfoSource {
+    script: Mutex<VecDeque<Result<Round, String>>>,
+    calls: Mutex<Vec<Vec<ParquetFile>>>,
+}
+
+impl MockRoundInfoSource {
+    /// Create new mock returning the given rounds.
+    #[allow(dead_code)] // used for testing
+    pub fn new(rounds: impl IntoIterator<Item = Round>) -> Self {
+        Self {
+            script: Mutex::new(rounds.into_iter().map(Ok).collect()),
+            calls: Default::default(),
+        }
+    }
+
+    /// Follow the rounds scripted so far with an error.
+    #[allow(dead_code)] // used for testing
+    pub fn with_error(self, msg: impl Into<String>) -> Self {
+        self.script
+            .lock()
+            .expect("not poisoned")
+            .push_back(Err(msg.into()));
+        self
+    }
+
+    /// Get the files of each call so far, in order.
+    #[allow(dead_code)] // used for testing
+    pub fn calls(&self) -> Vec<Vec<ParquetFile>> {
+        self.calls.lock().expect("not poisoned").clone()
+    }
+}
+
+impl Display for MockRoundInfoSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mock")
+    }
+}
+
+#[async_trait]
+impl RoundInfoSource for MockRoundInfoSource {
+    async fn calculate(
+        &self,
+        _components: Arc<Components>,
+        _last_round_info: Option<RoundInfo>,
+        _partition_info: &PartitionInfo,
+        files: Vec<ParquetFile>,
+    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        let call = {
+            let mut calls = self.calls.lock().expect("not poisoned");
+            calls.push(files);
+            calls.len()
+        };
+
+        match self.script.lock().expect("not poisoned").pop_front() {
+            Some(res) => res.map_err(|e| e.into()),
+            None => Err(format!(
+                "MockRoundInfoSource exhausted: call {call} has no scripted round (were all the \
+                 expected rounds scripted?)"
+            )
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data_types::CompactionLevel;
+    use iox_tests::ParquetFileBuilder;
+
+    use crate::test_utils::{test_components, PartitionInfoBuilder};
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(MockRoundInfoSource::new(vec![]).to_string(), "mock");
+    }
+
+    #[tokio::test]
+    async fn test_scripted_rounds() {
+        let partition_info = PartitionInfoBuilder::new().build();
+        let f1 = ParquetFileBuilder::new(1).build();
+        let f2 = ParquetFileBuilder::new(2).build();
+
+        let round_1 = (
+            RoundInfo::TargetLevel {
+                target_level: CompactionLevel::FileNonOverlapped,
+                max_total_file_size_to_group: 100,
+                estimated_branch_output_bytes: vec![],
+                level_stats: Default::default(),
+            },
+            vec![vec![f1.clone()]],
+            vec![f2.clone()],
+        );
+        let round_2 = (
+            RoundInfo::TargetLevel {
+                target_level: CompactionLevel::Final,
+                max_total_file_size_to_group: 100,
+                estimated_branch_output_bytes: vec![],
+                level_stats: Default::default(),
+            },
+            vec![vec![f1.clone(), f2.clone()]],
+            vec![],
+        );
+        let source = MockRoundInfoSource::new(vec![round_1.clone(), round_2.clone()])
+            .with_error("scripted failure");
+
+        for (files, expected) in [
+            (vec![f1.clone(), f2.clone()], Ok(round_1)),
+            (vec![f2.clone()], Ok(round_2)),
+            (vec![], Err("scripted failure".to_owned())),
+            (
+                vec![f1.clone()],
+                Err(
+                    "MockRoundInfoSource exhausted: call 4 has no scripted round (were all the \
+                     expected rounds scripted?)"
+                        .to_owned(),
+                ),
+            ),
+        ] {
+            let got = source
+                .calculate(test_components(), None, &partition_info, files)
+                .await
+                .map_err(|e| e.to_string());
+            assert_eq!(got, expected);
+        }
+
+        assert_eq!(
+            source.calls(),
+            vec![vec![f1.clone(), f2.clone()], vec![f2], vec![], vec![f1]]
+        );
+    }
+}
@@ -1,21 +1,36 @@
 use std::{
     cmp::max,
+    collections::HashMap,
     fmt::{Debug, Display},
+    num::NonZeroUsize,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use crate::components::{
     split_or_compact::start_level_files_to_split::{
-        linear_dist_ranges, merge_small_l0_chains, select_split_times, split_into_chains,
+        linear_dist_ranges, merge_small_l0_chains, select_split_times_with_sources,
+        split_into_chains,
     },
     Components,
 };
 use async_trait::async_trait;
-use data_types::{CompactionLevel, FileRange, ParquetFile, Timestamp};
+use data_types::{CompactionLevel, FileRange, ParquetFile, PartitionId, Timestamp};
+use iox_time::TimeProvider;
 use itertools::Itertools;
-use observability_deps::tracing::debug;
+use observability_deps::tracing::{debug, error};
 
-use crate::{error::DynError, PartitionInfo, RoundInfo};
+use crate::{
+    error::DynError,
+    round_info::{LevelStats, SplitTime, SplitTimeProvenance, SplitTimeSource},
+    PartitionInfo, RoundInfo,
+};
+
+pub mod decision_log;
+pub mod dry_run;
+pub mod loop_detection;
+pub mod metrics;
+pub mod mock;
 
 /// Calculates information about what this compaction round does.
 /// When we get deeper into the compaction decision making, there
@@ -25,6 +40,11 @@ use crate::{error::DynError, PartitionInfo, RoundInfo};
 /// information carry that intention through the compactions.
 #[async_trait]
 pub trait RoundInfoSource: Debug + Display + Send + Sync {
+    /// Decide the next round for the given files.
+    ///
+    /// Returns the [`RoundInfo`], the branches to compact this round and the files left for the
+    /// following rounds.  A round with neither branches nor files left for later ends the
+    /// compaction of the partition without touching it.
     async fn calculate(
         &self,
         components: Arc<Components>,
@@ -34,6 +54,24 @@ pub trait RoundInfoSource: Debug + Display + Send + Sync {
     ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError>;
 }
 
+#[async_trait]
+impl<T> RoundInfoSource for Arc<T>
+where
+    T: RoundInfoSource + ?Sized,
+{
+    async fn calculate(
+        &self,
+        components: Arc<Components>,
+        last_round_info: Option<RoundInfo>,
+        partition_info: &PartitionInfo,
+        files: Vec<ParquetFile>,
+    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        self.as_ref()
+            .calculate(components, last_round_info, partition_info, files)
+            .await
+    }
+}
+
 #[derive(Debug)]
 pub struct LoggingRoundInfoWrapper {
     inner: Arc<dyn RoundInfoSource>,
@@ -60,12 +98,28 @@ impl RoundInfoSource for LoggingRoundInfoWrapper {
         partition_info: &PartitionInfo,
         files: Vec<ParquetFile>,
     ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        let n_files = files.len();
+        let start = Instant::now();
         let res = self
             .inner
             .calculate(components, last_round_info, partition_info, files)
             .await;
-        if let Ok((round_info, branches, files_later)) = &res {
-            debug!(round_info_source=%self.inner, %round_info, branches=branches.len(), files_later=files_later.len(), "running round");
+        let duration_secs = start.elapsed().as_secs_f64();
+
+        match &res {
+            Ok((round_info, branches, files_later)) => {
+                debug!(round_info_source=%self.inner, %round_info, branches=branches.len(), level_stats=%round_info.level_stats(), estimated_branch_output_bytes=?round_info.estimated_branch_output_bytes(), split_time_provenance=?round_info.split_time_provenance(), files_later=files_later.len(), duration_secs, "running round");
+            }
+            Err(e) => {
+                error!(
+                    round_info_source=%self.inner,
+                    partition_id = partition_info.partition_id.get(),
+                    n_files,
+                    duration_secs,
+                    %e,
+                    "error calculating round info",
+                );
+            }
         }
         res
     }
@@ -76,6 +130,104 @@ impl RoundInfoSource for LoggingRoundInfoWrapper {
 pub struct LevelBasedRoundInfo {
     pub max_num_files_per_plan: usize,
     pub max_total_file_size_per_plan: usize,
+
+    /// Multiple of `max_total_file_size_per_plan` the L1 bytes must exceed before an early L1->L2
+    /// compaction is considered while L0s are backlogged.  A very high value effectively disables
+    /// the early compaction path.
+    pub early_l1_to_l2_size_multiplier: f64,
+
+    /// Whether an early L1->L2 compaction may be chosen while L0 files still exist.
+    pub allow_early_l1_to_l2: bool,
+
+    /// When set, small partitions that have not received writes recently are compacted straight to
+    /// L2 in a single round.  See [`RoundInfo::ColdCompaction`].
+    cold_compaction: Option<ColdCompaction>,
+
+    /// Whether vertical splitting also aligns split times to the edges of L2 files (as well as L1
+    /// files), so the following L1->L2 compaction doesn't have to re-split everything.
+    pub l2_split_hints: bool,
+
+    /// Maximum number of split times a single VerticalSplit round may use.  On badly backlogged
+    /// partitions vertical splitting can otherwise produce enough splits to keep the compactor busy
+    /// with one partition for a very long time.  The remaining splits are left to later rounds.
+    pub max_split_times_per_round: Option<NonZeroUsize>,
+
+    /// Plan size limits used instead of `max_num_files_per_plan` and `max_total_file_size_per_plan`
+    /// for specific partitions, e.g. very wide partitions whose uncompressed footprint is far larger
+    /// than their parquet files suggest.
+    pub partition_plan_limits: HashMap<PartitionId, PlanLimits>,
+
+    /// Minimum number of (merged) start level chains for
+    /// [`many_ungroupable_files`](Self::many_ungroupable_files) to consider the files ungroupable.
+    pub min_ungroupable_chains: usize,
+
+    /// Ratio of (merged) start level chains to start level files that must be exceeded for
+    /// [`many_ungroupable_files`](Self::many_ungroupable_files) to consider the files ungroupable.
+    pub ungroupable_chain_ratio: f64,
+
+    /// Whether [`too_many_small_files_to_compact`](Self::too_many_small_files_to_compact) counts
+    /// the next level files overlapping each chain of start level files separately, rather than
+    /// those overlapping the time range of all start level files.
+    pub per_chain_overlaps: bool,
+
+    /// When set, files only containing data older than the namespace's retention period are
+    /// dropped rather than compacted.  See [`RoundInfo::Expired`].
+    retention_time_provider: Option<Arc<dyn TimeProvider>>,
+
+    /// When set, `max_total_file_size_per_plan` is reduced for partitions whose estimated
+    /// in-memory (Arrow) footprint would otherwise exceed this many bytes per plan.
+    pub max_estimated_arrow_bytes_per_plan: Option<usize>,
+
+    /// Estimated growth of in-memory data over its parquet size, per column of the table.  The
+    /// expansion factor of a partition is this times its column count, but at least 1.
+    pub arrow_expansion_per_column: f64,
+
+    /// Maximum number of branches returned per round.  The branches with the oldest data are
+    /// kept, the rest are left for later rounds.
+    pub max_branches_per_round: Option<NonZeroUsize>,
+
+    /// When set, ManySmallFiles rounds never group L0 files created in different windows of this
+    /// width.  See [`RoundInfo::ManySmallFiles`].
+    pub many_small_files_window: Option<Duration>,
+
+    /// When set, `max_total_file_size_per_plan` is scaled by the inverse of the output to input
+    /// bytes ratio observed in the previous round of the partition, within these (floor, ceiling)
+    /// bounds.  Data that compacts well can then be compacted in larger plans.
+    pub adaptive_file_size_per_plan: Option<(usize, usize)>,
+
+    /// When set, start level files averaging more rows than implied by this many rows and
+    /// `max_num_files_per_plan` are not considered small, however few bytes they are.  Highly
+    /// repetitive data compresses into tiny files that are huge once decoded.
+    pub max_total_rows_per_plan: Option<usize>,
+
+    /// When set, rounds that would compact to a target level instead compact each bucket of time of
+    /// this width separately, when the files span more than one bucket.  See
+    /// [`RoundInfo::TimeBucketed`].
+    pub time_bucket: Option<Duration>,
+}
+
+/// Chains found among the start level files by [`LevelBasedRoundInfo::many_ungroupable_files`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainStats {
+    /// Number of chains of overlapping files.
+    pub chains: usize,
+    /// Number of chains after merging small chains together.
+    pub merged_chains: usize,
+}
+
+/// Per-plan size limits, see [`LevelBasedRoundInfo::with_partition_plan_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanLimits {
+    pub max_num_files_per_plan: usize,
+    pub max_total_file_size_per_plan: usize,
+}
+
+/// Settings for detecting partitions eligible for [`RoundInfo::ColdCompaction`].
+#[derive(Debug, Clone)]
+struct ColdCompaction {
+    time_provider: Arc<dyn TimeProvider>,
+    /// Minimum time since the most recent L0 was created for a partition to be considered cold.
+    threshold: Duration,
 }
 
 impl Display for LevelBasedRoundInfo {
@@ -84,38 +236,314 @@ impl Display for LevelBasedRoundInfo {
     }
 }
 impl LevelBasedRoundInfo {
-    pub fn new(max_num_files_per_plan: usize, max_total_file_size_per_plan: usize) -> Self {
+    pub fn new(
+        max_num_files_per_plan: usize,
+        max_total_file_size_per_plan: usize,
+        early_l1_to_l2_size_multiplier: f64,
+        allow_early_l1_to_l2: bool,
+    ) -> Self {
         Self {
             max_num_files_per_plan,
             max_total_file_size_per_plan,
+            early_l1_to_l2_size_multiplier,
+            allow_early_l1_to_l2,
+            cold_compaction: None,
+            l2_split_hints: false,
+            max_split_times_per_round: None,
+            partition_plan_limits: HashMap::new(),
+            min_ungroupable_chains: 2,
+            ungroupable_chain_ratio: 1.0 / 3.0,
+            per_chain_overlaps: false,
+            retention_time_provider: None,
+            max_estimated_arrow_bytes_per_plan: None,
+            arrow_expansion_per_column: 0.0,
+            max_branches_per_round: None,
+            many_small_files_window: None,
+            adaptive_file_size_per_plan: None,
+            max_total_rows_per_plan: None,
+            time_bucket: None,
         }
     }
 
+    /// Scale `max_total_file_size_per_plan` with the observed output ratio of the partition's
+    /// previous round, keeping it within `floor..=ceiling`.
+    pub fn with_adaptive_file_size_per_plan(mut self, floor: usize, ceiling: usize) -> Self {
+        assert!(
+            floor <= ceiling,
+            "adaptive plan size floor must not exceed its ceiling"
+        );
+        self.adaptive_file_size_per_plan = Some((floor, ceiling));
+        self
+    }
+
+    /// Group L0 files of ManySmallFiles rounds by windows of `max_l0_created_at`.
+    pub fn with_many_small_files_window(mut self, window: Duration) -> Self {
+        assert!(
+            !window.is_zero(),
+            "many small files window must be non-zero"
+        );
+        self.many_small_files_window = Some(window);
+        self
+    }
+
+    /// Take the row count of start level files into account when deciding whether they are small.
+    pub fn with_max_total_rows_per_plan(mut self, max_total_rows_per_plan: usize) -> Self {
+        self.max_total_rows_per_plan = Some(max_total_rows_per_plan);
+        self
+    }
+
+    /// Compact to the target level in buckets of time of the given width.
+    pub fn with_time_bucket(mut self, time_bucket: Duration) -> Self {
+        assert!(!time_bucket.is_zero(), "time bucket must be non-zero");
+        self.time_bucket = Some(time_bucket);
+        self
+    }
+
+    /// Limit the number of branches compacted in each round.
+    pub fn with_max_branches_per_round(mut self, max_branches_per_round: NonZeroUsize) -> Self {
+        self.max_branches_per_round = Some(max_branches_per_round);
+        self
+    }
+
+    /// Limit the estimated in-memory size of each plan to `max_estimated_arrow_bytes_per_plan`,
+    /// estimating the in-memory size of a partition's data from its parquet size and column count.
+    pub fn with_memory_budget(
+        mut self,
+        max_estimated_arrow_bytes_per_plan: usize,
+        arrow_expansion_per_column: f64,
+    ) -> Self {
+        self.max_estimated_arrow_bytes_per_plan = Some(max_estimated_arrow_bytes_per_plan);
+        self.arrow_expansion_per_column = arrow_expansion_per_column;
+        self
+    }
+
+    /// Drop files whose data is entirely outside the namespace's retention period, rather than
+    /// compacting them.
+    pub fn with_retention(mut self, time_provider: Arc<dyn TimeProvider>) -> Self {
+        self.retention_time_provider = Some(time_provider);
+        self
+    }
+
+    /// Count next level overlaps per chain of start level files.
+    pub fn with_per_chain_overlaps(mut self, per_chain_overlaps: bool) -> Self {
+        self.per_chain_overlaps = per_chain_overlaps;
+        self
+    }
+
+    /// Set the thresholds used by [`many_ungroupable_files`](Self::many_ungroupable_files).
+    pub fn with_ungroupable_chains(
+        mut self,
+        min_ungroupable_chains: usize,
+        ungroupable_chain_ratio: f64,
+    ) -> Self {
+        self.min_ungroupable_chains = min_ungroupable_chains;
+        self.ungroupable_chain_ratio = ungroupable_chain_ratio;
+        self
+    }
+
+    /// Use `limits` instead of the default plan size limits when compacting `partition_id`.
+    pub fn with_partition_plan_limits(
+        mut self,
+        partition_id: PartitionId,
+        limits: PlanLimits,
+    ) -> Self {
+        self.partition_plan_limits.insert(partition_id, limits);
+        self
+    }
+
+    /// Returns a copy of `self` using the given plan size limits (and no per-partition limits).
+    fn with_limits(&self, limits: PlanLimits) -> Self {
+        Self {
+            max_num_files_per_plan: limits.max_num_files_per_plan,
+            max_total_file_size_per_plan: limits.max_total_file_size_per_plan,
+            early_l1_to_l2_size_multiplier: self.early_l1_to_l2_size_multiplier,
+            allow_early_l1_to_l2: self.allow_early_l1_to_l2,
+            cold_compaction: self.cold_compaction.clone(),
+            l2_split_hints: self.l2_split_hints,
+            max_split_times_per_round: self.max_split_times_per_round,
+            partition_plan_limits: HashMap::new(),
+            min_ungroupable_chains: self.min_ungroupable_chains,
+            ungroupable_chain_ratio: self.ungroupable_chain_ratio,
+            per_chain_overlaps: self.per_chain_overlaps,
+            retention_time_provider: self.retention_time_provider.clone(),
+            max_estimated_arrow_bytes_per_plan: self.max_estimated_arrow_bytes_per_plan,
+            arrow_expansion_per_column: self.arrow_expansion_per_column,
+            max_branches_per_round: self.max_branches_per_round,
+            many_small_files_window: self.many_small_files_window,
+            adaptive_file_size_per_plan: self.adaptive_file_size_per_plan,
+            max_total_rows_per_plan: self.max_total_rows_per_plan,
+            time_bucket: self.time_bucket,
+        }
+    }
+
+    /// The plan size limits for the given partition: any per-partition override, scaled by the
+    /// output ratio observed in the previous round (if adaptive), further reduced to fit the memory
+    /// budget.
+    pub fn plan_limits(
+        &self,
+        partition_info: &PartitionInfo,
+        observed_output_ratio: Option<f64>,
+    ) -> PlanLimits {
+        let mut limits = self
+            .partition_plan_limits
+            .get(&partition_info.partition_id)
+            .copied()
+            .unwrap_or(PlanLimits {
+                max_num_files_per_plan: self.max_num_files_per_plan,
+                max_total_file_size_per_plan: self.max_total_file_size_per_plan,
+            });
+
+        if let (Some((floor, ceiling)), Some(ratio)) =
+            (self.adaptive_file_size_per_plan, observed_output_ratio)
+        {
+            // e.g. when the output was a quarter of the input, 4x the input makes as much output.
+            let scaled = limits.max_total_file_size_per_plan as f64 / ratio;
+            limits.max_total_file_size_per_plan =
+                scaled.clamp(floor as f64, ceiling as f64) as usize;
+        }
+
+        if let Some(max_arrow_bytes) = self.max_estimated_arrow_bytes_per_plan {
+            let expansion =
+                (partition_info.column_count() as f64 * self.arrow_expansion_per_column).max(1.0);
+            let max_parquet_bytes = (max_arrow_bytes as f64 / expansion) as usize;
+            limits.max_total_file_size_per_plan =
+                limits.max_total_file_size_per_plan.min(max_parquet_bytes);
+        }
+
+        limits
+    }
+
+    /// Limit the number of split times used by each VerticalSplit round.
+    pub fn with_max_split_times_per_round(
+        mut self,
+        max_split_times_per_round: NonZeroUsize,
+    ) -> Self {
+        self.max_split_times_per_round = Some(max_split_times_per_round);
+        self
+    }
+
+    /// Also use the min/max times of L2 files as hints when choosing vertical split times.
+    pub fn with_l2_split_hints(mut self, l2_split_hints: bool) -> Self {
+        self.l2_split_hints = l2_split_hints;
+        self
+    }
+
+    /// Compact small partitions straight to L2 when none of their data was ingested within
+    /// `threshold` of now.
+    pub fn with_cold_compaction(
+        mut self,
+        time_provider: Arc<dyn TimeProvider>,
+        threshold: Duration,
+    ) -> Self {
+        self.cold_compaction = Some(ColdCompaction {
+            time_provider,
+            threshold,
+        });
+        self
+    }
+
+    /// Files with a max_time before the returned time only contain data outside the partition's
+    /// retention period.  Returns `None` when retention is ignored or the namespace keeps data
+    /// forever.
+    pub fn retention_cutoff(&self, partition_info: &PartitionInfo) -> Option<Timestamp> {
+        let time_provider = self.retention_time_provider.as_ref()?;
+        let retention_period_ns = partition_info.retention_period_ns?;
+
+        Some(Timestamp::new(
+            time_provider
+                .now()
+                .timestamp_nanos()
+                .saturating_sub(retention_period_ns),
+        ))
+    }
+
+    /// Returns true if all of `files` fit in a single plan and none of them contain data ingested
+    /// within the cold compaction threshold.  Such a partition is receiving (at most) a trickle of
+    /// writes, so rather than stepping through L0->L1->L2 we compact everything to L2 at once.
+    pub fn is_cold(&self, files: &[ParquetFile]) -> bool {
+        let Some(cold_compaction) = &self.cold_compaction else {
+            return false;
+        };
+
+        // Nothing to do if there's a single file, or everything is already in L2.
+        if files.len() < 2
+            || files
+                .iter()
+                .all(|f| f.compaction_level == CompactionLevel::Final)
+        {
+            return false;
+        }
+
+        let total_bytes: usize = files.iter().map(|f| f.file_size_bytes as usize).sum();
+        if files.len() > self.max_num_files_per_plan
+            || total_bytes > self.max_total_file_size_per_plan
+        {
+            return false;
+        }
+
+        let Some(cutoff) = cold_compaction
+            .time_provider
+            .now()
+            .checked_sub(cold_compaction.threshold)
+        else {
+            return false;
+        };
+        let cutoff = Timestamp::new(cutoff.timestamp_nanos());
+
+        files.iter().all(|f| f.max_l0_created_at < cutoff)
+    }
+
+    /// get_start_level decides what level to start compaction from, applying this instance's
+    /// early L1->L2 settings.  See `get_start_level` for the reasoning.
+    pub fn start_level(&self, files: &[ParquetFile]) -> CompactionLevel {
+        let early_l1_to_l2_size_multiplier = if self.allow_early_l1_to_l2 {
+            Some(self.early_l1_to_l2_size_multiplier)
+        } else {
+            None
+        };
+
+        get_start_level(
+            files,
+            self.max_num_files_per_plan,
+            self.max_total_file_size_per_plan,
+            early_l1_to_l2_size_multiplier,
+        )
+    }
+
     /// Returns true if the scenario looks like ManySmallFiles, but we can't group them well into branches.
     /// TODO: use this or remove it.  For now, keep it in case we need the temporary workaround again.
     /// This can be used to identify criteria to trigger a SimulatedLeadingEdge as a temporary workaround
     /// for a situation that isn't well handled, when the desire is to postpone optimal handling to a later PR.
+    ///
+    /// The scenario is ungroupable when the start level files form at least `min_ungroupable_chains`
+    /// chains, and more than `ungroupable_chain_ratio` chains per start level file.  The chain
+    /// statistics are returned (when they were computed) so the caller can log them.
     #[allow(dead_code)]
     pub fn many_ungroupable_files(
         &self,
         files: &[ParquetFile],
         start_level: CompactionLevel,
         max_total_file_size_to_group: usize,
-    ) -> bool {
+    ) -> (bool, Option<ChainStats>) {
         if self.too_many_small_files_to_compact(files, CompactionLevel::Initial) {
             let start_level_files = files
                 .iter()
                 .filter(|f| f.compaction_level == start_level)
                 .collect::<Vec<_>>();
             let start_count = start_level_files.len();
-            let mut chains = split_into_chains(start_level_files.into_iter().cloned().collect());
-            chains = merge_small_l0_chains(chains, max_total_file_size_to_group);
+            let chains = split_into_chains(start_level_files.into_iter().cloned().collect());
+            let num_chains = chains.len();
+            let merged_chains = merge_small_l0_chains(chains, max_total_file_size_to_group).len();
+            let stats = ChainStats {
+                chains: num_chains,
+                merged_chains,
+            };
 
-            if chains.len() > 1 && chains.len() > start_count / 3 {
-                return true;
-            }
+            let ungroupable = merged_chains >= self.min_ungroupable_chains
+                && merged_chains as f64 > start_count as f64 * self.ungroupable_chain_ratio;
+            return (ungroupable, Some(stats));
         }
-        false
+        (false, None)
     }
 
     /// Returns true if number of files of the given start_level and
@@ -138,6 +566,7 @@ impl LevelBasedRoundInfo {
             .iter()
             .map(|f| f.file_size_bytes as usize)
             .sum();
+        let rows_start_level: usize = start_level_files.iter().map(|f| f.row_count as usize).sum();
         let start_max_l0_created_at = start_level_files
             .iter()
             .map(|f| f.max_l0_created_at)
@@ -152,10 +581,21 @@ impl LevelBasedRoundInfo {
         // The compactor may compact all the target level and next level together in one
         // branch in the worst case, thus if that would result in too many files to compact in a single
         // plan, run a pre-phase to reduce the number of files first
-        let num_overlapped_files = get_num_overlapped_files(start_level_files, next_level_files);
-        if num_start_level > 1
-            && num_start_level + num_overlapped_files > self.max_num_files_per_plan
-        {
+        let num_files_to_compact = if self.per_chain_overlaps {
+            // Disjoint chains of start level files will be compacted separately, so only the largest
+            // chain and its overlaps matter.
+            split_into_chains(start_level_files.iter().map(|f| (*f).clone()).collect())
+                .iter()
+                .map(|chain| {
+                    chain.len()
+                        + get_num_overlapped_files(chain.iter().collect(), next_level_files.clone())
+                })
+                .max()
+                .unwrap_or_default()
+        } else {
+            num_start_level + get_num_overlapped_files(start_level_files, next_level_files)
+        };
+        if num_start_level > 1 && num_files_to_compact > self.max_num_files_per_plan {
             // This scaenario meets the simple criteria of start level files + their overlaps are lots of files.
             // But ManySmallFiles implies we must compact only within the start level to reduce the quantity of
             // start level files. There are several reasons why that might be unhelpful.
@@ -177,6 +617,16 @@ impl LevelBasedRoundInfo {
                 return false;
             }
 
+            // Reason 2b: Likewise for many files with lots of rows.  Highly repetitive data can make files that
+            // are small in bytes, but that use a lot of memory once decoded.
+            if let Some(max_total_rows_per_plan) = self.max_total_rows_per_plan {
+                if rows_start_level / num_start_level
+                    > max_total_rows_per_plan / self.max_num_files_per_plan
+                {
+                    return false;
+                }
+            }
+
             // Reason 3: Maybe there are so many start level files because we did a bunch of splits.
             // Note that we'll do splits to ensure each start level file overlaps at most one target level file.
             // If the prior round did that, and now we declare this ManySmallFiles, which forces compactions
@@ -221,10 +671,25 @@ impl LevelBasedRoundInfo {
         files: Vec<ParquetFile>,
         max_compact_size: usize,
     ) -> (Vec<i64>, Vec<FileRange>) {
+        let (split_times, ranges) = self.vertical_split_times(files, max_compact_size);
+        (
+            split_times.into_iter().map(|split| split.time).collect(),
+            ranges,
+        )
+    }
+
+    /// vertical_split_times is vertical_split_handling, also telling where each split time came from.
+    pub fn vertical_split_times(
+        &self,
+        files: Vec<ParquetFile>,
+        max_compact_size: usize,
+    ) -> (Vec<SplitTime>, Vec<FileRange>) {
+        let (l2_files, files): (Vec<ParquetFile>, Vec<ParquetFile>) = files
+            .into_iter()
+            .partition(|f| f.compaction_level == CompactionLevel::Final);
         let (start_level_files, mut target_level_files): (Vec<ParquetFile>, Vec<ParquetFile>) =
             files
                 .into_iter()
-                .filter(|f| f.compaction_level != CompactionLevel::Final)
                 .partition(|f| f.compaction_level == CompactionLevel::Initial);
 
         let len = start_level_files.len();
@@ -251,7 +716,10 @@ impl LevelBasedRoundInfo {
                 for range in linear_ranges {
                     // split at every time range of linear distribution.
                     if !split_times.is_empty() {
-                        split_times.push(range.min - 1);
+                        split_times.push(SplitTime::new(
+                            range.min - 1,
+                            SplitTimeSource::RangeBoundary,
+                        ));
                     }
 
                     // how many start level files are in this range?
@@ -284,7 +752,36 @@ impl LevelBasedRoundInfo {
                             }
                         }
 
-                        let splits = select_split_times(
+                        if self.l2_split_hints {
+                            // Aligning to L2 edges saves re-splitting during the later L1->L2 compaction.  But the L1s are
+                            // compacted with these L0s first, so when an L2 edge falls within an L1 file, the L1 hints win
+                            // and that L2 edge is ignored.
+                            let within_l1 = |t: i64| {
+                                target_level_files
+                                    .iter()
+                                    .any(|f| f.min_time.get() <= t && t <= f.max_time.get())
+                            };
+                            for f in &l2_files {
+                                let min_hint = f.min_time.get() - 1;
+                                if min_hint > range.min
+                                    && f.min_time.get() < range.max
+                                    && !within_l1(min_hint)
+                                {
+                                    split_hints.push(min_hint);
+                                }
+                                let max_hint = f.max_time.get();
+                                if max_hint > range.min
+                                    && max_hint < range.max
+                                    && !within_l1(max_hint)
+                                {
+                                    split_hints.push(max_hint);
+                                }
+                            }
+                            split_hints.sort();
+                            split_hints.dedup();
+                        }
+
+                        let splits = select_split_times_with_sources(
                             range.cap,
                             max_compact_size,
                             range.min,
@@ -313,7 +810,10 @@ impl LevelBasedRoundInfo {
                     // Target level files overlap more than one start level file, and there is a target level file overlapping
                     // the prior chain of L0s and this one.  We'll split the target level file at the pror range/chain max before
                     // proceeding with compactions.
-                    split_times.push(prior_chain_max)
+                    split_times.push(SplitTime::new(
+                        prior_chain_max,
+                        SplitTimeSource::RangeBoundary,
+                    ))
                 }
 
                 // As we identify overlaps, we'll include some don't quite overlap, but are between the prior chain and this one.
@@ -352,23 +852,24 @@ impl LevelBasedRoundInfo {
             }
         }
 
+        // When several sources agree on a split time, the range boundary wins over the hint, which
+        // wins over the evenly spaced time.
         split_times.sort();
-        split_times.dedup();
+        split_times.dedup_by_key(|split| split.time);
+        if let Some(max_split_times) = self.max_split_times_per_round {
+            // Take the earliest splits; whatever remains overlapped will be split in later rounds.
+            split_times.truncate(max_split_times.get());
+        }
         (split_times, ranges)
     }
-}
 
-#[async_trait]
-impl RoundInfoSource for LevelBasedRoundInfo {
-    // The calculated RoundInfo is the most impactful decision for this round of compactions.
-    // Later decisions should be just working out details to implement what RoundInfo dictates.
-    async fn calculate(
+    /// Decide what type of round this is, based on the files in the partition and what the last
+    /// round did.
+    pub fn round_info(
         &self,
-        components: Arc<Components>,
         last_round_info: Option<RoundInfo>,
-        _partition_info: &PartitionInfo,
-        files: Vec<ParquetFile>,
-    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        files: &[ParquetFile],
+    ) -> RoundInfo {
         let mut ranges: Vec<FileRange> = vec![];
 
         if let Some(last_round_info) = last_round_info {
@@ -377,7 +878,7 @@ impl RoundInfoSource for LevelBasedRoundInfo {
                 // we need to continue with those ranges.
                 for range in last_ranges {
                     // If this range still has overapping L0 files, we need to keep it.
-                    for f in &files {
+                    for f in files {
                         if f.compaction_level == CompactionLevel::Initial
                             && f.overlaps_ranges(&vec![range])
                         {
@@ -392,57 +893,199 @@ impl RoundInfoSource for LevelBasedRoundInfo {
         // start_level is usually the lowest level we have files in, but occasionally we decide to
         // compact L1->L2 when L0s still exist.  If this comes back as L1, we'll ignore L0s for this
         // round and force an early L1-L2 compaction.
-        let start_level = get_start_level(
-            &files,
-            self.max_num_files_per_plan,
-            self.max_total_file_size_per_plan,
-        );
+        let start_level = self.start_level(files);
+
+        // Computed once here, so the components acting on the round don't walk the files again.
+        let level_stats = LevelStats::new(files);
 
-        let round_info = if !ranges.is_empty() {
+        if !ranges.is_empty() {
             RoundInfo::CompactRanges {
                 ranges,
                 max_num_files_to_group: self.max_num_files_per_plan,
                 max_total_file_size_to_group: self.max_total_file_size_per_plan,
+                level_stats,
+            }
+        } else if self.is_cold(files) {
+            RoundInfo::ColdCompaction {
+                max_num_files_to_group: self.max_num_files_per_plan,
+                max_total_file_size_to_group: self.max_total_file_size_per_plan,
+                level_stats,
             }
         } else if start_level == CompactionLevel::Initial {
-            let (split_times, ranges) = self
-                .vertical_split_handling(files.clone().to_vec(), self.max_total_file_size_per_plan);
+            let (split_times, ranges) =
+                self.vertical_split_times(files.to_vec(), self.max_total_file_size_per_plan);
 
             if !split_times.is_empty() {
-                RoundInfo::VerticalSplit { split_times }
+                RoundInfo::VerticalSplit {
+                    split_time_provenance: SplitTimeProvenance::new(&split_times),
+                    split_times: split_times.into_iter().map(|split| split.time).collect(),
+                    estimated_branch_output_bytes: vec![],
+                    level_stats,
+                }
             } else if !ranges.is_empty() {
                 RoundInfo::CompactRanges {
                     ranges,
                     max_num_files_to_group: self.max_num_files_per_plan,
                     max_total_file_size_to_group: self.max_total_file_size_per_plan,
+                    level_stats,
                 }
-            } else if self.too_many_small_files_to_compact(&files, start_level) {
+            } else if self.too_many_small_files_to_compact(files, start_level) {
                 RoundInfo::ManySmallFiles {
                     start_level,
                     max_num_files_to_group: self.max_num_files_per_plan,
                     max_total_file_size_to_group: self.max_total_file_size_per_plan,
+                    max_l0_created_at_window_ns: self
+                        .many_small_files_window
+                        .map(|window| window.as_nanos() as i64),
+                    level_stats,
                 }
             } else {
-                RoundInfo::TargetLevel {
-                    target_level: CompactionLevel::FileNonOverlapped,
-                    max_total_file_size_to_group: self.max_total_file_size_per_plan,
-                }
+                self.target_level_round(files, CompactionLevel::FileNonOverlapped, level_stats)
             }
         } else {
-            let target_level = start_level.next();
-            RoundInfo::TargetLevel {
-                target_level,
-                max_total_file_size_to_group: self.max_total_file_size_per_plan,
+            self.target_level_round(files, start_level.next(), level_stats)
+        }
+    }
+
+    /// A round compacting to `target_level`: either all at once, or by time bucket when time
+    /// buckets are enabled and the files to compact span more than one bucket.
+    fn target_level_round(
+        &self,
+        files: &[ParquetFile],
+        target_level: CompactionLevel,
+        level_stats: LevelStats,
+    ) -> RoundInfo {
+        if let Some(time_bucket) = self.time_bucket {
+            let bucket_nanos = time_bucket.as_nanos() as i64;
+            let buckets = files
+                .iter()
+                .filter(|f| {
+                    f.compaction_level == target_level.prev() || f.compaction_level == target_level
+                })
+                .map(|f| f.min_time.get().div_euclid(bucket_nanos))
+                .unique()
+                .count();
+            if buckets > 1 {
+                return RoundInfo::TimeBucketed {
+                    bucket_nanos,
+                    target_level,
+                    level_stats,
+                };
+            }
+        }
+
+        RoundInfo::TargetLevel {
+            target_level,
+            max_total_file_size_to_group: self.max_total_file_size_per_plan,
+            estimated_branch_output_bytes: vec![],
+            level_stats,
+        }
+    }
+}
+
+/// Move the `expired` files overlapping `live` files, directly or through other expired files, over
+/// to the live files.
+///
+/// Dropped files stay in the catalog until the garbage collector deletes them, so compacting live
+/// data they overlap would create files overlapping them.  Returns the expired files that are safe
+/// to drop, and the live files.
+fn keep_expired_overlapping_live(
+    mut expired: Vec<ParquetFile>,
+    mut live: Vec<ParquetFile>,
+) -> (Vec<ParquetFile>, Vec<ParquetFile>) {
+    loop {
+        let (overlapping, isolated): (Vec<ParquetFile>, Vec<ParquetFile>) = expired
+            .into_iter()
+            .partition(|e| live.iter().any(|f| f.overlaps(e)));
+        expired = isolated;
+        if overlapping.is_empty() {
+            return (expired, live);
+        }
+        live.extend(overlapping);
+    }
+}
+
+#[async_trait]
+impl RoundInfoSource for LevelBasedRoundInfo {
+    // The calculated RoundInfo is the most impactful decision for this round of compactions.
+    // Later decisions should be just working out details to implement what RoundInfo dictates.
+    async fn calculate(
+        &self,
+        components: Arc<Components>,
+        last_round_info: Option<RoundInfo>,
+        partition_info: &PartitionInfo,
+        files: Vec<ParquetFile>,
+    ) -> Result<(RoundInfo, Vec<Vec<ParquetFile>>, Vec<ParquetFile>), DynError> {
+        let files = match self.retention_cutoff(partition_info) {
+            Some(retention_cutoff) => {
+                // Files straddling the cutoff still contain live data, so they're compacted as usual.
+                let (expired, files): (Vec<ParquetFile>, Vec<ParquetFile>) = files
+                    .into_iter()
+                    .partition(|f| f.max_time < retention_cutoff);
+                let (expired, files) = keep_expired_overlapping_live(expired, files);
+
+                if !expired.is_empty() {
+                    // Leave the expired files for the garbage collector, and everything else for
+                    // the next round.
+                    let round_info = RoundInfo::Expired {
+                        retention_cutoff: retention_cutoff.get(),
+                        expired_files: expired.iter().map(|f| f.id).collect(),
+                        level_stats: LevelStats::new(expired.iter().chain(&files)),
+                    };
+                    return Ok((round_info, vec![], files));
+                }
+                files
+            }
+            None => files,
+        };
+
+        // The first round of a partition has nothing to adapt to; anything recorded before then
+        // is stale.
+        let observed_output_ratio = components
+            .output_ratio_tracker
+            .take_ratio(partition_info.partition_id)
+            .filter(|_| last_round_info.is_some());
+        let limits = self.plan_limits(partition_info, observed_output_ratio);
+        let round_info = if partition_info.files_truncated && self.cold_compaction.is_some() {
+            // The oldest files of a partition fitting in a single plan doesn't mean that all of
+            // them do, so a truncated partition is never cold.
+            Self {
+                cold_compaction: None,
+                ..self.with_limits(limits)
             }
+            .round_info(last_round_info, &files)
+        } else if limits.max_num_files_per_plan == self.max_num_files_per_plan
+            && limits.max_total_file_size_per_plan == self.max_total_file_size_per_plan
+        {
+            self.round_info(last_round_info, &files)
+        } else {
+            self.with_limits(limits).round_info(last_round_info, &files)
         };
 
         let (files_now, mut files_later) = components.round_split.split(files, round_info.clone());
 
-        let (branches, more_for_later) = components
+        let num_files_now = files_now.len();
+        let (mut branches, more_for_later) = components
             .divide_initial
             .divide(files_now, round_info.clone());
+        if num_files_now > 0 && branches.is_empty() && more_for_later.is_empty() {
+            // The files of this round were given up on (see the logged error), so skip the rest of
+            // the partition too, rather than repeating the same round on the files left for later.
+            files_later.clear();
+        }
         files_later.extend(more_for_later);
 
+        if let Some(max_branches) = self.max_branches_per_round {
+            if branches.len() > max_branches.get() {
+                // Compact the data that has been waiting the longest first, to keep latency bounded.
+                branches.sort_by_key(|branch| branch.iter().map(|f| f.max_l0_created_at).min());
+                files_later.extend(branches.drain(max_branches.get()..).flatten());
+            }
+        }
+
+        // Only now do we know what will be compacted this round.
+        let round_info = round_info.with_estimated_branch_output_bytes(&branches);
+
         Ok((round_info, branches, files_later))
     }
 }
@@ -461,7 +1104,15 @@ impl RoundInfoSource for LevelBasedRoundInfo {
 // with the L2s.  The relative ease of moving data from L1 to L2 provides additional motivation to compact the
 // L1s to L2s when a backlog of L0s exist. The easily solvable L1->L2 compaction can give us a clean slate in
 // L1, greatly simplifying the remaining L0->L1 compactions.
-fn get_start_level(files: &[ParquetFile], max_files: usize, max_bytes: usize) -> CompactionLevel {
+//
+// `early_l1_to_l2_size_multiplier` is how many times `max_bytes` the L1s must exceed before the early L1->L2
+// compaction is considered.  `None` disables the early compaction entirely.
+fn get_start_level(
+    files: &[ParquetFile],
+    max_files: usize,
+    max_bytes: usize,
+    early_l1_to_l2_size_multiplier: Option<f64>,
+) -> CompactionLevel {
     // panic if the files are empty
     assert!(!files.is_empty());
 
@@ -482,7 +1133,10 @@ fn get_start_level(files: &[ParquetFile], max_files: usize, max_bytes: usize) ->
         }
     }
 
-    if l1_bytes > 3 * max_bytes && (l0_cnt > max_files || l0_bytes > max_bytes) {
+    let l1_is_big = early_l1_to_l2_size_multiplier
+        .is_some_and(|multiplier| l1_bytes as f64 > multiplier * max_bytes as f64);
+
+    if l1_is_big && (l0_cnt > max_files || l0_bytes > max_bytes) {
         // L1 is big enough to pose an overlap challenge compacting from L0, and there is quite a bit more coming from L0.
         // The criteria for this early L1->L2 compaction significanly impacts write amplification.  The above values optimize
         // existing test cases, but may be changed as additional test cases are added.
@@ -529,10 +1183,28 @@ fn get_num_overlapped_files(
 
 #[cfg(test)]
 mod tests {
-    use data_types::CompactionLevel;
+    use std::{sync::Arc, time::Duration};
+
+    use data_types::{CompactionLevel, ParquetFile, ParquetFileId, PartitionId, Timestamp};
     use iox_tests::ParquetFileBuilder;
+    use iox_time::{MockProvider, Time};
+    use itertools::Itertools;
+
+    use test_helpers::tracing::TracingCapture;
 
-    use crate::components::round_info_source::LevelBasedRoundInfo;
+    use crate::{
+        components::{
+            divide_initial::{multiple_branches::MultipleBranchesDivideInitial, DivideInitial},
+            round_info_source::{
+                mock::MockRoundInfoSource, ChainStats, LevelBasedRoundInfo,
+                LoggingRoundInfoWrapper, PlanLimits, RoundInfoSource,
+            },
+            round_split::{many_files::ManyFilesRoundSplit, RoundSplit},
+        },
+        round_info::{LevelStat, LevelStats},
+        test_utils::{test_components, PartitionInfoBuilder},
+        RoundInfo,
+    };
 
     #[test]
     fn test_too_many_small_files_to_compact() {
@@ -559,10 +1231,7 @@ mod tests {
             .build();
 
         // max 2 files per plan
-        let round_info = LevelBasedRoundInfo {
-            max_num_files_per_plan: 2,
-            max_total_file_size_per_plan: 1000,
-        };
+        let round_info = LevelBasedRoundInfo::new(2, 1000, 3.0, true);
 
         // f1 and f2 are not over limit
         assert!(!round_info
@@ -582,4 +1251,1121 @@ mod tests {
             round_info.too_many_small_files_to_compact(&[f1, f2, f3, f4], CompactionLevel::Initial)
         );
     }
+
+    #[test]
+    fn test_too_many_small_files_to_compact_row_count() {
+        // 3 tiny L0s, each with many rows, overlapping an L1
+        let l0 = |id: i64, row_count: i64| {
+            ParquetFileBuilder::new(id)
+                .with_time_range(0, 100)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(10)
+                .with_row_count(row_count)
+                .with_max_l0_created_at(id)
+                .build()
+        };
+        let l1 = ParquetFileBuilder::new(10)
+            .with_time_range(50, 150)
+            .with_compaction_level(CompactionLevel::FileNonOverlapped)
+            .with_file_size_bytes(10)
+            .build();
+        let heavy = vec![
+            l0(1, 1_000_000),
+            l0(2, 1_000_000),
+            l0(3, 1_000_000),
+            l1.clone(),
+        ];
+        let light = vec![l0(1, 1_000), l0(2, 1_000), l0(3, 1_000), l1];
+
+        // max 2 files per plan
+        let round_info = LevelBasedRoundInfo::new(2, 1000, 3.0, true);
+
+        // By default only the bytes matter, so both are many small files.
+        assert!(round_info.too_many_small_files_to_compact(&heavy, CompactionLevel::Initial));
+        assert!(round_info.too_many_small_files_to_compact(&light, CompactionLevel::Initial));
+
+        // 1M rows per plan allows 500K rows per file.
+        let round_info = round_info.with_max_total_rows_per_plan(1_000_000);
+        assert!(!round_info.too_many_small_files_to_compact(&heavy, CompactionLevel::Initial));
+        assert!(round_info.too_many_small_files_to_compact(&light, CompactionLevel::Initial));
+    }
+
+    /// A backlog of L0s on top of a large set of L1s: 5 overlapping 300 byte L0s and 10 non
+    /// overlapping 250 byte L1s (2500 L1 bytes in total).
+    fn backlogged_l0s_over_l1s() -> Vec<ParquetFile> {
+        let l0s = (0..5).map(|i| {
+            ParquetFileBuilder::new(i)
+                .with_time_range(0, 1000)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(300)
+                .with_max_l0_created_at(i)
+                .build()
+        });
+        let l1s = (0..10).map(|i| {
+            ParquetFileBuilder::new(100 + i)
+                .with_time_range(i * 100, i * 100 + 99)
+                .with_compaction_level(CompactionLevel::FileNonOverlapped)
+                .with_file_size_bytes(250)
+                .build()
+        });
+        l0s.chain(l1s).collect()
+    }
+
+    #[test]
+    fn test_start_level_early_l1_to_l2_multiplier() {
+        let files = backlogged_l0s_over_l1s();
+
+        // 2500 L1 bytes is not over 3 * 1000, so the L0s are compacted first.
+        let round_info = LevelBasedRoundInfo::new(2, 1000, 3.0, true);
+        assert_eq!(round_info.start_level(&files), CompactionLevel::Initial);
+
+        // Lowering the threshold to 2 * 1000 triggers the early L1->L2 compaction.
+        let round_info = LevelBasedRoundInfo::new(2, 1000, 2.0, true);
+        assert_eq!(
+            round_info.start_level(&files),
+            CompactionLevel::FileNonOverlapped
+        );
+
+        // A very high multiplier effectively disables the early compaction.
+        let round_info = LevelBasedRoundInfo::new(2, 1000, f64::MAX, true);
+        assert_eq!(round_info.start_level(&files), CompactionLevel::Initial);
+
+        // As does disallowing it.
+        let round_info = LevelBasedRoundInfo::new(2, 1000, 2.0, false);
+        assert_eq!(round_info.start_level(&files), CompactionLevel::Initial);
+    }
+
+    #[test]
+    fn test_start_level_without_l0s() {
+        // Without L0s the early compaction settings are irrelevant.
+        let files = backlogged_l0s_over_l1s()
+            .into_iter()
+            .filter(|f| f.compaction_level != CompactionLevel::Initial)
+            .collect::<Vec<_>>();
+
+        for allow in [true, false] {
+            let round_info = LevelBasedRoundInfo::new(2, 1000, 2.0, allow);
+            assert_eq!(
+                round_info.start_level(&files),
+                CompactionLevel::FileNonOverlapped
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disallow_early_l1_to_l2_round() {
+        let files = backlogged_l0s_over_l1s();
+        let partition_info = PartitionInfoBuilder::new().build();
+        let levels = |branches: &[Vec<ParquetFile>]| {
+            branches
+                .iter()
+                .flatten()
+                .map(|f| f.compaction_level)
+                .unique()
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+
+        // The L1s are big enough to be compacted to L2 before the pending L0s...
+        let round_info = LevelBasedRoundInfo::new(2, 1000, 2.0, true);
+        let (got, branches, _) = round_info
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+        assert_eq!(got.target_level(), CompactionLevel::Final);
+        assert_eq!(levels(&branches), vec![CompactionLevel::FileNonOverlapped]);
+
+        // ...unless that's disallowed, in which case the L0s are compacted first.
+        let round_info = LevelBasedRoundInfo::new(2, 1000, 2.0, false);
+        let (got, branches, _) = round_info
+            .calculate(test_components(), None, &partition_info, files)
+            .await
+            .unwrap();
+        assert_ne!(got.target_level(), CompactionLevel::Final);
+        assert!(levels(&branches).contains(&CompactionLevel::Initial));
+    }
+
+    const ONE_HOUR_NANOS: i64 = 60 * 60 * 1_000_000_000;
+
+    /// A small partition: 3 overlapping L0s on top of an L1, with the newest L0 created at
+    /// `newest_l0_created_at`.
+    fn small_partition(newest_l0_created_at: i64) -> Vec<ParquetFile> {
+        let mut files = (1..=3)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_time_range(0, 100)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_file_size_bytes(10)
+                    .with_max_l0_created_at(newest_l0_created_at - 3 + i)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        files.push(
+            ParquetFileBuilder::new(4)
+                .with_time_range(0, 100)
+                .with_compaction_level(CompactionLevel::FileNonOverlapped)
+                .with_file_size_bytes(10)
+                .with_max_l0_created_at(1)
+                .build(),
+        );
+        files
+    }
+
+    fn cold_round_info(now: i64) -> LevelBasedRoundInfo {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(now)));
+        LevelBasedRoundInfo::new(10, 1000, 3.0, true)
+            .with_cold_compaction(time_provider, Duration::from_secs(60 * 60))
+    }
+
+    #[test]
+    fn test_cold_compaction_single_round() {
+        let now = 10 * ONE_HOUR_NANOS;
+        let files = small_partition(now - 2 * ONE_HOUR_NANOS);
+
+        // Without cold compaction this is an L0->L1 round, and another round is needed to reach L2.
+        let round_info = LevelBasedRoundInfo::new(10, 1000, 3.0, true).round_info(None, &files);
+        assert_eq!(
+            round_info,
+            RoundInfo::TargetLevel {
+                target_level: CompactionLevel::FileNonOverlapped,
+                max_total_file_size_to_group: 1000,
+                estimated_branch_output_bytes: vec![],
+                level_stats: LevelStats::new(&files),
+            }
+        );
+
+        // A cold partition goes straight to L2 with every file in a single branch.
+        let round_info = cold_round_info(now).round_info(None, &files);
+        assert_eq!(
+            round_info,
+            RoundInfo::ColdCompaction {
+                max_num_files_to_group: 10,
+                max_total_file_size_to_group: 1000,
+                level_stats: LevelStats::new(&files),
+            }
+        );
+        assert_eq!(round_info.target_level(), CompactionLevel::Final);
+
+        let (files_now, files_later) =
+            ManyFilesRoundSplit::new().split(files.clone(), round_info.clone());
+        assert!(files_later.is_empty());
+        let (branches, files_later) =
+            MultipleBranchesDivideInitial::new().divide(files_now, round_info);
+        assert!(files_later.is_empty());
+        assert_eq!(branches, vec![files]);
+    }
+
+    #[test]
+    fn test_cold_compaction_not_cold() {
+        let now = 10 * ONE_HOUR_NANOS;
+        let round_info = cold_round_info(now);
+
+        // recently written
+        let files = small_partition(now - ONE_HOUR_NANOS / 2);
+        assert!(!round_info.is_cold(&files));
+
+        // too many files for one plan
+        let files = (1..=11)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_file_size_bytes(10)
+                    .with_max_l0_created_at(i)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        assert!(!round_info.is_cold(&files));
+
+        // too many bytes for one plan
+        let files = small_partition(0)
+            .into_iter()
+            .map(|mut f| {
+                f.file_size_bytes = 500;
+                f
+            })
+            .collect::<Vec<_>>();
+        assert!(!round_info.is_cold(&files));
+
+        // already all L2
+        let files = small_partition(0)
+            .into_iter()
+            .map(|mut f| {
+                f.compaction_level = CompactionLevel::Final;
+                f
+            })
+            .collect::<Vec<_>>();
+        assert!(!round_info.is_cold(&files));
+
+        // but old, small and not yet L2 is cold
+        assert!(round_info.is_cold(&small_partition(0)));
+    }
+
+    #[tokio::test]
+    async fn test_cold_compaction_truncated_files() {
+        let now = 10 * ONE_HOUR_NANOS;
+        let files = small_partition(0);
+
+        let partition_info = PartitionInfoBuilder::new().build();
+        let (round_info, _branches, _files_later) = cold_round_info(now)
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+        assert!(matches!(round_info, RoundInfo::ColdCompaction { .. }));
+
+        let partition_info = PartitionInfoBuilder::new().with_files_truncated().build();
+        let (round_info, _branches, _files_later) = cold_round_info(now)
+            .calculate(test_components(), None, &partition_info, files)
+            .await
+            .unwrap();
+        assert!(matches!(round_info, RoundInfo::TargetLevel { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_logging_wrapper_logs_errors() {
+        let source = LoggingRoundInfoWrapper::new(Arc::new(
+            MockRoundInfoSource::new(vec![]).with_error("something broke"),
+        ));
+        let partition_info = PartitionInfoBuilder::new().with_partition_id(42).build();
+        let files = small_partition(0);
+
+        let capture = TracingCapture::new();
+
+        let err = source
+            .calculate(test_components(), None, &partition_info, files)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "something broke");
+
+        let logs = capture.to_string();
+        assert!(logs.starts_with(
+            "level = ERROR; message = error calculating round info; round_info_source = mock; partition_id = 42; n_files = 4; duration_secs = "
+        ), "{logs}");
+        assert!(logs.ends_with("e = something broke; "), "{logs}");
+    }
+
+    /// 3 highly overlapped L0s, too big to compact together, over three L2s.
+    fn overlapped_l0s_over_l2s() -> Vec<ParquetFile> {
+        let l0s = (1..=3).map(|i| {
+            ParquetFileBuilder::new(i)
+                .with_time_range(0, 1000)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(100)
+                .with_max_l0_created_at(i)
+                .build()
+        });
+        let l2s = [(0, 299), (300, 599), (600, 1000)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (min, max))| {
+                ParquetFileBuilder::new(10 + i as i64)
+                    .with_time_range(min, max)
+                    .with_compaction_level(CompactionLevel::Final)
+                    .with_file_size_bytes(100)
+                    .build()
+            });
+        l0s.chain(l2s).collect()
+    }
+
+    #[test]
+    fn test_vertical_split_l2_hints() {
+        let files = overlapped_l0s_over_l2s();
+
+        // By default L2s are ignored when choosing where to split.
+        let round_info = LevelBasedRoundInfo::new(10, 100, 3.0, true);
+        let (split_times, _) = round_info.vertical_split_handling(files.clone(), 100);
+        assert!(!split_times.is_empty());
+        assert!(!split_times.contains(&299));
+        assert!(!split_times.contains(&599));
+
+        // With L2 hints, the splits align to the L2 edges.
+        let round_info = round_info.with_l2_split_hints(true);
+        let (split_times, _) = round_info.vertical_split_handling(files, 100);
+        assert!(split_times.contains(&299), "{split_times:?}");
+        assert!(split_times.contains(&599), "{split_times:?}");
+    }
+
+    #[test]
+    fn test_vertical_split_l1_hints_preferred_over_l2() {
+        let mut files = overlapped_l0s_over_l2s();
+        // An L1 straddling the L2 edge at 599
+        files.push(
+            ParquetFileBuilder::new(20)
+                .with_time_range(550, 650)
+                .with_compaction_level(CompactionLevel::FileNonOverlapped)
+                .with_file_size_bytes(10)
+                .build(),
+        );
+
+        let round_info = LevelBasedRoundInfo::new(10, 100, 3.0, true).with_l2_split_hints(true);
+        let (split_times, _) = round_info.vertical_split_handling(files, 100);
+
+        // The L2 edge outside the L1 is still used, but the one inside it gives way to the L1 edge.
+        assert!(split_times.contains(&299), "{split_times:?}");
+        assert!(!split_times.contains(&599), "{split_times:?}");
+        assert!(split_times.contains(&549), "{split_times:?}");
+    }
+
+    #[test]
+    fn test_max_split_times_per_round() {
+        // 5 separate chains of 3 highly overlapped L0s, each of which needs several splits.
+        let files = (0..5)
+            .flat_map(|chain| {
+                (1..=3).map(move |i| {
+                    ParquetFileBuilder::new(chain * 10 + i)
+                        .with_time_range(chain * 10_000, chain * 10_000 + 1000)
+                        .with_compaction_level(CompactionLevel::Initial)
+                        .with_file_size_bytes(100)
+                        .with_max_l0_created_at(i)
+                        .build()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let round_info = LevelBasedRoundInfo::new(10, 100, 3.0, true);
+        let (all_split_times, _) = round_info.vertical_split_handling(files.clone(), 100);
+        assert!(all_split_times.len() > 5, "{all_split_times:?}");
+
+        let round_info = round_info.with_max_split_times_per_round(NonZeroUsize::new(5).unwrap());
+        let (split_times, _) = round_info.vertical_split_handling(files, 100);
+        assert_eq!(split_times.len(), 5);
+        // The earliest splits are kept, still sorted & deduplicated.
+        assert_eq!(split_times, all_split_times[..5]);
+        assert!(split_times.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_vertical_split_time_provenance() {
+        use crate::round_info::{SplitTime, SplitTimeProvenance, SplitTimeSource::*};
+
+        // 3 highly overlapped L0s, and one L0 spanning a much longer time range, so the data is
+        // split into 2 ranges of differently distributed data.  An L1 overlaps the dense range.
+        let mut files = (1..=3)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_time_range(0, 1000)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_file_size_bytes(100)
+                    .with_max_l0_created_at(i)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        files.push(
+            ParquetFileBuilder::new(4)
+                .with_time_range(0, 10_000)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(100)
+                .with_max_l0_created_at(4)
+                .build(),
+        );
+        files.push(
+            ParquetFileBuilder::new(5)
+                .with_time_range(300, 400)
+                .with_compaction_level(CompactionLevel::FileNonOverlapped)
+                .with_file_size_bytes(10)
+                .build(),
+        );
+
+        let round_info = LevelBasedRoundInfo::new(10, 100, 3.0, true);
+        let (split_times, _) = round_info.vertical_split_times(files.clone(), 100);
+        assert_eq!(
+            split_times,
+            vec![
+                // aligned to the start of the L1
+                SplitTime::new(299, Hint),
+                SplitTime::new(517, EvenlySpaced),
+                SplitTime::new(735, EvenlySpaced),
+                SplitTime::new(953, EvenlySpaced),
+                // between the dense range and the sparse one
+                SplitTime::new(1091, RangeBoundary),
+            ]
+        );
+
+        let got = round_info.round_info(None, &files);
+        assert_eq!(
+            got.split_times(),
+            Some([299, 517, 735, 953, 1091].as_slice())
+        );
+        assert_eq!(
+            got.split_time_provenance(),
+            Some(SplitTimeProvenance {
+                range_boundaries: 1,
+                hints: 1,
+                evenly_spaced: 3,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partition_plan_limits() {
+        // Two overlapping L0s that fit the default plan size, but not the overridden one.
+        let files = (1..=2)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_time_range(0, 100)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_file_size_bytes(60)
+                    .with_max_l0_created_at(i)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        let round_info = LevelBasedRoundInfo::new(10, 1000, 3.0, true).with_partition_plan_limits(
+            PartitionId::new(2),
+            PlanLimits {
+                max_num_files_per_plan: 10,
+                max_total_file_size_per_plan: 100,
+            },
+        );
+
+        let default_partition = PartitionInfoBuilder::new().with_partition_id(1).build();
+        let (got, _, _) = round_info
+            .calculate(test_components(), None, &default_partition, files.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            got,
+            RoundInfo::TargetLevel {
+                target_level: CompactionLevel::FileNonOverlapped,
+                max_total_file_size_to_group: 1000,
+                estimated_branch_output_bytes: vec![120],
+                level_stats: LevelStats::new(&files),
+            }
+        );
+
+        let overridden_partition = PartitionInfoBuilder::new().with_partition_id(2).build();
+        let (got, _, _) = round_info
+            .calculate(test_components(), None, &overridden_partition, files)
+            .await
+            .unwrap();
+        assert!(matches!(got, RoundInfo::VerticalSplit { .. }), "{got}");
+    }
+
+    #[test]
+    fn test_many_ungroupable_files() {
+        // 6 small, non overlapping L0s over 2 adjacent L1s, so all the files form a single chain.
+        let l0s = (0..6).map(|i| {
+            ParquetFileBuilder::new(i)
+                .with_time_range(i * 100, i * 100 + 50)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(10)
+                .with_max_l0_created_at(i)
+                .build()
+        });
+        let l1s = [(0, 249), (250, 599)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (min, max))| {
+                ParquetFileBuilder::new(10 + i as i64)
+                    .with_time_range(min, max)
+                    .with_compaction_level(CompactionLevel::FileNonOverlapped)
+                    .with_file_size_bytes(10)
+                    .build()
+            });
+        let files = l0s.chain(l1s).collect::<Vec<_>>();
+
+        let round_info = LevelBasedRoundInfo::new(4, 1000, 3.0, true);
+        assert!(round_info.too_many_small_files_to_compact(&files, CompactionLevel::Initial));
+
+        // 6 chains (too big to merge) for 6 L0s is over the default 1/3 ratio
+        let (ungroupable, stats) =
+            round_info.many_ungroupable_files(&files, CompactionLevel::Initial, 10);
+        assert!(ungroupable);
+        assert_eq!(
+            stats,
+            Some(ChainStats {
+                chains: 6,
+                merged_chains: 6
+            })
+        );
+
+        // ...but not over a raised ratio
+        let round_info = round_info.with_ungroupable_chains(2, 1.0);
+        let (ungroupable, stats) =
+            round_info.many_ungroupable_files(&files, CompactionLevel::Initial, 10);
+        assert!(!ungroupable);
+        assert_eq!(
+            stats,
+            Some(ChainStats {
+                chains: 6,
+                merged_chains: 6
+            })
+        );
+
+        // merging all the chains into one is never ungroupable
+        let round_info = LevelBasedRoundInfo::new(4, 1000, 3.0, true);
+        let (ungroupable, stats) =
+            round_info.many_ungroupable_files(&files, CompactionLevel::Initial, 1000);
+        assert!(!ungroupable);
+        assert_eq!(
+            stats,
+            Some(ChainStats {
+                chains: 6,
+                merged_chains: 1
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimated_branch_output_bytes() {
+        // Two overlapping L0s, and an L1 elsewhere that pushes the total over the plan size.
+        let files = vec![
+            ParquetFileBuilder::new(1)
+                .with_time_range(0, 100)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(300)
+                .with_max_l0_created_at(1)
+                .build(),
+            ParquetFileBuilder::new(2)
+                .with_time_range(50, 100)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(300)
+                .with_max_l0_created_at(2)
+                .build(),
+            ParquetFileBuilder::new(3)
+                .with_time_range(500, 600)
+                .with_compaction_level(CompactionLevel::FileNonOverlapped)
+                .with_file_size_bytes(500)
+                .build(),
+        ];
+
+        let round_info = LevelBasedRoundInfo::new(10, 1000, 3.0, true);
+        let partition_info = PartitionInfoBuilder::new().build();
+        let (got, branches, files_later) = round_info
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+
+        // The L1 doesn't overlap the L0s, so it waits, and isn't part of the estimate.
+        assert_eq!(branches.len(), 1);
+        assert_eq!(files_later.len(), 1);
+        assert_eq!(
+            got,
+            RoundInfo::TargetLevel {
+                target_level: CompactionLevel::FileNonOverlapped,
+                max_total_file_size_to_group: 1000,
+                estimated_branch_output_bytes: vec![600],
+                level_stats: LevelStats::new(&files),
+            }
+        );
+        assert_eq!(got.estimated_branch_output_bytes(), Some([600].as_slice()));
+    }
+
+    #[tokio::test]
+    async fn test_level_stats() {
+        let file = |id: i64, level, min_time, max_time, file_size_bytes| {
+            ParquetFileBuilder::new(id)
+                .with_compaction_level(level)
+                .with_time_range(min_time, max_time)
+                .with_file_size_bytes(file_size_bytes)
+                .with_max_l0_created_at(id)
+                .build()
+        };
+        let files = vec![
+            file(1, CompactionLevel::Initial, 100, 200, 10),
+            file(2, CompactionLevel::Initial, 150, 400, 20),
+            file(3, CompactionLevel::FileNonOverlapped, 50, 300, 30),
+            file(4, CompactionLevel::Final, 0, 49, 100),
+            file(5, CompactionLevel::Final, 500, 900, 200),
+        ];
+
+        let round_info = LevelBasedRoundInfo::new(10, 1000, 3.0, true);
+        let (got, _, _) = round_info
+            .calculate(
+                test_components(),
+                None,
+                &PartitionInfoBuilder::new().build(),
+                files,
+            )
+            .await
+            .unwrap();
+
+        let stats = got.level_stats();
+        assert_eq!(
+            *stats.level(CompactionLevel::Initial),
+            LevelStat {
+                num_files: 2,
+                total_bytes: 30,
+                min_time: Some(Timestamp::new(100)),
+                max_time: Some(Timestamp::new(400)),
+            }
+        );
+        assert_eq!(
+            *stats.level(CompactionLevel::FileNonOverlapped),
+            LevelStat {
+                num_files: 1,
+                total_bytes: 30,
+                min_time: Some(Timestamp::new(50)),
+                max_time: Some(Timestamp::new(300)),
+            }
+        );
+        assert_eq!(
+            *stats.level(CompactionLevel::Final),
+            LevelStat {
+                num_files: 2,
+                total_bytes: 300,
+                min_time: Some(Timestamp::new(0)),
+                max_time: Some(Timestamp::new(900)),
+            }
+        );
+        assert_eq!(stats.total_bytes_from_level(CompactionLevel::Initial), 360);
+        assert_eq!(
+            stats.total_bytes_from_level(CompactionLevel::FileNonOverlapped),
+            330
+        );
+        assert_eq!(
+            stats.to_string(),
+            "L0: 2 files, 30 bytes, [100, 400]; L1: 1 files, 30 bytes, [50, 300]; \
+             L2: 2 files, 300 bytes, [0, 900]"
+        );
+
+        // levels without files have no time range
+        assert_eq!(
+            LevelStats::default().to_string(),
+            "L0: 0 files, 0 bytes; L1: 0 files, 0 bytes; L2: 0 files, 0 bytes"
+        );
+    }
+
+    /// Two disjoint clusters of 3 L0s, the first over 2 L1s and the second over another L1.
+    fn disjoint_l0_clusters() -> Vec<ParquetFile> {
+        let l0s = (0..6).map(|i| {
+            let min = if i < 3 { 0 } else { 1000 };
+            ParquetFileBuilder::new(i)
+                .with_time_range(min, min + 100)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(10)
+                .with_max_l0_created_at(i)
+                .build()
+        });
+        let l1s = [(0, 40), (41, 100), (1000, 1100)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (min, max))| {
+                ParquetFileBuilder::new(10 + i as i64)
+                    .with_time_range(min, max)
+                    .with_compaction_level(CompactionLevel::FileNonOverlapped)
+                    .with_file_size_bytes(10)
+                    .build()
+            });
+        l0s.chain(l1s).collect()
+    }
+
+    #[test]
+    fn test_too_many_small_files_per_chain_overlaps() {
+        let files = disjoint_l0_clusters();
+
+        // Counting all 6 L0s and all 3 L1s they overlap is over the limit of 5 files...
+        let round_info = LevelBasedRoundInfo::new(5, 1000, 3.0, true);
+        assert!(round_info.too_many_small_files_to_compact(&files, CompactionLevel::Initial));
+        assert!(round_info.round_info(None, &files).is_many_small_files());
+
+        // ...but each cluster and its L1s fit in a plan.
+        let round_info = round_info.with_per_chain_overlaps(true);
+        assert!(!round_info.too_many_small_files_to_compact(&files, CompactionLevel::Initial));
+        assert_eq!(
+            round_info.round_info(None, &files),
+            RoundInfo::TargetLevel {
+                target_level: CompactionLevel::FileNonOverlapped,
+                max_total_file_size_to_group: 1000,
+                estimated_branch_output_bytes: vec![],
+                level_stats: LevelStats::new(&files),
+            }
+        );
+
+        // A cluster and its L1s can still be too many on their own.
+        let round_info = LevelBasedRoundInfo::new(4, 1000, 3.0, true).with_per_chain_overlaps(true);
+        assert!(round_info.too_many_small_files_to_compact(&files, CompactionLevel::Initial));
+    }
+
+    #[test]
+    fn test_many_small_files_window() {
+        let files = disjoint_l0_clusters();
+
+        let round_info = LevelBasedRoundInfo::new(5, 1000, 3.0, true);
+        assert_eq!(
+            round_info.round_info(None, &files),
+            RoundInfo::ManySmallFiles {
+                start_level: CompactionLevel::Initial,
+                max_num_files_to_group: 5,
+                max_total_file_size_to_group: 1000,
+                max_l0_created_at_window_ns: None,
+                level_stats: LevelStats::new(&files),
+            }
+        );
+
+        let round_info = round_info.with_many_small_files_window(Duration::from_nanos(3));
+        assert_eq!(
+            round_info.round_info(None, &files),
+            RoundInfo::ManySmallFiles {
+                start_level: CompactionLevel::Initial,
+                max_num_files_to_group: 5,
+                max_total_file_size_to_group: 1000,
+                max_l0_created_at_window_ns: Some(3),
+                level_stats: LevelStats::new(&files),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drop_expired_files() {
+        let now = 10 * ONE_HOUR_NANOS;
+        let l0 = |id: i64, min: i64, max: i64| {
+            ParquetFileBuilder::new(id)
+                .with_time_range(min, max)
+                .with_compaction_level(CompactionLevel::Initial)
+                .with_file_size_bytes(10)
+                .with_max_l0_created_at(id)
+                .build()
+        };
+        let files = vec![
+            // expired
+            l0(1, 0, 100),
+            l0(2, 50, 150),
+            // straddling the retention cutoff
+            l0(3, now - 2 * ONE_HOUR_NANOS, now - ONE_HOUR_NANOS / 2),
+            // live
+            l0(4, now - ONE_HOUR_NANOS / 3, now - ONE_HOUR_NANOS / 6),
+        ];
+        let partition_info = PartitionInfoBuilder::new()
+            .with_retention_period_ns(ONE_HOUR_NANOS)
+            .build();
+        let ids = |files: &[ParquetFile]| files.iter().map(|f| f.id.get()).collect::<Vec<_>>();
+
+        // Without retention awareness, everything is compacted together.
+        let round_info = LevelBasedRoundInfo::new(10, 1000, 3.0, true);
+        let (_, branches, _) = round_info
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+        assert_eq!(branches.len(), 1);
+        assert_eq!(branches[0].len(), 4);
+
+        // The expired files are flagged, and the rest left for the next round.
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(now)));
+        let round_info = round_info.with_retention(time_provider);
+        let (got, branches, files_later) = round_info
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            got,
+            RoundInfo::Expired {
+                retention_cutoff: now - ONE_HOUR_NANOS,
+                expired_files: vec![ParquetFileId::new(1), ParquetFileId::new(2)],
+                level_stats: LevelStats::new(&files),
+            }
+        );
+        assert!(branches.is_empty());
+        assert_eq!(ids(&files_later), vec![3, 4]);
+
+        // Which only compacts the live files.
+        let (got, branches, files_later) = round_info
+            .calculate(test_components(), Some(got), &partition_info, files_later)
+            .await
+            .unwrap();
+        assert!(!got.is_expired());
+        assert_eq!(branches.len(), 1);
+        assert_eq!(ids(&branches[0]), vec![3, 4]);
+        assert!(files_later.is_empty());
+
+        // Nothing is dropped when the namespace keeps data forever.
+        let partition_info = PartitionInfoBuilder::new().build();
+        assert_eq!(round_info.retention_cutoff(&partition_info), None);
+    }
+
+    #[tokio::test]
+    async fn test_expired_files_overlapping_live_files_are_kept() {
+        let now = 10 * ONE_HOUR_NANOS;
+        let file = |id: i64, min: i64, max: i64, level: CompactionLevel| {
+            ParquetFileBuilder::new(id)
+                .with_time_range(min, max)
+                .with_compaction_level(level)
+                .with_file_size_bytes(10)
+                .with_max_l0_created_at(id)
+                .build()
+        };
+        let files = vec![
+            // expired, overlapping nothing live
+            file(1, 0, 100, CompactionLevel::Initial),
+            // expired, overlapped by the straddling L0
+            file(
+                2,
+                now - 3 * ONE_HOUR_NANOS,
+                now - 3 * ONE_HOUR_NANOS / 2,
+                CompactionLevel::FileNonOverlapped,
+            ),
+            // expired, only overlapping the expired L1 above
+            file(
+                3,
+                now - 5 * ONE_HOUR_NANOS,
+                now - 3 * ONE_HOUR_NANOS,
+                CompactionLevel::Initial,
+            ),
+            // straddling the retention cutoff
+            file(
+                4,
+                now - 2 * ONE_HOUR_NANOS,
+                now - ONE_HOUR_NANOS / 2,
+                CompactionLevel::Initial,
+            ),
+        ];
+        let partition_info = PartitionInfoBuilder::new()
+            .with_retention_period_ns(ONE_HOUR_NANOS)
+            .build();
+        let ids = |files: &[ParquetFile]| {
+            let mut ids = files.iter().map(|f| f.id.get()).collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_nanos(now)));
+        let round_info =
+            LevelBasedRoundInfo::new(10, 1000, 3.0, true).with_retention(time_provider);
+        let (got, branches, files_later) = round_info
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+
+        // Only the isolated file is dropped...
+        assert_eq!(
+            got,
+            RoundInfo::Expired {
+                retention_cutoff: now - ONE_HOUR_NANOS,
+                expired_files: vec![ParquetFileId::new(1)],
+                level_stats: LevelStats::new(&files),
+            }
+        );
+        assert!(branches.is_empty());
+        assert_eq!(ids(&files_later), vec![2, 3, 4]);
+
+        // ...so compacting the straddling L0 also compacts the expired files it (transitively)
+        // overlaps, rather than creating an L1 overlapping them.
+        let (got, branches, files_later) = round_info
+            .calculate(test_components(), Some(got), &partition_info, files_later)
+            .await
+            .unwrap();
+        assert!(!got.is_expired());
+        let compacted = branches.into_iter().flatten().collect::<Vec<_>>();
+        assert_eq!(ids(&compacted), vec![2, 3, 4]);
+        assert!(files_later.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_budget() {
+        // 4 small, non overlapping L0s
+        let files = (1..=4)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_time_range(i * 100, i * 100 + 50)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_file_size_bytes(100)
+                    .with_max_l0_created_at(i)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        // 1500 bytes in memory is 1500 parquet bytes for 10 columns, but only 150 for 100 columns.
+        let round_info =
+            LevelBasedRoundInfo::new(10, 1000, 3.0, true).with_memory_budget(1500, 0.1);
+
+        // A narrow table is limited by the parquet size, so all files are compacted together.
+        let narrow = PartitionInfoBuilder::new().with_num_columns(10).build();
+        assert_eq!(
+            round_info
+                .plan_limits(&narrow, None)
+                .max_total_file_size_per_plan,
+            1000
+        );
+        let (got, branches, _) = round_info
+            .calculate(test_components(), None, &narrow, files.clone())
+            .await
+            .unwrap();
+        assert!(matches!(got, RoundInfo::TargetLevel { .. }), "{got}");
+        assert_eq!(branches.len(), 1);
+        assert_eq!(branches[0].len(), 4);
+
+        // A wide table can only fit one file in each plan.
+        let wide = PartitionInfoBuilder::new().with_num_columns(100).build();
+        assert_eq!(
+            round_info
+                .plan_limits(&wide, None)
+                .max_total_file_size_per_plan,
+            150
+        );
+        let (got, branches, _) = round_info
+            .calculate(test_components(), None, &wide, files)
+            .await
+            .unwrap();
+        assert!(matches!(got, RoundInfo::CompactRanges { .. }), "{got}");
+        assert_eq!(branches.len(), 4);
+        assert!(branches.iter().all(|b| b.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn test_max_branches_per_round() {
+        // 4 non overlapping L0s too big to compact together, created newest first
+        let files = (1..=4)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_time_range(i * 100, i * 100 + 50)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_file_size_bytes(100)
+                    .with_max_l0_created_at(10 - i)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let ids = |files: &[ParquetFile]| files.iter().map(|f| f.id.get()).collect::<Vec<_>>();
+        let partition_info = PartitionInfoBuilder::new().build();
+
+        let round_info = LevelBasedRoundInfo::new(10, 150, 3.0, true);
+        let (_, branches, _) = round_info
+            .calculate(test_components(), None, &partition_info, files.clone())
+            .await
+            .unwrap();
+        assert_eq!(branches.len(), 4);
+
+        // Only the 2 branches with the oldest files are compacted...
+        let round_info = round_info.with_max_branches_per_round(NonZeroUsize::new(2).unwrap());
+        let (got, branches, files_later) = round_info
+            .calculate(test_components(), None, &partition_info, files)
+            .await
+            .unwrap();
+        assert_eq!(branches.len(), 2);
+        assert_eq!(ids(&branches[0]), vec![4]);
+        assert_eq!(ids(&branches[1]), vec![3]);
+        assert_eq!(ids(&files_later), vec![2, 1]);
+
+        // ...and the others are compacted next round.
+        let (_, branches, files_later) = round_info
+            .calculate(test_components(), Some(got), &partition_info, files_later)
+            .await
+            .unwrap();
+        assert_eq!(branches.len(), 2);
+        assert_eq!(ids(&branches.concat()), vec![2, 1]);
+        assert!(files_later.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_file_size_per_plan() {
+        let files = (1..=4)
+            .map(|i| {
+                ParquetFileBuilder::new(i)
+                    .with_time_range(i * 100, i * 100 + 50)
+                    .with_compaction_level(CompactionLevel::Initial)
+                    .with_file_size_bytes(100)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let partition_info = PartitionInfoBuilder::new().with_partition_id(3).build();
+        let partition_id = partition_info.partition_id;
+        let components = test_components();
+        let round_info = LevelBasedRoundInfo::new(10, 150, 3.0, true)
+            .with_adaptive_file_size_per_plan(100, 1000);
+
+        // Whatever was observed before, the first round uses the static limit.
+        components
+            .output_ratio_tracker
+            .record(partition_id, 100, 10);
+        let (first, _, _) = round_info
+            .calculate(
+                Arc::clone(&components),
+                None,
+                &partition_info,
+                files.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(first.max_total_file_size_to_group(), Some(150));
+
+        // The driver observed the first round's output was a quarter of its input, so the next
+        // round takes 4x the input per plan, within the configured bounds.
+        for (input_bytes, output_bytes, expected) in
+            [(400, 100, 600), (400, 10, 1000), (100, 1000, 100)]
+        {
+            components
+                .output_ratio_tracker
+                .record(partition_id, input_bytes, output_bytes);
+            let (got, _, _) = round_info
+                .calculate(
+                    Arc::clone(&components),
+                    Some(first.clone()),
+                    &partition_info,
+                    files.clone(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(got.max_total_file_size_to_group(), Some(expected));
+        }
+
+        // Without observations, the static limit applies.
+        let (got, _, _) = round_info
+            .calculate(Arc::clone(&components), Some(first), &partition_info, files)
+            .await
+            .unwrap();
+        assert_eq!(got.max_total_file_size_to_group(), Some(150));
+    }
+
+    #[tokio::test]
+    async fn test_time_bucketed() {
+        let file = |id: i64, level, min_time, max_time| {
+            ParquetFileBuilder::new(id)
+                .with_compaction_level(level)
+                .with_time_range(min_time, max_time)
+                .with_file_size_bytes(10)
+                .with_max_l0_created_at(id)
+                .build()
+        };
+        // small L0s and an L1 in 2 buckets of 100ns, and an L2 spanning them
+        let bucket_0 = vec![
+            file(1, CompactionLevel::FileNonOverlapped, 0, 50),
+            file(2, CompactionLevel::Initial, 10, 20),
+            file(3, CompactionLevel::Initial, 30, 40),
+        ];
+        let bucket_1 = vec![
+            file(4, CompactionLevel::Initial, 110, 120),
+            file(5, CompactionLevel::Initial, 150, 160),
+        ];
+        let l2 = file(6, CompactionLevel::Final, 0, 500);
+        let files = bucket_0
+            .iter()
+            .chain(&bucket_1)
+            .chain([&l2])
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // By default everything is compacted to L1 at once.
+        let round_info = LevelBasedRoundInfo::new(10, 1000, 3.0, true);
+        assert!(matches!(
+            round_info.round_info(None, &files),
+            RoundInfo::TargetLevel { .. }
+        ));
+
+        // With time buckets, each bucket is its own branch.
+        let round_info = round_info.with_time_bucket(Duration::from_nanos(100));
+        let (got, branches, files_later) = round_info
+            .calculate(
+                test_components(),
+                None,
+                &PartitionInfoBuilder::new().build(),
+                files.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            got,
+            RoundInfo::TimeBucketed {
+                bucket_nanos: 100,
+                target_level: CompactionLevel::FileNonOverlapped,
+                level_stats: LevelStats::new(&files),
+            }
+        );
+        assert_eq!(branches, vec![bucket_0.clone(), bucket_1]);
+        assert_eq!(files_later, vec![l2.clone()]);
+
+        // Files within a single bucket are compacted as usual.
+        let files = bucket_0.into_iter().chain([l2]).collect::<Vec<_>>();
+        assert!(matches!(
+            round_info.round_info(None, &files),
+            RoundInfo::TargetLevel { .. }
+        ));
+    }
 }
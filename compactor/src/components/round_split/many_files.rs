@@ -63,7 +63,7 @@ impl RoundSplit for ManyFilesRoundSplit {
                 (start_files, rest)
             }
 
-            RoundInfo::VerticalSplit { split_times } => {
+            RoundInfo::VerticalSplit { split_times, .. } => {
                 // We're splitting L0 files at split_times.  So any L0 that overlaps a split_time needs processed, and all other files are ignored until later.
                 let (split_files, rest): (Vec<ParquetFile>, Vec<ParquetFile>) =
                     files.into_iter().partition(|f| {
@@ -87,6 +87,20 @@ impl RoundSplit for ManyFilesRoundSplit {
 
                 (compact_files, rest)
             }
+
+            // A cold partition is compacted all at once, so every file is needed now.
+            RoundInfo::ColdCompaction { .. } => (files, vec![]),
+
+            // Nothing is compacted while dropping expired files.
+            RoundInfo::Expired { .. } => (vec![], files),
+
+            // Only the start and target levels are compacted, the buckets are formed by divide.
+            RoundInfo::TimeBucketed { target_level, .. } => {
+                let start_level = target_level.prev();
+                files.into_iter().partition(|f| {
+                    f.compaction_level == start_level || f.compaction_level == target_level
+                })
+            }
         }
     }
 }
@@ -111,6 +125,8 @@ mod tests {
             start_level: CompactionLevel::Initial,
             max_num_files_to_group: 2,
             max_total_file_size_to_group: 100,
+            max_l0_created_at_window_ns: None,
+            level_stats: Default::default(),
         };
         let split = ManyFilesRoundSplit::new();
 
@@ -150,6 +166,8 @@ mod tests {
         let round_info = RoundInfo::TargetLevel {
             target_level: CompactionLevel::Final,
             max_total_file_size_to_group: 100 * 1024 * 1024,
+            estimated_branch_output_bytes: vec![],
+            level_stats: Default::default(),
         };
         let split = ManyFilesRoundSplit::new();
 
@@ -164,4 +182,54 @@ mod tests {
             (vec![f1, f2], vec![])
         );
     }
+
+    #[test]
+    fn test_split_cold_compaction() {
+        let round_info = RoundInfo::ColdCompaction {
+            max_num_files_to_group: 10,
+            max_total_file_size_to_group: 100 * 1024 * 1024,
+            level_stats: Default::default(),
+        };
+        let split = ManyFilesRoundSplit::new();
+
+        // every level is compacted together
+        let f1 = ParquetFileBuilder::new(1)
+            .with_compaction_level(CompactionLevel::Initial)
+            .build();
+        let f2 = ParquetFileBuilder::new(2)
+            .with_compaction_level(CompactionLevel::FileNonOverlapped)
+            .build();
+        let f3 = ParquetFileBuilder::new(3)
+            .with_compaction_level(CompactionLevel::Final)
+            .build();
+        assert_eq!(
+            split.split(vec![f1.clone(), f2.clone(), f3.clone()], round_info),
+            (vec![f1, f2, f3], vec![])
+        );
+    }
+
+    #[test]
+    fn test_split_time_bucketed() {
+        let round_info = RoundInfo::TimeBucketed {
+            bucket_nanos: 100,
+            target_level: CompactionLevel::FileNonOverlapped,
+            level_stats: Default::default(),
+        };
+        let split = ManyFilesRoundSplit::new();
+
+        // L2s wait for a later round
+        let f1 = ParquetFileBuilder::new(1)
+            .with_compaction_level(CompactionLevel::Initial)
+            .build();
+        let f2 = ParquetFileBuilder::new(2)
+            .with_compaction_level(CompactionLevel::FileNonOverlapped)
+            .build();
+        let f3 = ParquetFileBuilder::new(3)
+            .with_compaction_level(CompactionLevel::Final)
+            .build();
+        assert_eq!(
+            split.split(vec![f1.clone(), f2.clone(), f3.clone()], round_info),
+            (vec![f1, f2], vec![f3])
+        );
+    }
 }
@@ -0,0 +1,165 @@
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+
+use iox_time::{Time, TimeProvider};
+use metric::{Registry, U64Gauge};
+use observability_deps::tracing::{info, warn};
+use parking_lot::Mutex;
+
+const METRIC_NAME_SCRATCHPAD_BREAKER_OPEN: &str = "iox_compactor_scratchpad_breaker_open";
+
+/// Circuit breaker shared by all the scratchpads of a generator.
+///
+/// After `max_failures` consecutive failed transfers, the breaker opens: the scratchpads created
+/// during the cool-down bypass the scratchpad store. Once the cool-down is over, staging is tried
+/// again, but a single failed transfer opens the breaker again.
+#[derive(Debug)]
+pub struct ScratchpadBreaker {
+    max_failures: NonZeroUsize,
+    cooldown: Duration,
+    time_provider: Arc<dyn TimeProvider>,
+    state: Mutex<BreakerState>,
+
+    /// 1 while the breaker is open, 0 otherwise.
+    open: U64Gauge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BreakerState {
+    Closed { failures: usize },
+    Open { until: Time },
+}
+
+impl ScratchpadBreaker {
+    pub fn new(
+        max_failures: NonZeroUsize,
+        cooldown: Duration,
+        time_provider: Arc<dyn TimeProvider>,
+        registry: &Registry,
+    ) -> Self {
+        let open = registry
+            .register_metric::<U64Gauge>(
+                METRIC_NAME_SCRATCHPAD_BREAKER_OPEN,
+                "Whether new scratchpads bypass the scratchpad store after too many failed transfers (1) or not (0)",
+            )
+            .recorder(&[]);
+
+        Self {
+            max_failures,
+            cooldown,
+            time_provider,
+            state: Mutex::new(BreakerState::Closed { failures: 0 }),
+            open,
+        }
+    }
+
+    /// Whether new scratchpads should bypass the scratchpad store.
+    ///
+    /// Closes the breaker, half-way, if the cool-down is over.
+    pub fn is_open(&self) -> bool {
+        let mut state = self.state.lock();
+        match *state {
+            BreakerState::Closed { .. } => false,
+            BreakerState::Open { until } if self.time_provider.now() < until => true,
+            BreakerState::Open { .. } => {
+                info!("retrying to stage files in the scratchpad");
+                *state = BreakerState::Closed {
+                    failures: self.max_failures.get() - 1,
+                };
+                self.open.set(0);
+                false
+            }
+        }
+    }
+
+    /// Record a successful transfer, resetting the consecutive failures.
+    pub fn record_success(&self) {
+        let mut state = self.state.lock();
+        if let BreakerState::Closed { failures } = &mut *state {
+            *failures = 0;
+        }
+    }
+
+    /// Record a failed transfer, opening the breaker after too many in a row.
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock();
+        let BreakerState::Closed { failures } = &mut *state else {
+            // transfers of scratchpads created before the breaker opened
+            return;
+        };
+
+        *failures += 1;
+        if *failures >= self.max_failures.get() {
+            warn!(
+                failures = *failures,
+                cooldown_secs = self.cooldown.as_secs_f32(),
+                "too many failed scratchpad transfers, bypassing the scratchpad",
+            );
+            *state = BreakerState::Open {
+                until: self.time_provider.now() + self.cooldown,
+            };
+            self.open.set(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use iox_time::MockProvider;
+    use metric::{Attributes, Metric};
+
+    use super::*;
+
+    fn breaker_open(registry: &Registry) -> u64 {
+        registry
+            .get_instrument::<Metric<U64Gauge>>(METRIC_NAME_SCRATCHPAD_BREAKER_OPEN)
+            .expect("metric registered")
+            .get_observer(&Attributes::from(&[]))
+            .expect("observer exists")
+            .fetch()
+    }
+
+    #[test]
+    fn test_breaker() {
+        let time_provider = Arc::new(MockProvider::new(Time::MIN));
+        let registry = Registry::new();
+        let breaker = ScratchpadBreaker::new(
+            NonZeroUsize::new(2).unwrap(),
+            Duration::from_secs(10),
+            Arc::clone(&time_provider) as _,
+            &registry,
+        );
+        assert!(!breaker.is_open());
+        assert_eq!(breaker_open(&registry), 0);
+
+        // only consecutive failures count
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert_eq!(breaker_open(&registry), 1);
+
+        // successes while open don't close it early
+        breaker.record_success();
+        time_provider.inc(Duration::from_secs(9));
+        assert!(breaker.is_open());
+
+        time_provider.inc(Duration::from_secs(1));
+        assert!(!breaker.is_open());
+        assert_eq!(breaker_open(&registry), 0);
+
+        // a single failure opens it again after the cool-down
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert_eq!(breaker_open(&registry), 1);
+
+        time_provider.inc(Duration::from_secs(10));
+        assert!(!breaker.is_open());
+        breaker.record_success();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+        assert_eq!(breaker_open(&registry), 0);
+    }
+}
@@ -0,0 +1,438 @@
+use std::{fmt::Display, sync::Arc, time::Instant};
+
+use async_trait::async_trait;
+use metric::{DurationHistogram, Registry, U64Counter, U64Gauge};
+use parking_lot::Mutex;
+use parquet_file::ParquetFilePath;
+use trace::ctx::SpanContext;
+use uuid::Uuid;
+
+use super::{Scratchpad, ScratchpadCleanSummary, ScratchpadError, ScratchpadGen, ScratchpadStats};
+
+const METRIC_NAME_SCRATCHPAD_LOADED_FILES: &str = "iox_compactor_scratchpad_loaded_files";
+const METRIC_NAME_SCRATCHPAD_LOADED_BYTES: &str = "iox_compactor_scratchpad_loaded_bytes";
+const METRIC_NAME_SCRATCHPAD_PUBLIC_FILES: &str = "iox_compactor_scratchpad_public_files";
+const METRIC_NAME_SCRATCHPAD_PUBLIC_BYTES: &str = "iox_compactor_scratchpad_public_bytes";
+const METRIC_NAME_SCRATCHPAD_LOAD_DURATION: &str = "iox_compactor_scratchpad_load_duration";
+const METRIC_NAME_SCRATCHPAD_PUBLIC_DURATION: &str = "iox_compactor_scratchpad_public_duration";
+const METRIC_NAME_SCRATCHPAD_STAGED_BYTES: &str = "iox_compactor_scratchpad_staged_bytes";
+const METRIC_NAME_SCRATCHPAD_CLEAN_COUNT: &str = "iox_compactor_scratchpad_clean_count";
+const METRIC_NAME_SCRATCHPAD_TRANSFER_RETRIES: &str = "iox_compactor_scratchpad_transfer_retries";
+
+#[derive(Debug)]
+struct Metrics {
+    loaded_files: U64Counter,
+    loaded_bytes: U64Counter,
+    public_files: U64Counter,
+    public_bytes: U64Counter,
+    load_duration: DurationHistogram,
+    public_duration: DurationHistogram,
+    staged_bytes: U64Gauge,
+    clean_files_count: U64Counter,
+    clean_written_count: U64Counter,
+    clean_all_count: U64Counter,
+    transfer_retries: U64Counter,
+}
+
+/// Records the IO of the [`Scratchpad`]s created by the inner [`ScratchpadGen`].
+#[derive(Debug)]
+pub struct MetricsScratchpadGenWrapper<T>
+where
+    T: ScratchpadGen,
+{
+    metrics: Arc<Metrics>,
+    inner: T,
+}
+
+impl<T> MetricsScratchpadGenWrapper<T>
+where
+    T: ScratchpadGen,
+{
+    pub fn new(inner: T, registry: &Registry) -> Self {
+        let loaded_files = registry
+            .register_metric::<U64Counter>(
+                METRIC_NAME_SCRATCHPAD_LOADED_FILES,
+                "Number of files loaded from the object store to the scratchpad",
+            )
+            .recorder(&[]);
+        let loaded_bytes = registry
+            .register_metric::<U64Counter>(
+                METRIC_NAME_SCRATCHPAD_LOADED_BYTES,
+                "Number of bytes loaded from the object store to the scratchpad",
+            )
+            .recorder(&[]);
+        let public_files = registry
+            .register_metric::<U64Counter>(
+                METRIC_NAME_SCRATCHPAD_PUBLIC_FILES,
+                "Number of files copied from the scratchpad to the object store",
+            )
+            .recorder(&[]);
+        let public_bytes = registry
+            .register_metric::<U64Counter>(
+                METRIC_NAME_SCRATCHPAD_PUBLIC_BYTES,
+                "Number of bytes copied from the scratchpad to the object store",
+            )
+            .recorder(&[]);
+        let load_duration = registry
+            .register_metric::<DurationHistogram>(
+                METRIC_NAME_SCRATCHPAD_LOAD_DURATION,
+                "Duration of loading files to the scratchpad",
+            )
+            .recorder(&[]);
+        let public_duration = registry
+            .register_metric::<DurationHistogram>(
+                METRIC_NAME_SCRATCHPAD_PUBLIC_DURATION,
+                "Duration of copying files from the scratchpad to the object store",
+            )
+            .recorder(&[]);
+        let staged_bytes = registry
+            .register_metric::<U64Gauge>(
+                METRIC_NAME_SCRATCHPAD_STAGED_BYTES,
+                "Number of bytes loaded to the scratchpads and not cleaned yet",
+            )
+            .recorder(&[]);
+        let clean_metric = registry.register_metric::<U64Counter>(
+            METRIC_NAME_SCRATCHPAD_CLEAN_COUNT,
+            "Number of times files were cleaned from a scratchpad",
+        );
+        let clean_files_count = clean_metric.recorder(&[("kind", "files")]);
+        let clean_written_count = clean_metric.recorder(&[("kind", "written")]);
+        let clean_all_count = clean_metric.recorder(&[("kind", "all")]);
+        let transfer_retries = registry
+            .register_metric::<U64Counter>(
+                METRIC_NAME_SCRATCHPAD_TRANSFER_RETRIES,
+                "Number of failed attempts to copy a file into or out of the scratchpad that were retried",
+            )
+            .recorder(&[]);
+
+        Self {
+            metrics: Arc::new(Metrics {
+                loaded_files,
+                loaded_bytes,
+                public_files,
+                public_bytes,
+                load_duration,
+                public_duration,
+                staged_bytes,
+                clean_files_count,
+                clean_written_count,
+                clean_all_count,
+                transfer_retries,
+            }),
+            inner,
+        }
+    }
+}
+
+impl<T> Display for MetricsScratchpadGenWrapper<T>
+where
+    T: ScratchpadGen,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "metrics({})", self.inner)
+    }
+}
+
+impl<T> ScratchpadGen for MetricsScratchpadGenWrapper<T>
+where
+    T: ScratchpadGen,
+{
+    fn pad(&self, span_ctx: Option<SpanContext>) -> Arc<dyn Scratchpad> {
+        Arc::new(MetricsScratchpad {
+            metrics: Arc::clone(&self.metrics),
+            last_stats: Default::default(),
+            inner: self.inner.pad(span_ctx),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct MetricsScratchpad {
+    metrics: Arc<Metrics>,
+    /// Statistics of the inner scratchpad already recorded.
+    last_stats: Mutex<ScratchpadStats>,
+    inner: Arc<dyn Scratchpad>,
+}
+
+impl MetricsScratchpad {
+    /// Record the statistics of the inner scratchpad since they were last recorded.
+    fn record_stats(&self) {
+        let mut last_stats = self.last_stats.lock();
+        let stats = self.inner.stats();
+
+        let metrics = &self.metrics;
+        metrics
+            .loaded_files
+            .inc((stats.files_loaded - last_stats.files_loaded) as u64);
+        metrics
+            .loaded_bytes
+            .inc((stats.bytes_loaded - last_stats.bytes_loaded) as u64);
+        metrics
+            .public_files
+            .inc((stats.files_made_public - last_stats.files_made_public) as u64);
+        metrics
+            .public_bytes
+            .inc((stats.bytes_made_public - last_stats.bytes_made_public) as u64);
+        metrics
+            .staged_bytes
+            .delta(stats.staged_bytes as i64 - last_stats.staged_bytes as i64);
+        metrics
+            .transfer_retries
+            .inc((stats.transfer_retries - last_stats.transfer_retries) as u64);
+
+        *last_stats = stats;
+    }
+}
+
+impl Drop for MetricsScratchpad {
+    fn drop(&mut self) {
+        // the inner scratchpad cleans up what is left when dropped
+        let last_stats = self.last_stats.lock();
+        self.metrics
+            .staged_bytes
+            .dec(last_stats.staged_bytes as u64);
+    }
+}
+
+#[async_trait]
+impl Scratchpad for MetricsScratchpad {
+    fn uuids(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError> {
+        self.inner.uuids(files)
+    }
+
+    async fn load_to_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<Vec<Uuid>, ScratchpadError> {
+        let start = Instant::now();
+        let res = self.inner.load_to_scratchpad(files).await;
+        self.metrics.load_duration.record(start.elapsed());
+        self.record_stats();
+        res
+    }
+
+    fn record_written(&self, files: &[ParquetFilePath]) {
+        self.inner.record_written(files)
+    }
+
+    async fn make_public(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError> {
+        let start = Instant::now();
+        let res = self.inner.make_public(files).await;
+        self.metrics.public_duration.record(start.elapsed());
+        self.record_stats();
+        res
+    }
+
+    fn prefetch(&self, files: &[ParquetFilePath]) {
+        self.inner.prefetch(files)
+    }
+
+    async fn clean_from_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        let res = self.inner.clean_from_scratchpad(files).await;
+        self.metrics.clean_files_count.inc(1);
+        self.record_stats();
+        res
+    }
+
+    async fn clean_written_from_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        let res = self.inner.clean_written_from_scratchpad(files).await;
+        self.metrics.clean_written_count.inc(1);
+        self.record_stats();
+        res
+    }
+
+    async fn clean(&self) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        let res = self.inner.clean().await;
+        self.metrics.clean_all_count.inc(1);
+        self.record_stats();
+        res
+    }
+
+    async fn retain_only(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        let res = self.inner.retain_only(files).await;
+        self.record_stats();
+        res
+    }
+
+    fn stats(&self) -> ScratchpadStats {
+        self.inner.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use backoff::BackoffConfig;
+    use metric::{assert_counter, assert_histogram, Attributes, Metric};
+
+    use crate::components::scratchpad::{
+        noop::NoopScratchpadGen,
+        prod::ProdScratchpadGen,
+        test_util::{file_path, stores},
+    };
+
+    use super::*;
+
+    fn staged_bytes(registry: &Registry) -> u64 {
+        registry
+            .get_instrument::<Metric<U64Gauge>>(METRIC_NAME_SCRATCHPAD_STAGED_BYTES)
+            .expect("metric registered")
+            .get_observer(&Attributes::from(&[]))
+            .expect("observer exists")
+            .fetch()
+    }
+
+    fn assert_clean_count(registry: &Registry, kind: &'static str, value: u64) {
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_SCRATCHPAD_CLEAN_COUNT,
+            labels = Attributes::from(&[("kind", kind)]),
+            value = value,
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let registry = Registry::new();
+        let gen = MetricsScratchpadGenWrapper::new(NoopScratchpadGen::new(), &registry);
+        assert_eq!(gen.to_string(), "metrics(noop)");
+    }
+
+    #[tokio::test]
+    async fn test_noop() {
+        let registry = Registry::new();
+        let gen = MetricsScratchpadGenWrapper::new(NoopScratchpadGen::new(), &registry);
+        let pad = gen.pad(None);
+
+        pad.load_to_scratchpad(&[file_path(1)]).await.unwrap();
+        pad.clean().await.unwrap();
+
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_SCRATCHPAD_LOADED_FILES,
+            value = 0,
+        );
+        assert_histogram!(
+            registry,
+            DurationHistogram,
+            METRIC_NAME_SCRATCHPAD_LOAD_DURATION,
+            samples = 1,
+        );
+        assert_clean_count(&registry, "all", 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_public_clean() {
+        let registry = Registry::new();
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = MetricsScratchpadGenWrapper::new(
+            ProdScratchpadGen::new(
+                false,
+                NonZeroUsize::new(1).unwrap(),
+                BackoffConfig::default(),
+                Arc::clone(&store_input),
+                Arc::clone(&store_scratchpad),
+                store_output,
+            ),
+            &registry,
+        );
+        let pad = gen.pad(None);
+
+        let f1 = file_path(1);
+        let f2 = file_path(2);
+        let f3 = file_path(3);
+        for (f, size) in [(&f1, 10), (&f2, 20), (&f3, 30)] {
+            store_input
+                .put(&f.object_store_path(), vec![0; size].into())
+                .await
+                .unwrap();
+        }
+
+        pad.load_to_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
+        // already staged files are not loaded again
+        pad.load_to_scratchpad(&[f2.clone(), f3.clone()])
+            .await
+            .unwrap();
+        assert_eq!(staged_bytes(&registry), 60);
+
+        // compaction output
+        let f4_masked = file_path(4);
+        store_scratchpad
+            .put(&f4_masked.object_store_path(), vec![0; 5].into())
+            .await
+            .unwrap();
+        pad.record_written(&[f4_masked.clone()]);
+        let uuids = pad.make_public(&[f4_masked]).await.unwrap();
+        let f4 = file_path(4).with_object_store_id(uuids[0]);
+
+        pad.clean_from_scratchpad(&[f1.clone()]).await.unwrap();
+        assert_eq!(staged_bytes(&registry), 50);
+        pad.clean_written_from_scratchpad(&[f4]).await.unwrap();
+        assert_eq!(staged_bytes(&registry), 50);
+
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_SCRATCHPAD_LOADED_FILES,
+            value = 3,
+        );
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_SCRATCHPAD_LOADED_BYTES,
+            value = 60,
+        );
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_SCRATCHPAD_PUBLIC_FILES,
+            value = 1,
+        );
+        assert_counter!(
+            registry,
+            U64Counter,
+            METRIC_NAME_SCRATCHPAD_PUBLIC_BYTES,
+            value = 5,
+        );
+        assert_histogram!(
+            registry,
+            DurationHistogram,
+            METRIC_NAME_SCRATCHPAD_LOAD_DURATION,
+            samples = 2,
+        );
+        assert_histogram!(
+            registry,
+            DurationHistogram,
+            METRIC_NAME_SCRATCHPAD_PUBLIC_DURATION,
+            samples = 1,
+        );
+        assert_clean_count(&registry, "files", 1);
+        assert_clean_count(&registry, "written", 1);
+        assert_clean_count(&registry, "all", 0);
+
+        pad.clean().await.unwrap();
+        assert_eq!(staged_bytes(&registry), 0);
+        assert_clean_count(&registry, "all", 1);
+
+        // a dropped pad no longer counts as staged
+        let pad = gen.pad(None);
+        pad.load_to_scratchpad(&[f1]).await.unwrap();
+        assert_eq!(staged_bytes(&registry), 10);
+        drop(pad);
+        assert_eq!(staged_bytes(&registry), 0);
+    }
+}
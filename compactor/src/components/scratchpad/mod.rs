@@ -1,20 +1,174 @@
 use std::fmt::{Debug, Display};
+use std::num::NonZeroUsize;
+use std::ops::AddAssign;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use backoff::BackoffConfig;
 use parquet_file::ParquetFilePath;
+use trace::ctx::SpanContext;
 use uuid::Uuid;
 
+pub mod breaker;
+pub mod metrics;
 pub mod noop;
+mod pool;
 pub mod prod;
 mod util;
 
 #[cfg(test)]
 mod test_util;
 
+/// What [`Scratchpad::load_to_scratchpad`] does when the files to load don't fit the remaining
+/// capacity of the scratchpad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScratchpadCapacityPolicy {
+    /// Wait until enough files are cleaned from the scratchpad.
+    #[default]
+    Block,
+
+    /// Return [`ScratchpadError::CapacityExceeded`].
+    Error,
+}
+
+/// How a [`Scratchpad`] retries copying a file into or out of the scratchpad.
+#[derive(Debug, Clone, Default)]
+pub struct ScratchpadRetryPolicy {
+    /// Attempts per file before failing with [`ScratchpadError::TransferFailed`], or retry
+    /// forever.
+    pub max_attempts: Option<NonZeroUsize>,
+
+    /// Backoff between the attempts.
+    pub backoff_config: BackoffConfig,
+}
+
+/// Errors returned by a [`Scratchpad`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScratchpadError {
+    /// Loading the files would exceed the capacity of the scratchpad.
+    CapacityExceeded {
+        /// Bytes of the files that needed to be loaded.
+        requested_bytes: usize,
+        /// Bytes already staged in the scratchpad.
+        staged_bytes: usize,
+        /// Capacity of the scratchpad.
+        capacity_bytes: usize,
+    },
+
+    /// A file copied to the output store kept having a different size than the original.
+    IntegrityCheckFailed {
+        /// Object store location of the copy.
+        location: String,
+        /// Size of the original file.
+        expected_bytes: usize,
+        /// Size of the last copy.
+        actual_bytes: usize,
+        /// Number of times the file was copied.
+        attempts: usize,
+    },
+
+    /// A file could not be copied, after the maximum number of attempts of the
+    /// [`ScratchpadRetryPolicy`].
+    TransferFailed {
+        /// Object store location of the file copied.
+        location: String,
+        /// Number of times the copy was attempted.
+        attempts: usize,
+        /// Error of the last attempt.
+        error: String,
+        /// Whether the last attempt failed to read the file from the source store, rather than to
+        /// write it to the destination store.
+        source_failed: bool,
+    },
+
+    /// Some files are unknown to the scratchpad, e.g. because they were never loaded into it.
+    UnknownFiles {
+        /// Object store locations of the unknown files.
+        files: Vec<String>,
+    },
+}
+
+impl Display for ScratchpadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CapacityExceeded {
+                requested_bytes,
+                staged_bytes,
+                capacity_bytes,
+            } => write!(
+                f,
+                "cannot load {requested_bytes} bytes to the scratchpad: {staged_bytes} of {capacity_bytes} bytes already staged"
+            ),
+            Self::IntegrityCheckFailed {
+                location,
+                expected_bytes,
+                actual_bytes,
+                attempts,
+            } => write!(
+                f,
+                "copy of {location} has {actual_bytes} bytes instead of {expected_bytes} after {attempts} attempts"
+            ),
+            Self::TransferFailed {
+                location,
+                attempts,
+                error,
+                ..
+            } => write!(
+                f,
+                "failed to copy {location} after {attempts} attempts: {error}"
+            ),
+            Self::UnknownFiles { files } => write!(
+                f,
+                "{} files unknown to the scratchpad: {}",
+                files.len(),
+                files.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScratchpadError {}
+
+/// What cleaning a [`Scratchpad`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScratchpadCleanSummary {
+    /// Files deleted from the scratchpad.
+    pub files: usize,
+    /// Bytes of the loaded (or prefetched) files deleted.
+    pub bytes: usize,
+    /// Object store locations of the files that could not be deleted, and are leaked.
+    pub leaked: Vec<String>,
+}
+
+impl AddAssign for ScratchpadCleanSummary {
+    fn add_assign(&mut self, rhs: Self) {
+        self.files += rhs.files;
+        self.bytes += rhs.bytes;
+        self.leaked.extend(rhs.leaked);
+    }
+}
+
+/// Cumulative statistics of a [`Scratchpad`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScratchpadStats {
+    /// Files copied from the input store to the scratchpad.
+    pub files_loaded: usize,
+    /// Bytes copied from the input store to the scratchpad.
+    pub bytes_loaded: usize,
+    /// Files copied from the scratchpad to the output store.
+    pub files_made_public: usize,
+    /// Bytes copied from the scratchpad to the output store.
+    pub bytes_made_public: usize,
+    /// Bytes loaded from the input store and not cleaned yet.
+    pub staged_bytes: usize,
+    /// Failed attempts to copy a file that were retried.
+    pub transfer_retries: usize,
+}
+
 /// Create a [`Scratchpad`] for use as intermediate storage
 pub trait ScratchpadGen: Debug + Display + Send + Sync {
-    fn pad(&self) -> Arc<dyn Scratchpad>;
+    /// Create a scratchpad, tracing its transfers as children of `span_ctx`, if any.
+    fn pad(&self, span_ctx: Option<SpanContext>) -> Arc<dyn Scratchpad>;
 }
 
 /// An intermediate in-memory store (can be a disk later if we want)
@@ -49,10 +203,61 @@ pub trait ScratchpadGen: Debug + Display + Send + Sync {
 /// SMALLER than the uncompressed Arrow data during compaction itself.
 #[async_trait]
 pub trait Scratchpad: Debug + Send + Sync + 'static {
-    fn uuids(&self, files: &[ParquetFilePath]) -> Vec<Uuid>;
-    async fn load_to_scratchpad(&self, files: &[ParquetFilePath]) -> Vec<Uuid>;
-    async fn make_public(&self, files: &[ParquetFilePath]) -> Vec<Uuid>;
-    async fn clean_from_scratchpad(&self, files: &[ParquetFilePath]);
-    async fn clean_written_from_scratchpad(&self, files: &[ParquetFilePath]);
-    async fn clean(&self);
+    /// IDs of loaded files in the scratchpad.
+    ///
+    /// Fails with [`ScratchpadError::UnknownFiles`] if some files are not loaded.
+    fn uuids(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError>;
+    async fn load_to_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<Vec<Uuid>, ScratchpadError>;
+
+    /// Record files written to the scratchpad by the compaction, so that they can be
+    /// [made public](Self::make_public).
+    fn record_written(&self, files: &[ParquetFilePath]);
+
+    /// Copy files written to the scratchpad to the output store, returning their public IDs.
+    ///
+    /// Fails with [`ScratchpadError::UnknownFiles`], without copying any file, if some files
+    /// were neither [written](Self::record_written) nor loaded.
+    async fn make_public(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError>;
+
+    /// Start copying `files` to the scratchpad in the background, without waiting, so that a
+    /// later [`load_to_scratchpad`](Self::load_to_scratchpad) of them doesn't download them again.
+    ///
+    /// Files prefetched but never loaded are removed by [`clean`](Self::clean).
+    fn prefetch(&self, files: &[ParquetFilePath]);
+
+    /// Remove files from the scratchpad.
+    ///
+    /// Files that could not be deleted are forgotten anyway, and reported as
+    /// [leaked](ScratchpadCleanSummary::leaked).
+    async fn clean_from_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError>;
+
+    /// Remove files made public from the scratchpad, unless they're still needed.
+    ///
+    /// Fails with [`ScratchpadError::UnknownFiles`], without removing any file, if some files
+    /// were not made public.
+    async fn clean_written_from_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError>;
+
+    /// Remove all files from the scratchpad.
+    async fn clean(&self) -> Result<ScratchpadCleanSummary, ScratchpadError>;
+
+    /// Hint that no files but `files` will be loaded anymore, e.g. because all others were deleted
+    /// from the catalog.
+    ///
+    /// Scratchpads keeping written files for the next round evict all other files.
+    async fn retain_only(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError>;
+
+    /// Statistics of the files moved through this scratchpad so far.
+    fn stats(&self) -> ScratchpadStats;
 }
@@ -2,11 +2,15 @@ use std::{fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
 use parquet_file::ParquetFilePath;
+use trace::ctx::SpanContext;
 use uuid::Uuid;
 
-use super::{Scratchpad, ScratchpadGen};
+use super::{Scratchpad, ScratchpadCleanSummary, ScratchpadError, ScratchpadGen, ScratchpadStats};
 
 /// A scratchpad that ignores all inputs and outputs, for use in testing
+///
+/// Its scratchpad is the input and output store, so it never rejects files as
+/// [unknown](ScratchpadError::UnknownFiles).
 #[derive(Debug, Default)]
 pub struct NoopScratchpadGen;
 
@@ -23,7 +27,7 @@ impl Display for NoopScratchpadGen {
 }
 
 impl ScratchpadGen for NoopScratchpadGen {
-    fn pad(&self) -> Arc<dyn Scratchpad> {
+    fn pad(&self, _span_ctx: Option<SpanContext>) -> Arc<dyn Scratchpad> {
         Arc::new(NoopScratchpad)
     }
 }
@@ -33,21 +37,85 @@ struct NoopScratchpad;
 
 #[async_trait]
 impl Scratchpad for NoopScratchpad {
-    fn uuids(&self, files: &[ParquetFilePath]) -> Vec<Uuid> {
-        files.iter().map(|f| f.objest_store_id()).collect()
+    fn uuids(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError> {
+        Ok(files.iter().map(|f| f.objest_store_id()).collect())
     }
 
-    async fn load_to_scratchpad(&self, files: &[ParquetFilePath]) -> Vec<Uuid> {
-        files.iter().map(|f| f.objest_store_id()).collect()
+    async fn load_to_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<Vec<Uuid>, ScratchpadError> {
+        Ok(files.iter().map(|f| f.objest_store_id()).collect())
     }
 
-    async fn make_public(&self, files: &[ParquetFilePath]) -> Vec<Uuid> {
-        files.iter().map(|f| f.objest_store_id()).collect()
+    fn record_written(&self, _files: &[ParquetFilePath]) {}
+
+    async fn make_public(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError> {
+        Ok(files.iter().map(|f| f.objest_store_id()).collect())
+    }
+
+    fn prefetch(&self, _files: &[ParquetFilePath]) {}
+
+    async fn clean_from_scratchpad(
+        &self,
+        _files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        Ok(ScratchpadCleanSummary::default())
+    }
+
+    async fn clean_written_from_scratchpad(
+        &self,
+        _files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        Ok(ScratchpadCleanSummary::default())
+    }
+
+    async fn clean(&self) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        Ok(ScratchpadCleanSummary::default())
+    }
+
+    async fn retain_only(
+        &self,
+        _files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        Ok(ScratchpadCleanSummary::default())
+    }
+
+    fn stats(&self) -> ScratchpadStats {
+        ScratchpadStats::default()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::components::scratchpad::test_util::file_path;
 
-    async fn clean_from_scratchpad(&self, _files: &[ParquetFilePath]) {}
+    use super::*;
 
-    async fn clean_written_from_scratchpad(&self, _files: &[ParquetFilePath]) {}
+    #[tokio::test]
+    async fn test_unknown_files_are_accepted() {
+        let pad = NoopScratchpadGen::new().pad(None);
+        let files = [file_path(1), file_path(2)];
+        let uuids = files
+            .iter()
+            .map(|f| f.objest_store_id())
+            .collect::<Vec<_>>();
 
-    async fn clean(&self) {}
+        // nothing was loaded or written
+        assert_eq!(pad.uuids(&files).unwrap(), uuids);
+        assert_eq!(pad.make_public(&files).await.unwrap(), uuids);
+        pad.clean_written_from_scratchpad(&files).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_removes_nothing() {
+        let pad = NoopScratchpadGen::new().pad(None);
+        let files = [file_path(1)];
+
+        pad.load_to_scratchpad(&files).await.unwrap();
+        let summary = ScratchpadCleanSummary::default();
+        assert_eq!(pad.clean_from_scratchpad(&files).await.unwrap(), summary);
+        assert_eq!(pad.retain_only(&[]).await.unwrap(), summary);
+        assert_eq!(pad.clean().await.unwrap(), summary);
+    }
 }
@@ -0,0 +1,282 @@
+use std::collections::VecDeque;
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+use super::ScratchpadError;
+
+/// Byte budget shared by all the scratchpads of a generator.
+///
+/// Allocations are granted in the order they are requested: a waiting allocation is never
+/// overtaken by a later one, even if the later one would fit.
+#[derive(Debug)]
+pub struct ScratchpadPool {
+    capacity_bytes: usize,
+    state: Mutex<PoolState>,
+
+    /// Notified when bytes are released or the head of the queue changes.
+    changed: Notify,
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    allocated_bytes: usize,
+    next_ticket: u64,
+
+    /// Tickets of the waiting allocations, oldest first.
+    queue: VecDeque<u64>,
+}
+
+impl ScratchpadPool {
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self {
+            capacity_bytes,
+            state: Default::default(),
+            changed: Notify::new(),
+        }
+    }
+
+    pub fn capacity_bytes(&self) -> usize {
+        self.capacity_bytes
+    }
+
+    /// Bytes currently allocated, across all scratchpads.
+    pub fn allocated_bytes(&self) -> usize {
+        self.state.lock().allocated_bytes
+    }
+
+    /// Number of allocations waiting.
+    #[cfg(test)]
+    pub fn num_waiting(&self) -> usize {
+        self.state.lock().queue.len()
+    }
+
+    /// Allocate `bytes`, waiting behind earlier allocations until they fit.
+    ///
+    /// Fails right away if `bytes` exceeds the whole capacity.
+    pub async fn allocate(&self, bytes: usize) -> Result<(), ScratchpadError> {
+        let ticket = {
+            let mut state = self.state.lock();
+            if self.grant_now(&mut state, bytes)? {
+                return Ok(());
+            }
+
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            state.queue.push_back(ticket);
+            ticket
+        };
+        let mut waiter = Waiter {
+            pool: self,
+            ticket,
+            queued: true,
+        };
+
+        loop {
+            // create the future before checking, so a release in between is not missed
+            let changed = self.changed.notified();
+
+            {
+                let mut state = self.state.lock();
+                if state.queue.front() == Some(&ticket)
+                    && state.allocated_bytes + bytes <= self.capacity_bytes
+                {
+                    state.queue.pop_front();
+                    state.allocated_bytes += bytes;
+                    waiter.queued = false;
+                }
+            }
+            if !waiter.queued {
+                // the next allocation in line may fit as well
+                self.changed.notify_waiters();
+                return Ok(());
+            }
+
+            changed.await;
+        }
+    }
+
+    /// Allocate `bytes` if they fit right away, without overtaking waiting allocations.
+    pub fn try_allocate(&self, bytes: usize) -> Result<(), ScratchpadError> {
+        let mut state = self.state.lock();
+        if self.grant_now(&mut state, bytes)? {
+            Ok(())
+        } else {
+            Err(self.exceeded(&state, bytes))
+        }
+    }
+
+    /// Return bytes to the pool, waking up waiting allocations.
+    pub fn release(&self, bytes: usize) {
+        if bytes == 0 {
+            return;
+        }
+
+        {
+            let mut state = self.state.lock();
+            state.allocated_bytes = state
+                .allocated_bytes
+                .checked_sub(bytes)
+                .expect("released more than allocated");
+        }
+        self.changed.notify_waiters();
+    }
+
+    /// Grant the allocation if nothing is waiting and it fits.
+    fn grant_now(&self, state: &mut PoolState, bytes: usize) -> Result<bool, ScratchpadError> {
+        if bytes > self.capacity_bytes {
+            return Err(self.exceeded(state, bytes));
+        }
+
+        if bytes == 0
+            || (state.queue.is_empty() && state.allocated_bytes + bytes <= self.capacity_bytes)
+        {
+            state.allocated_bytes += bytes;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn exceeded(&self, state: &PoolState, bytes: usize) -> ScratchpadError {
+        ScratchpadError::CapacityExceeded {
+            requested_bytes: bytes,
+            staged_bytes: state.allocated_bytes,
+            capacity_bytes: self.capacity_bytes,
+        }
+    }
+}
+
+/// Leaves the queue if the allocation is abandoned while waiting.
+struct Waiter<'a> {
+    pool: &'a ScratchpadPool,
+    ticket: u64,
+    queued: bool,
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        if !self.queued {
+            return;
+        }
+
+        self.pool
+            .state
+            .lock()
+            .queue
+            .retain(|ticket| *ticket != self.ticket);
+        // the allocations behind may now be at the head of the queue
+        self.pool.changed.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::*;
+
+    /// Allocate in the background, waiting until the allocation is queued.
+    async fn queue_allocation(
+        pool: &Arc<ScratchpadPool>,
+        bytes: usize,
+    ) -> tokio::task::JoinHandle<Result<(), ScratchpadError>> {
+        let queued = pool.num_waiting();
+        let handle = tokio::spawn({
+            let pool = Arc::clone(pool);
+            async move { pool.allocate(bytes).await }
+        });
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while pool.num_waiting() == queued {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("allocation queued");
+        handle
+    }
+
+    #[tokio::test]
+    async fn test_allocate_release() {
+        let pool = ScratchpadPool::new(100);
+
+        pool.allocate(60).await.unwrap();
+        pool.try_allocate(40).unwrap();
+        assert_eq!(pool.allocated_bytes(), 100);
+
+        assert_eq!(
+            pool.try_allocate(1).unwrap_err(),
+            ScratchpadError::CapacityExceeded {
+                requested_bytes: 1,
+                staged_bytes: 100,
+                capacity_bytes: 100,
+            }
+        );
+
+        // nothing is needed for nothing
+        pool.try_allocate(0).unwrap();
+
+        pool.release(60);
+        pool.release(40);
+        assert_eq!(pool.allocated_bytes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_allocate_too_large() {
+        let pool = ScratchpadPool::new(100);
+        assert_eq!(
+            pool.allocate(101).await.unwrap_err(),
+            ScratchpadError::CapacityExceeded {
+                requested_bytes: 101,
+                staged_bytes: 0,
+                capacity_bytes: 100,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_allocate_fifo() {
+        let pool = Arc::new(ScratchpadPool::new(100));
+        pool.allocate(90).await.unwrap();
+
+        let mut large = queue_allocation(&pool, 80).await;
+        let mut small = queue_allocation(&pool, 10).await;
+
+        // the small allocation would fit, but does not overtake the large one
+        tokio::time::timeout(Duration::from_millis(50), &mut small)
+            .await
+            .unwrap_err();
+        pool.try_allocate(10).unwrap_err();
+
+        pool.release(90);
+        tokio::time::timeout(Duration::from_secs(5), &mut large)
+            .await
+            .expect("no timeout")
+            .unwrap()
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), &mut small)
+            .await
+            .expect("no timeout")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pool.allocated_bytes(), 90);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_allocation_leaves_queue() {
+        let pool = Arc::new(ScratchpadPool::new(100));
+        pool.allocate(90).await.unwrap();
+
+        let large = queue_allocation(&pool, 80).await;
+        let mut small = queue_allocation(&pool, 10).await;
+
+        large.abort();
+        tokio::time::timeout(Duration::from_secs(5), &mut small)
+            .await
+            .expect("no timeout")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pool.allocated_bytes(), 100);
+        assert_eq!(pool.num_waiting(), 0);
+    }
+}
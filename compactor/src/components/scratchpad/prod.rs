@@ -1,22 +1,42 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     fmt::Display,
     num::NonZeroUsize,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
 };
 
 use async_trait::async_trait;
 use backoff::BackoffConfig;
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
 use object_store::DynObjectStore;
 use observability_deps::tracing::warn;
 use parquet_file::ParquetFilePath;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use trace::{ctx::SpanContext, span::SpanRecorder};
 use uuid::Uuid;
 
 use super::{
-    util::{copy_files, delete_files},
-    Scratchpad, ScratchpadGen,
+    breaker::ScratchpadBreaker,
+    pool::ScratchpadPool,
+    util::{copy_files, delete_files, existing_files, file_sizes},
+    Scratchpad, ScratchpadCapacityPolicy, ScratchpadCleanSummary, ScratchpadError, ScratchpadGen,
+    ScratchpadRetryPolicy, ScratchpadStats,
 };
 
+/// Number of times a file is copied to the output store before giving up on copies with the
+/// wrong size.
+const MAX_PUBLISH_ATTEMPTS: usize = 3;
+
+/// Number of times deleting a file from the scratchpad is tried before the file is leaked.
+const MAX_DELETE_ATTEMPTS: usize = 3;
+
 #[derive(Debug)]
 pub struct ProdScratchpadGen {
     concurrency: NonZeroUsize,
@@ -25,6 +45,13 @@ pub struct ProdScratchpadGen {
     store_input: Arc<DynObjectStore>,
     store_scratchpad: Arc<DynObjectStore>,
     store_output: Arc<DynObjectStore>,
+    pool: Option<Arc<ScratchpadPool>>,
+    capacity_policy: ScratchpadCapacityPolicy,
+    transfer_permits: Option<Arc<Semaphore>>,
+    keep_written: bool,
+    read_through_bytes: Option<usize>,
+    retry_policy: ScratchpadRetryPolicy,
+    breaker: Option<Arc<ScratchpadBreaker>>,
 }
 
 impl ProdScratchpadGen {
@@ -36,6 +63,10 @@ impl ProdScratchpadGen {
         store_scratchpad: Arc<DynObjectStore>,
         store_output: Arc<DynObjectStore>,
     ) -> Self {
+        let retry_policy = ScratchpadRetryPolicy {
+            max_attempts: None,
+            backoff_config: backoff_config.clone(),
+        };
         Self {
             shadow_mode,
             concurrency,
@@ -43,20 +74,91 @@ impl ProdScratchpadGen {
             store_input,
             store_scratchpad,
             store_output,
+            pool: None,
+            capacity_policy: ScratchpadCapacityPolicy::default(),
+            transfer_permits: None,
+            keep_written: false,
+            read_through_bytes: None,
+            retry_policy,
+            breaker: None,
         }
     }
-}
 
-impl Display for ProdScratchpadGen {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "prod")
+    /// Retry copying each file into or out of the scratchpads according to `retry_policy`.
+    ///
+    /// By default, copies are retried forever with the backoff of the generator.
+    pub fn with_retry_policy(mut self, retry_policy: ScratchpadRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
     }
-}
 
-/// ScratchpadGen is the factory pattern; it creates Scratchpads
-impl ScratchpadGen for ProdScratchpadGen {
-    fn pad(&self) -> Arc<dyn Scratchpad> {
-        Arc::new(ProdScratchpad {
+    /// Keep the files written by compaction in the scratchpads, so that the next round of the
+    /// partition uses them without downloading them again.
+    ///
+    /// Files are then only evicted once [`Scratchpad::retain_only`] says they won't be used
+    /// anymore, or when they are compacted.
+    pub fn with_keep_written(mut self, keep_written: bool) -> Self {
+        self.keep_written = keep_written;
+        self
+    }
+
+    /// Read the smallest files of each load directly from the input store, as long as they total
+    /// less than `threshold_bytes`, instead of copying them to the scratchpad.
+    ///
+    /// These files keep their IDs, so the store the compaction reads the scratchpad from must fall
+    /// back to the input store, see [`ReadThroughStore`](crate::object_store::read_through::ReadThroughStore).
+    pub fn with_read_through(mut self, threshold_bytes: usize) -> Self {
+        self.read_through_bytes = Some(threshold_bytes);
+        self
+    }
+
+    /// Bypass the scratchpad store while it is failing.
+    ///
+    /// Once the breaker opens, the scratchpads created during its cool-down read all the files they
+    /// load through, like [`with_read_through`](Self::with_read_through). Files written to the
+    /// output store directly, see
+    /// [`WriteThroughStore`](crate::object_store::write_through::WriteThroughStore), are already
+    /// public. Staging is then tried again.
+    ///
+    /// Only failures of the scratchpad store count, so the retry policy must give up after a
+    /// finite number of attempts for the breaker to ever open.
+    pub fn with_breaker(mut self, breaker: Arc<ScratchpadBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Limit the number of files copied into or out of all the scratchpads at once.
+    ///
+    /// `concurrency` only limits the copies of a single load or publication.
+    pub fn with_max_concurrent_transfers(mut self, max_concurrent_transfers: NonZeroUsize) -> Self {
+        self.transfer_permits = Some(Arc::new(Semaphore::new(max_concurrent_transfers.get())));
+        self
+    }
+
+    /// Limit the bytes all the scratchpads together stage from the input store.
+    ///
+    /// A load that would exceed the capacity waits for files to be cleaned by any scratchpad, or
+    /// fails, depending on the policy. Waiting loads proceed in the order they started. A load
+    /// needing more than the whole capacity always fails.
+    ///
+    /// Prefetched files would not be accounted for, so [`Scratchpad::prefetch`] does nothing once
+    /// a capacity is set.
+    pub fn with_capacity(
+        mut self,
+        capacity_bytes: usize,
+        capacity_policy: ScratchpadCapacityPolicy,
+    ) -> Self {
+        self.pool = Some(Arc::new(ScratchpadPool::new(capacity_bytes)));
+        self.capacity_policy = capacity_policy;
+        self
+    }
+
+    fn prod_pad(&self, span_ctx: Option<SpanContext>) -> ProdScratchpad {
+        let pass_through = self
+            .breaker
+            .as_ref()
+            .map_or(false, |breaker| breaker.is_open());
+        ProdScratchpad {
             shadow_mode: self.shadow_mode,
             concurrency: self.concurrency,
             backoff_config: self.backoff_config.clone(),
@@ -64,11 +166,58 @@ impl ScratchpadGen for ProdScratchpadGen {
             store_scratchpad: Arc::clone(&self.store_scratchpad),
             store_output: Arc::clone(&self.store_output),
             mask: Uuid::new_v4(),
+            span_ctx,
             files_unmasked: RwLock::new(HashMap::default()),
-        })
+            pool: self.pool.clone(),
+            capacity_policy: self.capacity_policy,
+            transfer_permits: self.transfer_permits.clone(),
+            keep_written: self.keep_written,
+            read_through_bytes: self.read_through_bytes,
+            read_through: Mutex::new(HashSet::default()),
+            written: Mutex::new(HashSet::default()),
+            retry_policy: self.retry_policy.clone(),
+            breaker: self.breaker.clone(),
+            pass_through,
+            prefetched: Mutex::new(HashMap::default()),
+            staged: Mutex::new(Staged::default()),
+            files_loaded: AtomicUsize::new(0),
+            bytes_loaded: AtomicUsize::new(0),
+            files_made_public: AtomicUsize::new(0),
+            bytes_made_public: AtomicUsize::new(0),
+            transfer_retries: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl Display for ProdScratchpadGen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "prod")?;
+        if let Some(pool) = &self.pool {
+            write!(
+                f,
+                "(capacity_bytes={}, policy={:?})",
+                pool.capacity_bytes(),
+                self.capacity_policy
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// ScratchpadGen is the factory pattern; it creates Scratchpads
+impl ScratchpadGen for ProdScratchpadGen {
+    fn pad(&self, span_ctx: Option<SpanContext>) -> Arc<dyn Scratchpad> {
+        Arc::new(self.prod_pad(span_ctx))
     }
 }
 
+/// Files loaded from the input store, and their sizes.
+#[derive(Debug, Default)]
+struct Staged {
+    bytes: usize,
+    files: HashMap<ParquetFilePath, usize>,
+}
+
 struct ProdScratchpad {
     shadow_mode: bool,
     concurrency: NonZeroUsize,
@@ -78,11 +227,71 @@ struct ProdScratchpad {
     store_output: Arc<DynObjectStore>,
     mask: Uuid,
 
+    /// Parent of the spans of the transfers, if traced.
+    span_ctx: Option<SpanContext>,
+
     /// Set of known, unmasked file.
     ///
     /// If the file is part of this map, it is in the scratchpad. If the boolean key is set, it was already copied to
     /// the output store
     files_unmasked: RwLock<HashMap<ParquetFilePath, bool>>,
+
+    /// Shared by all scratchpads of the generator.
+    pool: Option<Arc<ScratchpadPool>>,
+    capacity_policy: ScratchpadCapacityPolicy,
+
+    /// Shared by all scratchpads of the generator.
+    transfer_permits: Option<Arc<Semaphore>>,
+
+    /// Keep written files for the next round.
+    keep_written: bool,
+
+    read_through_bytes: Option<usize>,
+
+    /// Files read directly from the input store, i.e. not in the scratchpad. They keep their IDs.
+    ///
+    /// Always locked after `files_unmasked`.
+    read_through: Mutex<HashSet<ParquetFilePath>>,
+
+    /// Files written to the scratchpad by the compaction, see [`Scratchpad::record_written`].
+    ///
+    /// Always locked after `read_through`.
+    written: Mutex<HashSet<ParquetFilePath>>,
+
+    retry_policy: ScratchpadRetryPolicy,
+
+    /// Shared by all scratchpads of the generator.
+    breaker: Option<Arc<ScratchpadBreaker>>,
+
+    /// Read all files through, and take the files written by the compaction from the output
+    /// store, because the breaker was open when the scratchpad was created.
+    pass_through: bool,
+
+    /// Files copied to the scratchpad in the background, and not loaded yet.
+    ///
+    /// Always locked after `read_through`.
+    prefetched: Mutex<HashMap<ParquetFilePath, Prefetch>>,
+
+    /// Files loaded from the input store and not cleaned yet.
+    ///
+    /// Always locked after `files_unmasked`. With a capacity, files are accounted for (and their
+    /// bytes allocated from the pool) before they are loaded, otherwise once they are.
+    staged: Mutex<Staged>,
+
+    files_loaded: AtomicUsize,
+    bytes_loaded: AtomicUsize,
+    files_made_public: AtomicUsize,
+    bytes_made_public: AtomicUsize,
+
+    /// Shared with the prefetch tasks.
+    transfer_retries: Arc<AtomicUsize>,
+}
+
+/// A file copied to the scratchpad in the background.
+struct Prefetch {
+    /// Size of the copy once the copy task is finished, or `None` if it failed or was cancelled.
+    done: Shared<BoxFuture<'static, Option<usize>>>,
+    cancel: CancellationToken,
 }
 
 impl std::fmt::Debug for ProdScratchpad {
@@ -95,7 +304,20 @@ impl std::fmt::Debug for ProdScratchpad {
             .field("store_scratchpad", &self.store_scratchpad)
             .field("store_output", &self.store_output)
             .field("mask", &self.mask)
+            .field("span_ctx", &self.span_ctx)
             .field("files_unmasked", &ref_files_unmasked)
+            .field("pool", &self.pool)
+            .field("capacity_policy", &self.capacity_policy)
+            .field("transfer_permits", &self.transfer_permits)
+            .field("keep_written", &self.keep_written)
+            .field("read_through_bytes", &self.read_through_bytes)
+            .field("read_through", &self.read_through)
+            .field("written", &self.written)
+            .field("retry_policy", &self.retry_policy)
+            .field("breaker", &self.breaker)
+            .field("pass_through", &self.pass_through)
+            .field("prefetched", &self.prefetched.lock().unwrap().keys())
+            .field("staged_bytes", &self.staged_bytes())
             .finish()
     }
 }
@@ -116,6 +338,68 @@ impl ProdScratchpad {
         Uuid::from_u128(a.as_u128() ^ b.as_u128())
     }
 
+    /// Bytes currently staged from the input store.
+    fn staged_bytes(&self) -> usize {
+        self.staged.lock().unwrap().bytes
+    }
+
+    /// Span of a transfer, child of the span context of the scratchpad.
+    fn span(&self, name: &'static str) -> SpanRecorder {
+        SpanRecorder::new(self.span_ctx.as_ref().map(|ctx| ctx.child(name)))
+    }
+
+    /// Record the number of files and bytes transferred, and the outcome, on the span.
+    fn record<T>(
+        mut span: SpanRecorder,
+        files: usize,
+        bytes: usize,
+        res: Result<T, ScratchpadError>,
+    ) -> Result<T, ScratchpadError> {
+        span.set_metadata("files", files.to_string());
+        span.set_metadata("bytes", bytes.to_string());
+        match &res {
+            Ok(_) => span.ok("done"),
+            Err(e) => span.error(e.to_string()),
+        }
+        res
+    }
+
+    /// Tell the breaker, if any, whether a transfer from (`scratchpad_is_source`) or to the
+    /// scratchpad store failed.
+    ///
+    /// Failures of the other store of the transfer are not recorded, since bypassing the scratchpad
+    /// store wouldn't avoid them.
+    fn record_transfer<T>(
+        breaker: Option<&ScratchpadBreaker>,
+        res: &Result<T, ScratchpadError>,
+        scratchpad_is_source: bool,
+    ) {
+        match (breaker, res) {
+            (Some(breaker), Ok(_)) => breaker.record_success(),
+            (Some(breaker), Err(ScratchpadError::TransferFailed { source_failed, .. }))
+                if *source_failed == scratchpad_is_source =>
+            {
+                breaker.record_failure()
+            }
+            _ => {}
+        }
+    }
+
+    /// Record the files and bytes removed, and the outcome, on the span.
+    fn record_clean(
+        mut span: SpanRecorder,
+        summary: ScratchpadCleanSummary,
+    ) -> ScratchpadCleanSummary {
+        span.set_metadata("files", summary.files.to_string());
+        span.set_metadata("bytes", summary.bytes.to_string());
+        if summary.leaked.is_empty() {
+            span.ok("done");
+        } else {
+            span.error(format!("leaked {} files", summary.leaked.len()));
+        }
+        summary
+    }
+
     fn check_known(
         &self,
         files_unmasked: &[ParquetFilePath],
@@ -123,7 +407,20 @@ impl ProdScratchpad {
         output: bool,
     ) -> (Vec<ParquetFilePath>, Vec<ParquetFilePath>) {
         let mut ref_files_unmasked = self.files_unmasked.write().unwrap();
+        Self::check_known_locked(
+            &mut ref_files_unmasked,
+            files_unmasked,
+            files_masked,
+            output,
+        )
+    }
 
+    fn check_known_locked(
+        ref_files_unmasked: &mut HashMap<ParquetFilePath, bool>,
+        files_unmasked: &[ParquetFilePath],
+        files_masked: &[ParquetFilePath],
+        output: bool,
+    ) -> (Vec<ParquetFilePath>, Vec<ParquetFilePath>) {
         files_unmasked
             .iter()
             .zip(files_masked)
@@ -143,6 +440,321 @@ impl ProdScratchpad {
             .map(|(un, masked)| (un.clone(), masked.clone()))
             .unzip()
     }
+
+    /// Same as `check_known` for files to load, but also allocates capacity from the pool for the
+    /// files that are not in the scratchpad yet.
+    ///
+    /// The capacity is allocated for all these files at once, or for none of them.
+    async fn reserve(
+        &self,
+        files_unmasked: &[ParquetFilePath],
+        files_masked: &[ParquetFilePath],
+        pool: &ScratchpadPool,
+        mut sizes: HashMap<ParquetFilePath, usize>,
+    ) -> Result<(Vec<ParquetFilePath>, Vec<ParquetFilePath>), ScratchpadError> {
+        loop {
+            let requested_bytes = match self.requested_bytes(files_unmasked, &sizes) {
+                Ok(requested_bytes) => requested_bytes,
+                Err(missing_sizes) => {
+                    let new_sizes = file_sizes(
+                        &missing_sizes,
+                        Arc::clone(&self.store_input),
+                        &self.backoff_config,
+                        self.concurrency,
+                    )
+                    .await?;
+                    sizes.extend(missing_sizes.into_iter().zip(new_sizes));
+                    continue;
+                }
+            };
+
+            match self.capacity_policy {
+                ScratchpadCapacityPolicy::Block => pool.allocate(requested_bytes).await?,
+                ScratchpadCapacityPolicy::Error => pool.try_allocate(requested_bytes)?,
+            }
+
+            // other loads of this scratchpad may have staged or cleaned files in the meantime
+            {
+                let mut ref_files_unmasked = self.files_unmasked.write().unwrap();
+                let mut staged = self.staged.lock().unwrap();
+
+                let new_files = Self::new_files(&ref_files_unmasked, files_unmasked);
+                let new_bytes = new_files
+                    .iter()
+                    .map(|f| sizes.get(*f).copied())
+                    .sum::<Option<usize>>();
+                match new_bytes {
+                    Some(new_bytes) if new_bytes <= requested_bytes => {
+                        pool.release(requested_bytes - new_bytes);
+                        staged.bytes += new_bytes;
+                        staged
+                            .files
+                            .extend(new_files.into_iter().map(|f| (f.clone(), sizes[f])));
+                        return Ok(Self::check_known_locked(
+                            &mut ref_files_unmasked,
+                            files_unmasked,
+                            files_masked,
+                            false,
+                        ));
+                    }
+                    _ => pool.release(requested_bytes),
+                }
+            }
+        }
+    }
+
+    /// Bytes needed to stage the files that are not in the scratchpad yet, or the files whose size
+    /// is missing.
+    fn requested_bytes(
+        &self,
+        files_unmasked: &[ParquetFilePath],
+        sizes: &HashMap<ParquetFilePath, usize>,
+    ) -> Result<usize, Vec<ParquetFilePath>> {
+        let ref_files_unmasked = self.files_unmasked.read().unwrap();
+        let new_files = Self::new_files(&ref_files_unmasked, files_unmasked);
+
+        let missing_sizes = new_files
+            .iter()
+            .filter(|f| !sizes.contains_key(**f))
+            .map(|f| (*f).clone())
+            .collect::<Vec<_>>();
+        if missing_sizes.is_empty() {
+            Ok(new_files.iter().map(|f| sizes[*f]).sum())
+        } else {
+            Err(missing_sizes)
+        }
+    }
+
+    fn new_files<'a>(
+        ref_files_unmasked: &HashMap<ParquetFilePath, bool>,
+        files_unmasked: &'a [ParquetFilePath],
+    ) -> HashSet<&'a ParquetFilePath> {
+        files_unmasked
+            .iter()
+            .filter(|f| !ref_files_unmasked.contains_key(*f))
+            .collect()
+    }
+
+    /// IDs of the files in the scratchpad, i.e. masked unless read through.
+    fn pad_uuids(&self, files: &[ParquetFilePath]) -> Vec<Uuid> {
+        let read_through = self.read_through.lock().unwrap();
+        files
+            .iter()
+            .map(|f| {
+                if read_through.contains(f) {
+                    f.objest_store_id()
+                } else {
+                    Self::xor_uuids(f.objest_store_id(), self.mask)
+                }
+            })
+            .collect()
+    }
+
+    /// Fail with [`ScratchpadError::UnknownFiles`] listing the `unknown` files, if any.
+    fn check_unknown<'a>(
+        unknown: impl Iterator<Item = &'a ParquetFilePath>,
+    ) -> Result<(), ScratchpadError> {
+        let files: Vec<_> = unknown.map(|f| f.object_store_path().to_string()).collect();
+        if files.is_empty() {
+            Ok(())
+        } else {
+            Err(ScratchpadError::UnknownFiles { files })
+        }
+    }
+
+    /// Read through the smallest files that are not in the scratchpad yet, as long as they total
+    /// less than `threshold_bytes`.
+    ///
+    /// Returns the files left to load, and the sizes of the files not in the scratchpad.
+    async fn read_through(
+        &self,
+        files: &[ParquetFilePath],
+        threshold_bytes: usize,
+    ) -> Result<(Vec<ParquetFilePath>, HashMap<ParquetFilePath, usize>), ScratchpadError> {
+        let new_files = {
+            let ref_files_unmasked = self.files_unmasked.read().unwrap();
+            let read_through = self.read_through.lock().unwrap();
+            let prefetched = self.prefetched.lock().unwrap();
+            files
+                .iter()
+                .filter(|f| {
+                    !ref_files_unmasked.contains_key(*f)
+                        && !read_through.contains(*f)
+                        && !prefetched.contains_key(*f)
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let sizes = file_sizes(
+            &new_files,
+            Arc::clone(&self.store_input),
+            &self.backoff_config,
+            self.concurrency,
+        )
+        .await?;
+
+        let mut by_size = new_files.iter().zip(&sizes).collect::<Vec<_>>();
+        by_size.sort_by_key(|(_f, size)| **size);
+        let mut total_bytes = 0;
+        let selected = by_size.into_iter().take_while(|(_f, size)| {
+            total_bytes += **size;
+            total_bytes < threshold_bytes
+        });
+
+        let files_left = {
+            let ref_files_unmasked = self.files_unmasked.read().unwrap();
+            let mut read_through = self.read_through.lock().unwrap();
+            // skip files staged by another load in the meantime
+            read_through.extend(
+                selected
+                    .filter(|(f, _size)| !ref_files_unmasked.contains_key(*f))
+                    .map(|(f, _size)| f.clone()),
+            );
+            files
+                .iter()
+                .filter(|f| !read_through.contains(*f))
+                .cloned()
+                .collect()
+        };
+
+        Ok((files_left, new_files.into_iter().zip(sizes).collect()))
+    }
+
+    /// Wait for the prefetches of `files`, returning the sizes of the files prefetched.
+    async fn join_prefetches(&self, files: &[ParquetFilePath]) -> HashMap<ParquetFilePath, usize> {
+        let prefetches = {
+            let mut prefetched = self.prefetched.lock().unwrap();
+            files
+                .iter()
+                .filter_map(|f| {
+                    prefetched
+                        .remove(f)
+                        .map(|prefetch| (f.clone(), prefetch.done))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut sizes = HashMap::with_capacity(prefetches.len());
+        for (f, done) in prefetches {
+            if let Some(size) = done.await {
+                sizes.insert(f, size);
+            }
+        }
+        sizes
+    }
+
+    /// Cancel all prefetches and delete their copies.
+    async fn cancel_prefetches(&self) -> ScratchpadCleanSummary {
+        let prefetched = std::mem::take(&mut *self.prefetched.lock().unwrap());
+        if prefetched.is_empty() {
+            return ScratchpadCleanSummary::default();
+        }
+
+        // the copies may only be deleted once the copy tasks are finished
+        for prefetch in prefetched.values() {
+            prefetch.cancel.cancel();
+        }
+        let (files, done): (Vec<_>, Vec<_>) = prefetched
+            .into_iter()
+            .map(|(f, prefetch)| (f, prefetch.done))
+            .unzip();
+        let sizes = futures::future::join_all(done).await;
+
+        // cancelled copies may have been written already, so delete them all
+        let (files_masked, _uuids) = self.apply_mask(&files);
+        let leaked = delete_files(
+            &files_masked,
+            Arc::clone(&self.store_scratchpad),
+            &self.backoff_config,
+            self.concurrency,
+            MAX_DELETE_ATTEMPTS,
+        )
+        .await;
+
+        let mut summary = ScratchpadCleanSummary::default();
+        for (f, size) in files_masked.iter().zip(sizes) {
+            if leaked.contains(f) {
+                summary.leaked.push(f.object_store_path().to_string());
+            } else if let Some(size) = size {
+                summary.files += 1;
+                summary.bytes += size;
+            }
+        }
+        summary
+    }
+
+    /// Remove files from the scratchpad, recording the files removed and the bytes released
+    /// on the span.
+    async fn delete(
+        &self,
+        files: &[ParquetFilePath],
+        span: SpanRecorder,
+    ) -> ScratchpadCleanSummary {
+        let files_masked: Vec<ParquetFilePath>;
+        let _uuid: Vec<Uuid>;
+        let mut sizes = vec![];
+
+        // files read through are not in the scratchpad, so there is nothing to delete: the
+        // originals in the input store must never be touched.
+        {
+            let mut read_through = self.read_through.lock().unwrap();
+            for f in files {
+                read_through.remove(f);
+            }
+        }
+
+        // scope the files_unmasked lock to protect manipulation of the scratchpad's state, but release it
+        // before doing the async delete of files removed from the scratchpad.
+        {
+            let mut ref_files_unmasked = self.files_unmasked.write().unwrap();
+            let mut staged = self.staged.lock().unwrap();
+
+            let files = files
+                .iter()
+                .filter(|f| ref_files_unmasked.remove(f).is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            for f in &files {
+                let bytes = staged.files.remove(f).unwrap_or_default();
+                staged.bytes -= bytes;
+                sizes.push(bytes);
+            }
+            (files_masked, _uuid) = self.apply_mask(&files);
+
+            let mut written = self.written.lock().unwrap();
+            for f in &files_masked {
+                written.remove(f);
+            }
+        }
+        let released_bytes = sizes.iter().sum();
+
+        // the files are no longer tracked, so their capacity is freed right away (and regardless of
+        // delete failures, which leak the files), which also wakes up loads waiting for capacity.
+        if let Some(pool) = &self.pool {
+            pool.release(released_bytes);
+        }
+
+        let leaked = delete_files(
+            &files_masked,
+            Arc::clone(&self.store_scratchpad),
+            &self.backoff_config,
+            self.concurrency,
+            MAX_DELETE_ATTEMPTS,
+        )
+        .await;
+
+        // leaked files still take space in the scratchpad store
+        let mut summary = ScratchpadCleanSummary::default();
+        for (f, bytes) in files_masked.iter().zip(sizes) {
+            if leaked.contains(f) {
+                summary.leaked.push(f.object_store_path().to_string());
+            } else {
+                summary.files += 1;
+                summary.bytes += bytes;
+            }
+        }
+        Self::record_clean(span, summary)
+    }
 }
 
 impl Drop for ProdScratchpad {
@@ -160,17 +772,60 @@ impl Drop for ProdScratchpad {
                 .map(|(k, _in_out)| k)
                 .collect::<Vec<_>>();
             let (files_masked, _uuids) = self.apply_mask(&files);
+            let staged = std::mem::take(&mut *self.staged.lock().unwrap());
+            if let Some(pool) = &self.pool {
+                pool.release(staged.bytes);
+            }
             let store_scratchpad = Arc::clone(&self.store_scratchpad);
             let concurrency = self.concurrency;
             let backoff_config = self.backoff_config.clone();
             tokio::spawn(async move {
-                delete_files(
+                let leaked = delete_files(
                     &files_masked,
                     Arc::clone(&store_scratchpad),
                     &backoff_config,
                     concurrency,
+                    MAX_DELETE_ATTEMPTS,
+                )
+                .await;
+                if !leaked.is_empty() {
+                    warn!(
+                        leaked = leaked.len(),
+                        "failed to delete files from dropped scratchpad context",
+                    );
+                }
+            });
+        }
+
+        let prefetched = std::mem::take(&mut *self.prefetched.lock().unwrap());
+        if !prefetched.is_empty() {
+            for prefetch in prefetched.values() {
+                prefetch.cancel.cancel();
+            }
+            let (files, done): (Vec<_>, Vec<_>) = prefetched
+                .into_iter()
+                .map(|(f, prefetch)| (f, prefetch.done))
+                .unzip();
+            let (files_masked, _uuids) = self.apply_mask(&files);
+            let store_scratchpad = Arc::clone(&self.store_scratchpad);
+            let concurrency = self.concurrency;
+            let backoff_config = self.backoff_config.clone();
+            tokio::spawn(async move {
+                futures::future::join_all(done).await;
+                let leaked = delete_files(
+                    &files_masked,
+                    store_scratchpad,
+                    &backoff_config,
+                    concurrency,
+                    MAX_DELETE_ATTEMPTS,
                 )
                 .await;
+                if !leaked.is_empty() {
+                    warn!(
+                        leaked = leaked.len(),
+                        "failed to delete prefetched files from dropped scratchpad context",
+                    );
+                }
             });
         }
     }
@@ -178,104 +833,400 @@ impl Drop for ProdScratchpad {
 
 #[async_trait]
 impl Scratchpad for ProdScratchpad {
-    fn uuids(&self, files: &[ParquetFilePath]) -> Vec<Uuid> {
-        let (_, uuids) = self.apply_mask(files);
-        uuids
+    fn uuids(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError> {
+        {
+            let ref_files_unmasked = self.files_unmasked.read().unwrap();
+            let read_through = self.read_through.lock().unwrap();
+            Self::check_unknown(
+                files
+                    .iter()
+                    .filter(|f| !ref_files_unmasked.contains_key(*f) && !read_through.contains(*f)),
+            )?;
+        }
+        Ok(self.pad_uuids(files))
     }
 
-    async fn load_to_scratchpad(&self, files: &[ParquetFilePath]) -> Vec<Uuid> {
-        let (files_to, uuids) = self.apply_mask(files);
-        let (files_from, files_to) = self.check_known(files, &files_to, false);
-        copy_files(
-            &files_from,
-            &files_to,
+    async fn load_to_scratchpad(
+        &self,
+        files_all: &[ParquetFilePath],
+    ) -> Result<Vec<Uuid>, ScratchpadError> {
+        let span = self.span("scratchpad_load");
+        let read_through_bytes = if self.pass_through {
+            Some(usize::MAX)
+        } else {
+            self.read_through_bytes
+        };
+        let read_through = match read_through_bytes {
+            Some(threshold_bytes) => self.read_through(files_all, threshold_bytes).await,
+            None => Ok((files_all.to_vec(), HashMap::new())),
+        };
+        let (files, sizes) = match read_through {
+            Ok(read_through) => read_through,
+            Err(e) => return Self::record(span, 0, 0, Err(e)),
+        };
+        let (files_to, _uuids) = self.apply_mask(&files);
+        let reserved = match &self.pool {
+            Some(pool) => self.reserve(&files, &files_to, pool, sizes).await,
+            None => Ok(self.check_known(&files, &files_to, false)),
+        };
+        let (files_from, files_to) = match reserved {
+            Ok(reserved) => reserved,
+            Err(e) => return Self::record(span, 0, 0, Err(e)),
+        };
+        let mut prefetched_sizes = self.join_prefetches(&files_from).await;
+        let (copy_from, copy_to): (Vec<_>, Vec<_>) = files_from
+            .iter()
+            .zip(&files_to)
+            .filter(|(f, _f_masked)| !prefetched_sizes.contains_key(*f))
+            .map(|(f, f_masked)| (f.clone(), f_masked.clone()))
+            .unzip();
+        let copied = copy_files(
+            &copy_from,
+            &copy_to,
             Arc::clone(&self.store_input),
             Arc::clone(&self.store_scratchpad),
-            &self.backoff_config,
+            &self.retry_policy,
+            &self.transfer_retries,
             self.concurrency,
+            self.transfer_permits.clone(),
+            None,
         )
         .await;
-        uuids
+        if !copy_from.is_empty() {
+            Self::record_transfer(self.breaker.as_deref(), &copied, false);
+        }
+        let sizes = match copied {
+            Ok(sizes) => {
+                prefetched_sizes.extend(copy_from.into_iter().zip(sizes));
+                files_from
+                    .iter()
+                    .map(|f| prefetched_sizes[f])
+                    .collect::<Vec<_>>()
+            }
+            Err(e) => {
+                // forget the files, so that a later load copies them again
+                let summary = self
+                    .delete(&files_from, self.span("scratchpad_clean_files"))
+                    .await;
+                if !summary.leaked.is_empty() {
+                    warn!(
+                        leaked=?summary.leaked,
+                        "failed to clean the files of a failed load"
+                    );
+                }
+                return Self::record(span, files_from.len(), 0, Err(e));
+            }
+        };
+
+        if self.pool.is_none() {
+            let ref_files_unmasked = self.files_unmasked.read().unwrap();
+            let mut staged = self.staged.lock().unwrap();
+            for (f, size) in files_from.iter().zip(&sizes) {
+                // skip files cleaned while they were copied
+                if ref_files_unmasked.contains_key(f) && !staged.files.contains_key(f) {
+                    staged.files.insert(f.clone(), *size);
+                    staged.bytes += size;
+                }
+            }
+        }
+        let bytes = sizes.iter().sum();
+        self.files_loaded
+            .fetch_add(files_from.len(), Ordering::Relaxed);
+        self.bytes_loaded.fetch_add(bytes, Ordering::Relaxed);
+
+        Self::record(span, files_from.len(), bytes, Ok(self.pad_uuids(files_all)))
+    }
+
+    fn record_written(&self, files: &[ParquetFilePath]) {
+        self.written.lock().unwrap().extend(files.iter().cloned());
     }
 
-    async fn make_public(&self, files: &[ParquetFilePath]) -> Vec<Uuid> {
-        let (files_to, uuids) = self.apply_mask(files);
+    async fn make_public(&self, files: &[ParquetFilePath]) -> Result<Vec<Uuid>, ScratchpadError> {
+        let span = self.span("scratchpad_make_public");
+
+        // files read through are already public
+        let files_all = files;
+        let files: Vec<_> = {
+            let read_through = self.read_through.lock().unwrap();
+            files
+                .iter()
+                .filter(|f| !read_through.contains(*f))
+                .cloned()
+                .collect()
+        };
+
+        // publishing files that are not in the scratchpad would make up their entries
+        let (files_public, _uuids) = self.apply_mask(&files);
+        let (checked, written) = {
+            let ref_files_unmasked = self.files_unmasked.read().unwrap();
+            let written = self.written.lock().unwrap();
+            let checked =
+                Self::check_unknown(files.iter().zip(&files_public).filter_map(|(f, f_public)| {
+                    (!written.contains(f) && !ref_files_unmasked.contains_key(f_public))
+                        .then_some(f)
+                }));
+            let written = files
+                .iter()
+                .filter(|f| written.contains(*f))
+                .cloned()
+                .collect::<Vec<_>>();
+            (checked, written)
+        };
+        if let Err(e) = checked {
+            return Self::record(span, 0, 0, Err(e));
+        }
+
+        // while the breaker is open, the compaction writes its files to the output store
+        // directly, so they are already public
+        let files = if self.pass_through && !written.is_empty() {
+            let written_through = match existing_files(
+                &written,
+                Arc::clone(&self.store_output),
+                &self.retry_policy,
+                &self.transfer_retries,
+                self.concurrency,
+            )
+            .await
+            {
+                Ok(written_through) => written_through,
+                Err(e) => return Self::record(span, 0, 0, Err(e)),
+            };
+            let mut read_through = self.read_through.lock().unwrap();
+            read_through.extend(written_through);
+            files
+                .into_iter()
+                .filter(|f| !read_through.contains(f))
+                .collect::<Vec<_>>()
+        } else {
+            files
+        };
+        let uuids = self.pad_uuids(files_all);
+        let (files_to, _uuids) = self.apply_mask(&files);
 
         // only keep files that we did not know about, all others we've already synced it between the two stores
-        let (files_to, files_from) = self.check_known(&files_to, files, true);
+        let (files_to, files_from) = self.check_known(&files_to, &files, true);
 
-        copy_files(
+        // in shadow mode, the output store ignores writes so there is nothing to verify
+        let verify_attempts = (!self.shadow_mode).then_some(MAX_PUBLISH_ATTEMPTS);
+        let copied = copy_files(
             &files_from,
             &files_to,
             Arc::clone(&self.store_scratchpad),
             Arc::clone(&self.store_output),
-            &self.backoff_config,
+            &self.retry_policy,
+            &self.transfer_retries,
             self.concurrency,
+            self.transfer_permits.clone(),
+            verify_attempts,
         )
         .await;
-        uuids
+        if !files_from.is_empty() {
+            Self::record_transfer(self.breaker.as_deref(), &copied, true);
+        }
+        let sizes = match copied {
+            Ok(sizes) => sizes,
+            Err(e) => {
+                // the files are not public, so a later call must copy them again
+                let mut ref_files_unmasked = self.files_unmasked.write().unwrap();
+                for f in &files_to {
+                    if let Some(output) = ref_files_unmasked.get_mut(f) {
+                        *output = false;
+                    }
+                }
+                return Self::record(span, files_from.len(), 0, Err(e));
+            }
+        };
+        let bytes = sizes.iter().sum();
+        self.files_made_public
+            .fetch_add(files_from.len(), Ordering::Relaxed);
+        self.bytes_made_public.fetch_add(bytes, Ordering::Relaxed);
+
+        Self::record(span, files_from.len(), bytes, Ok(uuids))
+    }
+
+    fn prefetch(&self, files: &[ParquetFilePath]) {
+        // prefetched files are not accounted for, so they could exceed the capacity. Scratchpads
+        // passing through don't copy files at all.
+        if self.pool.is_some() || self.pass_through {
+            return;
+        }
+
+        let ref_files_unmasked = self.files_unmasked.read().unwrap();
+        let read_through = self.read_through.lock().unwrap();
+        let mut prefetched = self.prefetched.lock().unwrap();
+
+        // limit the copies of this call like a load
+        let permits = Arc::new(Semaphore::new(self.concurrency.get()));
+        let cancel = CancellationToken::new();
+        for f in files {
+            if ref_files_unmasked.contains_key(f)
+                || read_through.contains(f)
+                || prefetched.contains_key(f)
+            {
+                continue;
+            }
+
+            let files_from = vec![f.clone()];
+            let (files_to, _uuids) = self.apply_mask(&files_from);
+            let store_input = Arc::clone(&self.store_input);
+            let store_scratchpad = Arc::clone(&self.store_scratchpad);
+            let retry_policy = self.retry_policy.clone();
+            let transfer_retries = Arc::clone(&self.transfer_retries);
+            let transfer_permits = self.transfer_permits.clone();
+            let breaker = self.breaker.clone();
+            let permits = Arc::clone(&permits);
+            let task_cancel = cancel.clone();
+            let task = tokio::spawn(async move {
+                let copy = async {
+                    let _permit = permits.acquire().await.expect("semaphore not closed");
+                    copy_files(
+                        &files_from,
+                        &files_to,
+                        store_input,
+                        store_scratchpad,
+                        &retry_policy,
+                        &transfer_retries,
+                        NonZeroUsize::new(1).unwrap(),
+                        transfer_permits,
+                        None,
+                    )
+                    .await
+                };
+                tokio::select! {
+                    _ = task_cancel.cancelled() => None,
+                    res = copy => {
+                        Self::record_transfer(breaker.as_deref(), &res, false);
+                        res.ok().map(|sizes| sizes[0])
+                    }
+                }
+            });
+
+            prefetched.insert(
+                f.clone(),
+                Prefetch {
+                    done: async move { task.await.ok().flatten() }.boxed().shared(),
+                    cancel: cancel.clone(),
+                },
+            );
+        }
     }
 
     // clean_from_scratchpad selectively removes some files from the scratchpad.
     // This should be called after uploading files to objectstore.
     // Cleaning should be done regularly, so the scratchpad doesn't get too big.
-    async fn clean_from_scratchpad(&self, files: &[ParquetFilePath]) {
-        let files_masked: Vec<ParquetFilePath>;
-        let _uuid: Vec<Uuid>;
+    async fn clean_from_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        Ok(self
+            .delete(files, self.span("scratchpad_clean_files"))
+            .await)
+    }
 
-        // scope the files_unmasked lock to protect manipulation of the scratchpad's state, but release it
-        // before doing the async delete of files removed from the scratchpad.
-        {
-            let mut ref_files_unmasked = self.files_unmasked.write().unwrap();
+    // clean_written_from_scratchpad is the same as clean_from_scratchpad, but it does not remove files
+    // when in shadow mode, since in shadow mode the scratchpad is the only copy of files, nor when
+    // keeping written files for the next round.
+    async fn clean_written_from_scratchpad(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        let span = self.span("scratchpad_clean_written");
+        let checked = {
+            let ref_files_unmasked = self.files_unmasked.read().unwrap();
+            Self::check_unknown(
+                files
+                    .iter()
+                    .filter(|f| ref_files_unmasked.get(*f) != Some(&true)),
+            )
+        };
+        if let Err(e) = checked {
+            return Self::record(span, 0, 0, Err(e));
+        }
 
-            let files = files
-                .iter()
-                .filter(|f| ref_files_unmasked.remove(f).is_some())
-                .cloned()
-                .collect::<Vec<_>>();
-            (files_masked, _uuid) = self.apply_mask(&files);
+        if self.shadow_mode || self.keep_written {
+            return Ok(Self::record_clean(span, ScratchpadCleanSummary::default()));
         }
+        Ok(self.delete(files, span).await)
+    }
 
-        delete_files(
-            &files_masked,
-            Arc::clone(&self.store_scratchpad),
-            &self.backoff_config,
-            self.concurrency,
-        )
-        .await;
+    async fn clean(&self) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        // clean will remove all files in the scratchpad as of the time files_unmasked is locked.
+        let files: Vec<_> = {
+            let ref_files_unmasked = self.files_unmasked.read().unwrap();
+            let read_through = self.read_through.lock().unwrap();
+            ref_files_unmasked
+                .keys()
+                .chain(read_through.iter())
+                .cloned()
+                .collect()
+        };
+
+        // self.files_unmasked is locked again in delete.  If another thread removes a file
+        // between this relock, delete will skip it.
+        let mut summary = self.delete(&files, self.span("scratchpad_clean")).await;
+        self.written.lock().unwrap().clear();
+
+        // files prefetched but never loaded
+        summary += self.cancel_prefetches().await;
+        Ok(summary)
     }
 
-    // clean_written_from_scratchpad is the same as clean_from_scratchpad, but it does not remove files
-    // when in shadow mode, since in shadow mode the scratchpad is the only copy of files.
-    async fn clean_written_from_scratchpad(&self, files: &[ParquetFilePath]) {
-        if !self.shadow_mode {
-            self.clean_from_scratchpad(files).await;
+    async fn retain_only(
+        &self,
+        files: &[ParquetFilePath],
+    ) -> Result<ScratchpadCleanSummary, ScratchpadError> {
+        let retained = files.iter().collect::<HashSet<_>>();
+
+        // files read through take no space, only forget them
+        self.read_through
+            .lock()
+            .unwrap()
+            .retain(|f| retained.contains(f));
+
+        // without kept files, everything not in use is already cleaned.  In shadow mode, written
+        // files are never in the catalog and must not be evicted.
+        if !self.keep_written || self.shadow_mode {
+            return Ok(ScratchpadCleanSummary::default());
         }
-    }
 
-    async fn clean(&self) {
-        // clean will remove all files in the scratchpad as of the time files_unmasked is locked.
-        let files: Vec<_> = self
+        let evicted: Vec<_> = self
             .files_unmasked
             .read()
             .unwrap()
             .keys()
+            .filter(|f| !retained.contains(f))
             .cloned()
             .collect();
+        Ok(self
+            .delete(&evicted, self.span("scratchpad_retain_only"))
+            .await)
+    }
 
-        // self.files_unmasked is locked again in clean_from_scratchpad.  If another thread removes a file
-        // between this relock, clean_from_scratchpad will skip it.
-        self.clean_from_scratchpad(&files).await;
+    fn stats(&self) -> ScratchpadStats {
+        ScratchpadStats {
+            files_loaded: self.files_loaded.load(Ordering::Relaxed),
+            bytes_loaded: self.bytes_loaded.load(Ordering::Relaxed),
+            files_made_public: self.files_made_public.load(Ordering::Relaxed),
+            bytes_made_public: self.bytes_made_public.load(Ordering::Relaxed),
+            staged_bytes: self.staged_bytes(),
+            transfer_retries: self.transfer_retries.load(Ordering::Relaxed),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
+    use std::{collections::VecDeque, time::Duration};
 
+    use iox_time::{MockProvider, Time};
+    use metric::Registry;
+    use object_store::ObjectStore;
     use test_helpers::{maybe_start_logging, tracing::TracingCapture};
+    use trace::{span::SpanStatus, RingBufferTraceCollector};
 
-    use crate::components::scratchpad::test_util::{assert_content, file_path, stores};
+    use crate::components::scratchpad::test_util::{
+        assert_content, file_path, stores, ConcurrencyTrackingStore, FaultyStore,
+    };
     use compactor_test_utils::list_object_store;
 
     use super::*;
@@ -307,7 +1258,7 @@ mod tests {
             Arc::clone(&store_scratchpad),
             Arc::clone(&store_output),
         );
-        let pad = gen.pad();
+        let pad = gen.pad(None);
 
         let f1 = file_path(1);
         let f2 = file_path(2);
@@ -328,11 +1279,23 @@ mod tests {
         assert_content(&store_scratchpad, []).await;
         assert_content(&store_output, []).await;
 
-        let early_get_uuids = pad.uuids(&[f1.clone(), f2.clone()]);
+        // the IDs are only known once loaded
+        assert_eq!(
+            pad.uuids(&[f1.clone(), f2.clone()]).unwrap_err(),
+            ScratchpadError::UnknownFiles {
+                files: vec![
+                    f1.object_store_path().to_string(),
+                    f2.object_store_path().to_string()
+                ],
+            }
+        );
 
-        let uuids = pad.load_to_scratchpad(&[f1.clone(), f2.clone()]).await;
+        let uuids = pad
+            .load_to_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
         assert_eq!(uuids.len(), 2);
-        assert_eq!(early_get_uuids, uuids);
+        assert_eq!(pad.uuids(&[f1.clone(), f2.clone()]).unwrap(), uuids);
         let f1_masked = f1.clone().with_object_store_id(uuids[0]);
         let f2_masked = f2.clone().with_object_store_id(uuids[1]);
 
@@ -340,7 +1303,10 @@ mod tests {
         assert_content(&store_scratchpad, [&f1_masked, &f2_masked]).await;
         assert_content(&store_output, []).await;
 
-        let uuids = pad.load_to_scratchpad(&[f2.clone(), f3.clone()]).await;
+        let uuids = pad
+            .load_to_scratchpad(&[f2.clone(), f3.clone()])
+            .await
+            .unwrap();
         assert_eq!(uuids.len(), 2);
         assert_eq!(f2_masked.objest_store_id(), uuids[0]);
         let f3_masked = f3.clone().with_object_store_id(uuids[1]);
@@ -355,6 +1321,7 @@ mod tests {
                 .await
                 .unwrap();
         }
+        pad.record_written(&[f5_masked.clone(), f6_masked.clone()]);
 
         assert_content(&store_input, [&f1, &f2, &f3, &f4]).await;
         assert_content(
@@ -368,7 +1335,8 @@ mod tests {
 
         let uuids = pad
             .make_public(&[f5_masked.clone(), f6_masked.clone()])
-            .await;
+            .await
+            .unwrap();
         assert_eq!(uuids.len(), 2);
         let f5 = f5_masked.clone().with_object_store_id(uuids[0]);
         let f6 = f6_masked.clone().with_object_store_id(uuids[1]);
@@ -383,7 +1351,7 @@ mod tests {
         .await;
         assert_content(&store_output, [&f5, &f6]).await;
 
-        let uuids = pad.make_public(&[f1_masked.clone()]).await;
+        let uuids = pad.make_public(&[f1_masked.clone()]).await.unwrap();
         assert_eq!(uuids.len(), 1);
         assert_eq!(f1.objest_store_id(), uuids[0]);
 
@@ -399,7 +1367,8 @@ mod tests {
 
         // we're in shadow mode, so written (compaction output) files must be be removed.
         pad.clean_written_from_scratchpad(&[f1.clone(), f5.clone()])
-            .await;
+            .await
+            .unwrap();
 
         // they're still there
         assert_content(
@@ -410,7 +1379,9 @@ mod tests {
         )
         .await;
 
-        pad.clean_from_scratchpad(&[f1.clone(), f5.clone()]).await;
+        pad.clean_from_scratchpad(&[f1.clone(), f5.clone()])
+            .await
+            .unwrap();
 
         assert_content(
             &store_scratchpad,
@@ -421,7 +1392,7 @@ mod tests {
         // Reload a cleaned file back into the scratchpad, simulating a backlogged partition that
         // requires several compaction loops (where the output of one compaction is later the input
         // to a subsequent compaction).
-        let uuids = pad.load_to_scratchpad(&[f1.clone()]).await;
+        let uuids = pad.load_to_scratchpad(&[f1.clone()]).await.unwrap();
         assert_eq!(uuids.len(), 1);
         assert_eq!(f1_masked.objest_store_id(), uuids[0]);
 
@@ -433,7 +1404,7 @@ mod tests {
         .await;
         assert_content(&store_output, [&f1, &f5, &f6]).await;
 
-        pad.clean().await;
+        pad.clean().await.unwrap();
 
         assert_content(&store_input, [&f1, &f2, &f3, &f4]).await;
         assert_content(&store_scratchpad, [&f7_masked]).await; // pad didn't know about these files
@@ -452,8 +1423,8 @@ mod tests {
             Arc::clone(&store_output),
         );
 
-        let pad1 = gen.pad();
-        let pad2 = gen.pad();
+        let pad1 = gen.pad(None);
+        let pad2 = gen.pad(None);
 
         let f = file_path(1);
 
@@ -462,17 +1433,17 @@ mod tests {
             .await
             .unwrap();
 
-        let uuids = pad1.load_to_scratchpad(&[f.clone()]).await;
+        let uuids = pad1.load_to_scratchpad(&[f.clone()]).await.unwrap();
         assert_eq!(uuids.len(), 1);
         let f_masked1 = f.clone().with_object_store_id(uuids[0]);
 
-        let uuids = pad2.load_to_scratchpad(&[f.clone()]).await;
+        let uuids = pad2.load_to_scratchpad(&[f.clone()]).await.unwrap();
         assert_eq!(uuids.len(), 1);
         let f_masked2 = f.with_object_store_id(uuids[0]);
 
         assert_content(&store_scratchpad, [&f_masked1, &f_masked2]).await;
 
-        pad2.clean().await;
+        pad2.clean().await.unwrap();
 
         assert_content(&store_scratchpad, [&f_masked1]).await;
     }
@@ -488,7 +1459,7 @@ mod tests {
             Arc::clone(&store_scratchpad),
             Arc::clone(&store_output),
         );
-        let pad = gen.pad();
+        let pad = gen.pad(None);
 
         let f = file_path(1);
 
@@ -497,7 +1468,7 @@ mod tests {
             .await
             .unwrap();
 
-        pad.load_to_scratchpad(&[f]).await;
+        pad.load_to_scratchpad(&[f]).await.unwrap();
 
         let capture = TracingCapture::new();
 
@@ -523,6 +1494,1388 @@ mod tests {
         .expect("no timeout");
     }
 
+    /// Generator with the given capacity, and `num_files` input files of 100 bytes each.
+    #[tokio::test]
+    async fn test_unknown_files() {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&store_input),
+            Arc::clone(&store_scratchpad),
+            Arc::clone(&store_output),
+        );
+        let pad = gen.pad(None);
+
+        let f1 = file_path(1);
+        let f2 = file_path(2);
+        let f3_masked = file_path(3);
+        let f4 = file_path(4);
+        let f5_masked = file_path(5);
+        store_input
+            .put(&f1.object_store_path(), vec![].into())
+            .await
+            .unwrap();
+        pad.load_to_scratchpad(&[f1.clone()]).await.unwrap();
+        let f1_masked = f1
+            .clone()
+            .with_object_store_id(pad.uuids(&[f1.clone()]).unwrap()[0]);
+
+        // compaction output
+        store_scratchpad
+            .put(&f3_masked.object_store_path(), vec![].into())
+            .await
+            .unwrap();
+        pad.record_written(&[f3_masked.clone()]);
+
+        assert_eq!(
+            pad.uuids(&[f2.clone(), f1.clone(), f4.clone()])
+                .unwrap_err(),
+            ScratchpadError::UnknownFiles {
+                files: vec![
+                    f2.object_store_path().to_string(),
+                    f4.object_store_path().to_string()
+                ],
+            }
+        );
+
+        // neither files not written, nor the public path of loaded files, can be published
+        assert_eq!(
+            pad.make_public(&[f5_masked.clone(), f3_masked.clone(), f1.clone()])
+                .await
+                .unwrap_err(),
+            ScratchpadError::UnknownFiles {
+                files: vec![
+                    f5_masked.object_store_path().to_string(),
+                    f1.object_store_path().to_string()
+                ],
+            }
+        );
+        assert_content(&store_output, []).await;
+        assert_content(&store_scratchpad, [&f1_masked, &f3_masked]).await;
+        assert_eq!(pad.stats().files_made_public, 0);
+
+        let uuids = pad.make_public(&[f3_masked.clone()]).await.unwrap();
+        let f3 = f3_masked.clone().with_object_store_id(uuids[0]);
+        assert_content(&store_output, [&f3]).await;
+
+        // loaded files were not written
+        assert_eq!(
+            pad.clean_written_from_scratchpad(&[f3.clone(), f1.clone()])
+                .await
+                .unwrap_err(),
+            ScratchpadError::UnknownFiles {
+                files: vec![f1.object_store_path().to_string()],
+            }
+        );
+        assert_content(&store_scratchpad, [&f1_masked, &f3_masked]).await;
+
+        pad.clean_written_from_scratchpad(&[f3]).await.unwrap();
+        assert_content(&store_scratchpad, [&f1_masked]).await;
+        pad.clean().await.unwrap();
+    }
+
+    async fn read_through_pad(
+        threshold_bytes: usize,
+        sizes: &[usize],
+    ) -> (
+        ProdScratchpad,
+        Arc<DynObjectStore>,
+        Arc<DynObjectStore>,
+        Vec<ParquetFilePath>,
+    ) {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&store_input),
+            Arc::clone(&store_scratchpad),
+            store_output,
+        )
+        .with_read_through(threshold_bytes);
+
+        let mut files = vec![];
+        for (i, size) in sizes.iter().enumerate() {
+            let f = file_path(i as u128 + 1);
+            store_input
+                .put(&f.object_store_path(), vec![0; *size].into())
+                .await
+                .unwrap();
+            files.push(f);
+        }
+
+        (gen.prod_pad(None), store_input, store_scratchpad, files)
+    }
+
+    #[tokio::test]
+    async fn test_read_through_mixed() {
+        maybe_start_logging();
+
+        // the two smallest files total less than the threshold, the third would not fit
+        let (pad, store_input, store_scratchpad, files) =
+            read_through_pad(50, &[60, 10, 30, 50]).await;
+        let (f1, f2, f3, f4) = (&files[0], &files[1], &files[2], &files[3]);
+
+        let uuids = pad.load_to_scratchpad(&files).await.unwrap();
+        assert_eq!(uuids.len(), 4);
+        let f1_masked = f1.clone().with_object_store_id(uuids[0]);
+        let f4_masked = f4.clone().with_object_store_id(uuids[3]);
+
+        // small files alias the input store, the others are staged
+        assert_ne!(uuids[0], f1.objest_store_id());
+        assert_eq!(uuids[1], f2.objest_store_id());
+        assert_eq!(uuids[2], f3.objest_store_id());
+        assert_ne!(uuids[3], f4.objest_store_id());
+        assert_content(&store_scratchpad, [&f1_masked, &f4_masked]).await;
+        assert_eq!(pad.uuids(&files).unwrap(), uuids);
+
+        // loading again keeps the files where they are
+        let uuids_2 = pad
+            .load_to_scratchpad(&[f2.clone(), f4.clone()])
+            .await
+            .unwrap();
+        assert_eq!(uuids_2, vec![uuids[1], uuids[3]]);
+        assert_eq!(pad.stats().files_loaded, 2);
+
+        // nothing to publish for files read through
+        pad.make_public(&[f2.clone()]).await.unwrap();
+        assert_eq!(pad.stats().files_made_public, 0);
+
+        // cleaning never touches the originals
+        pad.clean_from_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
+        assert_content(&store_scratchpad, [&f4_masked]).await;
+        pad.clean().await.unwrap();
+        assert_content(&store_scratchpad, []).await;
+        assert_content(&store_input, [f1, f2, f3, f4]).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_through_all() {
+        maybe_start_logging();
+
+        let (pad, store_input, store_scratchpad, files) =
+            read_through_pad(100, &[10, 20, 30]).await;
+
+        let uuids = pad.load_to_scratchpad(&files).await.unwrap();
+        assert_eq!(
+            uuids,
+            files
+                .iter()
+                .map(|f| f.objest_store_id())
+                .collect::<Vec<_>>()
+        );
+        assert_content(&store_scratchpad, []).await;
+        assert_eq!(pad.stats().files_loaded, 0);
+
+        pad.clean().await.unwrap();
+        assert_content(&store_input, [&files[0], &files[1], &files[2]]).await;
+    }
+
+    async fn capacity_gen(
+        capacity_bytes: usize,
+        capacity_policy: ScratchpadCapacityPolicy,
+        num_files: u128,
+    ) -> (ProdScratchpadGen, Arc<DynObjectStore>, Vec<ParquetFilePath>) {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&store_input),
+            Arc::clone(&store_scratchpad),
+            store_output,
+        )
+        .with_capacity(capacity_bytes, capacity_policy);
+
+        let files = (1..=num_files).map(file_path).collect::<Vec<_>>();
+        for f in &files {
+            store_input
+                .put(&f.object_store_path(), vec![0; 100].into())
+                .await
+                .unwrap();
+        }
+
+        (gen, store_scratchpad, files)
+    }
+
+    async fn capacity_pad(
+        capacity_bytes: usize,
+        capacity_policy: ScratchpadCapacityPolicy,
+        num_files: u128,
+    ) -> (
+        Arc<ProdScratchpad>,
+        Arc<DynObjectStore>,
+        Vec<ParquetFilePath>,
+    ) {
+        let (gen, store_scratchpad, files) =
+            capacity_gen(capacity_bytes, capacity_policy, num_files).await;
+        (Arc::new(gen.prod_pad(None)), store_scratchpad, files)
+    }
+
+    #[test]
+    fn test_display_capacity() {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            true,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            store_input,
+            store_scratchpad,
+            store_output,
+        )
+        .with_capacity(100, ScratchpadCapacityPolicy::Error);
+        assert_eq!(gen.to_string(), "prod(capacity_bytes=100, policy=Error)");
+    }
+
+    #[tokio::test]
+    async fn test_capacity_block() {
+        let (pad, store_scratchpad, files) =
+            capacity_pad(250, ScratchpadCapacityPolicy::Block, 3).await;
+        let (f1, f2, f3) = (files[0].clone(), files[1].clone(), files[2].clone());
+
+        let uuids = pad
+            .load_to_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
+        let f1_masked = f1.clone().with_object_store_id(uuids[0]);
+        let f2_masked = f2.clone().with_object_store_id(uuids[1]);
+        assert_eq!(pad.staged_bytes(), 200);
+
+        // does not fit, waits for a clean
+        let mut blocked = tokio::spawn({
+            let pad = Arc::clone(&pad);
+            let f3 = f3.clone();
+            async move { pad.load_to_scratchpad(&[f3]).await }
+        });
+        tokio::time::timeout(Duration::from_millis(100), &mut blocked)
+            .await
+            .unwrap_err();
+
+        // files already staged need no capacity
+        pad.load_to_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
+        assert_eq!(pad.staged_bytes(), 200);
+
+        // cleaning a file that is not staged does not free anything
+        pad.clean_from_scratchpad(&[f3.clone()]).await.unwrap();
+        tokio::time::timeout(Duration::from_millis(100), &mut blocked)
+            .await
+            .unwrap_err();
+
+        pad.clean_from_scratchpad(&[f1.clone()]).await.unwrap();
+        let uuids = tokio::time::timeout(Duration::from_secs(5), blocked)
+            .await
+            .expect("no timeout")
+            .unwrap()
+            .unwrap();
+        let f3_masked = f3.clone().with_object_store_id(uuids[0]);
+        assert_eq!(pad.staged_bytes(), 200);
+        assert_content(&store_scratchpad, [&f2_masked, &f3_masked]).await;
+
+        pad.clean().await.unwrap();
+        assert_eq!(pad.staged_bytes(), 0);
+        assert_content(&store_scratchpad, []).await;
+
+        pad.load_to_scratchpad(&[f1.clone()]).await.unwrap();
+        assert_eq!(pad.staged_bytes(), 100);
+        assert_content(&store_scratchpad, [&f1_masked]).await;
+        pad.clean().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_capacity_block_too_large() {
+        let (pad, store_scratchpad, files) =
+            capacity_pad(150, ScratchpadCapacityPolicy::Block, 2).await;
+
+        let err = pad.load_to_scratchpad(&files).await.unwrap_err();
+        assert_eq!(
+            err,
+            ScratchpadError::CapacityExceeded {
+                requested_bytes: 200,
+                staged_bytes: 0,
+                capacity_bytes: 150,
+            }
+        );
+        assert_eq!(pad.staged_bytes(), 0);
+        assert_content(&store_scratchpad, []).await;
+    }
+
+    #[tokio::test]
+    async fn test_capacity_missing_file() {
+        let (pad, store_scratchpad, files) =
+            capacity_pad(250, ScratchpadCapacityPolicy::Error, 2).await;
+        let missing = file_path(3);
+
+        // the size of a file missing from the input store is never known
+        let err = tokio::time::timeout(
+            Duration::from_secs(5),
+            pad.load_to_scratchpad(&[files[0].clone(), missing.clone()]),
+        )
+        .await
+        .expect("no timeout")
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ScratchpadError::TransferFailed { location, attempts: 1, source_failed: true, .. }
+                if location == missing.object_store_path().to_string()
+        ));
+        assert_eq!(pad.staged_bytes(), 0);
+        assert_content(&store_scratchpad, []).await;
+
+        // nothing was allocated
+        pad.load_to_scratchpad(&files).await.unwrap();
+        assert_eq!(pad.staged_bytes(), 200);
+        pad.clean().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_capacity_error_concurrent() {
+        let (pad, store_scratchpad, files) =
+            capacity_pad(300, ScratchpadCapacityPolicy::Error, 4).await;
+
+        let loads = [files[0..2].to_vec(), files[2..4].to_vec()]
+            .into_iter()
+            .map(|files| {
+                let pad = Arc::clone(&pad);
+                tokio::spawn(async move {
+                    let res = pad.load_to_scratchpad(&files).await;
+                    (files, res)
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut loaded = vec![];
+        let mut errors = vec![];
+        for load in loads {
+            match load.await.unwrap() {
+                (files, Ok(uuids)) => loaded.push((files, uuids)),
+                (files, Err(e)) => errors.push((files, e)),
+            }
+        }
+
+        // exactly one of the loads fits
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].1,
+            ScratchpadError::CapacityExceeded {
+                requested_bytes: 200,
+                staged_bytes: 200,
+                capacity_bytes: 300,
+            }
+        );
+        assert_eq!(pad.staged_bytes(), 200);
+        let (loaded_files, uuids) = &loaded[0];
+        let masked = loaded_files
+            .iter()
+            .zip(uuids)
+            .map(|(f, uuid)| f.clone().with_object_store_id(*uuid))
+            .collect::<Vec<_>>();
+        assert_content(&store_scratchpad, [&masked[0], &masked[1]]).await;
+
+        // once cleaned, the failed load fits
+        pad.clean_from_scratchpad(loaded_files).await.unwrap();
+        assert_eq!(pad.staged_bytes(), 0);
+        pad.load_to_scratchpad(&errors[0].0).await.unwrap();
+        assert_eq!(pad.staged_bytes(), 200);
+
+        pad.clean().await.unwrap();
+        assert_eq!(pad.staged_bytes(), 0);
+        assert_content(&store_scratchpad, []).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_capacity_shared_across_pads() {
+        let (gen, store_scratchpad, files) =
+            capacity_gen(300, ScratchpadCapacityPolicy::Block, 8).await;
+        let pool = Arc::clone(gen.pool.as_ref().unwrap());
+
+        // each pad loads two files, so only one load fits at a time
+        let first = Arc::new(gen.prod_pad(None));
+        first.load_to_scratchpad(&files[0..2]).await.unwrap();
+
+        // queue the other loads one after the other, so their order is known
+        let mut waiting = VecDeque::new();
+        for files in files[2..].chunks(2) {
+            let num_waiting = pool.num_waiting();
+            let pad = Arc::new(gen.prod_pad(None));
+            let handle = tokio::spawn({
+                let pad = Arc::clone(&pad);
+                let files = files.to_vec();
+                async move { pad.load_to_scratchpad(&files).await }
+            });
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while pool.num_waiting() == num_waiting {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            })
+            .await
+            .expect("load waiting");
+            waiting.push_back((pad, handle));
+        }
+        assert_eq!(pool.allocated_bytes(), 200);
+        assert_eq!(list_object_store(&store_scratchpad).await.len(), 2);
+
+        // each clean lets the next load in line proceed, and only that one
+        let mut loaded = first;
+        while let Some((pad, mut handle)) = waiting.pop_front() {
+            for (_pad, handle) in waiting.iter_mut() {
+                tokio::time::timeout(Duration::from_millis(100), handle)
+                    .await
+                    .unwrap_err();
+            }
+
+            loaded.clean().await.unwrap();
+            tokio::time::timeout(Duration::from_secs(5), &mut handle)
+                .await
+                .expect("no timeout")
+                .unwrap()
+                .unwrap();
+            assert_eq!(loaded.staged_bytes(), 0);
+            assert_eq!(pad.staged_bytes(), 200);
+            assert_eq!(pool.allocated_bytes(), 200);
+            assert_eq!(list_object_store(&store_scratchpad).await.len(), 2);
+
+            loaded = pad;
+        }
+
+        loaded.clean().await.unwrap();
+        assert_eq!(pool.allocated_bytes(), 0);
+        assert_content(&store_scratchpad, []).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_max_concurrent_transfers() {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let tracked_input = Arc::new(ConcurrencyTrackingStore::new(store_input));
+        let tracked_output = Arc::new(ConcurrencyTrackingStore::new(store_output));
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(10).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&tracked_input) as _,
+            Arc::clone(&store_scratchpad),
+            Arc::clone(&tracked_output) as _,
+        )
+        .with_max_concurrent_transfers(NonZeroUsize::new(2).unwrap());
+
+        let files = (1..=20).map(file_path).collect::<Vec<_>>();
+        for f in &files {
+            tracked_input
+                .put(&f.object_store_path(), vec![].into())
+                .await
+                .unwrap();
+        }
+
+        // several pads load and publish at once
+        let handles = files
+            .chunks(5)
+            .map(|files| {
+                let pad = gen.pad(None);
+                let files = files.to_vec();
+                tokio::spawn(async move {
+                    let uuids = pad.load_to_scratchpad(&files).await.unwrap();
+                    assert_eq!(uuids, pad.uuids(&files).unwrap());
+
+                    let files_masked = files
+                        .iter()
+                        .zip(&uuids)
+                        .map(|(f, uuid)| f.clone().with_object_store_id(*uuid))
+                        .collect::<Vec<_>>();
+                    let uuids = pad.make_public(&files_masked).await.unwrap();
+                    assert_eq!(
+                        uuids,
+                        files
+                            .iter()
+                            .map(|f| f.objest_store_id())
+                            .collect::<Vec<_>>()
+                    );
+
+                    pad.clean().await.unwrap();
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert!(tracked_input.peak() <= 2, "peak: {}", tracked_input.peak());
+        assert!(
+            tracked_output.peak() <= 2,
+            "peak: {}",
+            tracked_output.peak()
+        );
+        assert_content(&store_scratchpad, []).await;
+    }
+
+    /// Prod scratchpad reading slowly from an input store with `n` files.
+    async fn prefetching_pad(
+        n: u128,
+    ) -> (
+        ProdScratchpad,
+        Arc<ConcurrencyTrackingStore>,
+        Arc<DynObjectStore>,
+        Vec<ParquetFilePath>,
+    ) {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let tracked_input = Arc::new(ConcurrencyTrackingStore::new(store_input));
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(2).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&tracked_input) as _,
+            Arc::clone(&store_scratchpad),
+            store_output,
+        );
+
+        let files = (1..=n).map(file_path).collect::<Vec<_>>();
+        for f in &files {
+            tracked_input
+                .put(&f.object_store_path(), vec![0; 10].into())
+                .await
+                .unwrap();
+        }
+
+        (gen.prod_pad(None), tracked_input, store_scratchpad, files)
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_not_downloaded_twice() {
+        let (pad, tracked_input, store_scratchpad, files) = prefetching_pad(3).await;
+
+        pad.prefetch(&files[..2]);
+        // already prefetching
+        pad.prefetch(&files[..2]);
+
+        let uuids = pad.load_to_scratchpad(&files).await.unwrap();
+        assert_eq!(tracked_input.gets(), 3);
+        assert_eq!(pad.stats().files_loaded, 3);
+        assert_eq!(pad.stats().bytes_loaded, 30);
+        assert_eq!(pad.staged_bytes(), 30);
+
+        let files_masked = files
+            .iter()
+            .zip(uuids)
+            .map(|(f, uuid)| f.clone().with_object_store_id(uuid))
+            .collect::<Vec<_>>();
+        assert_content(
+            &store_scratchpad,
+            [&files_masked[0], &files_masked[1], &files_masked[2]],
+        )
+        .await;
+
+        // loaded files are not prefetched again
+        pad.prefetch(&files);
+        pad.clean().await.unwrap();
+        assert_eq!(tracked_input.gets(), 3);
+        assert_content(&store_scratchpad, []).await;
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_cleaned() {
+        let (pad, _tracked_input, store_scratchpad, files) = prefetching_pad(3).await;
+
+        // the second file is prefetched but never loaded
+        pad.prefetch(&files[..2]);
+        pad.load_to_scratchpad(&files[..1]).await.unwrap();
+        pad.clean().await.unwrap();
+        assert_content(&store_scratchpad, []).await;
+
+        // cancelled while copying
+        pad.prefetch(&files[2..]);
+        pad.clean().await.unwrap();
+        assert_content(&store_scratchpad, []).await;
+
+        // a cancelled prefetch does not prevent loading the file later
+        pad.load_to_scratchpad(&files[2..]).await.unwrap();
+        assert_eq!(pad.stats().files_loaded, 2);
+        pad.clean().await.unwrap();
+
+        // dropping the scratchpad cleans up eventually
+        pad.prefetch(&files);
+        drop(pad);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !list_object_store(&store_scratchpad).await.is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("prefetched files deleted");
+    }
+
+    async fn faulty_output_pad() -> (ProdScratchpad, Arc<FaultyStore>, ParquetFilePath) {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let faulty_output = Arc::new(FaultyStore::new(store_output));
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            store_input,
+            Arc::clone(&store_scratchpad),
+            Arc::clone(&faulty_output) as _,
+        );
+
+        // compaction output
+        let f_masked = file_path(1);
+        store_scratchpad
+            .put(&f_masked.object_store_path(), vec![0; 10].into())
+            .await
+            .unwrap();
+        let pad = gen.prod_pad(None);
+        pad.record_written(&[f_masked.clone()]);
+
+        (pad, faulty_output, f_masked)
+    }
+
+    #[tokio::test]
+    async fn test_make_public_retries_corrupt_copy() {
+        let (pad, faulty_output, f_masked) = faulty_output_pad().await;
+
+        faulty_output.truncate_next_puts(1);
+        let uuids = pad.make_public(&[f_masked.clone()]).await.unwrap();
+        let f = f_masked.with_object_store_id(uuids[0]);
+
+        let meta = faulty_output.head(&f.object_store_path()).await.unwrap();
+        assert_eq!(meta.size, 10);
+        assert_eq!(pad.stats().files_made_public, 1);
+
+        pad.clean().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_make_public_integrity_error() {
+        let (pad, faulty_output, f_masked) = faulty_output_pad().await;
+        let f = f_masked
+            .clone()
+            .with_object_store_id(pad.pad_uuids(&[f_masked.clone()])[0]);
+
+        faulty_output.truncate_next_puts(MAX_PUBLISH_ATTEMPTS);
+        let err = pad.make_public(&[f_masked.clone()]).await.unwrap_err();
+        assert_eq!(
+            err,
+            ScratchpadError::IntegrityCheckFailed {
+                location: f.object_store_path().to_string(),
+                expected_bytes: 10,
+                actual_bytes: 5,
+                attempts: MAX_PUBLISH_ATTEMPTS,
+            }
+        );
+        assert_eq!(pad.stats().files_made_public, 0);
+
+        // the file is copied again on the next call
+        pad.make_public(&[f_masked]).await.unwrap();
+        let meta = faulty_output.head(&f.object_store_path()).await.unwrap();
+        assert_eq!(meta.size, 10);
+        assert_eq!(pad.stats().files_made_public, 1);
+
+        pad.clean().await.unwrap();
+    }
+
+    /// Prod scratchpad whose scratchpad store fails deletes on demand, with `n` files loaded.
+    async fn faulty_scratchpad_pad(
+        n: u128,
+    ) -> (ProdScratchpad, Arc<FaultyStore>, Vec<ParquetFilePath>) {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let faulty_scratchpad = Arc::new(FaultyStore::new(store_scratchpad));
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig {
+                init_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+                ..Default::default()
+            },
+            Arc::clone(&store_input),
+            Arc::clone(&faulty_scratchpad) as _,
+            store_output,
+        );
+
+        let files = (1..=n).map(file_path).collect::<Vec<_>>();
+        for f in &files {
+            store_input
+                .put(&f.object_store_path(), vec![0; 10].into())
+                .await
+                .unwrap();
+        }
+
+        let pad = gen.prod_pad(None);
+        pad.load_to_scratchpad(&files).await.unwrap();
+
+        (pad, faulty_scratchpad, files)
+    }
+
+    #[tokio::test]
+    async fn test_clean_retries_failed_delete() {
+        let (pad, faulty_scratchpad, files) = faulty_scratchpad_pad(2).await;
+
+        faulty_scratchpad.fail_next_deletes(MAX_DELETE_ATTEMPTS - 1);
+        pad.clean().await.unwrap();
+
+        let faulty_scratchpad = Arc::clone(&faulty_scratchpad) as Arc<DynObjectStore>;
+        assert_content(&faulty_scratchpad, []).await;
+        assert_eq!(pad.staged_bytes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_clean_reports_leaked_files() {
+        let (pad, faulty_scratchpad, files) = faulty_scratchpad_pad(2).await;
+        let f1_masked = files[0]
+            .clone()
+            .with_object_store_id(pad.uuids(&files[..1]).unwrap()[0]);
+
+        // every attempt for the first file fails, the second file is deleted
+        faulty_scratchpad.fail_next_deletes(MAX_DELETE_ATTEMPTS);
+        let summary = pad.clean_from_scratchpad(&files[..1]).await.unwrap();
+        assert_eq!(
+            summary,
+            ScratchpadCleanSummary {
+                files: 0,
+                bytes: 0,
+                leaked: vec![f1_masked.object_store_path().to_string()],
+            }
+        );
+        let summary = pad.clean_from_scratchpad(&files[1..]).await.unwrap();
+        assert_eq!(
+            summary,
+            ScratchpadCleanSummary {
+                files: 1,
+                bytes: 10,
+                leaked: vec![],
+            }
+        );
+
+        // the leaked file is not tracked anymore
+        let faulty_scratchpad = Arc::clone(&faulty_scratchpad) as Arc<DynObjectStore>;
+        assert_content(&faulty_scratchpad, [&f1_masked]).await;
+        assert_eq!(pad.staged_bytes(), 0);
+        pad.clean().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_summary() {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&store_input),
+            Arc::clone(&store_scratchpad),
+            store_output,
+        );
+        let pad = gen.pad(None);
+
+        let files = (1..=3).map(file_path).collect::<Vec<_>>();
+        for (f, size) in files.iter().zip([10, 20, 30]) {
+            store_input
+                .put(&f.object_store_path(), vec![0; size].into())
+                .await
+                .unwrap();
+        }
+        pad.load_to_scratchpad(&files).await.unwrap();
+
+        let summary = pad.clean_from_scratchpad(&files[..1]).await.unwrap();
+        assert_eq!(
+            summary,
+            ScratchpadCleanSummary {
+                files: 1,
+                bytes: 10,
+                leaked: vec![],
+            }
+        );
+
+        // files already cleaned are not counted twice
+        let summary = pad.clean_from_scratchpad(&files[..2]).await.unwrap();
+        assert_eq!(
+            summary,
+            ScratchpadCleanSummary {
+                files: 1,
+                bytes: 20,
+                leaked: vec![],
+            }
+        );
+
+        let summary = pad.clean().await.unwrap();
+        assert_eq!(
+            summary,
+            ScratchpadCleanSummary {
+                files: 1,
+                bytes: 30,
+                leaked: vec![],
+            }
+        );
+        assert_eq!(
+            pad.clean().await.unwrap(),
+            ScratchpadCleanSummary::default()
+        );
+        assert_content(&store_scratchpad, []).await;
+    }
+
+    #[tokio::test]
+    async fn test_clean_summary_matches_capacity() {
+        let (pad, _store_scratchpad, files) =
+            capacity_pad(300, ScratchpadCapacityPolicy::Error, 3).await;
+        let pool = Arc::clone(pad.pool.as_ref().unwrap());
+
+        pad.load_to_scratchpad(&files).await.unwrap();
+        assert_eq!(pool.allocated_bytes(), 300);
+
+        let summary = pad.clean_from_scratchpad(&files[..1]).await.unwrap();
+        assert_eq!(summary.bytes, 100);
+        assert_eq!(pool.allocated_bytes(), 200);
+
+        let summary = pad.clean().await.unwrap();
+        assert_eq!(summary.files, 2);
+        assert_eq!(summary.bytes, 200);
+        assert_eq!(pool.allocated_bytes(), 0);
+    }
+
+    const TRANSFER_ATTEMPTS: usize = 3;
+
+    /// Prod scratchpad copying files at most [`TRANSFER_ATTEMPTS`] times, between an input and a
+    /// scratchpad store failing reads on demand. The input store has one file.
+    async fn retrying_pad() -> (
+        ProdScratchpad,
+        Arc<FaultyStore>,
+        Arc<FaultyStore>,
+        ParquetFilePath,
+    ) {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let faulty_input = Arc::new(FaultyStore::new(store_input));
+        let faulty_scratchpad = Arc::new(FaultyStore::new(store_scratchpad));
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&faulty_input) as _,
+            Arc::clone(&faulty_scratchpad) as _,
+            store_output,
+        )
+        .with_retry_policy(ScratchpadRetryPolicy {
+            max_attempts: Some(NonZeroUsize::new(TRANSFER_ATTEMPTS).unwrap()),
+            backoff_config: BackoffConfig {
+                init_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(1),
+                ..Default::default()
+            },
+        });
+
+        let f = file_path(1);
+        faulty_input
+            .put(&f.object_store_path(), vec![0; 10].into())
+            .await
+            .unwrap();
+
+        (gen.prod_pad(None), faulty_input, faulty_scratchpad, f)
+    }
+
+    #[tokio::test]
+    async fn test_transfer_retries_transient_failures() {
+        let (pad, faulty_input, faulty_scratchpad, f) = retrying_pad().await;
+
+        faulty_input.fail_next_gets(TRANSFER_ATTEMPTS - 1);
+        pad.load_to_scratchpad(&[f]).await.unwrap();
+        assert_eq!(pad.stats().files_loaded, 1);
+        assert_eq!(pad.stats().transfer_retries, TRANSFER_ATTEMPTS - 1);
+
+        // compaction output
+        let f_masked = file_path(2);
+        faulty_scratchpad
+            .put(&f_masked.object_store_path(), vec![0; 5].into())
+            .await
+            .unwrap();
+        pad.record_written(&[f_masked.clone()]);
+        faulty_scratchpad.fail_next_gets(TRANSFER_ATTEMPTS - 1);
+        pad.make_public(&[f_masked]).await.unwrap();
+        assert_eq!(pad.stats().files_made_public, 1);
+        assert_eq!(pad.stats().transfer_retries, 2 * (TRANSFER_ATTEMPTS - 1));
+
+        pad.clean().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transfer_fails_after_max_attempts() {
+        let (pad, faulty_input, faulty_scratchpad, f) = retrying_pad().await;
+
+        faulty_input.fail_next_gets(TRANSFER_ATTEMPTS);
+        let err = pad.load_to_scratchpad(&[f.clone()]).await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                ScratchpadError::TransferFailed { location, attempts, .. }
+                    if *location == f.object_store_path().to_string()
+                        && *attempts == TRANSFER_ATTEMPTS
+            ),
+            "unexpected error: {err}"
+        );
+        assert_eq!(pad.stats().files_loaded, 0);
+        assert_eq!(pad.stats().transfer_retries, TRANSFER_ATTEMPTS - 1);
+
+        // the file is forgotten, so it is copied again on the next load
+        assert_eq!(pad.staged_bytes(), 0);
+        pad.load_to_scratchpad(&[f.clone()]).await.unwrap();
+        assert_eq!(pad.stats().files_loaded, 1);
+
+        // compaction output
+        let f_masked = file_path(2);
+        faulty_scratchpad
+            .put(&f_masked.object_store_path(), vec![0; 5].into())
+            .await
+            .unwrap();
+        pad.record_written(&[f_masked.clone()]);
+        faulty_scratchpad.fail_next_gets(TRANSFER_ATTEMPTS);
+        let err = pad.make_public(&[f_masked.clone()]).await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                ScratchpadError::TransferFailed { location, attempts, .. }
+                    if *location == f_masked.object_store_path().to_string()
+                        && *attempts == TRANSFER_ATTEMPTS
+            ),
+            "unexpected error: {err}"
+        );
+        assert_eq!(pad.stats().files_made_public, 0);
+
+        pad.clean().await.unwrap();
+    }
+
+    /// Prod scratchpad generator copying files once from an input store with `n` files, between
+    /// stores failing on demand. It bypasses the scratchpad for 10 seconds after 2 failed transfers.
+    struct BreakerTest {
+        gen: ProdScratchpadGen,
+        faulty_input: Arc<FaultyStore>,
+        faulty_scratchpad: Arc<FaultyStore>,
+        faulty_output: Arc<FaultyStore>,
+        store_scratchpad: Arc<DynObjectStore>,
+        time_provider: Arc<MockProvider>,
+        files: Vec<ParquetFilePath>,
+    }
+
+    impl BreakerTest {
+        async fn new(n: u128) -> Self {
+            let (store_input, store_scratchpad, store_output) = stores();
+            let faulty_input = Arc::new(FaultyStore::new(store_input));
+            let faulty_scratchpad = Arc::new(FaultyStore::new(Arc::clone(&store_scratchpad)));
+            let faulty_output = Arc::new(FaultyStore::new(store_output));
+            let time_provider = Arc::new(MockProvider::new(Time::MIN));
+            let gen = ProdScratchpadGen::new(
+                false,
+                NonZeroUsize::new(1).unwrap(),
+                BackoffConfig::default(),
+                Arc::clone(&faulty_input) as _,
+                Arc::clone(&faulty_scratchpad) as _,
+                Arc::clone(&faulty_output) as _,
+            )
+            .with_retry_policy(ScratchpadRetryPolicy {
+                max_attempts: Some(NonZeroUsize::new(1).unwrap()),
+                backoff_config: BackoffConfig::default(),
+            })
+            .with_breaker(Arc::new(ScratchpadBreaker::new(
+                NonZeroUsize::new(2).unwrap(),
+                Duration::from_secs(10),
+                Arc::clone(&time_provider) as _,
+                &Registry::new(),
+            )));
+
+            let files = (1..=n).map(file_path).collect::<Vec<_>>();
+            for f in &files {
+                faulty_input
+                    .put(&f.object_store_path(), vec![0; 10].into())
+                    .await
+                    .unwrap();
+            }
+
+            Self {
+                gen,
+                faulty_input,
+                faulty_scratchpad,
+                faulty_output,
+                store_scratchpad,
+                time_provider,
+                files,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_breaker_passes_through() {
+        let BreakerTest {
+            gen,
+            faulty_scratchpad,
+            store_scratchpad,
+            time_provider,
+            files,
+            ..
+        } = BreakerTest::new(3).await;
+
+        // two failed loads in a row open the breaker
+        for f in &files[..2] {
+            let pad = gen.prod_pad(None);
+            assert!(!pad.pass_through);
+            faulty_scratchpad.fail_next_puts(1);
+            pad.load_to_scratchpad(&[f.clone()]).await.unwrap_err();
+        }
+
+        // new scratchpads read all files from the input store
+        let pad = gen.prod_pad(None);
+        assert!(pad.pass_through);
+        pad.prefetch(&files);
+        let uuids = pad.load_to_scratchpad(&files).await.unwrap();
+        assert_eq!(
+            uuids,
+            files
+                .iter()
+                .map(|f| f.objest_store_id())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(pad.uuids(&files).unwrap(), uuids);
+        assert_eq!(pad.stats().files_loaded, 0);
+        assert_content(&store_scratchpad, []).await;
+        pad.clean().await.unwrap();
+
+        // not yet
+        time_provider.inc(Duration::from_secs(9));
+        assert!(gen.prod_pad(None).pass_through);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_recovers() {
+        let BreakerTest {
+            gen,
+            faulty_scratchpad,
+            store_scratchpad,
+            time_provider,
+            files,
+            ..
+        } = BreakerTest::new(3).await;
+
+        // a successful transfer in between resets the failures
+        for (i, f) in files.iter().enumerate() {
+            let pad = gen.prod_pad(None);
+            assert!(!pad.pass_through);
+            faulty_scratchpad.fail_next_puts(usize::from(i != 1));
+            let _ = pad.load_to_scratchpad(&[f.clone()]).await;
+            pad.clean().await.unwrap();
+        }
+        assert!(!gen.prod_pad(None).pass_through);
+
+        let pad = gen.prod_pad(None);
+        faulty_scratchpad.fail_next_puts(1);
+        pad.load_to_scratchpad(&files[..1]).await.unwrap_err();
+        assert!(gen.prod_pad(None).pass_through);
+
+        // files are staged again after the cool-down
+        time_provider.inc(Duration::from_secs(10));
+        let pad = gen.prod_pad(None);
+        assert!(!pad.pass_through);
+        let uuids = pad.load_to_scratchpad(&files).await.unwrap();
+        assert_eq!(pad.stats().files_loaded, 3);
+        let files_masked = files
+            .iter()
+            .zip(uuids)
+            .map(|(f, uuid)| f.clone().with_object_store_id(uuid))
+            .collect::<Vec<_>>();
+        assert_content(
+            &store_scratchpad,
+            [&files_masked[0], &files_masked[1], &files_masked[2]],
+        )
+        .await;
+        pad.clean().await.unwrap();
+
+        // but a single failure bypasses the scratchpad again
+        let pad = gen.prod_pad(None);
+        faulty_scratchpad.fail_next_puts(1);
+        pad.load_to_scratchpad(&files[..1]).await.unwrap_err();
+        assert!(gen.prod_pad(None).pass_through);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_ignores_other_stores() {
+        let BreakerTest {
+            gen,
+            faulty_input,
+            faulty_output,
+            store_scratchpad,
+            files,
+            ..
+        } = BreakerTest::new(3).await;
+
+        // bypassing the scratchpad store doesn't help when the input store fails...
+        for f in &files[..2] {
+            let pad = gen.prod_pad(None);
+            faulty_input.fail_next_gets(1);
+            pad.load_to_scratchpad(&[f.clone()]).await.unwrap_err();
+            pad.clean().await.unwrap();
+        }
+        assert!(!gen.prod_pad(None).pass_through);
+
+        // ...or the output store
+        let pad = gen.prod_pad(None);
+        for f in [file_path(10), file_path(11)] {
+            // written by the compaction
+            store_scratchpad
+                .put(&f.object_store_path(), vec![0; 10].into())
+                .await
+                .unwrap();
+            pad.record_written(&[f.clone()]);
+            faulty_output.fail_next_puts(1);
+            pad.make_public(&[f]).await.unwrap_err();
+        }
+        assert!(!gen.prod_pad(None).pass_through);
+        pad.clean().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_breaker_writes_through() {
+        let BreakerTest {
+            gen,
+            faulty_scratchpad,
+            faulty_output,
+            store_scratchpad,
+            files,
+            ..
+        } = BreakerTest::new(3).await;
+        for f in &files[..2] {
+            faulty_scratchpad.fail_next_puts(1);
+            gen.prod_pad(None)
+                .load_to_scratchpad(&[f.clone()])
+                .await
+                .unwrap_err();
+        }
+        let pad = gen.prod_pad(None);
+        assert!(pad.pass_through);
+
+        // the compaction wrote its file to the output store directly, so it is already public
+        let written = file_path(10);
+        faulty_output
+            .put(&written.object_store_path(), vec![0; 10].into())
+            .await
+            .unwrap();
+        pad.record_written(&[written.clone()]);
+        let uuids = pad.make_public(&[written.clone()]).await.unwrap();
+        assert_eq!(uuids, vec![written.objest_store_id()]);
+        assert_eq!(pad.stats().files_made_public, 0);
+        assert_content(&store_scratchpad, []).await;
+
+        // checking the output store is bounded by the retry policy
+        let unchecked = file_path(12);
+        pad.record_written(&[unchecked.clone()]);
+        faulty_output.fail_next_heads(1);
+        let err = pad.make_public(&[unchecked.clone()]).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ScratchpadError::TransferFailed { location, attempts: 1, source_failed: false, .. }
+                if location == unchecked.object_store_path().to_string()
+        ));
+
+        // files that were written nowhere are still unknown
+        let missing = file_path(11);
+        assert_eq!(
+            pad.make_public(&[missing.clone()]).await.unwrap_err(),
+            ScratchpadError::UnknownFiles {
+                files: vec![missing.object_store_path().to_string()],
+            }
+        );
+        pad.clean().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_breaker_closed_rejects_unstaged() {
+        let BreakerTest {
+            gen,
+            faulty_output,
+            store_scratchpad,
+            ..
+        } = BreakerTest::new(0).await;
+        let pad = gen.prod_pad(None);
+        assert!(!pad.pass_through);
+
+        // a file in the output store is not public for this pad unless the pad wrote it through
+        let unstaged = file_path(10);
+        faulty_output
+            .put(&unstaged.object_store_path(), vec![0; 10].into())
+            .await
+            .unwrap();
+        assert_eq!(
+            pad.make_public(&[unstaged.clone()]).await.unwrap_err(),
+            ScratchpadError::UnknownFiles {
+                files: vec![unstaged.object_store_path().to_string()],
+            }
+        );
+        assert_eq!(pad.stats().files_made_public, 0);
+        assert_content(&store_scratchpad, []).await;
+        pad.clean().await.unwrap();
+    }
+
+    /// Run two rounds: the first compacts two input files into one, which the second round
+    /// compacts.  Returns the number of files loaded from the input store by the second round.
+    async fn two_rounds(keep_written: bool) -> usize {
+        // outside of shadow mode, files are published to the input store
+        let (store_input, store_scratchpad, _store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&store_input),
+            Arc::clone(&store_scratchpad),
+            Arc::clone(&store_input),
+        )
+        .with_keep_written(keep_written);
+        let pad = gen.pad(None);
+
+        let f1 = file_path(1);
+        let f2 = file_path(2);
+        let f3_masked = file_path(3);
+        for f in [&f1, &f2] {
+            store_input
+                .put(&f.object_store_path(), vec![0; 10].into())
+                .await
+                .unwrap();
+        }
+
+        // round 1
+        pad.retain_only(&[f1.clone(), f2.clone()]).await.unwrap();
+        pad.load_to_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
+        store_scratchpad
+            .put(&f3_masked.object_store_path(), vec![0; 20].into())
+            .await
+            .unwrap();
+        pad.record_written(&[f3_masked.clone()]);
+        let uuids = pad.make_public(&[f3_masked.clone()]).await.unwrap();
+        let f3 = f3_masked.clone().with_object_store_id(uuids[0]);
+        pad.clean_from_scratchpad(&[f1, f2]).await.unwrap();
+        pad.clean_written_from_scratchpad(&[f3.clone()])
+            .await
+            .unwrap();
+        let files_loaded = pad.stats().files_loaded;
+        assert_eq!(files_loaded, 2);
+
+        // round 2, with the output of round 1 as input
+        pad.retain_only(&[f3.clone()]).await.unwrap();
+        pad.load_to_scratchpad(&[f3.clone()]).await.unwrap();
+        assert_content(&store_scratchpad, [&f3_masked]).await;
+        pad.clean_from_scratchpad(&[f3]).await.unwrap();
+        assert_content(&store_scratchpad, []).await;
+
+        pad.stats().files_loaded - files_loaded
+    }
+
+    #[tokio::test]
+    async fn test_keep_written_across_rounds() {
+        assert_eq!(two_rounds(false).await, 1);
+        assert_eq!(two_rounds(true).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_retain_only() {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&store_input),
+            Arc::clone(&store_scratchpad),
+            store_output,
+        )
+        .with_keep_written(true);
+        let pad = gen.pad(None);
+
+        let f1_masked = file_path(1);
+        let f2_masked = file_path(2);
+        for f in [&f1_masked, &f2_masked] {
+            store_scratchpad
+                .put(&f.object_store_path(), vec![].into())
+                .await
+                .unwrap();
+        }
+        pad.record_written(&[f1_masked.clone(), f2_masked.clone()]);
+        let uuids = pad
+            .make_public(&[f1_masked.clone(), f2_masked.clone()])
+            .await
+            .unwrap();
+        let f1 = f1_masked.clone().with_object_store_id(uuids[0]);
+        let f2 = f2_masked.clone().with_object_store_id(uuids[1]);
+        pad.clean_written_from_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
+        assert_content(&store_scratchpad, [&f1_masked, &f2_masked]).await;
+
+        // f1 was deleted from the catalog
+        pad.retain_only(&[f2.clone()]).await.unwrap();
+        assert_content(&store_scratchpad, [&f2_masked]).await;
+
+        pad.clean().await.unwrap();
+        assert_content(&store_scratchpad, []).await;
+    }
+
+    #[tokio::test]
+    async fn test_spans() {
+        let (store_input, store_scratchpad, store_output) = stores();
+        let gen = ProdScratchpadGen::new(
+            false,
+            NonZeroUsize::new(1).unwrap(),
+            BackoffConfig::default(),
+            Arc::clone(&store_input),
+            Arc::clone(&store_scratchpad),
+            store_output,
+        );
+        let collector = Arc::new(RingBufferTraceCollector::new(10));
+        let span_ctx = SpanContext::new(Arc::clone(&collector) as _);
+        let pad = gen.pad(Some(span_ctx.clone()));
+
+        let f1 = file_path(1);
+        let f2 = file_path(2);
+        for f in [&f1, &f2] {
+            store_input
+                .put(&f.object_store_path(), vec![0; 10].into())
+                .await
+                .unwrap();
+        }
+        pad.load_to_scratchpad(&[f1.clone(), f2.clone()])
+            .await
+            .unwrap();
+
+        // compaction output
+        let f3_masked = file_path(3);
+        store_scratchpad
+            .put(&f3_masked.object_store_path(), vec![0; 5].into())
+            .await
+            .unwrap();
+        pad.record_written(&[f3_masked.clone()]);
+        let uuids = pad.make_public(&[f3_masked.clone()]).await.unwrap();
+        let f3 = f3_masked.with_object_store_id(uuids[0]);
+
+        pad.clean_written_from_scratchpad(&[f3]).await.unwrap();
+        pad.clean_from_scratchpad(&[f1]).await.unwrap();
+        pad.clean().await.unwrap();
+
+        let spans = collector
+            .spans()
+            .into_iter()
+            .map(|span| {
+                assert_eq!(span.ctx.trace_id, span_ctx.trace_id);
+                assert_eq!(span.ctx.parent_span_id, Some(span_ctx.span_id));
+                assert_eq!(span.status, SpanStatus::Ok);
+                let meta = |key: &str| span.metadata[key].string().unwrap().to_owned();
+                (span.name.to_string(), meta("files"), meta("bytes"))
+            })
+            .collect::<Vec<_>>();
+        let expected = [
+            ("scratchpad_load", "2", "20"),
+            ("scratchpad_make_public", "1", "5"),
+            ("scratchpad_clean_written", "1", "0"),
+            ("scratchpad_clean_files", "1", "10"),
+            ("scratchpad_clean", "1", "10"),
+        ]
+        .map(|(name, files, bytes)| (name.to_owned(), files.to_owned(), bytes.to_owned()));
+        assert_eq!(spans, expected);
+    }
+
     #[tokio::test]
     #[should_panic(expected = "foo")]
     async fn test_clean_does_not_crash_on_panic() {
@@ -535,7 +2888,7 @@ mod tests {
             Arc::clone(&store_scratchpad),
             Arc::clone(&store_output),
         );
-        let pad = gen.pad();
+        let pad = gen.pad(None);
 
         let f = file_path(1);
 
@@ -544,7 +2897,7 @@ mod tests {
             .await
             .unwrap();
 
-        pad.load_to_scratchpad(&[f]).await;
+        pad.load_to_scratchpad(&[f]).await.unwrap();
 
         panic!("foo");
     }
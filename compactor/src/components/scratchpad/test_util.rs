@@ -1,8 +1,25 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    future::Future,
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use async_trait::async_trait;
+use bytes::Bytes;
 use data_types::{NamespaceId, PartitionId, TableId, TransitionPartitionId};
-use object_store::{memory::InMemory, DynObjectStore};
+use futures::stream::BoxStream;
+use object_store::{
+    memory::InMemory, path::Path, DynObjectStore, GetOptions, GetResult, ListResult, MultipartId,
+    ObjectMeta, ObjectStore, Result,
+};
 use parquet_file::ParquetFilePath;
+use tokio::io::AsyncWrite;
 use uuid::Uuid;
 
 use compactor_test_utils::list_object_store;
@@ -41,3 +58,245 @@ pub async fn assert_content<const N: usize>(
     let actual = list_object_store(store).await;
     assert_eq!(actual, expected);
 }
+
+/// Store that tracks the peak number of concurrent reads and writes.
+///
+/// Each read and write takes a little while, so that concurrent requests overlap.
+#[derive(Debug)]
+pub struct ConcurrencyTrackingStore {
+    inner: Arc<DynObjectStore>,
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+    gets: AtomicUsize,
+}
+
+impl ConcurrencyTrackingStore {
+    pub fn new(inner: Arc<DynObjectStore>) -> Self {
+        Self {
+            inner,
+            in_flight: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            gets: AtomicUsize::new(0),
+        }
+    }
+
+    /// Peak number of concurrent reads and writes so far.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    /// Number of reads so far.
+    pub fn gets(&self) -> usize {
+        self.gets.load(Ordering::SeqCst)
+    }
+
+    async fn track<F, T>(&self, f: F) -> T
+    where
+        F: Future<Output = T> + Send,
+    {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let res = f.await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        res
+    }
+}
+
+impl Display for ConcurrencyTrackingStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "concurrency_tracking({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for ConcurrencyTrackingStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        self.track(self.inner.put(location, bytes)).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        self.gets.fetch_add(1, Ordering::SeqCst);
+        self.track(self.inner.get_opts(location, options)).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.track(self.inner.get_range(location, range)).await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+/// Store that injects faults into the requests to an inner store.
+#[derive(Debug)]
+pub struct FaultyStore {
+    inner: Arc<DynObjectStore>,
+    truncated_puts: AtomicUsize,
+    failed_puts: AtomicUsize,
+    failed_gets: AtomicUsize,
+    failed_heads: AtomicUsize,
+    failed_deletes: AtomicUsize,
+}
+
+impl FaultyStore {
+    pub fn new(inner: Arc<DynObjectStore>) -> Self {
+        Self {
+            inner,
+            truncated_puts: AtomicUsize::new(0),
+            failed_puts: AtomicUsize::new(0),
+            failed_gets: AtomicUsize::new(0),
+            failed_heads: AtomicUsize::new(0),
+            failed_deletes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Only write the first half of the data of the next `n` writes.
+    pub fn truncate_next_puts(&self, n: usize) {
+        self.truncated_puts.store(n, Ordering::SeqCst);
+    }
+
+    /// Fail the next `n` writes, without writing anything.
+    pub fn fail_next_puts(&self, n: usize) {
+        self.failed_puts.store(n, Ordering::SeqCst);
+    }
+
+    /// Fail the next `n` reads.
+    pub fn fail_next_gets(&self, n: usize) {
+        self.failed_gets.store(n, Ordering::SeqCst);
+    }
+
+    /// Fail the next `n` metadata reads.
+    pub fn fail_next_heads(&self, n: usize) {
+        self.failed_heads.store(n, Ordering::SeqCst);
+    }
+
+    /// Fail the next `n` deletes, without deleting anything.
+    pub fn fail_next_deletes(&self, n: usize) {
+        self.failed_deletes.store(n, Ordering::SeqCst);
+    }
+
+    /// Take one of the given faults left to inject, if any.
+    fn take_fault(faults: &AtomicUsize) -> bool {
+        faults
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+impl Display for FaultyStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "faulty({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for FaultyStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        if Self::take_fault(&self.failed_puts) {
+            return Err(object_store::Error::Generic {
+                store: "faulty",
+                source: format!("injected put failure for {location}").into(),
+            });
+        }
+        let bytes = if Self::take_fault(&self.truncated_puts) {
+            bytes.slice(..bytes.len() / 2)
+        } else {
+            bytes
+        };
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        if Self::take_fault(&self.failed_gets) {
+            return Err(object_store::Error::Generic {
+                store: "faulty",
+                source: format!("injected get failure for {location}").into(),
+            });
+        }
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.inner.get_range(location, range).await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        if Self::take_fault(&self.failed_heads) {
+            return Err(object_store::Error::Generic {
+                store: "faulty",
+                source: format!("injected head failure for {location}").into(),
+            });
+        }
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        if Self::take_fault(&self.failed_deletes) {
+            return Err(object_store::Error::Generic {
+                store: "faulty",
+                source: format!("injected delete failure for {location}").into(),
+            });
+        }
+        self.inner.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
@@ -1,48 +1,275 @@
-use std::{num::NonZeroUsize, sync::Arc};
+use std::{
+    num::NonZeroUsize,
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use backoff::{Backoff, BackoffConfig};
-use futures::StreamExt;
+use futures::{StreamExt, TryStreamExt};
 use object_store::DynObjectStore;
+use observability_deps::tracing::warn;
 use parquet_file::ParquetFilePath;
+use tokio::sync::Semaphore;
 
+use super::{ScratchpadError, ScratchpadRetryPolicy};
+
+/// Copy files between stores, returning the size of each file in order.
+///
+/// Each copy holds a permit of `transfer_permits`, if any, so the semaphore limits the transfers
+/// running at once across all callers sharing it.
+///
+/// Failed copies are retried according to `retry_policy`, counting the retries in `retries`.
+///
+/// With `verify_attempts`, the size of each copy is checked against the original, copying the
+/// file again up to that many times in total before failing.
+#[allow(clippy::too_many_arguments)]
 pub async fn copy_files(
     files_in: &[ParquetFilePath],
     files_out: &[ParquetFilePath],
     from: Arc<DynObjectStore>,
     to: Arc<DynObjectStore>,
-    backoff_config: &BackoffConfig,
+    retry_policy: &ScratchpadRetryPolicy,
+    retries: &AtomicUsize,
     concurrency: NonZeroUsize,
-) {
-    futures::stream::iter(files_in.iter().cloned().zip(files_out.to_vec()))
-        .map(|(f_in, f_out)| {
-            let backoff_config = backoff_config.clone();
+    transfer_permits: Option<Arc<Semaphore>>,
+    verify_attempts: Option<usize>,
+) -> Result<Vec<usize>, ScratchpadError> {
+    let mut sizes = vec![0; files_in.len()];
+    futures::stream::iter(files_in.iter().cloned().zip(files_out.to_vec()).enumerate())
+        .map(|(i, (f_in, f_out))| {
+            let backoff_config = retry_policy.backoff_config.clone();
+            let max_attempts = retry_policy.max_attempts;
             let from = Arc::clone(&from);
             let to = Arc::clone(&to);
             let path_in = f_in.object_store_path();
             let path_out = f_out.object_store_path();
+            let transfer_permits = transfer_permits.clone();
+
+            async move {
+                let _permit = match &transfer_permits {
+                    Some(permits) => Some(permits.acquire().await.expect("semaphore not closed")),
+                    None => None,
+                };
+
+                let attempts = verify_attempts.unwrap_or(1).max(1);
+                let mut expected_bytes = 0;
+                let mut actual_bytes = 0;
+                for attempt in 1..=attempts {
+                    let mut transfer_attempt = 0;
+                    let size = Backoff::new(&backoff_config)
+                        .retry_with_backoff("copy file", || {
+                            transfer_attempt += 1;
+                            let transfer_attempt = transfer_attempt;
+                            let (from, to) = (&from, &to);
+                            let (path_in, path_out) = (&path_in, &path_out);
+
+                            async move {
+                                // remember whether reading or writing the file failed
+                                let res = async {
+                                    let bytes = async { from.get(path_in).await?.bytes().await }
+                                        .await
+                                        .map_err(|e| (true, e))?;
+                                    let size = bytes.len();
+                                    to.put(path_out, bytes).await.map_err(|e| (false, e))?;
+                                    Ok::<_, (bool, object_store::Error)>(size)
+                                }
+                                .await;
+                                match res {
+                                    Ok(size) => ControlFlow::Break(Ok(size)),
+                                    Err((source_failed, e))
+                                        if max_attempts
+                                            .map_or(false, |max| transfer_attempt >= max.get()) =>
+                                    {
+                                        warn!(
+                                            %path_in,
+                                            %e,
+                                            attempt = transfer_attempt,
+                                            "giving up copying file",
+                                        );
+                                        ControlFlow::Break(Err(ScratchpadError::TransferFailed {
+                                            location: path_in.to_string(),
+                                            attempts: transfer_attempt,
+                                            error: e.to_string(),
+                                            source_failed,
+                                        }))
+                                    }
+                                    Err((_, e)) => {
+                                        warn!(
+                                            %path_in,
+                                            %e,
+                                            attempt = transfer_attempt,
+                                            "failed to copy file, retrying",
+                                        );
+                                        retries.fetch_add(1, Ordering::Relaxed);
+                                        ControlFlow::Continue(e)
+                                    }
+                                }
+                            }
+                        })
+                        .await
+                        .expect("retry forever")?;
+                    if verify_attempts.is_none() {
+                        return Ok((i, size));
+                    }
+
+                    actual_bytes = Backoff::new(&backoff_config)
+                        .retry_all_errors("check copied file", || async {
+                            to.head(&path_out).await.map(|meta| meta.size)
+                        })
+                        .await
+                        .expect("retry forever");
+                    if actual_bytes == size {
+                        return Ok((i, size));
+                    }
+                    warn!(
+                        %path_out,
+                        expected_bytes = size,
+                        actual_bytes,
+                        attempt,
+                        "copied file has the wrong size",
+                    );
+                    expected_bytes = size;
+                }
+
+                Err(ScratchpadError::IntegrityCheckFailed {
+                    location: path_out.to_string(),
+                    expected_bytes,
+                    actual_bytes,
+                    attempts,
+                })
+            }
+        })
+        .buffer_unordered(concurrency.get())
+        .try_for_each(|(i, size)| {
+            sizes[i] = size;
+            async { Ok(()) }
+        })
+        .await?;
+    Ok(sizes)
+}
+
+/// Get the sizes of the given files, in order.
+///
+/// Fails with [`ScratchpadError::TransferFailed`] if a file does not exist, other errors are
+/// retried.
+pub async fn file_sizes(
+    files: &[ParquetFilePath],
+    store: Arc<DynObjectStore>,
+    backoff_config: &BackoffConfig,
+    concurrency: NonZeroUsize,
+) -> Result<Vec<usize>, ScratchpadError> {
+    futures::stream::iter(files.to_vec())
+        .map(|f| {
+            let backoff_config = backoff_config.clone();
+            let store = Arc::clone(&store);
+            let path = f.object_store_path();
 
             async move {
+                let mut attempt = 0;
                 Backoff::new(&backoff_config)
-                    .retry_all_errors("copy file", || async {
-                        let bytes = from.get(&path_in).await?.bytes().await?;
-                        to.put(&path_out, bytes).await?;
-                        Ok::<_, object_store::Error>(())
+                    .retry_with_backoff("get file size", || {
+                        attempt += 1;
+                        let attempt = attempt;
+                        let (store, path) = (&store, &path);
+
+                        async move {
+                            match store.head(path).await {
+                                Ok(meta) => ControlFlow::Break(Ok(meta.size)),
+                                Err(e @ object_store::Error::NotFound { .. }) => {
+                                    ControlFlow::Break(Err(ScratchpadError::TransferFailed {
+                                        location: path.to_string(),
+                                        attempts: attempt,
+                                        error: e.to_string(),
+                                        source_failed: true,
+                                    }))
+                                }
+                                Err(e) => ControlFlow::Continue(e),
+                            }
+                        }
                     })
                     .await
                     .expect("retry forever")
             }
         })
-        .buffer_unordered(concurrency.get())
-        .collect::<()>()
-        .await;
+        .buffered(concurrency.get())
+        .try_collect()
+        .await
 }
 
+/// Get the files that exist in the destination `store` of a transfer.
+///
+/// Failed checks are retried according to `retry_policy`, counting the retries in `retries`.
+pub async fn existing_files(
+    files: &[ParquetFilePath],
+    store: Arc<DynObjectStore>,
+    retry_policy: &ScratchpadRetryPolicy,
+    retries: &AtomicUsize,
+    concurrency: NonZeroUsize,
+) -> Result<Vec<ParquetFilePath>, ScratchpadError> {
+    futures::stream::iter(files.to_vec())
+        .map(|f| {
+            let backoff_config = retry_policy.backoff_config.clone();
+            let max_attempts = retry_policy.max_attempts;
+            let store = Arc::clone(&store);
+            let path = f.object_store_path();
+
+            async move {
+                let mut attempt = 0;
+                let exists = Backoff::new(&backoff_config)
+                    .retry_with_backoff("check file exists", || {
+                        attempt += 1;
+                        let attempt = attempt;
+                        let (store, path) = (&store, &path);
+
+                        async move {
+                            match store.head(path).await {
+                                Ok(_) => ControlFlow::Break(Ok(true)),
+                                Err(object_store::Error::NotFound { .. }) => {
+                                    ControlFlow::Break(Ok(false))
+                                }
+                                Err(e)
+                                    if max_attempts.map_or(false, |max| attempt >= max.get()) =>
+                                {
+                                    warn!(%path, %e, attempt, "giving up checking file");
+                                    ControlFlow::Break(Err(ScratchpadError::TransferFailed {
+                                        location: path.to_string(),
+                                        attempts: attempt,
+                                        error: e.to_string(),
+                                        source_failed: false,
+                                    }))
+                                }
+                                Err(e) => {
+                                    warn!(%path, %e, attempt, "failed to check file, retrying");
+                                    retries.fetch_add(1, Ordering::Relaxed);
+                                    ControlFlow::Continue(e)
+                                }
+                            }
+                        }
+                    })
+                    .await
+                    .expect("retry forever")?;
+                Ok(exists.then_some(f))
+            }
+        })
+        .buffered(concurrency.get())
+        .try_filter_map(futures::future::ok)
+        .try_collect()
+        .await
+}
+
+/// Delete files from a store, returning the files that could not be deleted.
+///
+/// Each file is tried up to `max_attempts` times. Files that do not exist are considered deleted.
 pub async fn delete_files(
     files: &[ParquetFilePath],
     store: Arc<DynObjectStore>,
     backoff_config: &BackoffConfig,
     concurrency: NonZeroUsize,
-) {
+    max_attempts: usize,
+) -> Vec<ParquetFilePath> {
     // Note: `files.to_vec()` is required to avoid rustc freaking out about lifetimes
     futures::stream::iter(files.to_vec())
         .map(|f| {
@@ -51,13 +278,34 @@ pub async fn delete_files(
             let path = f.object_store_path();
 
             async move {
-                Backoff::new(&backoff_config)
-                    .retry_all_errors("delete file", || async { store.delete(&path).await })
+                let mut attempt = 0;
+                let deleted = Backoff::new(&backoff_config)
+                    .retry_with_backoff("delete file", || {
+                        attempt += 1;
+                        let attempt = attempt;
+                        let store = &store;
+                        let path = &path;
+
+                        async move {
+                            match store.delete(path).await {
+                                Ok(()) | Err(object_store::Error::NotFound { .. }) => {
+                                    ControlFlow::Break(true)
+                                }
+                                Err(e) if attempt >= max_attempts => {
+                                    warn!(%path, %e, attempt, "giving up deleting file");
+                                    ControlFlow::Break(false)
+                                }
+                                Err(e) => ControlFlow::Continue(e),
+                            }
+                        }
+                    })
                     .await
-                    .expect("retry forever")
+                    .expect("retry forever");
+                (!deleted).then_some(f)
             }
         })
         .buffer_unordered(concurrency.get())
-        .collect::<()>()
-        .await;
+        .filter_map(futures::future::ready)
+        .collect()
+        .await
 }
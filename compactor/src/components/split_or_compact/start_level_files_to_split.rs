@@ -5,6 +5,7 @@ use observability_deps::tracing::debug;
 use crate::{
     components::files_split::{target_level_split::TargetLevelSplit, FilesSplit},
     file_classification::FileToSplit,
+    round_info::{SplitTime, SplitTimeSource},
 };
 
 // selectSplitTimes returns an appropriate sets of split times to divide the given time range into,
@@ -24,6 +25,21 @@ pub fn select_split_times(
     max_time: i64,
     split_hint: Vec<i64>,
 ) -> Vec<i64> {
+    select_split_times_with_sources(cap, max_compact_size, min_time, max_time, split_hint)
+        .into_iter()
+        .map(|split_time| split_time.time)
+        .collect()
+}
+
+// select_split_times_with_sources is select_split_times, also telling whether each split time was aligned
+// to a hint or evenly spaced.
+pub fn select_split_times_with_sources(
+    cap: usize,
+    max_compact_size: usize,
+    min_time: i64,
+    max_time: i64,
+    split_hint: Vec<i64>,
+) -> Vec<SplitTime> {
     if min_time == max_time {
         // can't split below 1 ns.
         return vec![];
@@ -71,17 +87,18 @@ pub fn select_split_times(
             hint_idx += 1;
         }
 
-        split_time = if hint_idx < split_hint.len() && split_hint[hint_idx] < split_time + max_delta
-        {
-            // The next hint is close enough to the next split that we'll use it instead of the computed split.
-            split_hint[hint_idx]
-        } else {
-            // There is no next hint, or its too far away, so go with the default.
-            default_next
-        };
+        let (next_split_time, source) =
+            if hint_idx < split_hint.len() && split_hint[hint_idx] < split_time + max_delta {
+                // The next hint is close enough to the next split that we'll use it instead of the computed split.
+                (split_hint[hint_idx], SplitTimeSource::Hint)
+            } else {
+                // There is no next hint, or its too far away, so go with the default.
+                (default_next, SplitTimeSource::EvenlySpaced)
+            };
+        split_time = next_split_time;
 
         if split_time < max_time {
-            split_times.push(split_time);
+            split_times.push(SplitTime::new(split_time, source));
         }
     }
 
@@ -533,6 +550,23 @@ mod tests {
         assert!(split_times == vec![1, 2]);
     }
 
+    #[test]
+    fn test_select_split_times_with_sources() {
+        use crate::round_info::{SplitTime, SplitTimeSource::*};
+
+        // hints close to the default splits are used, the others are ignored.
+        let split_times = super::select_split_times_with_sources(299, 100, 0, 100, vec![43, 95]);
+        assert_eq!(
+            split_times,
+            vec![
+                SplitTime::new(20, EvenlySpaced),
+                SplitTime::new(43, Hint),
+                SplitTime::new(63, EvenlySpaced),
+                SplitTime::new(83, EvenlySpaced),
+            ]
+        );
+    }
+
     #[test]
     fn test_split_empty() {
         let files = vec![];
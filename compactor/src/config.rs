@@ -8,7 +8,10 @@ use iox_query::exec::Executor;
 use iox_time::TimeProvider;
 use parquet_file::storage::ParquetStorage;
 
-use crate::components::parquet_files_sink::ParquetFilesSink;
+use crate::components::{
+    parquet_files_sink::ParquetFilesSink, partition_files_source::rate_limit::RateLimitConfig,
+    round_info_source::decision_log::RoundDecisionLog, scratchpad::ScratchpadCapacityPolicy,
+};
 
 /// Multiple from `max_desired_file_size_bytes` to compute the minimum value for
 /// `max_compact_size_bytes`. Since `max_desired_file_size_bytes` is softly enforced, actual file
@@ -130,11 +133,193 @@ pub struct Config {
     /// max number of files per compaction plan
     pub max_num_files_per_plan: usize,
 
-    /// Limit the number of partition fetch queries to at most the specified
+    /// Multiple of the max compact size that the L1 bytes of a partition must exceed before L1s
+    /// are compacted to L2 while there is still a backlog of L0s.
+    ///
+    /// Setting this very high effectively disables the early L1->L2 compaction.
+    pub early_l1_to_l2_size_multiplier: f64,
+
+    /// Whether L1s may be compacted to L2 while there is still a backlog of L0s.  When disabled,
+    /// compaction always starts with the L0s, if there are any.
+    pub allow_early_l1_to_l2: bool,
+
+    /// When set, partitions whose files all fit in a single compaction plan and that have not
+    /// ingested any data for at least this long are compacted straight to L2 in a single round.
+    pub cold_compaction_threshold: Option<Duration>,
+
+    /// Align vertical split times to the edges of L2 files as well as L1 files.
+    pub vertical_split_l2_hints: bool,
+
+    /// Maximum number of split times a single vertical split round may use.  The remaining
+    /// splitting is done in later rounds.
+    pub max_split_times_per_round: Option<NonZeroUsize>,
+
+    /// Count the next level files overlapping each chain of start level files separately when deciding
+    /// whether a round has too many small files, rather than counting against the time range of all
+    /// start level files.
+    pub per_chain_overlaps: bool,
+
+    /// Drop files that only contain data older than the namespace retention period from compaction,
+    /// leaving them for the garbage collector to delete.
+    pub drop_expired_files: bool,
+
+    /// When set, the plan size limit of a partition is reduced so the estimated in-memory (Arrow) size
+    /// of each plan stays within this many bytes.  See `arrow_expansion_per_column`.
+    pub max_estimated_arrow_bytes_per_plan: Option<usize>,
+
+    /// How much the in-memory size of a partition's data grows, relative to its parquet size, per column
+    /// of its table.  Used with `max_estimated_arrow_bytes_per_plan`.
+    pub arrow_expansion_per_column: f64,
+
+    /// Maximum number of branches a single compaction round may compact.  The branches with the most
+    /// recently created data are left for later rounds.
+    pub max_branches_per_round: Option<NonZeroUsize>,
+
+    /// When set, many small files rounds never group L0 files whose `max_l0_created_at` fall in
+    /// different windows of this width, so output files don't mix data ingested at very different times.
+    pub many_small_files_window: Option<Duration>,
+
+    /// When set, the round decisions of the compactor are recorded here, so they can be inspected
+    /// after the fact.
+    pub round_decision_log: Option<Arc<RoundDecisionLog>>,
+
+    /// When set, the compaction of a partition is aborted once the same type of round is chosen for the
+    /// same set of files more than this many times, since it is not making progress.
+    pub max_repeated_rounds: Option<usize>,
+
+    /// Lower bound of `max_total_file_size_per_plan` when it adapts to the output ratio observed in the
+    /// previous round of a partition.  Adapting is enabled when both bounds are set.
+    pub adaptive_file_size_per_plan_floor: Option<usize>,
+
+    /// Upper bound of `max_total_file_size_per_plan` when it adapts to the output ratio observed in the
+    /// previous round of a partition.  Adapting is enabled when both bounds are set.
+    pub adaptive_file_size_per_plan_ceiling: Option<usize>,
+
+    /// When set, start level files averaging more rows than implied by this many rows per plan are not
+    /// considered small files, even if their size in bytes is.
+    pub max_total_rows_per_plan: Option<usize>,
+
+    /// When set, rounds compacting to a target level compact each bucket of time of this width in its own
+    /// branch, so output files line up with the time ranges queries typically ask for.
+    pub time_bucket: Option<Duration>,
+
+    /// Maximum number of bytes staged in the scratchpad from the input store, across all partitions
+    /// compacting at once.
+    ///
+    /// If not set, the scratchpad is unbounded.  If set, prefetching is disabled.
+    pub scratchpad_capacity_bytes: Option<usize>,
+
+    /// What to do when files to stage exceed the remaining scratchpad capacity.
+    ///
+    /// Only used if `scratchpad_capacity_bytes` is set.
+    pub scratchpad_capacity_policy: ScratchpadCapacityPolicy,
+
+    /// Maximum number of files copied into or out of the scratchpad at once, across all partitions.
+    ///
+    /// If not set, only `partition_scratchpad_concurrency` limits the copies.
+    pub scratchpad_max_concurrent_transfers: Option<NonZeroUsize>,
+
+    /// Keep the files written by compaction in the scratchpad for the next round of the partition,
+    /// instead of downloading them again.
+    pub scratchpad_keep_written: bool,
+
+    /// Files of a load totaling less than this many bytes are read directly from the input store
+    /// instead of being copied to the scratchpad.
+    ///
+    /// The store the compaction reads the scratchpad from must fall back to the input store, see
+    /// [`ReadThroughStore`](crate::object_store::read_through::ReadThroughStore). If not set, all
+    /// files are copied.
+    pub scratchpad_read_through_bytes: Option<usize>,
+
+    /// Attempts to copy each file into or out of the scratchpad before failing the compaction, with
+    /// [`backoff_config`](Self::backoff_config) between the attempts.
+    ///
+    /// If not set, copies are retried forever.
+    pub scratchpad_transfer_max_attempts: Option<NonZeroUsize>,
+
+    /// Consecutive failed transfers into or out of the scratchpad after which new scratchpads stop
+    /// staging files for [`scratchpad_breaker_cooldown`](Self::scratchpad_breaker_cooldown), and read
+    /// them from the input store instead.  Only failures of the scratchpad store count.
+    ///
+    /// Requires [`scratchpad_transfer_max_attempts`](Self::scratchpad_transfer_max_attempts).  If
+    /// not set, files are always staged.
+    pub scratchpad_breaker_failures: Option<NonZeroUsize>,
+
+    /// How long new scratchpads stop staging files once
+    /// [`scratchpad_breaker_failures`](Self::scratchpad_breaker_failures) is reached.
+    pub scratchpad_breaker_cooldown: Duration,
+
+    /// Cache the files of each partition for this long, instead of fetching them from the catalog for
+    /// every round. The files of a partition are fetched again once the compactor commits changes to it.
+    ///
+    /// If not set, files are not cached.
+    pub partition_files_cache_ttl: Option<Duration>,
+
+    /// Maximum number of partitions whose files are cached, see
+    /// [`partition_files_cache_ttl`](Self::partition_files_cache_ttl).
+    pub partition_files_cache_max_entries: NonZeroUsize,
+
+    /// Give up fetching the files of a partition from the catalog after this time, retries included.
+    /// The partition is skipped and compacted again later.
+    ///
+    /// If not set, fetches are retried forever.
+    pub partition_files_fetch_timeout: Option<Duration>,
+
+    /// Maximum number of partitions whose files are fetched from the catalog in a single query, when
+    /// fetching the files of many partitions at once.
+    pub max_partitions_per_fetch_query: NonZeroUsize,
+
+    /// Drop files at the final level larger than this, which overlap no other file of their partition,
+    /// right when the files of a partition are fetched. Such files can never take part in a compaction.
+    ///
+    /// If not set, no files are dropped.
+    pub partition_files_filter_max_file_size_bytes: Option<u64>,
+
+    /// For this long after a commit, the files of the partition committed to reflect the commit, even
+    /// if the catalog still returns the files as they were before it, e.g. when reading from a replica.
+    ///
+    /// If not set, the files are taken from the catalog as is.
+    pub partition_files_commit_window: Option<Duration>,
+
+    /// Minimum time between two fetch queries for the files of the same partition, however many
+    /// queries the rate limit allows, so that a partition that keeps being compacted doesn't use up the
+    /// queries of the others.
+    ///
+    /// Only used if [`partition_fetch_rate_limit`](Self::partition_fetch_rate_limit) is set.
+    pub partition_fetch_min_interval: Option<Duration>,
+
+    /// Number of files of a partition fetched from the catalog per query, paging through the files of
+    /// large partitions.
+    ///
+    /// If `None`, all files of a partition are fetched with a single query.
+    pub partition_files_page_size: Option<NonZeroUsize>,
+
+    /// Maximum number of L0 files compacted for a partition. Only the oldest L0 files (by
+    /// `max_l0_created_at`) of a partition with more L0 files are compacted, together with all its
+    /// L1 and L2 files, until it has fewer.
+    ///
+    /// If `None`, all files of a partition are compacted.
+    pub partition_files_max_files: Option<NonZeroUsize>,
+
+    /// Prefetch the files of the partitions of fetched compaction jobs in the background, and use the
+    /// prefetched files for this long.
+    ///
+    /// If not set, files are fetched once the compaction of a partition starts.
+    pub partition_files_prefetch_ttl: Option<Duration>,
+
+    /// Maximum number of batches of fetched compaction jobs whose files are prefetched at a time,
+    /// see [`partition_files_prefetch_ttl`](Self::partition_files_prefetch_ttl). The files of the
+    /// partitions of a batch are fetched together.
+    pub partition_files_prefetch_concurrency: NonZeroUsize,
+
+    /// Limit the number of partition fetch queries to at most the configured
     /// number of queries per second.
     ///
-    /// Queries are smoothed over the full second.
-    pub max_partition_fetch_queries_per_second: Option<usize>,
+    /// Queries are smoothed over the full second. The rate can be changed while the compactor
+    /// runs, see [`Compactor::partition_fetch_rate_limit`].
+    ///
+    /// [`Compactor::partition_fetch_rate_limit`]: crate::compactor::Compactor::partition_fetch_rate_limit
+    pub partition_fetch_rate_limit: Option<Arc<RateLimitConfig>>,
 }
 
 impl Config {
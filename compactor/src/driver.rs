@@ -5,7 +5,7 @@ use compactor_scheduler::CompactionJob;
 use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
 use futures::{stream, StreamExt, TryStreamExt};
 use iox_query::exec::query_tracing::send_metrics_to_tracing;
-use observability_deps::tracing::info;
+use observability_deps::tracing::{debug, info, warn};
 use parquet_file::ParquetFilePath;
 use tokio::sync::watch::Sender;
 use trace::span::Span;
@@ -15,7 +15,8 @@ use tracker::InstrumentedAsyncSemaphore;
 use crate::{
     components::{
         changed_files_filter::SavedParquetFileState,
-        scratchpad::Scratchpad,
+        partition_files_source::PartitionFiles,
+        scratchpad::{Scratchpad, ScratchpadCleanSummary, ScratchpadError},
         timeout::{timeout_with_progress_checking, TimeoutWithProgress},
         Components,
     },
@@ -71,7 +72,9 @@ async fn compact_partition(
     let partition_id = job.partition_id;
     info!(partition_id = partition_id.get(), timeout = ?partition_timeout, "compact partition",);
     span.set_metadata("partition_id", partition_id.get().to_string());
-    let scratchpad = components.scratchpad_gen.pad();
+    let scratchpad = components
+        .scratchpad_gen
+        .pad(span.span().map(|span| span.ctx.clone()));
 
     info!(partition_id = partition_id.get(), "compaction job starting");
 
@@ -113,10 +116,46 @@ async fn compact_partition(
     // TODO: how handle errors detected in the CompactionJob ending actions?
     let _ = components.compaction_job_done_sink.record(job, res).await;
 
-    scratchpad.clean().await;
+    components.output_ratio_tracker.forget(partition_id);
+    log_scratchpad_clean(partition_id, scratchpad.clean().await);
     info!(partition_id = partition_id.get(), "compaction job done",);
 }
 
+/// Log what was cleaned from the scratchpad.
+///
+/// Files that could not be deleted from the scratchpad are leaked, but the compaction itself is
+/// fine, so only log them.
+fn log_scratchpad_clean(
+    partition_id: PartitionId,
+    res: Result<ScratchpadCleanSummary, ScratchpadError>,
+) {
+    match res {
+        Ok(summary) => {
+            debug!(
+                partition_id = partition_id.get(),
+                files = summary.files,
+                bytes = summary.bytes,
+                leaked = summary.leaked.len(),
+                "cleaned scratchpad",
+            );
+            if !summary.leaked.is_empty() {
+                warn!(
+                    partition_id = partition_id.get(),
+                    leaked = ?summary.leaked,
+                    "leaked files in the scratchpad",
+                );
+            }
+        }
+        Err(e) => {
+            warn!(
+                partition_id = partition_id.get(),
+                %e,
+                "failed to clean the scratchpad",
+            );
+        }
+    }
+}
+
 /// Main function to compact files of a single partition.
 ///
 /// Input: any files in the partitions (L0s, L1s, L2s)
@@ -212,8 +251,29 @@ async fn try_compact_partition(
     transmit_progress_signal: Sender<bool>,
 ) -> Result<(), DynError> {
     let partition_id = job.partition_id;
-    let mut files = components.partition_files_source.fetch(partition_id).await;
-    let partition_info = components.partition_info_source.fetch(partition_id).await?;
+    let PartitionFiles {
+        mut files,
+        partition,
+        truncated,
+    } = components
+        .partition_files_source
+        .fetch_with_partition_info(partition_id)
+        .await?;
+    let mut partition_info = components
+        .partition_info_source
+        .fetch_for_partition(partition)
+        .await?;
+    if truncated {
+        info!(
+            partition_id = partition_id.get(),
+            file_count = files.len(),
+            "compacting the oldest L0 files of partition with too many L0 files"
+        );
+        partition_info = Arc::new(PartitionInfo {
+            files_truncated: true,
+            ..(*partition_info).clone()
+        });
+    }
     let transmit_progress_signal = Arc::new(transmit_progress_signal);
     let mut last_round_info: Option<RoundInfo> = None;
 
@@ -233,6 +293,14 @@ async fn try_compact_partition(
             return Ok(());
         }
 
+        // files of previous rounds kept in the scratchpad are only useful if they're still in the
+        // partition
+        let paths = files.iter().map(ParquetFilePath::from).collect::<Vec<_>>();
+        log_scratchpad_clean(
+            partition_info.partition_id,
+            scratchpad_ctx.retain_only(&paths).await,
+        );
+
         // This is the stop condition which will be different for different version of compaction
         // and describe where the filter is created at version_specific_partition_filters function
         if !components
@@ -253,6 +321,16 @@ async fn try_compact_partition(
             )
             .await?;
 
+        if branches.is_empty() && files_later.is_empty() {
+            // nothing to compact now or later, e.g. the round was only planned (dry run)
+            info!(
+                partition_id = partition_info.partition_id.get(),
+                %round_info,
+                "round leaves nothing to compact, partition done",
+            );
+            return Ok(());
+        }
+
         files = files_later;
 
         info!(
@@ -262,9 +340,20 @@ async fn try_compact_partition(
             "compacting branches concurrently",
         );
 
+        // while a branch compacts, prefetch the files of the branch that starts once it's done
+        let branch_concurrency = df_semaphore.total_permits();
+        let branch_paths: Vec<Vec<ParquetFilePath>> = branches
+            .iter()
+            .map(|branch| branch.iter().map(ParquetFilePath::from).collect())
+            .collect();
+
         // concurrently run the branches.
-        let branches_output: Vec<Vec<ParquetFile>> = stream::iter(branches.into_iter())
-            .map(|branch| {
+        let branches_output: Vec<Vec<ParquetFile>> = stream::iter(branches.into_iter().enumerate())
+            .map(|(i, branch)| {
+                if let Some(paths) = branch_paths.get(i + branch_concurrency) {
+                    scratchpad_ctx.prefetch(paths);
+                }
+
                 let partition_info = Arc::clone(&partition_info);
                 let components = Arc::clone(&components);
                 let df_semaphore = Arc::clone(&df_semaphore);
@@ -289,7 +378,7 @@ async fn try_compact_partition(
                     .await
                 }
             })
-            .buffer_unordered(df_semaphore.total_permits())
+            .buffer_unordered(branch_concurrency)
             .try_collect()
             .await?;
 
@@ -349,7 +438,8 @@ async fn execute_branch(
     } = files_to_make_progress_on;
 
     let paths = split_or_compact.file_input_paths();
-    let object_store_ids = scratchpad_ctx.uuids(&paths);
+    // the IDs in the scratchpad are only known once the files are loaded, see `run_plans`
+    let object_store_ids = paths.iter().map(|p| p.objest_store_id()).collect();
     let plans = components.ir_planner.create_plans(
         Arc::clone(&partition_info),
         target_level,
@@ -374,10 +464,14 @@ async fn execute_branch(
             .take(df_semaphore.total_permits() * 4)
             .collect();
 
-        let files_to_delete = chunk
+        let files_to_delete: Vec<ParquetFile> = chunk
             .iter()
             .flat_map(|plan| plan.input_parquet_files())
             .collect();
+        let input_bytes: usize = files_to_delete
+            .iter()
+            .map(|f| f.file_size_bytes as usize)
+            .sum();
 
         // Compact & Split
         let created_file_params = run_plans(
@@ -396,7 +490,7 @@ async fn execute_branch(
             created_file_params,
             Arc::<dyn Scratchpad>::clone(&scratchpad_ctx),
         )
-        .await;
+        .await?;
         drop(upload_span);
 
         for file_param in &created_file_params {
@@ -408,15 +502,24 @@ async fn execute_branch(
             );
         }
 
+        let output_bytes: usize = created_file_params
+            .iter()
+            .map(|p| p.file_size_bytes as usize)
+            .sum();
+
         let created_file_paths: Vec<ParquetFilePath> = created_file_params
             .iter()
             .map(ParquetFilePath::from)
             .collect();
 
-        // conditionally (if not shaddow mode) remove the newly created files from the scratchpad.
-        scratchpad_ctx
-            .clean_written_from_scratchpad(&created_file_paths)
-            .await;
+        // conditionally (if not shaddow mode, nor kept for the next round) remove the newly created
+        // files from the scratchpad.
+        log_scratchpad_clean(
+            partition_info.partition_id,
+            scratchpad_ctx
+                .clean_written_from_scratchpad(&created_file_paths)
+                .await,
+        );
 
         // Update the catalog to reflect the newly created files, soft delete the compacted
         // files and update the upgraded files
@@ -431,6 +534,13 @@ async fn execute_branch(
         )
         .await?;
 
+        // Let the following rounds know how much this data compacts.
+        components.output_ratio_tracker.record(
+            partition_info.partition_id,
+            input_bytes,
+            output_bytes,
+        );
+
         // we only need to upgrade files on the first iteration, so empty the upgrade list for next loop.
         upgrade = Vec::new();
 
@@ -453,7 +563,7 @@ async fn execute_branch(
 /// Compact or split given files
 async fn run_plans(
     span: SpanRecorder,
-    plans: Vec<PlanIR>,
+    mut plans: Vec<PlanIR>,
     partition_info: &Arc<PartitionInfo>,
     components: &Arc<Components>,
     df_semaphore: Arc<InstrumentedAsyncSemaphore>,
@@ -464,9 +574,17 @@ async fn run_plans(
     // stage files.  This could move to execute_plan to reduce peak scratchpad memory use, but that would
     // cost some concurrency in object downloads.
     let download_span = span.child("download_objects");
-    let _ = scratchpad_ctx.load_to_scratchpad(&paths).await;
+    scratchpad_ctx.load_to_scratchpad(&paths).await?;
     drop(download_span);
 
+    // compact the copies in the scratchpad, or the originals of the files read through
+    for plan in &mut plans {
+        let object_store_ids = scratchpad_ctx.uuids(&plan.input_paths())?;
+        for (file_ir, object_store_id) in plan.input_files_mut().iter_mut().zip(object_store_ids) {
+            file_ir.file.object_store_id = object_store_id;
+        }
+    }
+
     info!(
         partition_id = partition_info.partition_id.get(),
         plan_count = plans.len(),
@@ -574,9 +692,12 @@ async fn execute_plan(
         drop(df_span);
 
         // inputs can be removed from the scratchpad as soon as we're done with compaction.
-        scratchpad_ctx
-            .clean_from_scratchpad(&plan_ir.input_paths())
-            .await;
+        log_scratchpad_clean(
+            partition_info.partition_id,
+            scratchpad_ctx
+                .clean_from_scratchpad(&plan_ir.input_paths())
+                .await,
+        );
 
         info!(
             partition_id = partition_info.partition_id.get(),
@@ -586,6 +707,14 @@ async fn execute_plan(
         res?
     };
 
+    // the outputs are in the scratchpad now
+    scratchpad_ctx.record_written(
+        &create
+            .iter()
+            .map(|p| p.into())
+            .collect::<Vec<ParquetFilePath>>(),
+    );
+
     span.set_metadata("output_files", create.len().to_string());
     span.set_metadata(
         "output_bytes",
@@ -602,28 +731,40 @@ async fn execute_plan(
 async fn upload_files_to_object_store(
     created_file_params: Vec<ParquetFileParams>,
     scratchpad_ctx: Arc<dyn Scratchpad>,
-) -> Vec<ParquetFileParams> {
+) -> Result<Vec<ParquetFileParams>, DynError> {
     // Upload files to real object store
     let output_files: Vec<ParquetFilePath> = created_file_params.iter().map(|p| p.into()).collect();
-    let output_uuids = scratchpad_ctx.make_public(&output_files).await;
+    let output_uuids = scratchpad_ctx.make_public(&output_files).await?;
 
     // Update file params with object_store_id
-    created_file_params
+    Ok(created_file_params
         .into_iter()
         .zip(output_uuids)
         .map(|(f, uuid)| ParquetFileParams {
             object_store_id: uuid,
             ..f
         })
-        .collect()
+        .collect())
 }
 
 async fn fetch_and_save_parquet_file_state(
     components: &Components,
     partition_id: PartitionId,
-) -> SavedParquetFileState {
-    let catalog_files = components.partition_files_source.fetch(partition_id).await;
-    SavedParquetFileState::from(&catalog_files)
+) -> Result<SavedParquetFileState, DynError> {
+    // Only the newest L0 files are missing from truncated files, which the branches compacted
+    // from the oldest ones don't contain.
+    let catalog_files = components
+        .partition_files_state_source
+        .fetch(partition_id)
+        .await?;
+    if catalog_files.truncated {
+        debug!(
+            partition_id = partition_id.get(),
+            file_count = catalog_files.files.len(),
+            "checking the state of a truncated partition before commit"
+        );
+    }
+    Ok(SavedParquetFileState::from(&catalog_files.files))
 }
 
 /// Update the catalog to create, soft delete and upgrade corresponding given input
@@ -640,14 +781,14 @@ async fn update_catalog(
 ) -> Result<(Vec<ParquetFile>, Vec<ParquetFile>), DynError> {
     let partition_id = job.partition_id;
     let current_parquet_file_state =
-        fetch_and_save_parquet_file_state(&components, partition_id).await;
+        fetch_and_save_parquet_file_state(&components, partition_id).await?;
 
     // Right now this only logs; in the future we might decide not to commit these changes
     let _ignore = components
         .changed_files_filter
         .apply(saved_parquet_file_state, &current_parquet_file_state);
 
-    let created_ids = components
+    let created_files = components
         .commit
         .commit(
             job,
@@ -658,13 +799,6 @@ async fn update_catalog(
         )
         .await?;
 
-    // Update created ids to their corresponding file params
-    let created_file_params = file_params_to_create
-        .into_iter()
-        .zip(created_ids)
-        .map(|(params, id)| ParquetFile::from_params(params, id))
-        .collect::<Vec<_>>();
-
     // Update compaction_level for the files_to_upgrade
     let upgraded_files = files_to_upgrade
         .into_iter()
@@ -674,7 +808,7 @@ async fn update_catalog(
         })
         .collect::<Vec<_>>();
 
-    Ok((created_file_params, upgraded_files))
+    Ok((created_files, upgraded_files))
 }
 
 // SINGLE_THREADED_COLUMN_COUNT is the number of columns requiring a partition be compacted single threaded.
@@ -708,7 +842,286 @@ fn compute_permits(
 
 #[cfg(test)]
 mod tests {
+    use data_types::ColumnType;
+    use iox_tests::{TestCatalog, TestParquetFileBuilder, TestPartition};
+    use iox_time::Time;
+    use object_store::{memory::InMemory, path::Path, DynObjectStore};
+    use parquet_file::storage::{ParquetStorage, StorageId};
+    use tokio::sync::watch;
+    use tracker::AsyncSemaphoreMetrics;
+
     use super::*;
+    use crate::{
+        components::{
+            hardcoded::hardcoded_components,
+            partition_files_source::mock::MockPartitionFilesSource,
+            round_info_source::{
+                dry_run::DryRunRoundInfoSource, mock::MockRoundInfoSource, LevelBasedRoundInfo,
+            },
+        },
+        error::ErrorKindExt,
+        test_utils::{test_components, test_config},
+    };
+
+    #[tokio::test]
+    async fn test_partition_files_fetch_error() {
+        let mut components = (*test_components()).clone();
+        components.partition_files_source = Arc::new(
+            MockPartitionFilesSource::new(Default::default(), Default::default())
+                .with_delay(Duration::from_millis(10))
+                .with_error(ErrorKind::Catalog, "timed out"),
+        );
+        let components = Arc::new(components);
+
+        let semaphore_metrics = Arc::new(AsyncSemaphoreMetrics::new(
+            &metric::Registry::new(),
+            &[("semaphore", "job")],
+        ));
+        let df_semaphore = Arc::new(semaphore_metrics.new_semaphore(1));
+        let scratchpad = components.scratchpad_gen.pad(None);
+        let (transmit_progress_signal, _rx) = watch::channel(false);
+
+        // the partition is skipped with an error, instead of waiting
+        let err = try_compact_partition(
+            SpanRecorder::new(None),
+            CompactionJob::new(PartitionId::new(1)),
+            df_semaphore,
+            components,
+            scratchpad,
+            transmit_progress_signal,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.classify(), ErrorKind::Catalog);
+        assert_eq!(err.to_string(), "timed out");
+    }
+
+    #[tokio::test]
+    async fn test_round_info_source_drives_rounds() {
+        let (catalog, partition) = partition_with_two_l0s().await;
+        let files = list_catalog_files(&catalog).await;
+
+        let mut config = test_config();
+        config.catalog = catalog.catalog();
+        let mut components = (*hardcoded_components(&config)).clone();
+        // defer every file to the next round, which then fails
+        let round_info_source = Arc::new(
+            MockRoundInfoSource::new(vec![(
+                RoundInfo::TargetLevel {
+                    target_level: CompactionLevel::FileNonOverlapped,
+                    max_total_file_size_to_group: 100,
+                    estimated_branch_output_bytes: vec![],
+                    level_stats: Default::default(),
+                },
+                vec![],
+                files.clone(),
+            )])
+            .with_error("round failed"),
+        );
+        components.round_info_source = Arc::clone(&round_info_source) as _;
+        let components = Arc::new(components);
+
+        let semaphore_metrics = Arc::new(AsyncSemaphoreMetrics::new(
+            &metric::Registry::new(),
+            &[("semaphore", "job")],
+        ));
+        let df_semaphore = Arc::new(semaphore_metrics.new_semaphore(1));
+        let scratchpad = components.scratchpad_gen.pad(None);
+        let (transmit_progress_signal, _rx) = watch::channel(false);
+
+        // the round error fails the partition...
+        let err = try_compact_partition(
+            SpanRecorder::new(None),
+            CompactionJob::new(partition.partition.id),
+            df_semaphore,
+            components,
+            scratchpad,
+            transmit_progress_signal,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "round failed");
+
+        // ...after the deferred files were handed to the next round, and nothing was compacted
+        let sorted = |mut files: Vec<ParquetFile>| {
+            files.sort_by_key(|f| f.id);
+            files
+        };
+        let calls = round_info_source
+            .calls()
+            .into_iter()
+            .map(sorted)
+            .collect::<Vec<_>>();
+        assert_eq!(calls, vec![files.clone(), files.clone()]);
+        assert_eq!(list_catalog_files(&catalog).await, files);
+    }
+
+    #[tokio::test]
+    async fn test_state_before_commit_bypasses_cache() {
+        let (catalog, partition) = partition_with_two_l0s().await;
+        let partition_id = partition.partition.id;
+        let files = list_catalog_files(&catalog).await;
+
+        let mut config = test_config();
+        config.catalog = catalog.catalog();
+        config.partition_files_cache_ttl = Some(Duration::from_secs(3_600));
+        config.partition_files_prefetch_ttl = Some(Duration::from_secs(3_600));
+        let components = hardcoded_components(&config);
+        assert_eq!(
+            components
+                .partition_files_source
+                .fetch(partition_id)
+                .await
+                .unwrap()
+                .files
+                .len(),
+            2
+        );
+
+        // another process deletes a file
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .create_upgrade_delete(&[files[0].id], &[], &[], CompactionLevel::Initial)
+            .await
+            .unwrap();
+
+        // the cached files still have it, the state before a commit does not
+        assert_eq!(
+            components
+                .partition_files_source
+                .fetch(partition_id)
+                .await
+                .unwrap()
+                .files
+                .len(),
+            2
+        );
+        let state = fetch_and_save_parquet_file_state(&components, partition_id)
+            .await
+            .unwrap();
+        assert!(SavedParquetFileState::from(&files).existing_files_modified(&state));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_has_no_side_effects() {
+        let (catalog, partition) = partition_with_two_l0s().await;
+
+        let scratchpad_store: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let mut config = test_config();
+        config.catalog = catalog.catalog();
+        config.parquet_store_real =
+            ParquetStorage::new(catalog.object_store(), StorageId::from("iox"));
+        config.parquet_store_scratchpad =
+            ParquetStorage::new(Arc::clone(&scratchpad_store), StorageId::from("scratchpad"));
+        config.simulate_without_object_store = false;
+        let mut components = (*hardcoded_components(&config)).clone();
+        let dry_run = Arc::new(DryRunRoundInfoSource::new(LevelBasedRoundInfo::new(
+            10,
+            100 * 1024 * 1024,
+            3.0,
+            true,
+        )));
+        components.round_info_source = Arc::clone(&dry_run) as _;
+        let components = Arc::new(components);
+
+        let catalog_files_before = list_catalog_files(&catalog).await;
+        let store_before = list_store(&catalog.object_store()).await;
+        assert_eq!(catalog_files_before.len(), 2);
+
+        let semaphore_metrics = Arc::new(AsyncSemaphoreMetrics::new(
+            &metric::Registry::new(),
+            &[("semaphore", "job")],
+        ));
+        let df_semaphore = Arc::new(semaphore_metrics.new_semaphore(1));
+        let scratchpad = components.scratchpad_gen.pad(None);
+        let (transmit_progress_signal, _rx) = watch::channel(false);
+
+        // the partition is done without error...
+        try_compact_partition(
+            SpanRecorder::new(None),
+            CompactionJob::new(partition.partition.id),
+            df_semaphore,
+            components,
+            scratchpad,
+            transmit_progress_signal,
+        )
+        .await
+        .unwrap();
+
+        // ...after planning a single round that would compact both files...
+        let plans = dry_run.plans();
+        assert_eq!(plans.len(), 1);
+        assert_eq!(
+            plans[0].branches.iter().map(|b| b.len()).sum::<usize>(),
+            2,
+            "{plans:?}"
+        );
+
+        // ...but nothing was committed, written or even staged
+        assert_eq!(list_catalog_files(&catalog).await, catalog_files_before);
+        assert_eq!(list_store(&catalog.object_store()).await, store_before);
+        assert!(list_store(&scratchpad_store).await.is_empty());
+    }
+
+    /// A partition with two overlapping L0 files.
+    async fn partition_with_two_l0s() -> (Arc<TestCatalog>, Arc<TestPartition>) {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns.create_table("table").await;
+        table.create_column("field_int", ColumnType::I64).await;
+        table.create_column("tag1", ColumnType::Tag).await;
+        table.create_column("time", ColumnType::Time).await;
+        let partition = table.create_partition("2022-07-13").await;
+        for (i, lp) in [
+            "table,tag1=WA field_int=1000i 8000",
+            "table,tag1=VT field_int=10i 10000",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            partition
+                .create_parquet_file(
+                    TestParquetFileBuilder::default()
+                        .with_line_protocol(lp)
+                        .with_min_time(8000)
+                        .with_max_time(10000)
+                        .with_max_l0_created_at(Time::from_timestamp_nanos(i as i64 + 1)),
+                )
+                .await;
+        }
+        (catalog, partition)
+    }
+
+    /// All files in the catalog, including soft deleted ones, ordered by ID.
+    async fn list_catalog_files(catalog: &TestCatalog) -> Vec<ParquetFile> {
+        let mut files = catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .list_all()
+            .await
+            .unwrap();
+        files.sort_by_key(|f| f.id);
+        files
+    }
+
+    /// All paths in the object store, ordered.
+    async fn list_store(store: &Arc<DynObjectStore>) -> Vec<Path> {
+        let mut paths = store
+            .list(None)
+            .await
+            .unwrap()
+            .map_ok(|f| f.location)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        paths.sort();
+        paths
+    }
 
     #[test]
     fn concurrency_limits() {
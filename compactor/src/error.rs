@@ -1,5 +1,6 @@
 //! Error handling.
 
+use crate::components::scratchpad::ScratchpadError;
 use compactor_scheduler::ErrorKind as SchedulerErrorKind;
 use datafusion::{arrow::error::ArrowError, error::DataFusionError, parquet::errors::ParquetError};
 use object_store::Error as ObjectStoreError;
@@ -24,6 +25,11 @@ pub enum ErrorKind {
     /// Partition took too long.
     Timeout,
 
+    /// Could not access the catalog in time.
+    ///
+    /// The partition is compacted again later.
+    Catalog,
+
     /// Unknown/unexpected error.
     ///
     /// This will likely mark the affected partition as "skipped" and the compactor will no longer touch it.
@@ -37,6 +43,7 @@ impl ErrorKind {
             Self::ObjectStore,
             Self::OutOfMemory,
             Self::Timeout,
+            Self::Catalog,
             Self::Unknown,
         ]
     }
@@ -47,6 +54,7 @@ impl ErrorKind {
             Self::ObjectStore => "object_store",
             Self::OutOfMemory => "out_of_memory",
             Self::Timeout => "timeout",
+            Self::Catalog => "catalog",
             Self::Unknown => "unknown",
         }
     }
@@ -58,6 +66,8 @@ impl From<ErrorKind> for SchedulerErrorKind {
             ErrorKind::ObjectStore => Self::ObjectStore,
             ErrorKind::OutOfMemory => Self::OutOfMemory,
             ErrorKind::Timeout => Self::Timeout,
+            // the scheduler has no such kind
+            ErrorKind::Catalog => Self::Unknown("catalog".into()),
             ErrorKind::Unknown => Self::Unknown("".into()),
         }
     }
@@ -166,6 +176,20 @@ impl ErrorKindExt for SimpleError {
     }
 }
 
+impl ErrorKindExt for ScratchpadError {
+    fn classify(&self) -> ErrorKind {
+        match self {
+            // the files of the plans don't fit in memory, retry with fewer of them
+            Self::CapacityExceeded { .. } => ErrorKind::OutOfMemory,
+            Self::IntegrityCheckFailed { .. } | Self::TransferFailed { .. } => {
+                ErrorKind::ObjectStore
+            }
+            // a bug, retrying won't help
+            Self::UnknownFiles { .. } => ErrorKind::Unknown,
+        }
+    }
+}
+
 macro_rules! dispatch_body {
     ($self:ident) => {
         if let Some(e) = $self.downcast_ref::<ArrowError>() {
@@ -204,6 +228,8 @@ macro_rules! dispatch_body {
             e.as_ref().classify()
         } else if let Some(e) = $self.downcast_ref::<Box<SimpleError>>() {
             e.as_ref().classify()
+        } else if let Some(e) = $self.downcast_ref::<ScratchpadError>() {
+            e.classify()
         } else if let Some(e) = $self.downcast_ref::<Arc<dyn std::error::Error>>() {
             e.as_ref().classify()
         } else if let Some(e) = $self.downcast_ref::<Arc<dyn std::error::Error + Send + Sync>>() {
@@ -359,6 +385,22 @@ mod tests {
                 .classify(),
             ErrorKind::Timeout,
         );
+        assert_eq!(
+            (Box::new(ScratchpadError::CapacityExceeded {
+                requested_bytes: 2,
+                staged_bytes: 1,
+                capacity_bytes: 2,
+            }) as Box<dyn std::error::Error>)
+                .classify(),
+            ErrorKind::OutOfMemory,
+        );
+        assert_eq!(
+            (Box::new(ScratchpadError::UnknownFiles {
+                files: vec![String::from("foo")],
+            }) as Box<dyn std::error::Error>)
+                .classify(),
+            ErrorKind::Unknown,
+        );
 
         // dyn downcast in Arc
         assert_eq!(
@@ -221,14 +221,22 @@ mod round_info;
 
 // publically expose items needed for testing
 pub use components::{
-    df_planner::panic::PanicDataFusionPlanner, hardcoded::hardcoded_components,
-    namespaces_source::mock::NamespaceWrapper, parquet_files_sink::ParquetFilesSink, Components,
+    df_planner::panic::PanicDataFusionPlanner,
+    hardcoded::hardcoded_components,
+    namespaces_source::mock::NamespaceWrapper,
+    parquet_files_sink::ParquetFilesSink,
+    partition_files_source::rate_limit::RateLimitConfig,
+    round_info_source::decision_log::{RoundDecision, RoundDecisionLog},
+    scratchpad::{ScratchpadCapacityPolicy, ScratchpadError},
+    Components,
 };
 pub use driver::compact;
 pub use error::DynError;
 pub use partition_info::PartitionInfo;
 pub use plan_ir::PlanIR;
-pub use round_info::RoundInfo;
+pub use round_info::{
+    LevelStat, LevelStats, RoundInfo, SplitTime, SplitTimeProvenance, SplitTimeSource,
+};
 
 #[cfg(test)]
 mod test_utils;
@@ -2,3 +2,5 @@
 
 pub mod ignore_writes;
 pub mod metrics;
+pub mod read_through;
+pub mod write_through;
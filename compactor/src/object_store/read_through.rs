@@ -0,0 +1,153 @@
+//! Wrapper that reads missing files from another store.
+use std::{fmt::Display, ops::Range, sync::Arc};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::{
+    path::Path, DynObjectStore, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, Result,
+};
+use tokio::io::AsyncWrite;
+
+/// Store that reads files missing from an inner store from a fallback store.
+///
+/// All other requests only go to the inner store, so the fallback store is never modified.
+///
+/// This lets the compaction read files that the scratchpad reads through from the input store
+/// (i.e. without copying them) as if they were in the scratchpad store.
+#[derive(Debug)]
+pub struct ReadThroughStore {
+    inner: Arc<DynObjectStore>,
+    fallback: Arc<DynObjectStore>,
+}
+
+impl ReadThroughStore {
+    /// Creates new store that reads from `fallback` what is not in `inner`.
+    pub fn new(inner: Arc<DynObjectStore>, fallback: Arc<DynObjectStore>) -> Self {
+        Self { inner, fallback }
+    }
+}
+
+impl Display for ReadThroughStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "read_through({}, {})", self.inner, self.fallback)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for ReadThroughStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        self.inner.put(location, bytes).await
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        // the options are consumed by the request, so check where the file is first
+        match self.inner.head(location).await {
+            Err(object_store::Error::NotFound { .. }) => {
+                self.fallback.get_opts(location, options).await
+            }
+            _ => self.inner.get_opts(location, options).await,
+        }
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        match self.inner.get_range(location, range.clone()).await {
+            Err(object_store::Error::NotFound { .. }) => {
+                self.fallback.get_range(location, range).await
+            }
+            res => res,
+        }
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        match self.inner.get_ranges(location, ranges).await {
+            Err(object_store::Error::NotFound { .. }) => {
+                self.fallback.get_ranges(location, ranges).await
+            }
+            res => res,
+        }
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        match self.inner.head(location).await {
+            Err(object_store::Error::NotFound { .. }) => self.fallback.head(location).await,
+            res => res,
+        }
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_through() {
+        let inner: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let fallback: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let store = ReadThroughStore::new(Arc::clone(&inner), Arc::clone(&fallback));
+
+        let staged = Path::from("staged");
+        let public = Path::from("public");
+        inner.put(&staged, Bytes::from("inner")).await.unwrap();
+        fallback
+            .put(&staged, Bytes::from("fallback"))
+            .await
+            .unwrap();
+        fallback.put(&public, Bytes::from("public")).await.unwrap();
+
+        // the inner store wins
+        let bytes = store.get(&staged).await.unwrap().bytes().await.unwrap();
+        assert_eq!(bytes, Bytes::from("inner"));
+
+        // missing files are read from the fallback store
+        let bytes = store.get(&public).await.unwrap().bytes().await.unwrap();
+        assert_eq!(bytes, Bytes::from("public"));
+        assert_eq!(store.head(&public).await.unwrap().size, 6);
+        assert_eq!(
+            store.get_range(&public, 1..3).await.unwrap(),
+            Bytes::from("ub")
+        );
+        assert!(matches!(
+            store.head(&Path::from("missing")).await,
+            Err(object_store::Error::NotFound { .. })
+        ));
+
+        // but the fallback store is never modified
+        store.delete(&public).await.unwrap();
+        fallback.head(&public).await.unwrap();
+    }
+}
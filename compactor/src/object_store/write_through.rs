@@ -0,0 +1,159 @@
+//! Wrapper that writes to another store while the scratchpad store is bypassed.
+use std::{fmt::Display, ops::Range, sync::Arc};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::{
+    path::Path, DynObjectStore, GetOptions, GetResult, ListResult, MultipartId, ObjectMeta,
+    ObjectStore, Result,
+};
+use tokio::io::AsyncWrite;
+
+use crate::components::scratchpad::breaker::ScratchpadBreaker;
+
+/// Store that writes to an output store instead of the inner (scratchpad) store while the breaker
+/// is open.
+///
+/// Failed writes to the inner store count as failed transfers of the breaker. All other requests
+/// only go to the inner store.
+///
+/// This lets the compaction write its files while the scratchpad store is failing. The scratchpad
+/// then finds them in the output store when making them public.
+#[derive(Debug)]
+pub struct WriteThroughStore {
+    inner: Arc<DynObjectStore>,
+    output: Arc<DynObjectStore>,
+    breaker: Arc<ScratchpadBreaker>,
+}
+
+impl WriteThroughStore {
+    /// Creates new store that writes to `output` instead of `inner` while `breaker` is open.
+    pub fn new(
+        inner: Arc<DynObjectStore>,
+        output: Arc<DynObjectStore>,
+        breaker: Arc<ScratchpadBreaker>,
+    ) -> Self {
+        Self {
+            inner,
+            output,
+            breaker,
+        }
+    }
+}
+
+impl Display for WriteThroughStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "write_through({}, {})", self.inner, self.output)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for WriteThroughStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        if self.breaker.is_open() {
+            return self.output.put(location, bytes).await;
+        }
+
+        let res = self.inner.put(location, bytes).await;
+        match &res {
+            Ok(_) => self.breaker.record_success(),
+            Err(_) => self.breaker.record_failure(),
+        }
+        res
+    }
+
+    async fn put_multipart(
+        &self,
+        location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        self.inner.put_multipart(location).await
+    }
+
+    async fn abort_multipart(&self, location: &Path, multipart_id: &MultipartId) -> Result<()> {
+        self.inner.abort_multipart(location, multipart_id).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        self.inner.get_range(location, range).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<usize>]) -> Result<Vec<Bytes>> {
+        self.inner.get_ranges(location, ranges).await
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.inner.head(location).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        self.inner.delete(location).await
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        self.inner.list(prefix).await
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{num::NonZeroUsize, time::Duration};
+
+    use iox_time::{MockProvider, Time};
+    use metric::Registry;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_through() {
+        let inner: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let output: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let breaker = Arc::new(ScratchpadBreaker::new(
+            NonZeroUsize::new(1).unwrap(),
+            Duration::from_secs(10),
+            Arc::new(MockProvider::new(Time::MIN)),
+            &Registry::new(),
+        ));
+        let store = WriteThroughStore::new(
+            Arc::clone(&inner),
+            Arc::clone(&output),
+            Arc::clone(&breaker),
+        );
+
+        // writes go to the inner store while the breaker is closed...
+        let staged = Path::from("staged");
+        store.put(&staged, Bytes::from("staged")).await.unwrap();
+        inner.head(&staged).await.unwrap();
+        assert!(matches!(
+            output.head(&staged).await,
+            Err(object_store::Error::NotFound { .. })
+        ));
+
+        // ...and to the output store while it is open
+        breaker.record_failure();
+        let public = Path::from("public");
+        store.put(&public, Bytes::from("public")).await.unwrap();
+        output.head(&public).await.unwrap();
+        assert!(matches!(
+            inner.head(&public).await,
+            Err(object_store::Error::NotFound { .. })
+        ));
+    }
+}
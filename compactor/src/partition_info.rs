@@ -9,7 +9,7 @@ use data_types::{
 use schema::sort::SortKey;
 
 /// Information about the Partition being compacted
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PartitionInfo {
     /// the partition
     pub partition_id: PartitionId,
@@ -23,6 +23,9 @@ pub struct PartitionInfo {
     /// Namespace name
     pub namespace_name: String,
 
+    /// Namespace retention period in nanoseconds, `None` if data is kept forever
+    pub retention_period_ns: Option<i64>,
+
     /// Table.
     pub table: Arc<Table>,
 
@@ -34,6 +37,9 @@ pub struct PartitionInfo {
 
     /// partition_key
     pub partition_key: PartitionKey,
+
+    /// Only the oldest L0 files of the partition were fetched, because it has too many
+    pub files_truncated: bool,
 }
 
 impl PartitionInfo {
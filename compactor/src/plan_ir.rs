@@ -79,6 +79,15 @@ impl PlanIR {
         }
     }
 
+    /// return the input files that will be compacted together, for modification
+    pub fn input_files_mut(&mut self) -> &mut [FileIR] {
+        match self {
+            Self::Compact { files, .. } => files,
+            Self::Split { files, .. } => files,
+            Self::None { .. } => &mut [],
+        }
+    }
+
     /// return the ParquetFiles that will be compacted together
     pub fn input_parquet_files(&self) -> Vec<ParquetFile> {
         self.input_files()
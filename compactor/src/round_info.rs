@@ -2,7 +2,7 @@
 
 use std::fmt::Display;
 
-use data_types::{CompactionLevel, FileRange};
+use data_types::{CompactionLevel, FileRange, ParquetFile, ParquetFileId, Timestamp};
 
 /// Information about the current compaction round (see driver.rs for
 /// more details about a round)
@@ -14,6 +14,11 @@ pub enum RoundInfo {
         target_level: CompactionLevel,
         /// max total size limit of files to group in each plan
         max_total_file_size_to_group: usize,
+        /// estimated output size of each branch in this round, in bytes.  Only known once the files
+        /// have been divided into branches, see [`RoundInfo::with_estimated_branch_output_bytes`].
+        estimated_branch_output_bytes: Vec<usize>,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
     },
     /// In many small files mode
     ManySmallFiles {
@@ -23,6 +28,12 @@ pub enum RoundInfo {
         max_num_files_to_group: usize,
         /// max total size limit of files to group in each plan
         max_total_file_size_to_group: usize,
+        /// when set, L0 files are bucketed into windows of this many nanoseconds of
+        /// `max_l0_created_at` before grouping, and files from different windows are never grouped
+        /// in the same plan.
+        max_l0_created_at_window_ns: Option<i64>,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
     },
 
     /// This scenario is not 'leading edge', but we'll process it like it is.
@@ -41,6 +52,8 @@ pub enum RoundInfo {
         max_num_files_to_group: usize,
         /// max total size limit of files to group in each plan
         max_total_file_size_to_group: usize,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
     },
 
     /// Vertical Split always applies to L0.  This is triggered when we have too many overlapping L0s to
@@ -51,6 +64,13 @@ pub enum RoundInfo {
         /// split_times are the exact times L0 files will be split at.  Only L0 files overlapping these times
         /// need split.
         split_times: Vec<i64>,
+        /// how many of the split times came from each [`SplitTimeSource`].
+        split_time_provenance: SplitTimeProvenance,
+        /// estimated output size of each branch in this round, in bytes.  Only known once the files
+        /// have been divided into branches, see [`RoundInfo::with_estimated_branch_output_bytes`].
+        estimated_branch_output_bytes: Vec<usize>,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
     },
 
     /// CompactRanges are overlapping chains of L0s are less than max_compact_size, with no L0 or L1 overlaps
@@ -62,29 +82,248 @@ pub enum RoundInfo {
         max_num_files_to_group: usize,
         /// max total size limit of files to group in each plan
         max_total_file_size_to_group: usize,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
+    },
+
+    /// ColdCompaction applies to partitions that receive little more than a trickle of writes.  When
+    /// all the files of the partition fit in a single plan and none were recently ingested, there is
+    /// no point stepping through L0->L1->L2, so everything is compacted straight to L2 in one branch.
+    ColdCompaction {
+        /// max number of files to group in each plan
+        max_num_files_to_group: usize,
+        /// max total size limit of files to group in each plan
+        max_total_file_size_to_group: usize,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
+    },
+
+    /// Expired applies when some files of the partition only contain data older than the namespace's
+    /// retention period.  Compacting them would be wasted effort since the garbage collector's
+    /// retention flagger deletes them, so this round compacts nothing: the expired files are dropped
+    /// from the partition, and the remaining files are left for the following rounds.  Expired files
+    /// overlapping live data (directly or through other expired files) are not dropped, since they
+    /// stay in the catalog until deleted and compacting the live data around them would break the
+    /// non-overlapping invariant of L1 and L2.
+    Expired {
+        /// files with a max_time before this time are expired
+        retention_cutoff: i64,
+        /// the files flagged for deletion, rather than compaction
+        expired_files: Vec<ParquetFileId>,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
+    },
+
+    /// TimeBucketed compacts the start level files into the target level in one branch per time bucket
+    /// (e.g. per day), however many files each bucket has, so the output files line up with the time
+    /// ranges queries typically ask for.  Files are bucketed by their min_time, so a file straddling a
+    /// bucket boundary goes to the earlier bucket (along with anything else it overlaps).
+    TimeBucketed {
+        /// width of each time bucket, in nanoseconds
+        bucket_nanos: i64,
+        /// compaction level of target files
+        target_level: CompactionLevel,
+        /// statistics of the files of each level the round was decided on
+        level_stats: LevelStats,
     },
 }
 
+/// Statistics of the files of one compaction level, see [`LevelStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LevelStat {
+    pub num_files: usize,
+    pub total_bytes: usize,
+    /// earliest min_time of the files, if there are any
+    pub min_time: Option<Timestamp>,
+    /// latest max_time of the files, if there are any
+    pub max_time: Option<Timestamp>,
+}
+
+impl LevelStat {
+    fn add(&mut self, file: &ParquetFile) {
+        self.num_files += 1;
+        self.total_bytes += file.file_size_bytes as usize;
+        self.min_time = Some(
+            self.min_time
+                .map_or(file.min_time, |t| t.min(file.min_time)),
+        );
+        self.max_time = Some(
+            self.max_time
+                .map_or(file.max_time, |t| t.max(file.max_time)),
+        );
+    }
+}
+
+impl Display for LevelStat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} files, {} bytes", self.num_files, self.total_bytes)?;
+        if let (Some(min_time), Some(max_time)) = (self.min_time, self.max_time) {
+            write!(f, ", [{}, {}]", min_time.get(), max_time.get())?;
+        }
+        Ok(())
+    }
+}
+
+/// Per level statistics of the files a round was decided on.  They are computed once when deciding
+/// the round, so the components acting on the round don't need to walk the files again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LevelStats {
+    l0: LevelStat,
+    l1: LevelStat,
+    l2: LevelStat,
+}
+
+impl LevelStats {
+    pub fn new<'a>(files: impl IntoIterator<Item = &'a ParquetFile>) -> Self {
+        let mut stats = Self::default();
+        for file in files {
+            stats.level_mut(file.compaction_level).add(file);
+        }
+        stats
+    }
+
+    /// Statistics of the files of the given level.
+    pub fn level(&self, level: CompactionLevel) -> &LevelStat {
+        match level {
+            CompactionLevel::Initial => &self.l0,
+            CompactionLevel::FileNonOverlapped => &self.l1,
+            CompactionLevel::Final => &self.l2,
+        }
+    }
+
+    fn level_mut(&mut self, level: CompactionLevel) -> &mut LevelStat {
+        match level {
+            CompactionLevel::Initial => &mut self.l0,
+            CompactionLevel::FileNonOverlapped => &mut self.l1,
+            CompactionLevel::Final => &mut self.l2,
+        }
+    }
+
+    /// Total size of the files of the given level and the levels after it.
+    pub fn total_bytes_from_level(&self, level: CompactionLevel) -> usize {
+        [&self.l0, &self.l1, &self.l2][level as usize..]
+            .iter()
+            .map(|stat| stat.total_bytes)
+            .sum()
+    }
+}
+
+impl Display for LevelStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "L0: {}; L1: {}; L2: {}", self.l0, self.l1, self.l2)
+    }
+}
+
+/// Why a vertical split time was chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SplitTimeSource {
+    /// The boundary between two ranges of differently distributed data, or between two chains of
+    /// L0 files.
+    RangeBoundary,
+    /// Aligned to the edge of a target level (or L2) file.
+    Hint,
+    /// Evenly spaced within a range, since no hint was close enough.
+    EvenlySpaced,
+}
+
+/// A vertical split time, along with why it was chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SplitTime {
+    pub time: i64,
+    pub source: SplitTimeSource,
+}
+
+impl SplitTime {
+    pub fn new(time: i64, source: SplitTimeSource) -> Self {
+        Self { time, source }
+    }
+}
+
+/// Number of split times of a [`RoundInfo::VerticalSplit`] that came from each [`SplitTimeSource`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SplitTimeProvenance {
+    pub range_boundaries: usize,
+    pub hints: usize,
+    pub evenly_spaced: usize,
+}
+
+impl SplitTimeProvenance {
+    pub fn new(split_times: &[SplitTime]) -> Self {
+        let mut provenance = Self::default();
+        for split_time in split_times {
+            match split_time.source {
+                SplitTimeSource::RangeBoundary => provenance.range_boundaries += 1,
+                SplitTimeSource::Hint => provenance.hints += 1,
+                SplitTimeSource::EvenlySpaced => provenance.evenly_spaced += 1,
+            }
+        }
+        provenance
+    }
+}
+
+impl Display for SplitTimeProvenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "range_boundaries={}, hints={}, evenly_spaced={}",
+            self.range_boundaries, self.hints, self.evenly_spaced
+        )
+    }
+}
+
 impl Display for RoundInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::TargetLevel { target_level, max_total_file_size_to_group  } => write!(f, "TargetLevel: {target_level} {max_total_file_size_to_group}"),
+            Self::TargetLevel { target_level, max_total_file_size_to_group, .. } => write!(f, "TargetLevel: {target_level} {max_total_file_size_to_group}"),
             Self::ManySmallFiles {
                 start_level,
                 max_num_files_to_group,
                 max_total_file_size_to_group,
+                ..
             } => write!(f, "ManySmallFiles: {start_level}, {max_num_files_to_group}, {max_total_file_size_to_group}",),
             Self::SimulatedLeadingEdge {
                 max_num_files_to_group,
                 max_total_file_size_to_group,
+                ..
             } => write!(f, "SimulatedLeadingEdge: {max_num_files_to_group}, {max_total_file_size_to_group}",),
-            Self::VerticalSplit  { split_times } => write!(f, "VerticalSplit: {split_times:?}"),
-            Self::CompactRanges { ranges, max_num_files_to_group, max_total_file_size_to_group } => write!(f, "{:?}, {max_num_files_to_group}, {max_total_file_size_to_group}", ranges)
+            Self::VerticalSplit  { split_times, .. } => write!(f, "VerticalSplit: {split_times:?}"),
+            Self::CompactRanges { ranges, max_num_files_to_group, max_total_file_size_to_group, .. } => write!(f, "{:?}, {max_num_files_to_group}, {max_total_file_size_to_group}", ranges),
+            Self::ColdCompaction { max_num_files_to_group, max_total_file_size_to_group, .. } => write!(f, "ColdCompaction: {max_num_files_to_group}, {max_total_file_size_to_group}"),
+            Self::Expired { retention_cutoff, expired_files, .. } => write!(f, "Expired: {retention_cutoff}, {}", expired_files.len()),
+            Self::TimeBucketed { bucket_nanos, target_level, .. } => write!(f, "TimeBucketed: {target_level} {bucket_nanos}"),
         }
     }
 }
 
 impl RoundInfo {
+    /// Return the static names of all variants.
+    pub fn names() -> &'static [&'static str] {
+        &[
+            "target_level",
+            "many_small_files",
+            "simulated_leading_edge",
+            "vertical_split",
+            "compact_ranges",
+            "cold_compaction",
+            "expired",
+            "time_bucketed",
+        ]
+    }
+
+    /// Return the static name of this variant, e.g. for use as a metric label.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TargetLevel { .. } => "target_level",
+            Self::ManySmallFiles { .. } => "many_small_files",
+            Self::SimulatedLeadingEdge { .. } => "simulated_leading_edge",
+            Self::VerticalSplit { .. } => "vertical_split",
+            Self::CompactRanges { .. } => "compact_ranges",
+            Self::ColdCompaction { .. } => "cold_compaction",
+            Self::Expired { .. } => "expired",
+            Self::TimeBucketed { .. } => "time_bucketed",
+        }
+    }
+
     /// what levels should the files in this round be?
     pub fn target_level(&self) -> CompactionLevel {
         match self {
@@ -94,6 +333,10 @@ impl RoundInfo {
             Self::SimulatedLeadingEdge { .. } => CompactionLevel::FileNonOverlapped,
             Self::VerticalSplit { .. } => CompactionLevel::Initial,
             Self::CompactRanges { .. } => CompactionLevel::Initial,
+            Self::ColdCompaction { .. } => CompactionLevel::Final,
+            // nothing is compacted
+            Self::Expired { .. } => CompactionLevel::Final,
+            Self::TimeBucketed { target_level, .. } => *target_level,
         }
     }
 
@@ -107,6 +350,11 @@ impl RoundInfo {
         matches!(self, Self::SimulatedLeadingEdge { .. })
     }
 
+    /// Is this round compacting a cold partition straight to L2?
+    pub fn is_cold_compaction(&self) -> bool {
+        matches!(self, Self::ColdCompaction { .. })
+    }
+
     /// return max_num_files_to_group, when available.
     pub fn max_num_files_to_group(&self) -> Option<usize> {
         match self {
@@ -124,6 +372,12 @@ impl RoundInfo {
                 max_num_files_to_group,
                 ..
             } => Some(*max_num_files_to_group),
+            Self::ColdCompaction {
+                max_num_files_to_group,
+                ..
+            } => Some(*max_num_files_to_group),
+            Self::Expired { .. } => None,
+            Self::TimeBucketed { .. } => None,
         }
     }
 
@@ -144,9 +398,94 @@ impl RoundInfo {
                 max_total_file_size_to_group,
                 ..
             } => Some(*max_total_file_size_to_group),
+            Self::ColdCompaction {
+                max_total_file_size_to_group,
+                ..
+            } => Some(*max_total_file_size_to_group),
+            Self::Expired { .. } => None,
+            Self::TimeBucketed { .. } => None,
         }
     }
 
+    /// The split times of a VerticalSplit round.
+    pub fn split_times(&self) -> Option<&[i64]> {
+        match self {
+            Self::VerticalSplit { split_times, .. } => Some(split_times),
+            Self::TargetLevel { .. } => None,
+            Self::ManySmallFiles { .. } => None,
+            Self::SimulatedLeadingEdge { .. } => None,
+            Self::CompactRanges { .. } => None,
+            Self::ColdCompaction { .. } => None,
+            Self::Expired { .. } => None,
+            Self::TimeBucketed { .. } => None,
+        }
+    }
+
+    /// Where the split times of a VerticalSplit round came from.
+    pub fn split_time_provenance(&self) -> Option<SplitTimeProvenance> {
+        match self {
+            Self::VerticalSplit {
+                split_time_provenance,
+                ..
+            } => Some(*split_time_provenance),
+            Self::TargetLevel { .. } => None,
+            Self::ManySmallFiles { .. } => None,
+            Self::SimulatedLeadingEdge { .. } => None,
+            Self::CompactRanges { .. } => None,
+            Self::ColdCompaction { .. } => None,
+            Self::Expired { .. } => None,
+            Self::TimeBucketed { .. } => None,
+        }
+    }
+
+    /// Estimated output size of each branch, in bytes, when available.
+    pub fn estimated_branch_output_bytes(&self) -> Option<&[usize]> {
+        match self {
+            Self::TargetLevel {
+                estimated_branch_output_bytes,
+                ..
+            } => Some(estimated_branch_output_bytes),
+            Self::VerticalSplit {
+                estimated_branch_output_bytes,
+                ..
+            } => Some(estimated_branch_output_bytes),
+            Self::ManySmallFiles { .. } => None,
+            Self::SimulatedLeadingEdge { .. } => None,
+            Self::CompactRanges { .. } => None,
+            Self::ColdCompaction { .. } => None,
+            Self::Expired { .. } => None,
+            Self::TimeBucketed { .. } => None,
+        }
+    }
+
+    /// Record the estimated output size of each of the `branches` this round was divided into, for
+    /// the variants that carry it.  Compaction rarely removes much data, and splitting none at all,
+    /// so the estimate is the total size of the files in each branch.
+    pub fn with_estimated_branch_output_bytes(mut self, branches: &[Vec<ParquetFile>]) -> Self {
+        match &mut self {
+            Self::TargetLevel {
+                estimated_branch_output_bytes,
+                ..
+            }
+            | Self::VerticalSplit {
+                estimated_branch_output_bytes,
+                ..
+            } => {
+                *estimated_branch_output_bytes = branches
+                    .iter()
+                    .map(|branch| branch.iter().map(|f| f.file_size_bytes as usize).sum())
+                    .collect();
+            }
+            Self::ManySmallFiles { .. }
+            | Self::SimulatedLeadingEdge { .. }
+            | Self::CompactRanges { .. }
+            | Self::ColdCompaction { .. }
+            | Self::Expired { .. }
+            | Self::TimeBucketed { .. } => {}
+        }
+        self
+    }
+
     /// return compaction ranges, when available.
     /// We could generate ranges from VerticalSplit split times, but that asssumes the splits resulted in
     /// no ranges > max_compact_size, which is not guaranteed.  Instead, we'll detect the ranges the first
@@ -159,6 +498,42 @@ impl RoundInfo {
             Self::SimulatedLeadingEdge { .. } => None,
             Self::VerticalSplit { .. } => None,
             Self::CompactRanges { ranges, .. } => Some(ranges.clone()),
+            Self::ColdCompaction { .. } => None,
+            Self::Expired { .. } => None,
+            Self::TimeBucketed { .. } => None,
+        }
+    }
+
+    /// Is this round dropping expired files?
+    pub fn is_expired(&self) -> bool {
+        matches!(self, Self::Expired { .. })
+    }
+
+    /// Statistics of the files of each level this round was decided on.
+    pub fn level_stats(&self) -> &LevelStats {
+        match self {
+            Self::TargetLevel { level_stats, .. } => level_stats,
+            Self::ManySmallFiles { level_stats, .. } => level_stats,
+            Self::SimulatedLeadingEdge { level_stats, .. } => level_stats,
+            Self::VerticalSplit { level_stats, .. } => level_stats,
+            Self::CompactRanges { level_stats, .. } => level_stats,
+            Self::ColdCompaction { level_stats, .. } => level_stats,
+            Self::Expired { level_stats, .. } => level_stats,
+            Self::TimeBucketed { level_stats, .. } => level_stats,
+        }
+    }
+
+    /// The width of the time buckets of a TimeBucketed round, in nanoseconds.
+    pub fn bucket_nanos(&self) -> Option<i64> {
+        match self {
+            Self::TimeBucketed { bucket_nanos, .. } => Some(*bucket_nanos),
+            Self::TargetLevel { .. } => None,
+            Self::ManySmallFiles { .. } => None,
+            Self::SimulatedLeadingEdge { .. } => None,
+            Self::VerticalSplit { .. } => None,
+            Self::CompactRanges { .. } => None,
+            Self::ColdCompaction { .. } => None,
+            Self::Expired { .. } => None,
         }
     }
 }
@@ -1,11 +1,21 @@
-use std::sync::Arc;
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
+use backoff::BackoffConfig;
 use data_types::{
     Column, ColumnId, ColumnType, ColumnsByName, NamespaceId, PartitionHashId, PartitionId,
     PartitionKey, Table, TableId, TableSchema,
 };
+use iox_catalog::mem::MemCatalog;
+use iox_query::exec::Executor;
+use iox_time::SystemProvider;
+use object_store::memory::InMemory;
+use parquet_file::storage::{ParquetStorage, StorageId};
 
-use crate::PartitionInfo;
+use crate::{
+    components::{hardcoded::hardcoded_components, Components},
+    config::Config,
+    PartitionInfo,
+};
 
 pub struct PartitionInfoBuilder {
     inner: PartitionInfo,
@@ -32,10 +42,12 @@ impl PartitionInfoBuilder {
                 partition_hash_id,
                 namespace_id,
                 namespace_name: String::from("ns"),
+                retention_period_ns: None,
                 table,
                 table_schema,
                 sort_key: None,
                 partition_key,
+                files_truncated: false,
             },
         }
     }
@@ -45,6 +57,16 @@ impl PartitionInfoBuilder {
         self
     }
 
+    pub fn with_retention_period_ns(mut self, retention_period_ns: i64) -> Self {
+        self.inner.retention_period_ns = Some(retention_period_ns);
+        self
+    }
+
+    pub fn with_files_truncated(mut self) -> Self {
+        self.inner.files_truncated = true;
+        self
+    }
+
     pub fn with_num_columns(mut self, num_cols: usize) -> Self {
         let columns: Vec<_> = (0..num_cols)
             .map(|i| Column {
@@ -69,3 +91,84 @@ impl PartitionInfoBuilder {
         self.inner
     }
 }
+
+/// Build the hardcoded [`Components`] on top of an in-memory catalog and object store.
+///
+/// This is for tests of components that need to pass [`Components`] along (e.g. wrappers of
+/// [`RoundInfoSource`](crate::components::round_info_source::RoundInfoSource)) without caring
+/// about what is in them.
+pub fn test_components() -> Arc<Components> {
+    hardcoded_components(&test_config())
+}
+
+/// The [`Config`] used by [`test_components`], for tests that need to swap e.g. the catalog.
+pub fn test_config() -> Config {
+    let metric_registry = Arc::new(metric::Registry::new());
+    Config {
+        metric_registry: Arc::clone(&metric_registry),
+        trace_collector: None,
+        catalog: Arc::new(MemCatalog::new(metric_registry)),
+        scheduler_config: Default::default(),
+        parquet_store_real: ParquetStorage::new(Arc::new(InMemory::new()), StorageId::from("iox")),
+        parquet_store_scratchpad: ParquetStorage::new(
+            Arc::new(InMemory::new()),
+            StorageId::from("scratchpad"),
+        ),
+        exec: Arc::new(Executor::new_testing()),
+        time_provider: Arc::new(SystemProvider::new()),
+        backoff_config: BackoffConfig::default(),
+        partition_concurrency: NonZeroUsize::new(1).unwrap(),
+        df_concurrency: NonZeroUsize::new(1).unwrap(),
+        partition_scratchpad_concurrency: NonZeroUsize::new(1).unwrap(),
+        max_desired_file_size_bytes: 100 * 1024 * 1024,
+        percentage_max_file_size: 30,
+        split_percentage: 80,
+        partition_timeout: Duration::from_secs(3_600),
+        shadow_mode: false,
+        enable_scratchpad: true,
+        min_num_l1_files_to_compact: 1,
+        process_once: true,
+        simulate_without_object_store: true,
+        parquet_files_sink_override: None,
+        all_errors_are_fatal: true,
+        max_num_columns_per_table: 200,
+        max_num_files_per_plan: 200,
+        early_l1_to_l2_size_multiplier: 3.0,
+        allow_early_l1_to_l2: true,
+        cold_compaction_threshold: None,
+        vertical_split_l2_hints: false,
+        max_split_times_per_round: None,
+        per_chain_overlaps: false,
+        drop_expired_files: false,
+        max_estimated_arrow_bytes_per_plan: None,
+        arrow_expansion_per_column: 0.05,
+        max_branches_per_round: None,
+        many_small_files_window: None,
+        round_decision_log: None,
+        max_repeated_rounds: None,
+        adaptive_file_size_per_plan_floor: None,
+        adaptive_file_size_per_plan_ceiling: None,
+        max_total_rows_per_plan: None,
+        time_bucket: None,
+        scratchpad_capacity_bytes: None,
+        scratchpad_capacity_policy: Default::default(),
+        scratchpad_max_concurrent_transfers: None,
+        scratchpad_keep_written: false,
+        scratchpad_read_through_bytes: None,
+        scratchpad_transfer_max_attempts: None,
+        scratchpad_breaker_failures: None,
+        scratchpad_breaker_cooldown: Duration::from_secs(60),
+        partition_files_cache_ttl: None,
+        partition_files_cache_max_entries: NonZeroUsize::new(10_000).unwrap(),
+        partition_files_fetch_timeout: None,
+        max_partitions_per_fetch_query: NonZeroUsize::new(100).unwrap(),
+        partition_files_filter_max_file_size_bytes: None,
+        partition_files_commit_window: None,
+        partition_fetch_min_interval: None,
+        partition_files_page_size: None,
+        partition_files_max_files: None,
+        partition_files_prefetch_ttl: None,
+        partition_files_prefetch_concurrency: NonZeroUsize::new(10).unwrap(),
+        partition_fetch_rate_limit: None,
+    }
+}
@@ -3,7 +3,15 @@
 //!
 //! See [crate::layout] module for detailed documentation
 
-use crate::layouts::{all_overlapping_l0_files, layout_setup_builder, run_layout_scenario, ONE_MB};
+use std::num::NonZeroUsize;
+
+use compactor_test_utils::TestSetup;
+use data_types::CompactionLevel;
+use iox_time::Time;
+
+use crate::layouts::{
+    all_overlapping_l0_files, layout_setup_builder, parquet_builder, run_layout_scenario, ONE_MB,
+};
 
 /// This test show the effects of the split_percentage parameter
 #[tokio::test]
@@ -995,3 +1003,74 @@ async fn all_overlapping_l0_max_input_bytes_per_partition_small_max_desired_file
     "###
     );
 }
+
+/// Creates a backlog of 40 staggered, overlapping 10MB L0 files that needs many vertical splits.
+async fn staggered_l0_backlog(setup: TestSetup) -> TestSetup {
+    for i in 0..40 {
+        setup
+            .partition
+            .create_parquet_file(
+                parquet_builder()
+                    .with_min_time(i * 1000)
+                    .with_max_time(i * 1000 + 100_000)
+                    .with_file_size_bytes(10 * ONE_MB)
+                    .with_max_l0_created_at(Time::from_timestamp_nanos(i + 1)),
+            )
+            .await;
+    }
+    setup
+}
+
+/// Vertical split runs in the scenario output and their number of split times.
+fn vertical_split_times(output: &[String]) -> Vec<usize> {
+    output
+        .iter()
+        .filter_map(|line| line.split_once("type=split(VerticalSplit)(split_times=["))
+        .map(|(_, rest)| rest.split(']').next().unwrap().split(',').count())
+        .collect()
+}
+
+/// This test shows that capping the split times per round (max_split_times_per_round) only
+/// spreads the vertical splitting over more rounds, but still compacts the backlog entirely.
+#[tokio::test]
+async fn staggered_l0_max_split_times_per_round_converges() {
+    test_helpers::maybe_start_logging();
+
+    // without the cap, single rounds use several split times
+    let setup = layout_setup_builder().await.build().await;
+    let setup = staggered_l0_backlog(setup).await;
+    let output = run_layout_scenario(&setup).await;
+    assert!(
+        vertical_split_times(&output).iter().any(|n| *n > 1),
+        "{output:#?}"
+    );
+
+    let setup = layout_setup_builder()
+        .await
+        .with_max_split_times_per_round(NonZeroUsize::new(1).unwrap())
+        .build()
+        .await;
+    let setup = staggered_l0_backlog(setup).await;
+    let output = run_layout_scenario(&setup).await;
+
+    // every vertical split round respects the cap...
+    let split_times = vertical_split_times(&output);
+    assert!(!split_times.is_empty(), "{output:#?}");
+    assert!(split_times.iter().all(|n| *n == 1), "{output:#?}");
+
+    // ...and the partition still converges: nothing was skipped and no L0 is left behind
+    assert!(
+        !output
+            .iter()
+            .any(|line| line.starts_with("SKIPPED COMPACTION")),
+        "{output:#?}"
+    );
+    let files = setup.list_by_table_not_to_delete().await;
+    assert!(!files.is_empty());
+    assert!(
+        files
+            .iter()
+            .all(|f| f.compaction_level != CompactionLevel::Initial),
+        "{files:#?}"
+    );
+}
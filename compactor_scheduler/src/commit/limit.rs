@@ -0,0 +1,503 @@
+use std::{
+    fmt::Display,
+    num::{NonZeroU64, NonZeroUsize},
+};
+
+use async_trait::async_trait;
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
+use observability_deps::tracing::info;
+
+use crate::CommitUpdate;
+
+use super::{Commit, Error};
+
+/// What to do with a commit that exceeds the [`CommitLimits`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CommitLimitPolicy {
+    /// Fail the commit.
+    #[default]
+    Reject,
+
+    /// Split the commit into several commits within the limits, applied one after the other.
+    ///
+    /// The files are created first and the replaced files are deleted last, so a failure part
+    /// way through never leaves a partition without the data of the deleted files.
+    Split,
+}
+
+/// Limits on the size of a single commit, to keep the catalog transactions small.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CommitLimits {
+    /// Maximum number of files deleted by a commit.
+    pub max_deletes: Option<NonZeroUsize>,
+
+    /// Maximum number of files created by a commit.
+    pub max_creates: Option<NonZeroUsize>,
+
+    /// Maximum total size of the files created by a commit.
+    pub max_create_bytes: Option<NonZeroU64>,
+
+    /// What to do with commits exceeding the limits.
+    pub policy: CommitLimitPolicy,
+}
+
+impl CommitLimits {
+    /// Describe the first limit the numbers exceed, if any.
+    fn violation(&self, deletes: usize, creates: usize, create_bytes: u64) -> Option<String> {
+        if let Some(max) = self.max_deletes.filter(|max| deletes > max.get()) {
+            return Some(format!(
+                "{deletes} files to delete exceed the limit of {max}"
+            ));
+        }
+        if let Some(max) = self.max_creates.filter(|max| creates > max.get()) {
+            return Some(format!(
+                "{creates} files to create exceed the limit of {max}"
+            ));
+        }
+        if let Some(max) = self.max_create_bytes.filter(|max| create_bytes > max.get()) {
+            return Some(format!(
+                "{create_bytes} bytes to create exceed the limit of {max}"
+            ));
+        }
+        None
+    }
+
+    /// Split the files to create into chunks within the limits.
+    ///
+    /// A single file larger than the byte limit forms a chunk of its own.
+    fn create_chunks<'a>(&self, create: &'a [ParquetFileParams]) -> Vec<&'a [ParquetFileParams]> {
+        let mut chunks = vec![];
+        let mut start = 0;
+        let mut bytes = 0;
+        for (i, file) in create.iter().enumerate() {
+            let file_bytes = file.file_size_bytes as u64;
+            let full = self.max_creates.map_or(false, |max| i - start == max.get())
+                || self
+                    .max_create_bytes
+                    .map_or(false, |max| bytes + file_bytes > max.get());
+            if full && i > start {
+                chunks.push(&create[start..i]);
+                start = i;
+                bytes = 0;
+            }
+            bytes += file_bytes;
+        }
+        if start < create.len() {
+            chunks.push(&create[start..]);
+        }
+        chunks
+    }
+}
+
+/// Enforces [`CommitLimits`], rejecting or splitting the commits that exceed them.
+///
+/// Batches are never split, since that would break their atomicity, so they are rejected if their
+/// total exceeds the limits.
+#[derive(Debug)]
+pub(crate) struct LimitCommitWrapper<T>
+where
+    T: Commit,
+{
+    inner: T,
+    limits: CommitLimits,
+}
+
+impl<T> LimitCommitWrapper<T>
+where
+    T: Commit,
+{
+    pub(crate) fn new(inner: T, limits: CommitLimits) -> Self {
+        Self { inner, limits }
+    }
+
+    /// Commit in several steps: files are created first, then upgraded, then deleted.
+    async fn commit_split(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        let create_chunks = self.limits.create_chunks(create);
+        let delete_chunks = delete
+            .chunks(
+                self.limits
+                    .max_deletes
+                    .map_or(delete.len(), |max| max.get())
+                    .max(1),
+            )
+            .collect::<Vec<_>>();
+        info!(
+            partition_id = partition_id.get(),
+            create_chunks = create_chunks.len(),
+            delete_chunks = delete_chunks.len(),
+            "splitting commit that exceeds the limits",
+        );
+
+        let mut created = Vec::with_capacity(create.len());
+        for chunk in create_chunks {
+            created.extend(
+                self.inner
+                    .commit(partition_id, &[], &[], chunk, target_level)
+                    .await?,
+            );
+        }
+        if !upgrade.is_empty() {
+            self.inner
+                .commit(partition_id, &[], upgrade, &[], target_level)
+                .await?;
+        }
+        for chunk in delete_chunks {
+            self.inner
+                .commit(partition_id, chunk, &[], &[], target_level)
+                .await?;
+        }
+
+        Ok(created)
+    }
+}
+
+impl<T> Display for LimitCommitWrapper<T>
+where
+    T: Commit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "limit({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> Commit for LimitCommitWrapper<T>
+where
+    T: Commit,
+{
+    async fn commit(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        let create_bytes = create.iter().map(|f| f.file_size_bytes as u64).sum();
+        let Some(violation) = self
+            .limits
+            .violation(delete.len(), create.len(), create_bytes)
+        else {
+            return self
+                .inner
+                .commit(partition_id, delete, upgrade, create, target_level)
+                .await;
+        };
+
+        match self.limits.policy {
+            CommitLimitPolicy::Reject => Err(Error::LimitExceeded(format!(
+                "{violation} in partition {partition_id}"
+            ))),
+            CommitLimitPolicy::Split => {
+                self.commit_split(partition_id, delete, upgrade, create, target_level)
+                    .await
+            }
+        }
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        let deletes = batch.iter().map(|update| update.delete.len()).sum();
+        let creates = batch.iter().map(|update| update.create.len()).sum();
+        let create_bytes = batch
+            .iter()
+            .flat_map(|update| &update.create)
+            .map(|f| f.file_size_bytes as u64)
+            .sum();
+        if let Some(violation) = self.limits.violation(deletes, creates, create_bytes) {
+            return Err(Error::LimitExceeded(format!(
+                "{violation} in a batch of {} partitions",
+                batch.len()
+            )));
+        }
+
+        self.inner.commit_batch(batch).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use assert_matches::assert_matches;
+    use iox_tests::{partition_identifier, ParquetFileBuilder};
+
+    use super::*;
+    use crate::commit::mock::{CommitHistoryEntry, MockCommit};
+
+    /// Fails the `n`-th commit, counting from 1.
+    #[derive(Debug)]
+    struct FailNthCommit {
+        inner: Arc<MockCommit>,
+        n: usize,
+        count: AtomicUsize,
+    }
+
+    impl Display for FailNthCommit {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "fail_nth({})", self.inner)
+        }
+    }
+
+    #[async_trait]
+    impl Commit for FailNthCommit {
+        async fn commit(
+            &self,
+            partition_id: PartitionId,
+            delete: &[ParquetFile],
+            upgrade: &[ParquetFile],
+            create: &[ParquetFileParams],
+            target_level: CompactionLevel,
+        ) -> Result<Vec<ParquetFile>, Error> {
+            if self.count.fetch_add(1, Ordering::SeqCst) + 1 == self.n {
+                return Err(Error::BadRequest("nope".into()));
+            }
+            self.inner
+                .commit(partition_id, delete, upgrade, create, target_level)
+                .await
+        }
+
+        async fn commit_batch(
+            &self,
+            batch: &[CommitUpdate],
+        ) -> Result<Vec<Vec<ParquetFile>>, Error> {
+            self.inner.commit_batch(batch).await
+        }
+    }
+
+    fn limits(policy: CommitLimitPolicy) -> CommitLimits {
+        CommitLimits {
+            max_deletes: NonZeroUsize::new(2),
+            max_creates: NonZeroUsize::new(2),
+            max_create_bytes: NonZeroU64::new(100),
+            policy,
+        }
+    }
+
+    fn existing(id: i64) -> ParquetFile {
+        ParquetFileBuilder::new(id)
+            .with_partition(partition_identifier(1))
+            .with_compaction_level(CompactionLevel::Initial)
+            .build()
+    }
+
+    fn created(id: i64, size: i64) -> ParquetFile {
+        ParquetFileBuilder::new(id)
+            .with_partition(partition_identifier(1))
+            .with_compaction_level(CompactionLevel::FileNonOverlapped)
+            .with_file_size_bytes(size)
+            .build()
+    }
+
+    fn entry(
+        delete: Vec<ParquetFile>,
+        upgrade: Vec<ParquetFile>,
+        created: Vec<ParquetFile>,
+    ) -> CommitHistoryEntry {
+        CommitHistoryEntry {
+            partition_id: PartitionId::new(1),
+            delete,
+            upgrade,
+            created,
+            target_level: CompactionLevel::FileNonOverlapped,
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let commit = LimitCommitWrapper::new(MockCommit::new(), CommitLimits::default());
+        assert_eq!(commit.to_string(), "limit(mock)");
+    }
+
+    #[test]
+    fn test_create_chunks() {
+        let limits = limits(CommitLimitPolicy::Split);
+        let create = [10, 10, 10, 60, 50, 200, 10]
+            .into_iter()
+            .enumerate()
+            .map(|(i, size)| created(1000 + i as i64, size).into())
+            .collect::<Vec<ParquetFileParams>>();
+
+        let chunk_sizes = limits
+            .create_chunks(&create)
+            .into_iter()
+            .map(|chunk| chunk.iter().map(|f| f.file_size_bytes).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chunk_sizes,
+            vec![vec![10, 10], vec![10, 60], vec![50], vec![200], vec![10]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_within_limits() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = LimitCommitWrapper::new(Arc::clone(&inner), limits(CommitLimitPolicy::Reject));
+
+        let create = created(1000, 100);
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing(1), existing(2)],
+                &[existing(3)],
+                &[create.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![create.clone()]);
+        assert_eq!(
+            inner.history(),
+            vec![entry(
+                vec![existing(1), existing(2)],
+                vec![existing(3)],
+                vec![create]
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reject() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = LimitCommitWrapper::new(Arc::clone(&inner), limits(CommitLimitPolicy::Reject));
+
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing(1), existing(2), existing(3)],
+                &[],
+                &[created(1000, 1).into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::LimitExceeded(e))
+                if e == "3 files to delete exceed the limit of 2 in partition 1"
+        );
+
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing(1)],
+                &[],
+                &[created(1000, 60).into(), created(1001, 60).into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::LimitExceeded(e))
+                if e == "120 bytes to create exceed the limit of 100 in partition 1"
+        );
+
+        // batches are rejected even if they could be split
+        let commit = LimitCommitWrapper::new(Arc::clone(&inner), limits(CommitLimitPolicy::Split));
+        let update = |partition_id| {
+            CommitUpdate::new(
+                PartitionId::new(partition_id),
+                vec![existing(partition_id)],
+                vec![],
+                vec![created(1000 + partition_id, 1).into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+        };
+        let res = commit
+            .commit_batch(&[update(1), update(2), update(3)])
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::LimitExceeded(e))
+                if e == "3 files to delete exceed the limit of 2 in a batch of 3 partitions"
+        );
+
+        assert_eq!(inner.history(), vec![]);
+        assert_eq!(inner.batch_history(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_split() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = LimitCommitWrapper::new(Arc::clone(&inner), limits(CommitLimitPolicy::Split));
+
+        let delete = (1..=5).map(existing).collect::<Vec<_>>();
+        let upgrade = vec![existing(6)];
+        let create = [10, 20, 30, 90]
+            .into_iter()
+            .enumerate()
+            .map(|(i, size)| created(1000 + i as i64, size))
+            .collect::<Vec<_>>();
+
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &delete,
+                &upgrade,
+                &create.iter().cloned().map(Into::into).collect::<Vec<_>>(),
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == create);
+
+        // creates first, then upgrades, deletes last
+        assert_eq!(
+            inner.history(),
+            vec![
+                entry(vec![], vec![], create[0..2].to_vec()),
+                entry(vec![], vec![], create[2..3].to_vec()),
+                entry(vec![], vec![], create[3..4].to_vec()),
+                entry(vec![], upgrade, vec![]),
+                entry(delete[0..2].to_vec(), vec![], vec![]),
+                entry(delete[2..4].to_vec(), vec![], vec![]),
+                entry(delete[4..5].to_vec(), vec![], vec![]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_failure_keeps_deletes() {
+        for (fail, committed) in [(2, 1), (4, 3), (5, 4)] {
+            let inner = Arc::new(MockCommit::new());
+            let commit = LimitCommitWrapper::new(
+                FailNthCommit {
+                    inner: Arc::clone(&inner),
+                    n: fail,
+                    count: AtomicUsize::new(0),
+                },
+                limits(CommitLimitPolicy::Split),
+            );
+
+            let delete = (1..=3).map(existing).collect::<Vec<_>>();
+            let create = (0..4)
+                .map(|i| created(1000 + i, 10).into())
+                .collect::<Vec<ParquetFileParams>>();
+            let res = commit
+                .commit(
+                    PartitionId::new(1),
+                    &delete,
+                    &[existing(4)],
+                    &create,
+                    CompactionLevel::FileNonOverlapped,
+                )
+                .await;
+            assert_matches!(res, Err(Error::BadRequest(_)));
+
+            // the steps before the failure are visible, but none of the files are deleted unless
+            // all files were created
+            let history = inner.history();
+            assert_eq!(history.len(), committed, "failing step {fail}");
+            let created = history.iter().map(|e| e.created.len()).sum::<usize>();
+            let deleted = history.iter().map(|e| e.delete.len()).sum::<usize>();
+            assert!(deleted == 0 || created == create.len());
+            assert!(deleted < delete.len());
+        }
+    }
+}
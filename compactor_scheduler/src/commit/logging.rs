@@ -1,8 +1,10 @@
 use std::fmt::Display;
 
 use async_trait::async_trait;
-use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
-use observability_deps::tracing::info;
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
+use observability_deps::tracing::{debug, error, info};
+
+use crate::CommitUpdate;
 
 use super::{Commit, Error};
 
@@ -23,6 +25,64 @@ where
     }
 }
 
+fn log_error(
+    partition_id: PartitionId,
+    delete: &[ParquetFile],
+    upgrade: &[ParquetFile],
+    create: &[ParquetFileParams],
+    target_level: CompactionLevel,
+    e: &Error,
+) {
+    error!(
+        target_level=?target_level,
+        partition_id=partition_id.get(),
+        files_delete=delete.len(),
+        files_upgrade=upgrade.len(),
+        files_create=create.len(),
+        %e,
+        "failed to commit parquet file change",
+    );
+}
+
+fn log_committed(
+    partition_id: PartitionId,
+    delete: &[ParquetFile],
+    upgrade: &[ParquetFile],
+    create: &[ParquetFileParams],
+    created: &[ParquetFile],
+    target_level: CompactionLevel,
+) {
+    // Log numbers BEFORE IDs because the list may be so long that we hit the line-length limit. In this case we at
+    // least have the important information. Note that the message always is printed first, so we'll never loose
+    // that one.
+    info!(
+        target_level=?target_level,
+        partition_id=partition_id.get(),
+        files_delete=delete.len(),
+        files_upgrade=upgrade.len(),
+        files_create=created.len(),
+        bytes_delete=delete.iter().map(|f| f.file_size_bytes).sum::<i64>(),
+        bytes_upgrade=upgrade.iter().map(|f| f.file_size_bytes).sum::<i64>(),
+        bytes_create=create.iter().map(|f| f.file_size_bytes).sum::<i64>(),
+        rows_delete=delete.iter().map(|f| f.row_count).sum::<i64>(),
+        rows_upgrade=upgrade.iter().map(|f| f.row_count).sum::<i64>(),
+        rows_create=create.iter().map(|f| f.row_count).sum::<i64>(),
+        delete=?delete.iter().map(|f| f.id.get()).collect::<Vec<_>>(),
+        upgrade=?upgrade.iter().map(|f| f.id.get()).collect::<Vec<_>>(),
+        create=?created.iter().map(|f| f.id.get()).collect::<Vec<_>>(),
+        "committed parquet file change",
+    );
+
+    // (ID, level, bytes) of every file, with the levels they had before the commit
+    debug!(
+        partition_id=partition_id.get(),
+        delete=?delete.iter().map(|f| (f.id.get(), f.compaction_level.name(), f.file_size_bytes)).collect::<Vec<_>>(),
+        upgrade=?upgrade.iter().map(|f| (f.id.get(), f.compaction_level.name(), f.file_size_bytes)).collect::<Vec<_>>(),
+        create=?created.iter().map(|f| (f.id.get(), f.compaction_level.name(), f.file_size_bytes)).collect::<Vec<_>>(),
+        "committed parquet files",
+    );
+}
+
 impl<T> Display for LoggingCommitWrapper<T>
 where
     T: Commit,
@@ -44,36 +104,62 @@ where
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, Error> {
+    ) -> Result<Vec<ParquetFile>, Error> {
         // Perform commit first and report status AFTERWARDS.
-        let created = self
+        let created = match self
             .inner
             .commit(partition_id, delete, upgrade, create, target_level)
-            .await?;
-
-        // Log numbers BEFORE IDs because the list may be so long that we hit the line-length limit. In this case we at
-        // least have the important information. Note that the message always is printed first, so we'll never loose
-        // that one.
-        info!(
-            target_level=?target_level,
-            partition_id=partition_id.get(),
-            files_delete=delete.len(),
-            files_upgrade=upgrade.len(),
-            files_create=created.len(),
-            bytes_delete=delete.iter().map(|f| f.file_size_bytes).sum::<i64>(),
-            bytes_upgrade=upgrade.iter().map(|f| f.file_size_bytes).sum::<i64>(),
-            bytes_create=create.iter().map(|f| f.file_size_bytes).sum::<i64>(),
-            rows_delete=delete.iter().map(|f| f.row_count).sum::<i64>(),
-            rows_upgrade=upgrade.iter().map(|f| f.row_count).sum::<i64>(),
-            rows_create=create.iter().map(|f| f.row_count).sum::<i64>(),
-            delete=?delete.iter().map(|f| f.id.get()).collect::<Vec<_>>(),
-            upgrade=?upgrade.iter().map(|f| f.id.get()).collect::<Vec<_>>(),
-            create=?created.iter().map(|id| id.get()).collect::<Vec<_>>(),
-            "committed parquet file change",
+            .await
+        {
+            Ok(created) => created,
+            Err(e) => {
+                log_error(partition_id, delete, upgrade, create, target_level, &e);
+                return Err(e);
+            }
+        };
+        log_committed(
+            partition_id,
+            delete,
+            upgrade,
+            create,
+            &created,
+            target_level,
         );
 
         Ok(created)
     }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        // Perform commit first and report status AFTERWARDS.
+        let created = match self.inner.commit_batch(batch).await {
+            Ok(created) => created,
+            Err(e) => {
+                for update in batch {
+                    log_error(
+                        update.partition_id,
+                        &update.delete,
+                        &update.upgrade,
+                        &update.create,
+                        update.target_level,
+                        &e,
+                    );
+                }
+                return Err(e);
+            }
+        };
+        for (update, created) in batch.iter().zip(&created) {
+            log_committed(
+                update.partition_id,
+                &update.delete,
+                &update.upgrade,
+                &update.create,
+                created,
+                update.target_level,
+            );
+        }
+
+        Ok(created)
+    }
 }
 
 #[cfg(test)]
@@ -121,7 +207,7 @@ mod tests {
 
         let capture = TracingCapture::new();
 
-        let ids = commit
+        let files = commit
             .commit(
                 partition_id_1,
                 &[existing_1.clone()],
@@ -131,13 +217,13 @@ mod tests {
             )
             .await;
         assert_matches!(
-            ids,
-            Ok(res) if res == vec![ParquetFileId::new(1000), ParquetFileId::new(1001)]
+            files,
+            Ok(res) if res == vec![created_1.clone(), created_2.clone()]
         );
 
         let partition_id_2 = PartitionId::new(2);
 
-        let ids = commit
+        let files = commit
             .commit(
                 partition_id_2,
                 &[existing_2.clone(), existing_3.clone()],
@@ -146,12 +232,14 @@ mod tests {
                 CompactionLevel::Final,
             )
             .await;
-        assert_matches!(ids, Ok(res) if res == vec![]);
+        assert_matches!(files, Ok(res) if res == vec![]);
 
         assert_eq!(
             capture.to_string(),
             "level = INFO; message = committed parquet file change; target_level = Final; partition_id = 1; files_delete = 1; files_upgrade = 0; files_create = 2; bytes_delete = 10001; bytes_upgrade = 0; bytes_create = 2; rows_delete = 101; rows_upgrade = 0; rows_create = 2; delete = [1]; upgrade = []; create = [1000, 1001]; \n\
-level = INFO; message = committed parquet file change; target_level = Final; partition_id = 2; files_delete = 2; files_upgrade = 1; files_create = 0; bytes_delete = 20007; bytes_upgrade = 10001; bytes_create = 0; rows_delete = 207; rows_upgrade = 101; rows_create = 0; delete = [2, 3]; upgrade = [1]; create = []; "
+level = DEBUG; message = committed parquet files; partition_id = 1; delete = [(1, \"L1\", 10001)]; upgrade = []; create = [(1000, \"L1\", 1), (1001, \"L1\", 1)]; \n\
+level = INFO; message = committed parquet file change; target_level = Final; partition_id = 2; files_delete = 2; files_upgrade = 1; files_create = 0; bytes_delete = 20007; bytes_upgrade = 10001; bytes_create = 0; rows_delete = 207; rows_upgrade = 101; rows_create = 0; delete = [2, 3]; upgrade = [1]; create = []; \n\
+level = DEBUG; message = committed parquet files; partition_id = 2; delete = [(2, \"L1\", 10002), (3, \"L1\", 10005)]; upgrade = [(1, \"L1\", 10001)]; create = []; "
         );
 
         assert_eq!(
@@ -174,4 +262,50 @@ level = INFO; message = committed parquet file change; target_level = Final; par
             ]
         );
     }
+
+    #[tokio::test]
+    async fn test_commit_returns_inner_result() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = LoggingCommitWrapper::new(Arc::clone(&inner));
+        let created = ParquetFileBuilder::new(1000).build();
+
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[],
+                &[],
+                &[created.clone().into(), created.into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        assert_eq!(files, inner.history()[0].created);
+    }
+
+    #[tokio::test]
+    async fn test_commit_error() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = LoggingCommitWrapper::new(Arc::clone(&inner));
+        inner.fail_partition(PartitionId::new(1));
+
+        let capture = TracingCapture::new();
+
+        let err = commit
+            .commit(
+                PartitionId::new(1),
+                &[ParquetFileBuilder::new(1).build()],
+                &[],
+                &[],
+                CompactionLevel::Final,
+            )
+            .await
+            .unwrap_err();
+        assert_matches!(err, Error::BadRequest(_));
+
+        assert_eq!(
+            capture.to_string(),
+            "level = ERROR; message = failed to commit parquet file change; target_level = Final; partition_id = 1; files_delete = 1; files_upgrade = 0; files_create = 0; e = Bad commit request: mock failure for partition 1; "
+        );
+        assert!(inner.history().is_empty());
+    }
 }
@@ -1,11 +1,13 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, time::Instant};
 
 use async_trait::async_trait;
-use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
 use itertools::Itertools;
-use metric::{Registry, U64Histogram, U64HistogramOptions};
+use metric::{DurationHistogram, Registry, U64Counter, U64Histogram, U64HistogramOptions};
 
-use super::{Commit, Error};
+use crate::CommitUpdate;
+
+use super::{observer::CommitObserver, Commit, Error};
 
 #[derive(Debug, Clone, Copy)]
 enum HistogramType {
@@ -101,30 +103,106 @@ impl Histogram {
     }
 }
 
+/// Files and bytes committed for an operation.
 #[derive(Debug)]
-pub(crate) struct MetricsCommitWrapper<T>
-where
-    T: Commit,
-{
+struct Totals {
+    files: U64Counter,
+    bytes: U64Counter,
+}
+
+impl Totals {
+    fn record(&self, file_sizes: impl IntoIterator<Item = i64>) {
+        for file_size in file_sizes {
+            self.files.inc(1);
+            self.bytes.inc(file_size as u64);
+        }
+    }
+}
+
+/// Files and bytes committed to a target level.
+#[derive(Debug)]
+struct TargetLevelTotals {
+    create: Totals,
+    delete: Totals,
+    upgrade: Totals,
+}
+
+impl TargetLevelTotals {
+    fn new(registry: &Registry) -> HashMap<CompactionLevel, Self> {
+        let files = registry.register_metric::<U64Counter>(
+            METRIC_NAME_FILES,
+            "Number of files committed by the compactor",
+        );
+        let bytes = registry.register_metric::<U64Counter>(
+            METRIC_NAME_BYTES,
+            "Number of bytes committed by the compactor",
+        );
+
+        CompactionLevel::all()
+            .iter()
+            .map(|level| {
+                let target_level = ("target_level", level.name());
+                let totals = |op: &'static str| Totals {
+                    files: files.recorder(&[("op", op), target_level]),
+                    bytes: bytes.recorder(&[("op", op), target_level]),
+                };
+                let totals = Self {
+                    create: totals("create"),
+                    delete: totals("delete"),
+                    upgrade: totals("upgrade"),
+                };
+                (*level, totals)
+            })
+            .collect()
+    }
+}
+
+/// Outcomes of the commits to a target level.
+#[derive(Debug)]
+struct TargetLevelMetrics {
+    duration_ok: DurationHistogram,
+    duration_error: DurationHistogram,
+    errors: U64Counter,
+}
+
+impl TargetLevelMetrics {
+    fn new(registry: &Registry) -> HashMap<CompactionLevel, Self> {
+        let duration = registry.register_metric::<DurationHistogram>(
+            METRIC_NAME_DURATION,
+            "Time taken by the commits of the compactor",
+        );
+        let errors = registry.register_metric::<U64Counter>(
+            METRIC_NAME_ERRORS,
+            "Number of commits of the compactor that failed",
+        );
+
+        CompactionLevel::all()
+            .iter()
+            .map(|level| {
+                let target_level = ("target_level", level.name());
+                let metrics = Self {
+                    duration_ok: duration.recorder(&[("result", "ok"), target_level]),
+                    duration_error: duration.recorder(&[("result", "error"), target_level]),
+                    errors: errors.recorder(&[target_level]),
+                };
+                (*level, metrics)
+            })
+            .collect()
+    }
+}
+
+/// Per-file and per-job histograms.
+#[derive(Debug)]
+struct FileHistograms {
     file_bytes: Histogram,
     file_rows: Histogram,
     job_files: Histogram,
     job_bytes: Histogram,
     job_rows: Histogram,
-    inner: T,
 }
 
-const METRIC_NAME_FILE_BYTES: &str = "iox_compactor_commit_file_bytes";
-const METRIC_NAME_FILE_ROWS: &str = "iox_compactor_commit_file_rows";
-const METRIC_NAME_JOB_FILES: &str = "iox_compactor_commit_job_files";
-const METRIC_NAME_JOB_BYTES: &str = "iox_compactor_commit_job_bytes";
-const METRIC_NAME_JOB_ROWS: &str = "iox_compactor_commit_job_rows";
-
-impl<T> MetricsCommitWrapper<T>
-where
-    T: Commit,
-{
-    pub(crate) fn new(inner: T, registry: &Registry) -> Self {
+impl FileHistograms {
+    fn new(registry: &Registry) -> Self {
         Self {
             file_bytes: Histogram::new(
                 registry,
@@ -156,71 +234,123 @@ where
                 "Number of rows committed by the compactor, per job",
                 HistogramType::Rows,
             ),
-            inner,
         }
     }
 }
 
-impl<T> Display for MetricsCommitWrapper<T>
+/// Records the duration and errors of commits.
+///
+/// The committed files are recorded by the [`MetricsCommitObserver`].
+#[derive(Debug)]
+pub(crate) struct MetricsCommitWrapper<T>
 where
     T: Commit,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "metrics({})", self.inner)
-    }
+    target_levels: HashMap<CompactionLevel, TargetLevelMetrics>,
+    inner: T,
 }
 
-#[async_trait]
-impl<T> Commit for MetricsCommitWrapper<T>
+const METRIC_NAME_FILE_BYTES: &str = "iox_compactor_commit_file_bytes";
+const METRIC_NAME_FILE_ROWS: &str = "iox_compactor_commit_file_rows";
+const METRIC_NAME_JOB_FILES: &str = "iox_compactor_commit_job_files";
+const METRIC_NAME_JOB_BYTES: &str = "iox_compactor_commit_job_bytes";
+const METRIC_NAME_JOB_ROWS: &str = "iox_compactor_commit_job_rows";
+const METRIC_NAME_FILES: &str = "iox_compactor_commit_files";
+const METRIC_NAME_BYTES: &str = "iox_compactor_commit_bytes";
+const METRIC_NAME_DURATION: &str = "iox_compactor_commit_duration";
+const METRIC_NAME_ERRORS: &str = "iox_compactor_commit_errors";
+
+impl<T> MetricsCommitWrapper<T>
 where
     T: Commit,
 {
-    async fn commit(
+    pub(crate) fn new(inner: T, registry: &Registry) -> Self {
+        Self {
+            target_levels: TargetLevelMetrics::new(registry),
+            inner,
+        }
+    }
+}
+
+/// Records the files deleted, upgraded and created by commits.
+#[derive(Debug)]
+pub(crate) struct MetricsCommitObserver {
+    histograms: FileHistograms,
+    target_levels: HashMap<CompactionLevel, TargetLevelTotals>,
+}
+
+impl MetricsCommitObserver {
+    pub(crate) fn new(registry: &Registry) -> Self {
+        Self {
+            histograms: FileHistograms::new(registry),
+            target_levels: TargetLevelTotals::new(registry),
+        }
+    }
+}
+
+impl CommitObserver for MetricsCommitObserver {
+    fn committed_files(
         &self,
-        partition_id: PartitionId,
-        delete: &[ParquetFile],
-        upgrade: &[ParquetFile],
-        create: &[ParquetFileParams],
+        _partition: PartitionId,
+        deleted: &[ParquetFile],
+        upgraded: &[ParquetFile],
+        created: &[ParquetFile],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, Error> {
-        // Perform commit first and report status AFTERWARDS.
-        let ids = self
-            .inner
-            .commit(partition_id, delete, upgrade, create, target_level)
-            .await?;
+    ) {
+        let target_level_totals = self
+            .target_levels
+            .get(&target_level)
+            .expect("all compaction levels covered");
+        target_level_totals
+            .create
+            .record(created.iter().map(|f| f.file_size_bytes));
+        target_level_totals
+            .delete
+            .record(deleted.iter().map(|f| f.file_size_bytes));
+        target_level_totals
+            .upgrade
+            .record(upgraded.iter().map(|f| f.file_size_bytes));
+
+        let FileHistograms {
+            file_bytes,
+            file_rows,
+            job_files,
+            job_bytes,
+            job_rows,
+        } = &self.histograms;
 
         // per file metrics
-        for f in create {
-            self.file_bytes
+        for f in created {
+            file_bytes
                 .create
                 .get(&f.compaction_level)
                 .expect("all compaction levels covered")
                 .record(f.file_size_bytes as u64);
-            self.file_rows
+            file_rows
                 .create
                 .get(&f.compaction_level)
                 .expect("all compaction levels covered")
                 .record(f.row_count as u64);
         }
-        for f in delete {
-            self.file_bytes
+        for f in deleted {
+            file_bytes
                 .delete
                 .get(&f.compaction_level)
                 .expect("all compaction levels covered")
                 .record(f.file_size_bytes as u64);
-            self.file_rows
+            file_rows
                 .delete
                 .get(&f.compaction_level)
                 .expect("all compaction levels covered")
                 .record(f.row_count as u64);
         }
-        for f in upgrade {
-            self.file_bytes
+        for f in upgraded {
+            file_bytes
                 .upgrade
                 .get(&(f.compaction_level, target_level))
                 .expect("all compaction levels covered")
                 .record(f.file_size_bytes as u64);
-            self.file_rows
+            file_rows
                 .upgrade
                 .get(&(f.compaction_level, target_level))
                 .expect("all compaction levels covered")
@@ -229,87 +359,176 @@ where
 
         // per-partition metrics
         for file_level in CompactionLevel::all() {
-            let create = create
+            let created = created
                 .iter()
                 .filter(|f| f.compaction_level == *file_level)
                 .collect::<Vec<_>>();
-            let delete = delete
+            let deleted = deleted
                 .iter()
                 .filter(|f| f.compaction_level == *file_level)
                 .collect::<Vec<_>>();
-            let upgrade = upgrade
+            let upgraded = upgraded
                 .iter()
                 .filter(|f| f.compaction_level == *file_level)
                 .collect::<Vec<_>>();
 
-            self.job_files
+            job_files
                 .create
                 .get(file_level)
                 .expect("all compaction levels covered")
-                .record(create.len() as u64);
-
-            self.job_bytes
+                .record(created.len() as u64);
+            job_bytes
                 .create
                 .get(file_level)
                 .expect("all compaction levels covered")
-                .record(create.iter().map(|f| f.file_size_bytes as u64).sum::<u64>());
-            self.job_rows
+                .record(
+                    created
+                        .iter()
+                        .map(|f| f.file_size_bytes as u64)
+                        .sum::<u64>(),
+                );
+            job_rows
                 .create
                 .get(file_level)
                 .expect("all compaction levels covered")
-                .record(create.iter().map(|f| f.row_count as u64).sum::<u64>());
+                .record(created.iter().map(|f| f.row_count as u64).sum::<u64>());
 
-            self.job_files
+            job_files
                 .delete
                 .get(file_level)
                 .expect("all compaction levels covered")
-                .record(delete.len() as u64);
-            self.job_bytes
+                .record(deleted.len() as u64);
+            job_bytes
                 .delete
                 .get(file_level)
                 .expect("all compaction levels covered")
-                .record(delete.iter().map(|f| f.file_size_bytes as u64).sum::<u64>());
-            self.job_rows
+                .record(
+                    deleted
+                        .iter()
+                        .map(|f| f.file_size_bytes as u64)
+                        .sum::<u64>(),
+                );
+            job_rows
                 .delete
                 .get(file_level)
                 .expect("all compaction levels covered")
-                .record(delete.iter().map(|f| f.row_count as u64).sum::<u64>());
+                .record(deleted.iter().map(|f| f.row_count as u64).sum::<u64>());
 
-            self.job_files
+            job_files
                 .upgrade
                 .get(&(*file_level, target_level))
                 .expect("all compaction levels covered")
-                .record(upgrade.len() as u64);
-            self.job_bytes
+                .record(upgraded.len() as u64);
+            job_bytes
                 .upgrade
                 .get(&(*file_level, target_level))
                 .expect("all compaction levels covered")
                 .record(
-                    upgrade
+                    upgraded
                         .iter()
                         .map(|f| f.file_size_bytes as u64)
                         .sum::<u64>(),
                 );
-            self.job_rows
+            job_rows
                 .upgrade
                 .get(&(*file_level, target_level))
                 .expect("all compaction levels covered")
-                .record(upgrade.iter().map(|f| f.row_count as u64).sum::<u64>());
+                .record(upgraded.iter().map(|f| f.row_count as u64).sum::<u64>());
         }
+    }
+}
 
-        Ok(ids)
+impl<T> Display for MetricsCommitWrapper<T>
+where
+    T: Commit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "metrics({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> Commit for MetricsCommitWrapper<T>
+where
+    T: Commit,
+{
+    async fn commit(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        // Perform commit first and report status AFTERWARDS.
+        let start = Instant::now();
+        let res = self
+            .inner
+            .commit(partition_id, delete, upgrade, create, target_level)
+            .await;
+        let elapsed = start.elapsed();
+
+        let target_level_metrics = self
+            .target_levels
+            .get(&target_level)
+            .expect("all compaction levels covered");
+        match &res {
+            Ok(_) => target_level_metrics.duration_ok.record(elapsed),
+            Err(_) => {
+                target_level_metrics.duration_error.record(elapsed);
+                target_level_metrics.errors.inc(1);
+            }
+        }
+
+        res
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        // Perform commit first and report status AFTERWARDS.
+        let start = Instant::now();
+        let res = self.inner.commit_batch(batch).await;
+        let elapsed = start.elapsed();
+
+        // the batch is a single commit for every target level it touches
+        for target_level in batch.iter().map(|update| update.target_level).unique() {
+            let target_level_metrics = self
+                .target_levels
+                .get(&target_level)
+                .expect("all compaction levels covered");
+            match &res {
+                Ok(_) => target_level_metrics.duration_ok.record(elapsed),
+                Err(_) => {
+                    target_level_metrics.duration_error.record(elapsed);
+                    target_level_metrics.errors.inc(1);
+                }
+            }
+        }
+
+        res
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commit::mock::{CommitHistoryEntry, MockCommit};
+    use crate::commit::{
+        mock::{CommitHistoryEntry, MockCommit},
+        observer::ObservingCommitWrapper,
+    };
     use assert_matches::assert_matches;
     use iox_tests::{partition_identifier, ParquetFileBuilder};
-    use metric::{assert_histogram, Attributes};
+    use metric::{assert_histogram, Attributes, Metric};
     use std::sync::Arc;
 
+    fn counter(registry: &Registry, name: &'static str, labels: impl Into<Attributes>) -> u64 {
+        registry
+            .get_instrument::<Metric<U64Counter>>(name)
+            .expect("metric registered")
+            .get_observer(&labels.into())
+            .expect("observer exists")
+            .fetch()
+    }
+
     #[test]
     fn test_display() {
         let registry = Registry::new();
@@ -321,7 +540,13 @@ mod tests {
     async fn test_record() {
         let registry = Registry::new();
         let inner = Arc::new(MockCommit::new());
-        let commit = MetricsCommitWrapper::new(Arc::clone(&inner), &registry);
+        let commit = MetricsCommitWrapper::new(
+            ObservingCommitWrapper::new(
+                Arc::clone(&inner),
+                vec![Arc::new(MetricsCommitObserver::new(&registry))],
+            ),
+            &registry,
+        );
 
         let partition_id_1 = PartitionId::new(1);
         let transition_partition_id_1 = partition_identifier(1);
@@ -390,7 +615,7 @@ mod tests {
             }
         }
 
-        let ids = commit
+        let files = commit
             .commit(
                 partition_id_1,
                 &[existing_1.clone()],
@@ -399,11 +624,11 @@ mod tests {
                 CompactionLevel::FileNonOverlapped,
             )
             .await;
-        assert_matches!(ids, Ok(res) if res == vec![ParquetFileId::new(1000)]);
+        assert_matches!(files, Ok(res) if res == vec![created.clone()]);
 
         let partition_id_2 = PartitionId::new(2);
 
-        let ids = commit
+        let files = commit
             .commit(
                 partition_id_2,
                 &[existing_2b.clone(), existing_3.clone()],
@@ -412,7 +637,7 @@ mod tests {
                 CompactionLevel::Final,
             )
             .await;
-        assert_matches!(ids, Ok(res) if res == vec![]);
+        assert_matches!(files, Ok(res) if res == vec![]);
 
         assert_histogram!(
             registry,
@@ -467,4 +692,135 @@ mod tests {
             ]
         );
     }
+
+    #[tokio::test]
+    async fn test_record_by_target_level() {
+        let registry = Registry::new();
+        let inner = Arc::new(MockCommit::new());
+        let commit = MetricsCommitWrapper::new(
+            ObservingCommitWrapper::new(
+                Arc::clone(&inner),
+                vec![Arc::new(MetricsCommitObserver::new(&registry))],
+            ),
+            &registry,
+        );
+
+        let file = |id, size| {
+            ParquetFileBuilder::new(id)
+                .with_file_size_bytes(size)
+                .build()
+        };
+        let created = ParquetFileBuilder::new(1000)
+            .with_file_size_bytes(400)
+            .with_compaction_level(CompactionLevel::FileNonOverlapped)
+            .build();
+
+        commit
+            .commit(
+                PartitionId::new(1),
+                &[file(1, 100), file(2, 200)],
+                &[file(3, 300)],
+                &[created.into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        commit
+            .commit(
+                PartitionId::new(2),
+                &[],
+                &[file(4, 500)],
+                &[],
+                CompactionLevel::Final,
+            )
+            .await
+            .unwrap();
+
+        // failures only count as errors
+        inner.fail_partition(PartitionId::new(3));
+        commit
+            .commit(
+                PartitionId::new(3),
+                &[file(5, 600)],
+                &[],
+                &[],
+                CompactionLevel::Final,
+            )
+            .await
+            .unwrap_err();
+
+        for (op, target_level, files, bytes) in [
+            ("create", "L1", 1, 400),
+            ("delete", "L1", 2, 300),
+            ("upgrade", "L1", 1, 300),
+            ("create", "L2", 0, 0),
+            ("delete", "L2", 0, 0),
+            ("upgrade", "L2", 1, 500),
+            ("delete", "L0", 0, 0),
+        ] {
+            let labels = [("op", op), ("target_level", target_level)];
+            assert_eq!(counter(&registry, METRIC_NAME_FILES, &labels), files);
+            assert_eq!(counter(&registry, METRIC_NAME_BYTES, &labels), bytes);
+        }
+
+        for (target_level, ok, error) in [("L0", 0, 0), ("L1", 1, 0), ("L2", 1, 1)] {
+            for (result, samples) in [("ok", ok), ("error", error)] {
+                assert_histogram!(
+                    registry,
+                    DurationHistogram,
+                    METRIC_NAME_DURATION,
+                    labels =
+                        Attributes::from(&[("result", result), ("target_level", target_level)]),
+                    samples = samples,
+                );
+            }
+            assert_eq!(
+                counter(
+                    &registry,
+                    METRIC_NAME_ERRORS,
+                    &[("target_level", target_level)]
+                ),
+                error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wrapper_only_records_outcome() {
+        let registry = Registry::new();
+        let commit = MetricsCommitWrapper::new(MockCommit::new(), &registry);
+        // registers the file metrics, but is not notified
+        let _observer = MetricsCommitObserver::new(&registry);
+
+        let deleted = ParquetFileBuilder::new(1).with_file_size_bytes(100).build();
+        commit
+            .commit(
+                PartitionId::new(1),
+                &[deleted],
+                &[],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+
+        // the files are recorded by the observer only
+        let labels = [("op", "delete"), ("target_level", "L1")];
+        assert_eq!(counter(&registry, METRIC_NAME_FILES, &labels), 0);
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_FILE_BYTES,
+            labels = Attributes::from(&[("op", "delete"), ("level", "L1")]),
+            samples = 0,
+            sum = 0,
+        );
+        assert_histogram!(
+            registry,
+            DurationHistogram,
+            METRIC_NAME_DURATION,
+            labels = Attributes::from(&[("result", "ok"), ("target_level", "L1")]),
+            samples = 1,
+        );
+    }
 }
@@ -1,12 +1,16 @@
 use std::{
+    collections::{HashSet, VecDeque},
     fmt::Display,
-    sync::atomic::{AtomicI64, Ordering},
+    num::NonZeroUsize,
+    sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering},
 };
 
 use async_trait::async_trait;
 use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
 use parking_lot::Mutex;
 
+use crate::CommitUpdate;
+
 use super::{Commit, Error};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -18,23 +22,177 @@ pub(crate) struct CommitHistoryEntry {
     pub(crate) target_level: CompactionLevel,
 }
 
+/// The changes of a batch, committed together.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct CommitBatchHistoryEntry {
+    pub(crate) batch_id: usize,
+    pub(crate) entries: Vec<CommitHistoryEntry>,
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct MockCommit {
-    history: Mutex<Vec<CommitHistoryEntry>>,
+    history: Mutex<VecDeque<CommitHistoryEntry>>,
+    history_capacity: Option<NonZeroUsize>,
+    batch_history: Mutex<Vec<CommitBatchHistoryEntry>>,
     id_counter: AtomicI64,
+    failing_partitions: Mutex<HashSet<PartitionId>>,
+    failures: Mutex<VecDeque<Error>>,
+    dedup: AtomicBool,
+    total_created: AtomicUsize,
+    total_deleted: AtomicUsize,
 }
 
 impl MockCommit {
     pub(crate) fn new() -> Self {
         Self {
             history: Default::default(),
+            history_capacity: None,
+            batch_history: Default::default(),
             id_counter: AtomicI64::new(1000),
+            failing_partitions: Default::default(),
+            failures: Default::default(),
+            dedup: Default::default(),
+            total_created: Default::default(),
+            total_deleted: Default::default(),
         }
     }
 
-    #[cfg(test)]
+    /// Only keep the most recent `capacity` commits in the [history](Self::history), evicting the
+    /// oldest ones first.
+    ///
+    /// Evicted commits still count towards [`total_created`](Self::total_created) and
+    /// [`total_deleted`](Self::total_deleted), but their files are no longer deduplicated.
+    pub(crate) fn with_history_capacity(self, capacity: NonZeroUsize) -> Self {
+        Self {
+            history_capacity: Some(capacity),
+            ..self
+        }
+    }
+
+    /// Like the catalog commit, return the already created file instead of creating a new one
+    /// for files to create whose object store ID was created in the same partition before.
+    ///
+    /// Commits that only create such files are retries of commits that went through already, and
+    /// are not recorded again.
+    pub(crate) fn dedup_object_store_ids(&self) {
+        self.dedup.store(true, Ordering::SeqCst);
+    }
+
+    /// Fail the commits to the partition from now on, without recording them.
+    pub(crate) fn fail_partition(&self, partition_id: PartitionId) {
+        self.failing_partitions.lock().insert(partition_id);
+    }
+
+    /// Fail the next commits with the given errors, one per commit, without recording them.
+    pub(crate) fn fail_next(&self, errors: impl IntoIterator<Item = Error>) {
+        self.failures.lock().extend(errors);
+    }
+
     pub(crate) fn history(&self) -> Vec<CommitHistoryEntry> {
-        self.history.lock().clone()
+        self.history.lock().iter().cloned().collect()
+    }
+
+    /// The recorded commits to the partition, oldest first.
+    pub(crate) fn history_for_partition(
+        &self,
+        partition_id: PartitionId,
+    ) -> Vec<CommitHistoryEntry> {
+        self.history
+            .lock()
+            .iter()
+            .filter(|entry| entry.partition_id == partition_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Return the recorded commits, oldest first, and clear the history.
+    pub(crate) fn drain_history(&self) -> Vec<CommitHistoryEntry> {
+        self.history.lock().drain(..).collect()
+    }
+
+    /// Number of files created by all recorded commits and batches so far, including the ones
+    /// evicted or drained from the history.
+    pub(crate) fn total_created(&self) -> usize {
+        self.total_created.load(Ordering::SeqCst)
+    }
+
+    /// Number of files deleted by all recorded commits and batches so far, including the ones
+    /// evicted or drained from the history.
+    pub(crate) fn total_deleted(&self) -> usize {
+        self.total_deleted.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn batch_history(&self) -> Vec<CommitBatchHistoryEntry> {
+        self.batch_history.lock().clone()
+    }
+
+    /// Return the error for a commit to the partitions, if it should fail.
+    fn failure(&self, mut partition_ids: impl Iterator<Item = PartitionId>) -> Option<Error> {
+        let failing_partitions = self.failing_partitions.lock();
+        if let Some(partition_id) = partition_ids.find(|id| failing_partitions.contains(id)) {
+            return Some(Error::BadRequest(format!(
+                "mock failure for partition {partition_id}"
+            )));
+        }
+        self.failures.lock().pop_front()
+    }
+
+    /// The file created before for the params, if deduplicating.
+    fn existing_file(&self, params: &ParquetFileParams) -> Option<ParquetFile> {
+        if !self.dedup.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        let history = self.history.lock();
+        let batch_history = self.batch_history.lock();
+        history
+            .iter()
+            .chain(batch_history.iter().flat_map(|batch| &batch.entries))
+            .flat_map(|entry| &entry.created)
+            .find(|f| {
+                f.object_store_id == params.object_store_id && f.partition_id == params.partition_id
+            })
+            .cloned()
+    }
+
+    /// Whether all files to create were created before, i.e. the commit is a retry.
+    fn is_retry<'a>(&self, create: impl IntoIterator<Item = &'a ParquetFileParams>) -> bool {
+        let mut create = create.into_iter().peekable();
+        create.peek().is_some() && create.all(|params| self.existing_file(params).is_some())
+    }
+
+    fn count(&self, entry: &CommitHistoryEntry) {
+        self.total_created
+            .fetch_add(entry.created.len(), Ordering::SeqCst);
+        self.total_deleted
+            .fetch_add(entry.delete.len(), Ordering::SeqCst);
+    }
+
+    fn create(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> CommitHistoryEntry {
+        let created = create
+            .iter()
+            .map(|params| {
+                self.existing_file(params).unwrap_or_else(|| {
+                    let id = ParquetFileId::new(self.id_counter.fetch_add(1, Ordering::SeqCst));
+                    ParquetFile::from_params(params.clone(), id)
+                })
+            })
+            .collect();
+
+        CommitHistoryEntry {
+            partition_id,
+            delete: delete.to_vec(),
+            upgrade: upgrade.to_vec(),
+            created,
+            target_level,
+        }
     }
 }
 
@@ -53,25 +211,61 @@ impl Commit for MockCommit {
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, Error> {
-        let (created, ids): (Vec<_>, Vec<_>) = create
+    ) -> Result<Vec<ParquetFile>, Error> {
+        if let Some(e) = self.failure(std::iter::once(partition_id)) {
+            return Err(e);
+        }
+
+        let is_retry = self.is_retry(create);
+        let entry = self.create(partition_id, delete, upgrade, create, target_level);
+        let created = entry.created.clone();
+        if !is_retry {
+            self.count(&entry);
+
+            let mut history = self.history.lock();
+            if self
+                .history_capacity
+                .is_some_and(|capacity| history.len() == capacity.get())
+            {
+                history.pop_front();
+            }
+            history.push_back(entry);
+        }
+
+        Ok(created)
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        if let Some(e) = self.failure(batch.iter().map(|update| update.partition_id)) {
+            return Err(e);
+        }
+
+        let is_retry = self.is_retry(batch.iter().flat_map(|update| &update.create));
+        let entries: Vec<_> = batch
             .iter()
-            .map(|params| {
-                let id = ParquetFileId::new(self.id_counter.fetch_add(1, Ordering::SeqCst));
-                let created = ParquetFile::from_params(params.clone(), id);
-                (created, id)
+            .map(|update| {
+                self.create(
+                    update.partition_id,
+                    &update.delete,
+                    &update.upgrade,
+                    &update.create,
+                    update.target_level,
+                )
             })
-            .unzip();
+            .collect();
+        let created = entries.iter().map(|entry| entry.created.clone()).collect();
+        if is_retry {
+            return Ok(created);
+        }
 
-        self.history.lock().push(CommitHistoryEntry {
-            partition_id,
-            delete: delete.to_vec(),
-            upgrade: upgrade.to_vec(),
-            created,
-            target_level,
-        });
+        for entry in &entries {
+            self.count(entry);
+        }
+        let mut batch_history = self.batch_history.lock();
+        let batch_id = batch_history.len();
+        batch_history.push(CommitBatchHistoryEntry { batch_id, entries });
 
-        Ok(ids)
+        Ok(created)
     }
 }
 
@@ -117,7 +311,7 @@ mod tests {
             .with_partition(transition_partition_id_2)
             .build();
 
-        let ids = commit
+        let files_1 = commit
             .commit(
                 partition_id_1,
                 &[existing_1.clone(), existing_2.clone()],
@@ -127,11 +321,11 @@ mod tests {
             )
             .await;
         assert_matches!(
-            ids,
-            Ok(res) if res == vec![ParquetFileId::new(1000), ParquetFileId::new(1001)]
+            &files_1,
+            Ok(res) if res == &vec![created_1_1.clone(), created_1_2.clone()]
         );
 
-        let ids = commit
+        let files_2 = commit
             .commit(
                 partition_id_2,
                 &[existing_3.clone()],
@@ -141,11 +335,11 @@ mod tests {
             )
             .await;
         assert_matches!(
-            ids,
-            Ok(res) if res == vec![ParquetFileId::new(1002)]
+            &files_2,
+            Ok(res) if res == &vec![created_2_1.clone()]
         );
 
-        let ids = commit
+        let files_3 = commit
             .commit(
                 partition_id_1,
                 &[existing_5.clone(), existing_6.clone(), existing_7.clone()],
@@ -155,12 +349,12 @@ mod tests {
             )
             .await;
         assert_matches!(
-            ids,
-            Ok(res) if res == vec![ParquetFileId::new(1003)]
+            &files_3,
+            Ok(res) if res == &vec![created_1_3.clone()]
         );
 
         // simulate fill implosion of the file (this may happen w/ delete predicates)
-        let ids = commit
+        let files_4 = commit
             .commit(
                 partition_id_1,
                 &[existing_8.clone()],
@@ -170,12 +364,18 @@ mod tests {
             )
             .await;
         assert_matches!(
-            ids,
-            Ok(res) if res == vec![]
+            &files_4,
+            Ok(res) if res.is_empty()
         );
 
+        // the returned files are the ones recorded in the history, field by field
+        let history = commit.history();
+        for (entry, files) in history.iter().zip([files_1, files_2, files_3, files_4]) {
+            assert_eq!(entry.created, files.unwrap());
+        }
+
         assert_eq!(
-            commit.history(),
+            history,
             vec![
                 CommitHistoryEntry {
                     partition_id: partition_id_1,
@@ -208,4 +408,353 @@ mod tests {
             ]
         )
     }
+
+    #[tokio::test]
+    async fn test_commit_batch() {
+        let commit = MockCommit::new();
+
+        let existing_1 = ParquetFileBuilder::new(1).build();
+        let existing_2 = ParquetFileBuilder::new(2).build();
+        let existing_3 = ParquetFileBuilder::new(3).build();
+        let created_1 = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(1))
+            .build();
+        let created_2 = ParquetFileBuilder::new(1001)
+            .with_partition(partition_identifier(2))
+            .build();
+
+        let batch = [
+            CommitUpdate::new(
+                PartitionId::new(1),
+                vec![existing_1.clone()],
+                vec![],
+                vec![created_1.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            ),
+            CommitUpdate::new(
+                PartitionId::new(2),
+                vec![existing_2.clone()],
+                vec![existing_3.clone()],
+                vec![created_2.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            ),
+        ];
+        let files = commit.commit_batch(&batch).await;
+        assert_matches!(
+            files,
+            Ok(res) if res == vec![vec![created_1.clone()], vec![created_2.clone()]]
+        );
+
+        let files = commit
+            .commit_batch(&[CommitUpdate::new(
+                PartitionId::new(1),
+                vec![],
+                vec![existing_3.clone()],
+                vec![],
+                CompactionLevel::Final,
+            )])
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![vec![]]);
+
+        // batches are not recorded as single commits
+        assert_eq!(commit.history(), vec![]);
+        assert_eq!(
+            commit.batch_history(),
+            vec![
+                CommitBatchHistoryEntry {
+                    batch_id: 0,
+                    entries: vec![
+                        CommitHistoryEntry {
+                            partition_id: PartitionId::new(1),
+                            delete: vec![existing_1],
+                            upgrade: vec![],
+                            created: vec![created_1],
+                            target_level: CompactionLevel::FileNonOverlapped,
+                        },
+                        CommitHistoryEntry {
+                            partition_id: PartitionId::new(2),
+                            delete: vec![existing_2],
+                            upgrade: vec![existing_3.clone()],
+                            created: vec![created_2],
+                            target_level: CompactionLevel::FileNonOverlapped,
+                        },
+                    ],
+                },
+                CommitBatchHistoryEntry {
+                    batch_id: 1,
+                    entries: vec![CommitHistoryEntry {
+                        partition_id: PartitionId::new(1),
+                        delete: vec![],
+                        upgrade: vec![existing_3],
+                        created: vec![],
+                        target_level: CompactionLevel::Final,
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commit_batch_failure() {
+        let commit = MockCommit::new();
+        commit.fail_partition(PartitionId::new(2));
+
+        let existing_1 = ParquetFileBuilder::new(1).build();
+        let existing_2 = ParquetFileBuilder::new(2).build();
+        let created = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(1))
+            .build();
+
+        let update_1 = CommitUpdate::new(
+            PartitionId::new(1),
+            vec![existing_1],
+            vec![],
+            vec![created.clone().into()],
+            CompactionLevel::FileNonOverlapped,
+        );
+        let update_2 = CommitUpdate::new(
+            PartitionId::new(2),
+            vec![existing_2],
+            vec![],
+            vec![created.clone().into()],
+            CompactionLevel::FileNonOverlapped,
+        );
+
+        // the failing partition fails the whole batch
+        let res = commit.commit_batch(&[update_1.clone(), update_2]).await;
+        assert_matches!(res, Err(Error::BadRequest(e)) if e.contains("partition 2"));
+        assert_eq!(commit.batch_history(), vec![]);
+
+        // as does an injected failure
+        commit.fail_next([Error::BadRequest("nope".into())]);
+        let res = commit.commit_batch(&[update_1.clone()]).await;
+        assert_matches!(res, Err(Error::BadRequest(_)));
+        assert_eq!(commit.batch_history(), vec![]);
+
+        // no IDs were used up by the failed batches
+        let files = commit.commit_batch(&[update_1]).await;
+        assert_matches!(files, Ok(res) if res == vec![vec![created]]);
+        assert_eq!(commit.batch_history().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_commit_dedup_object_store_ids() {
+        let commit = MockCommit::new();
+        commit.dedup_object_store_ids();
+
+        let existing = ParquetFileBuilder::new(1).build();
+        let created_1 = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(1))
+            .build();
+        let created_2 = ParquetFileBuilder::new(1001)
+            .with_partition(partition_identifier(1))
+            .build();
+
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing.clone()],
+                &[],
+                &[created_1.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![created_1.clone()]);
+
+        // retrying the identical commit returns the same file
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing.clone()],
+                &[],
+                &[created_1.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![created_1.clone()]);
+        assert_eq!(commit.history().len(), 1);
+
+        // same for batches
+        let batch = [CommitUpdate::new(
+            PartitionId::new(1),
+            vec![existing.clone()],
+            vec![],
+            vec![created_2.clone().into()],
+            CompactionLevel::FileNonOverlapped,
+        )];
+        let files = commit.commit_batch(&batch).await;
+        assert_matches!(files, Ok(res) if res == vec![vec![created_2.clone()]]);
+        let files = commit.commit_batch(&batch).await;
+        assert_matches!(files, Ok(res) if res == vec![vec![created_2.clone()]]);
+        assert_eq!(commit.batch_history().len(), 1);
+
+        // a commit that creates new files as well is recorded, with the existing file reused
+        let created_3 = ParquetFileBuilder::new(1002)
+            .with_partition(partition_identifier(1))
+            .build();
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing],
+                &[],
+                &[created_1.clone().into(), created_3.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![created_1.clone(), created_3.clone()]);
+        assert_eq!(commit.history().len(), 2);
+
+        // files of other partitions are not reused
+        let files = commit
+            .commit(
+                PartitionId::new(2),
+                &[],
+                &[ParquetFileBuilder::new(2).build()],
+                &[ParquetFileParams {
+                    partition_id: partition_identifier(2),
+                    ..created_1.into()
+                }],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        assert_eq!(files[0].id, ParquetFileId::new(1003));
+        assert_eq!(commit.history().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_history_capacity() {
+        let commit = MockCommit::new().with_history_capacity(NonZeroUsize::new(2).unwrap());
+        let existing = ParquetFileBuilder::new(1).build();
+
+        for i in 1..=3 {
+            commit
+                .commit(
+                    PartitionId::new(i),
+                    &[],
+                    &[existing.clone()],
+                    &[],
+                    CompactionLevel::FileNonOverlapped,
+                )
+                .await
+                .unwrap();
+        }
+
+        // the oldest commit is evicted first
+        assert_eq!(
+            commit
+                .history()
+                .iter()
+                .map(|entry| entry.partition_id)
+                .collect::<Vec<_>>(),
+            vec![PartitionId::new(2), PartitionId::new(3)],
+        );
+
+        commit
+            .commit(
+                PartitionId::new(4),
+                &[],
+                &[existing],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            commit
+                .history()
+                .iter()
+                .map(|entry| entry.partition_id)
+                .collect::<Vec<_>>(),
+            vec![PartitionId::new(3), PartitionId::new(4)],
+        );
+    }
+
+    #[tokio::test]
+    async fn test_history_helpers() {
+        let commit = MockCommit::new().with_history_capacity(NonZeroUsize::new(1).unwrap());
+
+        let existing_1 = ParquetFileBuilder::new(1).build();
+        let existing_2 = ParquetFileBuilder::new(2).build();
+        let existing_3 = ParquetFileBuilder::new(3).build();
+        let created_1 = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(1))
+            .build();
+        let created_2 = ParquetFileBuilder::new(1001)
+            .with_partition(partition_identifier(1))
+            .build();
+        let created_3 = ParquetFileBuilder::new(1002)
+            .with_partition(partition_identifier(2))
+            .build();
+
+        commit
+            .commit(
+                PartitionId::new(1),
+                &[existing_1.clone(), existing_2.clone()],
+                &[],
+                &[created_1.clone().into(), created_2.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        commit
+            .commit(
+                PartitionId::new(2),
+                &[],
+                &[existing_3.clone()],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+
+        // partition 1 was evicted, but still counts towards the totals
+        let expected = vec![CommitHistoryEntry {
+            partition_id: PartitionId::new(2),
+            delete: vec![],
+            upgrade: vec![existing_3.clone()],
+            created: vec![],
+            target_level: CompactionLevel::FileNonOverlapped,
+        }];
+        assert_eq!(commit.history_for_partition(PartitionId::new(1)), vec![]);
+        assert_eq!(commit.history_for_partition(PartitionId::new(2)), expected);
+        assert_eq!(commit.total_created(), 2);
+        assert_eq!(commit.total_deleted(), 2);
+
+        // draining returns and clears the history, but not the totals
+        assert_eq!(commit.drain_history(), expected);
+        assert_eq!(commit.history(), vec![]);
+        assert_eq!(commit.drain_history(), vec![]);
+        assert_eq!(commit.total_created(), 2);
+        assert_eq!(commit.total_deleted(), 2);
+
+        // batches count towards the totals too
+        commit
+            .commit_batch(&[CommitUpdate::new(
+                PartitionId::new(2),
+                vec![existing_3],
+                vec![],
+                vec![created_3.into()],
+                CompactionLevel::FileNonOverlapped,
+            )])
+            .await
+            .unwrap();
+        assert_eq!(commit.total_created(), 3);
+        assert_eq!(commit.total_deleted(), 3);
+
+        // failed commits don't
+        commit.fail_partition(PartitionId::new(1));
+        commit
+            .commit(
+                PartitionId::new(1),
+                &[existing_1],
+                &[],
+                &[created_1.into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(commit.total_created(), 3);
+        assert_eq!(commit.total_deleted(), 3);
+    }
 }
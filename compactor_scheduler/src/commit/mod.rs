@@ -4,11 +4,19 @@ use std::{
 };
 
 use async_trait::async_trait;
-use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
 
+use crate::CommitUpdate;
+
+pub(crate) mod limit;
 pub(crate) mod logging;
 pub(crate) mod metrics;
+#[cfg(test)]
 pub(crate) mod mock;
+pub(crate) mod observer;
+pub(crate) mod retry;
+pub(crate) mod shadow;
+pub(crate) mod validate;
 
 /// Error returned by [`Commit`] implementations.
 #[derive(Debug, thiserror::Error)]
@@ -17,10 +25,22 @@ pub enum Error {
     #[error("Bad commit request: {0}")]
     BadRequest(String),
 
+    /// Commit violates the invariants of a partition, e.g. it would upgrade a deleted file
+    #[error("Commit violates invariants: {0}")]
+    InvalidCommit(String),
+
+    /// Commit exceeds the configured limits on its size
+    #[error("Commit exceeds limits: {0}")]
+    LimitExceeded(String),
+
     /// Commit succeeded, but catalog returned an invalid result
     #[error("Result from catalog is invalid: {0}")]
     InvalidCatalogResult(String),
 
+    /// Commit failed because of an error in the catalog
+    #[error("Catalog query failed: {0}")]
+    CatalogError(#[from] iox_catalog::interface::Error),
+
     /// Commit failed because of an error in the throttler
     #[error("Failure in throttler: {0}")]
     ThrottlerError(#[from] crate::ThrottleError),
@@ -31,7 +51,7 @@ pub enum Error {
 pub trait Commit: Debug + Display + Send + Sync {
     /// Commmit deletions, upgrades and creations in a single transaction.
     ///
-    /// Returns the IDs for the created files.
+    /// Returns the created files, as stored in the catalog.
     ///
     /// This method retries. During the retries, no intermediate states (i.e. some files deleted, some created) will be
     /// visible. Commits are always all-or-nothing.
@@ -42,7 +62,18 @@ pub trait Commit: Debug + Display + Send + Sync {
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, crate::commit::Error>;
+    ) -> Result<Vec<ParquetFile>, crate::commit::Error>;
+
+    /// Commit the changes to several partitions in a single transaction.
+    ///
+    /// Returns the created files of each partition, in the order of the `batch`.
+    ///
+    /// Like [`commit`](Self::commit), this is all-or-nothing: if the change to any partition fails, none of the
+    /// changes are applied.
+    async fn commit_batch(
+        &self,
+        batch: &[CommitUpdate],
+    ) -> Result<Vec<Vec<ParquetFile>>, crate::commit::Error>;
 }
 
 /// Something that can wrap `Commit` instances
@@ -65,9 +96,16 @@ where
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, crate::commit::Error> {
+    ) -> Result<Vec<ParquetFile>, crate::commit::Error> {
         self.as_ref()
             .commit(partition_id, delete, upgrade, create, target_level)
             .await
     }
+
+    async fn commit_batch(
+        &self,
+        batch: &[CommitUpdate],
+    ) -> Result<Vec<Vec<ParquetFile>>, crate::commit::Error> {
+        self.as_ref().commit_batch(batch).await
+    }
 }
@@ -0,0 +1,329 @@
+use std::{
+    fmt::{Debug, Display},
+    panic::AssertUnwindSafe,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
+use observability_deps::tracing::error;
+
+use crate::CommitUpdate;
+
+use super::{Commit, Error};
+
+/// Gets notified of successful commits.
+///
+/// Implement either method, the other one is ignored.
+pub(crate) trait CommitObserver: Debug + Send + Sync {
+    /// Called after the changes to a partition were committed.
+    fn committed(
+        &self,
+        _partition: PartitionId,
+        _deleted: &[ParquetFileId],
+        _upgraded: &[ParquetFileId],
+        _created: &[ParquetFile],
+    ) {
+    }
+
+    /// Called after the changes to a partition were committed, with the deleted and upgraded
+    /// files as they were before the commit.
+    ///
+    /// Defaults to [`committed`](Self::committed).
+    fn committed_files(
+        &self,
+        partition: PartitionId,
+        deleted: &[ParquetFile],
+        upgraded: &[ParquetFile],
+        created: &[ParquetFile],
+        _target_level: CompactionLevel,
+    ) {
+        let deleted = deleted.iter().map(|f| f.id).collect::<Vec<_>>();
+        let upgraded = upgraded.iter().map(|f| f.id).collect::<Vec<_>>();
+        self.committed(partition, &deleted, &upgraded, created)
+    }
+}
+
+/// Notifies [`CommitObserver`]s of the commits of the inner [`Commit`] that succeeded.
+///
+/// Observers can't fail a commit: a panicking observer is logged and skipped.
+#[derive(Debug)]
+pub(crate) struct ObservingCommitWrapper<T>
+where
+    T: Commit,
+{
+    inner: T,
+    observers: Vec<Arc<dyn CommitObserver>>,
+}
+
+impl<T> ObservingCommitWrapper<T>
+where
+    T: Commit,
+{
+    pub(crate) fn new(inner: T, observers: Vec<Arc<dyn CommitObserver>>) -> Self {
+        Self { inner, observers }
+    }
+
+    fn notify(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        created: &[ParquetFile],
+        target_level: CompactionLevel,
+    ) {
+        for observer in &self.observers {
+            let res = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                observer.committed_files(partition_id, delete, upgrade, created, target_level)
+            }));
+            if res.is_err() {
+                error!(
+                    partition_id = partition_id.get(),
+                    ?observer,
+                    "commit observer panicked",
+                );
+            }
+        }
+    }
+}
+
+impl<T> Display for ObservingCommitWrapper<T>
+where
+    T: Commit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "observe({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> Commit for ObservingCommitWrapper<T>
+where
+    T: Commit,
+{
+    async fn commit(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        let created = self
+            .inner
+            .commit(partition_id, delete, upgrade, create, target_level)
+            .await?;
+        self.notify(partition_id, delete, upgrade, &created, target_level);
+
+        Ok(created)
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        let created = self.inner.commit_batch(batch).await?;
+        for (update, created) in batch.iter().zip(&created) {
+            self.notify(
+                update.partition_id,
+                &update.delete,
+                &update.upgrade,
+                created,
+                update.target_level,
+            );
+        }
+
+        Ok(created)
+    }
+}
+
+/// A commit, as seen by a [`RecordingCommitObserver`].
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ObservedCommit {
+    pub(crate) partition_id: PartitionId,
+    pub(crate) deleted: Vec<ParquetFileId>,
+    pub(crate) upgraded: Vec<ParquetFileId>,
+    pub(crate) created: Vec<ParquetFile>,
+}
+
+/// Records the commits it is notified of.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct RecordingCommitObserver {
+    commits: parking_lot::Mutex<Vec<ObservedCommit>>,
+}
+
+#[cfg(test)]
+impl RecordingCommitObserver {
+    pub(crate) fn commits(&self) -> Vec<ObservedCommit> {
+        self.commits.lock().clone()
+    }
+}
+
+#[cfg(test)]
+impl CommitObserver for RecordingCommitObserver {
+    fn committed(
+        &self,
+        partition: PartitionId,
+        deleted: &[ParquetFileId],
+        upgraded: &[ParquetFileId],
+        created: &[ParquetFile],
+    ) {
+        self.commits.lock().push(ObservedCommit {
+            partition_id: partition,
+            deleted: deleted.to_vec(),
+            upgraded: upgraded.to_vec(),
+            created: created.to_vec(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use iox_tests::{partition_identifier, ParquetFileBuilder};
+
+    use super::*;
+    use crate::commit::mock::MockCommit;
+
+    #[derive(Debug)]
+    struct PanickingCommitObserver;
+
+    impl CommitObserver for PanickingCommitObserver {
+        fn committed(
+            &self,
+            _partition: PartitionId,
+            _deleted: &[ParquetFileId],
+            _upgraded: &[ParquetFileId],
+            _created: &[ParquetFile],
+        ) {
+            panic!("observer failed");
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let commit = ObservingCommitWrapper::new(MockCommit::new(), vec![]);
+        assert_eq!(commit.to_string(), "observe(mock)");
+    }
+
+    #[tokio::test]
+    async fn test_notify() {
+        let observer_1 = Arc::new(RecordingCommitObserver::default());
+        let observer_2 = Arc::new(RecordingCommitObserver::default());
+        let commit = ObservingCommitWrapper::new(
+            MockCommit::new(),
+            vec![
+                Arc::clone(&observer_1) as _,
+                Arc::new(PanickingCommitObserver),
+                Arc::clone(&observer_2) as _,
+            ],
+        );
+
+        let existing_1 = ParquetFileBuilder::new(1).build();
+        let existing_2 = ParquetFileBuilder::new(2).build();
+        let created = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(1))
+            .build();
+
+        // the panicking observer neither fails the commit nor keeps the others from being notified
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing_1],
+                &[existing_2],
+                &[created.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![created.clone()]);
+
+        let expected = vec![ObservedCommit {
+            partition_id: PartitionId::new(1),
+            deleted: vec![ParquetFileId::new(1)],
+            upgraded: vec![ParquetFileId::new(2)],
+            created: vec![created],
+        }];
+        assert_eq!(observer_1.commits(), expected);
+        assert_eq!(observer_2.commits(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_not_notified_on_failure() {
+        let observer = Arc::new(RecordingCommitObserver::default());
+        let inner = MockCommit::new();
+        inner.fail_partition(PartitionId::new(1));
+        let commit = ObservingCommitWrapper::new(inner, vec![Arc::clone(&observer) as _]);
+
+        let existing = ParquetFileBuilder::new(1).build();
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[],
+                &[existing.clone()],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(res, Err(Error::BadRequest(_)));
+
+        let res = commit
+            .commit_batch(&[CommitUpdate::new(
+                PartitionId::new(1),
+                vec![],
+                vec![existing],
+                vec![],
+                CompactionLevel::FileNonOverlapped,
+            )])
+            .await;
+        assert_matches!(res, Err(Error::BadRequest(_)));
+
+        assert_eq!(observer.commits(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_notify_batch() {
+        let observer = Arc::new(RecordingCommitObserver::default());
+        let commit =
+            ObservingCommitWrapper::new(MockCommit::new(), vec![Arc::clone(&observer) as _]);
+
+        let created = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(1))
+            .build();
+        commit
+            .commit_batch(&[
+                CommitUpdate::new(
+                    PartitionId::new(1),
+                    vec![ParquetFileBuilder::new(1).build()],
+                    vec![],
+                    vec![created.clone().into()],
+                    CompactionLevel::FileNonOverlapped,
+                ),
+                CommitUpdate::new(
+                    PartitionId::new(2),
+                    vec![],
+                    vec![ParquetFileBuilder::new(2).build()],
+                    vec![],
+                    CompactionLevel::FileNonOverlapped,
+                ),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            observer.commits(),
+            vec![
+                ObservedCommit {
+                    partition_id: PartitionId::new(1),
+                    deleted: vec![ParquetFileId::new(1)],
+                    upgraded: vec![],
+                    created: vec![created],
+                },
+                ObservedCommit {
+                    partition_id: PartitionId::new(2),
+                    deleted: vec![],
+                    upgraded: vec![ParquetFileId::new(2)],
+                    created: vec![],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,491 @@
+use std::{
+    fmt::Display,
+    future::Future,
+    num::NonZeroUsize,
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use backoff::{Backoff, BackoffConfig, BackoffError};
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
+use iox_catalog::interface::{Catalog, Error as CatalogError};
+use metric::{Registry, U64Histogram, U64HistogramOptions};
+use observability_deps::tracing::info;
+
+use crate::CommitUpdate;
+
+use super::{Commit, Error};
+
+const METRIC_NAME_ATTEMPTS: &str = "iox_compactor_commit_attempts";
+
+/// Retries commits that failed because of transient catalog errors.
+///
+/// The inner [`Commit`] is tried again with a backoff until it succeeds, fails permanently, or the
+/// maximum number of attempts is reached. Other errors are returned right away.
+///
+/// An attempt may have been committed even though its response got lost. If a retry then finds
+/// the created files already existing, or the deleted files already gone, the commit is considered
+/// done and the created files are looked up in the catalog.
+#[derive(Debug)]
+pub(crate) struct RetryCommitWrapper<T>
+where
+    T: Commit,
+{
+    inner: T,
+    backoff_config: BackoffConfig,
+    max_attempts: Option<NonZeroUsize>,
+    catalog: Arc<dyn Catalog>,
+    attempts_ok: U64Histogram,
+    attempts_error: U64Histogram,
+}
+
+impl<T> RetryCommitWrapper<T>
+where
+    T: Commit,
+{
+    /// Create a new wrapper, retrying forever if `max_attempts` is `None`.
+    pub(crate) fn new(
+        inner: T,
+        backoff_config: BackoffConfig,
+        max_attempts: Option<NonZeroUsize>,
+        catalog: Arc<dyn Catalog>,
+        registry: &Registry,
+    ) -> Self {
+        let metric = registry.register_metric_with_options::<U64Histogram, _>(
+            METRIC_NAME_ATTEMPTS,
+            "Number of attempts it took to commit to the catalog",
+            || U64HistogramOptions::new([1, 2, 3, 5, 10, 100, u64::MAX]),
+        );
+        Self {
+            inner,
+            backoff_config,
+            max_attempts,
+            catalog,
+            attempts_ok: metric.recorder(&[("result", "ok")]),
+            attempts_error: metric.recorder(&[("result", "error")]),
+        }
+    }
+
+    /// Look up the `create`d files, if they all exist.
+    async fn created_files(&self, create: &[ParquetFileParams]) -> Option<Vec<ParquetFile>> {
+        let mut repos = self.catalog.repositories().await;
+        let mut files = Vec::with_capacity(create.len());
+        for params in create {
+            let file = repos
+                .parquet_files()
+                .get_by_object_store_id(params.object_store_id)
+                .await
+                .ok()??;
+            files.push(file);
+        }
+        Some(files)
+    }
+
+    /// Run `commit` until it succeeds, fails permanently or runs out of attempts.
+    ///
+    /// Errors of attempts after the first that indicate an earlier attempt was committed are passed
+    /// to `already_committed`, together with the attempt number.
+    async fn retry<R, C, CFut, L, LFut>(&self, commit: C, already_committed: L) -> Result<R, Error>
+    where
+        R: Send,
+        C: Fn() -> CFut + Send + Sync,
+        CFut: Future<Output = Result<R, Error>> + Send,
+        L: Fn(usize, Error) -> LFut + Send + Sync,
+        LFut: Future<Output = Result<R, Error>> + Send,
+    {
+        let attempts = AtomicUsize::new(0);
+        let commit = &commit;
+        let already_committed = &already_committed;
+
+        let res = Backoff::new(&self.backoff_config)
+            .retry_with_backoff("commit parquet file changes", || async {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+
+                let res = match commit().await {
+                    Err(e) if attempt > 1 && is_already_committed(&e) => {
+                        already_committed(attempt, e).await
+                    }
+                    res => res,
+                };
+
+                match res {
+                    Err(e)
+                        if is_transient(&e)
+                            && self.max_attempts.map_or(true, |max| attempt < max.get()) =>
+                    {
+                        ControlFlow::Continue(e)
+                    }
+                    res => ControlFlow::Break(res),
+                }
+            })
+            .await
+            .unwrap_or_else(|e| match e {
+                BackoffError::DeadlineExceeded { source, .. } => Err(source),
+            });
+
+        let attempts = attempts.into_inner() as u64;
+        match &res {
+            Ok(_) => self.attempts_ok.record(attempts),
+            Err(_) => self.attempts_error.record(attempts),
+        }
+
+        res
+    }
+}
+
+impl<T> Display for RetryCommitWrapper<T>
+where
+    T: Commit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "retry({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> Commit for RetryCommitWrapper<T>
+where
+    T: Commit,
+{
+    async fn commit(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        self.retry(
+            || {
+                self.inner
+                    .commit(partition_id, delete, upgrade, create, target_level)
+            },
+            |attempt, e| async move {
+                match self.created_files(create).await {
+                    Some(created) => {
+                        info!(
+                            partition_id = partition_id.get(),
+                            attempt,
+                            %e,
+                            "commit was already done by an earlier attempt",
+                        );
+                        Ok(created)
+                    }
+                    None => Err(e),
+                }
+            },
+        )
+        .await
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        self.retry(
+            || self.inner.commit_batch(batch),
+            |attempt, e| async move {
+                // the batch is committed as a whole, so either all created files exist or none
+                let mut created = Vec::with_capacity(batch.len());
+                for update in batch {
+                    match self.created_files(&update.create).await {
+                        Some(files) => created.push(files),
+                        None => return Err(e),
+                    }
+                }
+                info!(
+                    partition_ids=?batch.iter().map(|u| u.partition_id.get()).collect::<Vec<_>>(),
+                    attempt,
+                    %e,
+                    "batch commit was already done by an earlier attempt",
+                );
+                Ok(created)
+            },
+        )
+        .await
+    }
+}
+
+/// Errors that may go away when trying again, e.g. because the connection to the catalog broke.
+fn is_transient(e: &Error) -> bool {
+    matches!(
+        e,
+        Error::CatalogError(
+            CatalogError::SqlxError { .. }
+                | CatalogError::StartTransaction { .. }
+                | CatalogError::FailedToCommit { .. }
+        )
+    )
+}
+
+/// Errors that a retry returns if an earlier attempt was committed.
+fn is_already_committed(e: &Error) -> bool {
+    matches!(
+        e,
+        Error::CatalogError(
+            CatalogError::FileExists { .. }
+                | CatalogError::FileNotFound { .. }
+                | CatalogError::ParquetRecordNotFound { .. }
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+    use data_types::ColumnType;
+    use iox_tests::{ParquetFileBuilder, TestCatalog, TestParquetFileBuilder};
+    use metric::{assert_histogram, Attributes};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::commit::mock::MockCommit;
+
+    fn backoff_config() -> BackoffConfig {
+        BackoffConfig {
+            init_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            ..Default::default()
+        }
+    }
+
+    fn transient_error() -> Error {
+        Error::CatalogError(CatalogError::SqlxError {
+            source: sqlx::Error::PoolTimedOut,
+        })
+    }
+
+    fn wrapper(
+        inner: Arc<MockCommit>,
+        max_attempts: Option<usize>,
+        catalog: Arc<dyn Catalog>,
+        registry: &Registry,
+    ) -> RetryCommitWrapper<Arc<MockCommit>> {
+        RetryCommitWrapper::new(
+            inner,
+            backoff_config(),
+            max_attempts.map(|n| NonZeroUsize::new(n).unwrap()),
+            catalog,
+            registry,
+        )
+    }
+
+    #[test]
+    fn test_display() {
+        let commit = wrapper(
+            Arc::new(MockCommit::new()),
+            None,
+            TestCatalog::new().catalog(),
+            &Registry::new(),
+        );
+        assert_eq!(commit.to_string(), "retry(mock)");
+    }
+
+    #[tokio::test]
+    async fn test_transient_then_success() {
+        let registry = Registry::new();
+        let inner = Arc::new(MockCommit::new());
+        let commit = wrapper(
+            Arc::clone(&inner),
+            Some(3),
+            TestCatalog::new().catalog(),
+            &registry,
+        );
+        inner.fail_next([transient_error(), transient_error()]);
+
+        let existing = ParquetFileBuilder::new(1).build();
+        let created = ParquetFileBuilder::new(1000).build();
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing],
+                &[],
+                &[created.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![created]);
+        assert_eq!(inner.history().len(), 1);
+
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ATTEMPTS,
+            labels = Attributes::from(&[("result", "ok")]),
+            samples = 1,
+            sum = 3,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exhausted() {
+        let registry = Registry::new();
+        let inner = Arc::new(MockCommit::new());
+        let commit = wrapper(
+            Arc::clone(&inner),
+            Some(3),
+            TestCatalog::new().catalog(),
+            &registry,
+        );
+        inner.fail_next([transient_error(), transient_error(), transient_error()]);
+
+        let existing = ParquetFileBuilder::new(1).build();
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[],
+                &[existing],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::CatalogError(CatalogError::SqlxError { .. }))
+        );
+        assert_eq!(inner.history(), vec![]);
+
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ATTEMPTS,
+            labels = Attributes::from(&[("result", "error")]),
+            samples = 1,
+            sum = 3,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_permanent_error() {
+        let registry = Registry::new();
+        let inner = Arc::new(MockCommit::new());
+        let commit = wrapper(
+            Arc::clone(&inner),
+            None,
+            TestCatalog::new().catalog(),
+            &registry,
+        );
+        inner.fail_next([Error::BadRequest("nope".into())]);
+
+        let existing = ParquetFileBuilder::new(1).build();
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[],
+                &[existing],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(res, Err(Error::BadRequest(_)));
+
+        // "already committed" errors are permanent on the first attempt
+        let existing = ParquetFileBuilder::new(2).build();
+        inner.fail_next([Error::CatalogError(CatalogError::FileExists {
+            object_store_id: Uuid::new_v4(),
+        })]);
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing],
+                &[],
+                &[ParquetFileBuilder::new(1000).build().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::CatalogError(CatalogError::FileExists { .. }))
+        );
+        assert_eq!(inner.history(), vec![]);
+
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ATTEMPTS,
+            labels = Attributes::from(&[("result", "error")]),
+            samples = 2,
+            sum = 2,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lost_response() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table = ns.create_table("table1").await;
+        table.create_column("time", ColumnType::Time).await;
+        table.create_column("load", ColumnType::F64).await;
+        let partition = table.create_partition("k").await;
+        let file_builder = TestParquetFileBuilder::default().with_line_protocol("table1 load=1 11");
+        let existing: ParquetFile = partition
+            .create_parquet_file(file_builder.clone())
+            .await
+            .into();
+        // committed by the attempt whose response got lost
+        let created: ParquetFile = partition.create_parquet_file(file_builder).await.into();
+
+        let registry = Registry::new();
+        let inner = Arc::new(MockCommit::new());
+        let commit = wrapper(Arc::clone(&inner), Some(3), catalog.catalog(), &registry);
+        inner.fail_next([
+            transient_error(),
+            Error::CatalogError(CatalogError::FileExists {
+                object_store_id: created.object_store_id,
+            }),
+        ]);
+
+        let files = commit
+            .commit(
+                partition.partition.id,
+                &[existing],
+                &[],
+                &[created.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![created.clone()]);
+        assert_eq!(inner.history(), vec![]);
+
+        // unless the created files don't exist
+        inner.fail_next([
+            transient_error(),
+            Error::CatalogError(CatalogError::FileExists {
+                object_store_id: Uuid::new_v4(),
+            }),
+        ]);
+        let res = commit
+            .commit(
+                partition.partition.id,
+                &[created],
+                &[],
+                &[ParquetFileBuilder::new(1000).build().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::CatalogError(CatalogError::FileExists { .. }))
+        );
+
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ATTEMPTS,
+            labels = Attributes::from(&[("result", "ok")]),
+            samples = 1,
+            sum = 2,
+        );
+        assert_histogram!(
+            registry,
+            U64Histogram,
+            METRIC_NAME_ATTEMPTS,
+            labels = Attributes::from(&[("result", "error")]),
+            samples = 1,
+            sum = 2,
+        );
+    }
+}
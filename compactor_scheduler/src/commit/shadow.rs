@@ -0,0 +1,256 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::atomic::{AtomicI64, Ordering},
+};
+
+use async_trait::async_trait;
+use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
+use observability_deps::tracing::info;
+use parking_lot::Mutex;
+
+use crate::CommitUpdate;
+
+use super::{Commit, Error};
+
+/// Number of commits kept in the history of a [`ShadowCommit`].
+const HISTORY_CAPACITY: usize = 1_000;
+
+/// What would have been committed in shadow mode, see [`Scheduler::shadow_commits`].
+///
+/// [`Scheduler::shadow_commits`]: crate::Scheduler::shadow_commits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowCommitEntry {
+    /// Partition of the commit.
+    pub partition_id: PartitionId,
+    /// Files that would have been deleted.
+    pub delete: Vec<ParquetFileId>,
+    /// Files that would have been upgraded.
+    pub upgrade: Vec<ParquetFileId>,
+    /// Created files, with their synthetic IDs.
+    pub created: Vec<ParquetFile>,
+    /// Level of the upgraded and created files.
+    pub target_level: CompactionLevel,
+    /// Total size of the deleted files.
+    pub bytes_delete: i64,
+    /// Total size of the upgraded files.
+    pub bytes_upgrade: i64,
+    /// Total size of the created files.
+    pub bytes_create: i64,
+}
+
+/// Records what would have been committed, without touching the catalog.
+///
+/// Used in shadow mode. Created files get synthetic, negative IDs so that they never clash with
+/// the IDs assigned by the catalog. Every commit is logged, and the most recent ones are kept in
+/// memory.
+#[derive(Debug)]
+pub(crate) struct ShadowCommit {
+    id_counter: AtomicI64,
+    history: Mutex<VecDeque<ShadowCommitEntry>>,
+}
+
+impl ShadowCommit {
+    pub(crate) fn new() -> Self {
+        Self {
+            id_counter: AtomicI64::new(-1),
+            history: Default::default(),
+        }
+    }
+
+    /// The recorded commits, oldest first.
+    pub(crate) fn history(&self) -> Vec<ShadowCommitEntry> {
+        self.history.lock().iter().cloned().collect()
+    }
+}
+
+impl Display for ShadowCommit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "shadow")
+    }
+}
+
+#[async_trait]
+impl Commit for ShadowCommit {
+    async fn commit(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        let created: Vec<_> = create
+            .iter()
+            .map(|params| {
+                let id = ParquetFileId::new(self.id_counter.fetch_sub(1, Ordering::SeqCst));
+                ParquetFile::from_params(params.clone(), id)
+            })
+            .collect();
+
+        let entry = ShadowCommitEntry {
+            partition_id,
+            delete: delete.iter().map(|f| f.id).collect(),
+            upgrade: upgrade.iter().map(|f| f.id).collect(),
+            created: created.clone(),
+            target_level,
+            bytes_delete: delete.iter().map(|f| f.file_size_bytes).sum(),
+            bytes_upgrade: upgrade.iter().map(|f| f.file_size_bytes).sum(),
+            bytes_create: create.iter().map(|f| f.file_size_bytes).sum(),
+        };
+        info!(
+            target_level=?entry.target_level,
+            partition_id=entry.partition_id.get(),
+            files_delete=entry.delete.len(),
+            files_upgrade=entry.upgrade.len(),
+            files_create=entry.created.len(),
+            bytes_delete=entry.bytes_delete,
+            bytes_upgrade=entry.bytes_upgrade,
+            bytes_create=entry.bytes_create,
+            delete=?entry.delete.iter().map(|id| id.get()).collect::<Vec<_>>(),
+            upgrade=?entry.upgrade.iter().map(|id| id.get()).collect::<Vec<_>>(),
+            create=?created.iter().map(|f| f.id.get()).collect::<Vec<_>>(),
+            "shadow commit of parquet file change",
+        );
+        let mut history = self.history.lock();
+        if history.len() == HISTORY_CAPACITY {
+            history.pop_front();
+        }
+        history.push_back(entry);
+
+        Ok(created)
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        // nothing is written, so there is nothing to roll back either
+        let mut created = Vec::with_capacity(batch.len());
+        for update in batch {
+            created.push(
+                self.commit(
+                    update.partition_id,
+                    &update.delete,
+                    &update.upgrade,
+                    &update.create,
+                    update.target_level,
+                )
+                .await?,
+            );
+        }
+        Ok(created)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use iox_tests::{partition_identifier, ParquetFileBuilder};
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ShadowCommit::new().to_string(), "shadow");
+    }
+
+    #[tokio::test]
+    async fn test_commit() {
+        let commit = ShadowCommit::new();
+
+        let existing_1 = ParquetFileBuilder::new(1).with_file_size_bytes(10).build();
+        let existing_2 = ParquetFileBuilder::new(2).with_file_size_bytes(20).build();
+        let created_1 = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(1))
+            .with_file_size_bytes(5)
+            .build();
+        let created_2 = ParquetFileBuilder::new(1001)
+            .with_partition(partition_identifier(1))
+            .with_file_size_bytes(6)
+            .build();
+
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing_1.clone()],
+                &[existing_2.clone()],
+                &[created_1.clone().into(), created_2.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            files.iter().map(|f| f.id).collect::<Vec<_>>(),
+            vec![ParquetFileId::new(-1), ParquetFileId::new(-2)]
+        );
+
+        let no_files = commit
+            .commit(
+                PartitionId::new(2),
+                &[],
+                &[existing_1],
+                &[],
+                CompactionLevel::Final,
+            )
+            .await;
+        assert_matches!(no_files, Ok(res) if res.is_empty());
+
+        let history = commit.history();
+        assert_eq!(history[0].created, files);
+        assert_eq!(
+            history,
+            vec![
+                ShadowCommitEntry {
+                    partition_id: PartitionId::new(1),
+                    delete: vec![ParquetFileId::new(1)],
+                    upgrade: vec![ParquetFileId::new(2)],
+                    created: vec![
+                        ParquetFile {
+                            id: ParquetFileId::new(-1),
+                            ..created_1
+                        },
+                        ParquetFile {
+                            id: ParquetFileId::new(-2),
+                            ..created_2
+                        },
+                    ],
+                    target_level: CompactionLevel::FileNonOverlapped,
+                    bytes_delete: 10,
+                    bytes_upgrade: 20,
+                    bytes_create: 11,
+                },
+                ShadowCommitEntry {
+                    partition_id: PartitionId::new(2),
+                    delete: vec![],
+                    upgrade: vec![ParquetFileId::new(1)],
+                    created: vec![],
+                    target_level: CompactionLevel::Final,
+                    bytes_delete: 0,
+                    bytes_upgrade: 10,
+                    bytes_create: 0,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_history_is_bounded() {
+        let commit = ShadowCommit::new();
+        let existing = ParquetFileBuilder::new(1).build();
+
+        for i in 0..(HISTORY_CAPACITY + 1) {
+            commit
+                .commit(
+                    PartitionId::new(i as i64),
+                    &[],
+                    &[existing.clone()],
+                    &[],
+                    CompactionLevel::Final,
+                )
+                .await
+                .unwrap();
+        }
+
+        let entries = commit.history();
+        assert_eq!(entries.len(), HISTORY_CAPACITY);
+        assert_eq!(entries[0].partition_id, PartitionId::new(1));
+    }
+}
@@ -0,0 +1,384 @@
+use std::{collections::HashSet, fmt::Display};
+
+use async_trait::async_trait;
+use data_types::{
+    CompactionLevel, ParquetFile, ParquetFileParams, PartitionId, TransitionPartitionId,
+};
+
+use crate::CommitUpdate;
+
+use super::{Commit, Error};
+
+/// Rejects malformed commits and commits that would leave the partition in an inconsistent state,
+/// before they reach the inner [`Commit`] (and with that the catalog).
+#[derive(Debug)]
+pub(crate) struct ValidatingCommitWrapper<T>
+where
+    T: Commit,
+{
+    inner: T,
+}
+
+impl<T> ValidatingCommitWrapper<T>
+where
+    T: Commit,
+{
+    pub(crate) fn new(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Display for ValidatingCommitWrapper<T>
+where
+    T: Commit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "validate({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<T> Commit for ValidatingCommitWrapper<T>
+where
+    T: Commit,
+{
+    async fn commit(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        check_request(delete, upgrade, create)?;
+        validate(partition_id, delete, upgrade, create, target_level)
+            .map_err(Error::InvalidCommit)?;
+
+        self.inner
+            .commit(partition_id, delete, upgrade, create, target_level)
+            .await
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        let mut partition_ids = HashSet::with_capacity(batch.len());
+        for update in batch {
+            if !partition_ids.insert(update.partition_id) {
+                return Err(Error::InvalidCommit(format!(
+                    "partition {} is committed more than once in the batch",
+                    update.partition_id,
+                )));
+            }
+            check_request(&update.delete, &update.upgrade, &update.create)?;
+            validate(
+                update.partition_id,
+                &update.delete,
+                &update.upgrade,
+                &update.create,
+                update.target_level,
+            )
+            .map_err(Error::InvalidCommit)?;
+        }
+
+        self.inner.commit_batch(batch).await
+    }
+}
+
+/// Check that the commit upgrades files and/or replaces files.
+fn check_request(
+    delete: &[ParquetFile],
+    upgrade: &[ParquetFile],
+    create: &[ParquetFileParams],
+) -> Result<(), Error> {
+    let is_upgrade_commit = !upgrade.is_empty();
+    let is_replacement_commit = !delete.is_empty() || !create.is_empty();
+    let replacement_commit_is_ok = !delete.is_empty() && !create.is_empty();
+
+    match (is_upgrade_commit, is_replacement_commit) {
+        (false, false) => {
+            Err(Error::BadRequest("commit must have files to upgrade, and/or a set of files to replace (delete and create)".into()))
+        }
+        (_, true) if !replacement_commit_is_ok => Err(Error::BadRequest(
+            "replacement commits must have both files to delete and files to create".into(),
+        )),
+        _ => Ok(()), // is ok
+    }
+}
+
+/// Check the invariants of a commit, returning a description of the first violation.
+fn validate(
+    partition_id: PartitionId,
+    delete: &[ParquetFile],
+    upgrade: &[ParquetFile],
+    create: &[ParquetFileParams],
+    target_level: CompactionLevel,
+) -> Result<(), String> {
+    let delete_ids: HashSet<_> = delete.iter().map(|f| f.id).collect();
+    let mut both: Vec<_> = upgrade
+        .iter()
+        .map(|f| f.id)
+        .filter(|id| delete_ids.contains(id))
+        .collect();
+    if !both.is_empty() {
+        both.sort();
+        return Err(format!(
+            "files {both:?} are both deleted and upgraded in partition {partition_id}"
+        ));
+    }
+
+    if let Some(file) = upgrade.iter().find(|f| f.compaction_level == target_level) {
+        return Err(format!(
+            "file {} of partition {partition_id} is upgraded but already at the target level {target_level:?}",
+            file.id,
+        ));
+    }
+
+    if let Some(file) = create.iter().find(|f| f.compaction_level > target_level) {
+        return Err(format!(
+            "created file {} of partition {partition_id} has level {:?} above the target level {target_level:?}",
+            file.object_store_id, file.compaction_level,
+        ));
+    }
+
+    // Files written before the partition got its hash ID are only identified by the catalog ID, so
+    // a hash ID can only be checked against the hash ID of the existing files, if any.
+    let hash_id = delete
+        .iter()
+        .chain(upgrade)
+        .find_map(|f| match &f.partition_id {
+            TransitionPartitionId::Deterministic(hash_id) => Some(hash_id),
+            TransitionPartitionId::Deprecated(_) => None,
+        });
+    for file in create {
+        let matches = match &file.partition_id {
+            TransitionPartitionId::Deprecated(id) => *id == partition_id,
+            TransitionPartitionId::Deterministic(id) => hash_id.map_or(true, |h| h == id),
+        };
+        if !matches {
+            return Err(format!(
+                "created file {} belongs to partition {} instead of partition {partition_id}",
+                file.object_store_id, file.partition_id,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use assert_matches::assert_matches;
+    use iox_tests::{partition_identifier, ParquetFileBuilder};
+
+    use super::*;
+    use crate::commit::mock::{CommitHistoryEntry, MockCommit};
+
+    #[test]
+    fn test_display() {
+        let commit = ValidatingCommitWrapper::new(MockCommit::new());
+        assert_eq!(commit.to_string(), "validate(mock)");
+    }
+
+    /// File of partition 1, at the given level.
+    fn file(id: i64, level: CompactionLevel) -> ParquetFile {
+        ParquetFileBuilder::new(id)
+            .with_partition(partition_identifier(1))
+            .with_compaction_level(level)
+            .build()
+    }
+
+    /// Run a commit to partition 1 at target level L1 and check that it's rejected without
+    /// reaching the inner commit.
+    async fn assert_rejected(
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        msg: &str,
+    ) {
+        let inner = Arc::new(MockCommit::new());
+        let commit = ValidatingCommitWrapper::new(Arc::clone(&inner));
+
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                delete,
+                upgrade,
+                create,
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::InvalidCommit(e)) if e.contains(msg),
+            "expected violation {msg:?}, got {res:?}"
+        );
+        assert_eq!(inner.history(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_valid_commit() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = ValidatingCommitWrapper::new(Arc::clone(&inner));
+
+        let existing_1 = file(1, CompactionLevel::Initial);
+        let existing_2 = file(2, CompactionLevel::Initial);
+        let created = file(1000, CompactionLevel::FileNonOverlapped);
+
+        let files = commit
+            .commit(
+                PartitionId::new(1),
+                &[existing_1.clone()],
+                &[existing_2.clone()],
+                &[created.clone().into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(files, Ok(res) if res == vec![created.clone()]);
+
+        assert_eq!(
+            inner.history(),
+            vec![CommitHistoryEntry {
+                partition_id: PartitionId::new(1),
+                delete: vec![existing_1],
+                upgrade: vec![existing_2],
+                created: vec![created],
+                target_level: CompactionLevel::FileNonOverlapped,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bad_request() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = ValidatingCommitWrapper::new(Arc::clone(&inner));
+
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[],
+                &[],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(res, Err(Error::BadRequest(e)) if e.contains("must have files to upgrade"));
+
+        let res = commit
+            .commit(
+                PartitionId::new(1),
+                &[file(1, CompactionLevel::Initial)],
+                &[],
+                &[],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(res, Err(Error::BadRequest(e)) if e.contains("replacement commits must have both"));
+
+        assert_eq!(inner.history(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_and_upgraded() {
+        let existing = file(1, CompactionLevel::Initial);
+        assert_rejected(
+            &[existing.clone()],
+            &[existing],
+            &[file(1000, CompactionLevel::FileNonOverlapped).into()],
+            "files [ParquetFileId(1)] are both deleted and upgraded",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_upgraded_already_at_target_level() {
+        assert_rejected(
+            &[],
+            &[file(1, CompactionLevel::FileNonOverlapped)],
+            &[],
+            "file 1 of partition 1 is upgraded but already at the target level FileNonOverlapped",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_created_above_target_level() {
+        assert_rejected(
+            &[file(1, CompactionLevel::Initial)],
+            &[],
+            &[file(1000, CompactionLevel::Final).into()],
+            "has level Final above the target level FileNonOverlapped",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_created_in_other_partition() {
+        let existing = file(1, CompactionLevel::Initial);
+
+        // other catalog ID
+        let created = ParquetFileBuilder::new(1000)
+            .with_partition(TransitionPartitionId::Deprecated(PartitionId::new(2)))
+            .build();
+        assert_rejected(
+            &[existing.clone()],
+            &[],
+            &[created.into()],
+            "belongs to partition 2 instead of partition 1",
+        )
+        .await;
+
+        // other hash ID
+        let created = ParquetFileBuilder::new(1000)
+            .with_partition(partition_identifier(2))
+            .build();
+        assert_rejected(
+            &[existing],
+            &[],
+            &[created.into()],
+            "instead of partition 1",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_batch() {
+        let inner = Arc::new(MockCommit::new());
+        let commit = ValidatingCommitWrapper::new(Arc::clone(&inner));
+
+        let update = |level| {
+            CommitUpdate::new(
+                PartitionId::new(1),
+                vec![],
+                vec![file(1, level)],
+                vec![],
+                CompactionLevel::FileNonOverlapped,
+            )
+        };
+
+        // every commit of the batch is validated
+        let res = commit
+            .commit_batch(&[update(CompactionLevel::FileNonOverlapped)])
+            .await;
+        assert_matches!(res, Err(Error::InvalidCommit(e)) if e.contains("already at the target level"));
+
+        // a partition may only be committed once per batch
+        let res = commit
+            .commit_batch(&[
+                update(CompactionLevel::Initial),
+                update(CompactionLevel::Initial),
+            ])
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::InvalidCommit(e)) if e == "partition 1 is committed more than once in the batch"
+        );
+        assert_eq!(inner.batch_history(), vec![]);
+
+        let res = commit
+            .commit_batch(&[update(CompactionLevel::Initial)])
+            .await;
+        assert_matches!(res, Ok(created) if created == vec![vec![]]);
+        assert_eq!(inner.batch_history().len(), 1);
+    }
+}
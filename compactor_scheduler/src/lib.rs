@@ -27,8 +27,13 @@ use iox_time::TimeProvider;
 use workspace_hack as _;
 
 pub(crate) mod commit;
+#[cfg(test)]
 pub(crate) use commit::mock::MockCommit;
-pub use commit::{Commit, CommitWrapper, Error as CommitError};
+pub use commit::{
+    limit::{CommitLimitPolicy, CommitLimits},
+    shadow::ShadowCommitEntry,
+    Commit, CommitWrapper, Error as CommitError,
+};
 
 mod error;
 pub use error::ErrorKind;
@@ -101,6 +106,8 @@ pub fn create_test_scheduler(
             ),
             shard_config: None,
             ignore_partition_skip_marker: false,
+            commit_max_attempts: None,
+            commit_limits: CommitLimits::default(),
         }),
     };
     create_scheduler(
@@ -8,7 +8,7 @@ pub(crate) mod partitions_source_config;
 pub(crate) mod partitions_subset_source;
 pub(crate) mod shard_config;
 
-use std::{sync::Arc, time::Duration};
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use backoff::BackoffConfig;
@@ -17,11 +17,19 @@ use iox_time::TimeProvider;
 use observability_deps::tracing::{info, warn};
 
 use crate::{
-    commit::{logging::LoggingCommitWrapper, metrics::MetricsCommitWrapper},
-    Commit, CommitUpdate, CommitWrapper, CompactionJob, CompactionJobEnd, CompactionJobEndVariant,
-    CompactionJobStatus, CompactionJobStatusResponse, CompactionJobStatusVariant, MockCommit,
-    MockPartitionsSource, PartitionsSource, PartitionsSourceConfig, Scheduler, ShardConfig,
-    SkipReason,
+    commit::{
+        limit::LimitCommitWrapper,
+        logging::LoggingCommitWrapper,
+        metrics::{MetricsCommitObserver, MetricsCommitWrapper},
+        observer::ObservingCommitWrapper,
+        retry::RetryCommitWrapper,
+        shadow::ShadowCommit,
+        validate::ValidatingCommitWrapper,
+    },
+    Commit, CommitLimits, CommitUpdate, CommitWrapper, CompactionJob, CompactionJobEnd,
+    CompactionJobEndVariant, CompactionJobStatus, CompactionJobStatusResponse,
+    CompactionJobStatusVariant, MockPartitionsSource, PartitionsSource, PartitionsSourceConfig,
+    Scheduler, ShadowCommitEntry, ShardConfig, SkipReason,
 };
 
 use self::{
@@ -54,6 +62,10 @@ pub struct LocalSchedulerConfig {
     pub shard_config: Option<ShardConfig>,
     /// If skipped partitions should be removed from the partitions_source.
     pub ignore_partition_skip_marker: bool,
+    /// Maximum number of attempts to commit to the catalog, retrying forever if `None`.
+    pub commit_max_attempts: Option<NonZeroUsize>,
+    /// Limits on the size of a single commit.
+    pub commit_limits: CommitLimits,
 }
 
 /// Implementation of the scheduler for local (per compactor) scheduling.
@@ -61,6 +73,8 @@ pub struct LocalSchedulerConfig {
 pub(crate) struct LocalScheduler {
     /// Commits changes (i.e. deletion and creation) to the catalog
     pub(crate) commit: Arc<dyn Commit>,
+    /// Records the changes instead of the catalog, in shadow mode.
+    shadow_commit: Option<Arc<ShadowCommit>>,
     /// The partitions source to use for scheduling.
     partitions_source: Arc<dyn PartitionsSource>,
     /// The actions to take when a partition is done.
@@ -81,12 +95,13 @@ impl LocalScheduler {
         metrics: Arc<metric::Registry>,
         shadow_mode: bool,
     ) -> Self {
+        let shadow_commit = shadow_mode.then(|| Arc::new(ShadowCommit::new()));
         let commit = Self::build_commit(
             config.clone(),
             backoff_config.clone(),
             Arc::clone(&catalog),
             metrics,
-            shadow_mode,
+            shadow_commit.clone(),
         );
 
         let partitions_source = Self::build_partitions_source(
@@ -107,6 +122,7 @@ impl LocalScheduler {
 
         Self {
             commit,
+            shadow_commit,
             partitions_source,
             partition_done_sink,
             shard_config: config.shard_config,
@@ -211,13 +227,23 @@ impl LocalScheduler {
         backoff_config: BackoffConfig,
         catalog: Arc<dyn Catalog>,
         metrics_registry: Arc<metric::Registry>,
-        shadow_mode: bool,
+        shadow_commit: Option<Arc<ShadowCommit>>,
     ) -> Arc<dyn Commit> {
-        let commit: Arc<dyn Commit> = if shadow_mode {
-            Arc::new(MockCommit::new())
-        } else {
-            Arc::new(CatalogCommit::new(backoff_config, Arc::clone(&catalog)))
+        let commit: Arc<dyn Commit> = match shadow_commit {
+            Some(shadow_commit) => shadow_commit,
+            None => Arc::new(CatalogCommit::new(Arc::clone(&catalog))),
         };
+        let commit: Arc<dyn Commit> =
+            Arc::new(ValidatingCommitWrapper::new(LimitCommitWrapper::new(
+                RetryCommitWrapper::new(
+                    commit,
+                    backoff_config,
+                    config.commit_max_attempts,
+                    catalog,
+                    &metrics_registry,
+                ),
+                config.commit_limits,
+            )));
 
         let commit = if let Some(commit_wrapper) = &config.commit_wrapper {
             commit_wrapper.wrap(commit)
@@ -225,6 +251,11 @@ impl LocalScheduler {
             commit
         };
 
+        let commit = ObservingCommitWrapper::new(
+            commit,
+            vec![Arc::new(MetricsCommitObserver::new(&metrics_registry))],
+        );
+
         Arc::new(LoggingCommitWrapper::new(MetricsCommitWrapper::new(
             commit,
             &metrics_registry,
@@ -293,6 +324,13 @@ impl Scheduler for LocalScheduler {
             }
         }
     }
+
+    fn shadow_commits(&self) -> Vec<ShadowCommitEntry> {
+        self.shadow_commit
+            .as_ref()
+            .map(|shadow_commit| shadow_commit.history())
+            .unwrap_or_default()
+    }
 }
 
 impl std::fmt::Display for LocalScheduler {
@@ -306,7 +344,8 @@ impl std::fmt::Display for LocalScheduler {
 
 #[cfg(test)]
 mod tests {
-    use iox_tests::TestCatalog;
+    use data_types::{ColumnType, CompactionLevel, ParquetFile};
+    use iox_tests::{ParquetFileBuilder, TestCatalog, TestParquetFileBuilder};
     use iox_time::{MockProvider, Time};
 
     use super::*;
@@ -337,6 +376,8 @@ mod tests {
             partitions_source_config: PartitionsSourceConfig::default(),
             shard_config,
             ignore_partition_skip_marker: false,
+            commit_max_attempts: None,
+            commit_limits: CommitLimits::default(),
         };
 
         let scheduler = LocalScheduler::new(
@@ -353,4 +394,66 @@ mod tests {
             "local_compaction_scheduler(shard_cfg(n_shards=2,shard_id=1))",
         );
     }
+
+    #[tokio::test]
+    async fn test_shadow_mode_does_not_touch_catalog() {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table = ns.create_table("table1").await;
+        table.create_column("time", ColumnType::Time).await;
+        table.create_column("load", ColumnType::F64).await;
+        let partition = table.create_partition("k").await;
+        let file_builder = TestParquetFileBuilder::default().with_line_protocol("table1 load=1 11");
+        let existing_1: ParquetFile = partition
+            .create_parquet_file(file_builder.clone())
+            .await
+            .into();
+        let existing_2: ParquetFile = partition.create_parquet_file(file_builder).await.into();
+
+        let list_files = || async {
+            let mut files = catalog
+                .catalog()
+                .repositories()
+                .await
+                .parquet_files()
+                .list_by_partition_not_to_delete(&partition.partition.transition_partition_id())
+                .await
+                .unwrap();
+            files.sort_by_key(|f| f.id);
+            files
+        };
+        let files_before = list_files().await;
+
+        let scheduler = LocalScheduler::new(
+            LocalSchedulerConfig::default(),
+            BackoffConfig::default(),
+            catalog.catalog(),
+            Arc::new(MockProvider::new(Time::MIN)),
+            Arc::new(metric::Registry::default()),
+            true,
+        );
+
+        let created = ParquetFileBuilder::new(1000)
+            .with_partition(partition.partition.transition_partition_id())
+            .build();
+        let files = scheduler
+            .commit
+            .commit(
+                partition.partition.id,
+                &[existing_1],
+                &[existing_2],
+                &[created.into()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+
+        assert_eq!(list_files().await, files_before);
+
+        let shadow_commits = scheduler.shadow_commits();
+        assert_eq!(shadow_commits.len(), 1);
+        assert_eq!(shadow_commits[0].partition_id, partition.partition.id);
+        assert_eq!(shadow_commits[0].created, files);
+    }
 }
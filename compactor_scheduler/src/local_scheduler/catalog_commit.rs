@@ -1,24 +1,76 @@
 use std::{fmt::Display, sync::Arc};
 
 use async_trait::async_trait;
-use backoff::{Backoff, BackoffConfig};
 use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
-use iox_catalog::interface::Catalog;
+use iox_catalog::interface::{Catalog, RepoCollection};
+use observability_deps::tracing::info;
 
-use crate::{commit::Error, Commit};
+use crate::{commit::Error, Commit, CommitUpdate};
 
+/// Commits to the catalog in a single attempt.
+///
+/// Failed attempts are retried by the [`RetryCommitWrapper`](crate::commit::retry::RetryCommitWrapper).
+/// Commits are idempotent: files to create that already exist in their partition are not created
+/// again, so that retrying a commit whose response got lost doesn't fail.
 #[derive(Debug)]
 pub(crate) struct CatalogCommit {
-    backoff_config: BackoffConfig,
     catalog: Arc<dyn Catalog>,
 }
 
 impl CatalogCommit {
-    pub(crate) fn new(backoff_config: BackoffConfig, catalog: Arc<dyn Catalog>) -> Self {
-        Self {
-            backoff_config,
-            catalog,
+    pub(crate) fn new(catalog: Arc<dyn Catalog>) -> Self {
+        Self { catalog }
+    }
+
+    /// Apply the changes in a single transaction, returning the created files.
+    ///
+    /// The existing row is returned for files to create whose object store ID is already in the
+    /// catalog for the same partition.
+    async fn apply(
+        &self,
+        delete: &[ParquetFileId],
+        upgrade: &[ParquetFileId],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) -> Result<Vec<ParquetFile>, Error> {
+        let mut repos = self.catalog.repositories().await;
+
+        let mut existing = Vec::with_capacity(create.len());
+        for params in create {
+            let file = repos
+                .parquet_files()
+                .get_by_object_store_id(params.object_store_id)
+                .await?
+                .filter(|f| f.partition_id == params.partition_id);
+            if let Some(file) = &file {
+                info!(
+                    partition_id=%file.partition_id,
+                    parquet_file_id=file.id.get(),
+                    object_store_id=%file.object_store_id,
+                    "parquet file to create already exists, not creating it again",
+                );
+            }
+            existing.push(file);
         }
+        let new = create
+            .iter()
+            .zip(&existing)
+            .filter(|(_, file)| file.is_none())
+            .map(|(params, _)| params.clone())
+            .collect::<Vec<_>>();
+
+        let ids = repos
+            .parquet_files()
+            .create_upgrade_delete(delete, upgrade, &new, target_level)
+            .await?;
+        let mut created = created_files(repos.as_mut(), &new, ids).await?.into_iter();
+
+        Ok(existing
+            .into_iter()
+            .map(|file| {
+                file.unwrap_or_else(|| created.next().expect("checked number of created files"))
+            })
+            .collect())
     }
 }
 
@@ -37,47 +89,300 @@ impl Commit for CatalogCommit {
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, Error> {
-        let is_upgrade_commit = !upgrade.is_empty();
-        let is_replacement_commit = !delete.is_empty() || !create.is_empty();
-        let replacement_commit_is_ok = !delete.is_empty() && !create.is_empty();
-
-        match (is_upgrade_commit, is_replacement_commit) {
-            (false, false) => {
-                return Err(Error::BadRequest("commit must have files to upgrade, and/or a set of files to replace (delete and create)".into()));
-            }
-            (_, true) if !replacement_commit_is_ok => {
-                return Err(Error::BadRequest(
-                    "replacement commits must have both files to delete and files to create".into(),
-                ));
+    ) -> Result<Vec<ParquetFile>, Error> {
+        let delete = delete.iter().map(|f| f.id).collect::<Vec<_>>();
+        let upgrade = upgrade.iter().map(|f| f.id).collect::<Vec<_>>();
+
+        self.apply(&delete, &upgrade, create, target_level).await
+    }
+
+    async fn commit_batch(&self, batch: &[CommitUpdate]) -> Result<Vec<Vec<ParquetFile>>, Error> {
+        let Some(target_level) = batch.first().map(|update| update.target_level) else {
+            return Ok(vec![]);
+        };
+        for update in batch {
+            if update.target_level != target_level {
+                return Err(Error::BadRequest(format!(
+                    "all commits of a batch must have the same target level, but partition {} has {:?} instead of {:?}",
+                    update.partition_id, update.target_level, target_level,
+                )));
             }
-            _ => {} // is ok
         }
 
-        let delete = delete.iter().map(|f| f.id).collect::<Vec<_>>();
-        let upgrade = upgrade.iter().map(|f| f.id).collect::<Vec<_>>();
+        // A single catalog call is a single transaction, so a failure of any partition rolls back
+        // the whole batch.
+        let delete = batch
+            .iter()
+            .flat_map(|update| update.delete.iter().map(|f| f.id))
+            .collect::<Vec<_>>();
+        let upgrade = batch
+            .iter()
+            .flat_map(|update| update.upgrade.iter().map(|f| f.id))
+            .collect::<Vec<_>>();
+        let create = batch
+            .iter()
+            .flat_map(|update| update.create.iter().cloned())
+            .collect::<Vec<_>>();
 
-        let result = Backoff::new(&self.backoff_config)
-            .retry_all_errors("commit parquet file changes", || async {
-                let mut repos = self.catalog.repositories().await;
-                let parquet_files = repos.parquet_files();
-                let ids = parquet_files
-                    .create_upgrade_delete(&delete, &upgrade, create, target_level)
-                    .await?;
+        let mut created = self
+            .apply(&delete, &upgrade, &create, target_level)
+            .await?
+            .into_iter();
 
-                Ok::<_, iox_catalog::interface::Error>(ids)
-            })
+        Ok(batch
+            .iter()
+            .map(|update| created.by_ref().take(update.create.len()).collect())
+            .collect())
+    }
+}
+
+/// Read back the catalog rows of the created files, in the order of `create`.
+async fn created_files(
+    repos: &mut dyn RepoCollection,
+    create: &[ParquetFileParams],
+    ids: Vec<ParquetFileId>,
+) -> Result<Vec<ParquetFile>, Error> {
+    if ids.len() != create.len() {
+        return Err(Error::InvalidCatalogResult(format!(
+            "Number of created parquet files is invalid: expected {} but found {}",
+            create.len(),
+            ids.len()
+        )));
+    }
+
+    let mut created = Vec::with_capacity(create.len());
+    for (params, id) in create.iter().zip(ids) {
+        let file = repos
+            .parquet_files()
+            .get_by_object_store_id(params.object_store_id)
+            .await?
+            .filter(|f| f.id == id)
+            .ok_or_else(|| {
+                Error::InvalidCatalogResult(format!(
+                    "Created parquet file {} with object store ID {} not found",
+                    id.get(),
+                    params.object_store_id
+                ))
+            })?;
+        created.push(file);
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use data_types::ColumnType;
+    use iox_catalog::interface::Error as CatalogError;
+    use iox_tests::{TestCatalog, TestParquetFileBuilder, TestPartition};
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let commit = CatalogCommit::new(TestCatalog::new().catalog());
+        assert_eq!(commit.to_string(), "catalog");
+    }
+
+    /// Two partitions of a table, with a file each.
+    async fn setup() -> (Arc<TestCatalog>, [(Arc<TestPartition>, ParquetFile); 2]) {
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table = ns.create_table("table1").await;
+        table.create_column("time", ColumnType::Time).await;
+        table.create_column("load", ColumnType::F64).await;
+
+        let file_builder = TestParquetFileBuilder::default().with_line_protocol("table1 load=1 11");
+        let partition_1 = table.create_partition("k1").await;
+        let file_1 = partition_1
+            .create_parquet_file(file_builder.clone())
             .await
-            .expect("retry forever");
-
-        if result.len() != create.len() {
-            return Err(Error::InvalidCatalogResult(format!(
-                "Number of created parquet files is invalid: expected {} but found {}",
-                create.len(),
-                result.len()
-            )));
+            .into();
+        let partition_2 = table.create_partition("k2").await;
+        let file_2 = partition_2.create_parquet_file(file_builder).await.into();
+
+        (catalog, [(partition_1, file_1), (partition_2, file_2)])
+    }
+
+    /// The compacted version of `file`.
+    fn compacted(file: &ParquetFile) -> ParquetFileParams {
+        ParquetFileParams {
+            object_store_id: Uuid::new_v4(),
+            compaction_level: CompactionLevel::FileNonOverlapped,
+            ..file.clone().into()
         }
+    }
+
+    async fn files(catalog: &TestCatalog, partition: &TestPartition) -> Vec<ParquetFile> {
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .parquet_files()
+            .list_by_partition_not_to_delete(&partition.partition.transition_partition_id())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_commit_retry_is_idempotent() {
+        let (catalog, [(partition, file), _]) = setup().await;
+        let commit = CatalogCommit::new(catalog.catalog());
+
+        let create = compacted(&file);
+        let created = commit
+            .commit(
+                partition.partition.id,
+                &[file.clone()],
+                &[],
+                &[create.clone()],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].object_store_id, create.object_store_id);
+
+        // the response of the first attempt got lost
+        let retried = commit
+            .commit(
+                partition.partition.id,
+                &[file],
+                &[],
+                &[create],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await
+            .unwrap();
+        assert_eq!(retried, created);
+        assert_eq!(files(&catalog, &partition).await, created);
+    }
+
+    #[tokio::test]
+    async fn test_commit_existing_file_of_other_partition() {
+        let (catalog, [(partition_1, file_1), (partition_2, file_2)]) = setup().await;
+        let commit = CatalogCommit::new(catalog.catalog());
+
+        // only files of the same partition are considered created already
+        let res = commit
+            .commit(
+                partition_2.partition.id,
+                &[file_2.clone()],
+                &[],
+                &[ParquetFileParams {
+                    object_store_id: file_1.object_store_id,
+                    ..compacted(&file_2)
+                }],
+                CompactionLevel::FileNonOverlapped,
+            )
+            .await;
+        assert_matches!(
+            res,
+            Err(Error::CatalogError(CatalogError::FileExists { .. }))
+        );
+        assert_eq!(files(&catalog, &partition_1).await, vec![file_1]);
+        assert_eq!(files(&catalog, &partition_2).await, vec![file_2]);
+    }
+
+    #[tokio::test]
+    async fn test_commit_batch() {
+        let (catalog, [(partition_1, file_1), (partition_2, file_2)]) = setup().await;
+        let commit = CatalogCommit::new(catalog.catalog());
+
+        let create_1 = compacted(&file_1);
+        let create_2 = compacted(&file_2);
+        let batch = [
+            CommitUpdate::new(
+                partition_1.partition.id,
+                vec![file_1],
+                vec![],
+                vec![create_1.clone()],
+                CompactionLevel::FileNonOverlapped,
+            ),
+            CommitUpdate::new(
+                partition_2.partition.id,
+                vec![file_2],
+                vec![],
+                vec![create_2.clone()],
+                CompactionLevel::FileNonOverlapped,
+            ),
+        ];
+        let created = commit.commit_batch(&batch).await.unwrap();
+
+        assert_eq!(created.len(), 2);
+        assert_eq!(created[0].len(), 1);
+        assert_eq!(created[0][0].object_store_id, create_1.object_store_id);
+        assert_eq!(created[1].len(), 1);
+        assert_eq!(created[1][0].object_store_id, create_2.object_store_id);
+
+        assert_eq!(files(&catalog, &partition_1).await, created[0]);
+        assert_eq!(files(&catalog, &partition_2).await, created[1]);
+    }
+
+    #[tokio::test]
+    async fn test_commit_batch_failure_rolls_back() {
+        let (catalog, [(partition_1, file_1), (partition_2, file_2)]) = setup().await;
+        let commit = CatalogCommit::new(catalog.catalog());
+
+        // the second partition creates a file that already exists
+        let batch = [
+            CommitUpdate::new(
+                partition_1.partition.id,
+                vec![file_1.clone()],
+                vec![],
+                vec![compacted(&file_1)],
+                CompactionLevel::FileNonOverlapped,
+            ),
+            CommitUpdate::new(
+                partition_2.partition.id,
+                vec![file_2.clone()],
+                vec![],
+                vec![ParquetFileParams {
+                    object_store_id: file_1.object_store_id,
+                    ..compacted(&file_2)
+                }],
+                CompactionLevel::FileNonOverlapped,
+            ),
+        ];
+        let res = commit.commit_batch(&batch).await;
+        assert_matches!(
+            res,
+            Err(Error::CatalogError(CatalogError::FileExists { object_store_id }))
+                if object_store_id == file_1.object_store_id
+        );
+
+        // nothing of the batch was applied, not even to the first partition
+        assert_eq!(files(&catalog, &partition_1).await, vec![file_1]);
+        assert_eq!(files(&catalog, &partition_2).await, vec![file_2]);
+    }
+
+    #[tokio::test]
+    async fn test_commit_batch_mixed_target_levels() {
+        let (catalog, [(partition_1, file_1), (partition_2, file_2)]) = setup().await;
+        let commit = CatalogCommit::new(catalog.catalog());
+
+        let batch = [
+            CommitUpdate::new(
+                partition_1.partition.id,
+                vec![],
+                vec![file_1.clone()],
+                vec![],
+                CompactionLevel::FileNonOverlapped,
+            ),
+            CommitUpdate::new(
+                partition_2.partition.id,
+                vec![],
+                vec![file_2.clone()],
+                vec![],
+                CompactionLevel::Final,
+            ),
+        ];
+        let res = commit.commit_batch(&batch).await;
+        assert_matches!(res, Err(Error::BadRequest(e)) if e.contains("same target level"));
 
-        return Ok(result);
+        assert_eq!(files(&catalog, &partition_1).await, vec![file_1]);
+        assert_eq!(files(&catalog, &partition_2).await, vec![file_2]);
     }
 }
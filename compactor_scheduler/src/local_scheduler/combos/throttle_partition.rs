@@ -3,14 +3,14 @@
 use std::{collections::HashMap, fmt::Display, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
-use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
 use futures::{StreamExt, TryStreamExt};
 use iox_time::{Time, TimeProvider};
 use parking_lot::Mutex;
 
 use crate::{
-    local_scheduler::partition_done_sink::DynError, Commit, CommitError, PartitionDoneSink,
-    PartitionDoneSinkError, PartitionsSource,
+    local_scheduler::partition_done_sink::DynError, Commit, CommitError, CommitUpdate,
+    PartitionDoneSink, PartitionDoneSinkError, PartitionsSource,
 };
 
 /// Ensures that partitions that do not receive any commits are throttled.
@@ -224,7 +224,7 @@ where
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, CommitError> {
+    ) -> Result<Vec<ParquetFile>, CommitError> {
         let known = {
             let mut guard = self.state.lock();
             match guard.in_flight.get_mut(&partition_id) {
@@ -244,6 +244,33 @@ where
             .commit(partition_id, delete, upgrade, create, target_level)
             .await
     }
+
+    async fn commit_batch(
+        &self,
+        batch: &[CommitUpdate],
+    ) -> Result<Vec<Vec<ParquetFile>>, CommitError> {
+        let unknown = {
+            let mut guard = self.state.lock();
+            match batch
+                .iter()
+                .find(|update| !guard.in_flight.contains_key(&update.partition_id))
+            {
+                Some(update) => Some(update.partition_id),
+                None => {
+                    for update in batch {
+                        guard.in_flight.insert(update.partition_id, true);
+                    }
+                    None
+                }
+            }
+        };
+        // perform check when NOT holding the mutex to not poison it
+        if let Some(partition_id) = unknown {
+            return Err(Error::Uniqueness(partition_id).into());
+        }
+
+        self.inner.commit_batch(batch).await
+    }
 }
 
 #[derive(Debug)]
@@ -447,25 +474,27 @@ mod tests {
         );
 
         // commits are just forwarded to inner `Commit` impl
-        assert_eq!(
-            inner_commit.history(),
-            vec![
-                CommitHistoryEntry {
-                    partition_id: PartitionId::new(1),
-                    delete: vec![],
-                    upgrade: vec![],
-                    created: vec![],
-                    target_level: CompactionLevel::Initial,
-                },
-                CommitHistoryEntry {
-                    partition_id: PartitionId::new(2),
+        for partition_id in [PartitionId::new(1), PartitionId::new(2)] {
+            assert_eq!(
+                inner_commit.history_for_partition(partition_id),
+                vec![CommitHistoryEntry {
+                    partition_id,
                     delete: vec![],
                     upgrade: vec![],
                     created: vec![],
                     target_level: CompactionLevel::Initial,
-                },
-            ]
-        );
+                }],
+            );
+        }
+        for partition_id in [
+            PartitionId::new(3),
+            PartitionId::new(4),
+            PartitionId::new(5),
+        ] {
+            assert_eq!(inner_commit.history_for_partition(partition_id), vec![]);
+        }
+        assert_eq!(inner_commit.total_created(), 0);
+        assert_eq!(inner_commit.total_deleted(), 0);
     }
 
     #[tokio::test]
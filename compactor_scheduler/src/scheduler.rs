@@ -4,10 +4,13 @@ use std::{
 };
 
 use async_trait::async_trait;
-use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
 use uuid::Uuid;
 
-use crate::{CommitWrapper, ErrorKind, LocalSchedulerConfig, PartitionsSourceConfig};
+use crate::{
+    CommitLimits, CommitWrapper, ErrorKind, LocalSchedulerConfig, PartitionsSourceConfig,
+    ShadowCommitEntry,
+};
 
 /// Scheduler configuration.
 #[derive(Debug, Clone)]
@@ -26,6 +29,8 @@ impl SchedulerConfig {
             partitions_source_config: PartitionsSourceConfig::default(),
             commit_wrapper: Some(commit_wrapper),
             ignore_partition_skip_marker: false,
+            commit_max_attempts: None,
+            commit_limits: CommitLimits::default(),
         })
     }
 }
@@ -44,6 +49,8 @@ impl std::fmt::Display for SchedulerConfig {
                 shard_config,
                 partitions_source_config: _,
                 ignore_partition_skip_marker: _,
+                commit_max_attempts: _,
+                commit_limits: _,
             }) => match (&shard_config, commit_wrapper) {
                 (None, None) => write!(f, "local_compaction_scheduler_cfg"),
                 (Some(shard_config), None) => {
@@ -88,7 +95,7 @@ impl CompactionJob {
 }
 
 /// Commit update for a given partition.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CommitUpdate {
     /// Partition to be updated.
     pub(crate) partition_id: PartitionId,
@@ -119,6 +126,31 @@ impl CommitUpdate {
             create,
         }
     }
+
+    /// Partition to be updated.
+    pub fn partition_id(&self) -> PartitionId {
+        self.partition_id
+    }
+
+    /// Files to be deleted.
+    pub fn delete(&self) -> &[ParquetFile] {
+        &self.delete
+    }
+
+    /// Files to be upgraded.
+    pub fn upgrade(&self) -> &[ParquetFile] {
+        &self.upgrade
+    }
+
+    /// Files to be created.
+    pub fn create(&self) -> &[ParquetFileParams] {
+        &self.create
+    }
+
+    /// Target level for upgraded files.
+    pub fn target_level(&self) -> CompactionLevel {
+        self.target_level
+    }
 }
 
 /// Status.
@@ -146,10 +178,10 @@ pub struct CompactionJobStatus {
 pub enum CompactionJobStatusResponse {
     /// Acknowledge receipt of a [`CompactionJobStatusVariant::Error`] request.
     Ack,
-    /// IDs of the created files that were processed.
+    /// The created files, as stored in the catalog.
     ///
     /// This is the response to a [`CompactionJobStatusVariant::Update`] request.
-    CreatedParquetFiles(Vec<ParquetFileId>),
+    CreatedParquetFiles(Vec<ParquetFile>),
 }
 
 /// Reason for skipping a partition.
@@ -199,6 +231,14 @@ pub trait Scheduler: Send + Sync + Debug + Display {
         &self,
         end_action: CompactionJobEnd,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The most recent commits that were recorded instead of committed in shadow mode, oldest
+    /// first.
+    ///
+    /// Empty if not in shadow mode.
+    fn shadow_commits(&self) -> Vec<ShadowCommitEntry> {
+        vec![]
+    }
 }
 
 #[cfg(test)]
@@ -1,8 +1,8 @@
 //! Handles recording commit information to the test run log
 
 use async_trait::async_trait;
-use compactor_scheduler::{Commit, CommitError, CommitWrapper};
-use data_types::{CompactionLevel, ParquetFile, ParquetFileId, ParquetFileParams, PartitionId};
+use compactor_scheduler::{Commit, CommitError, CommitUpdate, CommitWrapper};
+use data_types::{CompactionLevel, ParquetFile, ParquetFileParams, PartitionId};
 use std::{
     fmt::{Debug, Display},
     sync::{Arc, Mutex},
@@ -34,38 +34,12 @@ impl Commit for CommitRecorder {
         upgrade: &[ParquetFile],
         create: &[ParquetFileParams],
         target_level: CompactionLevel,
-    ) -> Result<Vec<ParquetFileId>, CommitError> {
+    ) -> Result<Vec<ParquetFile>, CommitError> {
         if let Some(invariant_check) = self.invariant_check.as_ref() {
             invariant_check.check().await
         };
 
-        // lock scope
-        {
-            let mut run_log = self.run_log.lock().unwrap();
-
-            run_log.push(format!("Committing partition {partition_id}:"));
-
-            if !delete.is_empty() {
-                run_log.push(format!(
-                    "  Soft Deleting {} files: {}",
-                    delete.len(),
-                    id_list(delete)
-                ));
-            }
-
-            if !upgrade.is_empty() {
-                run_log.push(format!(
-                    "  Upgrading {} files level to {}: {}",
-                    upgrade.len(),
-                    target_level,
-                    id_list(upgrade)
-                ));
-            }
-
-            if !create.is_empty() {
-                run_log.push(format!("  Creating {} files", create.len()));
-            }
-        }
+        self.record(partition_id, delete, upgrade, create, target_level);
         let output_files = self
             .inner
             .commit(partition_id, delete, upgrade, create, target_level)
@@ -76,6 +50,68 @@ impl Commit for CommitRecorder {
         };
         output_files
     }
+
+    async fn commit_batch(
+        &self,
+        batch: &[CommitUpdate],
+    ) -> Result<Vec<Vec<ParquetFile>>, CommitError> {
+        if let Some(invariant_check) = self.invariant_check.as_ref() {
+            invariant_check.check().await
+        };
+
+        for update in batch {
+            self.record(
+                update.partition_id(),
+                update.delete(),
+                update.upgrade(),
+                update.create(),
+                update.target_level(),
+            );
+        }
+        let output_files = self.inner.commit_batch(batch).await;
+
+        if let Some(invariant_check) = self.invariant_check.as_ref() {
+            invariant_check.check().await
+        };
+        output_files
+    }
+}
+
+impl CommitRecorder {
+    /// Add a commit to the run log.
+    fn record(
+        &self,
+        partition_id: PartitionId,
+        delete: &[ParquetFile],
+        upgrade: &[ParquetFile],
+        create: &[ParquetFileParams],
+        target_level: CompactionLevel,
+    ) {
+        let mut run_log = self.run_log.lock().unwrap();
+
+        run_log.push(format!("Committing partition {partition_id}:"));
+
+        if !delete.is_empty() {
+            run_log.push(format!(
+                "  Soft Deleting {} files: {}",
+                delete.len(),
+                id_list(delete)
+            ));
+        }
+
+        if !upgrade.is_empty() {
+            run_log.push(format!(
+                "  Upgrading {} files level to {}: {}",
+                upgrade.len(),
+                target_level,
+                id_list(upgrade)
+            ));
+        }
+
+        if !create.is_empty() {
+            run_log.push(format!("  Creating {} files", create.len()));
+        }
+    }
 }
 
 fn id_list(files: &[ParquetFile]) -> String {
@@ -162,7 +162,43 @@ impl TestSetupBuilder<false> {
             all_errors_are_fatal: true,
             max_num_columns_per_table: 200,
             max_num_files_per_plan: 200,
-            max_partition_fetch_queries_per_second: None,
+            early_l1_to_l2_size_multiplier: 3.0,
+            allow_early_l1_to_l2: true,
+            cold_compaction_threshold: None,
+            vertical_split_l2_hints: false,
+            max_split_times_per_round: None,
+            per_chain_overlaps: false,
+            drop_expired_files: false,
+            max_estimated_arrow_bytes_per_plan: None,
+            arrow_expansion_per_column: 0.05,
+            max_branches_per_round: None,
+            many_small_files_window: None,
+            round_decision_log: None,
+            max_repeated_rounds: None,
+            adaptive_file_size_per_plan_floor: None,
+            adaptive_file_size_per_plan_ceiling: None,
+            max_total_rows_per_plan: None,
+            time_bucket: None,
+            scratchpad_capacity_bytes: None,
+            scratchpad_capacity_policy: Default::default(),
+            scratchpad_max_concurrent_transfers: None,
+            scratchpad_keep_written: false,
+            scratchpad_read_through_bytes: None,
+            scratchpad_transfer_max_attempts: None,
+            scratchpad_breaker_failures: None,
+            scratchpad_breaker_cooldown: Duration::from_secs(60),
+            partition_files_cache_ttl: None,
+            partition_files_cache_max_entries: NonZeroUsize::new(10_000).unwrap(),
+            partition_files_fetch_timeout: None,
+            max_partitions_per_fetch_query: NonZeroUsize::new(100).unwrap(),
+            partition_files_filter_max_file_size_bytes: None,
+            partition_files_commit_window: None,
+            partition_fetch_min_interval: None,
+            partition_files_page_size: None,
+            partition_files_max_files: None,
+            partition_files_prefetch_ttl: None,
+            partition_files_prefetch_concurrency: NonZeroUsize::new(10).unwrap(),
+            partition_fetch_rate_limit: None,
         };
 
         let bytes_written = Arc::new(AtomicUsize::new(0));
@@ -595,6 +631,15 @@ impl<const WITH_FILES: bool> TestSetupBuilder<WITH_FILES> {
         self
     }
 
+    /// Set max_split_times_per_round
+    pub fn with_max_split_times_per_round(
+        mut self,
+        max_split_times_per_round: NonZeroUsize,
+    ) -> Self {
+        self.config.max_split_times_per_round = Some(max_split_times_per_round);
+        self
+    }
+
     /// Set the compaction timeout
     pub fn with_partition_timeout(mut self, partition_timeout: Duration) -> Self {
         self.config.partition_timeout = partition_timeout;
@@ -608,10 +653,12 @@ impl<const WITH_FILES: bool> TestSetupBuilder<WITH_FILES> {
             partition_hash_id: self.partition.partition.hash_id().cloned(),
             namespace_id: self.ns.namespace.id,
             namespace_name: self.ns.namespace.name.clone(),
+            retention_period_ns: self.ns.namespace.retention_period_ns,
             table: Arc::new(self.table.table.clone()),
             table_schema: Arc::new(self.table.catalog_schema().await),
             sort_key: self.partition.partition.sort_key(),
             partition_key: self.partition.partition.partition_key.clone(),
+            files_truncated: false,
         });
 
         TestSetup {
@@ -639,6 +639,25 @@ impl ParquetFile {
     }
 }
 
+/// Aggregate of the parquet files of a table at a single compaction level.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ParquetFileSummary {
+    /// the table
+    pub table_id: TableId,
+    /// the compaction level of the files
+    pub compaction_level: CompactionLevel,
+    /// the number of files
+    pub file_count: i64,
+    /// the total size of the files in bytes
+    pub total_file_size_bytes: i64,
+    /// the total number of rows of the files
+    pub total_row_count: i64,
+    /// the smallest [`ParquetFile::max_l0_created_at`] of the files
+    pub oldest_max_l0_created_at: Timestamp,
+    /// the largest [`ParquetFile::created_at`] of the files
+    pub newest_created_at: Timestamp,
+}
+
 /// Data for a parquet file to be inserted into the catalog.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParquetFileParams {
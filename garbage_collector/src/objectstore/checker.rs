@@ -230,7 +230,8 @@ mod tests {
     use chrono::TimeZone;
     use data_types::{
         ColumnId, ColumnSet, CompactionLevel, NamespaceId, ParquetFile, ParquetFileId,
-        ParquetFileParams, PartitionId, TableId, Timestamp, TransitionPartitionId,
+        ParquetFileParams, ParquetFileSummary, PartitionId, TableId, Timestamp,
+        TransitionPartitionId,
     };
     use iox_catalog::{
         interface::Catalog,
@@ -525,6 +526,15 @@ mod tests {
                 .await
         }
 
+        async fn summarize_by_namespace_not_to_delete(
+            &mut self,
+            namespace_id: NamespaceId,
+        ) -> iox_catalog::interface::Result<Vec<ParquetFileSummary>> {
+            self.inner
+                .summarize_by_namespace_not_to_delete(namespace_id)
+                .await
+        }
+
         async fn list_by_table_not_to_delete(
             &mut self,
             table_id: TableId,
@@ -548,6 +558,26 @@ mod tests {
                 .await
         }
 
+        async fn list_by_partition_not_to_delete_page(
+            &mut self,
+            partition_id: &TransitionPartitionId,
+            after: Option<ParquetFileId>,
+            limit: usize,
+        ) -> iox_catalog::interface::Result<Vec<ParquetFile>> {
+            self.inner
+                .list_by_partition_not_to_delete_page(partition_id, after, limit)
+                .await
+        }
+
+        async fn list_by_partitions_not_to_delete(
+            &mut self,
+            partition_ids: &[PartitionId],
+        ) -> iox_catalog::interface::Result<Vec<ParquetFile>> {
+            self.inner
+                .list_by_partitions_not_to_delete(partition_ids)
+                .await
+        }
+
         async fn get_by_object_store_id(
             &mut self,
             object_store_id: Uuid,
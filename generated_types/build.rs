@@ -96,6 +96,9 @@ fn generate_grpc_types(root: &Path) -> Result<()> {
         .extern_path(".google.protobuf", "::pbjson_types")
         .btree_map([
             ".influxdata.iox.ingester.v1.IngesterQueryResponseMetadata.unpersisted_partitions",
+            // Schema maps are ordered by name so that serialised responses are
+            // deterministic.
+            ".influxdata.iox.schema.v1",
         ]);
 
     let descriptor_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("proto_descriptor.bin");
@@ -109,6 +112,7 @@ fn generate_grpc_types(root: &Path) -> Result<()> {
 
     pbjson_build::Builder::new()
         .register_descriptors(&descriptor_set)?
+        .btree_map([".influxdata.iox.schema.v1"])
         .build(&[
             ".influxdata.iox",
             ".influxdata.pbdata",
@@ -14,8 +14,8 @@ use crate::show_tag_keys::show_tag_keys;
 use crate::show_tag_values::show_tag_values;
 use crate::statement::Statement;
 use nom::branch::alt;
-use nom::combinator::{map, value};
-use nom::sequence::{pair, preceded};
+use nom::combinator::{map, opt, value};
+use nom::sequence::{pair, preceded, tuple};
 use std::fmt::{Display, Formatter};
 
 /// Parse a SHOW statement.
@@ -23,7 +23,7 @@ pub(crate) fn show_statement(i: &str) -> ParseResult<&str, Statement> {
     preceded(
         pair(keyword("SHOW"), ws1),
         expect(
-            "invalid SHOW statement, expected DATABASES, FIELD, MEASUREMENTS, TAG, or RETENTION following SHOW",
+            "invalid SHOW statement, expected DATABASES, FIELD, MEASUREMENTS, PARTITIONS, QUERIES, TAG, or RETENTION following SHOW",
             alt((
                 // SHOW DATABASES
                 map(show_databases, |s| Statement::ShowDatabases(Box::new(s))),
@@ -33,6 +33,10 @@ pub(crate) fn show_statement(i: &str) -> ParseResult<&str, Statement> {
                 map(show_measurements, |s| {
                     Statement::ShowMeasurements(Box::new(s))
                 }),
+                // SHOW PARTITIONS
+                map(show_partitions, |s| Statement::ShowPartitions(Box::new(s))),
+                // SHOW QUERIES
+                map(show_queries, |s| Statement::ShowQueries(Box::new(s))),
                 // SHOW RETENTION POLICIES
                 map(show_retention_policies, |s| {
                     Statement::ShowRetentionPolicies(Box::new(s))
@@ -59,6 +63,51 @@ fn show_databases(i: &str) -> ParseResult<&str, ShowDatabasesStatement> {
     value(ShowDatabasesStatement, keyword("DATABASES"))(i)
 }
 
+/// Represents a `SHOW QUERIES` statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowQueriesStatement;
+
+impl Display for ShowQueriesStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SHOW QUERIES")
+    }
+}
+
+/// Parse a `SHOW QUERIES` statement.
+fn show_queries(i: &str) -> ParseResult<&str, ShowQueriesStatement> {
+    value(ShowQueriesStatement, keyword("QUERIES"))(i)
+}
+
+/// Represents a `SHOW PARTITIONS` statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShowPartitionsStatement {
+    /// Name of the measurement to list the partitions, or all if this is `None`.
+    pub measurement: Option<Identifier>,
+}
+
+impl Display for ShowPartitionsStatement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SHOW PARTITIONS")?;
+        if let Some(ref measurement) = self.measurement {
+            write!(f, " ON {measurement}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a `SHOW PARTITIONS [ON <measurement>]` statement.
+fn show_partitions(i: &str) -> ParseResult<&str, ShowPartitionsStatement> {
+    let (remaining, (_, measurement)) = tuple((
+        keyword("PARTITIONS"),
+        opt(preceded(
+            pair(ws1, keyword("ON")),
+            expect("invalid ON clause, expected identifier", identifier),
+        )),
+    ))(i)?;
+
+    Ok((remaining, ShowPartitionsStatement { measurement }))
+}
+
 /// Represents an `ON` clause for the case where the database is a single [`Identifier`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct OnClause(pub(crate) Identifier);
@@ -114,6 +163,15 @@ mod test {
         let (_, got) = show_statement("SHOW MEASUREMENTS").unwrap();
         assert_eq!(got.to_string(), "SHOW MEASUREMENTS");
 
+        let (_, got) = show_statement("SHOW PARTITIONS").unwrap();
+        assert_eq!(got.to_string(), "SHOW PARTITIONS");
+
+        let (_, got) = show_statement("SHOW PARTITIONS ON \"cpu\"").unwrap();
+        assert_eq!(got.to_string(), "SHOW PARTITIONS ON cpu");
+
+        let (_, got) = show_statement("SHOW QUERIES").unwrap();
+        assert_eq!(got.to_string(), "SHOW QUERIES");
+
         let (_, got) = show_statement("SHOW RETENTION POLICIES ON \"foo\"").unwrap();
         assert_eq!(got.to_string(), "SHOW RETENTION POLICIES ON foo");
 
@@ -130,10 +188,15 @@ mod test {
             "invalid SHOW TAG statement, expected KEYS or VALUES"
         );
 
+        assert_expect_error!(
+            show_statement("SHOW PARTITIONS ON "),
+            "invalid ON clause, expected identifier"
+        );
+
         // Unsupported SHOW
         assert_expect_error!(
             show_statement("SHOW FOO"),
-            "invalid SHOW statement, expected DATABASES, FIELD, MEASUREMENTS, TAG, or RETENTION following SHOW"
+            "invalid SHOW statement, expected DATABASES, FIELD, MEASUREMENTS, PARTITIONS, QUERIES, TAG, or RETENTION following SHOW"
         );
     }
 }
@@ -6,7 +6,9 @@ use crate::drop::{drop_statement, DropMeasurementStatement};
 use crate::explain::{explain_statement, ExplainStatement};
 use crate::internal::ParseResult;
 use crate::select::{select_statement, SelectStatement};
-use crate::show::{show_statement, ShowDatabasesStatement};
+use crate::show::{
+    show_statement, ShowDatabasesStatement, ShowPartitionsStatement, ShowQueriesStatement,
+};
 use crate::show_field_keys::ShowFieldKeysStatement;
 use crate::show_measurements::ShowMeasurementsStatement;
 use crate::show_retention_policies::ShowRetentionPoliciesStatement;
@@ -33,6 +35,10 @@ pub enum Statement {
     ShowDatabases(Box<ShowDatabasesStatement>),
     /// Represents a `SHOW MEASUREMENTS` statement.
     ShowMeasurements(Box<ShowMeasurementsStatement>),
+    /// Represents a `SHOW PARTITIONS` statement.
+    ShowPartitions(Box<ShowPartitionsStatement>),
+    /// Represents a `SHOW QUERIES` statement.
+    ShowQueries(Box<ShowQueriesStatement>),
     /// Represents a `SHOW RETENTION POLICIES` statement.
     ShowRetentionPolicies(Box<ShowRetentionPoliciesStatement>),
     /// Represents a `SHOW TAG KEYS` statement.
@@ -53,6 +59,8 @@ impl Display for Statement {
             Self::Select(s) => Display::fmt(s, f),
             Self::ShowDatabases(s) => Display::fmt(s, f),
             Self::ShowMeasurements(s) => Display::fmt(s, f),
+            Self::ShowPartitions(s) => Display::fmt(s, f),
+            Self::ShowQueries(s) => Display::fmt(s, f),
             Self::ShowRetentionPolicies(s) => Display::fmt(s, f),
             Self::ShowTagKeys(s) => Display::fmt(s, f),
             Self::ShowTagValues(s) => Display::fmt(s, f),
@@ -41,7 +41,9 @@ use crate::select::{
     MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
     TimeZoneClause,
 };
-use crate::show::{OnClause, ShowDatabasesStatement};
+use crate::show::{
+    OnClause, ShowDatabasesStatement, ShowPartitionsStatement, ShowQueriesStatement,
+};
 use crate::show_field_keys::ShowFieldKeysStatement;
 use crate::show_measurements::{
     ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
@@ -208,6 +210,38 @@ pub trait Visitor: Sized {
         Ok(self)
     }
 
+    /// Invoked before any children of the `SHOW PARTITIONS` statement are visited.
+    fn pre_visit_show_partitions_statement(
+        self,
+        _n: &ShowPartitionsStatement,
+    ) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of the `SHOW PARTITIONS` statement are visited.
+    fn post_visit_show_partitions_statement(
+        self,
+        _n: &ShowPartitionsStatement,
+    ) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    /// Invoked before any children of the `SHOW QUERIES` statement are visited.
+    fn pre_visit_show_queries_statement(
+        self,
+        _n: &ShowQueriesStatement,
+    ) -> Result<Recursion<Self>, Self::Error> {
+        Ok(Continue(self))
+    }
+
+    /// Invoked after all children of the `SHOW QUERIES` statement are visited.
+    fn post_visit_show_queries_statement(
+        self,
+        _n: &ShowQueriesStatement,
+    ) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
     /// Invoked before any children of the `SHOW RETENTION POLICIES` statement are visited.
     fn pre_visit_show_retention_policies_statement(
         self,
@@ -619,6 +653,8 @@ impl Visitable for Statement {
             Self::Select(s) => s.accept(visitor),
             Self::ShowDatabases(s) => s.accept(visitor),
             Self::ShowMeasurements(s) => s.accept(visitor),
+            Self::ShowPartitions(s) => s.accept(visitor),
+            Self::ShowQueries(s) => s.accept(visitor),
             Self::ShowRetentionPolicies(s) => s.accept(visitor),
             Self::ShowTagKeys(s) => s.accept(visitor),
             Self::ShowTagValues(s) => s.accept(visitor),
@@ -1224,6 +1260,26 @@ impl Visitable for ShowDatabasesStatement {
     }
 }
 
+impl Visitable for ShowPartitionsStatement {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_show_partitions_statement(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+        visitor.post_visit_show_partitions_statement(self)
+    }
+}
+
+impl Visitable for ShowQueriesStatement {
+    fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
+        let visitor = match visitor.pre_visit_show_queries_statement(self)? {
+            Continue(visitor) => visitor,
+            Stop(visitor) => return Ok(visitor),
+        };
+        visitor.post_visit_show_queries_statement(self)
+    }
+}
+
 impl Visitable for ConditionalExpression {
     fn accept<V: Visitor>(&self, visitor: V) -> Result<V, V::Error> {
         let visitor = match visitor.pre_visit_conditional_expression(self)? {
@@ -1358,7 +1414,9 @@ mod test {
         MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
         TimeZoneClause,
     };
-    use crate::show::{OnClause, ShowDatabasesStatement};
+    use crate::show::{
+        OnClause, ShowDatabasesStatement, ShowPartitionsStatement, ShowQueriesStatement,
+    };
     use crate::show_field_keys::ShowFieldKeysStatement;
     use crate::show_measurements::{
         ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
@@ -1415,6 +1473,8 @@ mod test {
         trace_visit!(select_statement, SelectStatement);
         trace_visit!(show_databases_statement, ShowDatabasesStatement);
         trace_visit!(show_measurements_statement, ShowMeasurementsStatement);
+        trace_visit!(show_partitions_statement, ShowPartitionsStatement);
+        trace_visit!(show_queries_statement, ShowQueriesStatement);
         trace_visit!(
             show_retention_policies_statement,
             ShowRetentionPoliciesStatement
@@ -1504,6 +1564,16 @@ mod test {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW DATABASES"));
     }
 
+    #[test]
+    fn test_show_partitions_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW PARTITIONS ON cpu"));
+    }
+
+    #[test]
+    fn test_show_queries_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW QUERIES"));
+    }
+
     #[test]
     fn test_show_measurements_statement() {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW MEASUREMENTS"));
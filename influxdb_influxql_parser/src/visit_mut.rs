@@ -41,7 +41,9 @@ use crate::select::{
     MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
     TimeZoneClause,
 };
-use crate::show::{OnClause, ShowDatabasesStatement};
+use crate::show::{
+    OnClause, ShowDatabasesStatement, ShowPartitionsStatement, ShowQueriesStatement,
+};
 use crate::show_field_keys::ShowFieldKeysStatement;
 use crate::show_measurements::{
     ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
@@ -215,6 +217,38 @@ pub trait VisitorMut: Sized {
         Ok(())
     }
 
+    /// Invoked before any children of the `SHOW PARTITIONS` statement are visited.
+    fn pre_visit_show_partitions_statement(
+        &mut self,
+        _n: &mut ShowPartitionsStatement,
+    ) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of the `SHOW PARTITIONS` statement are visited.
+    fn post_visit_show_partitions_statement(
+        &mut self,
+        _n: &mut ShowPartitionsStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Invoked before any children of the `SHOW QUERIES` statement are visited.
+    fn pre_visit_show_queries_statement(
+        &mut self,
+        _n: &mut ShowQueriesStatement,
+    ) -> Result<Recursion, Self::Error> {
+        Ok(Continue)
+    }
+
+    /// Invoked after all children of the `SHOW QUERIES` statement are visited.
+    fn post_visit_show_queries_statement(
+        &mut self,
+        _n: &mut ShowQueriesStatement,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Invoked before any children of the `SHOW RETENTION POLICIES` statement are visited.
     fn pre_visit_show_retention_policies_statement(
         &mut self,
@@ -649,6 +683,8 @@ impl VisitableMut for Statement {
             Self::Select(s) => s.accept(visitor),
             Self::ShowDatabases(s) => s.accept(visitor),
             Self::ShowMeasurements(s) => s.accept(visitor),
+            Self::ShowPartitions(s) => s.accept(visitor),
+            Self::ShowQueries(s) => s.accept(visitor),
             Self::ShowRetentionPolicies(s) => s.accept(visitor),
             Self::ShowTagKeys(s) => s.accept(visitor),
             Self::ShowTagValues(s) => s.accept(visitor),
@@ -1169,6 +1205,24 @@ impl VisitableMut for ShowDatabasesStatement {
     }
 }
 
+impl VisitableMut for ShowPartitionsStatement {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_show_partitions_statement(self)? {
+            return Ok(());
+        };
+        visitor.post_visit_show_partitions_statement(self)
+    }
+}
+
+impl VisitableMut for ShowQueriesStatement {
+    fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+        if let Stop = visitor.pre_visit_show_queries_statement(self)? {
+            return Ok(());
+        };
+        visitor.post_visit_show_queries_statement(self)
+    }
+}
+
 impl VisitableMut for ConditionalExpression {
     fn accept<V: VisitorMut>(&mut self, visitor: &mut V) -> Result<(), V::Error> {
         if let Stop = visitor.pre_visit_conditional_expression(self)? {
@@ -1296,7 +1350,9 @@ mod test {
         MeasurementSelection, SLimitClause, SOffsetClause, SelectStatement, TimeDimension,
         TimeZoneClause,
     };
-    use crate::show::{OnClause, ShowDatabasesStatement};
+    use crate::show::{
+        OnClause, ShowDatabasesStatement, ShowPartitionsStatement, ShowQueriesStatement,
+    };
     use crate::show_field_keys::ShowFieldKeysStatement;
     use crate::show_measurements::{
         ExtendedOnClause, ShowMeasurementsStatement, WithMeasurementClause,
@@ -1351,6 +1407,8 @@ mod test {
         trace_visit!(select_statement, SelectStatement);
         trace_visit!(show_databases_statement, ShowDatabasesStatement);
         trace_visit!(show_measurements_statement, ShowMeasurementsStatement);
+        trace_visit!(show_partitions_statement, ShowPartitionsStatement);
+        trace_visit!(show_queries_statement, ShowQueriesStatement);
         trace_visit!(
             show_retention_policies_statement,
             ShowRetentionPoliciesStatement
@@ -1442,6 +1500,16 @@ mod test {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW DATABASES"));
     }
 
+    #[test]
+    fn test_show_partitions_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW PARTITIONS ON cpu"));
+    }
+
+    #[test]
+    fn test_show_queries_statement() {
+        insta::assert_yaml_snapshot!(visit_statement!("SHOW QUERIES"));
+    }
+
     #[test]
     fn test_show_measurements_statement() {
         insta::assert_yaml_snapshot!(visit_statement!("SHOW MEASUREMENTS"));
@@ -350,6 +350,15 @@ pub struct Config {
     )]
     pub querier_max_concurrent_queries: usize,
 
+    /// Number of entries kept in the querier query log.
+    #[clap(
+        long = "querier-query-log-size",
+        env = "INFLUXDB_IOX_QUERIER_QUERY_LOG_SIZE",
+        default_value = "10000",
+        action
+    )]
+    pub querier_query_log_size: usize,
+
     /// Size of memory pool used during query exec, in bytes.
     ///
     /// Can be given as absolute value or in percentage of the total available memory (e.g. `10%`).
@@ -388,6 +397,7 @@ impl Config {
             querier_ram_pool_metadata_bytes,
             querier_ram_pool_data_bytes,
             querier_max_concurrent_queries,
+            querier_query_log_size,
             exec_mem_pool_bytes,
             single_tenant_deployment,
         } = self;
@@ -521,6 +531,42 @@ impl Config {
             process_once: false,
             max_num_columns_per_table: 200,
             max_num_files_per_plan: 200,
+            early_l1_to_l2_size_multiplier: 3.0,
+            allow_early_l1_to_l2: true,
+            compaction_cold_threshold_secs: None,
+            vertical_split_l2_hints: false,
+            max_split_times_per_round: None,
+            per_chain_overlaps: false,
+            drop_expired_files: false,
+            max_estimated_arrow_bytes_per_plan: None,
+            arrow_expansion_per_column: 0.05,
+            max_branches_per_round: None,
+            compaction_many_small_files_window_secs: None,
+            round_decision_log_capacity: None,
+            max_repeated_rounds: None,
+            adaptive_file_size_per_plan_floor_bytes: None,
+            adaptive_file_size_per_plan_ceiling_bytes: None,
+            max_total_rows_per_plan: None,
+            compaction_time_bucket_secs: None,
+            compaction_scratchpad_capacity_bytes: None,
+            compaction_scratchpad_block_when_full: true,
+            compaction_scratchpad_max_concurrent_transfers: None,
+            compaction_scratchpad_keep_written: false,
+            compaction_scratchpad_read_through_bytes: None,
+            compaction_scratchpad_transfer_max_attempts: None,
+            compaction_scratchpad_breaker_failures: None,
+            compaction_scratchpad_breaker_cooldown_secs: 60,
+            compaction_partition_files_cache_ttl_secs: None,
+            compaction_partition_files_cache_max_entries: NonZeroUsize::new(10_000).unwrap(),
+            compaction_partition_files_fetch_timeout_secs: None,
+            max_partitions_per_fetch_query: NonZeroUsize::new(100).unwrap(),
+            compaction_partition_files_filter_max_file_size_bytes: None,
+            compaction_partition_files_commit_window_secs: None,
+            compaction_partition_fetch_min_interval_secs: None,
+            compaction_partition_files_page_size: None,
+            compaction_partition_files_max_files: None,
+            compaction_partition_files_prefetch_ttl_secs: None,
+            compaction_partition_files_prefetch_concurrency: NonZeroUsize::new(10).unwrap(),
             max_partition_fetch_queries_per_second: Some(500),
         };
 
@@ -530,7 +576,19 @@ impl Config {
             ingester_addresses,
             ram_pool_metadata_bytes: querier_ram_pool_metadata_bytes,
             ram_pool_data_bytes: querier_ram_pool_data_bytes,
+            namespace_cache_ttl: Duration::from_secs(300),
+            namespace_cache_refresh_interval: Duration::from_secs(30),
+            warm_namespaces: vec![],
+            warm_all_namespaces: false,
             max_concurrent_queries: querier_max_concurrent_queries,
+            query_log_size: querier_query_log_size,
+            query_log_max_age: None,
+            query_log_max_text_length: None,
+            query_log_redact_text: false,
+            public_system_tables: vec![],
+            debug_system_tables: vec![],
+            hidden_system_tables: vec![],
+            admin_namespace: None,
             exec_mem_pool_bytes,
             ingester_circuit_breaker_threshold: u64::MAX, // never for all-in-one-mode
             datafusion_config: Default::default(),
@@ -6,7 +6,7 @@ use clap_blocks::{
     catalog_dsn::CatalogDsnConfig, compactor::CompactorConfig, object_store::make_object_store,
     run_config::RunConfig,
 };
-use compactor::object_store::metrics::MetricsStore;
+use compactor::object_store::{metrics::MetricsStore, read_through::ReadThroughStore};
 use iox_query::exec::{Executor, ExecutorConfig};
 use iox_time::{SystemProvider, TimeProvider};
 use ioxd_common::{
@@ -95,6 +95,25 @@ pub async fn command(config: Config) -> Result<(), Error> {
         )),
         StorageId::from("iox_scratchpad"),
     );
+    // the compaction reads the files the scratchpad reads through (or bypasses) from the real store
+    // instead. Only the executor falls back, so the scratchpad itself still tells its files from
+    // public ones.
+    let exec_store_scratchpad: Arc<DynObjectStore> = if config
+        .compactor_config
+        .compaction_scratchpad_read_through_bytes
+        .is_some()
+        || config
+            .compactor_config
+            .compaction_scratchpad_breaker_failures
+            .is_some()
+    {
+        Arc::new(ReadThroughStore::new(
+            Arc::clone(parquet_store_scratchpad.object_store()),
+            Arc::clone(parquet_store_real.object_store()),
+        ))
+    } else {
+        Arc::clone(parquet_store_scratchpad.object_store())
+    };
 
     let num_threads = config
         .compactor_config
@@ -108,10 +127,15 @@ pub async fn command(config: Config) -> Result<(), Error> {
     let exec = Arc::new(Executor::new_with_config(ExecutorConfig {
         num_threads,
         target_query_partitions: num_threads,
-        object_stores: [&parquet_store_real, &parquet_store_scratchpad]
-            .into_iter()
-            .map(|store| (store.id(), Arc::clone(store.object_store())))
-            .collect(),
+        object_stores: [
+            (
+                parquet_store_real.id(),
+                Arc::clone(parquet_store_real.object_store()),
+            ),
+            (parquet_store_scratchpad.id(), exec_store_scratchpad),
+        ]
+        .into_iter()
+        .collect(),
         metric_registry: Arc::clone(&metric_registry),
         mem_pool_size: config.compactor_config.exec_mem_pool_bytes.bytes(),
     }));
@@ -507,28 +507,32 @@ async fn flightsql_get_tables() {
                     - "table_types:[]"
                     - "include_schema:false"
                     - "*********************"
-                    - +--------------+--------------------+-------------+------------+
-                    - "| catalog_name | db_schema_name     | table_name  | table_type |"
-                    - +--------------+--------------------+-------------+------------+
-                    - "| public       | information_schema | columns     | VIEW       |"
-                    - "| public       | information_schema | df_settings | VIEW       |"
-                    - "| public       | information_schema | tables      | VIEW       |"
-                    - "| public       | information_schema | views       | VIEW       |"
-                    - "| public       | iox                | the_table   | BASE TABLE |"
-                    - "| public       | system             | queries     | BASE TABLE |"
-                    - +--------------+--------------------+-------------+------------+
+                    - +--------------+--------------------+---------------+------------+
+                    - "| catalog_name | db_schema_name     | table_name    | table_type |"
+                    - +--------------+--------------------+---------------+------------+
+                    - "| public       | information_schema | columns       | VIEW       |"
+                    - "| public       | information_schema | df_settings   | VIEW       |"
+                    - "| public       | information_schema | tables        | VIEW       |"
+                    - "| public       | information_schema | views         | VIEW       |"
+                    - "| public       | iox                | the_table     | BASE TABLE |"
+                    - "| public       | system             | compactions   | BASE TABLE |"
+                    - "| public       | system             | parquet_files | BASE TABLE |"
+                    - "| public       | system             | queries       | BASE TABLE |"
+                    - +--------------+--------------------+---------------+------------+
                     - "catalog:None"
                     - "db_schema_filter_pattern:None"
                     - "table_name_filter_pattern:None"
                     - "table_types:[\"BASE TABLE\"]"
                     - "include_schema:false"
                     - "*********************"
-                    - +--------------+----------------+------------+------------+
-                    - "| catalog_name | db_schema_name | table_name | table_type |"
-                    - +--------------+----------------+------------+------------+
-                    - "| public       | iox            | the_table  | BASE TABLE |"
-                    - "| public       | system         | queries    | BASE TABLE |"
-                    - +--------------+----------------+------------+------------+
+                    - +--------------+----------------+---------------+------------+
+                    - "| catalog_name | db_schema_name | table_name    | table_type |"
+                    - +--------------+----------------+---------------+------------+
+                    - "| public       | iox            | the_table     | BASE TABLE |"
+                    - "| public       | system         | compactions   | BASE TABLE |"
+                    - "| public       | system         | parquet_files | BASE TABLE |"
+                    - "| public       | system         | queries       | BASE TABLE |"
+                    - +--------------+----------------+---------------+------------+
                     - "catalog:None"
                     - "db_schema_filter_pattern:None"
                     - "table_name_filter_pattern:None"
@@ -685,16 +689,18 @@ async fn flightsql_get_tables_matches_information_schema() {
                         get_tables_output,
                         @r###"
                     ---
-                    - +--------------+--------------------+-------------+------------+
-                    - "| catalog_name | db_schema_name     | table_name  | table_type |"
-                    - +--------------+--------------------+-------------+------------+
-                    - "| public       | information_schema | columns     | VIEW       |"
-                    - "| public       | information_schema | df_settings | VIEW       |"
-                    - "| public       | information_schema | tables      | VIEW       |"
-                    - "| public       | information_schema | views       | VIEW       |"
-                    - "| public       | iox                | the_table   | BASE TABLE |"
-                    - "| public       | system             | queries     | BASE TABLE |"
-                    - +--------------+--------------------+-------------+------------+
+                    - +--------------+--------------------+---------------+------------+
+                    - "| catalog_name | db_schema_name     | table_name    | table_type |"
+                    - +--------------+--------------------+---------------+------------+
+                    - "| public       | information_schema | columns       | VIEW       |"
+                    - "| public       | information_schema | df_settings   | VIEW       |"
+                    - "| public       | information_schema | tables        | VIEW       |"
+                    - "| public       | information_schema | views         | VIEW       |"
+                    - "| public       | iox                | the_table     | BASE TABLE |"
+                    - "| public       | system             | compactions   | BASE TABLE |"
+                    - "| public       | system             | parquet_files | BASE TABLE |"
+                    - "| public       | system             | queries       | BASE TABLE |"
+                    - +--------------+--------------------+---------------+------------+
                     "###
                     );
 
@@ -1,7 +1,8 @@
+use arrow_util::assert_batches_sorted_eq;
 use futures::FutureExt;
 use test_helpers_end_to_end::{
-    check_flight_error, maybe_skip_integration, try_run_influxql, Authorizer, MiniCluster, Step,
-    StepTest, StepTestState,
+    check_flight_error, maybe_skip_integration, run_influxql, try_run_influxql, Authorizer,
+    MiniCluster, Step, StepTest, StepTestState,
 };
 
 #[tokio::test]
@@ -115,6 +116,7 @@ async fn authz() {
                         cluster.namespace(),
                         cluster.querier().querier_grpc_connection(),
                         Some(format!("Bearer {}", token.clone()).as_str()),
+                        false,
                     )
                     .await
                     .unwrap_err();
@@ -145,3 +147,85 @@ async fn authz() {
 
     authz.close().await;
 }
+
+#[tokio::test]
+async fn influxql_show_queries() {
+    test_helpers::maybe_start_logging();
+    let database_url = maybe_skip_integration!();
+
+    let table_name = "the_table";
+
+    // Set up the cluster  ====================================
+    let mut cluster = MiniCluster::create_shared(database_url).await;
+
+    StepTest::new(
+        &mut cluster,
+        vec![
+            Step::WriteLineProtocol(format!("{table_name},tag1=A,tag2=B val=42i 123456")),
+            Step::InfluxQLQuery {
+                query: format!("select tag1, val from {table_name}"),
+                expected: vec![
+                    "+------------------+--------------------------------+------+-----+",
+                    "| iox::measurement | time                           | tag1 | val |",
+                    "+------------------+--------------------------------+------+-----+",
+                    "| the_table        | 1970-01-01T00:00:00.000123456Z | A    | 42  |",
+                    "+------------------+--------------------------------+------+-----+",
+                ],
+            },
+            // system.queries is a debug table
+            Step::InfluxQLExpectingError {
+                query: "SHOW QUERIES".into(),
+                expected_error_code: tonic::Code::InvalidArgument,
+                expected_message:
+                    "Error while planning query: Error during planning: SHOW QUERIES requires the system.queries table, which is not available"
+                        .into(),
+            },
+            Step::InfluxQLExpectingError {
+                query: format!("SHOW PARTITIONS ON {table_name}"),
+                expected_error_code: tonic::Code::InvalidArgument,
+                expected_message:
+                    "Error while planning query: This feature is not implemented: SHOW PARTITIONS"
+                        .into(),
+            },
+            Step::Custom(Box::new(|state: &mut StepTestState| {
+                async move {
+                    let cluster = state.cluster();
+                    let (batches, schema) = run_influxql(
+                        "SHOW QUERIES",
+                        cluster.namespace(),
+                        cluster.querier().querier_grpc_connection(),
+                        None,
+                        true,
+                    )
+                    .await;
+
+                    // the remaining columns, such as the durations, are not deterministic
+                    let projection = ["iox::measurement", "query_type", "query_text"]
+                        .into_iter()
+                        .map(|name| schema.index_of(name).unwrap())
+                        .collect::<Vec<_>>();
+                    let batches = batches
+                        .iter()
+                        .map(|batch| batch.project(&projection).unwrap())
+                        .collect::<Vec<_>>();
+                    assert_batches_sorted_eq!(
+                        [
+                            "+------------------+------------+---------------------------------+",
+                            "| iox::measurement | query_type | query_text                      |",
+                            "+------------------+------------+---------------------------------+",
+                            "| queries          | influxql   | SHOW PARTITIONS ON the_table    |",
+                            "| queries          | influxql   | SHOW QUERIES                    |",
+                            "| queries          | influxql   | SHOW QUERIES                    |",
+                            "| queries          | influxql   | select tag1, val from the_table |",
+                            "+------------------+------------+---------------------------------+",
+                        ],
+                        &batches
+                    );
+                }
+                .boxed()
+            })),
+        ],
+    )
+    .run()
+    .await
+}
@@ -8,3 +8,7 @@ SELECT * from information_schema.tables where table_schema = 'system';
 -- Note the issue_time changes, so we can't display it directly
 -- Instead check that it is reasonable and non null
 SELECT issue_time <= now(), query_type, query_text, success FROM system.queries;
+
+-- EXPLAIN shows which system table is scanned
+-- IOX_COMPARE: uuid
+EXPLAIN SELECT query_type, query_text FROM system.queries;
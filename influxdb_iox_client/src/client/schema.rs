@@ -30,6 +30,9 @@ impl Client {
             .inner
             .get_schema(GetSchemaRequest {
                 namespace: namespace.to_string(),
+                page_size: None,
+                page_token: None,
+                if_not_fingerprint: None,
             })
             .await?;
 
@@ -324,7 +324,8 @@ where
                     )
                 }
                 None => PartitionResponse::new(vec![], id, completed_persistence_count),
-            };
+            }
+            .with_partition_template(table_partition_template.clone());
 
             span.ok("read partition data");
             Some(ret)
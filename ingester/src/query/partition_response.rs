@@ -3,7 +3,7 @@
 //! [`QueryResponse`]: super::response::QueryResponse
 
 use arrow::record_batch::RecordBatch;
-use data_types::TransitionPartitionId;
+use data_types::{partition_template::TablePartitionTemplateOverride, TransitionPartitionId};
 
 /// Response data for a single partition.
 #[derive(Debug)]
@@ -16,6 +16,9 @@ pub(crate) struct PartitionResponse {
 
     /// Count of persisted Parquet files for this partition by this ingester instance.
     completed_persistence_count: u64,
+
+    /// Partition template of the table.
+    partition_template: TablePartitionTemplateOverride,
 }
 
 impl PartitionResponse {
@@ -28,6 +31,18 @@ impl PartitionResponse {
             batches: data,
             id,
             completed_persistence_count,
+            partition_template: Default::default(),
+        }
+    }
+
+    /// Set the partition template of the table, which defaults to the default template.
+    pub(crate) fn with_partition_template(
+        self,
+        partition_template: TablePartitionTemplateOverride,
+    ) -> Self {
+        Self {
+            partition_template,
+            ..self
         }
     }
 
@@ -39,6 +54,10 @@ impl PartitionResponse {
         self.completed_persistence_count
     }
 
+    pub(crate) fn partition_template(&self) -> &TablePartitionTemplateOverride {
+        &self.partition_template
+    }
+
     pub(crate) fn into_record_batches(self) -> Vec<RecordBatch> {
         self.batches
     }
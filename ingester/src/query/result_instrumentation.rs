@@ -334,6 +334,7 @@ where
                 // Extract all the fields of the PartitionResponse
                 let id = p.id().clone();
                 let persist_count = p.completed_persistence_count();
+                let partition_template = p.partition_template().clone();
 
                 // And wrap the underlying stream of RecordBatch for this
                 // partition with a metric observer.
@@ -345,7 +346,10 @@ where
                 this.record_batch_count
                     .fetch_add(data.len(), Ordering::Relaxed);
 
-                Poll::Ready(Some(PartitionResponse::new(data, id, persist_count)))
+                Poll::Ready(Some(
+                    PartitionResponse::new(data, id, persist_count)
+                        .with_partition_template(partition_template),
+                ))
             }
             Poll::Ready(None) => {
                 // Record the wall clock timestamp of the stream end.
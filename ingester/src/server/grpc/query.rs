@@ -6,7 +6,9 @@ use arrow_flight::{
     FlightData, FlightDescriptor, FlightInfo, HandshakeRequest, HandshakeResponse, PutResult,
     SchemaResult, Ticket,
 };
-use data_types::{NamespaceId, TableId, TransitionPartitionId};
+use data_types::{
+    partition_template::TablePartitionTemplateOverride, NamespaceId, TableId, TransitionPartitionId,
+};
 use flatbuffers::FlatBufferBuilder;
 use futures::{Stream, StreamExt, TryStreamExt};
 use ingester_query_grpc::influxdata::iox::ingester::v1 as proto;
@@ -311,6 +313,8 @@ fn encode_partition(
     // [`PartitionData`]: crate::buffer_tree::partition::PartitionData
     // [`PartitionResponse`]: crate::query::partition_response::PartitionResponse
     completed_persistence_count: u64,
+    // Partition template of the table.
+    partition_template: &TablePartitionTemplateOverride,
     ingester_id: IngesterId,
 ) -> Result<FlightData, FlightError> {
     use proto::ingester_query_response_metadata::PartitionIdentifier;
@@ -329,6 +333,13 @@ fn encode_partition(
         partition_identifier: Some(partition_identifier),
         ingester_uuid: ingester_id.to_string(),
         completed_persistence_count,
+        // empty for the default template
+        partition_template: Some(
+            partition_template
+                .as_proto()
+                .map(prost::Message::encode_to_vec)
+                .unwrap_or_default(),
+        ),
     };
     prost::Message::encode(&app_metadata, &mut bytes)
         .map_err(|e| FlightError::from_external_error(Box::new(e)))?;
@@ -364,10 +375,16 @@ fn encode_response(
     response.into_partition_stream().flat_map(move |partition| {
         let partition_id = partition.id().clone();
         let completed_persistence_count = partition.completed_persistence_count();
+        let partition_template = partition.partition_template().clone();
 
         // prefix payload data w/ metadata for that particular partition
         let head = futures::stream::once(async move {
-            encode_partition(partition_id, completed_persistence_count, ingester_id)
+            encode_partition(
+                partition_id,
+                completed_persistence_count,
+                &partition_template,
+                ingester_id,
+            )
         });
 
         // An output vector of FlightDataEncoder streams, each entry stream with
@@ -412,7 +429,10 @@ mod tests {
     use arrow_flight::decode::{DecodedPayload, FlightRecordBatchStream};
     use assert_matches::assert_matches;
     use bytes::Bytes;
-    use data_types::PartitionId;
+    use data_types::{
+        partition_template::{test_table_partition_override, TemplatePart},
+        PartitionId,
+    };
     use proto::ingester_query_response_metadata::PartitionIdentifier;
     use tonic::Code;
     use trace::{ctx::SpanContext, RingBufferTraceCollector, TraceCollector};
@@ -457,10 +477,51 @@ mod tests {
             )),
             ingester_uuid: ingester_id.to_string(),
             completed_persistence_count: 42,
+            partition_template: Some(vec![]),
         };
         assert_eq!(md_actual, md_expected);
     }
 
+    #[tokio::test]
+    async fn sends_partition_template() {
+        let partition_template =
+            test_table_partition_override(vec![TemplatePart::TagValue("region")]);
+        let flight = FlightService::new(
+            MockQueryExec::default().with_result(Ok(QueryResponse::new(PartitionStream::new(
+                futures::stream::iter([PartitionResponse::new(
+                    vec![],
+                    ARBITRARY_TRANSITION_PARTITION_ID.clone(),
+                    42,
+                )
+                .with_partition_template(partition_template.clone())]),
+            )))),
+            IngesterId::new(),
+            100,
+            &metric::Registry::default(),
+        );
+
+        let req = tonic::Request::new(Ticket {
+            ticket: Bytes::new(),
+        });
+        let response_stream = flight
+            .do_get(req)
+            .await
+            .unwrap()
+            .into_inner()
+            .map_err(FlightError::Tonic);
+        let flight_decoder =
+            FlightRecordBatchStream::new_from_flight_data(response_stream).into_inner();
+        let flight_data = flight_decoder.try_collect::<Vec<_>>().await.unwrap();
+
+        assert_matches!(flight_data[0].payload, DecodedPayload::None);
+        let md =
+            proto::IngesterQueryResponseMetadata::decode(flight_data[0].app_metadata()).unwrap();
+        assert_eq!(
+            md.partition_template,
+            Some(partition_template.as_proto().unwrap().encode_to_vec())
+        );
+    }
+
     #[tokio::test]
     async fn doesnt_send_partition_hash_id_if_not_present() {
         let ingester_id = IngesterId::new();
@@ -498,6 +559,7 @@ mod tests {
             partition_identifier: Some(PartitionIdentifier::CatalogId(2)),
             ingester_uuid: ingester_id.to_string(),
             completed_persistence_count: 42,
+            partition_template: Some(vec![]),
         };
         assert_eq!(md_actual, md_expected);
     }
@@ -636,6 +698,7 @@ mod tests {
             )),
             ingester_uuid: ingester_id.to_string(),
             completed_persistence_count: 42,
+            partition_template: Some(vec![]),
         };
         assert_eq!(md_actual, md_expected);
 
@@ -3,10 +3,11 @@
 use async_trait::async_trait;
 use data_types::{
     partition_template::{NamespacePartitionTemplateOverride, TablePartitionTemplateOverride},
-    Column, ColumnType, ColumnsByName, CompactionLevel, Namespace, NamespaceId, NamespaceName,
-    NamespaceSchema, NamespaceServiceProtectionLimitsOverride, ParquetFile, ParquetFileId,
-    ParquetFileParams, Partition, PartitionHashId, PartitionId, PartitionKey, SkippedCompaction,
-    SortedColumnSet, Table, TableId, TableSchema, Timestamp, TransitionPartitionId,
+    Column, ColumnId, ColumnType, ColumnsByName, CompactionLevel, Namespace, NamespaceId,
+    NamespaceName, NamespaceSchema, NamespaceServiceProtectionLimitsOverride, ParquetFile,
+    ParquetFileId, ParquetFileParams, ParquetFileSummary, Partition, PartitionHashId, PartitionId,
+    PartitionKey, SkippedCompaction, SortedColumnSet, Table, TableId, TableSchema, Timestamp,
+    TransitionPartitionId,
 };
 use iox_time::TimeProvider;
 use snafu::{OptionExt, Snafu};
@@ -320,6 +321,15 @@ pub trait TableRepo: Send + Sync {
     /// Lists all tables in the catalog for the given namespace id.
     async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Table>>;
 
+    /// Lists all tables in the catalog for the given namespace id together with all their columns,
+    /// in a single query.
+    ///
+    /// Tables without columns are included.
+    async fn list_with_columns_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<(Vec<Table>, Vec<Column>)>;
+
     /// List all tables.
     async fn list(&mut self) -> Result<Vec<Table>>;
 }
@@ -352,6 +362,9 @@ pub trait ColumnRepo: Send + Sync {
         columns: HashMap<&str, ColumnType>,
     ) -> Result<Vec<Column>>;
 
+    /// Gets the column by its ID.
+    async fn get_by_id(&mut self, column_id: ColumnId) -> Result<Option<Column>>;
+
     /// Lists all columns in the passed in namespace id.
     async fn list_by_namespace_id(&mut self, namespace_id: NamespaceId) -> Result<Vec<Column>>;
 
@@ -494,6 +507,15 @@ pub trait ParquetFileRepo: Send + Sync {
     /// [`to_delete`](ParquetFile::to_delete).
     async fn list_by_table_not_to_delete(&mut self, table_id: TableId) -> Result<Vec<ParquetFile>>;
 
+    /// Summarize the parquet files within a given namespace that are NOT marked as
+    /// [`to_delete`](ParquetFile::to_delete), per table and compaction level.
+    ///
+    /// Summaries are ordered by table ID and compaction level.
+    async fn summarize_by_namespace_not_to_delete(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<ParquetFileSummary>>;
+
     /// Delete parquet files that were marked to be deleted earlier than the specified time.
     ///
     /// Returns the deleted IDs only.
@@ -509,6 +531,28 @@ pub trait ParquetFileRepo: Send + Sync {
         partition_id: &TransitionPartitionId,
     ) -> Result<Vec<ParquetFile>>;
 
+    /// List a page of the parquet files for a given partition that are NOT marked as
+    /// [`to_delete`](ParquetFile::to_delete).
+    ///
+    /// Files are ordered by ID. The page holds at most `limit` files with an ID greater than
+    /// `after`, so the next page starts after the last file of this one.
+    async fn list_by_partition_not_to_delete_page(
+        &mut self,
+        partition_id: &TransitionPartitionId,
+        after: Option<ParquetFileId>,
+        limit: usize,
+    ) -> Result<Vec<ParquetFile>>;
+
+    /// List parquet files for the given partitions that are NOT marked as
+    /// [`to_delete`](ParquetFile::to_delete), in a single query.
+    ///
+    /// Files are returned in implementation-defined order, use their
+    /// [`partition_id`](ParquetFile::partition_id) to tell the partitions apart.
+    async fn list_by_partitions_not_to_delete(
+        &mut self,
+        partition_ids: &[PartitionId],
+    ) -> Result<Vec<ParquetFile>>;
+
     /// Return the parquet file with the given object store id
     // used heavily in tests for verification of catalog state.
     async fn get_by_object_store_id(
@@ -1432,9 +1476,19 @@ pub(crate) mod test_helpers {
 
         let columns = repos.columns().list_by_table_id(table.id).await.unwrap();
 
-        let want2 = vec![c];
+        let want2 = vec![c.clone()];
         assert_eq!(want2, columns);
 
+        // test we can get a column by its ID
+        let got = repos.columns().get_by_id(c.id).await.unwrap();
+        assert_eq!(got, Some(c));
+        let got = repos
+            .columns()
+            .get_by_id(ColumnId::new(i64::MAX))
+            .await
+            .unwrap();
+        assert_eq!(got, None);
+
         // Add another tag column into table2
         let c3 = repos
             .columns()
@@ -1493,6 +1547,31 @@ pub(crate) mod test_helpers {
         table3_column_names.sort();
         assert_eq!(table3_column_names, vec!["apples", "oranges"]);
 
+        // test listing tables and columns at once, including tables without columns
+        let table4 = arbitrary_table(&mut *repos, "test_table_4", &namespace).await;
+        let (mut tables, mut columns) = repos
+            .tables()
+            .list_with_columns_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        tables.sort_by_key(|t| t.id);
+        columns.sort_by_key(|c| c.id);
+        assert_eq!(tables, vec![table, table2, table3, table4]);
+        let mut want = repos
+            .columns()
+            .list_by_namespace_id(namespace.id)
+            .await
+            .unwrap();
+        want.sort_by_key(|c| c.id);
+        assert_eq!(columns, want);
+        let (tables, columns) = repos
+            .tables()
+            .list_with_columns_by_namespace_id(NamespaceId::new(i64::MAX))
+            .await
+            .unwrap();
+        assert!(tables.is_empty());
+        assert!(columns.is_empty());
+
         repos
             .namespaces()
             .soft_delete("namespace_column_test")
@@ -2245,6 +2324,31 @@ pub(crate) mod test_helpers {
             .unwrap();
         assert!(files.is_empty());
 
+        // test summarize_by_namespace_not_to_delete
+        let summaries = repos
+            .parquet_files()
+            .summarize_by_namespace_not_to_delete(namespace2.id)
+            .await
+            .unwrap();
+        assert_eq!(
+            summaries,
+            vec![ParquetFileSummary {
+                table_id: table2.id,
+                compaction_level: f1.compaction_level,
+                file_count: 2,
+                total_file_size_bytes: f1.file_size_bytes + f3.file_size_bytes,
+                total_row_count: f1.row_count + f3.row_count,
+                oldest_max_l0_created_at: f1.max_l0_created_at.min(f3.max_l0_created_at),
+                newest_created_at: f1.created_at.max(f3.created_at),
+            }]
+        );
+        let summaries = repos
+            .parquet_files()
+            .summarize_by_namespace_not_to_delete(NamespaceId::new(i64::MAX))
+            .await
+            .unwrap();
+        assert!(summaries.is_empty());
+
         // test delete_old_ids_only
         let older_than = Timestamp::new(
             (catalog.time_provider().now() + Duration::from_secs(100)).timestamp_nanos(),
@@ -2924,7 +3028,7 @@ pub(crate) mod test_helpers {
             object_store_id: Uuid::new_v4(),
             ..parquet_file_params.clone()
         };
-        let _partition2_file = repos
+        let partition2_file = repos
             .parquet_files()
             .create(other_partition_params)
             .await
@@ -2957,6 +3061,77 @@ pub(crate) mod test_helpers {
         let mut expected_ids = vec![parquet_file.id, level1_file.id];
         expected_ids.sort();
         assert_eq!(file_ids, expected_ids);
+
+        // Listing several partitions at once returns the files of all of them
+        let partition3 = repos
+            .partitions()
+            .create_or_get("test_list_by_partiton_not_to_delete_three".into(), table.id)
+            .await
+            .unwrap();
+        let files = repos
+            .parquet_files()
+            .list_by_partitions_not_to_delete(&[partition.id, partition2.id, partition3.id])
+            .await
+            .unwrap();
+        let mut file_ids: Vec<_> = files.into_iter().map(|f| f.id).collect();
+        file_ids.sort();
+        let mut expected_ids = vec![parquet_file.id, level1_file.id, partition2_file.id];
+        expected_ids.sort();
+        assert_eq!(file_ids, expected_ids);
+
+        // ...and nothing for partitions without files
+        let files = repos
+            .parquet_files()
+            .list_by_partitions_not_to_delete(&[partition3.id])
+            .await
+            .unwrap();
+        assert!(files.is_empty());
+        let files = repos
+            .parquet_files()
+            .list_by_partitions_not_to_delete(&[])
+            .await
+            .unwrap();
+        assert!(files.is_empty());
+
+        // Paging through the files of a partition returns them by ID, without the deleted ones
+        let mut expected_ids = vec![parquet_file.id, level1_file.id];
+        expected_ids.sort();
+        for partition_id in [
+            partition.transition_partition_id(),
+            TransitionPartitionId::Deprecated(partition.id),
+        ] {
+            let page = repos
+                .parquet_files()
+                .list_by_partition_not_to_delete_page(&partition_id, None, 1)
+                .await
+                .unwrap();
+            let page_ids: Vec<_> = page.iter().map(|f| f.id).collect();
+            assert_eq!(page_ids, expected_ids[..1]);
+
+            let page = repos
+                .parquet_files()
+                .list_by_partition_not_to_delete_page(&partition_id, Some(page_ids[0]), 1)
+                .await
+                .unwrap();
+            let page_ids: Vec<_> = page.iter().map(|f| f.id).collect();
+            assert_eq!(page_ids, expected_ids[1..]);
+
+            // the last page is short
+            let page = repos
+                .parquet_files()
+                .list_by_partition_not_to_delete_page(&partition_id, Some(page_ids[0]), 1)
+                .await
+                .unwrap();
+            assert!(page.is_empty());
+
+            let page = repos
+                .parquet_files()
+                .list_by_partition_not_to_delete_page(&partition_id, None, 10)
+                .await
+                .unwrap();
+            let page_ids: Vec<_> = page.iter().map(|f| f.id).collect();
+            assert_eq!(page_ids, expected_ids);
+        }
     }
 
     async fn test_update_to_compaction_level_1(catalog: Arc<dyn Catalog>) {
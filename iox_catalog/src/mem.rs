@@ -19,14 +19,14 @@ use data_types::{
     },
     Column, ColumnId, ColumnType, CompactionLevel, Namespace, NamespaceId, NamespaceName,
     NamespaceServiceProtectionLimitsOverride, ParquetFile, ParquetFileId, ParquetFileParams,
-    Partition, PartitionHashId, PartitionId, PartitionKey, SkippedCompaction, Table, TableId,
-    Timestamp, TransitionPartitionId,
+    ParquetFileSummary, Partition, PartitionHashId, PartitionId, PartitionKey, SkippedCompaction,
+    Table, TableId, Timestamp, TransitionPartitionId,
 };
 use iox_time::{SystemProvider, TimeProvider};
 use snafu::ensure;
 use sqlx::types::Uuid;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::{Display, Formatter},
     sync::Arc,
 };
@@ -381,6 +381,15 @@ impl TableRepo for MemTxn {
         Ok(tables)
     }
 
+    async fn list_with_columns_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<(Vec<Table>, Vec<Column>)> {
+        let tables = TableRepo::list_by_namespace_id(self, namespace_id).await?;
+        let columns = ColumnRepo::list_by_namespace_id(self, namespace_id).await?;
+        Ok((tables, columns))
+    }
+
     async fn list(&mut self) -> Result<Vec<Table>> {
         let stage = self.stage();
         Ok(stage.tables.clone())
@@ -531,6 +540,12 @@ impl ColumnRepo for MemTxn {
         Ok(columns)
     }
 
+    async fn get_by_id(&mut self, column_id: ColumnId) -> Result<Option<Column>> {
+        let stage = self.stage();
+
+        Ok(stage.columns.iter().find(|c| c.id == column_id).cloned())
+    }
+
     async fn list_by_table_id(&mut self, table_id: TableId) -> Result<Vec<Column>> {
         let stage = self.stage();
 
@@ -873,6 +888,39 @@ impl ParquetFileRepo for MemTxn {
         Ok(parquet_files)
     }
 
+    async fn summarize_by_namespace_not_to_delete(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<ParquetFileSummary>> {
+        let files = self.list_by_namespace_not_to_delete(namespace_id).await?;
+
+        let mut summaries: BTreeMap<(TableId, CompactionLevel), ParquetFileSummary> =
+            BTreeMap::new();
+        for file in files {
+            summaries
+                .entry((file.table_id, file.compaction_level))
+                .and_modify(|summary| {
+                    summary.file_count += 1;
+                    summary.total_file_size_bytes += file.file_size_bytes;
+                    summary.total_row_count += file.row_count;
+                    summary.oldest_max_l0_created_at =
+                        summary.oldest_max_l0_created_at.min(file.max_l0_created_at);
+                    summary.newest_created_at = summary.newest_created_at.max(file.created_at);
+                })
+                .or_insert_with(|| ParquetFileSummary {
+                    table_id: file.table_id,
+                    compaction_level: file.compaction_level,
+                    file_count: 1,
+                    total_file_size_bytes: file.file_size_bytes,
+                    total_row_count: file.row_count,
+                    oldest_max_l0_created_at: file.max_l0_created_at,
+                    newest_created_at: file.created_at,
+                });
+        }
+
+        Ok(summaries.into_values().collect())
+    }
+
     async fn list_by_table_not_to_delete(&mut self, table_id: TableId) -> Result<Vec<ParquetFile>> {
         let stage = self.stage();
 
@@ -936,6 +984,53 @@ impl ParquetFileRepo for MemTxn {
             .collect())
     }
 
+    async fn list_by_partition_not_to_delete_page(
+        &mut self,
+        partition_id: &TransitionPartitionId,
+        after: Option<ParquetFileId>,
+        limit: usize,
+    ) -> Result<Vec<ParquetFile>> {
+        let mut files: Vec<_> = self
+            .list_by_partition_not_to_delete(partition_id)
+            .await?
+            .into_iter()
+            .filter(|f| after.map(|after| f.id > after).unwrap_or(true))
+            .collect();
+        files.sort_by_key(|f| f.id);
+        files.truncate(limit);
+
+        Ok(files)
+    }
+
+    async fn list_by_partitions_not_to_delete(
+        &mut self,
+        partition_ids: &[PartitionId],
+    ) -> Result<Vec<ParquetFile>> {
+        let stage = self.stage();
+
+        let partitions: Vec<_> = stage
+            .partitions
+            .iter()
+            .filter(|p| partition_ids.contains(&p.id))
+            .collect();
+
+        Ok(stage
+            .parquet_files
+            .iter()
+            .filter(|f| {
+                partitions.iter().any(|partition| match &f.partition_id {
+                    TransitionPartitionId::Deterministic(hash_id) => partition
+                        .hash_id()
+                        .map(|p_hash_id| p_hash_id == hash_id)
+                        .unwrap_or(false),
+                    TransitionPartitionId::Deprecated(id) => id == &partition.id,
+                })
+            })
+            .filter(|f| f.to_delete.is_none())
+            .cloned()
+            .collect())
+    }
+
     async fn get_by_object_store_id(
         &mut self,
         object_store_id: Uuid,
@@ -21,10 +21,10 @@ use data_types::{
     partition_template::{
         NamespacePartitionTemplateOverride, TablePartitionTemplateOverride, TemplatePart,
     },
-    Column, ColumnType, CompactionLevel, Namespace, NamespaceId, NamespaceName,
+    Column, ColumnId, ColumnType, CompactionLevel, Namespace, NamespaceId, NamespaceName,
     NamespaceServiceProtectionLimitsOverride, ParquetFile, ParquetFileId, ParquetFileParams,
-    Partition, PartitionHashId, PartitionId, PartitionKey, SkippedCompaction, Table, TableId,
-    Timestamp, TransitionPartitionId,
+    ParquetFileSummary, Partition, PartitionHashId, PartitionId, PartitionKey, SkippedCompaction,
+    Table, TableId, Timestamp, TransitionPartitionId,
 };
 use iox_time::{SystemProvider, TimeProvider};
 use metric::{Attributes, Instrument, MetricKind};
@@ -1032,6 +1032,29 @@ WHERE namespace_id = $1;
         Ok(rec)
     }
 
+    async fn list_with_columns_by_namespace_id(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<(Vec<Table>, Vec<Column>)> {
+        let rec = sqlx::query_as::<_, TableColumnRow>(
+            r#"
+SELECT table_name.*,
+       column_name.id AS column_id,
+       column_name.name AS column_name,
+       column_name.column_type AS column_type
+FROM table_name
+LEFT JOIN column_name ON column_name.table_id = table_name.id
+WHERE table_name.namespace_id = $1;
+            "#,
+        )
+        .bind(namespace_id)
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(split_table_column_rows(rec))
+    }
+
     async fn list(&mut self) -> Result<Vec<Table>> {
         let rec = sqlx::query_as::<_, Table>("SELECT * FROM table_name;")
             .fetch_all(&mut self.inner)
@@ -1042,6 +1065,41 @@ WHERE namespace_id = $1;
     }
 }
 
+/// A table joined with one of its columns, if it has any.
+#[derive(Debug, sqlx::FromRow)]
+struct TableColumnRow {
+    #[sqlx(flatten)]
+    table: Table,
+    column_id: Option<ColumnId>,
+    column_name: Option<String>,
+    column_type: Option<ColumnType>,
+}
+
+/// Split the rows of a table-column join into the distinct tables and their columns.
+fn split_table_column_rows(rows: Vec<TableColumnRow>) -> (Vec<Table>, Vec<Column>) {
+    let mut seen = HashSet::new();
+    let mut tables = vec![];
+    let mut columns = vec![];
+
+    for row in rows {
+        if let (Some(id), Some(name), Some(column_type)) =
+            (row.column_id, row.column_name, row.column_type)
+        {
+            columns.push(Column {
+                id,
+                table_id: row.table.id,
+                name,
+                column_type,
+            });
+        }
+        if seen.insert(row.table.id) {
+            tables.push(row.table);
+        }
+    }
+
+    (tables, columns)
+}
+
 #[async_trait]
 impl ColumnRepo for PostgresTxn {
     async fn create_or_get(
@@ -1069,6 +1127,27 @@ WHERE table_name.namespace_id = $1;
         Ok(rec)
     }
 
+    async fn get_by_id(&mut self, column_id: ColumnId) -> Result<Option<Column>> {
+        let rec = sqlx::query_as::<_, Column>(
+            r#"
+SELECT *
+FROM column_name
+WHERE id = $1;
+            "#,
+        )
+        .bind(column_id) // $1
+        .fetch_one(&mut self.inner)
+        .await;
+
+        if let Err(sqlx::Error::RowNotFound) = rec {
+            return Ok(None);
+        }
+
+        let column = rec.map_err(|e| Error::SqlxError { source: e })?;
+
+        Ok(Some(column))
+    }
+
     async fn list_by_table_id(&mut self, table_id: TableId) -> Result<Vec<Column>> {
         let rec = sqlx::query_as::<_, Column>(
             r#"
@@ -1604,6 +1683,33 @@ WHERE table_name.namespace_id = $1
         .map_err(|e| Error::SqlxError { source: e })
     }
 
+    async fn summarize_by_namespace_not_to_delete(
+        &mut self,
+        namespace_id: NamespaceId,
+    ) -> Result<Vec<ParquetFileSummary>> {
+        // `SUM` of a `BIGINT` is a `NUMERIC` in Postgres
+        sqlx::query_as::<_, ParquetFileSummary>(
+            r#"
+SELECT parquet_file.table_id, parquet_file.compaction_level,
+       COUNT(*) AS file_count,
+       SUM(parquet_file.file_size_bytes)::BIGINT AS total_file_size_bytes,
+       SUM(parquet_file.row_count)::BIGINT AS total_row_count,
+       MIN(parquet_file.max_l0_created_at) AS oldest_max_l0_created_at,
+       MAX(parquet_file.created_at) AS newest_created_at
+FROM parquet_file
+INNER JOIN table_name on table_name.id = parquet_file.table_id
+WHERE table_name.namespace_id = $1
+  AND parquet_file.to_delete IS NULL
+GROUP BY parquet_file.table_id, parquet_file.compaction_level
+ORDER BY parquet_file.table_id, parquet_file.compaction_level;
+             "#,
+        )
+        .bind(namespace_id) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
     async fn list_by_table_not_to_delete(&mut self, table_id: TableId) -> Result<Vec<ParquetFile>> {
         sqlx::query_as::<_, ParquetFile>(
             r#"
@@ -1685,6 +1791,85 @@ WHERE partition.id = $1
             .map_err(|e| Error::SqlxError { source: e })
     }
 
+    async fn list_by_partition_not_to_delete_page(
+        &mut self,
+        partition_id: &TransitionPartitionId,
+        after: Option<ParquetFileId>,
+        limit: usize,
+    ) -> Result<Vec<ParquetFile>> {
+        let after = after.map(|id| id.get()).unwrap_or(i64::MIN);
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+
+        // This `match` will go away when all partitions have hash IDs in the database.
+        let query = match partition_id {
+            TransitionPartitionId::Deterministic(hash_id) => sqlx::query_as::<_, ParquetFile>(
+                r#"
+SELECT parquet_file.id, namespace_id, parquet_file.table_id, partition_id, partition_hash_id,
+       object_store_id, min_time, max_time, parquet_file.to_delete, file_size_bytes, row_count,
+       compaction_level, created_at, column_set, max_l0_created_at
+FROM parquet_file
+INNER JOIN partition
+ON partition.id = parquet_file.partition_id OR partition.hash_id = parquet_file.partition_hash_id
+WHERE partition.hash_id = $1
+  AND parquet_file.to_delete IS NULL
+  AND parquet_file.id > $2
+ORDER BY parquet_file.id
+LIMIT $3;
+        "#,
+            )
+            .bind(hash_id), // $1
+            TransitionPartitionId::Deprecated(id) => sqlx::query_as::<_, ParquetFile>(
+                r#"
+SELECT parquet_file.id, namespace_id, parquet_file.table_id, partition_id, partition_hash_id,
+       object_store_id, min_time, max_time, parquet_file.to_delete, file_size_bytes, row_count,
+       compaction_level, created_at, column_set, max_l0_created_at
+FROM parquet_file
+INNER JOIN partition
+ON partition.id = parquet_file.partition_id OR partition.hash_id = parquet_file.partition_hash_id
+WHERE partition.id = $1
+  AND parquet_file.to_delete IS NULL
+  AND parquet_file.id > $2
+ORDER BY parquet_file.id
+LIMIT $3;
+        "#,
+            )
+            .bind(id), // $1
+        };
+
+        query
+            .bind(after) // $2
+            .bind(limit) // $3
+            .fetch_all(&mut self.inner)
+            .await
+            .map_err(|e| Error::SqlxError { source: e })
+    }
+
+    async fn list_by_partitions_not_to_delete(
+        &mut self,
+        partition_ids: &[PartitionId],
+    ) -> Result<Vec<ParquetFile>> {
+        let ids: Vec<_> = partition_ids.iter().map(|p| p.get()).collect();
+
+        sqlx::query_as::<_, ParquetFile>(
+            // sqlx's readme suggests using PG's ANY operator instead of IN; see link below.
+            // https://github.com/launchbadge/sqlx/blob/main/FAQ.md#how-can-i-do-a-select--where-foo-in--query
+            r#"
+SELECT parquet_file.id, namespace_id, parquet_file.table_id, partition_id, partition_hash_id,
+       object_store_id, min_time, max_time, parquet_file.to_delete, file_size_bytes, row_count,
+       compaction_level, created_at, column_set, max_l0_created_at
+FROM parquet_file
+INNER JOIN partition
+ON partition.id = parquet_file.partition_id OR partition.hash_id = parquet_file.partition_hash_id
+WHERE partition.id = ANY($1)
+  AND parquet_file.to_delete IS NULL;
+        "#,
+        )
+        .bind(ids) // $1
+        .fetch_all(&mut self.inner)
+        .await
+        .map_err(|e| Error::SqlxError { source: e })
+    }
+
     async fn get_by_object_store_id(
         &mut self,
         object_store_id: Uuid,
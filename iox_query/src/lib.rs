@@ -102,37 +102,83 @@ pub struct QueryCompletedToken {
     /// If this query completed successfully
     success: bool,
 
+    /// Why this query failed, if known
+    error: Option<String>,
+
+    /// Function invoked when the query was planned
+    planned: Option<Box<dyn FnOnce() + Send>>,
+
     /// Function invoked when the token is dropped. It is passed the
-    /// vaue of `self.success`
-    f: Option<Box<dyn FnOnce(bool) + Send>>,
+    /// value of `self.success` and `self.error`
+    f: Option<Box<dyn FnOnce(QueryCompletion) + Send>>,
+}
+
+/// How a query recorded by a [`QueryCompletedToken`] completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCompletion {
+    /// If the query completed successfully
+    pub success: bool,
+
+    /// Why the query failed, if known
+    pub error: Option<String>,
 }
 
 impl Debug for QueryCompletedToken {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("QueryCompletedToken")
             .field("success", &self.success)
+            .field("error", &self.error)
             .finish()
     }
 }
 
 impl QueryCompletedToken {
     pub fn new(f: impl FnOnce(bool) + Send + 'static) -> Self {
+        Self::new_with_completion(move |completion| f(completion.success))
+    }
+
+    /// Like [`new`](Self::new), but `f` also learns why the query failed, if known.
+    pub fn new_with_completion(f: impl FnOnce(QueryCompletion) + Send + 'static) -> Self {
         Self {
             success: false,
+            error: None,
+            planned: None,
             f: Some(Box::new(f)),
         }
     }
 
+    /// Invoke `f` once the query was planned, see [`set_planned`](Self::set_planned).
+    pub fn with_planned(mut self, f: impl FnOnce() + Send + 'static) -> Self {
+        self.planned = Some(Box::new(f));
+        self
+    }
+
+    /// Record that this query was planned and starts executing
+    pub fn set_planned(&mut self) {
+        if let Some(f) = self.planned.take() {
+            (f)()
+        }
+    }
+
     /// Record that this query completed successfully
     pub fn set_success(&mut self) {
         self.success = true;
     }
+
+    /// Record that this query failed with the given error
+    pub fn set_error(&mut self, error: impl ToString) {
+        self.success = false;
+        self.error = Some(error.to_string());
+    }
 }
 
 impl Drop for QueryCompletedToken {
     fn drop(&mut self) {
         if let Some(f) = self.f.take() {
-            (f)(self.success)
+            (f)(QueryCompletion {
+                success: self.success,
+                error: self.error.take(),
+            })
         }
     }
 }
@@ -11,7 +11,7 @@ use std::fmt::Debug;
 use std::ops::Deref;
 use std::sync::Arc;
 
-use crate::plan::{parse_regex, InfluxQLToLogicalPlan, SchemaProvider};
+use crate::plan::{parse_regex, InfluxQLToLogicalPlan, SchemaProvider, QUERIES_TABLE};
 use datafusion::common::Statistics;
 use datafusion::datasource::provider_as_source;
 use datafusion::execution::context::{SessionState, TaskContext};
@@ -24,6 +24,7 @@ use datafusion::{
     error::{DataFusionError, Result},
     physical_plan::ExecutionPlan,
 };
+use datafusion_util::config::SYSTEM_SCHEMA;
 use influxdb_influxql_parser::common::MeasurementName;
 use influxdb_influxql_parser::parse_statements;
 use influxdb_influxql_parser::statement::Statement;
@@ -35,6 +36,7 @@ use schema::Schema;
 struct ContextSchemaProvider<'a> {
     state: &'a SessionState,
     tables: HashMap<String, (Arc<dyn TableSource>, Schema)>,
+    system_tables: HashMap<&'static str, Arc<dyn TableSource>>,
 }
 
 impl<'a> SchemaProvider for ContextSchemaProvider<'a> {
@@ -45,6 +47,10 @@ impl<'a> SchemaProvider for ContextSchemaProvider<'a> {
             .ok_or_else(|| DataFusionError::Plan(format!("measurement does not exist: {name}")))
     }
 
+    fn get_system_table_provider(&self, name: &str) -> Option<Arc<dyn TableSource>> {
+        self.system_tables.get(name).cloned()
+    }
+
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
         self.state.scalar_functions().get(name).cloned()
     }
@@ -181,7 +187,7 @@ impl InfluxQLQueryPlanner {
 
         let session_cfg = ctx.inner().copied_config();
         let cfg = session_cfg.options();
-        let schema = ctx
+        let catalog = ctx
             .inner()
             .catalog(&cfg.catalog.default_catalog)
             .ok_or_else(|| {
@@ -189,20 +195,20 @@ impl InfluxQLQueryPlanner {
                     "failed to resolve catalog: {}",
                     cfg.catalog.default_catalog
                 ))
-            })?
-            .schema(&cfg.catalog.default_schema)
-            .ok_or_else(|| {
-                DataFusionError::Plan(format!(
-                    "failed to resolve schema: {}",
-                    cfg.catalog.default_schema
-                ))
             })?;
+        let schema = catalog.schema(&cfg.catalog.default_schema).ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "failed to resolve schema: {}",
+                cfg.catalog.default_schema
+            ))
+        })?;
         let names = schema.table_names();
         let query_tables = find_all_measurements(&statement, &names)?;
 
         let mut sp = ContextSchemaProvider {
             state: &ctx.inner().state(),
             tables: HashMap::with_capacity(query_tables.len()),
+            system_tables: HashMap::new(),
         };
 
         for table_name in &query_tables {
@@ -217,6 +223,17 @@ impl InfluxQLQueryPlanner {
             }
         }
 
+        // InfluxQL has no way to reference other schemas, so the system tables are only resolved
+        // for the statements that are planned as scans of them.
+        if let Some(system_schema) = catalog.schema(SYSTEM_SCHEMA) {
+            for &table_name in find_system_tables(&statement) {
+                if let Some(table) = system_schema.table(table_name).await {
+                    sp.system_tables
+                        .insert(table_name, provider_as_source(table));
+                }
+            }
+        }
+
         let planner = InfluxQLToLogicalPlan::new(&sp, ctx);
         let logical_plan = planner.statement_to_plan(statement)?;
         debug!(plan=%logical_plan.display_graphviz(), "logical plan");
@@ -237,6 +254,14 @@ impl InfluxQLQueryPlanner {
     }
 }
 
+/// Returns the system tables that are scanned by `stmt`.
+fn find_system_tables(stmt: &Statement) -> &'static [&'static str] {
+    match stmt {
+        Statement::ShowQueries(_) => &[QUERIES_TABLE],
+        _ => &[],
+    }
+}
+
 fn find_all_measurements(stmt: &Statement, tables: &[String]) -> Result<HashSet<String>> {
     struct Matcher<'a>(&'a mut HashSet<String>, &'a [String]);
     impl<'a> Visitor for Matcher<'a> {
@@ -341,6 +366,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_find_system_tables() {
+        fn find(q: &str) -> &'static [&'static str] {
+            let p = InfluxQLQueryPlanner::new();
+            let s = p.query_to_statement(q).unwrap();
+            find_system_tables(&s)
+        }
+
+        assert_eq!(find("SHOW QUERIES"), &["queries"]);
+        assert!(find("SHOW MEASUREMENTS").is_empty());
+        assert!(find("SELECT * FROM queries").is_empty());
+    }
+
     #[test]
     fn test_find_all_measurements() {
         fn find(q: &str) -> Vec<String> {
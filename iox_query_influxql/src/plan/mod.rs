@@ -13,4 +13,5 @@ mod var_ref;
 
 pub use planner::InfluxQLToLogicalPlan;
 pub use planner::SchemaProvider;
+pub(crate) use planner::QUERIES_TABLE;
 pub(crate) use util::parse_regex;
@@ -46,7 +46,7 @@ use datafusion::logical_expr::{
 use datafusion::optimizer::utils::conjunction;
 use datafusion::physical_expr::execution_props::ExecutionProps;
 use datafusion::prelude::{cast, sum, when, Column};
-use datafusion_util::{lit_dict, AsExpr};
+use datafusion_util::{config::SYSTEM_SCHEMA, lit_dict, AsExpr};
 use generated_types::influxdata::iox::querier::v1::InfluxQlMetadata;
 use influxdb_influxql_parser::common::{LimitClause, OffsetClause, OrderByClause};
 use influxdb_influxql_parser::explain::{ExplainOption, ExplainStatement};
@@ -103,12 +103,18 @@ use super::util::contains_expr;
 /// The column index of the measurement column.
 const MEASUREMENT_COLUMN_INDEX: u32 = 0;
 
+/// Name of the system table listing the queries, which is scanned by `SHOW QUERIES`.
+pub(crate) const QUERIES_TABLE: &str = "queries";
+
 /// The `SchemaProvider` trait allows the InfluxQL query planner to obtain
 /// meta-data about tables referenced in InfluxQL statements.
 pub trait SchemaProvider {
     /// Getter for a datasource
     fn get_table_provider(&self, name: &str) -> Result<Arc<dyn TableSource>>;
 
+    /// Getter for a table of the system schema, or `None` if it is not available.
+    fn get_system_table_provider(&self, name: &str) -> Option<Arc<dyn TableSource>>;
+
     /// Getter for a UDF description
     fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>>;
 
@@ -454,6 +460,8 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
             Statement::ShowMeasurements(show_measurements) => {
                 self.show_measurements_to_plan(*show_measurements)
             }
+            Statement::ShowPartitions(_) => error::not_implemented("SHOW PARTITIONS"),
+            Statement::ShowQueries(_) => self.show_queries_to_plan(),
             Statement::ShowRetentionPolicies(show_retention_policies) => {
                 self.show_retention_policies_to_plan(*show_retention_policies)
             }
@@ -2911,6 +2919,41 @@ impl<'a> InfluxQLToLogicalPlan<'a> {
         Ok(plan)
     }
 
+    /// Plan `SHOW QUERIES` as a scan of the system table listing the queries.
+    fn show_queries_to_plan(&self) -> Result<LogicalPlan> {
+        let Some(source) = self.s.get_system_table_provider(QUERIES_TABLE) else {
+            return error::query(format!(
+                "SHOW QUERIES requires the {SYSTEM_SCHEMA}.{QUERIES_TABLE} table, which is not available"
+            ));
+        };
+
+        let plan = LogicalPlanBuilder::scan(
+            TableReference::partial(SYSTEM_SCHEMA, QUERIES_TABLE),
+            source,
+            None,
+        )?;
+        let columns = plan
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| Expr::Column(f.qualified_column()))
+            .collect::<Vec<_>>();
+        let plan = plan
+            .project(
+                iter::once(lit_dict(QUERIES_TABLE).alias(INFLUXQL_MEASUREMENT_COLUMN_NAME))
+                    .chain(columns),
+            )?
+            .build()?;
+
+        plan_with_metadata(
+            plan,
+            &InfluxQlMetadata {
+                measurement_column_index: MEASUREMENT_COLUMN_INDEX,
+                tag_key_columns: vec![],
+            },
+        )
+    }
+
     fn metadata_cutoff(&self) -> MetadataCutoff {
         self.iox_ctx
             .inner()
@@ -3459,6 +3502,7 @@ mod test {
         assert_snapshot!(plan("DELETE FROM foo"), @"This feature is not implemented: DELETE");
         assert_snapshot!(plan("DROP MEASUREMENT foo"), @"This feature is not implemented: DROP MEASUREMENT");
         assert_snapshot!(plan("SHOW DATABASES"), @"This feature is not implemented: SHOW DATABASES");
+        assert_snapshot!(plan("SHOW PARTITIONS ON cpu"), @"This feature is not implemented: SHOW PARTITIONS");
     }
 
     mod metadata_queries {
@@ -3827,6 +3871,17 @@ mod test {
             This feature is not implemented: SHOW RETENTION POLICIES ON <database>
             "###);
         }
+
+        #[test]
+        fn test_show_queries() {
+            assert_snapshot!(plan("SHOW QUERIES"), @r###"
+            Projection: Dictionary(Int32, Utf8("queries")) AS iox::measurement, system.queries.issue_time, system.queries.query_type, system.queries.query_text [iox::measurement:Dictionary(Int32, Utf8), issue_time:Timestamp(Nanosecond, None), query_type:Utf8, query_text:Utf8]
+              TableScan: system.queries [issue_time:Timestamp(Nanosecond, None), query_type:Utf8, query_text:Utf8]
+            "###);
+            let md = metadata("SHOW QUERIES").unwrap();
+            assert_eq!(md.measurement_column_index, 0);
+            assert!(md.tag_key_columns.is_empty());
+        }
     }
 
     /// Tests to validate InfluxQL `SELECT` statements, where the projections do not matter,
@@ -2,7 +2,8 @@
 #![cfg(test)]
 
 use crate::error;
-use crate::plan::SchemaProvider;
+use crate::plan::{SchemaProvider, QUERIES_TABLE};
+use arrow::datatypes::{DataType, Field, Schema as ArrowSchema, TimeUnit};
 use chrono::{DateTime, NaiveDate, Utc};
 use datafusion::common::Result as DataFusionResult;
 use datafusion::datasource::empty::EmptyTable;
@@ -135,6 +136,7 @@ pub(crate) mod database {
 pub(crate) struct MockSchemaProvider {
     execution_props: ExecutionProps,
     tables: HashMap<String, (Arc<dyn TableSource>, Schema)>,
+    system_tables: HashMap<&'static str, Arc<dyn TableSource>>,
 }
 
 impl Default for MockSchemaProvider {
@@ -147,9 +149,20 @@ impl Default for MockSchemaProvider {
         let start_time = DateTime::<Utc>::from_utc(start_time, Utc);
         let mut execution_props = ExecutionProps::new();
         execution_props.query_execution_start_time = start_time;
+        // A subset of the columns of the system table listing the queries
+        let queries = Arc::new(EmptyTable::new(Arc::new(ArrowSchema::new(vec![
+            Field::new(
+                "issue_time",
+                DataType::Timestamp(TimeUnit::Nanosecond, None),
+                false,
+            ),
+            Field::new("query_type", DataType::Utf8, false),
+            Field::new("query_text", DataType::Utf8, false),
+        ]))));
         let mut res = Self {
             execution_props,
             tables: HashMap::new(),
+            system_tables: HashMap::from([(QUERIES_TABLE, provider_as_source(queries))]),
         };
         res.add_schemas(database::schemas());
         res
@@ -179,6 +192,10 @@ impl SchemaProvider for MockSchemaProvider {
             .ok_or_else(|| error::map::query(format!("measurement does not exist: {name}")))
     }
 
+    fn get_system_table_provider(&self, name: &str) -> Option<Arc<dyn TableSource>> {
+        self.system_tables.get(name).cloned()
+    }
+
     fn get_function_meta(&self, _name: &str) -> Option<Arc<ScalarUDF>> {
         None
     }
@@ -18,7 +18,10 @@ use workspace_hack as _;
 use async_trait::async_trait;
 use backoff::BackoffConfig;
 use clap_blocks::compactor::CompactorConfig;
-use compactor::{compactor::Compactor, config::Config};
+use compactor::{
+    compactor::Compactor, config::Config, RateLimitConfig, RoundDecisionLog,
+    ScratchpadCapacityPolicy,
+};
 use hyper::{Body, Request, Response};
 use iox_catalog::interface::Catalog;
 use iox_query::exec::Executor;
@@ -187,8 +190,80 @@ pub async fn create_compactor_server_type(
         all_errors_are_fatal: false,
         max_num_columns_per_table: compactor_config.max_num_columns_per_table,
         max_num_files_per_plan: compactor_config.max_num_files_per_plan,
-        max_partition_fetch_queries_per_second: compactor_config
-            .max_partition_fetch_queries_per_second,
+        early_l1_to_l2_size_multiplier: compactor_config.early_l1_to_l2_size_multiplier,
+        allow_early_l1_to_l2: compactor_config.allow_early_l1_to_l2,
+        cold_compaction_threshold: compactor_config
+            .compaction_cold_threshold_secs
+            .map(Duration::from_secs),
+        vertical_split_l2_hints: compactor_config.vertical_split_l2_hints,
+        max_split_times_per_round: compactor_config.max_split_times_per_round,
+        per_chain_overlaps: compactor_config.per_chain_overlaps,
+        drop_expired_files: compactor_config.drop_expired_files,
+        max_estimated_arrow_bytes_per_plan: compactor_config.max_estimated_arrow_bytes_per_plan,
+        arrow_expansion_per_column: compactor_config.arrow_expansion_per_column,
+        max_branches_per_round: compactor_config.max_branches_per_round,
+        many_small_files_window: compactor_config
+            .compaction_many_small_files_window_secs
+            .map(Duration::from_secs),
+        round_decision_log: compactor_config
+            .round_decision_log_capacity
+            .map(|capacity| Arc::new(RoundDecisionLog::new(capacity))),
+        max_repeated_rounds: compactor_config.max_repeated_rounds,
+        adaptive_file_size_per_plan_floor: compactor_config.adaptive_file_size_per_plan_floor_bytes,
+        adaptive_file_size_per_plan_ceiling: compactor_config
+            .adaptive_file_size_per_plan_ceiling_bytes,
+        max_total_rows_per_plan: compactor_config.max_total_rows_per_plan,
+        time_bucket: compactor_config
+            .compaction_time_bucket_secs
+            .map(Duration::from_secs),
+        scratchpad_capacity_bytes: compactor_config.compaction_scratchpad_capacity_bytes,
+        scratchpad_capacity_policy: if compactor_config.compaction_scratchpad_block_when_full {
+            ScratchpadCapacityPolicy::Block
+        } else {
+            ScratchpadCapacityPolicy::Error
+        },
+        scratchpad_max_concurrent_transfers: compactor_config
+            .compaction_scratchpad_max_concurrent_transfers,
+        scratchpad_keep_written: compactor_config.compaction_scratchpad_keep_written,
+        scratchpad_read_through_bytes: compactor_config.compaction_scratchpad_read_through_bytes,
+        scratchpad_transfer_max_attempts: compactor_config
+            .compaction_scratchpad_transfer_max_attempts,
+        scratchpad_breaker_failures: compactor_config.compaction_scratchpad_breaker_failures,
+        scratchpad_breaker_cooldown: Duration::from_secs(
+            compactor_config.compaction_scratchpad_breaker_cooldown_secs,
+        ),
+        partition_files_cache_ttl: compactor_config
+            .compaction_partition_files_cache_ttl_secs
+            .map(Duration::from_secs),
+        partition_files_cache_max_entries: compactor_config
+            .compaction_partition_files_cache_max_entries,
+        partition_files_fetch_timeout: compactor_config
+            .compaction_partition_files_fetch_timeout_secs
+            .map(Duration::from_secs),
+        max_partitions_per_fetch_query: compactor_config.max_partitions_per_fetch_query,
+        partition_files_filter_max_file_size_bytes: compactor_config
+            .compaction_partition_files_filter_max_file_size_bytes,
+        partition_files_commit_window: compactor_config
+            .compaction_partition_files_commit_window_secs
+            .map(Duration::from_secs),
+        partition_fetch_min_interval: compactor_config
+            .compaction_partition_fetch_min_interval_secs
+            .map(Duration::from_secs),
+        partition_files_page_size: compactor_config.compaction_partition_files_page_size,
+        partition_files_max_files: compactor_config.compaction_partition_files_max_files,
+        partition_files_prefetch_ttl: compactor_config
+            .compaction_partition_files_prefetch_ttl_secs
+            .map(Duration::from_secs),
+        partition_files_prefetch_concurrency: compactor_config
+            .compaction_partition_files_prefetch_concurrency,
+        partition_fetch_rate_limit: compactor_config.max_partition_fetch_queries_per_second.map(
+            |rps| {
+                Arc::new(RateLimitConfig::new(
+                    rps,
+                    RateLimitConfig::DEFAULT_MAX_BURST,
+                ))
+            },
+        ),
     });
 
     Arc::new(CompactorServerType::new(
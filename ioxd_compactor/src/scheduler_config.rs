@@ -1,11 +1,11 @@
 use std::time::Duration;
 
 use clap_blocks::compactor_scheduler::{
-    CompactorSchedulerConfig, CompactorSchedulerType, PartitionSourceConfigForLocalScheduler,
-    ShardConfigForLocalScheduler,
+    CommitLimitPolicy, CompactorSchedulerConfig, CompactorSchedulerType,
+    PartitionSourceConfigForLocalScheduler, ShardConfigForLocalScheduler,
 };
 use compactor_scheduler::{
-    LocalSchedulerConfig, PartitionsSourceConfig, SchedulerConfig, ShardConfig,
+    CommitLimits, LocalSchedulerConfig, PartitionsSourceConfig, SchedulerConfig, ShardConfig,
 };
 use data_types::PartitionId;
 
@@ -75,6 +75,16 @@ pub(crate) fn convert_scheduler_config(config: CompactorSchedulerConfig) -> Sche
             ignore_partition_skip_marker: config
                 .partition_source_config
                 .ignore_partition_skip_marker,
+            commit_max_attempts: config.commit_max_attempts,
+            commit_limits: CommitLimits {
+                max_deletes: config.commit_max_deletes,
+                max_creates: config.commit_max_creates,
+                max_create_bytes: config.commit_max_create_bytes,
+                policy: match config.commit_limit_policy {
+                    CommitLimitPolicy::Reject => compactor_scheduler::CommitLimitPolicy::Reject,
+                    CommitLimitPolicy::Split => compactor_scheduler::CommitLimitPolicy::Split,
+                },
+            },
         }),
         CompactorSchedulerType::Remote => unimplemented!("Remote scheduler not implemented"),
     }
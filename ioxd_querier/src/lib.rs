@@ -40,7 +40,10 @@ use ioxd_common::{
 };
 use metric::Registry;
 use object_store::{DynObjectStore, ObjectStore};
-use querier::{create_ingester_connections, QuerierCatalogCache, QuerierDatabase, QuerierServer};
+use querier::{
+    create_ingester_connections, NamespaceCacheConfig, QuerierCatalogCache, QuerierDatabase,
+    QuerierServer, QueryTextConfig, SystemTablePolicy, SystemTableVisibility,
+};
 use std::{
     fmt::{Debug, Display},
     sync::Arc,
@@ -199,6 +202,13 @@ pub enum Error {
 pub async fn create_querier_server_type(
     args: QuerierServerTypeArgs<'_>,
 ) -> Result<Arc<dyn ServerType>, Error> {
+    let mut namespace_cache_config = NamespaceCacheConfig {
+        ttl_existing: args.querier_config.namespace_cache_ttl,
+        ..Default::default()
+    };
+    namespace_cache_config.refresh_existing.init_backoff =
+        args.querier_config.namespace_cache_refresh_interval;
+
     let catalog_cache = Arc::new(QuerierCatalogCache::new(
         Arc::clone(&args.catalog),
         args.time_provider,
@@ -206,9 +216,29 @@ pub async fn create_querier_server_type(
         Arc::clone(&args.object_store),
         args.querier_config.ram_pool_metadata_bytes.bytes(),
         args.querier_config.ram_pool_data_bytes.bytes(),
+        namespace_cache_config,
         &Handle::current(),
     ));
 
+    // warm the namespace cache in the background so startup is not delayed
+    let warm_all_namespaces = args.querier_config.warm_all_namespaces;
+    let warm_namespaces = args
+        .querier_config
+        .warm_namespaces
+        .iter()
+        .map(|name| Arc::from(name.as_str()))
+        .collect::<Vec<_>>();
+    if warm_all_namespaces || !warm_namespaces.is_empty() {
+        let catalog_cache = Arc::clone(&catalog_cache);
+        tokio::spawn(async move {
+            if warm_all_namespaces {
+                catalog_cache.warm_all_namespaces().await;
+            } else {
+                catalog_cache.warm_namespaces(&warm_namespaces).await;
+            }
+        });
+    }
+
     // register cached object store with the execution context
     let parquet_store = catalog_cache.parquet_store();
     let runtime_env = args
@@ -255,6 +285,29 @@ pub async fn create_querier_server_type(
         ))
     };
 
+    // later entries take precedence, so hidden tables come last
+    let mut system_table_policy = SystemTablePolicy::new(
+        args.querier_config
+            .public_system_tables
+            .iter()
+            .map(|table| (table, SystemTableVisibility::Public))
+            .chain(
+                args.querier_config
+                    .debug_system_tables
+                    .iter()
+                    .map(|table| (table, SystemTableVisibility::Debug)),
+            )
+            .chain(
+                args.querier_config
+                    .hidden_system_tables
+                    .iter()
+                    .map(|table| (table, SystemTableVisibility::Hidden)),
+            ),
+    );
+    if let Some(admin_namespace) = args.querier_config.admin_namespace {
+        system_table_policy = system_table_policy.with_admin_namespace(admin_namespace);
+    }
+
     let database = Arc::new(
         QuerierDatabase::new(
             catalog_cache,
@@ -262,6 +315,13 @@ pub async fn create_querier_server_type(
             args.exec,
             ingester_connections,
             args.querier_config.max_concurrent_queries,
+            args.querier_config.query_log_size,
+            args.querier_config.query_log_max_age,
+            QueryTextConfig {
+                max_length: args.querier_config.query_log_max_text_length,
+                redact: args.querier_config.query_log_redact_text,
+            },
+            system_table_policy,
             Arc::new(args.querier_config.datafusion_config),
         )
         .await?,
@@ -132,6 +132,10 @@ mod tests {
                 catalog.exec(),
                 Some(create_ingester_connection_for_testing()),
                 QuerierDatabase::MAX_CONCURRENT_QUERIES_MAX,
+                QuerierDatabase::QUERY_LOG_SIZE_DEFAULT,
+                None,
+                Default::default(),
+                Default::default(),
                 Arc::new(HashMap::default()),
             )
             .await
@@ -165,6 +169,10 @@ mod tests {
                 catalog.exec(),
                 Some(create_ingester_connection_for_testing()),
                 QuerierDatabase::MAX_CONCURRENT_QUERIES_MAX,
+                QuerierDatabase::QUERY_LOG_SIZE_DEFAULT,
+                None,
+                Default::default(),
+                Default::default(),
                 Arc::new(HashMap::default()),
             )
             .await
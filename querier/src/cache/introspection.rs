@@ -0,0 +1,113 @@
+//! Introspection of the querier caches.
+use std::sync::Arc;
+
+use cache_system::backend::policy::lru::ResourcePool;
+use metric::{Attributes, DurationHistogram, Metric, U64Gauge};
+
+use super::ram::RamSize;
+
+/// Statistics of a single cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CacheStats {
+    /// Cache ID, e.g. `namespace`.
+    pub(crate) cache_id: &'static str,
+
+    /// Name of the RAM pool the cache belongs to.
+    pub(crate) pool: &'static str,
+
+    /// Number of cached entries.
+    pub(crate) entries: u64,
+
+    /// RAM consumed by the cached entries.
+    pub(crate) ram_bytes: u64,
+
+    /// RAM consumed by all caches of the pool.
+    pub(crate) pool_ram_bytes: u64,
+
+    /// RAM limit of the pool.
+    pub(crate) pool_ram_limit_bytes: u64,
+
+    /// Number of GET requests that were served from the cache.
+    pub(crate) hits: u64,
+
+    /// Number of GET requests that had to load the entry, or wait for it to be loaded.
+    pub(crate) misses: u64,
+}
+
+/// Handle to inspect the state of the caches of a [`CatalogCache`](super::CatalogCache).
+///
+/// The numbers are read from the cache metrics and RAM pools, so they are as current as these.
+#[derive(Debug, Clone)]
+pub struct CacheIntrospection {
+    metric_registry: Arc<metric::Registry>,
+    caches: Arc<[(&'static str, Arc<ResourcePool<RamSize>>)]>,
+}
+
+impl CacheIntrospection {
+    /// Create new handle for the caches with the given IDs and RAM pools.
+    pub(crate) fn new(
+        metric_registry: Arc<metric::Registry>,
+        caches: Vec<(&'static str, Arc<ResourcePool<RamSize>>)>,
+    ) -> Self {
+        Self {
+            metric_registry,
+            caches: caches.into(),
+        }
+    }
+
+    /// Number of caches.
+    pub(crate) fn num_caches(&self) -> usize {
+        self.caches.len()
+    }
+
+    /// Statistics of all caches, in the order the caches were registered.
+    pub(crate) fn stats(&self) -> Vec<CacheStats> {
+        self.caches
+            .iter()
+            .map(|&(cache_id, ref pool)| {
+                let member = [("pool", pool.name()), ("member", cache_id)];
+                let mut member_usage = Attributes::from(&member);
+                member_usage.insert("unit", "bytes");
+
+                CacheStats {
+                    cache_id,
+                    pool: pool.name(),
+                    entries: self.gauge("cache_lru_member_count", &Attributes::from(&member)),
+                    ram_bytes: self.gauge("cache_lru_member_usage", &member_usage),
+                    pool_ram_bytes: pool.current().0 as u64,
+                    pool_ram_limit_bytes: pool.limit().0 as u64,
+                    hits: self.get_count(cache_id, &["hit"]),
+                    misses: self.get_count(cache_id, &["miss", "miss_already_loading"]),
+                }
+            })
+            .collect()
+    }
+
+    fn gauge(&self, name: &'static str, attributes: &Attributes) -> u64 {
+        self.metric_registry
+            .get_instrument::<Metric<U64Gauge>>(name)
+            .and_then(|metric| metric.get_observer(attributes).map(|gauge| gauge.fetch()))
+            .unwrap_or_default()
+    }
+
+    fn get_count(&self, cache_id: &'static str, statuses: &[&'static str]) -> u64 {
+        let Some(metric) = self
+            .metric_registry
+            .get_instrument::<Metric<DurationHistogram>>("iox_cache_get")
+        else {
+            return 0;
+        };
+
+        statuses
+            .iter()
+            .filter_map(|status| {
+                metric
+                    .get_observer(&Attributes::from(&[
+                        ("name", cache_id),
+                        ("status", *status),
+                    ]))
+                    .map(|histogram| histogram.fetch().sample_count())
+            })
+            .sum()
+    }
+}
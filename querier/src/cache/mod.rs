@@ -3,16 +3,23 @@ use ::object_store::ObjectStore;
 use ::parquet_file::storage::{ParquetStorage, StorageId};
 use backoff::BackoffConfig;
 use cache_system::backend::policy::lru::ResourcePool;
-use iox_catalog::interface::Catalog;
+use iox_catalog::interface::{Catalog, SoftDeletedRows};
 use iox_time::TimeProvider;
+use observability_deps::tracing::warn;
 use std::sync::Arc;
 use tokio::runtime::Handle;
 
 use self::{
-    namespace::NamespaceCache, object_store::ObjectStoreCache, parquet_file::ParquetFileCache,
-    partition::PartitionCache, projected_schema::ProjectedSchemaCache, ram::RamSize,
+    introspection::CacheIntrospection,
+    namespace::{NamespaceCache, NamespaceCacheConfig},
+    object_store::ObjectStoreCache,
+    parquet_file::ParquetFileCache,
+    partition::PartitionCache,
+    projected_schema::ProjectedSchemaCache,
+    ram::RamSize,
 };
 
+pub(crate) mod introspection;
 pub mod namespace;
 pub mod object_store;
 pub mod parquet_file;
@@ -44,6 +51,9 @@ pub struct CatalogCache {
     /// Object store cache.
     object_store_cache: ObjectStoreCache,
 
+    /// Introspection of the caches above.
+    introspection: CacheIntrospection,
+
     /// Metric registry
     metric_registry: Arc<metric::Registry>,
 
@@ -53,6 +63,7 @@ pub struct CatalogCache {
 
 impl CatalogCache {
     /// Create empty cache.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         catalog: Arc<dyn Catalog>,
         time_provider: Arc<dyn TimeProvider>,
@@ -60,6 +71,7 @@ impl CatalogCache {
         object_store: Arc<dyn ObjectStore>,
         ram_pool_metadata_bytes: usize,
         ram_pool_data_bytes: usize,
+        namespace_config: NamespaceCacheConfig,
         handle: &Handle,
     ) -> Self {
         Self::new_internal(
@@ -69,6 +81,7 @@ impl CatalogCache {
             object_store,
             ram_pool_metadata_bytes,
             ram_pool_data_bytes,
+            namespace_config,
             handle,
             false,
         )
@@ -91,6 +104,7 @@ impl CatalogCache {
             object_store,
             usize::MAX,
             usize::MAX,
+            NamespaceCacheConfig::default(),
             handle,
             true,
         )
@@ -104,6 +118,7 @@ impl CatalogCache {
         object_store: Arc<dyn ObjectStore>,
         ram_pool_metadata_bytes: usize,
         ram_pool_data_bytes: usize,
+        namespace_config: NamespaceCacheConfig,
         handle: &Handle,
         testing: bool,
     ) -> Self {
@@ -133,6 +148,7 @@ impl CatalogCache {
         let namespace_cache = NamespaceCache::new(
             Arc::clone(&catalog),
             backoff_config.clone(),
+            namespace_config,
             Arc::clone(&time_provider),
             &metric_registry,
             Arc::clone(&ram_pool_metadata),
@@ -163,6 +179,17 @@ impl CatalogCache {
             testing,
         );
 
+        let introspection = CacheIntrospection::new(
+            Arc::clone(&metric_registry),
+            vec![
+                (namespace::CACHE_ID, Arc::clone(&ram_pool_metadata)),
+                (parquet_file::CACHE_ID, Arc::clone(&ram_pool_metadata)),
+                (partition::CACHE_ID, Arc::clone(&ram_pool_metadata)),
+                (projected_schema::CACHE_ID, Arc::clone(&ram_pool_metadata)),
+                (object_store::CACHE_ID, ram_pool_data),
+            ],
+        );
+
         Self {
             catalog,
             partition_cache,
@@ -170,6 +197,7 @@ impl CatalogCache {
             parquet_file_cache,
             projected_schema_cache,
             object_store_cache,
+            introspection,
             metric_registry,
             time_provider,
         }
@@ -210,6 +238,42 @@ impl CatalogCache {
         &self.projected_schema_cache
     }
 
+    /// Load the given namespaces into the namespace cache, see [`NamespaceCache::warm`].
+    pub async fn warm_namespaces(&self, names: &[Arc<str>]) {
+        self.namespace_cache.warm(names).await
+    }
+
+    /// Load all namespaces known to the catalog into the namespace cache.
+    ///
+    /// Failures are logged but otherwise ignored since warming is only an optimization.
+    pub async fn warm_all_namespaces(&self) {
+        let namespaces = match self
+            .catalog
+            .repositories()
+            .await
+            .namespaces()
+            .list(SoftDeletedRows::ExcludeDeleted)
+            .await
+        {
+            Ok(namespaces) => namespaces,
+            Err(e) => {
+                warn!(%e, "cannot list namespaces to warm namespace cache");
+                return;
+            }
+        };
+
+        let names = namespaces
+            .into_iter()
+            .map(|namespace| Arc::from(namespace.name))
+            .collect::<Vec<_>>();
+        self.warm_namespaces(&names).await
+    }
+
+    /// Introspection handle for the state of the caches.
+    pub(crate) fn introspection(&self) -> CacheIntrospection {
+        self.introspection.clone()
+    }
+
     /// Parquet store that points to the cached object store.
     pub fn parquet_store(&self) -> ParquetStorage {
         ParquetStorage::new(
@@ -9,7 +9,7 @@ use cache_system::{
         ttl::{OptionalValueTtlProvider, TtlPolicy},
         PolicyBackend,
     },
-    cache::{driver::CacheDriver, metrics::CacheWithMetrics, Cache},
+    cache::{driver::CacheDriver, metrics::CacheWithMetrics, Cache, CacheGetStatus},
     loader::{metrics::MetricsLoader, FunctionLoader},
     resource_consumption::FunctionEstimator,
 };
@@ -17,9 +17,14 @@ use data_types::{
     partition_template::TablePartitionTemplateOverride, Column, ColumnId, Namespace, NamespaceId,
     Table, TableId,
 };
+use futures::StreamExt;
 use iox_catalog::interface::{Catalog, SoftDeletedRows};
 use iox_time::TimeProvider;
+use metric::U64Counter;
+use observability_deps::tracing::{error, info, warn};
+use parking_lot::Mutex;
 use schema::{InfluxColumnType, Schema, SchemaBuilder};
+use snafu::{ResultExt, Snafu};
 use std::{
     collections::{HashMap, HashSet},
     mem::{size_of, size_of_val},
@@ -57,7 +62,33 @@ pub const REFRESH_EXISTING: BackoffConfig = BackoffConfig {
 ///              non-existing namespaces.
 pub const TTL_NON_EXISTING: Duration = Duration::from_nanos(1);
 
-const CACHE_ID: &str = "namespace";
+pub(crate) const CACHE_ID: &str = "namespace";
+
+/// Maximum number of namespaces that [`NamespaceCache::warm`] loads concurrently.
+const WARM_CONCURRENCY: usize = 10;
+
+/// Expiration and refresh policy of the [`NamespaceCache`].
+#[derive(Debug, Clone)]
+pub struct NamespaceCacheConfig {
+    /// Duration to keep existing namespaces, see [`TTL_EXISTING`].
+    pub ttl_existing: Duration,
+
+    /// Duration to keep non-existing namespaces, see [`TTL_NON_EXISTING`].
+    pub ttl_non_existing: Duration,
+
+    /// When to refresh an existing namespace, see [`REFRESH_EXISTING`].
+    pub refresh_existing: BackoffConfig,
+}
+
+impl Default for NamespaceCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_existing: TTL_EXISTING,
+            ttl_non_existing: TTL_NON_EXISTING,
+            refresh_existing: REFRESH_EXISTING,
+        }
+    }
+}
 
 type CacheT = Box<
     dyn Cache<
@@ -68,66 +99,126 @@ type CacheT = Box<
     >,
 >;
 
+/// Errors of the [`NamespaceCache`] and of building a [`CachedNamespace`].
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(display("Broken schema of table '{}': {}", table_name, source))]
+    TableSchema {
+        table_name: String,
+        source: schema::builder::Error,
+    },
+
+    #[snafu(display("All {} tables of namespace {} are broken", n_tables, namespace_id))]
+    AllTablesBroken {
+        namespace_id: NamespaceId,
+        n_tables: usize,
+    },
+}
+
+/// Requirement that a cached namespace has to fulfill, see [`NamespaceCache::get`].
+#[derive(Debug, Clone, Copy)]
+pub enum ShouldCover<'a> {
+    /// The table exists and knows the given columns.
+    Columns(&'a str, &'a HashSet<ColumnId>),
+
+    /// The namespace has the given retention period.
+    RetentionPeriod(Option<Duration>),
+
+    /// The table exists and uses the given partition template.
+    PartitionTemplate(&'a str, &'a TablePartitionTemplateOverride),
+}
+
+/// Maps namespace IDs to the names under which they are cached.
+type IdIndex = Arc<Mutex<HashMap<NamespaceId, Arc<str>>>>;
+
+/// Serializes the table refreshes of each namespace, see [`NamespaceCache::refresh_tables`].
+type RefreshLocks = Mutex<HashMap<Arc<str>, Arc<tokio::sync::Mutex<()>>>>;
+
 /// Cache for namespace-related attributes.
 #[derive(Debug)]
 pub struct NamespaceCache {
     cache: CacheT,
     remove_if_handle: RemoveIfHandle<Arc<str>, Option<Arc<CachedNamespace>>>,
+    id_index: IdIndex,
+    refresh_locks: RefreshLocks,
+    catalog: Arc<dyn Catalog>,
+    backoff_config: BackoffConfig,
+    metric_broken_tables: U64Counter,
 }
 
 impl NamespaceCache {
     /// Create new empty cache.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         catalog: Arc<dyn Catalog>,
         backoff_config: BackoffConfig,
+        config: NamespaceCacheConfig,
         time_provider: Arc<dyn TimeProvider>,
         metric_registry: &metric::Registry,
         ram_pool: Arc<ResourcePool<RamSize>>,
         handle: &Handle,
         testing: bool,
     ) -> Self {
+        let id_index = IdIndex::default();
+        let metric_broken_tables = metric_registry
+            .register_metric::<U64Counter>(
+                "namespace_cache_broken_tables",
+                "Number of tables that could not be cached due to a broken catalog schema",
+            )
+            .recorder(&[]);
+
+        let catalog_captured = Arc::clone(&catalog);
+        let backoff_config_captured = backoff_config.clone();
+        let id_index_captured = Arc::clone(&id_index);
+        let metric_broken_tables_captured = metric_broken_tables.clone();
         let loader = FunctionLoader::new(move |namespace_name: Arc<str>, _extra: ()| {
-            let catalog = Arc::clone(&catalog);
-            let backoff_config = backoff_config.clone();
+            let catalog = Arc::clone(&catalog_captured);
+            let backoff_config = backoff_config_captured.clone();
+            let id_index = Arc::clone(&id_index_captured);
+            let metric_broken_tables = metric_broken_tables_captured.clone();
 
             async move {
-                let namespace = Backoff::new(&backoff_config)
+                // fetch the namespace with all its tables and columns in one go so a slow catalog
+                // only costs two round trips
+                let loaded = Backoff::new(&backoff_config)
                     .retry_all_errors("get namespace", || async {
-                        catalog
-                            .repositories()
-                            .await
+                        let mut repos = catalog.repositories().await;
+
+                        let Some(namespace) = repos
                             .namespaces()
                             .get_by_name(&namespace_name, SoftDeletedRows::ExcludeDeleted)
-                            .await
-                    })
-                    .await
-                    .expect("retry forever")?;
-
-                let tables = Backoff::new(&backoff_config)
-                    .retry_all_errors("get namespace tables", || async {
-                        catalog
-                            .repositories()
-                            .await
+                            .await?
+                        else {
+                            return Ok(None);
+                        };
+                        let (tables, columns) = repos
                             .tables()
-                            .list_by_namespace_id(namespace.id)
-                            .await
-                    })
-                    .await
-                    .expect("retry forever");
+                            .list_with_columns_by_namespace_id(namespace.id)
+                            .await?;
 
-                let columns = Backoff::new(&backoff_config)
-                    .retry_all_errors("get namespace columns", || async {
-                        catalog
-                            .repositories()
-                            .await
-                            .columns()
-                            .list_by_namespace_id(namespace.id)
-                            .await
+                        Ok::<_, iox_catalog::interface::Error>(Some((namespace, tables, columns)))
                     })
                     .await
                     .expect("retry forever");
 
-                Some(Arc::new(CachedNamespace::new(namespace, tables, columns)))
+                // keep the ID index in sync with what is cached under this name
+                {
+                    let loaded_id = loaded.as_ref().map(|(namespace, _, _)| namespace.id);
+                    let mut id_index = id_index.lock();
+                    id_index.retain(|id, name| name != &namespace_name || Some(*id) == loaded_id);
+                    if let Some(id) = loaded_id {
+                        id_index.insert(id, Arc::clone(&namespace_name));
+                    }
+                }
+
+                let (namespace, tables, columns) = loaded?;
+                let namespace = CachedNamespace::new(namespace, tables, columns);
+                metric_broken_tables.inc(namespace.broken_tables.len() as u64);
+                if let Err(e) = namespace.ensure_not_broken() {
+                    // cached anyways, so that the namespace is not re-fetched on every query
+                    error!(%e, namespace=%namespace_name, "cannot use namespace");
+                }
+                Some(Arc::new(namespace))
             }
         });
         let loader = Arc::new(MetricsLoader::new(
@@ -141,8 +232,8 @@ impl NamespaceCache {
         let mut backend = PolicyBackend::hashmap_backed(Arc::clone(&time_provider));
         backend.add_policy(TtlPolicy::new(
             Arc::new(OptionalValueTtlProvider::new(
-                Some(TTL_NON_EXISTING),
-                Some(TTL_EXISTING),
+                Some(config.ttl_non_existing),
+                Some(config.ttl_existing),
             )),
             CACHE_ID,
             metric_registry,
@@ -151,7 +242,7 @@ impl NamespaceCache {
             Arc::clone(&time_provider),
             Arc::new(OptionalValueRefreshDurationProvider::new(
                 None,
-                Some(REFRESH_EXISTING),
+                Some(config.refresh_existing),
             )),
             Arc::clone(&loader) as _,
             CACHE_ID,
@@ -188,43 +279,357 @@ impl NamespaceCache {
         Self {
             cache,
             remove_if_handle,
+            id_index,
+            refresh_locks: Default::default(),
+            catalog,
+            backoff_config,
+            metric_broken_tables,
         }
     }
 
+    /// Expire the cached namespace if it does NOT fulfill the given [requirements](ShouldCover).
+    ///
+    /// Unlike [`get`](Self::get) this does not load the namespace. It is used when fresher data (e.g. a namespace
+    /// listing) is at hand anyways, so the next request re-fetches the namespace instead of serving stale data.
+    pub fn expire_outdated(&self, name: &Arc<str>, should_cover: &[ShouldCover<'_>]) -> bool {
+        self.remove_if_handle.remove_if(name, |cached_namespace| {
+            cached_namespace.is_some_and(|namespace| !namespace.covers_namespace(should_cover))
+        })
+    }
+
     /// Get namespace schema by name.
     ///
-    /// Expire namespace if the cached schema does NOT cover the given set of columns. The set is given as a list of
-    /// pairs of table name and column set.
+    /// Refresh namespace if the cached schema does NOT fulfill the given [requirements](ShouldCover). Only the
+    /// tables that are not covered are reloaded, unless the namespace itself changed in the meantime.
+    ///
+    /// Returns `None` if the namespace does not exist and [`Error::AllTablesBroken`] if it exists but none of its
+    /// tables can be used.
     pub async fn get(
         &self,
         name: Arc<str>,
-        should_cover: &[(&str, &HashSet<ColumnId>)],
+        should_cover: &[ShouldCover<'_>],
+        span: Option<Span>,
+    ) -> Result<Option<Arc<CachedNamespace>>, Error> {
+        let Some(namespace) = self.get_or_refresh(name, should_cover, span).await else {
+            return Ok(None);
+        };
+        namespace.ensure_not_broken()?;
+        Ok(Some(namespace))
+    }
+
+    /// Get the cached namespace, see [`get`](Self::get), without checking if it is broken.
+    async fn get_or_refresh(
+        &self,
+        name: Arc<str>,
+        should_cover: &[ShouldCover<'_>],
         span: Option<Span>,
     ) -> Option<Arc<CachedNamespace>> {
-        self.remove_if_handle
-            .remove_if_and_get(
+        let (namespace, status) = self
+            .remove_if_handle
+            .remove_if_and_get_with_status(
                 &self.cache,
-                name,
+                Arc::clone(&name),
                 |cached_namespace| {
                     if let Some(namespace) = cached_namespace.as_ref() {
-                        should_cover.iter().any(|(table_name, columns)| {
-                            if let Some(table) = namespace.tables.get(*table_name) {
-                                columns
-                                    .iter()
-                                    .any(|col| !table.column_id_map.contains_key(col))
-                            } else {
-                                // table unknown => need to update
-                                true
-                            }
-                        })
+                        // uncovered tables are refreshed individually, see below
+                        !namespace.covers_namespace(should_cover)
                     } else {
                         // namespace unknown => need to update if should cover anything
                         !should_cover.is_empty()
                     }
                 },
-                ((), span),
+                ((), span.clone()),
             )
+            .await;
+        let namespace = namespace?;
+
+        if status == CacheGetStatus::Miss {
+            // just loaded, nothing to refresh
+            return Some(namespace);
+        }
+
+        if namespace.uncovered_tables(should_cover).is_empty() {
+            return Some(namespace);
+        }
+        self.refresh_tables(name, namespace, should_cover, span)
+            .await
+    }
+
+    /// Load the given namespaces into the cache without waiting for queries to request them.
+    ///
+    /// At most [`WARM_CONCURRENCY`] namespaces are loaded at the same time. Namespaces that do not exist or that are
+    /// broken are logged and skipped.
+    pub async fn warm(&self, names: &[Arc<str>]) {
+        let loaded = futures::stream::iter(names)
+            .map(|name| async move {
+                match self.get(Arc::clone(name), &[], None).await {
+                    Ok(Some(_)) => true,
+                    Ok(None) => {
+                        warn!(namespace = %name, "cannot warm namespace cache, namespace not found");
+                        false
+                    }
+                    Err(e) => {
+                        warn!(%e, namespace = %name, "cannot warm namespace cache");
+                        false
+                    }
+                }
+            })
+            .buffer_unordered(WARM_CONCURRENCY)
+            .filter(|exists| futures::future::ready(*exists))
+            .count()
+            .await;
+
+        info!(loaded, requested = names.len(), "warmed namespace cache");
+    }
+
+    /// Reload the tables of a cached namespace that are not covered and write the merged namespace back to the
+    /// cache.
+    ///
+    /// Refreshes of the same namespace run one at a time, so that they don't overwrite each other's tables and a
+    /// table that is requested by concurrent queries is only reloaded once. The reloaded tables are merged into
+    /// whatever is cached when they arrive, which may have been replaced by the loader of the cache in the meantime.
+    ///
+    /// Falls back to reloading the entire namespace if the namespace itself changed.
+    async fn refresh_tables(
+        &self,
+        name: Arc<str>,
+        cached: Arc<CachedNamespace>,
+        should_cover: &[ShouldCover<'_>],
+        span: Option<Span>,
+    ) -> Option<Arc<CachedNamespace>> {
+        let lock = Arc::clone(
+            self.refresh_locks
+                .lock()
+                .entry(Arc::clone(&name))
+                .or_default(),
+        );
+        let _guard = lock.lock().await;
+
+        // a concurrent refresh may have covered the tables while waiting
+        let cached = self.current(&name, cached, span.clone()).await;
+        let table_names = cached.uncovered_tables(should_cover);
+        if table_names.is_empty() {
+            return Some(cached);
+        }
+
+        let tables = Backoff::new(&self.backoff_config)
+            .retry_all_errors("refresh namespace tables", || async {
+                let mut repos = self.catalog.repositories().await;
+
+                let namespace = repos
+                    .namespaces()
+                    .get_by_name(&name, SoftDeletedRows::ExcludeDeleted)
+                    .await?;
+                let Some(namespace) = namespace.filter(|namespace| {
+                    namespace.id == cached.id
+                        && retention_period(namespace) == cached.retention_period
+                }) else {
+                    return Ok(None);
+                };
+
+                let mut tables = Vec::with_capacity(table_names.len());
+                for table_name in &table_names {
+                    let Some(table) = repos
+                        .tables()
+                        .get_by_namespace_and_name(namespace.id, table_name)
+                        .await?
+                    else {
+                        // table does not exist (yet)
+                        continue;
+                    };
+                    let columns = repos.columns().list_by_table_id(table.id).await?;
+                    tables.push((table, columns));
+                }
+
+                Ok::<_, iox_catalog::interface::Error>(Some(tables))
+            })
+            .await
+            .expect("retry forever");
+
+        let Some(tables) = tables else {
+            // namespace is outdated => reload it entirely
+            self.remove_if_handle.remove_if(&name, |namespace| {
+                namespace.is_some_and(|namespace| Arc::ptr_eq(&namespace, &cached))
+            });
+            return self.cache.get(name, ((), span)).await;
+        };
+
+        let mut namespace = self.current(&name, cached, span).await.as_ref().clone();
+        for (table, columns) in tables {
+            let table_name = Arc::from(table.name.clone());
+            match CachedTable::new(table, columns) {
+                Ok(table) => {
+                    namespace.broken_tables.remove(&table_name);
+                    namespace.tables.insert(table_name, Arc::new(table));
+                }
+                Err(e) => {
+                    // keep whatever was cached before, but don't refresh the table again until the namespace is
+                    // reloaded
+                    self.metric_broken_tables.inc(1);
+                    warn!(%e, namespace=%name, "cannot refresh table with broken schema");
+                    namespace.broken_tables.insert(table_name);
+                }
+            }
+        }
+        let namespace = Arc::new(namespace);
+
+        self.cache.set(name, Some(Arc::clone(&namespace))).await;
+        Some(namespace)
+    }
+
+    /// The namespace that is currently cached under the given name, or `cached` if that is no longer the same
+    /// namespace.
+    async fn current(
+        &self,
+        name: &Arc<str>,
+        cached: Arc<CachedNamespace>,
+        span: Option<Span>,
+    ) -> Arc<CachedNamespace> {
+        match self.cache.peek(Arc::clone(name), ((), span)).await {
+            Some(Some(current)) if current.id == cached.id => current,
+            _ => cached,
+        }
+    }
+
+    /// Get namespace schema by ID.
+    ///
+    /// The ID is resolved to the namespace name via an index that is updated whenever a namespace is loaded, so
+    /// this shares its entries with [`get`](Self::get). If the ID is unknown, the name is looked up in the catalog
+    /// first. See [`get`](Self::get) for `should_cover` and the result.
+    pub async fn get_by_id(
+        &self,
+        id: NamespaceId,
+        should_cover: &[ShouldCover<'_>],
+        span: Option<Span>,
+    ) -> Result<Option<Arc<CachedNamespace>>, Error> {
+        let known_name = self.id_index.lock().get(&id).cloned();
+        if let Some(name) = known_name {
+            match self
+                .get_or_refresh(Arc::clone(&name), should_cover, span.clone())
+                .await
+            {
+                Some(namespace) if namespace.id == id => {
+                    namespace.ensure_not_broken()?;
+                    return Ok(Some(namespace));
+                }
+                _ => {
+                    // the name now refers to another namespace or to none at all
+                    let mut id_index = self.id_index.lock();
+                    if id_index.get(&id) == Some(&name) {
+                        id_index.remove(&id);
+                    }
+                }
+            }
+        }
+
+        let Some(name) = self.name_by_id(id).await else {
+            return Ok(None);
+        };
+        let Some(namespace) = self
+            .get_or_refresh(name, should_cover, span)
+            .await
+            .filter(|namespace| namespace.id == id)
+        else {
+            return Ok(None);
+        };
+        namespace.ensure_not_broken()?;
+        Ok(Some(namespace))
+    }
+
+    /// Look up the name of the given namespace in the catalog and record it in the ID index.
+    async fn name_by_id(&self, id: NamespaceId) -> Option<Arc<str>> {
+        let namespace = Backoff::new(&self.backoff_config)
+            .retry_all_errors("get namespace by id", || async {
+                self.catalog
+                    .repositories()
+                    .await
+                    .namespaces()
+                    .get_by_id(id, SoftDeletedRows::ExcludeDeleted)
+                    .await
+            })
             .await
+            .expect("retry forever")?;
+
+        let name: Arc<str> = Arc::from(namespace.name);
+        self.id_index.lock().insert(id, Arc::clone(&name));
+        Some(name)
+    }
+}
+
+/// Bidirectional mapping between the IDs and names of the columns of a [`CachedTable`].
+///
+/// Every column is stored exactly once, sorted by ID. Lookups by name go through a secondary index of
+/// positions sorted by name, so both directions are a binary search and no name is stored twice.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMap {
+    /// Columns, sorted by ID.
+    by_id: Box<[(ColumnId, Arc<str>)]>,
+
+    /// Positions within `by_id`, sorted by column name.
+    by_name: Box<[u32]>,
+}
+
+impl ColumnMap {
+    /// Name of the column with the given ID.
+    pub fn name_for_id(&self, id: ColumnId) -> Option<&Arc<str>> {
+        self.by_id
+            .binary_search_by_key(&id, |(id, _name)| *id)
+            .ok()
+            .map(|idx| &self.by_id[idx].1)
+    }
+
+    /// ID of the column with the given name.
+    pub fn id_for_name(&self, name: &str) -> Option<ColumnId> {
+        self.get_by_name(name).map(|(id, _name)| id)
+    }
+
+    /// ID and shared name of the column with the given name.
+    pub fn get_by_name(&self, name: &str) -> Option<(ColumnId, &Arc<str>)> {
+        self.by_name
+            .binary_search_by(|idx| self.by_id[*idx as usize].1.as_ref().cmp(name))
+            .ok()
+            .map(|pos| {
+                let (id, name) = &self.by_id[self.by_name[pos] as usize];
+                (*id, name)
+            })
+    }
+
+    /// Returns `true` if the column with the given ID is known.
+    pub fn contains_id(&self, id: ColumnId) -> bool {
+        self.name_for_id(id).is_some()
+    }
+
+    /// RAM-bytes EXCLUDING `self`.
+    fn size(&self) -> usize {
+        (self.by_id.len() * size_of::<(ColumnId, Arc<str>)>())
+            + self
+                .by_id
+                .iter()
+                .map(|(_id, name)| name.len())
+                .sum::<usize>()
+            + (self.by_name.len() * size_of::<u32>())
+    }
+}
+
+impl FromIterator<(ColumnId, Arc<str>)> for ColumnMap {
+    fn from_iter<T: IntoIterator<Item = (ColumnId, Arc<str>)>>(iter: T) -> Self {
+        let mut by_id: Vec<(ColumnId, Arc<str>)> = iter.into_iter().collect();
+        by_id.sort_by_key(|(id, _name)| *id);
+        by_id.dedup_by_key(|(id, _name)| *id);
+
+        let mut by_name: Vec<u32> = (0..by_id.len())
+            .map(|idx| u32::try_from(idx).expect("too many columns"))
+            .collect();
+        by_name.sort_by(|a, b| {
+            let (id_a, name_a) = &by_id[*a as usize];
+            let (id_b, name_b) = &by_id[*b as usize];
+            name_a.cmp(name_b).then(id_a.cmp(id_b))
+        });
+
+        Self {
+            by_id: by_id.into_boxed_slice(),
+            by_name: by_name.into_boxed_slice(),
+        }
     }
 }
 
@@ -232,72 +637,54 @@ impl NamespaceCache {
 pub struct CachedTable {
     pub id: TableId,
     pub schema: Schema,
-    pub column_id_map: HashMap<ColumnId, Arc<str>>,
-    pub column_id_map_rev: HashMap<Arc<str>, ColumnId>,
+    pub column_map: ColumnMap,
     pub primary_key_column_ids: Box<[ColumnId]>,
     pub partition_template: TablePartitionTemplateOverride,
 }
 
 impl CachedTable {
-    fn new(table: Table, mut columns: Vec<Column>) -> Self {
+    fn new(table: Table, mut columns: Vec<Column>) -> Result<Self, Error> {
         // sort columns by name so that schema is normalized
         // Note: `sort_by_key` doesn't work if we don't wanna clone the strings every time
         columns.sort_by(|x, y| x.name.cmp(&y.name));
 
-        let mut column_id_map: HashMap<ColumnId, Arc<str>> = columns
+        let column_map: ColumnMap = columns
             .iter()
             .map(|c| (c.id, Arc::from(c.name.clone())))
             .collect();
-        column_id_map.shrink_to_fit();
-
-        let mut column_id_map_rev: HashMap<Arc<str>, ColumnId> = column_id_map
-            .iter()
-            .map(|(v, k)| (Arc::clone(k), *v))
-            .collect();
-        column_id_map_rev.shrink_to_fit();
 
         let mut builder = SchemaBuilder::new();
         for col in columns {
             let t = InfluxColumnType::from(col.column_type);
             builder.influx_column(col.name, t);
         }
-        let schema = builder.build().expect("catalog schema broken");
+        let schema = builder.build().context(TableSchemaSnafu {
+            table_name: table.name.clone(),
+        })?;
 
         let primary_key_column_ids: Box<[ColumnId]> = schema
             .primary_key()
             .into_iter()
             .map(|name| {
-                *column_id_map_rev
-                    .get(name)
+                column_map
+                    .id_for_name(name)
                     .unwrap_or_else(|| panic!("primary key not known?!: {name}"))
             })
             .collect();
 
-        Self {
+        Ok(Self {
             id: table.id,
             schema,
-            column_id_map,
-            column_id_map_rev,
+            column_map,
             primary_key_column_ids,
             partition_template: table.partition_template,
-        }
+        })
     }
 
     /// RAM-bytes EXCLUDING `self`.
     fn size(&self) -> usize {
         self.schema.estimate_size()
-            + (self.column_id_map.capacity() * size_of::<(ColumnId, Arc<str>)>())
-            + self
-                .column_id_map
-                .values()
-                .map(|name| name.len())
-                .sum::<usize>()
-            + (self.column_id_map_rev.capacity() * size_of::<(Arc<str>, ColumnId)>())
-            + self
-                .column_id_map_rev
-                .keys()
-                .map(|name| name.len())
-                .sum::<usize>()
+            + self.column_map.size()
             + (self.primary_key_column_ids.len() * size_of::<ColumnId>())
             + (self.partition_template.size() - size_of::<TablePartitionTemplateOverride>())
     }
@@ -308,9 +695,17 @@ pub struct CachedNamespace {
     pub id: NamespaceId,
     pub retention_period: Option<Duration>,
     pub tables: HashMap<Arc<str>, Arc<CachedTable>>,
+
+    /// Tables with a broken schema.
+    ///
+    /// These are not refreshed on demand, only when the entire namespace is reloaded.
+    pub broken_tables: HashSet<Arc<str>>,
 }
 
 impl CachedNamespace {
+    /// Build namespace from its catalog rows.
+    ///
+    /// Tables with a broken schema are logged and recorded in [`broken_tables`](Self::broken_tables).
     pub fn new(namespace: Namespace, tables: Vec<Table>, columns: Vec<Column>) -> Self {
         let mut tables_by_id = tables
             .into_iter()
@@ -322,25 +717,88 @@ impl CachedNamespace {
             }
         }
 
-        let mut tables: HashMap<Arc<str>, Arc<CachedTable>> = tables_by_id
-            .into_iter()
-            .map(|(_tid, (t, tcols))| {
-                let name = Arc::from(t.name.clone());
-                let table = Arc::new(CachedTable::new(t, tcols));
-                (name, table)
-            })
-            .collect();
+        let mut tables = HashMap::with_capacity(tables_by_id.len());
+        let mut broken_tables = HashSet::new();
+        for (_tid, (t, tcols)) in tables_by_id {
+            let name = Arc::from(t.name.clone());
+            match CachedTable::new(t, tcols) {
+                Ok(table) => {
+                    tables.insert(name, Arc::new(table));
+                }
+                Err(e) => {
+                    warn!(%e, namespace_id=%namespace.id, "skipping table with broken schema");
+                    broken_tables.insert(name);
+                }
+            }
+        }
         tables.shrink_to_fit();
 
-        let retention_period = namespace
-            .retention_period_ns
-            .map(|retention| Duration::from_nanos(retention as u64));
-
         Self {
             id: namespace.id,
-            retention_period,
+            retention_period: retention_period(&namespace),
             tables,
+            broken_tables,
+        }
+    }
+
+    /// Fails if the namespace has tables but all of them are broken.
+    pub fn ensure_not_broken(&self) -> Result<(), Error> {
+        if self.tables.is_empty() && !self.broken_tables.is_empty() {
+            return AllTablesBrokenSnafu {
+                namespace_id: self.id,
+                n_tables: self.broken_tables.len(),
+            }
+            .fail();
         }
+        Ok(())
+    }
+
+    /// Checks the namespace-wide requirements of `should_cover`, i.e. everything that is not specific to a table.
+    fn covers_namespace(&self, should_cover: &[ShouldCover<'_>]) -> bool {
+        should_cover.iter().all(|requirement| match requirement {
+            ShouldCover::RetentionPeriod(retention_period) => {
+                self.retention_period == *retention_period
+            }
+            ShouldCover::Columns(..) | ShouldCover::PartitionTemplate(..) => true,
+        })
+    }
+
+    /// Tables of `should_cover` that are unknown or that do not fulfill the requirements, e.g. because they miss
+    /// some of the given columns. Broken tables are never uncovered.
+    fn uncovered_tables<'a>(&self, should_cover: &[ShouldCover<'a>]) -> Vec<&'a str> {
+        let mut uncovered = should_cover
+            .iter()
+            .filter_map(|requirement| {
+                let (table_name, covered) = match *requirement {
+                    ShouldCover::Columns(table_name, _)
+                    | ShouldCover::PartitionTemplate(table_name, _)
+                        if self.broken_tables.contains(table_name) =>
+                    {
+                        // broken => don't refresh until the namespace is reloaded
+                        return None;
+                    }
+                    ShouldCover::Columns(table_name, columns) => (
+                        table_name,
+                        self.tables.get(table_name).map(|table| {
+                            columns.iter().all(|col| table.column_map.contains_id(*col))
+                        }),
+                    ),
+                    ShouldCover::PartitionTemplate(table_name, partition_template) => (
+                        table_name,
+                        self.tables
+                            .get(table_name)
+                            .map(|table| &table.partition_template == partition_template),
+                    ),
+                    ShouldCover::RetentionPeriod(_) => return None,
+                };
+
+                // table unknown => need to update
+                (!covered.unwrap_or_default()).then_some(table_name)
+            })
+            .collect::<Vec<_>>();
+        uncovered.sort_unstable();
+        uncovered.dedup();
+        uncovered
     }
 
     /// RAM-bytes EXCLUDING `self`.
@@ -351,13 +809,26 @@ impl CachedNamespace {
                 .iter()
                 .map(|(name, table)| name.len() + table.size())
                 .sum::<usize>()
+            + self.broken_tables.capacity() * size_of::<Arc<str>>()
+            + self
+                .broken_tables
+                .iter()
+                .map(|name| name.len())
+                .sum::<usize>()
     }
 }
 
+pub(crate) fn retention_period(namespace: &Namespace) -> Option<Duration> {
+    namespace
+        .retention_period_ns
+        .map(|retention| Duration::from_nanos(retention as u64))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::cache::{
-        ram::test_util::test_ram_pool, test_util::assert_catalog_access_metric_count,
+        ram::test_util::test_ram_pool,
+        test_util::{assert_catalog_access_metric_count, catalog_access_total_count},
     };
     use arrow::datatypes::DataType;
     use data_types::ColumnType;
@@ -365,6 +836,7 @@ mod tests {
         template_part::Part, PartitionTemplate, TemplatePart,
     };
     use iox_tests::TestCatalog;
+    use metric::{Attributes, DurationHistogram, Metric, U64Counter};
     use schema::SchemaBuilder;
 
     use super::*;
@@ -400,16 +872,19 @@ mod tests {
         let cache = NamespaceCache::new(
             catalog.catalog(),
             BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
             catalog.time_provider(),
             &catalog.metric_registry(),
             test_ram_pool(),
             &Handle::current(),
             true,
         );
+        let catalog_ops_before = catalog_access_total_count(&catalog.metric_registry);
 
         let actual_ns_1_a = cache
             .get(Arc::from(String::from("ns1")), &[], None)
             .await
+            .unwrap()
             .unwrap();
         let retention_period = ns1
             .namespace
@@ -430,16 +905,11 @@ mod tests {
                             .timestamp()
                             .build()
                             .unwrap(),
-                        column_id_map: HashMap::from([
+                        column_map: ColumnMap::from_iter([
                             (col111.column.id, Arc::from(col111.column.name.clone())),
                             (col112.column.id, Arc::from(col112.column.name.clone())),
                             (col113.column.id, Arc::from(col113.column.name.clone())),
                         ]),
-                        column_id_map_rev: HashMap::from([
-                            (Arc::from(col111.column.name.clone()), col111.column.id),
-                            (Arc::from(col112.column.name.clone()), col112.column.id),
-                            (Arc::from(col113.column.name.clone()), col113.column.id),
-                        ]),
                         primary_key_column_ids: [col112.column.id, col113.column.id].into(),
                         partition_template: table11.table.partition_template.clone(),
                     }),
@@ -454,26 +924,34 @@ mod tests {
                             .timestamp()
                             .build()
                             .unwrap(),
-                        column_id_map: HashMap::from([
+                        column_map: ColumnMap::from_iter([
                             (col121.column.id, Arc::from(col121.column.name.clone())),
                             (col122.column.id, Arc::from(col122.column.name.clone())),
                         ]),
-                        column_id_map_rev: HashMap::from([
-                            (Arc::from(col121.column.name.clone()), col121.column.id),
-                            (Arc::from(col122.column.name.clone()), col122.column.id),
-                        ]),
                         primary_key_column_ids: [col122.column.id].into(),
                         partition_template: TablePartitionTemplateOverride::default(),
                     }),
                 ),
             ]),
+            broken_tables: HashSet::new(),
         };
         assert_eq!(actual_ns_1_a.as_ref(), &expected_ns_1);
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            1,
+        );
+        // a cold load only looks up the namespace and then lists its tables and columns at once
+        assert_eq!(
+            catalog_access_total_count(&catalog.metric_registry) - catalog_ops_before,
+            2
+        );
 
         let actual_ns_2 = cache
             .get(Arc::from(String::from("ns2")), &[], None)
             .await
+            .unwrap()
             .unwrap();
         let retention_period = ns2
             .namespace
@@ -487,18 +965,15 @@ mod tests {
                 Arc::new(CachedTable {
                     id: table21.table.id,
                     schema: SchemaBuilder::new().timestamp().build().unwrap(),
-                    column_id_map: HashMap::from([(
+                    column_map: ColumnMap::from_iter([(
                         col211.column.id,
                         Arc::from(col211.column.name.clone()),
                     )]),
-                    column_id_map_rev: HashMap::from([(
-                        Arc::from(col211.column.name.clone()),
-                        col211.column.id,
-                    )]),
                     primary_key_column_ids: [col211.column.id].into(),
                     partition_template: TablePartitionTemplateOverride::default(),
                 }),
             )]),
+            broken_tables: HashSet::new(),
         };
         assert_eq!(actual_ns_2.as_ref(), &expected_ns_2);
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
@@ -506,6 +981,7 @@ mod tests {
         let actual_ns_1_b = cache
             .get(Arc::from(String::from("ns1")), &[], None)
             .await
+            .unwrap()
             .unwrap();
         assert!(Arc::ptr_eq(&actual_ns_1_a, &actual_ns_1_b));
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
@@ -518,6 +994,7 @@ mod tests {
         let cache = NamespaceCache::new(
             catalog.catalog(),
             BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
             catalog.time_provider(),
             &catalog.metric_registry(),
             test_ram_pool(),
@@ -526,11 +1003,11 @@ mod tests {
         );
 
         let none = cache.get(Arc::from(String::from("foo")), &[], None).await;
-        assert!(none.is_none());
+        assert!(none.unwrap().is_none());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
 
         let none = cache.get(Arc::from(String::from("foo")), &[], None).await;
-        assert!(none.is_none());
+        assert!(none.unwrap().is_none());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
     }
 
@@ -541,6 +1018,7 @@ mod tests {
         let cache = NamespaceCache::new(
             catalog.catalog(),
             BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
             catalog.time_provider(),
             &catalog.metric_registry(),
             test_ram_pool(),
@@ -549,15 +1027,28 @@ mod tests {
         );
 
         // ========== namespace unknown ==========
-        assert!(cache.get(Arc::from("ns1"), &[], None).await.is_none());
+        assert!(cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_none());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
 
-        assert!(cache.get(Arc::from("ns1"), &[], None).await.is_none());
+        assert!(cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_none());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
 
         assert!(cache
-            .get(Arc::from("ns1"), &[("t1", &HashSet::from([]))], None)
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &HashSet::from([]))],
+                None
+            )
             .await
+            .unwrap()
             .is_none());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
 
@@ -565,14 +1056,24 @@ mod tests {
         let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
 
         assert!(cache
-            .get(Arc::from("ns1"), &[("t1", &HashSet::from([]))], None)
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &HashSet::from([]))],
+                None
+            )
             .await
+            .unwrap()
             .is_some());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 3);
 
         assert!(cache
-            .get(Arc::from("ns1"), &[("t1", &HashSet::from([]))], None)
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &HashSet::from([]))],
+                None
+            )
             .await
+            .unwrap()
             .is_some());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 4);
 
@@ -580,14 +1081,24 @@ mod tests {
         let t1 = ns1.create_table("t1").await;
 
         assert!(cache
-            .get(Arc::from("ns1"), &[("t1", &HashSet::from([]))], None)
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &HashSet::from([]))],
+                None
+            )
             .await
+            .unwrap()
             .is_some());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 5);
 
         assert!(cache
-            .get(Arc::from("ns1"), &[("t1", &HashSet::from([]))], None)
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &HashSet::from([]))],
+                None
+            )
             .await
+            .unwrap()
             .is_some());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 5);
 
@@ -596,29 +1107,806 @@ mod tests {
         let c2 = t1.create_column("c2", ColumnType::Bool).await;
 
         assert!(cache
-            .get(Arc::from("ns1"), &[("t1", &HashSet::from([]))], None)
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &HashSet::from([]))],
+                None
+            )
             .await
+            .unwrap()
             .is_some());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 5);
 
         assert!(cache
             .get(
                 Arc::from("ns1"),
-                &[("t1", &HashSet::from([c1.column.id]))],
+                &[ShouldCover::Columns("t1", &HashSet::from([c1.column.id]))],
                 None
             )
             .await
+            .unwrap()
             .is_some());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 6);
 
         assert!(cache
             .get(
                 Arc::from("ns1"),
-                &[("t1", &HashSet::from([c2.column.id]))],
+                &[ShouldCover::Columns("t1", &HashSet::from([c2.column.id]))],
                 None
             )
             .await
+            .unwrap()
             .is_some());
         assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 6);
     }
+
+    #[tokio::test]
+    async fn test_custom_ttl() {
+        let catalog = TestCatalog::new();
+        catalog.create_namespace_1hr_retention("ns1").await;
+
+        let config = NamespaceCacheConfig {
+            ttl_existing: Duration::from_secs(10),
+            refresh_existing: BackoffConfig {
+                init_backoff: Duration::from_secs(3600),
+                ..REFRESH_EXISTING
+            },
+            ..Default::default()
+        };
+        let custom = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            config,
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+        let default = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        assert!(custom
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(default
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
+
+        // both caches still hold the namespace before the custom TTL is over
+        catalog.mock_time_provider().inc(Duration::from_secs(9));
+        assert!(custom
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(default
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
+
+        // only the custom TTL expired
+        catalog.mock_time_provider().inc(Duration::from_secs(2));
+        assert!(custom
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 3);
+        assert!(default
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_id_after_name() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+
+        let config = NamespaceCacheConfig {
+            ttl_existing: Duration::from_secs(10),
+            refresh_existing: BackoffConfig {
+                init_backoff: Duration::from_secs(3600),
+                ..REFRESH_EXISTING
+            },
+            ..Default::default()
+        };
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            config,
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        // unknown ID
+        assert!(cache
+            .get_by_id(NamespaceId::new(i64::MAX), &[], None)
+            .await
+            .unwrap()
+            .is_none());
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_id", 1);
+
+        let by_name = cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+
+        // index hit
+        let by_id = cache
+            .get_by_id(ns1.namespace.id, &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&by_name, &by_id));
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_id", 1);
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+
+        // expired entries are reloaded by name
+        catalog.mock_time_provider().inc(Duration::from_secs(11));
+        let by_id = cache
+            .get_by_id(ns1.namespace.id, &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_id.id, ns1.namespace.id);
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_id", 1);
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
+
+        // a soft-deleted namespace is gone from both lookups
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .namespaces()
+            .soft_delete("ns1")
+            .await
+            .unwrap();
+        catalog.mock_time_provider().inc(Duration::from_secs(11));
+        assert!(cache
+            .get_by_id(ns1.namespace.id, &[], None)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(cache.id_index.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_by_id_cold() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        let by_id = cache
+            .get_by_id(ns1.namespace.id, &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_id.id, ns1.namespace.id);
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_id", 1);
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+
+        // both lookups now share the cached entry
+        let by_id_2 = cache
+            .get_by_id(ns1.namespace.id, &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        let by_name = cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&by_id, &by_id_2));
+        assert!(Arc::ptr_eq(&by_id, &by_name));
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_id", 1);
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+    }
+
+    #[tokio::test]
+    async fn test_partial_refresh() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+        let t1 = ns1.create_table("t1").await;
+        let t2 = ns1.create_table("t2").await;
+        t1.create_column("time", ColumnType::Time).await;
+        t2.create_column("time", ColumnType::Time).await;
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        let initial = cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            1,
+        );
+
+        // new column => only reload the affected table
+        let c1 = t1.create_column("c1", ColumnType::Tag).await;
+        let refreshed = cache
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &HashSet::from([c1.column.id]))],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(refreshed.tables["t1"].column_map.contains_id(c1.column.id));
+        assert!(Arc::ptr_eq(&initial.tables["t2"], &refreshed.tables["t2"]));
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            1,
+        );
+        assert_catalog_access_metric_count(&catalog.metric_registry, "column_list_by_table_id", 1);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            1,
+        );
+
+        // merged namespace was written back to the cache
+        let cached = cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&refreshed, &cached));
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 2);
+
+        // new table => only load that table
+        let t3 = ns1.create_table("t3").await;
+        let refreshed = cache
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t3", &HashSet::from([]))],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refreshed.tables["t3"].id, t3.table.id);
+        assert_eq!(refreshed.tables.len(), 3);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            2,
+        );
+        assert_catalog_access_metric_count(&catalog.metric_registry, "column_list_by_table_id", 2);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            1,
+        );
+
+        // namespace changed => full reload
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .namespaces()
+            .update_retention_period("ns1", None)
+            .await
+            .unwrap();
+        let c2 = t2.create_column("c2", ColumnType::Tag).await;
+        let reloaded = cache
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t2", &HashSet::from([c2.column.id]))],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reloaded.retention_period, None);
+        assert!(reloaded.tables["t2"].column_map.contains_id(c2.column.id));
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            2,
+        );
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            2,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_partial_refresh() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+        let t1 = ns1.create_table("t1").await;
+        let t2 = ns1.create_table("t2").await;
+        t1.create_column("time", ColumnType::Time).await;
+        t2.create_column("time", ColumnType::Time).await;
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+        cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // refreshes of different tables don't overwrite each other
+        let c1 = t1.create_column("c1", ColumnType::Tag).await;
+        let c2 = t2.create_column("c2", ColumnType::Tag).await;
+        let columns_1 = HashSet::from([c1.column.id]);
+        let columns_2 = HashSet::from([c2.column.id]);
+        tokio::join!(
+            cache.get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &columns_1)],
+                None
+            ),
+            cache.get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t2", &columns_2)],
+                None
+            ),
+        );
+        let cached = cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(cached.tables["t1"].column_map.contains_id(c1.column.id));
+        assert!(cached.tables["t2"].column_map.contains_id(c2.column.id));
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            2,
+        );
+
+        // concurrent refreshes of the same table only reload it once
+        let c3 = t1.create_column("c3", ColumnType::Tag).await;
+        let columns_3 = HashSet::from([c3.column.id]);
+        let (refreshed_1, refreshed_2) = tokio::join!(
+            cache.get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &columns_3)],
+                None
+            ),
+            cache.get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t1", &columns_3)],
+                None
+            ),
+        );
+        assert!(Arc::ptr_eq(
+            &refreshed_1.unwrap().unwrap(),
+            &refreshed_2.unwrap().unwrap()
+        ));
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            3,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_warm() {
+        let catalog = TestCatalog::new();
+        catalog.create_namespace_1hr_retention("ns1").await;
+        catalog.create_namespace_1hr_retention("ns2").await;
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        // unknown namespaces are skipped
+        cache
+            .warm(&[Arc::from("ns1"), Arc::from("ns2"), Arc::from("unknown")])
+            .await;
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 3);
+        let cache_hits = || {
+            catalog
+                .metric_registry
+                .get_instrument::<Metric<DurationHistogram>>("iox_cache_get")
+                .unwrap()
+                .get_observer(&Attributes::from(&[("name", CACHE_ID), ("status", "hit")]))
+                .unwrap()
+                .fetch()
+                .sample_count()
+        };
+        assert_eq!(cache_hits(), 0);
+
+        assert!(cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(cache
+            .get(Arc::from("ns2"), &[], None)
+            .await
+            .unwrap()
+            .is_some());
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 3);
+        assert_eq!(cache_hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_should_cover_retention_and_partition_template() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+        let t1 = ns1
+            .create_table_with_partition_template(
+                "t1",
+                Some(PartitionTemplate {
+                    parts: vec![TemplatePart {
+                        part: Some(Part::TagValue(String::from("tag"))),
+                    }],
+                }),
+            )
+            .await;
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        let cached = cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            1,
+        );
+
+        // up-to-date
+        let retention_period = cached.retention_period;
+        assert!(retention_period.is_some());
+        let covered = cache
+            .get(
+                Arc::from("ns1"),
+                &[
+                    ShouldCover::RetentionPeriod(retention_period),
+                    ShouldCover::PartitionTemplate("t1", &t1.table.partition_template),
+                ],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&cached, &covered));
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+
+        // different partition template => reload the table
+        let other_template = TablePartitionTemplateOverride::default();
+        cache
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::PartitionTemplate("t1", &other_template)],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            1,
+        );
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            1,
+        );
+
+        // retention period changed => reload the namespace
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .namespaces()
+            .update_retention_period("ns1", None)
+            .await
+            .unwrap();
+        let reloaded = cache
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::RetentionPeriod(None)],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reloaded.retention_period, None);
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            2,
+        );
+
+        let cached = cache
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::RetentionPeriod(None)],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&reloaded, &cached));
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_list_with_columns_by_namespace_id",
+            2,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broken_table() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+        let t1 = ns1.create_table("t1").await;
+        t1.create_column("time", ColumnType::Time).await;
+        // the time column must be called `time`
+        let t2 = ns1.create_table("t2").await;
+        t2.create_column("ts", ColumnType::Time).await;
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        let namespace = cache
+            .get(Arc::from("ns1"), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            namespace
+                .tables
+                .keys()
+                .map(|k| k.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["t1"]
+        );
+        let broken_tables = catalog
+            .metric_registry
+            .get_instrument::<Metric<U64Counter>>("namespace_cache_broken_tables")
+            .unwrap()
+            .get_observer(&Attributes::from(&[]))
+            .unwrap()
+            .fetch();
+        assert_eq!(broken_tables, 1);
+
+        // the broken table is not refreshed on demand
+        let columns = HashSet::new();
+        let cached = cache
+            .get(
+                Arc::from("ns1"),
+                &[ShouldCover::Columns("t2", &columns)],
+                None,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(Arc::ptr_eq(&namespace, &cached));
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            0,
+        );
+    }
+
+    #[tokio::test]
+    async fn test_broken_namespace() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+        // the time column must be called `time`
+        let t1 = ns1.create_table("t1").await;
+        t1.create_column("ts", ColumnType::Time).await;
+
+        let cache = NamespaceCache::new(
+            catalog.catalog(),
+            BackoffConfig::default(),
+            NamespaceCacheConfig::default(),
+            catalog.time_provider(),
+            &catalog.metric_registry(),
+            test_ram_pool(),
+            &Handle::current(),
+            true,
+        );
+
+        let err = cache.get(Arc::from("ns1"), &[], None).await.unwrap_err();
+        assert!(matches!(err, Error::AllTablesBroken { n_tables: 1, .. }));
+        let err = cache
+            .get_by_id(ns1.namespace.id, &[], None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::AllTablesBroken { n_tables: 1, .. }));
+
+        // the broken namespace is cached
+        assert_catalog_access_metric_count(&catalog.metric_registry, "namespace_get_by_name", 1);
+
+        // unknown namespaces are still reported as such
+        assert!(cache
+            .get(Arc::from("ns2"), &[], None)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cached_namespace_duplicate_column() {
+        let catalog = TestCatalog::new();
+        let ns1 = catalog.create_namespace_1hr_retention("ns1").await;
+        let t1 = ns1.create_table("t1").await;
+        let t2 = ns1.create_table("t2").await;
+        let c1 = t1.create_column("tag", ColumnType::Tag).await;
+        let mut duplicate = c1.column.clone();
+        duplicate.id = ColumnId::new(i64::MAX);
+        let c2 = t2.create_column("tag", ColumnType::Tag).await;
+
+        let namespace = CachedNamespace::new(
+            ns1.namespace.clone(),
+            vec![t1.table.clone(), t2.table.clone()],
+            vec![c1.column.clone(), duplicate.clone(), c2.column.clone()],
+        );
+        assert_eq!(
+            namespace
+                .tables
+                .keys()
+                .map(|k| k.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["t2"]
+        );
+
+        assert_eq!(
+            namespace
+                .broken_tables
+                .iter()
+                .map(|k| k.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["t1"]
+        );
+        namespace.ensure_not_broken().unwrap();
+
+        // nothing left
+        let namespace = CachedNamespace::new(
+            ns1.namespace.clone(),
+            vec![t1.table.clone()],
+            vec![c1.column.clone(), duplicate],
+        );
+        let err = namespace.ensure_not_broken().unwrap_err();
+        assert!(matches!(err, Error::AllTablesBroken { n_tables: 1, .. }));
+    }
+
+    #[test]
+    fn test_column_map() {
+        let columns = (0..100)
+            .map(|i| (ColumnId::new(1_000 - i), Arc::from(format!("col_{i:03}"))))
+            .collect::<Vec<(ColumnId, Arc<str>)>>();
+
+        // layout before the column map was introduced
+        let mut old_map: HashMap<ColumnId, Arc<str>> = columns.iter().cloned().collect();
+        old_map.shrink_to_fit();
+        let mut old_map_rev: HashMap<Arc<str>, ColumnId> = old_map
+            .iter()
+            .map(|(id, name)| (Arc::clone(name), *id))
+            .collect();
+        old_map_rev.shrink_to_fit();
+        let old_size = (old_map.capacity() * size_of::<(ColumnId, Arc<str>)>())
+            + old_map.values().map(|name| name.len()).sum::<usize>()
+            + (old_map_rev.capacity() * size_of::<(Arc<str>, ColumnId)>())
+            + old_map_rev.keys().map(|name| name.len()).sum::<usize>();
+
+        let column_map: ColumnMap = columns.iter().cloned().collect();
+        assert!(
+            column_map.size() < old_size,
+            "{} >= {old_size}",
+            column_map.size()
+        );
+
+        for (id, name) in &columns {
+            assert_eq!(column_map.name_for_id(*id), old_map.get(id));
+            assert_eq!(column_map.id_for_name(name), old_map_rev.get(name).copied());
+            assert_eq!(
+                column_map.get_by_name(name),
+                old_map_rev
+                    .get_key_value(name)
+                    .map(|(name, id)| (*id, name))
+            );
+            assert!(column_map.contains_id(*id));
+        }
+        for id in [ColumnId::new(0), ColumnId::new(1_001)] {
+            assert_eq!(column_map.name_for_id(id), None);
+            assert!(!column_map.contains_id(id));
+        }
+        for name in ["", "col", "col_100", "zzz"] {
+            assert_eq!(column_map.id_for_name(name), None);
+            assert_eq!(column_map.get_by_name(name), None);
+        }
+
+        // order of the input does not matter
+        let reversed: ColumnMap = columns.iter().rev().cloned().collect();
+        assert_eq!(reversed, column_map);
+
+        let empty = ColumnMap::default();
+        assert_eq!(empty.size(), 0);
+        assert_eq!(empty.name_for_id(ColumnId::new(1)), None);
+        assert_eq!(empty.id_for_name("col_000"), None);
+    }
 }
@@ -25,7 +25,7 @@ use trace::span::Span;
 
 use super::ram::RamSize;
 
-const CACHE_ID: &str = "object_store";
+pub(crate) const CACHE_ID: &str = "object_store";
 
 async fn read_from_store(
     store: &dyn ObjectStore,
@@ -27,7 +27,7 @@ use super::ram::RamSize;
 /// This is currently `12h`.
 pub const TTL: Duration = Duration::from_secs(12 * 60 * 60);
 
-const CACHE_ID: &str = "parquet_file";
+pub(crate) const CACHE_ID: &str = "parquet_file";
 
 #[derive(Debug, Snafu)]
 #[allow(missing_copy_implementations, missing_docs)]
@@ -32,9 +32,12 @@ use std::{
 };
 use trace::span::{Span, SpanRecorder};
 
-use super::{namespace::CachedTable, ram::RamSize};
+use super::{
+    namespace::{CachedTable, ColumnMap},
+    ram::RamSize,
+};
 
-const CACHE_ID: &str = "partition";
+pub(crate) const CACHE_ID: &str = "partition";
 
 type CacheT = Box<
     dyn Cache<
@@ -253,13 +256,13 @@ impl CachedPartition {
     fn new(partition: Partition, table: &CachedTable) -> Self {
         let sort_key = partition
             .sort_key()
-            .map(|sort_key| Arc::new(PartitionSortKey::new(sort_key, &table.column_id_map_rev)));
+            .map(|sort_key| Arc::new(PartitionSortKey::new(sort_key, &table.column_map)));
 
         let mut column_ranges =
             build_column_values(&table.partition_template, partition.partition_key.inner())
                 .filter_map(|(col, val)| {
                     // resolve column name to already existing Arc for cheaper storage
-                    let col = Arc::clone(table.column_id_map_rev.get_key_value(col)?.0);
+                    let col = Arc::clone(table.column_map.get_by_name(col)?.1);
 
                     let range = match val {
                         ColumnValue::Identity(s) => {
@@ -351,15 +354,15 @@ pub struct PartitionSortKey {
 }
 
 impl PartitionSortKey {
-    fn new(sort_key: SortKey, column_id_map_rev: &HashMap<Arc<str>, ColumnId>) -> Self {
+    fn new(sort_key: SortKey, column_map: &ColumnMap) -> Self {
         let sort_key = Arc::new(sort_key);
 
         let column_order: Box<[ColumnId]> = sort_key
             .iter()
             .map(|(name, _opts)| {
-                *column_id_map_rev
-                    .get(name.as_ref())
-                    .unwrap_or_else(|| panic!("column_id_map_rev misses data: {name}"))
+                column_map
+                    .id_for_name(name.as_ref())
+                    .unwrap_or_else(|| panic!("column_map misses data: {name}"))
             })
             .collect();
 
@@ -422,14 +425,10 @@ mod tests {
         let cached_table = Arc::new(CachedTable {
             id: t.table.id,
             schema: schema(),
-            column_id_map: HashMap::from([
+            column_map: ColumnMap::from_iter([
                 (c1.column.id, Arc::from(c1.column.name.clone())),
                 (c2.column.id, Arc::from(c2.column.name.clone())),
             ]),
-            column_id_map_rev: HashMap::from([
-                (Arc::from(c1.column.name.clone()), c1.column.id),
-                (Arc::from(c2.column.name.clone()), c2.column.id),
-            ]),
             primary_key_column_ids: [c1.column.id, c2.column.id].into(),
             partition_template: TablePartitionTemplateOverride::default(),
         });
@@ -571,18 +570,12 @@ mod tests {
         let cached_table = Arc::new(CachedTable {
             id: t.table.id,
             schema: schema(),
-            column_id_map: HashMap::from([
+            column_map: ColumnMap::from_iter([
                 (c1.column.id, Arc::from(c1.column.name.clone())),
                 (c2.column.id, Arc::from(c2.column.name.clone())),
                 (c3.column.id, Arc::from(c3.column.name.clone())),
                 (c4.column.id, Arc::from(c4.column.name.clone())),
             ]),
-            column_id_map_rev: HashMap::from([
-                (Arc::from(c1.column.name.clone()), c1.column.id),
-                (Arc::from(c2.column.name.clone()), c2.column.id),
-                (Arc::from(c3.column.name.clone()), c3.column.id),
-                (Arc::from(c4.column.name.clone()), c4.column.id),
-            ]),
             primary_key_column_ids: [c1.column.id, c2.column.id, c3.column.id, c4.column.id].into(),
             partition_template: t.table.partition_template.clone(),
         });
@@ -803,14 +796,10 @@ mod tests {
         let cached_table = Arc::new(CachedTable {
             id: t.table.id,
             schema: schema(),
-            column_id_map: HashMap::from([
+            column_map: ColumnMap::from_iter([
                 (c1.column.id, Arc::from(c1.column.name.clone())),
                 (c2.column.id, Arc::from(c2.column.name.clone())),
             ]),
-            column_id_map_rev: HashMap::from([
-                (Arc::from(c1.column.name.clone()), c1.column.id),
-                (Arc::from(c2.column.name.clone()), c2.column.id),
-            ]),
             primary_key_column_ids: [c1.column.id, c2.column.id].into(),
             partition_template: TablePartitionTemplateOverride::default(),
         });
@@ -951,8 +940,7 @@ mod tests {
         let cached_table = Arc::new(CachedTable {
             id: t.table.id,
             schema: schema(),
-            column_id_map: HashMap::default(),
-            column_id_map_rev: HashMap::default(),
+            column_map: ColumnMap::default(),
             primary_key_column_ids: [].into(),
             partition_template: TablePartitionTemplateOverride::default(),
         });
@@ -1104,8 +1092,7 @@ mod tests {
             let cached_table = Arc::new(CachedTable {
                 id: t.table.id,
                 schema: schema(),
-                column_id_map: HashMap::from([(c.column.id, Arc::from(c.column.name.clone()))]),
-                column_id_map_rev: HashMap::from([(Arc::from(c.column.name.clone()), c.column.id)]),
+                column_map: ColumnMap::from_iter([(c.column.id, Arc::from(c.column.name.clone()))]),
                 primary_key_column_ids: [c.column.id].into(),
                 partition_template: TablePartitionTemplateOverride::default(),
             });
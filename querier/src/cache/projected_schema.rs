@@ -23,7 +23,7 @@ use trace::span::Span;
 
 use super::{namespace::CachedTable, ram::RamSize};
 
-const CACHE_ID: &str = "projected_schema";
+pub(crate) const CACHE_ID: &str = "projected_schema";
 
 /// Cache key.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -84,8 +84,8 @@ impl ProjectedSchemaCache {
                     .iter()
                     .map(|id| {
                         table
-                            .column_id_map
-                            .get(id)
+                            .column_map
+                            .name_for_id(*id)
                             .expect("cache table complete")
                             .as_ref()
                     })
@@ -155,9 +155,8 @@ mod tests {
     use data_types::partition_template::TablePartitionTemplateOverride;
     use iox_time::SystemProvider;
     use schema::{builder::SchemaBuilder, TIME_COLUMN_NAME};
-    use std::collections::HashMap;
 
-    use crate::cache::ram::test_util::test_ram_pool;
+    use crate::cache::{namespace::ColumnMap, ram::test_util::test_ram_pool};
 
     use super::*;
 
@@ -187,13 +186,13 @@ mod tests {
             .timestamp()
             .build()
             .unwrap();
-        let column_id_map_a = HashMap::from([
+        let column_map_a = ColumnMap::from_iter([
             (ColumnId::new(1), Arc::from("t1")),
             (ColumnId::new(2), Arc::from("t2")),
             (ColumnId::new(3), Arc::from("t3")),
             (ColumnId::new(4), Arc::from(TIME_COLUMN_NAME)),
         ]);
-        let column_id_map_b = HashMap::from([
+        let column_map_b = ColumnMap::from_iter([
             (ColumnId::new(1), Arc::from("t1")),
             (ColumnId::new(2), Arc::from("t2")),
             (ColumnId::new(3), Arc::from("t3")),
@@ -203,8 +202,7 @@ mod tests {
         let table_1a = Arc::new(CachedTable {
             id: table_id_1,
             schema: table_schema_a.clone(),
-            column_id_map: column_id_map_a.clone(),
-            column_id_map_rev: reverse_map(&column_id_map_a),
+            column_map: column_map_a.clone(),
             primary_key_column_ids: [
                 ColumnId::new(1),
                 ColumnId::new(2),
@@ -217,8 +215,7 @@ mod tests {
         let table_1b = Arc::new(CachedTable {
             id: table_id_1,
             schema: table_schema_b.clone(),
-            column_id_map: column_id_map_b.clone(),
-            column_id_map_rev: reverse_map(&column_id_map_b),
+            column_map: column_map_b.clone(),
             primary_key_column_ids: [
                 ColumnId::new(1),
                 ColumnId::new(2),
@@ -231,8 +228,7 @@ mod tests {
         let table_2a = Arc::new(CachedTable {
             id: table_id_2,
             schema: table_schema_a.clone(),
-            column_id_map: column_id_map_a.clone(),
-            column_id_map_rev: reverse_map(&column_id_map_a),
+            column_map: column_map_a.clone(),
             primary_key_column_ids: [
                 ColumnId::new(1),
                 ColumnId::new(2),
@@ -317,12 +313,4 @@ mod tests {
             .await;
         assert!(Arc::ptr_eq(projection_1.inner(), projection_7.inner()));
     }
-
-    fn reverse_map<K, V>(map: &HashMap<K, V>) -> HashMap<V, K>
-    where
-        K: Clone,
-        V: Clone + std::hash::Hash + Eq,
-    {
-        map.iter().map(|(k, v)| (v.clone(), k.clone())).collect()
-    }
 }
@@ -1,4 +1,4 @@
-use metric::{Attributes, DurationHistogram, Metric};
+use metric::{Attributes, DurationHistogram, Metric, Observation, RawReporter};
 
 #[track_caller]
 pub fn assert_catalog_access_metric_count(metrics: &metric::Registry, name: &'static str, n: u64) {
@@ -30,3 +30,27 @@ pub fn assert_cache_access_metric_count(metrics: &metric::Registry, name: &'stat
 
     assert_eq!(total, n);
 }
+
+/// Total number of successful catalog operations, regardless of the operation.
+pub fn catalog_access_total_count(metrics: &metric::Registry) -> u64 {
+    let mut reporter = RawReporter::default();
+    metrics.report(&mut reporter);
+
+    reporter
+        .metric("catalog_op_duration")
+        .map(|set| {
+            set.observations
+                .iter()
+                .filter(|(attributes, _)| {
+                    attributes
+                        .iter()
+                        .any(|(k, v)| *k == "result" && v.as_ref() == "success")
+                })
+                .map(|(_, observation)| match observation {
+                    Observation::DurationHistogram(histogram) => histogram.sample_count(),
+                    _ => 0,
+                })
+                .sum()
+        })
+        .unwrap_or_default()
+}
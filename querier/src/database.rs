@@ -1,31 +1,31 @@
 //! Database for the querier that contains all namespaces.
 
 use crate::{
-    cache::CatalogCache,
+    cache::{
+        namespace::{retention_period, ShouldCover},
+        CatalogCache,
+    },
     ingester::IngesterConnection,
     namespace::{QuerierNamespace, QuerierNamespaceArgs},
     parquet::ChunkAdapter,
-    query_log::QueryLog,
+    query_log::{QueryLog, QueryTextConfig},
+    system_tables::SystemTablePolicy,
     table::PruneMetrics,
 };
 use async_trait::async_trait;
 use backoff::{Backoff, BackoffConfig};
 use data_types::Namespace;
+use datafusion::error::DataFusionError;
 use iox_catalog::interface::SoftDeletedRows;
 use iox_query::exec::Executor;
 use service_common::QueryNamespaceProvider;
-use snafu::Snafu;
-use std::{collections::HashMap, sync::Arc};
+use snafu::{ResultExt, Snafu};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use trace::span::{Span, SpanRecorder};
 use tracker::{
     AsyncSemaphoreMetrics, InstrumentedAsyncOwnedSemaphorePermit, InstrumentedAsyncSemaphore,
 };
 
-/// The number of entries to store in the circular query buffer log.
-///
-/// That buffer is shared between all namespaces, and filtered on query
-const QUERY_LOG_SIZE: usize = 10_000;
-
 #[allow(missing_docs)]
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -33,6 +33,12 @@ pub enum Error {
     Catalog {
         source: iox_catalog::interface::Error,
     },
+
+    #[snafu(display("Cannot get namespace '{name}': {source}"))]
+    Namespace {
+        name: String,
+        source: crate::cache::namespace::Error,
+    },
 }
 
 /// Database for the querier.
@@ -70,6 +76,9 @@ pub struct QuerierDatabase {
 
     /// DataFusion config.
     datafusion_config: Arc<HashMap<String, String>>,
+
+    /// Visibility of the system tables.
+    system_table_policy: Arc<SystemTablePolicy>,
 }
 
 #[async_trait]
@@ -81,8 +90,10 @@ impl QueryNamespaceProvider for QuerierDatabase {
         name: &str,
         span: Option<Span>,
         include_debug_info_tables: bool,
-    ) -> Option<Arc<Self::Db>> {
-        self.namespace(name, span, include_debug_info_tables).await
+    ) -> Result<Option<Arc<Self::Db>>, DataFusionError> {
+        self.namespace(name, span, include_debug_info_tables)
+            .await
+            .map_err(|e| DataFusionError::External(Box::new(e)))
     }
 
     async fn acquire_semaphore(&self, span: Option<Span>) -> InstrumentedAsyncOwnedSemaphorePermit {
@@ -101,6 +112,11 @@ impl QuerierDatabase {
     /// reasonable but smaller number.
     pub const MAX_CONCURRENT_QUERIES_MAX: usize = u16::MAX as usize;
 
+    /// The default number of entries to store in the circular query buffer log.
+    ///
+    /// That buffer is shared between all namespaces, and filtered on query
+    pub const QUERY_LOG_SIZE_DEFAULT: usize = 10_000;
+
     /// Create new database.
     pub async fn new(
         catalog_cache: Arc<CatalogCache>,
@@ -108,6 +124,10 @@ impl QuerierDatabase {
         exec: Arc<Executor>,
         ingester_connection: Option<Arc<dyn IngesterConnection>>,
         max_concurrent_queries: usize,
+        query_log_size: usize,
+        query_log_max_age: Option<Duration>,
+        query_text_config: QueryTextConfig,
+        system_table_policy: SystemTablePolicy,
         datafusion_config: Arc<HashMap<String, String>>,
     ) -> Result<Self, Error> {
         assert!(
@@ -123,7 +143,15 @@ impl QuerierDatabase {
             Arc::clone(&catalog_cache),
             Arc::clone(&metric_registry),
         ));
-        let query_log = Arc::new(QueryLog::new(QUERY_LOG_SIZE, catalog_cache.time_provider()));
+        let query_log = Arc::new(
+            QueryLog::new(
+                query_log_size,
+                catalog_cache.time_provider(),
+                &metric_registry,
+            )
+            .with_max_age(query_log_max_age)
+            .with_text_config(query_text_config),
+        );
         let semaphore_metrics = Arc::new(AsyncSemaphoreMetrics::new(
             &metric_registry,
             &[("semaphore", "query_execution")],
@@ -143,6 +171,7 @@ impl QuerierDatabase {
             query_execution_semaphore,
             prune_metrics,
             datafusion_config,
+            system_table_policy: Arc::new(system_table_policy),
         })
     }
 
@@ -150,12 +179,14 @@ impl QuerierDatabase {
     ///
     /// This will await the internal namespace semaphore. Existence of namespaces is checked AFTER
     /// a semaphore permit was acquired since this lowers the chance that we obtain stale data.
+    ///
+    /// Fails if the namespace exists but cannot be used, e.g. because all its tables are broken.
     pub async fn namespace(
         &self,
         name: &str,
         span: Option<Span>,
         include_debug_info_tables: bool,
-    ) -> Option<Arc<QuerierNamespace>> {
+    ) -> Result<Option<Arc<QuerierNamespace>>, Error> {
         let span_recorder = SpanRecorder::new(span);
         let name = Arc::from(name.to_owned());
         let ns = self
@@ -167,24 +198,34 @@ impl QuerierDatabase {
                 &[],
                 span_recorder.child_span("cache GET namespace schema"),
             )
-            .await?;
-        Some(Arc::new(QuerierNamespace::new(QuerierNamespaceArgs {
-            chunk_adapter: Arc::clone(&self.chunk_adapter),
-            ns,
-            name,
-            exec: Arc::clone(&self.exec),
-            ingester_connection: self.ingester_connection.clone(),
-            query_log: Arc::clone(&self.query_log),
-            prune_metrics: Arc::clone(&self.prune_metrics),
-            datafusion_config: Arc::clone(&self.datafusion_config),
-            include_debug_info_tables,
-        })))
+            .await
+            .context(NamespaceSnafu { name: &*name })?;
+        let Some(ns) = ns else {
+            return Ok(None);
+        };
+        Ok(Some(Arc::new(QuerierNamespace::new(
+            QuerierNamespaceArgs {
+                chunk_adapter: Arc::clone(&self.chunk_adapter),
+                ns,
+                name,
+                exec: Arc::clone(&self.exec),
+                ingester_connection: self.ingester_connection.clone(),
+                query_log: Arc::clone(&self.query_log),
+                prune_metrics: Arc::clone(&self.prune_metrics),
+                datafusion_config: Arc::clone(&self.datafusion_config),
+                system_table_policy: Arc::clone(&self.system_table_policy),
+                include_debug_info_tables,
+            },
+        ))))
     }
 
     /// Return all namespaces this querier knows about
+    ///
+    /// Cached namespaces whose retention period differs from the listing are expired, so the next
+    /// query sees the new retention period.
     pub async fn namespaces(&self) -> Vec<Namespace> {
         let catalog = &self.catalog_cache.catalog();
-        Backoff::new(&self.backoff_config)
+        let namespaces = Backoff::new(&self.backoff_config)
             .retry_all_errors("listing namespaces", || async {
                 catalog
                     .repositories()
@@ -194,7 +235,17 @@ impl QuerierDatabase {
                     .await
             })
             .await
-            .expect("retry forever")
+            .expect("retry forever");
+
+        let namespace_cache = self.catalog_cache.namespace();
+        for ns in &namespaces {
+            namespace_cache.expire_outdated(
+                &Arc::from(ns.name.as_str()),
+                &[ShouldCover::RetentionPeriod(retention_period(ns))],
+            );
+        }
+
+        namespaces
     }
 
     /// Return connection to ingester(s) to get and aggregate information from them
@@ -235,6 +286,10 @@ mod tests {
             catalog.exec(),
             Some(create_ingester_connection_for_testing()),
             QuerierDatabase::MAX_CONCURRENT_QUERIES_MAX.saturating_add(1),
+            QuerierDatabase::QUERY_LOG_SIZE_DEFAULT,
+            None,
+            Default::default(),
+            Default::default(),
             Arc::new(HashMap::default()),
         )
         .await
@@ -248,8 +303,8 @@ mod tests {
 
         catalog.create_namespace_1hr_retention("ns1").await;
 
-        assert!(db.namespace("ns1", None, true).await.is_some());
-        assert!(db.namespace("ns2", None, true).await.is_none());
+        assert!(db.namespace("ns1", None, true).await.unwrap().is_some());
+        assert!(db.namespace("ns2", None, true).await.unwrap().is_none());
     }
 
     #[tokio::test]
@@ -267,6 +322,53 @@ mod tests {
         assert_eq!(namespaces[1].name, "ns2");
     }
 
+    #[tokio::test]
+    async fn test_namespaces_expire_retention_period() {
+        let catalog = TestCatalog::new();
+        let db = new_db(&catalog).await;
+
+        catalog.create_namespace_1hr_retention("ns1").await;
+        let name: Arc<str> = Arc::from("ns1");
+        let ns = db
+            .catalog_cache
+            .namespace()
+            .get(Arc::clone(&name), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ns.retention_period, Some(Duration::from_secs(3600)));
+
+        catalog
+            .catalog()
+            .repositories()
+            .await
+            .namespaces()
+            .update_retention_period("ns1", None)
+            .await
+            .unwrap();
+
+        // still cached
+        let ns = db
+            .catalog_cache
+            .namespace()
+            .get(Arc::clone(&name), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ns.retention_period, Some(Duration::from_secs(3600)));
+
+        // listing the namespaces expires the outdated entry
+        db.namespaces().await;
+        let ns = db
+            .catalog_cache
+            .namespace()
+            .get(Arc::clone(&name), &[], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ns.retention_period, None);
+    }
+
     async fn new_db(catalog: &Arc<TestCatalog>) -> QuerierDatabase {
         let catalog_cache = Arc::new(CatalogCache::new_testing(
             catalog.catalog(),
@@ -281,6 +383,10 @@ mod tests {
             catalog.exec(),
             Some(create_ingester_connection_for_testing()),
             QuerierDatabase::MAX_CONCURRENT_QUERIES_MAX,
+            QuerierDatabase::QUERY_LOG_SIZE_DEFAULT,
+            None,
+            Default::default(),
+            Default::default(),
             Arc::new(HashMap::default()),
         )
         .await
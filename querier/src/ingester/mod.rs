@@ -4,6 +4,7 @@ use self::{
         Error as FlightClientError, FlightClientImpl, FlightError, IngesterFlightClient,
     },
     invalidate_on_error::InvalidateOnErrorFlightClient,
+    status::{IngesterStatus, IngesterStatusTracker},
     test_util::MockIngesterConnection,
 };
 use crate::cache::{namespace::CachedTable, CatalogCache};
@@ -13,6 +14,7 @@ use async_trait::async_trait;
 use backoff::{Backoff, BackoffConfig, BackoffError};
 use client_util::connection;
 use data_types::{
+    partition_template::{NamespacePartitionTemplateOverride, TablePartitionTemplateOverride},
     ChunkId, ChunkOrder, NamespaceId, PartitionHashId, PartitionId, TransitionPartitionId,
 };
 use datafusion::{physical_plan::Statistics, prelude::Expr};
@@ -33,6 +35,7 @@ use iox_time::{Time, TimeProvider};
 use metric::{DurationHistogram, Metric};
 use observability_deps::tracing::{debug, trace, warn};
 use predicate::Predicate;
+use prost::Message;
 use schema::{sort::SortKey, Schema};
 use snafu::{ensure, OptionExt, ResultExt, Snafu};
 use std::{
@@ -47,6 +50,7 @@ use uuid::Uuid;
 mod circuit_breaker;
 pub(crate) mod flight_client;
 mod invalidate_on_error;
+pub(crate) mod status;
 pub(crate) mod test_util;
 
 #[derive(Debug, Snafu)]
@@ -139,6 +143,14 @@ pub enum Error {
         at least one of these is required."
     ))]
     NoPartitionIdentifier,
+
+    #[snafu(display("Could not decode partition template: {source}"))]
+    DecodePartitionTemplate { source: prost::DecodeError },
+
+    #[snafu(display("Invalid partition template: {source}"))]
+    InvalidPartitionTemplate {
+        source: data_types::partition_template::ValidationError,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -195,6 +207,9 @@ pub trait IngesterConnection: std::fmt::Debug + Send + Sync + 'static {
         span: Option<Span>,
     ) -> Result<Vec<IngesterPartition>>;
 
+    /// Returns the last known connectivity status of the ingester(s).
+    fn status(&self) -> Vec<IngesterStatus>;
+
     /// Return backend as [`Any`] which can be used to downcast to a specific implementation.
     fn as_any(&self) -> &dyn Any;
 }
@@ -334,6 +349,7 @@ pub struct IngesterConnectionImpl {
     time_provider: Arc<dyn TimeProvider>,
     metrics: Arc<IngesterConnectionMetrics>,
     backoff_config: BackoffConfig,
+    status: Arc<IngesterStatusTracker>,
 }
 
 impl IngesterConnectionImpl {
@@ -376,6 +392,10 @@ impl IngesterConnectionImpl {
     ) -> Self {
         let metric_registry = catalog_cache.metric_registry();
         let metrics = Arc::new(IngesterConnectionMetrics::new(&metric_registry));
+        let status = Arc::new(IngesterStatusTracker::new(
+            ingester_addresses.clone(),
+            metric_registry,
+        ));
 
         Self {
             unique_ingester_addresses: ingester_addresses.into_iter().collect(),
@@ -383,6 +403,7 @@ impl IngesterConnectionImpl {
             time_provider: catalog_cache.time_provider(),
             metrics,
             backoff_config,
+            status,
         }
     }
 }
@@ -397,6 +418,7 @@ struct GetPartitionForIngester<'a> {
     columns: Vec<String>,
     filters: &'a [Expr],
     cached_table: Arc<CachedTable>,
+    status: Arc<IngesterStatusTracker>,
 }
 
 /// Fetches the partitions for a single ingester
@@ -406,12 +428,13 @@ async fn execute(
 ) -> Result<Vec<IngesterPartition>> {
     let GetPartitionForIngester {
         flight_client,
-        time_provider: _,
+        time_provider,
         ingester_address,
         namespace_id,
         columns,
         filters,
         cached_table,
+        status,
     } = request;
 
     let ingester_query_request = IngesterQueryRequest {
@@ -451,6 +474,7 @@ async fn execute(
                 table_id = cached_table.id.get(),
                 "Ingester does not know namespace or table, skipping",
             );
+            status.record_success(&ingester_address, time_provider.now());
             return Ok(vec![]);
         }
         _ => {}
@@ -472,6 +496,7 @@ async fn execute(
                 filters_binary=encode_filters_as_base64(filters).as_str(),
                 "Failed to perform ingester query",
             );
+            status.record_error(&ingester_address, time_provider.now(), &e);
 
             //  need to return error until https://github.com/rust-lang/rust/issues/91345 is stable
             e
@@ -487,10 +512,15 @@ async fn execute(
         .map_err(|source| FlightClientError::Flight { source })
         .context(RemoteQuerySnafu {
             ingester_address: ingester_address.as_ref(),
+        })
+        .map_err(|e| {
+            status.record_error(&ingester_address, time_provider.now(), &e);
+            e
         })?
     {
         messages.push(data);
     }
+    status.record_success(&ingester_address, time_provider.now());
 
     // reconstruct partitions
     let mut decoder = IngesterStreamDecoder::new(
@@ -595,11 +625,15 @@ impl IngesterStreamDecoder {
                         ingester_uuid: md.ingester_uuid,
                     })?;
 
-                let partition = IngesterPartition::new(
+                let mut partition = IngesterPartition::new(
                     ingester_uuid,
                     partition_id,
                     md.completed_persistence_count,
                 );
+                if let Some(partition_template) = md.partition_template {
+                    partition = partition
+                        .with_partition_template(decode_partition_template(&partition_template)?);
+                }
                 self.current_partition = Some(partition);
             }
             DecodedPayload::Schema(schema) => {
@@ -650,6 +684,22 @@ impl IngesterStreamDecoder {
     }
 }
 
+/// Decode the partition template sent by an ingester, which is empty for the default template.
+fn decode_partition_template(bytes: &[u8]) -> Result<TablePartitionTemplateOverride, Error> {
+    if bytes.is_empty() {
+        return Ok(TablePartitionTemplateOverride::default());
+    }
+
+    let proto =
+        generated_types::influxdata::iox::partition_template::v1::PartitionTemplate::decode(bytes)
+            .context(DecodePartitionTemplateSnafu)?;
+    TablePartitionTemplateOverride::try_new(
+        Some(proto),
+        &NamespacePartitionTemplateOverride::const_default(),
+    )
+    .context(InvalidPartitionTemplateSnafu)
+}
+
 fn encode_filters_as_base64(filters: &[Expr]) -> String {
     use ingester_query_grpc::influxdata::iox::ingester::v1::Predicate as ProtoPredicate;
 
@@ -692,6 +742,7 @@ impl IngesterConnection for IngesterConnectionImpl {
                 cached_table: Arc::clone(&cached_table),
                 columns: columns.clone(),
                 filters,
+                status: Arc::clone(&self.status),
             };
 
             let backoff_config = self.backoff_config.clone();
@@ -759,6 +810,10 @@ impl IngesterConnection for IngesterConnectionImpl {
         Ok(ingester_partitions)
     }
 
+    fn status(&self) -> Vec<IngesterStatus> {
+        self.status.status()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self as &dyn Any
     }
@@ -789,6 +844,9 @@ pub struct IngesterPartition {
     /// The number of Parquet files this ingester UUID has persisted for this partition.
     completed_persistence_count: u64,
 
+    /// The partition template of the table that the ingester used, if it sent one.
+    partition_template: Option<TablePartitionTemplateOverride>,
+
     chunks: Vec<IngesterChunk>,
 }
 
@@ -804,10 +862,22 @@ impl IngesterPartition {
             ingester_uuid,
             partition_id,
             completed_persistence_count,
+            partition_template: None,
             chunks: vec![],
         }
     }
 
+    /// Set the partition template of the table that the ingester used.
+    pub(crate) fn with_partition_template(
+        self,
+        partition_template: TablePartitionTemplateOverride,
+    ) -> Self {
+        Self {
+            partition_template: Some(partition_template),
+            ..self
+        }
+    }
+
     /// Try to add a new chunk to this partition.
     pub(crate) fn try_add_chunk(
         mut self,
@@ -877,6 +947,10 @@ impl IngesterPartition {
         self.completed_persistence_count
     }
 
+    pub(crate) fn partition_template(&self) -> Option<&TablePartitionTemplateOverride> {
+        self.partition_template.as_ref()
+    }
+
     pub(crate) fn chunks(&self) -> &[IngesterChunk] {
         &self.chunks
     }
@@ -1038,13 +1112,17 @@ mod tests {
         datatypes::Int32Type,
     };
     use assert_matches::assert_matches;
-    use data_types::{PartitionKey, TableId};
+    use data_types::{
+        partition_template::{test_table_partition_override, TemplatePart},
+        PartitionKey, TableId,
+    };
     use ingester_query_grpc::influxdata::iox::ingester::v1::IngesterQueryResponseMetadata;
     use iox_tests::TestCatalog;
     use metric::Attributes;
     use mutable_batch_lp::test_helpers::lp_to_mutable_batch;
     use schema::{builder::SchemaBuilder, InfluxFieldType, Projection};
     use std::collections::{BTreeSet, HashMap};
+    use test_helpers::assert_contains;
     use tokio::{runtime::Handle, sync::Mutex};
     use trace::{ctx::SpanContext, span::SpanStatus, RingBufferTraceCollector};
 
@@ -1114,6 +1192,45 @@ mod tests {
         assert_matches!(err, Error::RemoteQuery { .. });
     }
 
+    #[tokio::test]
+    async fn test_flight_status() {
+        // successful request
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new([("addr1", Ok(MockQueryData { results: vec![] }))]).await,
+        );
+        let ingester_conn = mock_flight_client.ingester_conn().await;
+
+        // nothing is known before the first request
+        let status = ingester_conn.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].address.as_ref(), "addr1");
+        assert_eq!(status[0].last_success, None);
+        assert_eq!(status[0].last_error, None);
+
+        get_partitions(&ingester_conn).await.unwrap();
+        let status = ingester_conn.status();
+        assert!(status[0].last_success.is_some());
+        assert_eq!(status[0].last_error, None);
+
+        // failed request
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new([(
+                "addr1",
+                Err(FlightClientError::Flight {
+                    source: tonic::Status::internal("cow exploded").into(),
+                }),
+            )])
+            .await,
+        );
+        let ingester_conn = mock_flight_client.ingester_conn().await;
+        get_partitions(&ingester_conn).await.unwrap_err();
+
+        let status = ingester_conn.status();
+        assert_eq!(status[0].last_success, None);
+        assert!(status[0].last_error_time.is_some());
+        assert_contains!(status[0].last_error.as_deref().unwrap(), "cow exploded");
+    }
+
     #[tokio::test]
     async fn test_flight_no_partitions() {
         let mock_flight_client = Arc::new(
@@ -1163,6 +1280,7 @@ mod tests {
                             partition_identifier: Some(PartitionIdentifier::CatalogId(1)),
                             ingester_uuid: ingester_uuid.to_string(),
                             completed_persistence_count: 5,
+                            partition_template: None,
                         },
                     ))],
                 }),
@@ -1184,6 +1302,72 @@ mod tests {
         assert_eq!(p.completed_persistence_count, 5);
     }
 
+    #[tokio::test]
+    async fn test_flight_partition_template() {
+        let ingester_uuid = Uuid::new_v4();
+        let partition_template =
+            test_table_partition_override(vec![TemplatePart::TagValue("region")]);
+        let with_template = |table_id, partition_template: Option<Vec<u8>>| {
+            let (payload, md) = metadata(table_id, ingester_uuid.to_string(), 5).unwrap();
+            Ok((
+                payload,
+                IngesterQueryResponseMetadata {
+                    partition_template,
+                    ..md
+                },
+            ))
+        };
+
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new([(
+                "addr1",
+                Ok(MockQueryData {
+                    results: vec![
+                        metadata(1, ingester_uuid.to_string(), 5),
+                        with_template(2, Some(vec![])),
+                        with_template(
+                            3,
+                            Some(partition_template.as_proto().unwrap().encode_to_vec()),
+                        ),
+                    ],
+                }),
+            )])
+            .await,
+        );
+        let ingester_conn = mock_flight_client.ingester_conn().await;
+
+        let partitions = get_partitions(&ingester_conn).await.unwrap();
+        let templates = partitions
+            .iter()
+            .map(|p| (p.partition_id(), p.partition_template().cloned()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            templates,
+            HashMap::from([
+                (partition_id(1), None),
+                (
+                    partition_id(2),
+                    Some(TablePartitionTemplateOverride::default())
+                ),
+                (partition_id(3), Some(partition_template)),
+            ])
+        );
+
+        // an invalid template fails the query
+        let mock_flight_client = Arc::new(
+            MockFlightClient::new([(
+                "addr1",
+                Ok(MockQueryData {
+                    results: vec![with_template(1, Some(vec![0xff]))],
+                }),
+            )])
+            .await,
+        );
+        let ingester_conn = mock_flight_client.ingester_conn().await;
+        let err = get_partitions(&ingester_conn).await.unwrap_err();
+        assert_matches!(err, Error::DecodePartitionTemplate { .. });
+    }
+
     #[tokio::test]
     async fn test_flight_err_duplicate_partition_info() {
         let ingester_uuid = Uuid::new_v4();
@@ -1392,6 +1576,7 @@ mod tests {
                             )),
                             ingester_uuid: ingester_uuid.to_string(),
                             completed_persistence_count: 5,
+                            partition_template: None,
                         },
                     ))],
                 }),
@@ -1598,6 +1783,7 @@ mod tests {
                 )),
                 ingester_uuid: ingester_uuid.into(),
                 completed_persistence_count,
+                partition_template: None,
             },
         ))
     }
@@ -1782,8 +1968,7 @@ mod tests {
         Arc::new(CachedTable {
             id: TableId::new(2),
             schema: schema(),
-            column_id_map: Default::default(),
-            column_id_map_rev: Default::default(),
+            column_map: Default::default(),
             primary_key_column_ids: Default::default(),
             partition_template: Default::default(),
         })
@@ -0,0 +1,197 @@
+//! Tracking of the last responses of the ingesters.
+use std::{borrow::Cow, collections::HashMap, fmt::Display, sync::Arc};
+
+use iox_time::Time;
+use metric::{Attributes, Metric, U64Gauge};
+use parking_lot::Mutex;
+
+/// Connectivity status of a single ingester, see [`IngesterConnection::status`].
+///
+/// [`IngesterConnection::status`]: super::IngesterConnection::status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IngesterStatus {
+    /// Address of the ingester.
+    pub address: Arc<str>,
+
+    /// Time of the last successful response, if any.
+    pub last_success: Option<Time>,
+
+    /// Time of the last error, if any.
+    pub last_error_time: Option<Time>,
+
+    /// The last error, if any.
+    pub last_error: Option<String>,
+
+    /// State of the circuit breaker for this ingester (`closed`, `open` or `half_open`), if known.
+    pub circuit_state: Option<&'static str>,
+}
+
+/// The circuit states reported by the `ingester_circuit_state` metric.
+const CIRCUIT_STATES: [&str; 3] = ["closed", "open", "half_open"];
+
+#[derive(Debug, Default)]
+struct Responses {
+    last_success: Option<Time>,
+    last_error: Option<(Time, String)>,
+}
+
+/// Records the last responses of the ingesters.
+#[derive(Debug)]
+pub(crate) struct IngesterStatusTracker {
+    addresses: Vec<Arc<str>>,
+    metric_registry: Arc<metric::Registry>,
+    responses: Mutex<HashMap<Arc<str>, Responses>>,
+}
+
+impl IngesterStatusTracker {
+    /// Create tracker for the given ingesters.
+    ///
+    /// The circuit state is read from the `ingester_circuit_state` metric in `metric_registry`.
+    pub(crate) fn new(
+        mut addresses: Vec<Arc<str>>,
+        metric_registry: Arc<metric::Registry>,
+    ) -> Self {
+        addresses.sort();
+        addresses.dedup();
+
+        Self {
+            addresses,
+            metric_registry,
+            responses: Default::default(),
+        }
+    }
+
+    /// Record a successful response of the given ingester.
+    pub(crate) fn record_success(&self, address: &Arc<str>, now: Time) {
+        self.responses
+            .lock()
+            .entry(Arc::clone(address))
+            .or_default()
+            .last_success = Some(now);
+    }
+
+    /// Record an error talking to the given ingester.
+    pub(crate) fn record_error(&self, address: &Arc<str>, now: Time, error: &dyn Display) {
+        self.responses
+            .lock()
+            .entry(Arc::clone(address))
+            .or_default()
+            .last_error = Some((now, error.to_string()));
+    }
+
+    /// Status of all ingesters, ordered by address.
+    pub(crate) fn status(&self) -> Vec<IngesterStatus> {
+        let responses = self.responses.lock();
+
+        self.addresses
+            .iter()
+            .map(|address| {
+                let (last_success, last_error) = match responses.get(address) {
+                    Some(r) => (r.last_success, r.last_error.clone()),
+                    None => (None, None),
+                };
+                let (last_error_time, last_error) = match last_error {
+                    Some((time, error)) => (Some(time), Some(error)),
+                    None => (None, None),
+                };
+
+                IngesterStatus {
+                    address: Arc::clone(address),
+                    last_success,
+                    last_error_time,
+                    last_error,
+                    circuit_state: self.circuit_state(address),
+                }
+            })
+            .collect()
+    }
+
+    fn circuit_state(&self, address: &str) -> Option<&'static str> {
+        let metric = self
+            .metric_registry
+            .get_instrument::<Metric<U64Gauge>>("ingester_circuit_state")?;
+
+        CIRCUIT_STATES.into_iter().find(|state| {
+            let attributes = Attributes::from([
+                ("ingester", Cow::Owned(address.to_owned())),
+                ("state", Cow::Borrowed(*state)),
+            ]);
+            metric
+                .get_observer(&attributes)
+                .map(|gauge| gauge.fetch() == 1)
+                .unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let metric_registry = Arc::new(metric::Registry::new());
+        let addr1: Arc<str> = Arc::from("http://ingester-1:8082");
+        let addr2: Arc<str> = Arc::from("http://ingester-2:8082");
+        let tracker = IngesterStatusTracker::new(
+            vec![Arc::clone(&addr2), Arc::clone(&addr1), Arc::clone(&addr2)],
+            Arc::clone(&metric_registry),
+        );
+
+        // nothing known yet
+        assert_eq!(
+            tracker.status(),
+            vec![
+                IngesterStatus {
+                    address: Arc::clone(&addr1),
+                    last_success: None,
+                    last_error_time: None,
+                    last_error: None,
+                    circuit_state: None,
+                },
+                IngesterStatus {
+                    address: Arc::clone(&addr2),
+                    last_success: None,
+                    last_error_time: None,
+                    last_error: None,
+                    circuit_state: None,
+                },
+            ]
+        );
+
+        tracker.record_success(&addr1, Time::from_timestamp_nanos(1));
+        tracker.record_error(&addr2, Time::from_timestamp_nanos(2), &"connection refused");
+        tracker.record_success(&addr1, Time::from_timestamp_nanos(3));
+
+        let circuit_state: Metric<U64Gauge> =
+            metric_registry.register_metric("ingester_circuit_state", "circuit state");
+        for (state, value) in [("closed", 0), ("open", 1), ("half_open", 0)] {
+            circuit_state
+                .recorder([
+                    ("ingester", Cow::Owned(addr2.to_string())),
+                    ("state", Cow::Borrowed(state)),
+                ])
+                .set(value);
+        }
+
+        assert_eq!(
+            tracker.status(),
+            vec![
+                IngesterStatus {
+                    address: Arc::clone(&addr1),
+                    last_success: Some(Time::from_timestamp_nanos(3)),
+                    last_error_time: None,
+                    last_error: None,
+                    circuit_state: None,
+                },
+                IngesterStatus {
+                    address: Arc::clone(&addr2),
+                    last_success: None,
+                    last_error_time: Some(Time::from_timestamp_nanos(2)),
+                    last_error: Some("connection refused".to_owned()),
+                    circuit_state: Some("open"),
+                },
+            ]
+        );
+    }
+}
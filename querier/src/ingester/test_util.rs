@@ -1,4 +1,4 @@
-use super::IngesterConnection;
+use super::{status::IngesterStatus, IngesterConnection};
 use crate::cache::namespace::CachedTable;
 use async_trait::async_trait;
 use data_types::NamespaceId;
@@ -12,6 +12,7 @@ use trace::span::Span;
 #[derive(Debug, Default)]
 pub struct MockIngesterConnection {
     next_response: Mutex<Option<super::Result<Vec<super::IngesterPartition>>>>,
+    status: Mutex<Vec<IngesterStatus>>,
 }
 
 impl MockIngesterConnection {
@@ -25,6 +26,12 @@ impl MockIngesterConnection {
     pub fn next_response(&self, response: super::Result<Vec<super::IngesterPartition>>) {
         *self.next_response.lock() = Some(response);
     }
+
+    /// Set the status reported by this connection.
+    #[cfg(test)]
+    pub fn set_status(&self, status: Vec<IngesterStatus>) {
+        *self.status.lock() = status;
+    }
 }
 
 #[async_trait]
@@ -88,6 +95,10 @@ impl IngesterConnection for MockIngesterConnection {
         Ok(partitions)
     }
 
+    fn status(&self) -> Vec<IngesterStatus> {
+        self.status.lock().clone()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self as &dyn Any
     }
@@ -31,7 +31,7 @@ mod table;
 /// This is mostly to fetch per-partition data concurrently.
 const CONCURRENT_CHUNK_CREATION_JOBS: usize = 100;
 
-pub use cache::CatalogCache as QuerierCatalogCache;
+pub use cache::{namespace::NamespaceCacheConfig, CatalogCache as QuerierCatalogCache};
 pub use database::{Error as QuerierDatabaseError, QuerierDatabase};
 pub use ingester::{
     create_ingester_connection_for_testing, create_ingester_connections,
@@ -39,7 +39,10 @@ pub use ingester::{
         Error as IngesterFlightClientError, IngesterFlightClient,
         QueryData as IngesterFlightClientQueryData,
     },
+    status::IngesterStatus,
     Error as IngesterError, IngesterConnection, IngesterConnectionImpl, IngesterPartition,
 };
 pub use namespace::QuerierNamespace;
+pub use query_log::QueryTextConfig;
 pub use server::QuerierServer;
+pub use system_tables::{SystemTablePolicy, SystemTableVisibility};
@@ -5,6 +5,7 @@ use crate::{
     ingester::IngesterConnection,
     parquet::ChunkAdapter,
     query_log::QueryLog,
+    system_tables::SystemTablePolicy,
     table::{PruneMetrics, QuerierTable, QuerierTableArgs},
 };
 use data_types::NamespaceId;
@@ -27,6 +28,7 @@ pub struct QuerierNamespaceArgs {
     pub query_log: Arc<QueryLog>,
     pub prune_metrics: Arc<PruneMetrics>,
     pub datafusion_config: Arc<HashMap<String, String>>,
+    pub system_table_policy: Arc<SystemTablePolicy>,
     pub include_debug_info_tables: bool,
 }
 
@@ -59,9 +61,15 @@ pub struct QuerierNamespace {
     /// Query log.
     query_log: Arc<QueryLog>,
 
+    /// Connection to ingester(s).
+    ingester_connection: Option<Arc<dyn IngesterConnection>>,
+
     /// DataFusion config.
     datafusion_config: Arc<HashMap<String, String>>,
 
+    /// Visibility of the system tables.
+    system_table_policy: Arc<SystemTablePolicy>,
+
     /// Include debug info tables.
     include_debug_info_tables: bool,
 
@@ -81,6 +89,7 @@ impl QuerierNamespace {
             query_log,
             prune_metrics,
             datafusion_config,
+            system_table_policy,
             include_debug_info_tables,
         } = args;
 
@@ -113,7 +122,9 @@ impl QuerierNamespace {
             exec,
             catalog_cache: Arc::clone(chunk_adapter.catalog_cache()),
             query_log,
+            ingester_connection,
             datafusion_config,
+            system_table_policy,
             include_debug_info_tables,
             retention_period: ns.retention_period,
         }
@@ -129,8 +140,8 @@ impl QuerierNamespace {
         ingester_connection: Option<Arc<dyn IngesterConnection>>,
     ) -> Self {
         let time_provider = catalog_cache.time_provider();
+        let query_log = Arc::new(QueryLog::new(10, time_provider, &metric_registry));
         let chunk_adapter = Arc::new(ChunkAdapter::new(catalog_cache, metric_registry));
-        let query_log = Arc::new(QueryLog::new(10, time_provider));
         let prune_metrics = Arc::new(PruneMetrics::new(&chunk_adapter.metric_registry()));
 
         Self::new(QuerierNamespaceArgs {
@@ -142,6 +153,7 @@ impl QuerierNamespace {
             query_log,
             prune_metrics,
             datafusion_config: Default::default(),
+            system_table_policy: Default::default(),
             include_debug_info_tables: true,
         })
     }
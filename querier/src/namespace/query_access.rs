@@ -1,9 +1,11 @@
 //! This module contains implementations of [`iox_query`] interfaces for [QuerierNamespace].
 
 use crate::{
+    cache::introspection::CacheIntrospection,
+    ingester::IngesterConnection,
     namespace::QuerierNamespace,
     query_log::QueryLog,
-    system_tables::{SystemSchemaProvider, SYSTEM_SCHEMA},
+    system_tables::{SystemSchemaProvider, SystemTablePolicy, SYSTEM_SCHEMA},
     table::QuerierTable,
 };
 use async_trait::async_trait;
@@ -15,6 +17,7 @@ use datafusion::{
     prelude::Expr,
 };
 use datafusion_util::config::DEFAULT_SCHEMA;
+use iox_catalog::interface::Catalog;
 use iox_query::{
     exec::{ExecutorType, IOxSessionContext},
     QueryChunk, QueryCompletedToken, QueryNamespace, QueryText,
@@ -67,11 +70,18 @@ impl QueryNamespace for QuerierNamespace {
         query_text: QueryText,
     ) -> QueryCompletedToken {
         // When the query token is dropped the query entry's completion time
-        // will be set.
+        // will be set. The plan duration is set once the query is planned.
         let query_log = Arc::clone(&self.query_log);
         let trace_id = ctx.span().map(|s| s.ctx.trace_id);
         let entry = query_log.push(self.id, query_type, query_text, trace_id);
-        QueryCompletedToken::new(move |success| query_log.set_completed(entry, success))
+
+        let planned_log = Arc::clone(&query_log);
+        let planned_entry = Arc::clone(&entry);
+        QueryCompletedToken::new_with_completion(move |completion| match completion.error {
+            Some(error) => query_log.set_failed(entry, error),
+            None => query_log.set_completed(entry, completion.success),
+        })
+        .with_planned(move || planned_log.set_planned(&planned_entry))
     }
 
     fn new_query_context(&self, span_ctx: Option<SpanContext>) -> IOxSessionContext {
@@ -93,12 +103,30 @@ pub struct QuerierCatalogProvider {
     /// Namespace ID.
     namespace_id: NamespaceId,
 
+    /// Namespace name.
+    namespace_name: Arc<str>,
+
+    /// Catalog.
+    catalog: Arc<dyn Catalog>,
+
+    /// Introspection of the catalog cache.
+    cache_introspection: CacheIntrospection,
+
+    /// Metric registry.
+    metric_registry: Arc<metric::Registry>,
+
     /// A snapshot of all tables.
     tables: Arc<HashMap<Arc<str>, Arc<QuerierTable>>>,
 
     /// Query log.
     query_log: Arc<QueryLog>,
 
+    /// Connection to ingester(s).
+    ingester_connection: Option<Arc<dyn IngesterConnection>>,
+
+    /// Visibility of the system tables.
+    system_table_policy: Arc<SystemTablePolicy>,
+
     /// Include debug info tables.
     include_debug_info_tables: bool,
 }
@@ -107,8 +135,14 @@ impl QuerierCatalogProvider {
     fn from_namespace(namespace: &QuerierNamespace) -> Self {
         Self {
             namespace_id: namespace.id,
+            namespace_name: Arc::clone(&namespace.name),
+            catalog: namespace.catalog_cache.catalog(),
+            cache_introspection: namespace.catalog_cache.introspection(),
+            metric_registry: namespace.catalog_cache.metric_registry(),
             tables: Arc::clone(&namespace.tables),
             query_log: Arc::clone(&namespace.query_log),
+            ingester_connection: namespace.ingester_connection.clone(),
+            system_table_policy: Arc::clone(&namespace.system_table_policy),
             include_debug_info_tables: namespace.include_debug_info_tables,
         }
     }
@@ -128,9 +162,25 @@ impl CatalogProvider for QuerierCatalogProvider {
             DEFAULT_SCHEMA => Some(Arc::new(UserSchemaProvider {
                 tables: Arc::clone(&self.tables),
             })),
+            SYSTEM_SCHEMA
+                if self
+                    .system_table_policy
+                    .is_admin_namespace(&self.namespace_name) =>
+            {
+                Some(Arc::new(SystemSchemaProvider::new_admin(
+                    Arc::clone(&self.query_log),
+                    &self.system_table_policy,
+                    self.include_debug_info_tables,
+                )))
+            }
             SYSTEM_SCHEMA => Some(Arc::new(SystemSchemaProvider::new(
+                Arc::clone(&self.catalog),
+                self.cache_introspection.clone(),
+                Arc::clone(&self.metric_registry),
                 Arc::clone(&self.query_log),
+                self.ingester_connection.clone(),
                 self.namespace_id,
+                &self.system_table_policy,
                 self.include_debug_info_tables,
             ))),
             _ => None,
@@ -171,7 +221,7 @@ mod tests {
     use crate::namespace::test_util::{clear_parquet_cache, querier_namespace};
     use arrow::record_batch::RecordBatch;
     use arrow_util::test_util::{batches_to_sorted_lines, Normalizer};
-    use data_types::ColumnType;
+    use data_types::{ColumnType, CompactionLevel};
     use datafusion::common::DataFusionError;
     use iox_query::frontend::sql::SqlQueryPlanner;
     use iox_tests::{TestCatalog, TestParquetFileBuilder};
@@ -529,6 +579,399 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_system_parquet_files() {
+        test_helpers::maybe_start_logging();
+
+        let catalog = TestCatalog::new();
+
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table_cpu = ns.create_table("cpu").await;
+        let table_mem = ns.create_table("mem").await;
+        table_cpu.create_column("host", ColumnType::Tag).await;
+        table_cpu.create_column("time", ColumnType::Time).await;
+        table_cpu.create_column("load", ColumnType::F64).await;
+        table_mem.create_column("host", ColumnType::Tag).await;
+        table_mem.create_column("time", ColumnType::Time).await;
+        table_mem.create_column("perc", ColumnType::F64).await;
+
+        let partition_cpu = table_cpu.create_partition("a").await;
+        let partition_mem = table_mem.create_partition("c").await;
+
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol("cpu,host=a load=1 11")
+            .with_min_time(11)
+            .with_max_time(11);
+        partition_cpu.create_parquet_file(builder).await;
+
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol("cpu,host=a load=2 22")
+            .with_min_time(22)
+            .with_max_time(22);
+        partition_cpu
+            .create_parquet_file(builder)
+            .await
+            .flag_for_delete() // not listed because of soft delete
+            .await;
+
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol("mem,host=c perc=50 11\nmem,host=c perc=51 12")
+            .with_min_time(11)
+            .with_max_time(12)
+            .with_compaction_level(CompactionLevel::FileNonOverlapped);
+        partition_mem.create_parquet_file(builder).await;
+
+        // files of other namespaces are not listed
+        let other_ns = catalog.create_namespace_with_retention("other", None).await;
+        let other_table = other_ns.create_table("cpu").await;
+        other_table.create_column("host", ColumnType::Tag).await;
+        other_table.create_column("time", ColumnType::Time).await;
+        other_table.create_column("load", ColumnType::F64).await;
+        let builder = TestParquetFileBuilder::default()
+            .with_line_protocol("cpu,host=a load=1 11")
+            .with_min_time(11)
+            .with_max_time(11);
+        other_table
+            .create_partition("a")
+            .await
+            .create_parquet_file(builder)
+            .await;
+
+        let querier_namespace = Arc::new(querier_namespace(&ns).await);
+
+        insta::assert_yaml_snapshot!(
+            format_query(
+                &querier_namespace,
+                "SELECT id, table_id, compaction_level, min_time, max_time, row_count, to_delete FROM system.parquet_files",
+            ).await,
+            @r###"
+        ---
+        - +----+----------+------------------+--------------------------------+--------------------------------+-----------+-----------+
+        - "| id | table_id | compaction_level | min_time                       | max_time                       | row_count | to_delete |"
+        - +----+----------+------------------+--------------------------------+--------------------------------+-----------+-----------+
+        - "| 1  | 1        | 0                | 1970-01-01T00:00:00.000000011Z | 1970-01-01T00:00:00.000000011Z | 1         |           |"
+        - "| 3  | 2        | 2                | 1970-01-01T00:00:00.000000011Z | 1970-01-01T00:00:00.000000012Z | 2         |           |"
+        - +----+----------+------------------+--------------------------------+--------------------------------+-----------+-----------+
+        "###
+        );
+
+        // every file of the namespace is listed with its partition
+        let results = run(
+            &querier_namespace,
+            "SELECT partition_id FROM system.parquet_files ORDER BY id",
+            None,
+        )
+        .await;
+        let expected = [
+            partition_cpu.partition.transition_partition_id(),
+            partition_mem.partition.transition_partition_id(),
+        ]
+        .iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>();
+        let partition_ids = results
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<arrow::array::StringArray>()
+                    .unwrap()
+                    .iter()
+                    .map(|id| id.unwrap().to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(partition_ids, expected);
+    }
+
+    #[tokio::test]
+    async fn test_system_compactions() {
+        test_helpers::maybe_start_logging();
+
+        let catalog = TestCatalog::new();
+
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let table_cpu = ns.create_table("cpu").await;
+        let table_mem = ns.create_table("mem").await;
+        table_cpu.create_column("host", ColumnType::Tag).await;
+        table_cpu.create_column("time", ColumnType::Time).await;
+        table_cpu.create_column("load", ColumnType::F64).await;
+        table_mem.create_column("host", ColumnType::Tag).await;
+        table_mem.create_column("time", ColumnType::Time).await;
+        table_mem.create_column("perc", ColumnType::F64).await;
+
+        let partition_cpu_a = table_cpu.create_partition("a").await;
+        let partition_cpu_b = table_cpu.create_partition("b").await;
+        let partition_mem = table_mem.create_partition("c").await;
+
+        let secs = |s: i64| Time::from_timestamp_nanos(s * 1_000_000_000);
+        let file = |lp: &str, level, size, created_at, max_l0_created_at| {
+            TestParquetFileBuilder::default()
+                .with_line_protocol(lp)
+                .with_compaction_level(level)
+                .with_file_size_bytes(size)
+                .with_creation_time(secs(created_at))
+                .with_max_l0_created_at(secs(max_l0_created_at))
+        };
+
+        // two L0 files in different partitions, and one L1 file for `cpu`
+        partition_cpu_a
+            .create_parquet_file(file(
+                "cpu,host=a load=1 11",
+                CompactionLevel::Initial,
+                100,
+                10,
+                10,
+            ))
+            .await;
+        partition_cpu_b
+            .create_parquet_file(file(
+                "cpu,host=b load=1 11\ncpu,host=b load=2 12",
+                CompactionLevel::Initial,
+                200,
+                20,
+                20,
+            ))
+            .await;
+        partition_cpu_a
+            .create_parquet_file(file(
+                "cpu,host=a load=3 13",
+                CompactionLevel::FileNonOverlapped,
+                1000,
+                30,
+                20,
+            ))
+            .await;
+
+        // soft-deleted files are not counted
+        partition_cpu_a
+            .create_parquet_file(file(
+                "cpu,host=a load=4 14",
+                CompactionLevel::Initial,
+                10_000,
+                40,
+                5,
+            ))
+            .await
+            .flag_for_delete()
+            .await;
+
+        // one L2 file for `mem`
+        partition_mem
+            .create_parquet_file(file(
+                "mem,host=c perc=50 11\nmem,host=c perc=51 12",
+                CompactionLevel::Final,
+                50,
+                40,
+                15,
+            ))
+            .await;
+
+        // files of other namespaces are not counted
+        let other_ns = catalog.create_namespace_with_retention("other", None).await;
+        let other_table = other_ns.create_table("cpu").await;
+        other_table.create_column("host", ColumnType::Tag).await;
+        other_table.create_column("time", ColumnType::Time).await;
+        other_table.create_column("load", ColumnType::F64).await;
+        other_table
+            .create_partition("a")
+            .await
+            .create_parquet_file(file(
+                "cpu,host=a load=1 11",
+                CompactionLevel::Initial,
+                100,
+                10,
+                10,
+            ))
+            .await;
+
+        let querier_namespace = Arc::new(querier_namespace(&ns).await);
+
+        insta::assert_yaml_snapshot!(
+            format_query(
+                &querier_namespace,
+                "SELECT * FROM system.compactions",
+            ).await,
+            @r###"
+        ---
+        - +----------+------------+------------------+------------+-----------------------+-----------------+--------------------------+----------------------+
+        - "| table_id | table_name | compaction_level | file_count | total_file_size_bytes | total_row_count | oldest_max_l0_created_at | newest_created_at    |"
+        - +----------+------------+------------------+------------+-----------------------+-----------------+--------------------------+----------------------+
+        - "| 1        | cpu        | 0                | 2          | 300                   | 3               | 1970-01-01T00:00:10Z     | 1970-01-01T00:00:20Z |"
+        - "| 1        | cpu        | 1                | 1          | 1000                  | 1               | 1970-01-01T00:00:20Z     | 1970-01-01T00:00:30Z |"
+        - "| 2        | mem        | 2                | 1          | 50                    | 2               | 1970-01-01T00:00:15Z     | 1970-01-01T00:00:40Z |"
+        - +----------+------------+------------------+------------+-----------------------+-----------------+--------------------------+----------------------+
+        "###
+        );
+    }
+
+    #[tokio::test]
+    async fn test_system_queries_completion() {
+        test_helpers::maybe_start_logging();
+
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let querier_namespace = Arc::new(querier_namespace(&ns).await);
+
+        // successful query
+        let ctx = querier_namespace.new_query_context(None);
+        let sql = "SELECT * FROM system.parquet_files";
+        let mut token = querier_namespace.record_query(&ctx, "sql", Box::new(sql));
+        let physical_plan = SqlQueryPlanner::default().query(sql, &ctx).await.unwrap();
+        token.set_planned();
+        ctx.collect(physical_plan).await.unwrap();
+        token.set_success();
+        drop(token);
+
+        // query failing during planning
+        let ctx = querier_namespace.new_query_context(None);
+        let sql = "SELECT * FROM missing";
+        let mut token = querier_namespace.record_query(&ctx, "sql", Box::new(sql));
+        let err = SqlQueryPlanner::default()
+            .query(sql, &ctx)
+            .await
+            .unwrap_err();
+        token.set_error(&err);
+        drop(token);
+
+        insta::assert_yaml_snapshot!(
+            format_query(
+                &querier_namespace,
+                "SELECT query_text, success, completed, plan_duration IS NOT NULL AS planned, \
+                 execute_duration IS NOT NULL AS executed FROM system.queries",
+            ).await,
+            @r###"
+        ---
+        - +------------------------------------+---------+-----------+---------+----------+
+        - "| query_text                         | success | completed | planned | executed |"
+        - +------------------------------------+---------+-----------+---------+----------+
+        - "| SELECT * FROM missing              | false   | true      | false   | false    |"
+        - "| SELECT * FROM system.parquet_files | true    | true      | true    | true     |"
+        - +------------------------------------+---------+-----------+---------+----------+
+        "###
+        );
+
+        // the error is only recorded for the failing query
+        let results = run(
+            &querier_namespace,
+            "SELECT error FROM system.queries WHERE error IS NOT NULL",
+            None,
+        )
+        .await;
+        let errors = results
+            .iter()
+            .flat_map(|batch| {
+                batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<arrow::array::StringArray>()
+                    .unwrap()
+                    .iter()
+                    .map(|error| error.unwrap().to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![err.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_system_queries_running() {
+        test_helpers::maybe_start_logging();
+
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let querier_namespace = Arc::new(querier_namespace(&ns).await);
+
+        // start a query, but hold on to its token while inspecting the system table
+        let ctx = querier_namespace.new_query_context(None);
+        let sql = "SELECT * FROM system.parquet_files";
+        let mut token = querier_namespace.record_query(&ctx, "sql", Box::new(sql));
+        let physical_plan = SqlQueryPlanner::default().query(sql, &ctx).await.unwrap();
+        token.set_planned();
+
+        let query = "SELECT query_text, completed, running, plan_duration IS NOT NULL AS planned \
+                     FROM system.queries";
+        insta::assert_yaml_snapshot!(
+            format_query(&querier_namespace, query).await,
+            @r###"
+        ---
+        - +------------------------------------+-----------+---------+---------+
+        - "| query_text                         | completed | running | planned |"
+        - +------------------------------------+-----------+---------+---------+
+        - "| SELECT * FROM system.parquet_files | false     | true    | true    |"
+        - +------------------------------------+-----------+---------+---------+
+        "###
+        );
+
+        // finish the query
+        ctx.collect(physical_plan).await.unwrap();
+        token.set_success();
+        drop(token);
+
+        insta::assert_yaml_snapshot!(
+            format_query(&querier_namespace, query).await,
+            @r###"
+        ---
+        - +------------------------------------+-----------+---------+---------+
+        - "| query_text                         | completed | running | planned |"
+        - +------------------------------------+-----------+---------+---------+
+        - "| SELECT * FROM system.parquet_files | true      | false   | true    |"
+        - +------------------------------------+-----------+---------+---------+
+        "###
+        );
+    }
+
+    #[tokio::test]
+    async fn test_system_queries_ordering() {
+        test_helpers::maybe_start_logging();
+
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_with_retention("ns", None).await;
+        let querier_namespace = Arc::new(querier_namespace(&ns).await);
+
+        // the rows are already sorted newest first, so no sort is needed
+        insta::assert_yaml_snapshot!(
+            format_explain(
+                &querier_namespace,
+                "EXPLAIN SELECT issue_time, query_text FROM system.queries ORDER BY issue_time DESC",
+            )
+            .await,
+            @r###"
+        ---
+        - "----------"
+        - "| plan_type    | plan    |"
+        - "----------"
+        - "| logical_plan    | Sort: system.queries.issue_time DESC NULLS FIRST    |"
+        - "|    |   TableScan: system.queries projection=[issue_time, query_text]    |"
+        - "| physical_plan    | SystemTableExecutionPlan: table=queries, projection=[issue_time, query_text], output_ordering=[issue_time@0 DESC]    |"
+        - "|    |    |"
+        - "----------"
+        "###
+        );
+
+        // ... but the opposite order still needs one
+        insta::assert_yaml_snapshot!(
+            format_explain(
+                &querier_namespace,
+                "EXPLAIN SELECT issue_time, query_text FROM system.queries ORDER BY issue_time",
+            )
+            .await,
+            @r###"
+        ---
+        - "----------"
+        - "| plan_type    | plan    |"
+        - "----------"
+        - "| logical_plan    | Sort: system.queries.issue_time ASC NULLS LAST    |"
+        - "|    |   TableScan: system.queries projection=[issue_time, query_text]    |"
+        - "| physical_plan    | SortExec: expr=[issue_time@0 ASC NULLS LAST]    |"
+        - "|    |   SystemTableExecutionPlan: table=queries, projection=[issue_time, query_text], output_ordering=[issue_time@0 DESC]    |"
+        - "|    |    |"
+        - "----------"
+        "###
+        );
+    }
+
     async fn format_query(querier_namespace: &Arc<QuerierNamespace>, sql: &str) -> Vec<String> {
         format_query_with_span_ctx(querier_namespace, sql, None).await
     }
@@ -158,7 +158,7 @@ impl ChunkAdapter {
                 .column_order
                 .iter()
                 .filter(|c_id| parquet_file.col_set.contains(*c_id))
-                .filter_map(|c_id| cached_table.column_id_map.get(c_id))
+                .filter_map(|c_id| cached_table.column_map.name_for_id(*c_id))
                 .cloned(),
         );
         assert!(
@@ -208,7 +208,7 @@ impl PreparedParquetFile {
         let col_set: HashSet<ColumnId> = file
             .column_set
             .iter()
-            .filter(|id| cached_table.column_id_map.contains_key(*id))
+            .filter(|id| cached_table.column_map.contains_id(**id))
             .copied()
             .collect();
 
@@ -3,8 +3,10 @@
 use data_types::NamespaceId;
 use iox_query::QueryText;
 use iox_time::{Time, TimeProvider};
+use metric::U64Counter;
 use observability_deps::tracing::warn;
 use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
 use std::{
     collections::VecDeque,
     sync::{atomic, Arc},
@@ -15,6 +17,50 @@ use trace::ctx::TraceId;
 // The query duration used for queries still running.
 const UNCOMPLETED_DURATION: i64 = -1;
 
+/// Queries running for longer than this are assumed to be lost (e.g. their completion token was
+/// leaked) and are no longer tracked as running.
+const RUNNING_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Controls how the query text is recorded in the [`QueryLog`].
+///
+/// The default records the full text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryTextConfig {
+    /// Maximum number of characters of the query text to record, `None` records the full text.
+    pub max_length: Option<usize>,
+
+    /// Replace the query text with a hash of the full text, keeping only the first
+    /// [`max_length`](Self::max_length) characters (none if unset).
+    pub redact: bool,
+}
+
+impl QueryTextConfig {
+    /// Returns the text to record for `query_text` and if it differs from the full text.
+    fn apply(&self, query_text: QueryText) -> (QueryText, bool) {
+        if !self.redact && self.max_length.is_none() {
+            return (query_text, false);
+        }
+
+        let text = query_text.to_string();
+        let prefix = match self.max_length {
+            Some(max_length) => text.chars().take(max_length).collect::<String>(),
+            None => String::new(),
+        };
+
+        if self.redact {
+            let hash = Sha256::digest(text.as_bytes())
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>();
+            (Box::new(format!("{prefix}[sha256:{hash}]")), true)
+        } else if prefix.len() < text.len() {
+            (Box::new(prefix), true)
+        } else {
+            (Box::new(text), false)
+        }
+    }
+}
+
 /// Information about a single query that was executed
 pub struct QueryLogEntry {
     /// Namespace ID.
@@ -26,6 +72,9 @@ pub struct QueryLogEntry {
     /// The text of the query (SQL for sql queries, pbjson for storage rpc queries)
     pub query_text: QueryText,
 
+    /// If `query_text` was truncated or redacted, see [`QueryTextConfig`]
+    pub query_text_truncated: bool,
+
     /// The trace ID if any
     pub trace_id: Option<TraceId>,
 
@@ -36,8 +85,21 @@ pub struct QueryLogEntry {
     /// indicating query not completed).
     query_completed_duration: atomic::AtomicI64,
 
+    /// Duration in nanoseconds query took to plan (-1 is a sentinel value
+    /// indicating query not planned).
+    plan_duration: atomic::AtomicI64,
+
     /// If the query completed successfully
     pub success: atomic::AtomicBool,
+
+    /// If the query completed, successfully or not
+    completed: atomic::AtomicBool,
+
+    /// If the query is tracked as running by the [`QueryLog`]
+    running: atomic::AtomicBool,
+
+    /// Why the query failed, if known
+    error: Mutex<Option<String>>,
 }
 
 impl std::fmt::Debug for QueryLogEntry {
@@ -45,9 +107,14 @@ impl std::fmt::Debug for QueryLogEntry {
         f.debug_struct("QueryLogEntry")
             .field("query_type", &self.query_type)
             .field("query_text", &self.query_text.to_string())
+            .field("query_text_truncated", &self.query_text_truncated)
             .field("issue_time", &self.issue_time)
             .field("query_completed_duration", &self.query_completed_duration)
+            .field("plan_duration", &self.plan_duration)
             .field("success", &self.success)
+            .field("completed", &self.completed)
+            .field("running", &self.running)
+            .field("error", &self.error)
             .finish()
     }
 }
@@ -65,10 +132,15 @@ impl QueryLogEntry {
             namespace_id,
             query_type,
             query_text,
+            query_text_truncated: false,
             trace_id,
             issue_time,
             query_completed_duration: UNCOMPLETED_DURATION.into(),
+            plan_duration: UNCOMPLETED_DURATION.into(),
             success: atomic::AtomicBool::new(false),
+            completed: atomic::AtomicBool::new(false),
+            running: atomic::AtomicBool::new(false),
+            error: Mutex::new(None),
         }
     }
 
@@ -84,11 +156,56 @@ impl QueryLogEntry {
         }
     }
 
+    /// If this query is planned, returns `Some(duration)` of how
+    /// long planning took
+    pub fn plan_duration(&self) -> Option<Duration> {
+        match self.plan_duration.load(atomic::Ordering::Relaxed) {
+            UNCOMPLETED_DURATION => None,
+            d => Some(Duration::from_nanos(d as u64)),
+        }
+    }
+
+    /// If this query is planned and completed, returns `Some(duration)` of
+    /// how long execution took
+    pub fn execute_duration(&self) -> Option<Duration> {
+        self.query_completed_duration()?
+            .checked_sub(self.plan_duration()?)
+    }
+
     /// Returns true if `set_completed` was called with `success=true`
     pub fn success(&self) -> bool {
         self.success.load(atomic::Ordering::SeqCst)
     }
 
+    /// Returns true if `set_completed` or `set_failed` was called
+    pub fn completed(&self) -> bool {
+        self.completed.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Returns true if the query is still running, i.e. it was started by `QueryLog::push`, has not
+    /// completed yet and was not swept as stale
+    pub fn running(&self) -> bool {
+        self.running.load(atomic::Ordering::SeqCst)
+    }
+
+    /// Returns the error passed to `set_failed`, if any
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().clone()
+    }
+
+    /// Mark this entry planned as of `now`.
+    pub fn set_planned(&self, now: Time) {
+        match now.checked_duration_since(self.issue_time) {
+            Some(dur) => {
+                self.plan_duration
+                    .store(dur.as_nanos() as i64, atomic::Ordering::Relaxed);
+            }
+            None => {
+                warn!("Clock went backwards, not query plan duration")
+            }
+        }
+    }
+
     /// Mark this entry complete as of `now`. `success` records if the
     /// entry is successful or not.
     pub fn set_completed(&self, now: Time, success: bool) {
@@ -102,26 +219,89 @@ impl QueryLogEntry {
             }
         }
         self.success.store(success, atomic::Ordering::SeqCst);
+        self.completed.store(true, atomic::Ordering::SeqCst);
+        self.running.store(false, atomic::Ordering::SeqCst);
+    }
+
+    /// Mark this entry failed as of `now`, with the given `error`.
+    pub fn set_failed(&self, now: Time, error: String) {
+        *self.error.lock() = Some(error);
+        self.set_completed(now, false);
     }
 }
 
 /// Stores a fixed number `QueryExecutions` -- handles locking
 /// internally so can be shared across multiple
+///
+/// If a maximum age is set, entries are also dropped once their query was issued longer ago than
+/// that, see [`with_max_age`](Self::with_max_age).
+///
+/// Queries that are still running are tracked separately, so they stay visible even after their
+/// entry was evicted from the log.
 #[derive(Debug)]
 pub struct QueryLog {
     log: Mutex<VecDeque<Arc<QueryLogEntry>>>,
+    running: Mutex<Vec<Arc<QueryLogEntry>>>,
     max_size: usize,
+    max_age: Option<Duration>,
+    text_config: QueryTextConfig,
     time_provider: Arc<dyn TimeProvider>,
+    evicted: atomic::AtomicU64,
+    evicted_metric: U64Counter,
+    expired: atomic::AtomicU64,
+    expired_metric: U64Counter,
 }
 
 impl QueryLog {
     /// Create a new QueryLog that can hold at most `size` items.
     /// When the `size+1` item is added, item `0` is evicted.
-    pub fn new(max_size: usize, time_provider: Arc<dyn TimeProvider>) -> Self {
+    pub fn new(
+        max_size: usize,
+        time_provider: Arc<dyn TimeProvider>,
+        metric_registry: &metric::Registry,
+    ) -> Self {
+        let evicted_metric = metric_registry
+            .register_metric::<U64Counter>(
+                "query_log_evicted",
+                "number of entries evicted from the query log",
+            )
+            .recorder(&[]);
+        let expired_metric = metric_registry
+            .register_metric::<U64Counter>(
+                "query_log_expired",
+                "number of entries dropped from the query log because they exceeded the maximum age",
+            )
+            .recorder(&[]);
+
         Self {
             log: Mutex::new(VecDeque::with_capacity(max_size)),
+            running: Default::default(),
             max_size,
+            max_age: None,
+            text_config: QueryTextConfig::default(),
             time_provider,
+            evicted: atomic::AtomicU64::new(0),
+            evicted_metric,
+            expired: atomic::AtomicU64::new(0),
+            expired_metric,
+        }
+    }
+
+    /// Drop entries once their query was issued longer than `max_age` ago, `None` keeps entries
+    /// until they are evicted because the log is full.
+    ///
+    /// Expired entries are dropped whenever the log is accessed, so they are never returned.
+    /// Queries that are still running stay visible until they complete, see
+    /// [`entries_with_running`](Self::entries_with_running).
+    pub fn with_max_age(self, max_age: Option<Duration>) -> Self {
+        Self { max_age, ..self }
+    }
+
+    /// Record query texts according to `text_config`.
+    pub fn with_text_config(self, text_config: QueryTextConfig) -> Self {
+        Self {
+            text_config,
+            ..self
         }
     }
 
@@ -132,23 +312,36 @@ impl QueryLog {
         query_text: QueryText,
         trace_id: Option<TraceId>,
     ) -> Arc<QueryLogEntry> {
-        let entry = Arc::new(QueryLogEntry::new(
+        let (query_text, query_text_truncated) = self.text_config.apply(query_text);
+        let mut entry = QueryLogEntry::new(
             namespace_id,
             query_type.into(),
             query_text,
             trace_id,
             self.time_provider.now(),
-        ));
+        );
+        entry.query_text_truncated = query_text_truncated;
+        entry.running.store(true, atomic::Ordering::SeqCst);
+        let entry = Arc::new(entry);
+
+        {
+            let mut running = self.running.lock();
+            self.sweep_running(&mut running);
+            running.push(Arc::clone(&entry));
+        }
 
         if self.max_size == 0 {
+            self.record_eviction();
             return entry;
         }
 
         let mut log = self.log.lock();
+        self.sweep_expired(&mut log);
 
         // enforce limit
         if log.len() == self.max_size {
             log.pop_front();
+            self.record_eviction();
         }
 
         log.push_back(Arc::clone(&entry));
@@ -156,20 +349,128 @@ impl QueryLog {
     }
 
     pub fn entries(&self) -> VecDeque<Arc<QueryLogEntry>> {
-        let log = self.log.lock();
+        let mut log = self.log.lock();
+        self.sweep_expired(&mut log);
         log.clone()
     }
 
+    /// Queries that are still running, ordered by the time they were started.
+    pub fn running_entries(&self) -> Vec<Arc<QueryLogEntry>> {
+        let mut running = self.running.lock();
+        self.sweep_running(&mut running);
+        running.clone()
+    }
+
+    /// Entries of the log, preceded by the running queries that were already evicted from it.
+    pub fn entries_with_running(&self) -> VecDeque<Arc<QueryLogEntry>> {
+        let mut entries = self.entries();
+
+        // running queries missing from the log are older than all entries in it
+        let evicted = self
+            .running_entries()
+            .into_iter()
+            .filter(|running| !entries.iter().any(|entry| Arc::ptr_eq(entry, running)))
+            .collect::<Vec<_>>();
+        for entry in evicted.into_iter().rev() {
+            entries.push_front(entry);
+        }
+
+        entries
+    }
+
+    /// Drop entries that were issued longer than `max_age` ago.
+    ///
+    /// Entries are pushed in the order they were issued, so the expired ones are at the front.
+    fn sweep_expired(&self, log: &mut VecDeque<Arc<QueryLogEntry>>) {
+        let Some(max_age) = self.max_age else {
+            return;
+        };
+
+        let now = self.time_provider.now();
+        while let Some(entry) = log.front() {
+            let expired = now
+                .checked_duration_since(entry.issue_time)
+                .map(|age| age > max_age)
+                .unwrap_or_default();
+            if !expired {
+                break;
+            }
+
+            log.pop_front();
+            self.expired.fetch_add(1, atomic::Ordering::Relaxed);
+            self.expired_metric.inc(1);
+        }
+    }
+
+    /// Stop tracking queries that completed or have been running for longer than
+    /// [`RUNNING_TIMEOUT`].
+    fn sweep_running(&self, running: &mut Vec<Arc<QueryLogEntry>>) {
+        let now = self.time_provider.now();
+        running.retain(|entry| {
+            if !entry.running() {
+                return false;
+            }
+
+            let stale = now
+                .checked_duration_since(entry.issue_time)
+                .map(|elapsed| elapsed > RUNNING_TIMEOUT)
+                .unwrap_or_default();
+            if stale {
+                warn!(
+                    query_type=%entry.query_type,
+                    issue_time=%entry.issue_time,
+                    "Query did not complete in time, no longer tracking it as running",
+                );
+                entry.running.store(false, atomic::Ordering::SeqCst);
+            }
+            !stale
+        });
+    }
+
+    fn stop_running(&self, entry: &Arc<QueryLogEntry>) {
+        self.running
+            .lock()
+            .retain(|running| !Arc::ptr_eq(running, entry));
+    }
+
+    /// Number of entries evicted (or never stored) because the log was full.
+    pub fn evicted_count(&self) -> u64 {
+        self.evicted.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Number of entries dropped because they exceeded the maximum age.
+    pub fn expired_count(&self) -> u64 {
+        self.expired.load(atomic::Ordering::Relaxed)
+    }
+
+    fn record_eviction(&self) {
+        self.evicted.fetch_add(1, atomic::Ordering::Relaxed);
+        self.evicted_metric.inc(1);
+    }
+
     /// Marks the provided query entry as completed using the current time.
     /// `success` specifies the query ran successfully
     pub fn set_completed(&self, entry: Arc<QueryLogEntry>, success: bool) {
-        entry.set_completed(self.time_provider.now(), success)
+        entry.set_completed(self.time_provider.now(), success);
+        self.stop_running(&entry);
+    }
+
+    /// Marks the provided query entry as planned using the current time.
+    pub fn set_planned(&self, entry: &QueryLogEntry) {
+        entry.set_planned(self.time_provider.now())
+    }
+
+    /// Marks the provided query entry as failed with `error` using the current time.
+    pub fn set_failed(&self, entry: Arc<QueryLogEntry>, error: String) {
+        entry.set_failed(self.time_provider.now(), error);
+        self.stop_running(&entry);
     }
 }
 
 #[cfg(test)]
 mod test_super {
     use iox_time::MockProvider;
+    use metric::{Attributes, Metric};
 
     use super::*;
 
@@ -205,4 +506,249 @@ mod test_super {
         );
         assert!(!entry.success());
     }
+
+    #[test]
+    fn test_query_log_entry_phases() {
+        let time_provider = MockProvider::new(Time::from_timestamp_millis(100).unwrap());
+
+        let entry = Arc::new(QueryLogEntry::new(
+            NamespaceId::new(1),
+            "sql".into(),
+            Box::new("SELECT 1"),
+            None,
+            time_provider.now(),
+        ));
+        assert_eq!(entry.plan_duration(), None);
+        assert_eq!(entry.execute_duration(), None);
+        assert!(!entry.completed());
+        assert_eq!(entry.error(), None);
+
+        time_provider.set(Time::from_timestamp_millis(150).unwrap());
+        entry.set_planned(time_provider.now());
+        assert_eq!(entry.plan_duration(), Some(Duration::from_millis(50)));
+        assert_eq!(entry.execute_duration(), None);
+        assert!(!entry.completed());
+
+        time_provider.set(Time::from_timestamp_millis(400).unwrap());
+        entry.set_failed(time_provider.now(), "boom".to_string());
+        assert_eq!(
+            entry.query_completed_duration(),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(entry.execute_duration(), Some(Duration::from_millis(250)));
+        assert!(entry.completed());
+        assert!(!entry.success());
+        assert_eq!(entry.error().as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_query_log_eviction() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_millis(100).unwrap()));
+        let metric_registry = metric::Registry::new();
+        let query_log = QueryLog::new(2, time_provider, &metric_registry);
+
+        for query in ["SELECT 1", "SELECT 2", "SELECT 3", "SELECT 4"] {
+            query_log.push(NamespaceId::new(1), "sql", Box::new(query), None);
+        }
+
+        // the oldest entries are evicted
+        let queries = query_log
+            .entries()
+            .iter()
+            .map(|e| e.query_text.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(queries, vec!["SELECT 3", "SELECT 4"]);
+        assert_eq!(query_log.evicted_count(), 2);
+
+        let evicted = metric_registry
+            .get_instrument::<Metric<U64Counter>>("query_log_evicted")
+            .unwrap()
+            .get_observer(&Attributes::from(&[]))
+            .unwrap()
+            .fetch();
+        assert_eq!(evicted, 2);
+    }
+
+    #[test]
+    fn test_query_log_max_age() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_millis(100).unwrap()));
+        let metric_registry = metric::Registry::new();
+        let query_log = QueryLog::new(3, Arc::clone(&time_provider) as _, &metric_registry)
+            .with_max_age(Some(Duration::from_secs(60)));
+        let queries = |entries: VecDeque<Arc<QueryLogEntry>>| {
+            entries
+                .iter()
+                .map(|e| e.query_text.to_string())
+                .collect::<Vec<_>>()
+        };
+        let expired_metric = || {
+            metric_registry
+                .get_instrument::<Metric<U64Counter>>("query_log_expired")
+                .unwrap()
+                .get_observer(&Attributes::from(&[]))
+                .unwrap()
+                .fetch()
+        };
+
+        let entry1 = query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 1"), None);
+        query_log.set_completed(entry1, true);
+        time_provider.inc(Duration::from_secs(30));
+        let entry2 = query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 2"), None);
+        time_provider.inc(Duration::from_secs(30));
+        assert_eq!(queries(query_log.entries()), vec!["SELECT 1", "SELECT 2"]);
+
+        // expired entries are dropped on read, without pushing new entries
+        time_provider.inc(Duration::from_millis(1));
+        assert_eq!(queries(query_log.entries()), vec!["SELECT 2"]);
+        assert_eq!(query_log.expired_count(), 1);
+        assert_eq!(expired_metric(), 1);
+
+        // queries that are still running stay visible until they complete
+        time_provider.inc(Duration::from_secs(30));
+        query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 3"), None);
+        assert_eq!(queries(query_log.entries()), vec!["SELECT 3"]);
+        assert_eq!(
+            queries(query_log.entries_with_running()),
+            vec!["SELECT 2", "SELECT 3"]
+        );
+        query_log.set_completed(entry2, true);
+        assert_eq!(queries(query_log.entries_with_running()), vec!["SELECT 3"]);
+        assert_eq!(query_log.expired_count(), 2);
+        assert_eq!(expired_metric(), 2);
+
+        // capacity evictions are counted separately
+        for query in ["SELECT 4", "SELECT 5", "SELECT 6"] {
+            query_log.push(NamespaceId::new(1), "sql", Box::new(query), None);
+        }
+        assert_eq!(
+            queries(query_log.entries()),
+            vec!["SELECT 4", "SELECT 5", "SELECT 6"]
+        );
+        assert_eq!(query_log.evicted_count(), 1);
+        assert_eq!(query_log.expired_count(), 2);
+
+        // without a maximum age, entries are kept
+        let query_log = QueryLog::new(
+            3,
+            Arc::clone(&time_provider) as _,
+            &metric::Registry::default(),
+        );
+        query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 1"), None);
+        time_provider.inc(Duration::from_secs(24 * 60 * 60));
+        assert_eq!(queries(query_log.entries()), vec!["SELECT 1"]);
+        assert_eq!(query_log.expired_count(), 0);
+    }
+
+    #[test]
+    fn test_running_entries() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_millis(100).unwrap()));
+        let query_log = QueryLog::new(
+            2,
+            Arc::clone(&time_provider) as _,
+            &metric::Registry::default(),
+        );
+        fn queries<'a>(entries: impl IntoIterator<Item = &'a Arc<QueryLogEntry>>) -> Vec<String> {
+            entries
+                .into_iter()
+                .map(|e| e.query_text.to_string())
+                .collect()
+        }
+
+        let entry1 = query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 1"), None);
+        let entry2 = query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 2"), None);
+        assert!(entry1.running());
+        assert!(entry2.running());
+        query_log.set_completed(Arc::clone(&entry2), true);
+        assert!(!entry2.running());
+        assert_eq!(queries(&query_log.running_entries()), vec!["SELECT 1"]);
+
+        // the running query stays visible after being evicted, the completed one does not
+        query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 3"), None);
+        query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 4"), None);
+        assert_eq!(queries(&query_log.entries()), vec!["SELECT 3", "SELECT 4"]);
+        assert_eq!(
+            queries(&query_log.entries_with_running()),
+            vec!["SELECT 1", "SELECT 3", "SELECT 4"]
+        );
+
+        // failed queries are no longer running either
+        query_log.set_failed(entry1, "boom".to_owned());
+        assert_eq!(
+            queries(&query_log.entries_with_running()),
+            vec!["SELECT 3", "SELECT 4"]
+        );
+
+        // queries that never complete are swept eventually
+        time_provider.inc(RUNNING_TIMEOUT);
+        let entry5 = query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 5"), None);
+        assert_eq!(
+            queries(&query_log.running_entries()),
+            vec!["SELECT 3", "SELECT 4", "SELECT 5"]
+        );
+        time_provider.inc(Duration::from_millis(1));
+        assert_eq!(queries(&query_log.running_entries()), vec!["SELECT 5"]);
+        assert!(entry5.running());
+        assert!(!query_log.entries()[0].running());
+        assert!(!query_log.entries()[0].completed());
+    }
+
+    #[test]
+    fn test_query_text_config() {
+        let text = "SELECT * FROM cpu WHERE password = 'secret'";
+        let apply = |config: QueryTextConfig| {
+            let (text, truncated) = config.apply(Box::new(text));
+            (text.to_string(), truncated)
+        };
+
+        // full text by default
+        assert_eq!(apply(QueryTextConfig::default()), (text.to_string(), false));
+
+        // truncation
+        let config = QueryTextConfig {
+            max_length: Some(8),
+            redact: false,
+        };
+        assert_eq!(apply(config), ("SELECT *".to_string(), true));
+        let config = QueryTextConfig {
+            max_length: Some(text.len()),
+            redact: false,
+        };
+        assert_eq!(apply(config), (text.to_string(), false));
+
+        // redaction, hashes are stable
+        let config = QueryTextConfig {
+            max_length: None,
+            redact: true,
+        };
+        let redacted = "[sha256:e3c3da898a5bcf95ea072896103590c9876246e2bef789afb15d2be164b2629d]";
+        assert_eq!(apply(config), (redacted.to_string(), true));
+        assert_ne!(config.apply(Box::new("SELECT 1")).0.to_string(), redacted);
+
+        // redaction keeping a prefix
+        let config = QueryTextConfig {
+            max_length: Some(6),
+            redact: true,
+        };
+        let (prefixed, truncated) = apply(config);
+        assert!(truncated);
+        assert_eq!(prefixed, format!("SELECT{redacted}"));
+    }
+
+    #[test]
+    fn test_query_log_text_config() {
+        let time_provider = Arc::new(MockProvider::new(Time::from_timestamp_millis(100).unwrap()));
+        let query_log = QueryLog::new(10, time_provider, &metric::Registry::default())
+            .with_text_config(QueryTextConfig {
+                max_length: Some(8),
+                redact: false,
+            });
+
+        let entry = query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 1"), None);
+        assert_eq!(entry.query_text.to_string(), "SELECT 1");
+        assert!(!entry.query_text_truncated);
+
+        let entry = query_log.push(NamespaceId::new(1), "sql", Box::new("SELECT 10"), None);
+        assert_eq!(entry.query_text.to_string(), "SELECT 1");
+        assert!(entry.query_text_truncated);
+    }
 }
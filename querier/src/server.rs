@@ -107,6 +107,10 @@ mod tests {
                     exec,
                     Some(create_ingester_connection_for_testing()),
                     QuerierDatabase::MAX_CONCURRENT_QUERIES_MAX,
+                    QuerierDatabase::QUERY_LOG_SIZE_DEFAULT,
+                    None,
+                    Default::default(),
+                    Default::default(),
                     Arc::new(HashMap::default()),
                 )
                 .await
@@ -0,0 +1,241 @@
+use crate::{
+    cache::introspection::{CacheIntrospection, CacheStats},
+    system_tables::{BatchIterator, IoxSystemTable, SystemTableStatistics},
+};
+use arrow::{
+    array::{ArrayRef, StringArray, UInt64Array},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    error::Result,
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use observability_deps::tracing::error;
+use std::sync::Arc;
+
+/// Implementation of system.caches table
+#[derive(Debug)]
+pub(super) struct CachesTable {
+    schema: SchemaRef,
+    introspection: CacheIntrospection,
+}
+
+impl CachesTable {
+    pub(super) fn new(introspection: CacheIntrospection) -> Self {
+        Self {
+            schema: caches_schema(),
+            introspection,
+        }
+    }
+}
+
+#[async_trait]
+impl IoxSystemTable for CachesTable {
+    fn name(&self) -> &'static str {
+        super::CACHES_TABLE
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    async fn scan(
+        &self,
+        batch_size: usize,
+        _projection: Option<&[usize]>,
+    ) -> Result<BatchIterator> {
+        let schema = self.schema();
+        let stats = self.introspection.stats();
+
+        let mut offset = 0;
+        Ok(Box::new(std::iter::from_fn(move || {
+            if offset >= stats.len() {
+                return None;
+            }
+
+            let len = batch_size.min(stats.len() - offset);
+            match from_cache_stats(Arc::clone(&schema), &stats[offset..offset + len]) {
+                Ok(batch) => {
+                    offset += len;
+                    Some(Ok(batch))
+                }
+                Err(e) => {
+                    error!("Error system.caches table: {:?}", e);
+                    Some(Err(e))
+                }
+            }
+        })))
+    }
+
+    fn statistics(&self) -> SystemTableStatistics {
+        SystemTableStatistics {
+            num_rows: Some(self.introspection.num_caches()),
+            total_byte_size: None,
+            is_exact: true,
+        }
+    }
+}
+
+fn caches_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("cache_id", DataType::Utf8, false),
+        Field::new("pool", DataType::Utf8, false),
+        Field::new("entries", DataType::UInt64, false),
+        Field::new("ram_bytes", DataType::UInt64, false),
+        Field::new("pool_ram_bytes", DataType::UInt64, false),
+        Field::new("pool_ram_limit_bytes", DataType::UInt64, false),
+        Field::new("hits", DataType::UInt64, false),
+        Field::new("misses", DataType::UInt64, false),
+    ]))
+}
+
+fn from_cache_stats(schema: SchemaRef, stats: &[CacheStats]) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.cache_id))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(stats.iter().map(|s| Some(s.pool)).collect::<StringArray>()),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.entries))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.ram_bytes))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.pool_ram_bytes))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.pool_ram_limit_bytes))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(stats.iter().map(|s| Some(s.hits)).collect::<UInt64Array>()),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.misses))
+                .collect::<UInt64Array>(),
+        ),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuerierCatalogCache;
+    use arrow::array::Array;
+    use iox_tests::TestCatalog;
+    use tokio::runtime::Handle;
+
+    #[tokio::test]
+    async fn test_caches() {
+        let catalog = TestCatalog::new();
+        catalog.create_namespace_1hr_retention("ns").await;
+
+        let catalog_cache = QuerierCatalogCache::new_testing(
+            catalog.catalog(),
+            catalog.time_provider(),
+            catalog.metric_registry(),
+            catalog.object_store(),
+            &Handle::current(),
+        );
+        let table = CachesTable::new(catalog_cache.introspection());
+
+        let namespace = |batches: &[RecordBatch]| {
+            assert_eq!(batches.len(), 1);
+            let batch = &batches[0];
+            let cache_ids = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            let row = (0..cache_ids.len())
+                .find(|i| cache_ids.value(*i) == "namespace")
+                .expect("namespace cache listed");
+            // all columns after `cache_id` and `pool`
+            (2..batch.num_columns())
+                .map(|col| {
+                    batch
+                        .column(col)
+                        .as_any()
+                        .downcast_ref::<UInt64Array>()
+                        .unwrap()
+                        .value(row)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // every cache is listed, empty
+        let batches = table
+            .scan(10, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let cache_ids = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap()
+            .iter()
+            .map(|id| id.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cache_ids,
+            vec![
+                "namespace",
+                "parquet_file",
+                "partition",
+                "projected_schema",
+                "object_store"
+            ]
+        );
+        let [entries, ram_bytes, _pool_ram_bytes, _pool_ram_limit_bytes, hits, misses] =
+            namespace(&batches)[..]
+        else {
+            panic!("unexpected columns");
+        };
+        assert_eq!((entries, ram_bytes, hits, misses), (0, 0, 0, 0));
+
+        // populate the namespace cache
+        for _ in 0..2 {
+            catalog_cache
+                .namespace()
+                .get(Arc::from("ns"), &[], None)
+                .await
+                .unwrap()
+                .expect("namespace exists");
+        }
+
+        let batches = table
+            .scan(10, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let [entries, ram_bytes, pool_ram_bytes, pool_ram_limit_bytes, hits, misses] =
+            namespace(&batches)[..]
+        else {
+            panic!("unexpected columns");
+        };
+        assert_eq!(entries, 1);
+        assert!(ram_bytes > 0);
+        assert!(pool_ram_bytes >= ram_bytes);
+        assert_eq!(pool_ram_limit_bytes, usize::MAX as u64);
+        assert_eq!((hits, misses), (1, 1));
+    }
+}
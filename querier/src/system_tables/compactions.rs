@@ -0,0 +1,174 @@
+use crate::system_tables::{BatchIterator, IoxSystemTable};
+use arrow::{
+    array::{ArrayRef, Int16Array, Int64Array, StringArray, TimestampNanosecondArray},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    error::{ArrowError, Result},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use data_types::{NamespaceId, ParquetFileSummary, TableId};
+use iox_catalog::interface::Catalog;
+use observability_deps::tracing::error;
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Implementation of system.compactions table
+#[derive(Debug)]
+pub(super) struct CompactionsTable {
+    schema: SchemaRef,
+    catalog: Arc<dyn Catalog>,
+    namespace_id: NamespaceId,
+}
+
+impl CompactionsTable {
+    pub(super) fn new(catalog: Arc<dyn Catalog>, namespace_id: NamespaceId) -> Self {
+        Self {
+            schema: compactions_schema(),
+            catalog,
+            namespace_id,
+        }
+    }
+}
+
+#[async_trait]
+impl IoxSystemTable for CompactionsTable {
+    fn name(&self) -> &'static str {
+        super::COMPACTIONS_TABLE
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    async fn scan(
+        &self,
+        batch_size: usize,
+        _projection: Option<&[usize]>,
+    ) -> Result<BatchIterator> {
+        let schema = self.schema();
+
+        // the files are aggregated by the catalog, so only the summaries are loaded
+        let mut repos = self.catalog.repositories().await;
+        let summaries = repos
+            .parquet_files()
+            .summarize_by_namespace_not_to_delete(self.namespace_id)
+            .await
+            .map_err(|e| {
+                error!(%e, "Error summarizing parquet files for system.compactions table");
+                ArrowError::ExternalError(Box::new(e))
+            })?;
+        let table_names = repos
+            .tables()
+            .list_by_namespace_id(self.namespace_id)
+            .await
+            .map_err(|e| {
+                error!(%e, "Error listing tables for system.compactions table");
+                ArrowError::ExternalError(Box::new(e))
+            })?
+            .into_iter()
+            .map(|table| (table.id, Arc::from(table.name)))
+            .collect::<BTreeMap<TableId, Arc<str>>>();
+
+        let mut offset = 0;
+        Ok(Box::new(std::iter::from_fn(move || {
+            if offset >= summaries.len() {
+                return None;
+            }
+
+            let len = batch_size.min(summaries.len() - offset);
+            match from_summaries(
+                Arc::clone(&schema),
+                &summaries[offset..offset + len],
+                &table_names,
+            ) {
+                Ok(batch) => {
+                    offset += len;
+                    Some(Ok(batch))
+                }
+                Err(e) => {
+                    error!("Error system.compactions table: {:?}", e);
+                    Some(Err(e))
+                }
+            }
+        })))
+    }
+}
+
+fn compactions_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("table_id", DataType::Int64, false),
+        Field::new("table_name", DataType::Utf8, true),
+        Field::new("compaction_level", DataType::Int16, false),
+        Field::new("file_count", DataType::Int64, false),
+        Field::new("total_file_size_bytes", DataType::Int64, false),
+        Field::new("total_row_count", DataType::Int64, false),
+        Field::new(
+            "oldest_max_l0_created_at",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false,
+        ),
+        Field::new(
+            "newest_created_at",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false,
+        ),
+    ]))
+}
+
+fn from_summaries(
+    schema: SchemaRef,
+    summaries: &[ParquetFileSummary],
+    table_names: &BTreeMap<TableId, Arc<str>>,
+) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| Some(s.table_id.get()))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| table_names.get(&s.table_id).map(|name| name.as_ref()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| Some(s.compaction_level as i16))
+                .collect::<Int16Array>(),
+        ),
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| Some(s.file_count))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| Some(s.total_file_size_bytes))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| Some(s.total_row_count))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| Some(s.oldest_max_l0_created_at.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            summaries
+                .iter()
+                .map(|s| Some(s.newest_created_at.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
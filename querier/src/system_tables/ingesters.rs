@@ -0,0 +1,185 @@
+use crate::{
+    ingester::{status::IngesterStatus, IngesterConnection},
+    system_tables::{BatchIterator, IoxSystemTable},
+};
+use arrow::{
+    array::{ArrayRef, StringArray, TimestampNanosecondArray},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    error::Result,
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use observability_deps::tracing::error;
+use std::sync::Arc;
+
+/// Implementation of system.ingesters table
+#[derive(Debug)]
+pub(super) struct IngestersTable {
+    schema: SchemaRef,
+    ingester_connection: Option<Arc<dyn IngesterConnection>>,
+}
+
+impl IngestersTable {
+    pub(super) fn new(ingester_connection: Option<Arc<dyn IngesterConnection>>) -> Self {
+        Self {
+            schema: ingesters_schema(),
+            ingester_connection,
+        }
+    }
+}
+
+#[async_trait]
+impl IoxSystemTable for IngestersTable {
+    fn name(&self) -> &'static str {
+        super::INGESTERS_TABLE
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    async fn scan(
+        &self,
+        batch_size: usize,
+        _projection: Option<&[usize]>,
+    ) -> Result<BatchIterator> {
+        let schema = self.schema();
+        let status = self
+            .ingester_connection
+            .as_ref()
+            .map(|c| c.status())
+            .unwrap_or_default();
+
+        let mut offset = 0;
+        Ok(Box::new(std::iter::from_fn(move || {
+            if offset >= status.len() {
+                return None;
+            }
+
+            let len = batch_size.min(status.len() - offset);
+            match from_ingester_status(Arc::clone(&schema), &status[offset..offset + len]) {
+                Ok(batch) => {
+                    offset += len;
+                    Some(Ok(batch))
+                }
+                Err(e) => {
+                    error!("Error system.ingesters table: {:?}", e);
+                    Some(Err(e))
+                }
+            }
+        })))
+    }
+}
+
+fn ingesters_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("ingester", DataType::Utf8, false),
+        Field::new(
+            "last_success_time",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true,
+        ),
+        Field::new(
+            "last_error_time",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true,
+        ),
+        Field::new("last_error", DataType::Utf8, true),
+        Field::new("circuit_state", DataType::Utf8, true),
+    ]))
+}
+
+fn from_ingester_status(schema: SchemaRef, status: &[IngesterStatus]) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            status
+                .iter()
+                .map(|s| Some(s.address.as_ref()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            status
+                .iter()
+                .map(|s| s.last_success.map(|t| t.timestamp_nanos()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            status
+                .iter()
+                .map(|s| s.last_error_time.map(|t| t.timestamp_nanos()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            status
+                .iter()
+                .map(|s| s.last_error.as_deref())
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            status
+                .iter()
+                .map(|s| s.circuit_state)
+                .collect::<StringArray>(),
+        ),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingester::test_util::MockIngesterConnection;
+    use arrow_util::assert_batches_eq;
+    use iox_time::Time;
+
+    #[tokio::test]
+    async fn test_ingesters() {
+        let ingester_connection = Arc::new(MockIngesterConnection::new());
+        ingester_connection.set_status(vec![
+            IngesterStatus {
+                address: Arc::from("http://ingester-1:8082"),
+                last_success: Some(Time::from_timestamp_nanos(1_000_000_000)),
+                last_error_time: None,
+                last_error: None,
+                circuit_state: Some("closed"),
+            },
+            IngesterStatus {
+                address: Arc::from("http://ingester-2:8082"),
+                last_success: None,
+                last_error_time: Some(Time::from_timestamp_nanos(2_000_000_000)),
+                last_error: Some("connection refused".to_owned()),
+                circuit_state: Some("open"),
+            },
+        ]);
+
+        let table = IngestersTable::new(Some(ingester_connection as _));
+        let batches = table
+            .scan(10, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_batches_eq!(
+            [
+                "+------------------------+----------------------+----------------------+--------------------+---------------+",
+                "| ingester               | last_success_time    | last_error_time      | last_error         | circuit_state |",
+                "+------------------------+----------------------+----------------------+--------------------+---------------+",
+                "| http://ingester-1:8082 | 1970-01-01T00:00:01Z |                      |                    | closed        |",
+                "| http://ingester-2:8082 |                      | 1970-01-01T00:00:02Z | connection refused | open          |",
+                "+------------------------+----------------------+----------------------+--------------------+---------------+",
+            ],
+            &batches
+        );
+
+        // without ingesters, the table is empty
+        let table = IngestersTable::new(None);
+        let batches = table
+            .scan(10, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(batches.is_empty());
+    }
+}
@@ -1,53 +1,235 @@
-use crate::query_log::QueryLog;
-use arrow::{datatypes::SchemaRef, error::Result as ArrowResult, record_batch::RecordBatch};
+use crate::{
+    cache::introspection::CacheIntrospection, ingester::IngesterConnection, query_log::QueryLog,
+};
+use arrow::{
+    compute::SortOptions, datatypes::SchemaRef, error::Result as ArrowResult,
+    record_batch::RecordBatch,
+};
 use async_trait::async_trait;
 use data_types::NamespaceId;
-use datafusion::error::DataFusionError;
 use datafusion::physical_plan::{DisplayAs, DisplayFormatType};
 use datafusion::{
     catalog::schema::SchemaProvider,
     datasource::TableProvider,
     error::Result as DataFusionResult,
     execution::context::{SessionState, TaskContext},
-    logical_expr::TableType,
+    logical_expr::{TableProviderFilterPushDown, TableType},
     physical_plan::{
-        expressions::PhysicalSortExpr, ExecutionPlan, Partitioning, RecordBatchStream,
-        SendableRecordBatchStream, Statistics,
+        expressions::{Column, PhysicalSortExpr},
+        stream::RecordBatchStreamAdapter,
+        ColumnStatistics, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
     },
     prelude::Expr,
 };
+use futures::{StreamExt, TryStreamExt};
+use iox_catalog::interface::Catalog;
+use observability_deps::tracing::warn;
 use std::collections::HashMap;
-use std::{
-    any::Any,
-    pin::Pin,
-    sync::Arc,
-    task::{Context, Poll},
-};
+use std::{any::Any, sync::Arc};
 
+mod caches;
+mod compactions;
+mod ingesters;
+mod object_store_metrics;
+mod parquet_files;
 mod queries;
 
-pub const SYSTEM_SCHEMA: &str = "system";
+pub use datafusion_util::config::SYSTEM_SCHEMA;
 
 const QUERIES_TABLE: &str = "queries";
 
+const PARQUET_FILES_TABLE: &str = "parquet_files";
+
+const CACHES_TABLE: &str = "caches";
+
+const COMPACTIONS_TABLE: &str = "compactions";
+
+const INGESTERS_TABLE: &str = "ingesters";
+
+const OBJECT_STORE_METRICS_TABLE: &str = "object_store_metrics";
+
+/// Names of all system tables.
+const TABLE_NAMES: [&str; 6] = [
+    QUERIES_TABLE,
+    PARQUET_FILES_TABLE,
+    COMPACTIONS_TABLE,
+    CACHES_TABLE,
+    INGESTERS_TABLE,
+    OBJECT_STORE_METRICS_TABLE,
+];
+
+/// Who can see a system table, see [`SystemTablePolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemTableVisibility {
+    /// Visible in every query.
+    Public,
+
+    /// Only visible in queries that requested debug information.
+    Debug,
+
+    /// Never visible.
+    Hidden,
+}
+
+/// Visibility of the system tables of a deployment.
+///
+/// Tables that are not configured otherwise have their [default visibility]: the
+/// [`QUERIES_TABLE`], [`PARQUET_FILES_TABLE`] and [`COMPACTIONS_TABLE`] are only visible in
+/// queries that requested debug information, all others are hidden.
+///
+/// [default visibility]: SystemTablePolicy::default_visibility
+///
+/// Optionally, an admin namespace can be configured. Its system schema only contains a
+/// [`QUERIES_TABLE`] that lists the queries of all namespaces, see
+/// [`SystemSchemaProvider::new_admin`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemTablePolicy {
+    visibility: HashMap<&'static str, SystemTableVisibility>,
+    admin_namespace: Option<Arc<str>>,
+}
+
+impl SystemTablePolicy {
+    /// Create policy from the visibility of individual tables.
+    ///
+    /// Later entries override earlier ones. Names that are not system tables are ignored.
+    pub fn new<S>(visibility: impl IntoIterator<Item = (S, SystemTableVisibility)>) -> Self
+    where
+        S: AsRef<str>,
+    {
+        let mut policy = HashMap::new();
+        for (name, v) in visibility {
+            let name = name.as_ref();
+            match TABLE_NAMES.into_iter().find(|table| *table == name) {
+                Some(table) => {
+                    policy.insert(table, v);
+                }
+                None => {
+                    warn!(table = name, "Ignoring visibility of unknown system table");
+                }
+            }
+        }
+
+        Self {
+            visibility: policy,
+            admin_namespace: None,
+        }
+    }
+
+    /// Use the given namespace as admin namespace.
+    ///
+    /// Access to this namespace must be restricted to operators, because it exposes the queries
+    /// of all other namespaces.
+    pub fn with_admin_namespace(self, name: impl Into<Arc<str>>) -> Self {
+        Self {
+            admin_namespace: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// If the given namespace is the admin namespace.
+    pub fn is_admin_namespace(&self, name: &str) -> bool {
+        self.admin_namespace.as_deref() == Some(name)
+    }
+
+    /// Visibility of the given system table.
+    pub fn visibility(&self, table: &str) -> SystemTableVisibility {
+        self.visibility
+            .get(table)
+            .copied()
+            .unwrap_or_else(|| Self::default_visibility(table))
+    }
+
+    /// Visibility of the given system table if it is not configured.
+    ///
+    /// The tables that are not scoped to the namespace of the query (e.g. the caches and metrics of
+    /// the querier process) are hidden, so that they have to be enabled explicitly.
+    pub fn default_visibility(table: &str) -> SystemTableVisibility {
+        match table {
+            QUERIES_TABLE | PARQUET_FILES_TABLE | COMPACTIONS_TABLE => SystemTableVisibility::Debug,
+            _ => SystemTableVisibility::Hidden,
+        }
+    }
+
+    /// If the given system table is visible in a query, depending on whether the query requested
+    /// debug information.
+    fn is_visible(&self, table: &str, include_debug_info: bool) -> bool {
+        match self.visibility(table) {
+            SystemTableVisibility::Public => true,
+            SystemTableVisibility::Debug => include_debug_info,
+            SystemTableVisibility::Hidden => false,
+        }
+    }
+}
+
 pub struct SystemSchemaProvider {
     tables: HashMap<&'static str, Arc<dyn TableProvider>>,
 }
 
 impl SystemSchemaProvider {
     pub fn new(
+        catalog: Arc<dyn Catalog>,
+        cache_introspection: CacheIntrospection,
+        metric_registry: Arc<metric::Registry>,
         query_log: Arc<QueryLog>,
+        ingester_connection: Option<Arc<dyn IngesterConnection>>,
         namespace_id: NamespaceId,
+        policy: &SystemTablePolicy,
         include_debug_info: bool,
     ) -> Self {
         let mut tables: HashMap<&'static str, Arc<dyn TableProvider>> = HashMap::new();
 
-        if include_debug_info {
-            let queries = Arc::new(SystemTableProvider {
-                table: Arc::new(queries::QueriesTable::new(query_log, Some(namespace_id))),
-            });
-            tables.insert(QUERIES_TABLE, queries);
-        }
+        let queries = SystemTableProvider::new(Arc::new(queries::QueriesTable::new(
+            query_log,
+            Some(namespace_id),
+        )));
+        tables.insert(queries.name, Arc::new(queries));
+
+        let parquet_files = SystemTableProvider::new(Arc::new(
+            parquet_files::ParquetFilesTable::new(Arc::clone(&catalog), namespace_id),
+        ));
+        tables.insert(parquet_files.name, Arc::new(parquet_files));
+
+        let compactions = SystemTableProvider::new(Arc::new(compactions::CompactionsTable::new(
+            catalog,
+            namespace_id,
+        )));
+        tables.insert(compactions.name, Arc::new(compactions));
+
+        let caches =
+            SystemTableProvider::new(Arc::new(caches::CachesTable::new(cache_introspection)));
+        tables.insert(caches.name, Arc::new(caches));
+
+        let ingesters = SystemTableProvider::new(Arc::new(ingesters::IngestersTable::new(
+            ingester_connection,
+        )));
+        tables.insert(ingesters.name, Arc::new(ingesters));
+
+        let object_store_metrics = SystemTableProvider::new(Arc::new(
+            object_store_metrics::ObjectStoreMetricsTable::new(metric_registry),
+        ));
+        tables.insert(object_store_metrics.name, Arc::new(object_store_metrics));
+
+        tables.retain(|name, _| policy.is_visible(name, include_debug_info));
+
+        Self { tables }
+    }
+
+    /// Create the system schema of the admin namespace.
+    ///
+    /// It only contains the [`QUERIES_TABLE`], which is not scoped to any namespace but has an
+    /// additional `namespace_id` column.
+    pub fn new_admin(
+        query_log: Arc<QueryLog>,
+        policy: &SystemTablePolicy,
+        include_debug_info: bool,
+    ) -> Self {
+        let mut tables: HashMap<&'static str, Arc<dyn TableProvider>> = HashMap::new();
+
+        let queries =
+            SystemTableProvider::new(Arc::new(queries::QueriesTable::new(query_log, None)));
+        tables.insert(queries.name, Arc::new(queries));
+
+        tables.retain(|name, _| policy.is_visible(name, include_debug_info));
 
         Self { tables }
     }
@@ -80,20 +262,83 @@ impl SchemaProvider for SystemSchemaProvider {
 
 type BatchIterator = Box<dyn Iterator<Item = ArrowResult<RecordBatch>> + Send + Sync>;
 
+/// Size estimate of a system table, see [`IoxSystemTable::statistics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SystemTableStatistics {
+    /// Number of rows, if known.
+    num_rows: Option<usize>,
+
+    /// Total size of the rows in bytes, if known.
+    total_byte_size: Option<usize>,
+
+    /// If the numbers are exact, and not just an estimate.
+    is_exact: bool,
+}
+
 /// The minimal thing that a system table needs to implement
+#[async_trait]
 trait IoxSystemTable: Send + Sync {
+    /// Name of the table within the system schema, e.g. `queries`
+    fn name(&self) -> &'static str;
+
     /// Produce the schema from this system table
     fn schema(&self) -> SchemaRef;
 
     /// Get the contents of the system table
-    fn scan(&self, batch_size: usize) -> ArrowResult<BatchIterator>;
+    ///
+    /// If `projection` is given, the table may only produce these columns (in this order) to
+    /// avoid building columns that are not needed. Batches with the full schema are projected
+    /// afterwards, so tables are free to ignore the projection.
+    async fn scan(
+        &self,
+        batch_size: usize,
+        projection: Option<&[usize]>,
+    ) -> ArrowResult<BatchIterator>;
+
+    /// Get the contents of the system table, skipping rows that don't match the filters where
+    /// the table knows how to.
+    ///
+    /// The filters are re-applied to the result, so the table may ignore some or all of them. By
+    /// default, all of them are ignored.
+    async fn scan_with_filters(
+        &self,
+        batch_size: usize,
+        projection: Option<&[usize]>,
+        _filters: &[Expr],
+    ) -> ArrowResult<BatchIterator> {
+        self.scan(batch_size, projection).await
+    }
+
+    /// Estimate the size of the system table without scanning it.
+    ///
+    /// By default, nothing is known.
+    fn statistics(&self) -> SystemTableStatistics {
+        SystemTableStatistics::default()
+    }
+
+    /// Columns that the rows produced by a scan are sorted by, across all batches.
+    ///
+    /// By default, the rows are not sorted.
+    fn sort_order(&self) -> Vec<(&'static str, SortOptions)> {
+        vec![]
+    }
 }
 
 /// Adapter that makes any `IoxSystemTable` a DataFusion `TableProvider`
 struct SystemTableProvider<T: IoxSystemTable> {
+    name: &'static str,
     table: Arc<T>,
 }
 
+impl<T: IoxSystemTable> SystemTableProvider<T> {
+    fn new(table: Arc<T>) -> Self {
+        Self {
+            name: table.name(),
+            table,
+        }
+    }
+}
+
 #[async_trait]
 impl<T> TableProvider for SystemTableProvider<T>
 where
@@ -111,9 +356,8 @@ where
         &self,
         _ctx: &SessionState,
         projection: Option<&Vec<usize>>,
-        // It would be cool to push projection and limit down
-        _filters: &[Expr],
-        _limit: Option<usize>,
+        filters: &[Expr],
+        limit: Option<usize>,
     ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
         let schema = self.table.schema();
         let projected_schema = match projection.as_ref() {
@@ -121,22 +365,52 @@ where
             None => schema,
         };
 
+        // the rows are only known to be sorted by the columns of the sort order up to the first
+        // one that is not projected
+        let output_ordering = self
+            .table
+            .sort_order()
+            .into_iter()
+            .map_while(|(name, options)| {
+                let column = Column::new_with_schema(name, &projected_schema).ok()?;
+                Some(PhysicalSortExpr {
+                    expr: Arc::new(column),
+                    options,
+                })
+            })
+            .collect();
+
         Ok(Arc::new(SystemTableExecutionPlan {
+            name: self.name,
             table: Arc::clone(&self.table),
             projection: projection.cloned(),
             projected_schema,
+            output_ordering,
+            filters: filters.to_vec(),
+            limit,
         }))
     }
 
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> DataFusionResult<Vec<TableProviderFilterPushDown>> {
+        Ok(vec![TableProviderFilterPushDown::Inexact; filters.len()])
+    }
+
     fn table_type(&self) -> TableType {
         TableType::Base
     }
 }
 
 struct SystemTableExecutionPlan<T> {
+    name: &'static str,
     table: Arc<T>,
     projected_schema: SchemaRef,
     projection: Option<Vec<usize>>,
+    output_ordering: Vec<PhysicalSortExpr>,
+    filters: Vec<Expr>,
+    limit: Option<usize>,
 }
 
 impl<T> std::fmt::Debug for SystemTableExecutionPlan<T> {
@@ -159,8 +433,9 @@ impl<T: IoxSystemTable + 'static> ExecutionPlan for SystemTableExecutionPlan<T>
     }
 
     fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
-        None
+        (!self.output_ordering.is_empty()).then_some(self.output_ordering.as_slice())
     }
+
     fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
         vec![]
     }
@@ -178,52 +453,567 @@ impl<T: IoxSystemTable + 'static> ExecutionPlan for SystemTableExecutionPlan<T>
         context: Arc<TaskContext>,
     ) -> DataFusionResult<SendableRecordBatchStream> {
         let batch_size = context.session_config().batch_size();
+        let table = Arc::clone(&self.table);
+        let projection = self.projection.clone();
+        let scan_projection = self.projection.clone();
+        let projected_schema = Arc::clone(&self.projected_schema);
+        let filters = self.filters.clone();
+        let limit = self.limit;
+
+        let batches = futures::stream::once(async move {
+            let batches = table
+                .scan_with_filters(batch_size, scan_projection.as_deref(), &filters)
+                .await;
+            match limit {
+                Some(limit) => batches.map(|batches| limit_batches(batches, limit)),
+                None => batches,
+            }
+        })
+        .map_ok(futures::stream::iter)
+        .try_flatten()
+        .map(move |maybe_batch| -> DataFusionResult<RecordBatch> {
+            let batch = maybe_batch?;
+            match &projection {
+                // the table ignored the projection
+                Some(projection) if batch.schema() != projected_schema => {
+                    Ok(batch.project(projection)?)
+                }
+                _ => Ok(batch),
+            }
+        });
 
-        Ok(Box::pin(SystemTableStream {
-            projected_schema: Arc::clone(&self.projected_schema),
-            batches: self.table.scan(batch_size)?,
-            projection: self.projection.clone(),
-        }))
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.projected_schema),
+            batches,
+        )))
     }
 
     fn statistics(&self) -> Statistics {
-        Statistics::default()
+        let SystemTableStatistics {
+            num_rows,
+            total_byte_size,
+            is_exact,
+        } = self.table.statistics();
+
+        // the table may skip rows that don't match the filters, so the numbers are an upper bound
+        let is_exact = is_exact && self.filters.is_empty();
+        let num_rows = match self.limit {
+            Some(limit) => num_rows.map(|num_rows| num_rows.min(limit)),
+            None => num_rows,
+        };
+        // the byte size refers to all rows and columns
+        let total_byte_size = total_byte_size.filter(|_| {
+            self.projection.is_none() && self.limit.is_none() && self.filters.is_empty()
+        });
+
+        let column_statistics = self
+            .projected_schema
+            .fields()
+            .iter()
+            .map(|field| ColumnStatistics {
+                null_count: (!field.is_nullable()).then_some(0),
+                ..Default::default()
+            })
+            .collect();
+
+        Statistics {
+            num_rows,
+            total_byte_size,
+            column_statistics: Some(column_statistics),
+            is_exact,
+        }
     }
 }
 
+/// Stop pulling batches once `limit` rows were produced, truncating the last batch.
+fn limit_batches(mut batches: BatchIterator, limit: usize) -> BatchIterator {
+    let mut remaining = limit;
+    Box::new(std::iter::from_fn(move || {
+        if remaining == 0 {
+            return None;
+        }
+
+        let batch = match batches.next()? {
+            Ok(batch) => batch,
+            Err(e) => return Some(Err(e)),
+        };
+        let batch = if batch.num_rows() > remaining {
+            batch.slice(0, remaining)
+        } else {
+            batch
+        };
+        remaining -= batch.num_rows();
+        Some(Ok(batch))
+    }))
+}
+
 impl<T> DisplayAs for SystemTableExecutionPlan<T> {
     fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match t {
-            DisplayFormatType::Default | DisplayFormatType::Verbose => f
-                .debug_struct("SystemTableExecutionPlan")
-                .field("projection", &self.projection)
-                .finish(),
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                let columns = self
+                    .projected_schema
+                    .fields()
+                    .iter()
+                    .map(|field| field.name().as_str())
+                    .collect::<Vec<_>>();
+                write!(
+                    f,
+                    "SystemTableExecutionPlan: table={}, projection=[{}]",
+                    self.name,
+                    columns.join(", ")
+                )?;
+
+                if !self.output_ordering.is_empty() {
+                    let ordering = self
+                        .output_ordering
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>();
+                    write!(f, ", output_ordering=[{}]", ordering.join(", "))?;
+                }
+
+                Ok(())
+            }
         }
     }
 }
 
-struct SystemTableStream {
-    projected_schema: SchemaRef,
-    projection: Option<Vec<usize>>,
-    batches: BatchIterator,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::{
+        array::{ArrayRef, Int64Array},
+        datatypes::{DataType, Field, Schema},
+    };
+    use arrow_util::assert_batches_eq;
+    use datafusion::{
+        physical_plan::{collect, displayable},
+        prelude::SessionContext,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Produces batches of two rows, counting the batches pulled from it and the columns built.
+    struct CountingTable {
+        schema: SchemaRef,
+        num_batches: usize,
+        honor_projection: bool,
+        pulled: Arc<AtomicUsize>,
+        materialized: Arc<AtomicUsize>,
+    }
 
-impl RecordBatchStream for SystemTableStream {
-    fn schema(&self) -> SchemaRef {
-        Arc::clone(&self.projected_schema)
+    impl CountingTable {
+        fn new(num_batches: usize) -> Self {
+            Self {
+                schema: Arc::new(Schema::new(vec![
+                    Field::new("a", DataType::Int64, false),
+                    Field::new("b", DataType::Int64, false),
+                ])),
+                num_batches,
+                honor_projection: false,
+                pulled: Default::default(),
+                materialized: Default::default(),
+            }
+        }
+
+        /// Only build the projected columns.
+        fn honoring_projection(self) -> Self {
+            Self {
+                honor_projection: true,
+                ..self
+            }
+        }
     }
-}
 
-impl futures::Stream for SystemTableStream {
-    type Item = Result<RecordBatch, DataFusionError>;
+    #[async_trait]
+    impl IoxSystemTable for CountingTable {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn schema(&self) -> SchemaRef {
+            Arc::clone(&self.schema)
+        }
 
-    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Poll::Ready(self.batches.next().map(|maybe_batch| {
-            let batch = maybe_batch?;
-            match &self.projection {
-                Some(projection) => Ok(batch.project(projection)?),
-                None => Ok(batch),
+        async fn scan(
+            &self,
+            _batch_size: usize,
+            projection: Option<&[usize]>,
+        ) -> ArrowResult<BatchIterator> {
+            let indices = match projection {
+                Some(projection) if self.honor_projection => projection.to_vec(),
+                _ => (0..self.schema.fields().len()).collect(),
+            };
+            let schema = Arc::new(self.schema.project(&indices)?);
+            let pulled = Arc::clone(&self.pulled);
+            let materialized = Arc::clone(&self.materialized);
+            Ok(Box::new((0..self.num_batches as i64).map(move |i| {
+                pulled.fetch_add(1, Ordering::SeqCst);
+                let columns = indices
+                    .iter()
+                    .map(|&col| -> ArrayRef {
+                        materialized.fetch_add(1, Ordering::SeqCst);
+                        // `a` counts up, `b` counts down
+                        let sign = if col == 0 { 1 } else { -1 };
+                        Arc::new(Int64Array::from(vec![sign * 2 * i, sign * (2 * i + 1)]))
+                    })
+                    .collect();
+                RecordBatch::try_new(Arc::clone(&schema), columns)
+            })))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_limit() {
+        let table = Arc::new(CountingTable::new(10));
+        let provider = SystemTableProvider::new(Arc::clone(&table));
+        let ctx = SessionContext::new();
+
+        // the last batch is truncated, and no further batches are pulled
+        let plan = provider
+            .scan(&ctx.state(), Some(&vec![1]), &[], Some(3))
+            .await
+            .unwrap();
+        let batches = collect(plan, ctx.task_ctx()).await.unwrap();
+        assert_batches_eq!(
+            [
+                "+----+", //
+                "| b  |", "+----+", "| 0  |", "| -1 |", "| -2 |", "+----+",
+            ],
+            &batches
+        );
+        assert_eq!(table.pulled.load(Ordering::SeqCst), 2);
+
+        // a limit that ends on a batch boundary doesn't pull the next batch
+        table.pulled.store(0, Ordering::SeqCst);
+        let plan = provider
+            .scan(&ctx.state(), None, &[], Some(4))
+            .await
+            .unwrap();
+        let batches = collect(plan, ctx.task_ctx()).await.unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+        assert_eq!(table.pulled.load(Ordering::SeqCst), 2);
+
+        // without a limit, all batches are pulled
+        table.pulled.store(0, Ordering::SeqCst);
+        let plan = provider.scan(&ctx.state(), None, &[], None).await.unwrap();
+        let batches = collect(plan, ctx.task_ctx()).await.unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 20);
+        assert_eq!(table.pulled.load(Ordering::SeqCst), 10);
+    }
+
+    #[tokio::test]
+    async fn test_projection() {
+        let ctx = SessionContext::new();
+        let expected = [
+            "+----+", //
+            "| b  |", "+----+", "| 0  |", "| -1 |", "| -2 |", "| -3 |", "+----+",
+        ];
+
+        // only the projected column is built
+        let table = Arc::new(CountingTable::new(2).honoring_projection());
+        let provider = SystemTableProvider::new(Arc::clone(&table));
+        let plan = provider
+            .scan(&ctx.state(), Some(&vec![1]), &[], None)
+            .await
+            .unwrap();
+        let batches = collect(plan, ctx.task_ctx()).await.unwrap();
+        assert_batches_eq!(expected, &batches);
+        assert_eq!(table.materialized.load(Ordering::SeqCst), 2);
+
+        // tables ignoring the projection build all columns, which are projected afterwards
+        let table = Arc::new(CountingTable::new(2));
+        let provider = SystemTableProvider::new(Arc::clone(&table));
+        let plan = provider
+            .scan(&ctx.state(), Some(&vec![1]), &[], None)
+            .await
+            .unwrap();
+        let batches = collect(plan, ctx.task_ctx()).await.unwrap();
+        assert_batches_eq!(expected, &batches);
+        assert_eq!(table.materialized.load(Ordering::SeqCst), 4);
+
+        // reordering columns works either way
+        for table in [
+            CountingTable::new(1).honoring_projection(),
+            CountingTable::new(1),
+        ] {
+            let provider = SystemTableProvider::new(Arc::new(table));
+            let plan = provider
+                .scan(&ctx.state(), Some(&vec![1, 0]), &[], None)
+                .await
+                .unwrap();
+            let batches = collect(plan, ctx.task_ctx()).await.unwrap();
+            assert_batches_eq!(
+                [
+                    "+----+---+",
+                    "| b  | a |",
+                    "+----+---+",
+                    "| 0  | 0 |",
+                    "| -1 | 1 |",
+                    "+----+---+",
+                ],
+                &batches
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_display() {
+        let provider = SystemTableProvider::new(Arc::new(CountingTable::new(10)));
+        let ctx = SessionContext::new();
+
+        let plan = provider.scan(&ctx.state(), None, &[], None).await.unwrap();
+        assert_eq!(
+            displayable(plan.as_ref()).indent(false).to_string(),
+            "SystemTableExecutionPlan: table=counting, projection=[a, b]\n",
+        );
+
+        let plan = provider
+            .scan(&ctx.state(), Some(&vec![1]), &[], Some(3))
+            .await
+            .unwrap();
+        assert_eq!(
+            displayable(plan.as_ref()).indent(true).to_string(),
+            "SystemTableExecutionPlan: table=counting, projection=[b]\n",
+        );
+        assert_eq!(
+            format!("{plan:?}"),
+            "SystemTableExecutionPlan: table=counting, projection=[b]",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_statistics() {
+        let time_provider = Arc::new(iox_time::MockProvider::new(
+            iox_time::Time::from_timestamp_nanos(0),
+        ));
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            time_provider,
+            &metric::Registry::default(),
+        ));
+        let id1 = NamespaceId::new(1);
+        let id2 = NamespaceId::new(2);
+        query_log.push(id1, "sql", Box::new("select * from foo"), None);
+        query_log.push(id1, "sql", Box::new("select * from bar"), None);
+        query_log.push(id2, "sql", Box::new("select * from baz"), None);
+
+        let provider = SystemTableProvider::new(Arc::new(queries::QueriesTable::new(
+            Arc::clone(&query_log),
+            Some(id1),
+        )));
+        let ctx = SessionContext::new();
+
+        // exact row count of the namespace
+        let plan = provider.scan(&ctx.state(), None, &[], None).await.unwrap();
+        let stats = plan.statistics();
+        assert_eq!(stats.num_rows, Some(2));
+        assert!(stats.is_exact);
+
+        // null counts are known for non-nullable columns
+        let schema = plan.schema();
+        let column_statistics = stats.column_statistics.unwrap();
+        assert_eq!(column_statistics.len(), schema.fields().len());
+        for (field, column_statistics) in schema.fields().iter().zip(column_statistics) {
+            let expected = (!field.is_nullable()).then_some(0);
+            assert_eq!(column_statistics.null_count, expected, "{}", field.name());
+        }
+
+        // the row count is capped by the limit
+        let plan = provider
+            .scan(&ctx.state(), Some(&vec![0]), &[], Some(1))
+            .await
+            .unwrap();
+        let stats = plan.statistics();
+        assert_eq!(stats.num_rows, Some(1));
+        assert!(stats.is_exact);
+        assert_eq!(stats.column_statistics.unwrap().len(), 1);
+
+        // filters make the row count an upper bound
+        let filter = datafusion::prelude::col("query_type").eq(datafusion::prelude::lit("sql"));
+        let plan = provider
+            .scan(&ctx.state(), None, &[filter], None)
+            .await
+            .unwrap();
+        let stats = plan.statistics();
+        assert_eq!(stats.num_rows, Some(2));
+        assert!(!stats.is_exact);
+
+        // tables without statistics
+        let provider = SystemTableProvider::new(Arc::new(CountingTable::new(10)));
+        let plan = provider.scan(&ctx.state(), None, &[], None).await.unwrap();
+        let stats = plan.statistics();
+        assert_eq!(stats.num_rows, None);
+        assert!(!stats.is_exact);
+    }
+
+    #[tokio::test]
+    async fn test_policy() {
+        let catalog = iox_tests::TestCatalog::new();
+        let catalog_cache = crate::QuerierCatalogCache::new_testing(
+            catalog.catalog(),
+            catalog.time_provider(),
+            catalog.metric_registry(),
+            catalog.object_store(),
+            &tokio::runtime::Handle::current(),
+        );
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            catalog.time_provider(),
+            &metric::Registry::default(),
+        ));
+        let table_names = |policy: &SystemTablePolicy, include_debug_info: bool| {
+            SystemSchemaProvider::new(
+                catalog.catalog(),
+                catalog_cache.introspection(),
+                catalog.metric_registry(),
+                Arc::clone(&query_log),
+                None,
+                NamespaceId::new(1),
+                policy,
+                include_debug_info,
+            )
+            .table_names()
+        };
+        let all = vec![
+            "caches",
+            "compactions",
+            "ingesters",
+            "object_store_metrics",
+            "parquet_files",
+            "queries",
+        ];
+
+        // by default, only the tables scoped to the namespace are debug tables
+        let policy = SystemTablePolicy::default();
+        for (table, visibility) in [
+            ("caches", SystemTableVisibility::Hidden),
+            ("compactions", SystemTableVisibility::Debug),
+            ("ingesters", SystemTableVisibility::Hidden),
+            ("object_store_metrics", SystemTableVisibility::Hidden),
+            ("parquet_files", SystemTableVisibility::Debug),
+            ("queries", SystemTableVisibility::Debug),
+        ] {
+            assert_eq!(policy.visibility(table), visibility, "{table}");
+        }
+        assert_eq!(
+            table_names(&policy, true),
+            vec!["compactions", "parquet_files", "queries"]
+        );
+        assert!(table_names(&policy, false).is_empty());
+
+        // hidden tables can be enabled explicitly
+        let policy = SystemTablePolicy::new(
+            all.iter()
+                .map(|table| (*table, SystemTableVisibility::Debug)),
+        );
+        assert_eq!(table_names(&policy, true), all);
+        assert!(table_names(&policy, false).is_empty());
+
+        // later entries win, unknown tables are ignored
+        let policy = SystemTablePolicy::new([
+            ("queries", SystemTableVisibility::Public),
+            ("caches", SystemTableVisibility::Public),
+            ("caches", SystemTableVisibility::Hidden),
+            ("ingesters", SystemTableVisibility::Debug),
+            ("foo", SystemTableVisibility::Public),
+        ]);
+        assert_eq!(policy.visibility("queries"), SystemTableVisibility::Public);
+        assert_eq!(policy.visibility("caches"), SystemTableVisibility::Hidden);
+        assert_eq!(
+            policy.visibility("parquet_files"),
+            SystemTableVisibility::Debug
+        );
+        assert_eq!(policy.visibility("foo"), SystemTableVisibility::Hidden);
+        assert_eq!(
+            table_names(&policy, true),
+            vec!["compactions", "ingesters", "parquet_files", "queries"]
+        );
+        assert_eq!(table_names(&policy, false), vec!["queries"]);
+    }
+
+    #[tokio::test]
+    async fn test_admin() {
+        let catalog = iox_tests::TestCatalog::new();
+        let catalog_cache = crate::QuerierCatalogCache::new_testing(
+            catalog.catalog(),
+            catalog.time_provider(),
+            catalog.metric_registry(),
+            catalog.object_store(),
+            &tokio::runtime::Handle::current(),
+        );
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            catalog.time_provider(),
+            &metric::Registry::default(),
+        ));
+        query_log.push(NamespaceId::new(1), "sql", Box::new("select 1"), None);
+        query_log.push(NamespaceId::new(2), "sql", Box::new("select 2"), None);
+
+        let policy = SystemTablePolicy::new([("queries", SystemTableVisibility::Public)])
+            .with_admin_namespace("ops");
+        assert!(policy.is_admin_namespace("ops"));
+        assert!(!policy.is_admin_namespace("ns"));
+        assert!(!SystemTablePolicy::default().is_admin_namespace("ops"));
+
+        let ctx = SessionContext::new();
+        let query = |provider: SystemSchemaProvider| {
+            let ctx = &ctx;
+            async move {
+                let table = provider.table(QUERIES_TABLE).await.unwrap();
+                let schema = table.schema();
+                let projection = ["namespace_id", "query_text"]
+                    .into_iter()
+                    .filter_map(|name| schema.index_of(name).ok())
+                    .collect::<Vec<_>>();
+                let plan = table
+                    .scan(&ctx.state(), Some(&projection), &[], None)
+                    .await
+                    .unwrap();
+                collect(plan, ctx.task_ctx()).await.unwrap()
             }
-        }))
+        };
+
+        // the admin schema only has the queries table, listing all namespaces
+        let provider = SystemSchemaProvider::new_admin(Arc::clone(&query_log), &policy, false);
+        assert_eq!(provider.table_names(), vec![QUERIES_TABLE]);
+        assert_batches_eq!(
+            [
+                "+--------------+------------+",
+                "| namespace_id | query_text |",
+                "+--------------+------------+",
+                "| 2            | select 2   |",
+                "| 1            | select 1   |",
+                "+--------------+------------+",
+            ],
+            &query(provider).await
+        );
+
+        // namespace-scoped schemas only see their own queries
+        let provider = SystemSchemaProvider::new(
+            catalog.catalog(),
+            catalog_cache.introspection(),
+            catalog.metric_registry(),
+            Arc::clone(&query_log),
+            None,
+            NamespaceId::new(2),
+            &policy,
+            false,
+        );
+        assert_batches_eq!(
+            [
+                "+------------+",
+                "| query_text |",
+                "+------------+",
+                "| select 2   |",
+                "+------------+",
+            ],
+            &query(provider).await
+        );
+
+        // the policy still applies
+        let policy = SystemTablePolicy::default().with_admin_namespace("ops");
+        let provider = SystemSchemaProvider::new_admin(Arc::clone(&query_log), &policy, false);
+        assert!(provider.table_names().is_empty());
     }
 }
@@ -0,0 +1,361 @@
+use crate::system_tables::{BatchIterator, IoxSystemTable};
+use arrow::{
+    array::{ArrayRef, DurationNanosecondArray, StringArray, UInt64Array},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    error::Result,
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use metric::{HistogramObservation, Instrument, Metric, Observation, RawReporter, U64Counter};
+use observability_deps::tracing::error;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+/// Name of the duration histogram of the object store requests, broken down by `op` and `result`.
+const OP_DURATION_METRIC: &str = "object_store_op_duration";
+
+/// Name of the counter of the transferred bytes, broken down by `op`.
+const TRANSFER_BYTES_METRIC: &str = "object_store_transfer_bytes";
+
+/// Quantiles of the request duration, as `(column name, quantile)`.
+const QUANTILES: [(&str, f64); 3] = [
+    ("duration_p50", 0.5),
+    ("duration_p90", 0.9),
+    ("duration_p99", 0.99),
+];
+
+/// Implementation of system.object_store_metrics table
+#[derive(Debug)]
+pub(super) struct ObjectStoreMetricsTable {
+    schema: SchemaRef,
+    metric_registry: Arc<metric::Registry>,
+}
+
+impl ObjectStoreMetricsTable {
+    pub(super) fn new(metric_registry: Arc<metric::Registry>) -> Self {
+        Self {
+            schema: object_store_metrics_schema(),
+            metric_registry,
+        }
+    }
+}
+
+#[async_trait]
+impl IoxSystemTable for ObjectStoreMetricsTable {
+    fn name(&self) -> &'static str {
+        super::OBJECT_STORE_METRICS_TABLE
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    async fn scan(
+        &self,
+        batch_size: usize,
+        _projection: Option<&[usize]>,
+    ) -> Result<BatchIterator> {
+        let schema = self.schema();
+        let stats = op_stats(&self.metric_registry);
+
+        let mut offset = 0;
+        Ok(Box::new(std::iter::from_fn(move || {
+            if offset >= stats.len() {
+                return None;
+            }
+
+            let len = batch_size.min(stats.len() - offset);
+            match from_op_stats(Arc::clone(&schema), &stats[offset..offset + len]) {
+                Ok(batch) => {
+                    offset += len;
+                    Some(Ok(batch))
+                }
+                Err(e) => {
+                    error!("Error system.object_store_metrics table: {:?}", e);
+                    Some(Err(e))
+                }
+            }
+        })))
+    }
+}
+
+/// Requests of a single object store operation, e.g. `get`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct OpStats {
+    op: String,
+    success_count: u64,
+    error_count: u64,
+    transfer_bytes: Option<u64>,
+    duration: Option<HistogramObservation<Duration>>,
+}
+
+/// Pivot the object store metrics in `metric_registry` into one [`OpStats`] per operation,
+/// ordered by operation.
+///
+/// Nothing is returned if the object store is not instrumented.
+fn op_stats(metric_registry: &metric::Registry) -> Vec<OpStats> {
+    let mut stats: BTreeMap<String, OpStats> = BTreeMap::new();
+
+    for (attributes, observation) in
+        observations::<Metric<metric::DurationHistogram>>(metric_registry, OP_DURATION_METRIC)
+    {
+        let (Some(op), Observation::DurationHistogram(histogram)) =
+            (attribute(&attributes, "op"), observation)
+        else {
+            continue;
+        };
+        let entry = stats_for(&mut stats, op);
+
+        match attribute(&attributes, "result") {
+            Some("success") => entry.success_count += histogram.sample_count(),
+            Some("error") => entry.error_count += histogram.sample_count(),
+            _ => {}
+        }
+        entry.duration = Some(match entry.duration.take() {
+            Some(duration) => merge_histograms(duration, histogram),
+            None => histogram,
+        });
+    }
+
+    for (attributes, observation) in
+        observations::<Metric<U64Counter>>(metric_registry, TRANSFER_BYTES_METRIC)
+    {
+        let (Some(op), Observation::U64Counter(bytes)) =
+            (attribute(&attributes, "op"), observation)
+        else {
+            continue;
+        };
+        let entry = stats_for(&mut stats, op);
+        entry.transfer_bytes = Some(entry.transfer_bytes.unwrap_or_default() + bytes);
+    }
+
+    stats.into_values().collect()
+}
+
+fn stats_for<'a>(stats: &'a mut BTreeMap<String, OpStats>, op: &str) -> &'a mut OpStats {
+    stats.entry(op.to_owned()).or_insert_with(|| OpStats {
+        op: op.to_owned(),
+        ..Default::default()
+    })
+}
+
+/// All observations of the given instrument, if it is registered.
+fn observations<I>(
+    metric_registry: &metric::Registry,
+    name: &'static str,
+) -> Vec<(metric::Attributes, Observation)>
+where
+    I: Instrument + Clone + 'static,
+{
+    let Some(instrument) = metric_registry.get_instrument::<I>(name) else {
+        return vec![];
+    };
+
+    let mut reporter = RawReporter::default();
+    instrument.report(&mut reporter);
+    reporter
+        .metric(name)
+        .map(|set| set.observations.clone())
+        .unwrap_or_default()
+}
+
+fn attribute<'a>(attributes: &'a metric::Attributes, key: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(k, _)| **k == key)
+        .map(|(_, v)| v.as_ref())
+}
+
+/// Add up two histograms with the same buckets.
+fn merge_histograms(
+    mut a: HistogramObservation<Duration>,
+    b: HistogramObservation<Duration>,
+) -> HistogramObservation<Duration> {
+    a.total += b.total;
+    for (bucket_a, bucket_b) in a.buckets.iter_mut().zip(b.buckets) {
+        bucket_a.count += bucket_b.count;
+    }
+    a
+}
+
+/// Estimate the quantile `q` as the upper bound of the bucket it falls into.
+///
+/// Returns `None` if nothing was recorded or if the bucket is unbounded.
+fn quantile(histogram: &HistogramObservation<Duration>, q: f64) -> Option<i64> {
+    let sample_count = histogram.sample_count();
+    if sample_count == 0 {
+        return None;
+    }
+
+    let rank = ((sample_count as f64) * q).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for bucket in &histogram.buckets {
+        seen += bucket.count;
+        if seen >= rank {
+            return i64::try_from(bucket.le.as_nanos()).ok();
+        }
+    }
+    None
+}
+
+fn object_store_metrics_schema() -> SchemaRef {
+    let mut columns = vec![
+        Field::new("op", DataType::Utf8, false),
+        Field::new("success_count", DataType::UInt64, false),
+        Field::new("error_count", DataType::UInt64, false),
+        Field::new("transfer_bytes", DataType::UInt64, true),
+    ];
+    columns.extend(
+        QUANTILES
+            .iter()
+            .map(|(name, _)| Field::new(*name, DataType::Duration(TimeUnit::Nanosecond), true)),
+    );
+
+    Arc::new(Schema::new(columns))
+}
+
+fn from_op_stats(schema: SchemaRef, stats: &[OpStats]) -> Result<RecordBatch> {
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.op.as_str()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.success_count))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| Some(s.error_count))
+                .collect::<UInt64Array>(),
+        ),
+        Arc::new(
+            stats
+                .iter()
+                .map(|s| s.transfer_bytes)
+                .collect::<UInt64Array>(),
+        ),
+    ];
+    for (_, q) in QUANTILES {
+        columns.push(Arc::new(
+            stats
+                .iter()
+                .map(|s| s.duration.as_ref().and_then(|d| quantile(d, q)))
+                .collect::<DurationNanosecondArray>(),
+        ));
+    }
+
+    RecordBatch::try_new(schema, columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Array;
+    use arrow_util::assert_batches_eq;
+    use bytes::Bytes;
+    use iox_time::SystemProvider;
+    use object_store::{memory::InMemory, path::Path, ObjectStore};
+    use object_store_metrics::ObjectStoreMetrics;
+
+    #[tokio::test]
+    async fn test_object_store_metrics() {
+        let metric_registry = Arc::new(metric::Registry::new());
+        let table = ObjectStoreMetricsTable::new(Arc::clone(&metric_registry));
+        let scan = || {
+            let table = &table;
+            async move {
+                table
+                    .scan(10, None)
+                    .await
+                    .unwrap()
+                    .collect::<Result<Vec<_>>>()
+                    .unwrap()
+            }
+        };
+
+        // the object store is not instrumented yet
+        assert!(scan().await.is_empty());
+
+        let store = ObjectStoreMetrics::new(
+            Arc::new(InMemory::new()),
+            Arc::new(SystemProvider::new()),
+            &metric_registry,
+        );
+        let path = Path::from("foo");
+        store
+            .put(&path, Bytes::from_static(b"data_foo"))
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            store.get(&path).await.unwrap().bytes().await.unwrap();
+        }
+        store.get(&Path::from("bar")).await.unwrap_err();
+
+        let batches = scan().await;
+        let counts = batches
+            .iter()
+            .map(|batch| batch.project(&[0, 1, 2, 3]).unwrap())
+            .collect::<Vec<_>>();
+        assert_batches_eq!(
+            [
+                "+-----------+---------------+-------------+----------------+",
+                "| op        | success_count | error_count | transfer_bytes |",
+                "+-----------+---------------+-------------+----------------+",
+                "| delete    | 0             | 0           |                |",
+                "| get       | 2             | 1           | 16             |",
+                "| get_range | 0             | 0           | 0              |",
+                "| head      | 0             | 0           |                |",
+                "| list      | 0             | 0           |                |",
+                "| put       | 1             | 0           | 8              |",
+                "+-----------+---------------+-------------+----------------+",
+            ],
+            &counts
+        );
+
+        // durations are only known for operations that were performed
+        let p50 = batches[0]
+            .column(4)
+            .as_any()
+            .downcast_ref::<DurationNanosecondArray>()
+            .unwrap();
+        let performed = (0..p50.len()).map(|i| p50.is_valid(i)).collect::<Vec<_>>();
+        assert_eq!(performed, vec![false, true, false, false, false, true]);
+    }
+
+    #[test]
+    fn test_quantile() {
+        let histogram = HistogramObservation {
+            total: Duration::from_millis(1_000),
+            buckets: vec![
+                metric::ObservationBucket {
+                    le: Duration::from_millis(1),
+                    count: 50,
+                },
+                metric::ObservationBucket {
+                    le: Duration::from_millis(10),
+                    count: 45,
+                },
+                metric::ObservationBucket {
+                    le: Duration::from_nanos(u64::MAX),
+                    count: 5,
+                },
+            ],
+        };
+
+        assert_eq!(quantile(&histogram, 0.5), Some(1_000_000));
+        assert_eq!(quantile(&histogram, 0.9), Some(10_000_000));
+        // the last bucket is unbounded
+        assert_eq!(quantile(&histogram, 0.99), None);
+
+        let empty = HistogramObservation {
+            total: Duration::ZERO,
+            buckets: vec![],
+        };
+        assert_eq!(quantile(&empty, 0.5), None);
+    }
+}
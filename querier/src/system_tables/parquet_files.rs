@@ -0,0 +1,179 @@
+use crate::system_tables::{BatchIterator, IoxSystemTable};
+use arrow::{
+    array::{ArrayRef, Int16Array, Int64Array, StringArray, TimestampNanosecondArray},
+    datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
+    error::{ArrowError, Result},
+    record_batch::RecordBatch,
+};
+use async_trait::async_trait;
+use data_types::{NamespaceId, ParquetFile};
+use iox_catalog::interface::Catalog;
+use observability_deps::tracing::error;
+use std::sync::Arc;
+
+/// Implementation of system.parquet_files table
+#[derive(Debug)]
+pub(super) struct ParquetFilesTable {
+    schema: SchemaRef,
+    catalog: Arc<dyn Catalog>,
+    namespace_id: NamespaceId,
+}
+
+impl ParquetFilesTable {
+    pub(super) fn new(catalog: Arc<dyn Catalog>, namespace_id: NamespaceId) -> Self {
+        Self {
+            schema: parquet_files_schema(),
+            catalog,
+            namespace_id,
+        }
+    }
+}
+
+#[async_trait]
+impl IoxSystemTable for ParquetFilesTable {
+    fn name(&self) -> &'static str {
+        super::PARQUET_FILES_TABLE
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    async fn scan(
+        &self,
+        batch_size: usize,
+        _projection: Option<&[usize]>,
+    ) -> Result<BatchIterator> {
+        let schema = self.schema();
+
+        let mut files = self
+            .catalog
+            .repositories()
+            .await
+            .parquet_files()
+            .list_by_namespace_not_to_delete(self.namespace_id)
+            .await
+            .map_err(|e| {
+                error!(%e, "Error listing parquet files for system.parquet_files table");
+                ArrowError::ExternalError(Box::new(e))
+            })?;
+        files.sort_by_key(|f| f.id);
+
+        let mut offset = 0;
+        Ok(Box::new(std::iter::from_fn(move || {
+            if offset >= files.len() {
+                return None;
+            }
+
+            let len = batch_size.min(files.len() - offset);
+            match from_parquet_files(Arc::clone(&schema), &files[offset..offset + len]) {
+                Ok(batch) => {
+                    offset += len;
+                    Some(Ok(batch))
+                }
+                Err(e) => {
+                    error!("Error system.parquet_files table: {:?}", e);
+                    Some(Err(e))
+                }
+            }
+        })))
+    }
+}
+
+fn parquet_files_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("table_id", DataType::Int64, false),
+        Field::new("partition_id", DataType::Utf8, false),
+        Field::new("compaction_level", DataType::Int16, false),
+        Field::new(
+            "min_time",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false,
+        ),
+        Field::new(
+            "max_time",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false,
+        ),
+        Field::new("file_size_bytes", DataType::Int64, false),
+        Field::new("row_count", DataType::Int64, false),
+        Field::new(
+            "created_at",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            false,
+        ),
+        Field::new(
+            "to_delete",
+            DataType::Timestamp(TimeUnit::Nanosecond, None),
+            true,
+        ),
+    ]))
+}
+
+fn from_parquet_files(schema: SchemaRef, files: &[ParquetFile]) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.id.get()))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.table_id.get()))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.partition_id.to_string()))
+                .collect::<StringArray>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.compaction_level as i16))
+                .collect::<Int16Array>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.min_time.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.max_time.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.file_size_bytes))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.row_count))
+                .collect::<Int64Array>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| Some(f.created_at.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+        Arc::new(
+            files
+                .iter()
+                .map(|f| f.to_delete.map(|ts| ts.get()))
+                .collect::<TimestampNanosecondArray>(),
+        ),
+    ];
+
+    RecordBatch::try_new(schema, columns)
+}
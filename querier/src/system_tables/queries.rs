@@ -1,19 +1,26 @@
 use crate::{
     query_log::{QueryLog, QueryLogEntry},
-    system_tables::{BatchIterator, IoxSystemTable},
+    system_tables::{BatchIterator, IoxSystemTable, SystemTableStatistics},
 };
 use arrow::{
     array::{
         ArrayRef, BooleanArray, DurationNanosecondArray, Int64Array, StringArray,
-        TimestampNanosecondArray,
+        TimestampNanosecondArray, UInt64Array,
     },
+    compute::SortOptions,
     datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
     error::Result,
-    record_batch::RecordBatch,
+    record_batch::{RecordBatch, RecordBatchOptions},
 };
+use async_trait::async_trait;
 use data_types::NamespaceId;
+use datafusion::{
+    logical_expr::{expr::Cast, expr::TryCast, BinaryExpr, Operator},
+    prelude::Expr,
+    scalar::ScalarValue,
+};
 use observability_deps::tracing::error;
-use std::{collections::VecDeque, sync::Arc};
+use std::sync::Arc;
 
 /// Implementation of system.queries table
 #[derive(Debug)]
@@ -33,34 +40,87 @@ impl QueriesTable {
     }
 }
 
+#[async_trait]
 impl IoxSystemTable for QueriesTable {
+    fn name(&self) -> &'static str {
+        super::QUERIES_TABLE
+    }
+
     fn schema(&self) -> SchemaRef {
         Arc::clone(&self.schema)
     }
 
-    fn scan(&self, batch_size: usize) -> Result<BatchIterator> {
-        let schema = self.schema();
+    async fn scan(&self, batch_size: usize, projection: Option<&[usize]>) -> Result<BatchIterator> {
+        self.scan_with_filters(batch_size, projection, &[]).await
+    }
+
+    fn statistics(&self) -> SystemTableStatistics {
+        let entries = self.query_log.entries_with_running();
+        let num_rows = match self.namespace_id_filter {
+            Some(namespace_id) => entries
+                .iter()
+                .filter(|entry| entry.namespace_id == namespace_id)
+                .count(),
+            None => entries.len(),
+        };
+
+        SystemTableStatistics {
+            num_rows: Some(num_rows),
+            total_byte_size: None,
+            is_exact: true,
+        }
+    }
+
+    fn sort_order(&self) -> Vec<(&'static str, SortOptions)> {
+        // matches `ORDER BY issue_time DESC`
+        vec![(
+            "issue_time",
+            SortOptions {
+                descending: true,
+                nulls_first: true,
+            },
+        )]
+    }
 
-        let mut entries = self.query_log.entries();
+    async fn scan_with_filters(
+        &self,
+        batch_size: usize,
+        projection: Option<&[usize]>,
+        filters: &[Expr],
+    ) -> Result<BatchIterator> {
+        // only build the requested columns
+        let schema = match projection {
+            Some(projection) => Arc::new(self.schema.project(projection)?),
+            None => self.schema(),
+        };
+
+        let entry_filters = filters
+            .iter()
+            .filter_map(|expr| EntryFilter::try_new(expr, self.namespace_id_filter.is_none()))
+            .collect::<Vec<_>>();
+
+        let evicted_count = self.query_log.evicted_count();
+        let mut entries = self.query_log.entries_with_running();
         if let Some(namespace_id) = self.namespace_id_filter {
             entries.retain(|entry| entry.namespace_id == namespace_id);
         }
+        entries.retain(|entry| entry_filters.iter().all(|filter| filter.matches(entry)));
+
+        // newest first, see `sort_order`. The log is only roughly ordered by issue time, e.g.
+        // queries issued concurrently may be pushed in any order.
+        let mut entries = Vec::from(entries);
+        entries.reverse();
+        entries.sort_by(|a, b| b.issue_time.cmp(&a.issue_time));
 
         let mut offset = 0;
-        let namespace_id_filter = self.namespace_id_filter;
         Ok(Box::new(std::iter::from_fn(move || {
             if offset >= entries.len() {
                 return None;
             }
 
             let len = batch_size.min(entries.len() - offset);
-            match from_query_log_entries(
-                Arc::clone(&schema),
-                &entries,
-                offset,
-                len,
-                namespace_id_filter.is_none(),
-            ) {
+            match from_query_log_entries(Arc::clone(&schema), &entries, offset, len, evicted_count)
+            {
                 Ok(batch) => {
                     offset += len;
                     Some(Ok(batch))
@@ -74,6 +134,77 @@ impl IoxSystemTable for QueriesTable {
     }
 }
 
+/// A filter on the [`QueriesTable`] that can be evaluated on the query log entries directly.
+#[derive(Debug, Clone, Copy)]
+enum EntryFilter {
+    IssueTime(Operator, i64),
+    NamespaceId(Operator, i64),
+}
+
+impl EntryFilter {
+    /// Convert a comparison of the `issue_time` or `namespace_id` column with a literal into a
+    /// filter, or return `None` for unsupported expressions.
+    fn try_new(expr: &Expr, include_namespace_id: bool) -> Option<Self> {
+        let Expr::BinaryExpr(BinaryExpr { left, op, right }) = expr else {
+            return None;
+        };
+        let (column, op, value) = match (column_name(left), column_name(right)) {
+            (Some(column), None) => (column, *op, right.as_ref()),
+            (None, Some(column)) => (column, op.swap()?, left.as_ref()),
+            _ => return None,
+        };
+        if !matches!(
+            op,
+            Operator::Eq
+                | Operator::NotEq
+                | Operator::Lt
+                | Operator::LtEq
+                | Operator::Gt
+                | Operator::GtEq
+        ) {
+            return None;
+        }
+
+        match (column, value) {
+            ("issue_time", Expr::Literal(ScalarValue::TimestampNanosecond(Some(v), _))) => {
+                Some(Self::IssueTime(op, *v))
+            }
+            ("namespace_id", Expr::Literal(ScalarValue::Int64(Some(v))))
+                if include_namespace_id =>
+            {
+                Some(Self::NamespaceId(op, *v))
+            }
+            _ => None,
+        }
+    }
+
+    fn matches(&self, entry: &QueryLogEntry) -> bool {
+        let (op, actual, expected) = match self {
+            Self::IssueTime(op, v) => (op, entry.issue_time.timestamp_nanos(), v),
+            Self::NamespaceId(op, v) => (op, entry.namespace_id.get(), v),
+        };
+
+        match op {
+            Operator::Eq => actual == *expected,
+            Operator::NotEq => actual != *expected,
+            Operator::Lt => actual < *expected,
+            Operator::LtEq => actual <= *expected,
+            Operator::Gt => actual > *expected,
+            Operator::GtEq => actual >= *expected,
+            _ => true,
+        }
+    }
+}
+
+/// Name of the column the expression refers to, looking through casts.
+fn column_name(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Column(column) => Some(&column.name),
+        Expr::Cast(Cast { expr, .. }) | Expr::TryCast(TryCast { expr, .. }) => column_name(expr),
+        _ => None,
+    }
+}
+
 fn queries_schema(include_namespace_id: bool) -> SchemaRef {
     let mut columns = vec![];
     if include_namespace_id {
@@ -94,6 +225,21 @@ fn queries_schema(include_namespace_id: bool) -> SchemaRef {
         ),
         Field::new("success", DataType::Boolean, false),
         Field::new("trace_id", DataType::Utf8, true),
+        Field::new("completed", DataType::Boolean, false),
+        Field::new("error", DataType::Utf8, true),
+        Field::new(
+            "plan_duration",
+            DataType::Duration(TimeUnit::Nanosecond),
+            true,
+        ),
+        Field::new(
+            "execute_duration",
+            DataType::Duration(TimeUnit::Nanosecond),
+            true,
+        ),
+        Field::new("evicted_count", DataType::UInt64, false),
+        Field::new("query_text_truncated", DataType::Boolean, false),
+        Field::new("running", DataType::Boolean, false),
     ]);
 
     Arc::new(Schema::new(columns))
@@ -101,91 +247,98 @@ fn queries_schema(include_namespace_id: bool) -> SchemaRef {
 
 fn from_query_log_entries(
     schema: SchemaRef,
-    entries: &VecDeque<Arc<QueryLogEntry>>,
+    entries: &[Arc<QueryLogEntry>],
     offset: usize,
     len: usize,
-    include_namespace_id: bool,
+    evicted_count: u64,
 ) -> Result<RecordBatch> {
-    let mut columns: Vec<ArrayRef> = vec![];
-
-    if include_namespace_id {
-        columns.push(Arc::new(
-            entries
-                .iter()
-                .skip(offset)
-                .take(len)
-                .map(|e| Some(e.namespace_id.get()))
-                .collect::<Int64Array>(),
-        ));
-    }
-
-    columns.push(Arc::new(
-        entries
-            .iter()
-            .skip(offset)
-            .take(len)
-            .map(|e| e.issue_time)
-            .map(|ts| Some(ts.timestamp_nanos()))
-            .collect::<TimestampNanosecondArray>(),
-    ));
-
-    columns.push(Arc::new(
-        entries
-            .iter()
-            .skip(offset)
-            .take(len)
-            .map(|e| Some(&e.query_type))
-            .collect::<StringArray>(),
-    ));
-
-    columns.push(Arc::new(
-        entries
-            .iter()
-            .skip(offset)
-            .take(len)
-            .map(|e| Some(e.query_text.to_string()))
-            .collect::<StringArray>(),
-    ));
-
-    columns.push(Arc::new(
-        entries
-            .iter()
-            .skip(offset)
-            .take(len)
-            .map(|e| e.query_completed_duration().map(|d| d.as_nanos() as i64))
-            .collect::<DurationNanosecondArray>(),
-    ));
-
-    columns.push(Arc::new(
-        entries
-            .iter()
-            .skip(offset)
-            .take(len)
-            .map(|e| Some(e.success()))
-            .collect::<BooleanArray>(),
-    ));
-
-    columns.push(Arc::new(
-        entries
-            .iter()
-            .skip(offset)
-            .take(len)
-            .map(|e| e.trace_id.map(|x| format!("{:x}", x.0)))
-            .collect::<StringArray>(),
-    ));
-
-    RecordBatch::try_new(schema, columns)
+    let rows = || entries[offset..offset + len].iter();
+
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| -> ArrayRef {
+            match field.name().as_str() {
+                "namespace_id" => Arc::new(
+                    rows()
+                        .map(|e| Some(e.namespace_id.get()))
+                        .collect::<Int64Array>(),
+                ),
+                "issue_time" => Arc::new(
+                    rows()
+                        .map(|e| Some(e.issue_time.timestamp_nanos()))
+                        .collect::<TimestampNanosecondArray>(),
+                ),
+                "query_type" => {
+                    Arc::new(rows().map(|e| Some(&e.query_type)).collect::<StringArray>())
+                }
+                "query_text" => Arc::new(
+                    rows()
+                        .map(|e| Some(e.query_text.to_string()))
+                        .collect::<StringArray>(),
+                ),
+                "completed_duration" => Arc::new(
+                    rows()
+                        .map(|e| e.query_completed_duration().map(|d| d.as_nanos() as i64))
+                        .collect::<DurationNanosecondArray>(),
+                ),
+                "success" => Arc::new(rows().map(|e| Some(e.success())).collect::<BooleanArray>()),
+                "trace_id" => Arc::new(
+                    rows()
+                        .map(|e| e.trace_id.map(|x| format!("{:x}", x.0)))
+                        .collect::<StringArray>(),
+                ),
+                "completed" => Arc::new(
+                    rows()
+                        .map(|e| Some(e.completed()))
+                        .collect::<BooleanArray>(),
+                ),
+                "error" => Arc::new(rows().map(|e| e.error()).collect::<StringArray>()),
+                "plan_duration" => Arc::new(
+                    rows()
+                        .map(|e| e.plan_duration().map(|d| d.as_nanos() as i64))
+                        .collect::<DurationNanosecondArray>(),
+                ),
+                "execute_duration" => Arc::new(
+                    rows()
+                        .map(|e| e.execute_duration().map(|d| d.as_nanos() as i64))
+                        .collect::<DurationNanosecondArray>(),
+                ),
+                // the number of evicted entries is not specific to any entry, but repeated on each
+                // row so that users can tell that entries are missing
+                "evicted_count" => {
+                    Arc::new(rows().map(|_| Some(evicted_count)).collect::<UInt64Array>())
+                }
+                "query_text_truncated" => Arc::new(
+                    rows()
+                        .map(|e| Some(e.query_text_truncated))
+                        .collect::<BooleanArray>(),
+                ),
+                "running" => Arc::new(rows().map(|e| Some(e.running())).collect::<BooleanArray>()),
+                name => unreachable!("unknown column: {name}"),
+            }
+        })
+        .collect();
+
+    // the row count is needed when no columns are requested, e.g. for `COUNT(*)`
+    RecordBatch::try_new_with_options(
+        schema,
+        columns,
+        &RecordBatchOptions::new().with_row_count(Some(len)),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query_log::QueryTextConfig;
     use arrow_util::assert_batches_eq;
+    use datafusion::prelude::{col, lit, lit_timestamp_nano};
     use iox_time::{Time, TimeProvider};
     use trace::ctx::TraceId;
 
-    #[test]
-    fn test_from_query_log() {
+    #[tokio::test]
+    async fn test_from_query_log() {
         let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
         let time_provider = Arc::new(iox_time::MockProvider::new(now));
 
@@ -195,6 +348,7 @@ mod tests {
         let query_log = Arc::new(QueryLog::new(
             10,
             Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
         ));
         query_log.push(id1, "sql", Box::new("select * from foo"), None);
         time_provider.inc(std::time::Duration::from_secs(24 * 60 * 60));
@@ -208,17 +362,23 @@ mod tests {
 
         let table = QueriesTable::new(Arc::clone(&query_log), None);
 
+        // the first query runs for longer than the timeout, so it's no longer tracked as running
         let expected = vec![
-            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+",
-            "| namespace_id | issue_time           | query_type  | query_text        | completed_duration | success | trace_id |",
-            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+",
-            "| 1            | 1996-12-19T16:39:57Z | sql         | select * from foo |                    | false   |          |",
-            "| 1            | 1996-12-20T16:39:57Z | sql         | select * from bar |                    | false   |          |",
-            "| 2            | 1996-12-20T16:39:57Z | read_filter | json goop         |                    | false   | 45fe     |",
-            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+",
+            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| namespace_id | issue_time           | query_type  | query_text        | completed_duration | success | trace_id | completed | error | plan_duration | execute_duration | evicted_count | query_text_truncated | running |",
+            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| 2            | 1996-12-20T16:39:57Z | read_filter | json goop         |                    | false   | 45fe     | false     |       |               |                  | 0             | false                | true    |",
+            "| 1            | 1996-12-20T16:39:57Z | sql         | select * from bar |                    | false   |          | false     |       |               |                  | 0             | false                | true    |",
+            "| 1            | 1996-12-19T16:39:57Z | sql         | select * from foo |                    | false   |          | false     |       |               |                  | 0             | false                | false   |",
+            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
         ];
 
-        let entries = table.scan(3).unwrap().collect::<Result<Vec<_>>>().unwrap();
+        let entries = table
+            .scan(3, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
         assert_eq!(entries.len(), 1);
         assert_batches_eq!(&expected, &entries);
 
@@ -230,16 +390,21 @@ mod tests {
         read_filter_entry.set_completed(now, true);
 
         let expected = vec![
-            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+",
-            "| namespace_id | issue_time           | query_type  | query_text        | completed_duration | success | trace_id |",
-            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+",
-            "| 1            | 1996-12-19T16:39:57Z | sql         | select * from foo |                    | false   |          |",
-            "| 1            | 1996-12-20T16:39:57Z | sql         | select * from bar | 4s                 | false   |          |",
-            "| 2            | 1996-12-20T16:39:57Z | read_filter | json goop         | 4s                 | true    | 45fe     |",
-            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+",
+            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| namespace_id | issue_time           | query_type  | query_text        | completed_duration | success | trace_id | completed | error | plan_duration | execute_duration | evicted_count | query_text_truncated | running |",
+            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| 2            | 1996-12-20T16:39:57Z | read_filter | json goop         | 4s                 | true    | 45fe     | true      |       |               |                  | 0             | false                | false   |",
+            "| 1            | 1996-12-20T16:39:57Z | sql         | select * from bar | 4s                 | false   |          | true      |       |               |                  | 0             | false                | false   |",
+            "| 1            | 1996-12-19T16:39:57Z | sql         | select * from foo |                    | false   |          | false     |       |               |                  | 0             | false                | false   |",
+            "+--------------+----------------------+-------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
         ];
 
-        let entries = table.scan(2).unwrap().collect::<Result<Vec<_>>>().unwrap();
+        let entries = table
+            .scan(2, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
         assert_eq!(entries.len(), 2);
         assert_batches_eq!(&expected, &entries);
 
@@ -247,16 +412,542 @@ mod tests {
         let table = QueriesTable::new(Arc::clone(&query_log), Some(id1));
 
         let expected = vec![
-            "+----------------------+------------+-------------------+--------------------+---------+----------+",
-            "| issue_time           | query_type | query_text        | completed_duration | success | trace_id |",
-            "+----------------------+------------+-------------------+--------------------+---------+----------+",
-            "| 1996-12-19T16:39:57Z | sql        | select * from foo |                    | false   |          |",
-            "| 1996-12-20T16:39:57Z | sql        | select * from bar | 4s                 | false   |          |",
-            "+----------------------+------------+-------------------+--------------------+---------+----------+",
+            "+----------------------+------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| issue_time           | query_type | query_text        | completed_duration | success | trace_id | completed | error | plan_duration | execute_duration | evicted_count | query_text_truncated | running |",
+            "+----------------------+------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| 1996-12-20T16:39:57Z | sql        | select * from bar | 4s                 | false   |          | true      |       |               |                  | 0             | false                | false   |",
+            "| 1996-12-19T16:39:57Z | sql        | select * from foo |                    | false   |          | false     |       |               |                  | 0             | false                | false   |",
+            "+----------------------+------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
         ];
 
-        let entries = table.scan(3).unwrap().collect::<Result<Vec<_>>>().unwrap();
+        let entries = table
+            .scan(3, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
         assert_eq!(entries.len(), 1);
         assert_batches_eq!(&expected, &entries);
     }
+
+    #[tokio::test]
+    async fn test_completion() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let id = NamespaceId::new(1);
+
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
+        ));
+        let success_entry = query_log.push(id, "sql", Box::new("select * from foo"), None);
+        let failure_entry = query_log.push(id, "sql", Box::new("select * from bar"), None);
+        let running_entry = query_log.push(id, "sql", Box::new("select * from baz"), None);
+
+        // the successful and the running query are planned after 1s
+        time_provider.inc(std::time::Duration::from_secs(1));
+        query_log.set_planned(&success_entry);
+        query_log.set_planned(&running_entry);
+
+        // the failing query fails during planning
+        time_provider.inc(std::time::Duration::from_secs(2));
+        query_log.set_completed(success_entry, true);
+        query_log.set_failed(failure_entry, "table 'bar' not found".to_string());
+
+        let expected = vec![
+            "+-------------------+--------------------+---------+-----------+-----------------------+---------------+------------------+",
+            "| query_text        | completed_duration | success | completed | error                 | plan_duration | execute_duration |",
+            "+-------------------+--------------------+---------+-----------+-----------------------+---------------+------------------+",
+            "| select * from baz |                    | false   | false     |                       | 1s            |                  |",
+            "| select * from bar | 3s                 | false   | true      | table 'bar' not found |               |                  |",
+            "| select * from foo | 3s                 | true    | true      |                       | 1s            | 2s               |",
+            "+-------------------+--------------------+---------+-----------+-----------------------+---------------+------------------+",
+        ];
+
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+        let batches = table
+            .scan(10, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let batches = batches
+            .into_iter()
+            .map(|batch| {
+                let schema = batch.schema();
+                let indices = [
+                    "query_text",
+                    "completed_duration",
+                    "success",
+                    "completed",
+                    "error",
+                    "plan_duration",
+                    "execute_duration",
+                ]
+                .map(|name| schema.index_of(name).unwrap());
+                batch.project(&indices).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_batches_eq!(&expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn test_evicted_count() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let id = NamespaceId::new(1);
+
+        let query_log = Arc::new(QueryLog::new(
+            2,
+            Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
+        ));
+        let foo_entry = query_log.push(id, "sql", Box::new("select * from foo"), None);
+        query_log.set_completed(foo_entry, true);
+        query_log.push(id, "sql", Box::new("select * from bar"), None);
+        query_log.push(id, "sql", Box::new("select * from baz"), None);
+
+        let expected = vec![
+            "+-------------------+---------------+",
+            "| query_text        | evicted_count |",
+            "+-------------------+---------------+",
+            "| select * from baz | 1             |",
+            "| select * from bar | 1             |",
+            "+-------------------+---------------+",
+        ];
+
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+        let batches = table
+            .scan(10, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let batches = batches
+            .into_iter()
+            .map(|batch| {
+                let schema = batch.schema();
+                let indices =
+                    ["query_text", "evicted_count"].map(|name| schema.index_of(name).unwrap());
+                batch.project(&indices).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_batches_eq!(&expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn test_running() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let id = NamespaceId::new(1);
+
+        let query_log = Arc::new(QueryLog::new(
+            2,
+            Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
+        ));
+        let running_entry = query_log.push(id, "sql", Box::new("select * from foo"), None);
+        let completed_entry = query_log.push(id, "sql", Box::new("select * from bar"), None);
+        query_log.set_completed(completed_entry, true);
+        query_log.push(id, "sql", Box::new("select * from baz"), None);
+        query_log.push(id, "sql", Box::new("select * from qux"), None);
+
+        // the running query is still listed after being evicted from the log
+        let expected = vec![
+            "+-------------------+-----------+---------+---------------+",
+            "| query_text        | completed | running | evicted_count |",
+            "+-------------------+-----------+---------+---------------+",
+            "| select * from qux | false     | true    | 2             |",
+            "| select * from baz | false     | true    | 2             |",
+            "| select * from foo | false     | true    | 2             |",
+            "+-------------------+-----------+---------+---------------+",
+        ];
+
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+        let scan = || {
+            let table = &table;
+            async move {
+                table
+                    .scan(10, None)
+                    .await
+                    .unwrap()
+                    .collect::<Result<Vec<_>>>()
+                    .unwrap()
+                    .into_iter()
+                    .map(|batch| {
+                        let schema = batch.schema();
+                        let indices = ["query_text", "completed", "running", "evicted_count"]
+                            .map(|name| schema.index_of(name).unwrap());
+                        batch.project(&indices).unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_batches_eq!(&expected, &scan().await);
+        assert_eq!(table.statistics().num_rows, Some(3));
+
+        // once completed, it's gone
+        query_log.set_completed(running_entry, true);
+        let expected = vec![
+            "+-------------------+-----------+---------+---------------+",
+            "| query_text        | completed | running | evicted_count |",
+            "+-------------------+-----------+---------+---------------+",
+            "| select * from qux | false     | true    | 2             |",
+            "| select * from baz | false     | true    | 2             |",
+            "+-------------------+-----------+---------+---------------+",
+        ];
+        assert_batches_eq!(&expected, &scan().await);
+    }
+
+    #[tokio::test]
+    async fn test_query_text_truncated() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let id = NamespaceId::new(1);
+
+        let query_log = Arc::new(
+            QueryLog::new(
+                10,
+                Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+                &metric::Registry::default(),
+            )
+            .with_text_config(QueryTextConfig {
+                max_length: Some(17),
+                redact: false,
+            }),
+        );
+        query_log.push(id, "sql", Box::new("select * from foo"), None);
+        query_log.push(id, "sql", Box::new("select * from foo where x = 1"), None);
+
+        let expected = vec![
+            "+-------------------+----------------------+",
+            "| query_text        | query_text_truncated |",
+            "+-------------------+----------------------+",
+            "| select * from foo | true                 |",
+            "| select * from foo | false                |",
+            "+-------------------+----------------------+",
+        ];
+
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+        let batches = table
+            .scan(10, None)
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let batches = batches
+            .into_iter()
+            .map(|batch| {
+                let schema = batch.schema();
+                let indices = ["query_text", "query_text_truncated"]
+                    .map(|name| schema.index_of(name).unwrap());
+                batch.project(&indices).unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_batches_eq!(&expected, &batches);
+    }
+
+    #[tokio::test]
+    async fn test_projection() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let id = NamespaceId::new(1);
+
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
+        ));
+        query_log.push(id, "sql", Box::new("select * from foo"), None);
+        query_log.push(id, "read_filter", Box::new("json goop"), None);
+
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+        let schema = table.schema();
+        let projection = ["query_type", "issue_time"].map(|name| schema.index_of(name).unwrap());
+
+        // only the requested columns are built, in the requested order
+        let batches = table
+            .scan(10, Some(&projection[..]))
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            batches[0].schema(),
+            Arc::new(schema.project(&projection).unwrap())
+        );
+        assert_batches_eq!(
+            [
+                "+-------------+----------------------+",
+                "| query_type  | issue_time           |",
+                "+-------------+----------------------+",
+                "| read_filter | 1996-12-19T16:39:57Z |",
+                "| sql         | 1996-12-19T16:39:57Z |",
+                "+-------------+----------------------+",
+            ],
+            &batches
+        );
+
+        // no columns at all, e.g. for `COUNT(*)`
+        let batches = table
+            .scan(10, Some(&[] as &[usize]))
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_columns(), 0);
+        assert_eq!(batches[0].num_rows(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_scan_with_filters() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+
+        let id1 = NamespaceId::new(1);
+        let id2 = NamespaceId::new(2);
+
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
+        ));
+        query_log.push(id1, "sql", Box::new("select * from foo"), None);
+        time_provider.inc(std::time::Duration::from_secs(60));
+        query_log.push(id1, "sql", Box::new("select * from bar"), None);
+        query_log.push(id2, "sql", Box::new("select * from baz"), None);
+
+        let table = QueriesTable::new(Arc::clone(&query_log), None);
+        let num_rows = |batches: Vec<RecordBatch>| -> usize {
+            batches.iter().map(|batch| batch.num_rows()).sum()
+        };
+        let scan = |filters: Vec<Expr>| {
+            let table = &table;
+            async move {
+                table
+                    .scan_with_filters(10, None, &filters)
+                    .await
+                    .unwrap()
+                    .collect::<Result<Vec<_>>>()
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(num_rows(scan(vec![]).await), 3);
+
+        // time range
+        let cutoff = lit_timestamp_nano(now.timestamp_nanos() + 1);
+        assert_eq!(
+            num_rows(scan(vec![col("issue_time").gt(cutoff.clone())]).await),
+            2
+        );
+        // ... with the literal on the left
+        assert_eq!(num_rows(scan(vec![cutoff.gt(col("issue_time"))]).await), 1);
+
+        // namespace ID
+        assert_eq!(
+            num_rows(scan(vec![col("namespace_id").eq(lit(2i64))]).await),
+            1
+        );
+
+        // combined
+        let expected = vec![
+            "+--------------+----------------------+------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| namespace_id | issue_time           | query_type | query_text        | completed_duration | success | trace_id | completed | error | plan_duration | execute_duration | evicted_count | query_text_truncated | running |",
+            "+--------------+----------------------+------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+            "| 1            | 1996-12-19T16:40:57Z | sql        | select * from bar |                    | false   |          | false     |       |               |                  | 0             | false                | true    |",
+            "+--------------+----------------------+------------+-------------------+--------------------+---------+----------+-----------+-------+---------------+------------------+---------------+----------------------+---------+",
+        ];
+        let batches = scan(vec![
+            col("issue_time").gt_eq(lit_timestamp_nano(now.timestamp_nanos() + 1)),
+            col("namespace_id").not_eq(lit(2i64)),
+        ])
+        .await;
+        assert_batches_eq!(&expected, &batches);
+
+        // unsupported filters are ignored, and left to DataFusion
+        assert_eq!(
+            num_rows(scan(vec![col("query_text").eq(lit("select * from foo"))]).await),
+            3
+        );
+
+        // the namespace ID can't be filtered on in a namespace-scoped table
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id1));
+        let batches = table
+            .scan_with_filters(10, None, &[col("namespace_id").eq(lit(2i64))])
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(num_rows(batches), 2);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_isolation() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let ids = [1, 2, 3].map(NamespaceId::new);
+
+        // interleave completed, failed and running queries of all namespaces, evicting some
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
+        ));
+        for i in 0..6 {
+            for id in ids {
+                let entry =
+                    query_log.push(id, "sql", Box::new(format!("ns{} q{i}", id.get())), None);
+                match i % 3 {
+                    0 => query_log.set_completed(entry, true),
+                    1 => query_log.set_failed(entry, "error".to_owned()),
+                    _ => {}
+                }
+                time_provider.inc(std::time::Duration::from_secs(1));
+            }
+        }
+        assert!(query_log.evicted_count() > 0);
+
+        let query_texts = |batches: Vec<RecordBatch>| -> Vec<String> {
+            batches
+                .iter()
+                .flat_map(|batch| {
+                    let column = batch.column(batch.schema().index_of("query_text").unwrap());
+                    column
+                        .as_any()
+                        .downcast_ref::<StringArray>()
+                        .unwrap()
+                        .iter()
+                        .map(|text| text.unwrap().to_owned())
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        };
+
+        for id in ids {
+            let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+            assert!(table.schema().index_of("namespace_id").is_err());
+
+            let prefix = format!("ns{} ", id.get());
+            let mut expected = query_log
+                .entries_with_running()
+                .iter()
+                .filter(|entry| entry.namespace_id == id)
+                .map(|entry| entry.query_text.to_string())
+                .collect::<Vec<_>>();
+            expected.sort();
+            assert!(!expected.is_empty());
+            assert!(expected.iter().all(|text| text.starts_with(&prefix)));
+
+            // in any batch size, and with any filters
+            for filters in [
+                vec![],
+                vec![col("issue_time").gt_eq(lit_timestamp_nano(now.timestamp_nanos()))],
+                vec![col("namespace_id").not_eq(lit(id.get()))],
+            ] {
+                for batch_size in [1, 2, 100] {
+                    let batches = table
+                        .scan_with_filters(batch_size, None, &filters)
+                        .await
+                        .unwrap()
+                        .collect::<Result<Vec<_>>>()
+                        .unwrap();
+                    let mut actual = query_texts(batches);
+                    actual.sort();
+                    assert_eq!(actual, expected);
+                }
+            }
+
+            assert_eq!(table.statistics().num_rows, Some(expected.len()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_age() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let id = NamespaceId::new(1);
+
+        let query_log = Arc::new(
+            QueryLog::new(
+                10,
+                Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+                &metric::Registry::default(),
+            )
+            .with_max_age(Some(std::time::Duration::from_secs(60))),
+        );
+        let entry = query_log.push(id, "sql", Box::new("select * from foo"), None);
+        query_log.set_completed(entry, true);
+        time_provider.inc(std::time::Duration::from_secs(30));
+        let entry = query_log.push(id, "sql", Box::new("select * from bar"), None);
+        query_log.set_completed(entry, true);
+
+        // the first query expires without any new query being logged
+        time_provider.inc(std::time::Duration::from_secs(31));
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+        let batches = table
+            .scan(10, Some(&[2]))
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_batches_eq!(
+            [
+                "+-------------------+",
+                "| query_text        |",
+                "+-------------------+",
+                "| select * from bar |",
+                "+-------------------+",
+            ],
+            &batches
+        );
+        assert_eq!(table.statistics().num_rows, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_sort_order() {
+        let now = Time::from_rfc3339("1996-12-19T16:39:57+00:00").unwrap();
+        let time_provider = Arc::new(iox_time::MockProvider::new(now));
+        let id = NamespaceId::new(1);
+
+        let query_log = Arc::new(QueryLog::new(
+            10,
+            Arc::clone(&time_provider) as Arc<dyn TimeProvider>,
+            &metric::Registry::default(),
+        ));
+        // the log is not ordered by issue time if the clock jumps
+        for (offset_secs, query) in [(0, "a"), (20, "b"), (10, "c"), (30, "d"), (10, "e")] {
+            time_provider.set(now + std::time::Duration::from_secs(offset_secs));
+            query_log.push(id, "sql", Box::new(query), None);
+        }
+
+        let table = QueriesTable::new(Arc::clone(&query_log), Some(id));
+        let schema = table.schema();
+        let projection = ["issue_time", "query_text"].map(|name| schema.index_of(name).unwrap());
+        let batches = table
+            .scan(2, Some(&projection[..]))
+            .await
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        // newest first, across batches
+        assert_eq!(batches.len(), 3);
+        assert_batches_eq!(
+            [
+                "+----------------------+------------+",
+                "| issue_time           | query_text |",
+                "+----------------------+------------+",
+                "| 1996-12-19T16:40:27Z | d          |",
+                "| 1996-12-19T16:40:17Z | b          |",
+                "| 1996-12-19T16:40:07Z | e          |",
+                "| 1996-12-19T16:40:07Z | c          |",
+                "| 1996-12-19T16:39:57Z | a          |",
+                "+----------------------+------------+",
+            ],
+            &batches
+        );
+    }
 }
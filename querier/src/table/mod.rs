@@ -1,7 +1,7 @@
 use self::query_access::QuerierTableChunkPruner;
 use crate::{
     cache::{
-        namespace::CachedTable,
+        namespace::{CachedTable, ShouldCover},
         partition::{CachedPartition, PartitionRequest},
     },
     ingester::{self, IngesterPartition},
@@ -39,6 +39,11 @@ pub enum Error {
 
     #[snafu(display("Chunk pruning failed: {}", source))]
     ChunkPruning { source: provider::Error },
+
+    #[snafu(display("Cannot get namespace: {}", source))]
+    Namespace {
+        source: crate::cache::namespace::Error,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -239,16 +244,26 @@ impl QuerierTable {
             .iter()
             .flat_map(|cached_file| cached_file.column_set.iter().copied())
             .collect();
+        let mut should_cover = vec![ShouldCover::Columns(&self.table_name, &columns)];
+        // the ingesters partitioned and pruned their data using this template, so the cached table
+        // has to use the same one to prune the persisted data
+        if let Some(partition_template) = partitions.iter().find_map(|p| p.partition_template()) {
+            should_cover.push(ShouldCover::PartitionTemplate(
+                &self.table_name,
+                partition_template,
+            ));
+        }
         let cached_namespace = self
             .chunk_adapter
             .catalog_cache()
             .namespace()
             .get(
                 Arc::clone(&self.namespace_name),
-                &[(&self.table_name, &columns)],
+                &should_cover,
                 span_recorder.child_span("cache GET namespace schema"),
             )
-            .await;
+            .await
+            .context(NamespaceSnafu)?;
         let Some(cached_table) = cached_namespace
             .as_ref()
             .and_then(|ns| ns.tables.get(self.table_name.as_ref()))
@@ -443,6 +458,7 @@ impl QuerierTable {
                 span_recorder.child_span("get namespace"),
             )
             .await
+            .context(NamespaceSnafu)?
             .and_then(|ns| ns.tables.get(&self.table_name).cloned())
         else {
             return Ok(vec![]);
@@ -839,6 +855,53 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_ingester_partition_template() {
+        maybe_start_logging();
+        let catalog = TestCatalog::new();
+        let ns = catalog.create_namespace_1hr_retention("ns").await;
+        let table = ns
+            .create_table_with_partition_template(
+                "table",
+                Some(PartitionTemplate {
+                    parts: vec![TemplatePart {
+                        part: Some(Part::TagValue(String::from("tag1"))),
+                    }],
+                }),
+            )
+            .await;
+        let partition = table.create_partition("val1a").await;
+        let schema = make_schema_two_fields_two_tags(&table).await;
+        let builder = IngesterPartitionBuilder::new(schema, &partition)
+            .with_lp(["table,tag1=val1a,tag2=val2a foo=3,bar=4 11"]);
+
+        // the ingester uses the cached template => no refresh
+        let querier_table = TestQuerierTable::new(&catalog, &table)
+            .await
+            .with_ingester_partition(
+                builder
+                    .build()
+                    .with_partition_template(table.table.partition_template.clone()),
+            );
+        querier_table.chunks().await.unwrap();
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            0,
+        );
+
+        // the ingester uses another template => the table is refreshed
+        let querier_table = querier_table
+            .clear_ingester_partitions()
+            .with_ingester_partition(builder.build().with_partition_template(Default::default()));
+        querier_table.chunks().await.unwrap();
+        assert_catalog_access_metric_count(
+            &catalog.metric_registry,
+            "table_get_by_namespace_and_name",
+            1,
+        );
+    }
+
     #[tokio::test]
     async fn test_partition_caching() {
         maybe_start_logging();
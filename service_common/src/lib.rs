@@ -24,6 +24,7 @@ pub mod test_util;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use datafusion::error::DataFusionError;
 use iox_query::QueryNamespace;
 use trace::span::Span;
 use tracker::InstrumentedAsyncOwnedSemaphorePermit;
@@ -39,13 +40,15 @@ pub trait QueryNamespaceProvider: std::fmt::Debug + Send + Sync + 'static {
 
     /// Get namespace if it exists.
     ///
+    /// Fails if the namespace exists but cannot be used.
+    ///
     /// System tables may contain debug information depending on `include_debug_info_tables`.
     async fn db(
         &self,
         name: &str,
         span: Option<Span>,
         include_debug_info_tables: bool,
-    ) -> Option<Arc<Self::Db>>;
+    ) -> Result<Option<Arc<Self::Db>>, DataFusionError>;
 
     /// Acquire concurrency-limiting sempahore
     async fn acquire_semaphore(&self, span: Option<Span>) -> InstrumentedAsyncOwnedSemaphorePermit;
@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, sync::Arc};
 
 use async_trait::async_trait;
+use datafusion::error::DataFusionError;
 use iox_query::{exec::Executor, test::TestDatabase};
 use parking_lot::Mutex;
 use trace::span::Span;
@@ -66,10 +67,10 @@ impl QueryNamespaceProvider for TestDatabaseStore {
         name: &str,
         _span: Option<Span>,
         _include_debug_info_tables: bool,
-    ) -> Option<Arc<Self::Db>> {
+    ) -> Result<Option<Arc<Self::Db>>, DataFusionError> {
         let databases = self.databases.lock();
 
-        databases.get(name).cloned()
+        Ok(databases.get(name).cloned())
     }
 
     async fn acquire_semaphore(&self, span: Option<Span>) -> InstrumentedAsyncOwnedSemaphorePermit {
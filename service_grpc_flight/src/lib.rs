@@ -88,6 +88,12 @@ pub enum Error {
     #[snafu(display("Database '{}' not found", namespace_name))]
     DatabaseNotFound { namespace_name: String },
 
+    #[snafu(display("Internal error getting namespace {}: {}", namespace_name, source))]
+    GettingDatabase {
+        namespace_name: String,
+        source: DataFusionError,
+    },
+
     #[snafu(display(
         "Internal error reading points from namespace {}: {}",
         namespace_name,
@@ -177,6 +183,7 @@ impl From<Error> for tonic::Status {
             | Error::InternalCreatingTicket { .. }
             | Error::UnsupportedMessageType { .. }
             | Error::FlightSQL { .. }
+            | Error::GettingDatabase { .. }
             | Error::Authz { .. } => {
                 warn!(e=%err, %namespace, %query, msg)
             }
@@ -200,9 +207,9 @@ impl Error {
             | Self::NoFlightSQLDatabase
             | Self::InvalidDatabaseHeader { .. }
             | Self::InvalidDatabaseName { .. } => tonic::Code::InvalidArgument,
-            Self::Planning { source, .. } | Self::Query { source, .. } => {
-                datafusion_error_to_tonic_code(&source)
-            }
+            Self::Planning { source, .. }
+            | Self::Query { source, .. }
+            | Self::GettingDatabase { source, .. } => datafusion_error_to_tonic_code(&source),
             Self::UnsupportedMessageType { .. } => tonic::Code::Unimplemented,
             Self::FlightSQL { source } => match source {
                 flightsql::Error::InvalidHandle { .. }
@@ -247,6 +254,7 @@ impl Error {
             | Error::PermissionDenied
             | Error::Authz { .. } => "<unknown>",
             Error::DatabaseNotFound { namespace_name } => namespace_name,
+            Error::GettingDatabase { namespace_name, .. } => namespace_name,
             Error::Query { namespace_name, .. } => namespace_name,
             Error::Planning { namespace_name, .. } => namespace_name,
         }
@@ -270,7 +278,8 @@ impl Error {
             | Error::Unauthenticated
             | Error::PermissionDenied
             | Error::Authz { .. }
-            | Error::DatabaseNotFound { .. } => "NONE",
+            | Error::DatabaseNotFound { .. }
+            | Error::GettingDatabase { .. } => "NONE",
             Error::Query { query, .. } => query,
             Error::Planning { query, .. } => query,
         }
@@ -504,47 +513,48 @@ where
                 is_debug,
             )
             .await
+            .context(GettingDatabaseSnafu {
+                namespace_name: &namespace_name,
+            })?
             .context(DatabaseNotFoundSnafu {
                 namespace_name: &namespace_name,
             })?;
 
         let ctx = db.new_query_context(span_ctx);
-        let (query_completed_token, physical_plan) = match &query {
+        let (mut query_completed_token, plan) = match &query {
             RunQuery::Sql(sql_query) => {
                 let token = db.record_query(&ctx, "sql", Box::new(sql_query.clone()));
-                let plan = Planner::new(&ctx)
-                    .sql(sql_query)
-                    .await
-                    .context(PlanningSnafu {
-                        namespace_name: &namespace_name,
-                        query: query.to_string(),
-                    })?;
+                let plan = Planner::new(&ctx).sql(sql_query).await;
                 (token, plan)
             }
             RunQuery::InfluxQL(sql_query) => {
                 let token = db.record_query(&ctx, "influxql", Box::new(sql_query.clone()));
-                let plan = Planner::new(&ctx)
-                    .influxql(sql_query)
-                    .await
-                    .context(PlanningSnafu {
-                        namespace_name: &namespace_name,
-                        query: query.to_string(),
-                    })?;
+                let plan = Planner::new(&ctx).influxql(sql_query).await;
                 (token, plan)
             }
             RunQuery::FlightSQL(msg) => {
                 let token = db.record_query(&ctx, "flightsql", Box::new(msg.to_string()));
                 let plan = Planner::new(&ctx)
                     .flight_sql_do_get(&namespace_name, db, msg.clone())
-                    .await
-                    .context(PlanningSnafu {
-                        namespace_name: &namespace_name,
-                        query: query.to_string(),
-                    })?;
+                    .await;
                 (token, plan)
             }
         };
 
+        let physical_plan = match plan.context(PlanningSnafu {
+            namespace_name: &namespace_name,
+            query: query.to_string(),
+        }) {
+            Ok(plan) => {
+                query_completed_token.set_planned();
+                plan
+            }
+            Err(e) => {
+                query_completed_token.set_error(&e);
+                return Err(e);
+            }
+        };
+
         let output = GetStream::new(
             ctx,
             physical_plan,
@@ -745,6 +755,9 @@ where
                 is_debug,
             )
             .await
+            .context(GettingDatabaseSnafu {
+                namespace_name: &namespace_name,
+            })?
             .context(DatabaseNotFoundSnafu {
                 namespace_name: &namespace_name,
             })?;
@@ -826,6 +839,9 @@ where
                 is_debug,
             )
             .await
+            .context(GettingDatabaseSnafu {
+                namespace_name: &namespace_name,
+            })?
             .context(DatabaseNotFoundSnafu {
                 namespace_name: &namespace_name,
             })?;
@@ -969,24 +985,30 @@ impl GetStream {
         physical_plan: Arc<dyn ExecutionPlan>,
         namespace_name: String,
         query: &RunQuery,
-        query_completed_token: QueryCompletedToken,
+        mut query_completed_token: QueryCompletedToken,
         permit: InstrumentedAsyncOwnedSemaphorePermit,
     ) -> Result<Self, tonic::Status> {
         let app_metadata = proto::AppMetadata {};
 
         let schema = physical_plan.schema();
 
-        let query_results = ctx
+        let query_results = match ctx
             .execute_stream(Arc::clone(&physical_plan))
             .await
             .context(QuerySnafu {
                 namespace_name: namespace_name.clone(),
                 query: query.to_string(),
-            })?
-            .map_err(|e| {
-                let code = datafusion_error_to_tonic_code(&e);
-                tonic::Status::new(code, e.to_string()).into()
-            });
+            }) {
+            Ok(query_results) => query_results,
+            Err(e) => {
+                query_completed_token.set_error(&e);
+                return Err(e.into());
+            }
+        };
+        let query_results = query_results.map_err(|e| {
+            let code = datafusion_error_to_tonic_code(&e);
+            tonic::Status::new(code, e.to_string()).into()
+        });
 
         // setup inner stream
         let inner = FlightDataEncoderBuilder::new()
@@ -1030,6 +1052,7 @@ impl Stream for GetStream {
                 }
                 Some(Err(e)) => {
                     self.done = true;
+                    self.query_completed_token.set_error(&e);
                     return Poll::Ready(Some(Err(e.into())));
                 }
             }
@@ -7,6 +7,8 @@ use futures::{ready, Stream, StreamExt};
 use iox_query::QueryCompletedToken;
 
 /// Wraps an inner query stream, calling the `QueryCompletedToken::set_success` on success
+///
+/// The query is considered planned once the stream is created.
 #[derive(Debug)]
 pub struct QueryCompletedTokenStream<S, T, E>
 where
@@ -21,7 +23,8 @@ impl<S, T, E> QueryCompletedTokenStream<S, T, E>
 where
     S: Stream<Item = Result<T, E>> + Unpin + Send,
 {
-    pub fn new(inner: S, token: QueryCompletedToken) -> Self {
+    pub fn new(inner: S, mut token: QueryCompletedToken) -> Self {
+        token.set_planned();
         Self {
             inner,
             token,
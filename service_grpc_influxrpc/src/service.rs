@@ -67,6 +67,12 @@ pub enum Error {
     #[snafu(display("Namespace not found: {}", db_name))]
     NamespaceNotFound { db_name: String },
 
+    #[snafu(display("Error getting namespace '{}': {}", db_name, source))]
+    GettingNamespace {
+        db_name: String,
+        source: DataFusionError,
+    },
+
     #[snafu(display("Error listing tables in namespace '{}': {}", db_name, source))]
     ListingTables {
         db_name: String,
@@ -211,7 +217,8 @@ impl Error {
 
         let code = match self {
             Self::NamespaceNotFound { .. } => tonic::Code::NotFound,
-            Self::ListingTables { source, .. }
+            Self::GettingNamespace { source, .. }
+            | Self::ListingTables { source, .. }
             | Self::ListingColumns { source, .. }
             | Self::ListingFields { source, .. }
             | Self::PlanningFilteringSeries { source, .. }
@@ -391,6 +398,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -439,6 +447,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -515,6 +524,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -593,6 +603,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -661,6 +672,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -761,6 +773,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -870,6 +883,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -927,6 +941,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -995,6 +1010,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -1065,6 +1081,7 @@ where
             .db_store
             .db(&db_name, span_ctx.child_span("get namespace"), false)
             .await
+            .context(GettingNamespaceSnafu { db_name: &db_name })?
             .context(NamespaceNotFoundSnafu { db_name: &db_name })?;
 
         let ctx = db.new_query_context(span_ctx);
@@ -16,12 +16,33 @@
 // Workaround for "unused crate" lint false positives.
 use workspace_hack as _;
 
-use std::{ops::DerefMut, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    hash::Hasher,
+    ops::{Bound, DerefMut},
+    sync::Arc,
+};
 
+use data_types::{
+    partition_template::TablePartitionTemplateOverride, ColumnId, ColumnType, NamespaceId, TableId,
+};
+use futures::{stream::BoxStream, StreamExt};
 use generated_types::influxdata::iox::schema::v1::*;
-use iox_catalog::interface::{get_schema_by_name, Catalog, SoftDeletedRows};
-use observability_deps::tracing::warn;
+use iox_catalog::interface::{
+    get_schema_by_id, get_schema_by_name, get_table_columns_by_id, Catalog, RepoCollection,
+    SoftDeletedRows,
+};
+use observability_deps::tracing::{debug, warn};
+use siphasher::sip::SipHasher13;
 use tonic::{Request, Response, Status};
+use trace::{
+    ctx::SpanContext,
+    span::{SpanExt, SpanRecorder},
+};
+
+/// The maximum number of namespace schemas fetched from the catalog
+/// concurrently when serving a [`GetSchemasRequest`].
+const MAX_CONCURRENT_SCHEMA_FETCHES: usize = 10;
 
 /// Implementation of the gRPC schema service
 #[derive(Debug)]
@@ -38,13 +59,20 @@ impl SchemaService {
 
 #[tonic::async_trait]
 impl schema_service_server::SchemaService for SchemaService {
+    type GetSchemaStreamStream = BoxStream<'static, Result<GetSchemaStreamResponse, Status>>;
+
     async fn get_schema(
         &self,
         request: Request<GetSchemaRequest>,
     ) -> Result<Response<GetSchemaResponse>, Status> {
+        let span_ctx: Option<SpanContext> = request.extensions().get().cloned();
+        let mut span_recorder = SpanRecorder::new(span_ctx.child_span("catalog get schema"));
+
         let mut repos = self.catalog.repositories().await;
 
         let req = request.into_inner();
+        span_recorder.set_metadata("namespace", req.namespace.clone());
+
         let schema = get_schema_by_name(
             &req.namespace,
             repos.deref_mut(),
@@ -53,57 +81,752 @@ impl schema_service_server::SchemaService for SchemaService {
         .await
         .map_err(|e| {
             warn!(error=%e, %req.namespace, "failed to retrieve namespace schema");
+            span_recorder.error(e.to_string());
             Status::not_found(e.to_string())
         })
         .map(Arc::new)?;
+
+        span_recorder.set_metadata("tables", schema.tables.len() as i64);
+        span_recorder.set_metadata(
+            "columns",
+            schema
+                .tables
+                .values()
+                .map(|t| t.column_count())
+                .sum::<usize>() as i64,
+        );
+        span_recorder.ok("fetched schema");
+        drop(span_recorder);
+
+        if let Some(want) = req.if_not_fingerprint.as_deref() {
+            let fingerprint = schema_fingerprint(&schema);
+            if fingerprint == want {
+                return Ok(Response::new(GetSchemaResponse {
+                    schema: None,
+                    next_page_token: String::new(),
+                    fingerprint,
+                    not_modified: true,
+                }));
+            }
+        }
+
+        let response = match (req.page_size, req.page_token) {
+            (None, None) => schema_to_proto(schema),
+            (page_size, page_token) => {
+                paged_schema_to_proto(&schema, page_size, page_token.as_deref())?
+            }
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn get_schema_by_id(
+        &self,
+        request: Request<GetSchemaByIdRequest>,
+    ) -> Result<Response<GetSchemaResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let namespace_id = NamespaceId::new(request.into_inner().namespace_id);
+        let schema = get_schema_by_id(
+            namespace_id,
+            repos.deref_mut(),
+            SoftDeletedRows::ExcludeDeleted,
+        )
+        .await
+        .map_err(|e| {
+            warn!(error=%e, %namespace_id, "failed to retrieve namespace schema");
+            match e {
+                iox_catalog::interface::Error::NamespaceNotFoundById { .. } => {
+                    Status::not_found(e.to_string())
+                }
+                _ => Status::internal(e.to_string()),
+            }
+        })
+        .map(Arc::new)?;
         Ok(Response::new(schema_to_proto(schema)))
     }
-}
 
-fn schema_to_proto(schema: Arc<data_types::NamespaceSchema>) -> GetSchemaResponse {
-    let response = GetSchemaResponse {
-        schema: Some(NamespaceSchema {
-            id: schema.id.get(),
-            tables: schema
-                .tables
-                .iter()
-                .map(|(name, t)| {
-                    (
-                        name.clone(),
-                        TableSchema {
-                            id: t.id.get(),
-                            columns: t
-                                .columns
-                                .iter()
-                                .map(|(name, c)| {
-                                    (
-                                        name.clone(),
-                                        ColumnSchema {
-                                            id: c.id.get(),
-                                            column_type: c.column_type as i32,
-                                        },
-                                    )
-                                })
-                                .collect(),
-                        },
-                    )
+    async fn get_schema_stream(
+        &self,
+        request: Request<GetSchemaStreamRequest>,
+    ) -> Result<Response<Self::GetSchemaStreamStream>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let req = request.into_inner();
+        let schema = get_schema_by_name(
+            &req.namespace,
+            repos.deref_mut(),
+            SoftDeletedRows::ExcludeDeleted,
+        )
+        .await
+        .map_err(|e| {
+            warn!(error=%e, %req.namespace, "failed to retrieve namespace schema");
+            Status::not_found(e.to_string())
+        })?;
+
+        let header = GetSchemaStreamResponse {
+            item: Some(get_schema_stream_response::Item::Header(
+                GetSchemaStreamHeader {
+                    namespace_id: schema.id.get(),
+                },
+            )),
+        };
+
+        // Convert each table only as the stream is polled, rather than
+        // materialising the entire namespace up front.
+        let tables = schema
+            .tables
+            .into_iter()
+            .map(|(name, t)| GetSchemaStreamResponse {
+                item: Some(get_schema_stream_response::Item::Table(NamedTableSchema {
+                    name,
+                    schema: Some(table_to_proto(&t)),
+                })),
+            });
+
+        let stream =
+            futures::stream::iter(std::iter::once(header).chain(tables)).map(Ok::<_, Status>);
+
+        Ok(Response::new(stream.boxed()))
+    }
+
+    async fn list_namespaces(
+        &self,
+        request: Request<ListNamespacesRequest>,
+    ) -> Result<Response<ListNamespacesResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let deleted = match request.into_inner().include_deleted {
+            true => SoftDeletedRows::AllRows,
+            false => SoftDeletedRows::ExcludeDeleted,
+        };
+
+        let mut namespaces = repos.namespaces().list(deleted).await.map_err(|e| {
+            warn!(error=%e, "failed to retrieve namespaces from catalog");
+            Status::internal(e.to_string())
+        })?;
+
+        // Sort by name for a deterministic response.
+        namespaces.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Response::new(ListNamespacesResponse {
+            namespaces: namespaces
+                .into_iter()
+                .map(|ns| NamespaceSummary {
+                    id: ns.id.get(),
+                    name: ns.name,
+                    retention_period_ns: ns.retention_period_ns,
+                    deleted: ns.deleted_at.is_some(),
                 })
                 .collect(),
-        }),
-    };
+        }))
+    }
+
+    async fn diff_schemas(
+        &self,
+        request: Request<DiffSchemasRequest>,
+    ) -> Result<Response<DiffSchemasResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let req = request.into_inner();
+        let mut schemas = Vec::with_capacity(2);
+        for namespace in [&req.namespace_a, &req.namespace_b] {
+            let schema = get_schema_by_name(
+                namespace,
+                repos.deref_mut(),
+                SoftDeletedRows::ExcludeDeleted,
+            )
+            .await
+            .map_err(|e| {
+                warn!(error=%e, %namespace, "failed to retrieve namespace schema");
+                Status::not_found(e.to_string())
+            })?;
+            schemas.push(schema);
+        }
+
+        Ok(Response::new(diff_schemas(&schemas[0], &schemas[1])))
+    }
+
+    async fn resolve_ids(
+        &self,
+        request: Request<ResolveIdsRequest>,
+    ) -> Result<Response<ResolveIdsResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let ResolveIdsRequest {
+            table_ids,
+            column_ids,
+        } = request.into_inner();
+
+        let mut response = ResolveIdsResponse::default();
+
+        // Many of the requested IDs may refer to the same table - cache the
+        // resolved tables to avoid repeatedly querying the catalog.
+        let mut table_cache = HashMap::new();
+
+        for id in table_ids {
+            match resolve_table(repos.deref_mut(), &mut table_cache, TableId::new(id)).await? {
+                Some(t) => response.tables.push(t),
+                None => response.unresolved_table_ids.push(id),
+            }
+        }
+
+        for id in column_ids {
+            let column = repos
+                .columns()
+                .get_by_id(ColumnId::new(id))
+                .await
+                .map_err(|e| {
+                    warn!(error=%e, column_id=%id, "failed to retrieve column");
+                    Status::internal(e.to_string())
+                })?;
+
+            let resolved = match column {
+                Some(c) => resolve_table(repos.deref_mut(), &mut table_cache, c.table_id)
+                    .await?
+                    .map(|table| ResolvedColumn {
+                        id,
+                        name: c.name,
+                        table: Some(table),
+                    }),
+                None => None,
+            };
+
+            match resolved {
+                Some(c) => response.columns.push(c),
+                None => response.unresolved_column_ids.push(id),
+            }
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn upsert_schema(
+        &self,
+        request: Request<UpsertSchemaRequest>,
+    ) -> Result<Response<UpsertSchemaResponse>, Status> {
+        let mut repos = self.catalog.repositories().await;
+
+        let UpsertSchemaRequest {
+            namespace: namespace_name,
+            table: table_name,
+            columns,
+        } = request.into_inner();
+
+        if table_name.is_empty() {
+            return Err(Status::invalid_argument("table name must not be empty"));
+        }
+
+        // Validate all the requested column types before touching the catalog.
+        let columns = columns
+            .into_iter()
+            .map(|(name, column_type)| {
+                let column_type = column_schema::ColumnType::from_i32(column_type)
+                    .and_then(|t| ColumnType::try_from(t).ok())
+                    .ok_or_else(|| {
+                        Status::invalid_argument(format!("invalid type for column `{name}`"))
+                    })?;
+                Ok((name, column_type))
+            })
+            .collect::<Result<BTreeMap<_, _>, Status>>()?;
+
+        let namespace = repos
+            .namespaces()
+            .get_by_name(&namespace_name, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .map_err(|e| {
+                warn!(error=%e, %namespace_name, "failed to retrieve namespace");
+                Status::internal(e.to_string())
+            })?
+            .ok_or_else(|| Status::not_found(format!("namespace `{namespace_name}` not found")))?;
+
+        let table = repos
+            .tables()
+            .get_by_namespace_and_name(namespace.id, &table_name)
+            .await
+            .map_err(|e| {
+                warn!(error=%e, %namespace_name, %table_name, "failed to retrieve table");
+                Status::internal(e.to_string())
+            })?;
+
+        let table = match table {
+            Some(table) => {
+                // Reject the whole request if any column conflicts with an
+                // existing column, before any new column is created.
+                let existing = get_table_columns_by_id(table.id, repos.deref_mut())
+                    .await
+                    .map_err(|e| {
+                        warn!(error=%e, %namespace_name, %table_name, "failed to retrieve columns");
+                        Status::internal(e.to_string())
+                    })?;
+                for (name, column_type) in &columns {
+                    match existing.get(name) {
+                        Some(c) if c.column_type != *column_type => {
+                            return Err(Status::failed_precondition(format!(
+                                "column `{name}` is type {} but upsert has type {column_type}",
+                                c.column_type
+                            )));
+                        }
+                        _ => {}
+                    }
+                }
+                table
+            }
+            None => {
+                let partition_template =
+                    TablePartitionTemplateOverride::try_new(None, &namespace.partition_template)
+                        .map_err(|e| Status::internal(e.to_string()))?;
+                repos
+                    .tables()
+                    .create(&table_name, partition_template, namespace.id)
+                    .await
+                    .map_err(|e| {
+                        warn!(error=%e, %namespace_name, %table_name, "failed to create table");
+                        status_from_catalog_upsert_error(e)
+                    })?
+            }
+        };
+
+        for (name, column_type) in &columns {
+            repos
+                .columns()
+                .create_or_get(name, table.id, *column_type)
+                .await
+                .map_err(|e| {
+                    warn!(
+                        error=%e,
+                        %namespace_name,
+                        %table_name,
+                        column_name=%name,
+                        "failed to create column"
+                    );
+                    status_from_catalog_upsert_error(e)
+                })?;
+        }
+
+        let mut schema = data_types::TableSchema::new_empty_from(&table);
+        schema.columns = get_table_columns_by_id(table.id, repos.deref_mut())
+            .await
+            .map_err(|e| {
+                warn!(error=%e, %namespace_name, %table_name, "failed to retrieve columns");
+                Status::internal(e.to_string())
+            })?;
+
+        debug!(%namespace_name, %table_name, "upserted table schema");
+
+        Ok(Response::new(UpsertSchemaResponse {
+            table: Some(table_to_proto(&schema)),
+        }))
+    }
+
+    async fn get_schemas(
+        &self,
+        request: Request<GetSchemasRequest>,
+    ) -> Result<Response<GetSchemasResponse>, Status> {
+        // Dedupe the requested names, as each is fetched independently.
+        let namespaces = request
+            .into_inner()
+            .namespaces
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+
+        let results = futures::stream::iter(namespaces)
+            .map(|namespace| async move {
+                let mut repos = self.catalog.repositories().await;
+                let schema = get_schema_by_name(
+                    &namespace,
+                    repos.deref_mut(),
+                    SoftDeletedRows::ExcludeDeleted,
+                )
+                .await;
+                (namespace, schema)
+            })
+            .buffer_unordered(MAX_CONCURRENT_SCHEMA_FETCHES)
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(Response::new(get_schemas_response(results)))
+    }
+}
+
+/// Assemble the [`GetSchemasResponse`] from the individual namespace lookups.
+///
+/// A failed lookup is reported for its namespace only and does not fail the
+/// other namespaces of the batch.
+fn get_schemas_response(
+    results: impl IntoIterator<
+        Item = (
+            String,
+            Result<data_types::NamespaceSchema, iox_catalog::interface::Error>,
+        ),
+    >,
+) -> GetSchemasResponse {
+    let mut response = GetSchemasResponse::default();
+    for (namespace, schema) in results {
+        match schema {
+            Ok(schema) => {
+                response
+                    .schemas
+                    .insert(namespace, namespace_schema_to_proto(&schema));
+            }
+            Err(iox_catalog::interface::Error::NamespaceNotFoundByName { .. }) => {
+                response.not_found.push(namespace)
+            }
+            Err(e) => {
+                warn!(error=%e, %namespace, "failed to retrieve namespace schema");
+                response.errors.insert(namespace, e.to_string());
+            }
+        }
+    }
+    response.not_found.sort_unstable();
+
     response
 }
 
+fn status_from_catalog_upsert_error(err: iox_catalog::interface::Error) -> Status {
+    match err {
+        iox_catalog::interface::Error::ColumnTypeMismatch { .. } => {
+            Status::failed_precondition(err.to_string())
+        }
+        iox_catalog::interface::Error::ColumnCreateLimitError { .. }
+        | iox_catalog::interface::Error::TableCreateLimitError { .. } => {
+            Status::resource_exhausted(err.to_string())
+        }
+        _ => Status::internal(err.to_string()),
+    }
+}
+
+/// Resolve the table with the specified `id` and its owning namespace,
+/// returning [`None`] if either does not exist.
+///
+/// Results are memoised in `cache`.
+async fn resolve_table<R>(
+    repos: &mut R,
+    cache: &mut HashMap<TableId, Option<ResolvedTable>>,
+    id: TableId,
+) -> Result<Option<ResolvedTable>, Status>
+where
+    R: RepoCollection + ?Sized,
+{
+    if let Some(v) = cache.get(&id) {
+        return Ok(v.clone());
+    }
+
+    let table = repos.tables().get_by_id(id).await.map_err(|e| {
+        warn!(error=%e, table_id=%id, "failed to retrieve table");
+        Status::internal(e.to_string())
+    })?;
+
+    let resolved = match table {
+        Some(table) => repos
+            .namespaces()
+            .get_by_id(table.namespace_id, SoftDeletedRows::AllRows)
+            .await
+            .map_err(|e| {
+                warn!(error=%e, namespace_id=%table.namespace_id, "failed to retrieve namespace");
+                Status::internal(e.to_string())
+            })?
+            .map(|namespace| ResolvedTable {
+                id: id.get(),
+                name: table.name,
+                namespace_id: namespace.id.get(),
+                namespace_name: namespace.name,
+            }),
+        None => None,
+    };
+
+    cache.insert(id, resolved.clone());
+    Ok(resolved)
+}
+
+fn schema_to_proto(schema: Arc<data_types::NamespaceSchema>) -> GetSchemaResponse {
+    GetSchemaResponse {
+        schema: Some(namespace_schema_to_proto(&schema)),
+        next_page_token: String::new(),
+        fingerprint: schema_fingerprint(&schema),
+        not_modified: false,
+    }
+}
+
+fn namespace_schema_to_proto(schema: &data_types::NamespaceSchema) -> NamespaceSchema {
+    namespace_schema_page_to_proto(schema, schema.tables.iter())
+}
+
+/// Convert `schema` to its proto representation, including only the given
+/// subset of `tables`.
+///
+/// The table & column counts always describe the entire namespace.
+fn namespace_schema_page_to_proto<'a>(
+    schema: &data_types::NamespaceSchema,
+    tables: impl Iterator<Item = (&'a String, &'a data_types::TableSchema)>,
+) -> NamespaceSchema {
+    NamespaceSchema {
+        id: schema.id.get(),
+        tables: tables
+            .map(|(name, t)| (name.clone(), table_to_proto(t)))
+            .collect(),
+        table_count: schema.tables.len() as u64,
+        column_count: schema
+            .tables
+            .values()
+            .map(|t| t.column_count() as u64)
+            .sum(),
+        max_tables: schema.max_tables as i32,
+        max_columns_per_table: schema.max_columns_per_table as i32,
+    }
+}
+
+/// Convert at most `page_size` tables of `schema`, in table name order, that
+/// follow the table named by `page_token` (if any).
+///
+/// If more tables remain after this page, the returned `next_page_token` is
+/// set to the name of the last table in the page.
+fn paged_schema_to_proto(
+    schema: &data_types::NamespaceSchema,
+    page_size: Option<u32>,
+    page_token: Option<&str>,
+) -> Result<GetSchemaResponse, Status> {
+    let page_size = match page_size {
+        Some(0) => return Err(Status::invalid_argument("page size must be greater than 0")),
+        Some(n) => n as usize,
+        None => usize::MAX,
+    };
+
+    let start = match page_token {
+        Some(name) => Bound::Excluded(name),
+        None => Bound::Unbounded,
+    };
+    let mut iter = schema.tables.range::<str, _>((start, Bound::Unbounded));
+
+    let page = namespace_schema_page_to_proto(schema, iter.by_ref().take(page_size));
+
+    let next_page_token = match (iter.next(), page.tables.keys().next_back()) {
+        (Some(_), Some(last)) => last.clone(),
+        _ => String::new(),
+    };
+
+    Ok(GetSchemaResponse {
+        schema: Some(page),
+        next_page_token,
+        fingerprint: schema_fingerprint(schema),
+        not_modified: false,
+    })
+}
+
+/// Compute a fingerprint of `schema` that changes whenever a table or column
+/// is added, or the namespace limits change.
+///
+/// The fingerprint is computed over the tables & columns in name order, so it
+/// is independent of any map iteration order.
+fn schema_fingerprint(schema: &data_types::NamespaceSchema) -> String {
+    fn write_str(hasher: &mut SipHasher13, s: &str) {
+        hasher.write(&(s.len() as u64).to_le_bytes());
+        hasher.write(s.as_bytes());
+    }
+
+    let mut hasher = SipHasher13::new();
+    hasher.write(&schema.id.get().to_le_bytes());
+    hasher.write(&(schema.max_tables as u64).to_le_bytes());
+    hasher.write(&(schema.max_columns_per_table as u64).to_le_bytes());
+
+    for (table_name, table) in &schema.tables {
+        write_str(&mut hasher, table_name);
+        hasher.write(&table.id.get().to_le_bytes());
+        hasher.write(&(table.column_count() as u64).to_le_bytes());
+
+        for (column_name, column) in table.columns.iter() {
+            write_str(&mut hasher, column_name);
+            hasher.write(&column.id.get().to_le_bytes());
+            hasher.write(&(column.column_type as i16).to_le_bytes());
+        }
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+fn table_to_proto(table: &data_types::TableSchema) -> TableSchema {
+    TableSchema {
+        id: table.id.get(),
+        columns: table
+            .columns
+            .iter()
+            .map(|(name, c)| (name.clone(), column_to_proto(c)))
+            .collect(),
+        column_count: table.column_count() as u64,
+    }
+}
+
+fn column_to_proto(column: &data_types::ColumnSchema) -> ColumnSchema {
+    use column_schema::InfluxType;
+
+    let (influx_type, nullable) = match column.column_type {
+        ColumnType::Tag => (InfluxType::Tag, true),
+        ColumnType::F64 => (InfluxType::FieldFloat, true),
+        ColumnType::I64 => (InfluxType::FieldInteger, true),
+        ColumnType::U64 => (InfluxType::FieldUinteger, true),
+        ColumnType::Bool => (InfluxType::FieldBoolean, true),
+        ColumnType::String => (InfluxType::FieldString, true),
+        ColumnType::Time => (InfluxType::Time, false),
+    };
+
+    ColumnSchema {
+        id: column.id.get(),
+        column_type: column.column_type as i32,
+        influx_type: influx_type as i32,
+        nullable,
+    }
+}
+
+/// Compute the differences between the tables & columns of schema `a` and
+/// schema `b`.
+///
+/// All names in the output are sorted, and tables that are identical in both
+/// schemas are omitted. A column present in both tables with a different type
+/// is reported as a type mismatch, not as a matching column.
+fn diff_schemas(
+    a: &data_types::NamespaceSchema,
+    b: &data_types::NamespaceSchema,
+) -> DiffSchemasResponse {
+    let tables_only_in_a = a
+        .tables
+        .keys()
+        .filter(|name| !b.tables.contains_key(*name))
+        .cloned()
+        .collect();
+    let tables_only_in_b = b
+        .tables
+        .keys()
+        .filter(|name| !a.tables.contains_key(*name))
+        .cloned()
+        .collect();
+
+    let table_diffs = a
+        .tables
+        .iter()
+        .filter_map(|(table_name, table_a)| {
+            let table_b = b.tables.get(table_name)?;
+
+            let mut diff = TableDiff {
+                table_name: table_name.clone(),
+                columns_only_in_a: vec![],
+                columns_only_in_b: vec![],
+                type_mismatches: vec![],
+            };
+
+            for (column_name, column_a) in table_a.columns.iter() {
+                match table_b.columns.get(column_name) {
+                    None => diff.columns_only_in_a.push(column_name.clone()),
+                    Some(column_b) if column_b.column_type != column_a.column_type => {
+                        diff.type_mismatches.push(ColumnTypeMismatch {
+                            column_name: column_name.clone(),
+                            column_type_a: column_a.column_type as i32,
+                            column_type_b: column_b.column_type as i32,
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            diff.columns_only_in_b = table_b
+                .columns
+                .iter()
+                .filter(|(name, _)| !table_a.contains_column_name(name))
+                .map(|(name, _)| name.clone())
+                .collect();
+
+            if diff.columns_only_in_a.is_empty()
+                && diff.columns_only_in_b.is_empty()
+                && diff.type_mismatches.is_empty()
+            {
+                return None;
+            }
+
+            Some(diff)
+        })
+        .collect();
+
+    DiffSchemasResponse {
+        tables_only_in_a,
+        tables_only_in_b,
+        table_diffs,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use data_types::ColumnType;
+    use assert_matches::assert_matches;
     use generated_types::influxdata::iox::schema::v1::schema_service_server::SchemaService;
     use iox_catalog::{
         mem::MemCatalog,
         test_helpers::{arbitrary_namespace, arbitrary_table},
     };
     use std::sync::Arc;
+    use tonic::Code;
+    use trace::{
+        span::{MetaValue, SpanStatus},
+        RingBufferTraceCollector, TraceCollector,
+    };
+
+    #[test]
+    fn test_column_to_proto() {
+        use column_schema::{ColumnType as ProtoColumnType, InfluxType};
+
+        for (column_type, want_column_type, want_influx_type, want_nullable) in [
+            (
+                ColumnType::I64,
+                ProtoColumnType::I64,
+                InfluxType::FieldInteger,
+                true,
+            ),
+            (
+                ColumnType::U64,
+                ProtoColumnType::U64,
+                InfluxType::FieldUinteger,
+                true,
+            ),
+            (
+                ColumnType::F64,
+                ProtoColumnType::F64,
+                InfluxType::FieldFloat,
+                true,
+            ),
+            (
+                ColumnType::Bool,
+                ProtoColumnType::Bool,
+                InfluxType::FieldBoolean,
+                true,
+            ),
+            (
+                ColumnType::String,
+                ProtoColumnType::String,
+                InfluxType::FieldString,
+                true,
+            ),
+            (
+                ColumnType::Time,
+                ProtoColumnType::Time,
+                InfluxType::Time,
+                false,
+            ),
+            (ColumnType::Tag, ProtoColumnType::Tag, InfluxType::Tag, true),
+        ] {
+            let got = column_to_proto(&data_types::ColumnSchema {
+                id: ColumnId::new(42),
+                column_type,
+            });
+
+            assert_eq!(
+                got,
+                ColumnSchema {
+                    id: 42,
+                    column_type: want_column_type as i32,
+                    influx_type: want_influx_type as i32,
+                    nullable: want_nullable,
+                },
+                "unexpected conversion of {column_type}"
+            );
+        }
+    }
 
     #[tokio::test]
     async fn test_schema() {
@@ -126,6 +849,9 @@ mod tests {
         let grpc = super::SchemaService::new(catalog);
         let request = GetSchemaRequest {
             namespace: "namespace_schema_test".to_string(),
+            page_size: None,
+            page_token: None,
+            if_not_fingerprint: None,
         };
 
         let tonic_response = grpc
@@ -149,4 +875,824 @@ mod tests {
             vec![&"schema_test_column".to_string()]
         );
     }
+
+    #[tokio::test]
+    async fn test_schema_span() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let catalog = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_schema_span").await;
+            let table = arbitrary_table(&mut *repos, "schema_test_table", &namespace).await;
+            for column_name in ["a", "b"] {
+                repos
+                    .columns()
+                    .create_or_get(column_name, table.id, ColumnType::Tag)
+                    .await
+                    .unwrap();
+            }
+            Arc::clone(&catalog)
+        };
+
+        // Initialise a tracing backend to capture the emitted traces.
+        let trace_collector = Arc::new(RingBufferTraceCollector::new(5));
+        let trace_observer: Arc<dyn TraceCollector> = Arc::new(Arc::clone(&trace_collector));
+
+        let mut request = Request::new(GetSchemaRequest {
+            namespace: "namespace_schema_span".to_string(),
+            page_size: None,
+            page_token: None,
+            if_not_fingerprint: None,
+        });
+        request
+            .extensions_mut()
+            .insert(SpanContext::new(trace_observer));
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+        grpc.get_schema(request)
+            .await
+            .expect("rpc request should succeed");
+
+        let spans = trace_collector.spans();
+        assert_matches!(spans.as_slice(), [span] => {
+            assert_eq!(span.name, "catalog get schema");
+            assert_eq!(span.status, SpanStatus::Ok);
+            assert_eq!(
+                span.metadata.get("namespace"),
+                Some(&MetaValue::String("namespace_schema_span".into()))
+            );
+            assert_eq!(span.metadata.get("tables"), Some(&MetaValue::Int(1)));
+            assert_eq!(span.metadata.get("columns"), Some(&MetaValue::Int(2)));
+        });
+    }
+
+    #[tokio::test]
+    async fn test_schema_serialisation_is_stable() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let catalog = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_schema_stable").await;
+            for table_name in ["platanos", "bananas", "cavendish", "plantain"] {
+                let table = arbitrary_table(&mut *repos, table_name, &namespace).await;
+                for column_name in ["z", "a", "m", "b", "y"] {
+                    repos
+                        .columns()
+                        .create_or_get(column_name, table.id, ColumnType::Tag)
+                        .await
+                        .unwrap();
+                }
+            }
+            Arc::clone(&catalog)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        let get = || async {
+            let response = grpc
+                .get_schema(Request::new(GetSchemaRequest {
+                    namespace: "namespace_schema_stable".to_string(),
+                    page_size: None,
+                    page_token: None,
+                    if_not_fingerprint: None,
+                }))
+                .await
+                .expect("rpc request should succeed")
+                .into_inner();
+            serde_json::to_vec(&response).unwrap()
+        };
+
+        let a = get().await;
+        let b = get().await;
+        assert_eq!(a, b);
+
+        // And the tables are serialised in name order.
+        let a = String::from_utf8(a).unwrap();
+        let offsets = ["bananas", "cavendish", "plantain", "platanos"]
+            .into_iter()
+            .map(|name| a.find(name).expect("table should be present"))
+            .collect::<Vec<_>>();
+        assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_schema_counts_and_limits() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let catalog = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_schema_counts").await;
+            for (table_name, columns) in [("bananas", &["a", "b"][..]), ("platanos", &["c"][..])] {
+                let table = arbitrary_table(&mut *repos, table_name, &namespace).await;
+                for column_name in columns {
+                    repos
+                        .columns()
+                        .create_or_get(column_name, table.id, ColumnType::Tag)
+                        .await
+                        .unwrap();
+                }
+            }
+            Arc::clone(&catalog)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        let schema = grpc
+            .get_schema(Request::new(GetSchemaRequest {
+                namespace: "namespace_schema_counts".to_string(),
+                page_size: None,
+                page_token: None,
+                if_not_fingerprint: None,
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .schema
+            .expect("schema should be Some()");
+
+        assert_eq!(schema.table_count, 2);
+        assert_eq!(schema.column_count, 3);
+        assert_eq!(schema.tables["bananas"].column_count, 2);
+        assert_eq!(schema.tables["platanos"].column_count, 1);
+        assert_eq!(schema.max_tables, iox_catalog::DEFAULT_MAX_TABLES);
+        assert_eq!(
+            schema.max_columns_per_table,
+            iox_catalog::DEFAULT_MAX_COLUMNS_PER_TABLE
+        );
+
+        // The namespace totals describe the whole namespace, even when paging.
+        let schema = grpc
+            .get_schema(Request::new(GetSchemaRequest {
+                namespace: "namespace_schema_counts".to_string(),
+                page_size: Some(1),
+                page_token: None,
+                if_not_fingerprint: None,
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .schema
+            .expect("schema should be Some()");
+        assert_eq!(schema.tables.len(), 1);
+        assert_eq!(schema.table_count, 2);
+        assert_eq!(schema.column_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_schema_fingerprint() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let (catalog, table_id) = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_schema_fingerprint").await;
+            let table = arbitrary_table(&mut *repos, "bananas", &namespace).await;
+            repos
+                .columns()
+                .create_or_get("a", table.id, ColumnType::Tag)
+                .await
+                .unwrap();
+            (Arc::clone(&catalog), table.id)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(Arc::clone(&catalog) as _);
+
+        let get = |if_not_fingerprint: Option<String>| {
+            let grpc = &grpc;
+            async move {
+                grpc.get_schema(Request::new(GetSchemaRequest {
+                    namespace: "namespace_schema_fingerprint".to_string(),
+                    page_size: None,
+                    page_token: None,
+                    if_not_fingerprint,
+                }))
+                .await
+                .expect("rpc request should succeed")
+                .into_inner()
+            }
+        };
+
+        let first = get(None).await;
+        assert!(!first.fingerprint.is_empty());
+        assert!(!first.not_modified);
+        assert!(first.schema.is_some());
+
+        // An unchanged schema short-circuits.
+        let unchanged = get(Some(first.fingerprint.clone())).await;
+        assert!(unchanged.not_modified);
+        assert!(unchanged.schema.is_none());
+        assert_eq!(unchanged.fingerprint, first.fingerprint);
+
+        // Adding a column changes the fingerprint, and the full schema is
+        // returned.
+        catalog
+            .repositories()
+            .await
+            .columns()
+            .create_or_get("b", table_id, ColumnType::Tag)
+            .await
+            .unwrap();
+
+        let changed = get(Some(first.fingerprint.clone())).await;
+        assert!(!changed.not_modified);
+        assert_ne!(changed.fingerprint, first.fingerprint);
+        assert_eq!(
+            changed.schema.expect("schema should be Some()").tables["bananas"]
+                .columns
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_pagination() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let (catalog, table_names) = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_schema_paged").await;
+            let mut table_names = vec![];
+            for i in 0..10 {
+                let name = format!("table_{i}");
+                arbitrary_table(&mut *repos, &name, &namespace).await;
+                table_names.push(name);
+            }
+            (Arc::clone(&catalog), table_names)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        // Walk the namespace in pages of 3 tables.
+        let mut pages = vec![];
+        let mut page_token = None;
+        loop {
+            let response = grpc
+                .get_schema(Request::new(GetSchemaRequest {
+                    namespace: "namespace_schema_paged".to_string(),
+                    page_size: Some(3),
+                    page_token: page_token.take(),
+                    if_not_fingerprint: None,
+                }))
+                .await
+                .expect("rpc request should succeed")
+                .into_inner();
+
+            let mut names = response
+                .schema
+                .expect("schema should be Some()")
+                .tables
+                .into_keys()
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+            pages.push(names);
+
+            if response.next_page_token.is_empty() {
+                break;
+            }
+            page_token = Some(response.next_page_token);
+        }
+
+        assert_eq!(
+            pages.iter().map(|p| p.len()).collect::<Vec<_>>(),
+            [3, 3, 3, 1]
+        );
+        // No duplicates or gaps.
+        assert_eq!(pages.concat(), table_names);
+
+        // Without paging fields, all tables are returned in one response.
+        let response = grpc
+            .get_schema(Request::new(GetSchemaRequest {
+                namespace: "namespace_schema_paged".to_string(),
+                page_size: None,
+                page_token: None,
+                if_not_fingerprint: None,
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+        assert_eq!(response.schema.unwrap().tables.len(), 10);
+        assert!(response.next_page_token.is_empty());
+
+        // A zero page size is rejected.
+        let err = grpc
+            .get_schema(Request::new(GetSchemaRequest {
+                namespace: "namespace_schema_paged".to_string(),
+                page_size: Some(0),
+                page_token: None,
+                if_not_fingerprint: None,
+            }))
+            .await
+            .expect_err("zero page size should be rejected");
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_schema_by_id() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let (catalog, namespace_id, deleted_namespace_id) = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_schema_by_id").await;
+            let table = arbitrary_table(&mut *repos, "schema_test_table", &namespace).await;
+            repos
+                .columns()
+                .create_or_get("schema_test_column", table.id, ColumnType::Tag)
+                .await
+                .unwrap();
+
+            let deleted = arbitrary_namespace(&mut *repos, "namespace_deleted").await;
+            repos
+                .namespaces()
+                .soft_delete("namespace_deleted")
+                .await
+                .unwrap();
+
+            (Arc::clone(&catalog), namespace.id, deleted.id)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        let schema = grpc
+            .get_schema_by_id(Request::new(GetSchemaByIdRequest {
+                namespace_id: namespace_id.get(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .schema
+            .expect("schema should be Some()");
+        assert_eq!(schema.id, namespace_id.get());
+        assert_eq!(
+            schema.tables["schema_test_table"]
+                .columns
+                .keys()
+                .collect::<Vec<&String>>(),
+            vec![&"schema_test_column".to_string()]
+        );
+
+        // Soft-deleted namespaces are not returned.
+        let err = grpc
+            .get_schema_by_id(Request::new(GetSchemaByIdRequest {
+                namespace_id: deleted_namespace_id.get(),
+            }))
+            .await
+            .expect_err("soft-deleted namespace should not be found");
+        assert_eq!(err.code(), Code::NotFound);
+
+        // An ID that was never allocated.
+        let err = grpc
+            .get_schema_by_id(Request::new(GetSchemaByIdRequest { namespace_id: 4242 }))
+            .await
+            .expect_err("unknown namespace should not be found");
+        assert_eq!(err.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_list_namespaces() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let catalog = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            // Created out of name order.
+            arbitrary_namespace(&mut *repos, "platanos").await;
+            arbitrary_namespace(&mut *repos, "bananas").await;
+            arbitrary_namespace(&mut *repos, "deleted").await;
+            repos.namespaces().soft_delete("deleted").await.unwrap();
+            Arc::clone(&catalog)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        let list = |include_deleted| {
+            let grpc = &grpc;
+            async move {
+                grpc.list_namespaces(Request::new(ListNamespacesRequest { include_deleted }))
+                    .await
+                    .expect("rpc request should succeed")
+                    .into_inner()
+                    .namespaces
+                    .into_iter()
+                    .map(|ns| (ns.name, ns.deleted))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            list(false).await,
+            [
+                ("bananas".to_string(), false),
+                ("platanos".to_string(), false)
+            ]
+        );
+        assert_eq!(
+            list(true).await,
+            [
+                ("bananas".to_string(), false),
+                ("deleted".to_string(), true),
+                ("platanos".to_string(), false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_schemas() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let catalog = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+
+            for (namespace, tables) in [
+                (
+                    "namespace_a",
+                    [
+                        ("shared", "same", ColumnType::Tag),
+                        ("shared", "conflict", ColumnType::Tag),
+                        ("shared", "only_a", ColumnType::F64),
+                        ("identical", "v", ColumnType::I64),
+                        ("table_a", "v", ColumnType::I64),
+                    ],
+                ),
+                (
+                    "namespace_b",
+                    [
+                        ("shared", "same", ColumnType::Tag),
+                        ("shared", "conflict", ColumnType::String),
+                        ("shared", "only_b", ColumnType::Bool),
+                        ("identical", "v", ColumnType::I64),
+                        ("table_b", "v", ColumnType::I64),
+                    ],
+                ),
+            ] {
+                let namespace = arbitrary_namespace(&mut *repos, namespace).await;
+                for (table_name, column_name, column_type) in tables {
+                    let table = match repos
+                        .tables()
+                        .get_by_namespace_and_name(namespace.id, table_name)
+                        .await
+                        .unwrap()
+                    {
+                        Some(t) => t,
+                        None => arbitrary_table(&mut *repos, table_name, &namespace).await,
+                    };
+                    repos
+                        .columns()
+                        .create_or_get(column_name, table.id, column_type)
+                        .await
+                        .unwrap();
+                }
+            }
+            Arc::clone(&catalog)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        let diff = grpc
+            .diff_schemas(Request::new(DiffSchemasRequest {
+                namespace_a: "namespace_a".to_string(),
+                namespace_b: "namespace_b".to_string(),
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+
+        assert_eq!(diff.tables_only_in_a, ["table_a"]);
+        assert_eq!(diff.tables_only_in_b, ["table_b"]);
+
+        // Only the "shared" table differs.
+        assert_eq!(
+            diff.table_diffs,
+            [TableDiff {
+                table_name: "shared".to_string(),
+                columns_only_in_a: vec!["only_a".to_string()],
+                columns_only_in_b: vec!["only_b".to_string()],
+                type_mismatches: vec![ColumnTypeMismatch {
+                    column_name: "conflict".to_string(),
+                    column_type_a: column_schema::ColumnType::Tag as i32,
+                    column_type_b: column_schema::ColumnType::String as i32,
+                }],
+            }]
+        );
+
+        // Diffing against an unknown namespace is an error.
+        let err = grpc
+            .diff_schemas(Request::new(DiffSchemasRequest {
+                namespace_a: "namespace_a".to_string(),
+                namespace_b: "missing".to_string(),
+            }))
+            .await
+            .expect_err("missing namespace should fail");
+        assert_eq!(err.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_ids() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let (catalog, namespace, table, columns) = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_resolve_ids").await;
+            let table = arbitrary_table(&mut *repos, "bananas", &namespace).await;
+            let mut columns = vec![];
+            for column_name in ["tag", "field"] {
+                let c = repos
+                    .columns()
+                    .create_or_get(column_name, table.id, ColumnType::Tag)
+                    .await
+                    .unwrap();
+                columns.push(c);
+            }
+            (Arc::clone(&catalog), namespace, table, columns)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        let response = grpc
+            .resolve_ids(Request::new(ResolveIdsRequest {
+                table_ids: vec![table.id.get(), 4242],
+                column_ids: vec![columns[1].id.get(), 4242, columns[0].id.get()],
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+
+        let want_table = ResolvedTable {
+            id: table.id.get(),
+            name: "bananas".to_string(),
+            namespace_id: namespace.id.get(),
+            namespace_name: "namespace_resolve_ids".to_string(),
+        };
+
+        assert_eq!(response.tables, [want_table.clone()]);
+        assert_eq!(response.unresolved_table_ids, [4242]);
+        assert_eq!(
+            response.columns,
+            [
+                ResolvedColumn {
+                    id: columns[1].id.get(),
+                    name: "field".to_string(),
+                    table: Some(want_table.clone()),
+                },
+                ResolvedColumn {
+                    id: columns[0].id.get(),
+                    name: "tag".to_string(),
+                    table: Some(want_table),
+                },
+            ]
+        );
+        assert_eq!(response.unresolved_column_ids, [4242]);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_schema() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let catalog = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            arbitrary_namespace(&mut *repos, "namespace_upsert").await;
+            Arc::clone(&catalog)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(Arc::clone(&catalog) as _);
+
+        let upsert = |columns: &[(&str, column_schema::ColumnType)]| {
+            let grpc = &grpc;
+            let request = UpsertSchemaRequest {
+                namespace: "namespace_upsert".to_string(),
+                table: "bananas".to_string(),
+                columns: columns
+                    .iter()
+                    .map(|(name, t)| (name.to_string(), *t as i32))
+                    .collect(),
+            };
+            async move { grpc.upsert_schema(Request::new(request)).await }
+        };
+
+        // A brand new table & columns.
+        let created = upsert(&[
+            ("tag", column_schema::ColumnType::Tag),
+            ("field", column_schema::ColumnType::F64),
+        ])
+        .await
+        .expect("rpc request should succeed")
+        .into_inner()
+        .table
+        .expect("table should be Some()");
+        assert_eq!(created.columns.keys().collect::<Vec<_>>(), ["field", "tag"]);
+
+        // Repeating the call is idempotent.
+        let repeated = upsert(&[
+            ("tag", column_schema::ColumnType::Tag),
+            ("field", column_schema::ColumnType::F64),
+        ])
+        .await
+        .expect("rpc request should succeed")
+        .into_inner()
+        .table
+        .expect("table should be Some()");
+        assert_eq!(created, repeated);
+
+        // A type conflict is rejected, naming the offending column, and the
+        // other columns are not created.
+        let err = upsert(&[
+            ("tag", column_schema::ColumnType::Tag),
+            ("field", column_schema::ColumnType::String),
+            ("new", column_schema::ColumnType::Bool),
+        ])
+        .await
+        .expect_err("conflicting column type should fail");
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        assert!(err.message().contains("`field`"));
+
+        let mut repos = catalog.repositories().await;
+        let columns = repos
+            .columns()
+            .list_by_table_id(TableId::new(created.id))
+            .await
+            .unwrap();
+        let mut names = columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, ["field", "tag"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_schemas() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let catalog = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            for (namespace, table_name) in [("bananas", "yellow"), ("platanos", "green")] {
+                let namespace = arbitrary_namespace(&mut *repos, namespace).await;
+                arbitrary_table(&mut *repos, table_name, &namespace).await;
+            }
+            Arc::clone(&catalog)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+
+        let response = grpc
+            .get_schemas(Request::new(GetSchemasRequest {
+                namespaces: vec![
+                    "bananas".to_string(),
+                    "missing".to_string(),
+                    "platanos".to_string(),
+                ],
+            }))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner();
+
+        assert_eq!(
+            response.schemas.keys().collect::<Vec<_>>(),
+            ["bananas", "platanos"]
+        );
+        assert_eq!(
+            response.schemas["bananas"]
+                .tables
+                .keys()
+                .collect::<Vec<_>>(),
+            ["yellow"]
+        );
+        assert_eq!(
+            response.schemas["platanos"]
+                .tables
+                .keys()
+                .collect::<Vec<_>>(),
+            ["green"]
+        );
+        assert_eq!(response.not_found, ["missing"]);
+        assert!(response.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_schemas_partial_failure() {
+        let metrics = Arc::new(metric::Registry::default());
+        let catalog = Arc::new(MemCatalog::new(metrics));
+        let mut repos = catalog.repositories().await;
+        let namespace = arbitrary_namespace(&mut *repos, "bananas").await;
+        arbitrary_table(&mut *repos, "yellow", &namespace).await;
+        let schema = get_schema_by_name("bananas", &mut *repos, SoftDeletedRows::ExcludeDeleted)
+            .await
+            .unwrap();
+
+        // one namespace of the batch fails, the others are still served
+        let response = get_schemas_response([
+            (
+                "broken".to_string(),
+                Err(iox_catalog::interface::Error::UnknownColumnType {
+                    data_type: 42,
+                    name: "col".to_string(),
+                }),
+            ),
+            ("bananas".to_string(), Ok(schema)),
+            (
+                "missing".to_string(),
+                Err(iox_catalog::interface::Error::NamespaceNotFoundByName {
+                    name: "missing".to_string(),
+                }),
+            ),
+        ]);
+
+        assert_eq!(response.schemas.keys().collect::<Vec<_>>(), ["bananas"]);
+        assert_eq!(response.not_found, ["missing"]);
+        assert_eq!(
+            response.errors,
+            BTreeMap::from([(
+                "broken".to_string(),
+                "column type 42 is in the db for column col, which is unknown".to_string()
+            )])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_stream() {
+        // create a catalog and populate it with some test data, then drop the write lock
+        let (catalog, namespace_id) = {
+            let metrics = Arc::new(metric::Registry::default());
+            let catalog = Arc::new(MemCatalog::new(metrics));
+            let mut repos = catalog.repositories().await;
+            let namespace = arbitrary_namespace(&mut *repos, "namespace_schema_stream").await;
+            for (table_name, column_name) in
+                [("bananas", "a"), ("platanos", "b"), ("cavendish", "c")]
+            {
+                let table = arbitrary_table(&mut *repos, table_name, &namespace).await;
+                repos
+                    .columns()
+                    .create_or_get(column_name, table.id, ColumnType::Tag)
+                    .await
+                    .unwrap();
+            }
+            (Arc::clone(&catalog), namespace.id)
+        };
+
+        // create grpc schema service
+        let grpc = super::SchemaService::new(catalog);
+        let request = GetSchemaStreamRequest {
+            namespace: "namespace_schema_stream".to_string(),
+        };
+
+        let items = grpc
+            .get_schema_stream(Request::new(request))
+            .await
+            .expect("rpc request should succeed")
+            .into_inner()
+            .map(|item| item.expect("stream item should be Ok").item.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        // One header, then one item per table.
+        assert_eq!(items.len(), 4);
+
+        let mut items = items.into_iter();
+        match items.next().unwrap() {
+            get_schema_stream_response::Item::Header(h) => {
+                assert_eq!(h.namespace_id, namespace_id.get())
+            }
+            v => panic!("expected header, got {v:?}"),
+        }
+
+        let tables = items
+            .map(|item| match item {
+                get_schema_stream_response::Item::Table(t) => {
+                    let columns = t
+                        .schema
+                        .expect("table schema should be Some()")
+                        .columns
+                        .into_keys()
+                        .collect::<Vec<_>>();
+                    (t.name, columns)
+                }
+                v => panic!("expected table, got {v:?}"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            tables,
+            [
+                ("bananas".to_string(), vec!["a".to_string()]),
+                ("cavendish".to_string(), vec!["c".to_string()]),
+                ("platanos".to_string(), vec!["b".to_string()]),
+            ]
+        );
+    }
 }
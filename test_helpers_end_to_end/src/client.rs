@@ -118,8 +118,12 @@ pub async fn try_run_influxql(
     namespace: impl Into<String>,
     querier_connection: Connection,
     authorization: Option<&str>,
+    with_debug: bool,
 ) -> Result<(Vec<RecordBatch>, SchemaRef), influxdb_iox_client::flight::Error> {
     let mut client = influxdb_iox_client::flight::Client::new(querier_connection);
+    if with_debug {
+        client.add_header("iox-debug", "true").unwrap();
+    }
     if let Some(authorization) = authorization {
         client.add_header("authorization", authorization).unwrap();
     }
@@ -173,12 +177,14 @@ pub async fn run_influxql(
     namespace: impl Into<String>,
     querier_connection: Connection,
     authorization: Option<&str>,
+    with_debug: bool,
 ) -> (Vec<RecordBatch>, SchemaRef) {
     try_run_influxql(
         influxql.clone(),
         namespace,
         querier_connection,
         authorization,
+        with_debug,
     )
     .await
     .unwrap_or_else(|_| panic!("Error executing InfluxQL query: {influxql}"))
@@ -399,6 +399,9 @@ impl MiniCluster {
                 let id = SchemaServiceClient::new(c)
                     .get_schema(GetSchemaRequest {
                         namespace: self.namespace().to_string(),
+                        page_size: None,
+                        page_token: None,
+                        if_not_fingerprint: None,
                     })
                     .await
                     .expect("failed to query for namespace ID")
@@ -424,6 +427,9 @@ impl MiniCluster {
         let id = SchemaServiceClient::new(c)
             .get_schema(GetSchemaRequest {
                 namespace: self.namespace().to_string(),
+                page_size: None,
+                page_token: None,
+                if_not_fingerprint: None,
             })
             .await
             .expect("failed to query for namespace ID")
@@ -456,6 +462,9 @@ impl MiniCluster {
         let table_id = SchemaServiceClient::new(c.clone())
             .get_schema(GetSchemaRequest {
                 namespace: namespace_name.clone(),
+                page_size: None,
+                page_token: None,
+                if_not_fingerprint: None,
             })
             .await
             .expect("failed to query for namespace ID")
@@ -563,6 +563,7 @@ where
                         state.cluster.namespace(),
                         state.cluster.querier().querier_grpc_connection(),
                         None,
+                        false,
                     )
                     .await;
                     batches.push(RecordBatch::new_empty(schema));
@@ -604,6 +605,7 @@ where
                         state.cluster().namespace(),
                         state.cluster().querier().querier_grpc_connection(),
                         None,
+                        false,
                     )
                     .await
                     .unwrap_err();
@@ -624,6 +626,7 @@ where
                         state.cluster.namespace(),
                         state.cluster.querier().querier_grpc_connection(),
                         Some(authorization),
+                        false,
                     )
                     .await;
                     batches.push(RecordBatch::new_empty(schema));